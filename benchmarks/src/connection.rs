@@ -0,0 +1,259 @@
+//! Node connection with reconnection and failover
+//!
+//! Long runs outlive WebSocket connections. Every submission goes through [`Connection`],
+//! which on a transport error reconnects with exponential backoff, moving round-robin through
+//! the `--endpoint`s given, and retries the submission on the new connection. Tasks hitting
+//! the same outage share one reconnection.
+//!
+//! Nonces are tracked per account here instead of being read from the node for every
+//! transaction, so concurrent submissions of one account never reuse a nonce. After a
+//! reconnection, each account's next nonce is read once from the new node, which has seen
+//! the transactions that reached the network, so those lost with the old connection leave no
+//! gap; from there tracking continues locally.
+//!
+//! Every outage is recorded as a [`Disruption`] and listed with the run's results.
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sp_core::sr25519;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use subxt::{
+    backend::rpc::RpcClient,
+    config::polkadot::PolkadotExtrinsicParamsBuilder,
+    error::RpcError,
+    tx::{PairSigner, Payload, Signer, TxProgress},
+    utils::AccountId32,
+    OnlineClient, PolkadotConfig,
+};
+
+/// Wait before the second connection attempt of a reconnection, doubled after every failure
+pub const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Longest wait between connection attempts
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Connection attempts after which a reconnection gives up
+pub const MAX_RECONNECT_ATTEMPTS: u32 = 20;
+
+/// Signer of benchmark transactions
+pub type BenchSigner = PairSigner<PolkadotConfig, sr25519::Pair>;
+
+/// Outage of the node connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Disruption {
+    /// Milliseconds from the start of the run until the connection was found lost
+    pub started_ms: u64,
+    /// Milliseconds from the start of the run until it was restored
+    pub ended_ms: u64,
+    /// Endpoint the connection was lost to
+    pub lost: String,
+    /// Endpoint connected to instead
+    pub restored: String,
+    /// Connection attempts it took
+    pub attempts: u32,
+}
+
+impl Disruption {
+    /// Length of the outage
+    pub fn duration_ms(&self) -> u64 {
+        self.ended_ms - self.started_ms
+    }
+}
+
+/// Connection in use, and how many reconnections preceded it
+struct Current {
+    client: OnlineClient<PolkadotConfig>,
+    rpc: RpcClient,
+    endpoint: usize,
+    generation: u64,
+}
+
+/// Connection to one of several endpoints of the benchmarked network
+pub struct Connection {
+    endpoints: Vec<String>,
+    current: RwLock<Current>,
+    reconnecting: tokio::sync::Mutex<()>,
+    /// Next nonce of each account, and the connection generation it was read in
+    nonces: Mutex<HashMap<AccountId32, (u64, u64)>>,
+    disruptions: Mutex<Vec<Disruption>>,
+    start: Instant,
+}
+
+/// Whether `error` means the connection was lost, rather than the node refusing a request
+pub fn is_disconnect(error: &subxt::Error) -> bool {
+    matches!(error, subxt::Error::Rpc(RpcError::ClientError(_) | RpcError::SubscriptionDropped))
+}
+
+async fn open(endpoint: &str) -> Result<(OnlineClient<PolkadotConfig>, RpcClient), subxt::Error> {
+    let rpc = RpcClient::from_url(endpoint).await?;
+    let client = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc.clone()).await?;
+    Ok((client, rpc))
+}
+
+impl Connection {
+    /// Connect to the first of `endpoints` that accepts the connection
+    pub async fn connect(endpoints: Vec<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut last_error = None;
+        for (index, endpoint) in endpoints.iter().enumerate() {
+            info!("Connecting to Netchain node at {}", endpoint);
+            match open(endpoint).await {
+                Ok((client, rpc)) => {
+                    return Ok(Self {
+                        current: RwLock::new(Current { client, rpc, endpoint: index, generation: 0 }),
+                        endpoints,
+                        reconnecting: tokio::sync::Mutex::new(()),
+                        nonces: Mutex::new(HashMap::new()),
+                        disruptions: Mutex::new(Vec::new()),
+                        start: Instant::now(),
+                    })
+                },
+                Err(e) => {
+                    warn!("Failed to connect to {}: {}", endpoint, e);
+                    last_error = Some(e);
+                },
+            }
+        }
+        Err(last_error.map_or_else(|| "no endpoint given".into(), Into::into))
+    }
+
+    /// Client of the current connection
+    pub fn client(&self) -> OnlineClient<PolkadotConfig> {
+        self.current.read().unwrap().client.clone()
+    }
+
+    /// RPC client of the current connection, and its generation for [`Self::recover`]
+    pub fn rpc(&self) -> (RpcClient, u64) {
+        let current = self.current.read().unwrap();
+        (current.rpc.clone(), current.generation)
+    }
+
+    /// Outages so far
+    pub fn disruptions(&self) -> Vec<Disruption> {
+        self.disruptions.lock().unwrap().clone()
+    }
+
+    /// Sign `call` with the next nonce of `signer` and submit it, reconnecting and retrying
+    /// on a lost connection
+    ///
+    /// Returns the progress of the transaction and the generation of the connection that
+    /// watches it, for [`Self::recover`].
+    pub async fn submit<Call: Payload>(
+        &self,
+        call: &Call,
+        signer: &BenchSigner,
+    ) -> Result<(TxProgress<PolkadotConfig, OnlineClient<PolkadotConfig>>, u64), subxt::Error> {
+        loop {
+            let (client, generation) = {
+                let current = self.current.read().unwrap();
+                (current.client.clone(), current.generation)
+            };
+            let account = signer.account_id();
+            let submitted = async {
+                let nonce = self.next_nonce(&client, generation, &account).await?;
+                let params = PolkadotExtrinsicParamsBuilder::new().nonce(nonce).build();
+                client.tx().sign_and_submit_then_watch(call, signer, params).await
+            }
+            .await;
+
+            match submitted {
+                Ok(progress) => return Ok((progress, generation)),
+                Err(e) if is_disconnect(&e) => self.reconnect(generation).await?,
+                Err(e) => {
+                    // The nonce may not have been used; read it again
+                    self.nonces.lock().unwrap().remove(&account);
+                    return Err(e);
+                },
+            }
+        }
+    }
+
+    /// Reconnect if `error`, met on the connection of `generation`, means it was lost
+    pub async fn recover(&self, generation: u64, error: &subxt::Error) -> Result<(), subxt::Error> {
+        if is_disconnect(error) {
+            self.reconnect(generation).await?;
+        }
+        Ok(())
+    }
+
+    /// Next nonce of `account`, read from the node the first time on every connection
+    async fn next_nonce(
+        &self,
+        client: &OnlineClient<PolkadotConfig>,
+        generation: u64,
+        account: &AccountId32,
+    ) -> Result<u64, subxt::Error> {
+        if let Some(nonce) = self.take_nonce(generation, account) {
+            return Ok(nonce);
+        }
+        let read = client.tx().account_nonce(account).await?;
+
+        // Another submission of the account may have read it meanwhile
+        let mut nonces = self.nonces.lock().unwrap();
+        let entry = nonces.entry(account.clone()).or_insert((generation, read));
+        if entry.0 != generation {
+            *entry = (generation, read);
+        }
+        let nonce = entry.1;
+        entry.1 += 1;
+        Ok(nonce)
+    }
+
+    /// Next tracked nonce of `account`, if read on the connection of `generation`
+    fn take_nonce(&self, generation: u64, account: &AccountId32) -> Option<u64> {
+        let mut nonces = self.nonces.lock().unwrap();
+        let (read_in, next) = nonces.get_mut(account)?;
+        (*read_in == generation).then(|| {
+            *next += 1;
+            *next - 1
+        })
+    }
+
+    /// Replace the connection of `generation`, unless another task already did
+    async fn reconnect(&self, generation: u64) -> Result<(), subxt::Error> {
+        let _reconnecting = self.reconnecting.lock().await;
+        let lost = {
+            let current = self.current.read().unwrap();
+            if current.generation != generation {
+                return Ok(());
+            }
+            current.endpoint
+        };
+        let started = Instant::now();
+        warn!("Lost connection to {}, reconnecting", self.endpoints[lost]);
+
+        let mut backoff = INITIAL_BACKOFF;
+        let mut last_error = None;
+        for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+            let endpoint = (lost + attempt as usize) % self.endpoints.len();
+            match open(&self.endpoints[endpoint]).await {
+                Ok((client, rpc)) => {
+                    *self.current.write().unwrap() = Current { client, rpc, endpoint, generation: generation + 1 };
+                    let disruption = Disruption {
+                        started_ms: started.duration_since(self.start).as_millis() as u64,
+                        ended_ms: self.start.elapsed().as_millis() as u64,
+                        lost: self.endpoints[lost].clone(),
+                        restored: self.endpoints[endpoint].clone(),
+                        attempts: attempt,
+                    };
+                    info!(
+                        "Reconnected to {} after {} ms and {} attempts",
+                        disruption.restored,
+                        disruption.duration_ms(),
+                        attempt
+                    );
+                    self.disruptions.lock().unwrap().push(disruption);
+                    return Ok(());
+                },
+                Err(e) => {
+                    warn!("Reconnection attempt {} to {} failed: {}", attempt, self.endpoints[endpoint], e);
+                    last_error = Some(e);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                },
+            }
+        }
+        Err(last_error.unwrap_or_else(|| subxt::Error::Other("no endpoint to reconnect to".into())))
+    }
+}
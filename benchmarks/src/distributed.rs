@@ -0,0 +1,197 @@
+//! Distributed benchmark mode
+//!
+//! One host saturates its own CPU long before the node does, so a run can be split across
+//! agents on several machines. The coordinator listens for WebSocket connections and, once
+//! the expected number of agents has joined, sends each one its share of the run at the
+//! same time:
+//! - transactions and workers are split evenly, the remainder going to the first agents
+//! - every agent gets its own range of benchmark accounts, one per worker, so agents
+//!   never submit with the same nonce
+//!
+//! Agents run their share against their own `--endpoint` and send back their raw samples,
+//! which the coordinator merges into a single set of metrics, CSV export and report.
+//! Sample timestamps are relative to each agent's start, which the simultaneous
+//! assignment keeps within network latency of each other. Agents also report the outages of
+//! their node connections, listed together with the merged results.
+
+use crate::{connection::Disruption, BenchmarkMetrics, BenchmarkRunner, TxResult};
+use futures::{SinkExt, StreamExt};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{accept_async, connect_async, tungstenite::Message, WebSocketStream};
+
+/// Parameters of a whole run, before it is split across agents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunParams {
+    pub transactions: u64,
+    pub workers: u32,
+    pub duration: u64,
+    pub batch_size: u32,
+    pub sharding: bool,
+}
+
+/// Share of a run assigned to one agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    pub agent: u32,
+    /// First benchmark account of the agent; its workers sign with the following ones
+    pub first_account: u32,
+    pub run: RunParams,
+}
+
+/// Messages exchanged between coordinator and agents, JSON-encoded in text frames
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Envelope {
+    /// Agent is ready to run
+    Join,
+    /// Coordinator assigns the agent its share
+    Assign(Assignment),
+    /// Agent finished its share
+    Results {
+        agent: u32,
+        duration_ms: u64,
+        results: Vec<TxResult>,
+        #[serde(default)]
+        disruptions: Vec<Disruption>,
+    },
+}
+
+/// Split `run` into one assignment per agent
+pub fn assignments(run: &RunParams, agents: u32) -> Vec<Assignment> {
+    let agents = agents.max(1);
+    let mut first_account = 0;
+    (0..agents)
+        .map(|agent| {
+            let workers = run.workers / agents + u32::from(agent < run.workers % agents);
+            let transactions = run.transactions / agents as u64 + u64::from((agent as u64) < run.transactions % agents as u64);
+            let assignment = Assignment {
+                agent,
+                first_account,
+                run: RunParams { transactions, workers: workers.max(1), ..run.clone() },
+            };
+            first_account += workers.max(1);
+            assignment
+        })
+        .collect()
+}
+
+/// Wait for `agents` agents on `address`, run `run` across them and merge their samples
+pub async fn run_coordinator(
+    runner: &BenchmarkRunner,
+    address: &str,
+    agents: u32,
+    run: RunParams,
+) -> Result<BenchmarkMetrics, Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(address).await?;
+    info!("Coordinator listening on {}, waiting for {} agents", address, agents);
+
+    let mut sockets = Vec::new();
+    while sockets.len() < agents as usize {
+        let (stream, peer) = listener.accept().await?;
+        let mut socket = accept_async(stream).await?;
+        match receive(&mut socket).await? {
+            Envelope::Join => {
+                info!("Agent {} joined from {}", sockets.len(), peer);
+                sockets.push(socket);
+            },
+            other => return Err(format!("expected Join from {}, got {:?}", peer, other).into()),
+        }
+    }
+
+    for (socket, assignment) in sockets.iter_mut().zip(assignments(&run, agents)) {
+        send(socket, &Envelope::Assign(assignment)).await?;
+    }
+    info!("Run assigned; collecting results");
+
+    let mut merged = Vec::new();
+    let mut disruptions = Vec::new();
+    let mut duration_ms = 0;
+    for socket in sockets.iter_mut() {
+        match receive(socket).await? {
+            Envelope::Results { agent, duration_ms: agent_ms, results, disruptions: agent_disruptions } => {
+                info!("Agent {} reported {} samples in {} ms", agent, results.len(), agent_ms);
+                duration_ms = duration_ms.max(agent_ms);
+                merged.extend(results);
+                disruptions.extend(agent_disruptions);
+            },
+            other => return Err(format!("expected Results, got {:?}", other).into()),
+        }
+    }
+
+    let total_sent = merged.len() as u64;
+    let mut metrics = runner
+        .calculate_metrics(&merged, Duration::from_millis(duration_ms), total_sent, run.sharding)
+        .await?;
+    metrics.disruptions.extend(disruptions);
+    runner.set_results(merged);
+
+    Ok(metrics)
+}
+
+/// Join the coordinator at `url`, run the assigned share and report its samples
+pub async fn run_agent(runner: &BenchmarkRunner, url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut socket, _) = connect_async(url).await?;
+    send(&mut socket, &Envelope::Join).await?;
+    info!("Joined coordinator at {}, waiting for assignment", url);
+
+    let assignment = match receive(&mut socket).await? {
+        Envelope::Assign(assignment) => assignment,
+        other => return Err(format!("expected Assign, got {:?}", other).into()),
+    };
+    info!(
+        "Agent {}: {} transactions with accounts {}..{}",
+        assignment.agent,
+        assignment.run.transactions,
+        assignment.first_account,
+        assignment.first_account + assignment.run.workers,
+    );
+
+    let run = &assignment.run;
+    let metrics = runner
+        .run_tps_benchmark(
+            run.transactions,
+            run.workers,
+            run.duration,
+            run.batch_size,
+            run.sharding,
+            Some(assignment.first_account),
+        )
+        .await?;
+    runner.print_results(&metrics);
+
+    send(&mut socket, &Envelope::Results {
+        agent: assignment.agent,
+        duration_ms: metrics.total_duration_ms,
+        results: runner.results(),
+        disruptions: metrics.disruptions.clone(),
+    })
+    .await?;
+    socket.close(None).await?;
+
+    Ok(())
+}
+
+async fn send<S>(socket: &mut WebSocketStream<S>, envelope: &Envelope) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    socket.send(Message::Text(serde_json::to_string(envelope)?)).await?;
+    Ok(())
+}
+
+async fn receive<S>(socket: &mut WebSocketStream<S>) -> Result<Envelope, Box<dyn std::error::Error>>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    loop {
+        match socket.next().await {
+            Some(Ok(Message::Text(text))) => return Ok(serde_json::from_str(&text)?),
+            Some(Ok(Message::Close(_))) | None => return Err("connection closed".into()),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(e.into()),
+        }
+    }
+}
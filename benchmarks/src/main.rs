@@ -10,6 +10,13 @@
 //! - Comprehensive performance metrics
 //! - Hardware utilization monitoring
 //! - Export results to CSV for analysis
+//! - HTML report with TPS-over-time and latency histogram charts
+//! - Distributed mode: a coordinator splits a run across agents on several machines and
+//!   merges their samples into one report
+//! - Soak mode: hours of constant load, sampling the node's memory, database size and
+//!   transaction pool to catch leaks and throughput degradation with a pass/fail verdict
+//! - Reconnection with exponential backoff and round-robin failover across `--endpoint`s,
+//!   keeping nonces continuous and listing the disruptions with the results
 
 use clap::{Parser, Subcommand};
 use futures::{stream, StreamExt};
@@ -19,15 +26,24 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
+    path::PathBuf,
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc,
     },
     time::{Duration, Instant},
 };
-use subxt::{OnlineClient, PolkadotConfig};
+use sp_core::{sr25519, Pair};
+use subxt::{tx::PairSigner, PolkadotConfig};
 use tokio::time::sleep;
 
+mod connection;
+mod distributed;
+mod report;
+mod soak;
+
+use connection::{Connection, Disruption};
+
 // Generate the API from metadata
 #[subxt::subxt(runtime_metadata_path = "../target/release/wbuild/netchain-runtime/netchain_runtime.compact.scale")]
 pub mod netchain {}
@@ -37,9 +53,10 @@ pub mod netchain {}
 #[command(name = "netchain-benchmark")]
 #[command(about = "High-performance TPS benchmarking for Netchain")]
 pub struct Args {
-    /// Substrate node WebSocket endpoint
+    /// Substrate node WebSocket endpoint; repeat it for the others to fail over to,
+    /// round-robin, when the connection is lost
     #[arg(short, long, default_value = "ws://127.0.0.1:9944")]
-    pub endpoint: String,
+    pub endpoint: Vec<String>,
 
     /// Benchmark command to run
     #[command(subcommand)]
@@ -73,6 +90,23 @@ pub enum Commands {
         /// Export results to CSV file
         #[arg(short, long)]
         export: Option<String>,
+
+        /// Write an HTML report with TPS and latency charts
+        #[arg(long)]
+        report: Option<String>,
+
+        /// Coordinate a distributed run: listen on this address and split the run across agents
+        #[arg(long, conflicts_with = "agent")]
+        coordinator: Option<String>,
+
+        /// Agents the coordinator waits for before starting
+        #[arg(long, default_value = "2", requires = "coordinator")]
+        agents: u32,
+
+        /// Run as an agent of the coordinator at this WebSocket URL (e.g. ws://10.0.0.1:9950);
+        /// the run parameters come from the coordinator
+        #[arg(long)]
+        agent: Option<String>,
     },
     /// Test cross-shard transactions
     CrossShard {
@@ -94,6 +128,52 @@ pub enum Commands {
         #[arg(short, long, default_value = "100000")]
         max_tps: u32,
     },
+    /// Keep a constant load on the node for hours and check it for leaks and degradation
+    Soak {
+        /// Duration in seconds
+        #[arg(short, long, default_value = "14400")]
+        duration: u64,
+
+        /// Transfers per second to submit
+        #[arg(short, long, default_value = "200")]
+        tps: u32,
+
+        /// Number of concurrent workers, each signing with its own benchmark account
+        #[arg(short, long, default_value = "20")]
+        workers: u32,
+
+        /// Seconds between samples
+        #[arg(long, default_value = "60")]
+        interval: u64,
+
+        /// Seconds at the start whose samples are not checked, while caches fill up
+        #[arg(long, default_value = "600")]
+        warmup: u64,
+
+        /// Process id of the node, to sample its memory
+        #[arg(long)]
+        node_pid: Option<u32>,
+
+        /// Database directory of the node, to sample its size
+        #[arg(long)]
+        db_path: Option<PathBuf>,
+
+        /// Percent the node's memory may grow by over the run
+        #[arg(long, default_value = "20")]
+        max_memory_growth: f64,
+
+        /// Percent the database may grow by over the run
+        #[arg(long, default_value = "100")]
+        max_db_growth: f64,
+
+        /// Percent the last quarter's TPS may fall below the first quarter's
+        #[arg(long, default_value = "10")]
+        max_tps_drop: f64,
+
+        /// Export the samples to CSV file
+        #[arg(short, long)]
+        export: Option<String>,
+    },
     /// Benchmark smart contracts
     Contracts {
         /// Number of contract calls
@@ -110,6 +190,7 @@ pub enum Commands {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TxResult {
     pub tx_hash: String,
+    /// Milliseconds from the start of the run until the transaction finalized or failed
     pub timestamp: u64,
     pub success: bool,
     pub block_number: u64,
@@ -134,6 +215,8 @@ pub struct BenchmarkMetrics {
     pub blocks_processed: u64,
     pub shards_used: Vec<u8>,
     pub hardware_stats: HardwareStats,
+    /// Outages of the node connection during the run
+    pub disruptions: Vec<Disruption>,
 }
 
 /// Hardware utilization statistics
@@ -147,7 +230,7 @@ pub struct HardwareStats {
 
 /// TPS Benchmark runner
 pub struct BenchmarkRunner {
-    client: OnlineClient<PolkadotConfig>,
+    connection: Arc<Connection>,
     metrics: Arc<AtomicU64>,
     start_time: Instant,
     results: Arc<std::sync::Mutex<Vec<TxResult>>>,
@@ -155,17 +238,16 @@ pub struct BenchmarkRunner {
 
 impl BenchmarkRunner {
     /// Create new benchmark runner
-    pub async fn new(endpoint: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        info!("Connecting to Netchain node at {}", endpoint);
-        
-        let client = OnlineClient::<PolkadotConfig>::from_url(endpoint).await?;
-        
+    pub async fn new(endpoints: &[String]) -> Result<Self, Box<dyn std::error::Error>> {
+        let connection = Connection::connect(endpoints.to_vec()).await?;
+        let client = connection.client();
+
         info!("Connected successfully!");
         info!("Node: {}", client.runtime_version().spec_name);
         info!("Version: {}", client.runtime_version().spec_version);
 
         Ok(Self {
-            client,
+            connection: Arc::new(connection),
             metrics: Arc::new(AtomicU64::new(0)),
             start_time: Instant::now(),
             results: Arc::new(std::sync::Mutex::new(Vec::new())),
@@ -180,6 +262,7 @@ impl BenchmarkRunner {
         duration: u64,
         batch_size: u32,
         sharding: bool,
+        first_account: Option<u32>,
     ) -> Result<BenchmarkMetrics, Box<dyn std::error::Error>> {
         info!("Starting TPS benchmark:");
         info!("  Transactions: {}", transactions);
@@ -201,15 +284,21 @@ impl BenchmarkRunner {
 
         // Create worker tasks
         for worker_id in 0..workers {
-            let client = self.client.clone();
+            let connection = Arc::clone(&self.connection);
             let metrics = Arc::clone(&self.metrics);
             let results = Arc::clone(&self.results);
             let progress = progress.clone();
 
             let handle = tokio::spawn(async move {
+                let signer = match first_account {
+                    Some(first) => bench_account(first + worker_id),
+                    None => sp_keyring::sr25519::sr25519::Keyring::Alice.pair(), // Use Alice for testing
+                };
                 Self::worker_task(
                     worker_id,
-                    client,
+                    signer,
+                    start_time,
+                    connection,
                     transactions / workers as u64,
                     batch_size,
                     sharding,
@@ -251,7 +340,9 @@ impl BenchmarkRunner {
     /// Worker task for sending transactions
     async fn worker_task(
         worker_id: u32,
-        client: OnlineClient<PolkadotConfig>,
+        signer: sr25519::Pair,
+        run_start: Instant,
+        connection: Arc<Connection>,
         transactions_per_worker: u64,
         batch_size: u32,
         sharding: bool,
@@ -259,15 +350,13 @@ impl BenchmarkRunner {
         results: Arc<std::sync::Mutex<Vec<TxResult>>>,
         progress: ProgressBar,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let signer = sp_keyring::sr25519::sr25519::Keyring::Alice; // Use Alice for testing
-        
         for batch_start in (0..transactions_per_worker).step_by(batch_size as usize) {
             let batch_end = (batch_start + batch_size as u64).min(transactions_per_worker);
             let mut batch_handles = Vec::new();
 
             // Create batch of transactions
             for tx_index in batch_start..batch_end {
-                let client = client.clone();
+                let connection = Arc::clone(&connection);
                 let signer = signer.clone();
                 let metrics = Arc::clone(&metrics);
                 let results = Arc::clone(&results);
@@ -275,9 +364,10 @@ impl BenchmarkRunner {
 
                 let handle = tokio::spawn(async move {
                     Self::send_transaction(
-                        client,
+                        connection,
                         signer,
                         worker_id,
+                        run_start,
                         tx_index,
                         sharding,
                         metrics,
@@ -301,9 +391,10 @@ impl BenchmarkRunner {
 
     /// Send individual transaction
     async fn send_transaction(
-        client: OnlineClient<PolkadotConfig>,
-        signer: sp_keyring::sr25519::sr25519::Keyring,
+        connection: Arc<Connection>,
+        signer: sr25519::Pair,
         worker_id: u32,
+        run_start: Instant,
         tx_index: u64,
         sharding: bool,
         metrics: Arc<AtomicU64>,
@@ -337,9 +428,10 @@ impl BenchmarkRunner {
         let mut tx_hash = String::new();
         let mut shard_id = None;
 
-        // Submit transaction
-        match client.tx().sign_and_submit_then_watch_default(&tx, &signer).await {
-            Ok(mut progress) => {
+        // Submit transaction, through any reconnection
+        let tx_signer = PairSigner::<PolkadotConfig, _>::new(signer.clone());
+        match connection.submit(&tx, &tx_signer).await {
+            Ok((mut progress, generation)) => {
                 match progress.wait_for_finalized().await {
                     Ok(tx_events) => {
                         success = true;
@@ -348,11 +440,14 @@ impl BenchmarkRunner {
                         
                         // If sharding is enabled, determine shard ID
                         if sharding {
-                            shard_id = Some(Self::calculate_shard_id(&signer.to_account_id()));
+                            shard_id = Some(Self::calculate_shard_id(&signer.public()));
                         }
                     }
                     Err(e) => {
                         warn!("Transaction failed to finalize: {:?}", e);
+                        if let Err(e) = connection.recover(generation, &e).await {
+                            error!("Failed to reconnect: {:?}", e);
+                        }
                     }
                 }
             }
@@ -368,7 +463,7 @@ impl BenchmarkRunner {
         // Record result
         let result = TxResult {
             tx_hash,
-            timestamp: start_time.elapsed().as_millis() as u64,
+            timestamp: run_start.elapsed().as_millis() as u64,
             success,
             block_number,
             execution_time_ms: execution_time.as_millis() as u64,
@@ -389,9 +484,7 @@ impl BenchmarkRunner {
 
     /// Calculate shard ID for account (matches sharding pallet logic)
     fn calculate_shard_id(account: &sp_core::sr25519::Public) -> u8 {
-        use sp_core::Hasher;
-        let hash = sp_core::blake2_256(account);
-        hash[0] % 4 // 4 shards
+        netchain_primitives::account_shard(account.as_ref())
     }
 
     /// Monitor performance during benchmark
@@ -421,7 +514,7 @@ impl BenchmarkRunner {
     }
 
     /// Calculate comprehensive benchmark metrics
-    async fn calculate_metrics(
+    pub(crate) async fn calculate_metrics(
         &self,
         results: &[TxResult],
         total_duration: Duration,
@@ -516,6 +609,7 @@ impl BenchmarkRunner {
             blocks_processed,
             shards_used,
             hardware_stats,
+            disruptions: self.connection.disruptions(),
         })
     }
 
@@ -559,6 +653,12 @@ impl BenchmarkRunner {
         writer.write_record(&["p95_latency", &format!("{:.2}", metrics.p95_latency_ms), "ms"])?;
         writer.write_record(&["p99_latency", &format!("{:.2}", metrics.p99_latency_ms), "ms"])?;
         writer.write_record(&["blocks_processed", &metrics.blocks_processed.to_string(), "count"])?;
+        writer.write_record(&["disruptions", &metrics.disruptions.len().to_string(), "count"])?;
+        writer.write_record(&[
+            "disrupted_time",
+            &metrics.disruptions.iter().map(Disruption::duration_ms).sum::<u64>().to_string(),
+            "ms",
+        ])?;
 
         writer.flush()?;
         info!("Results exported to {}", filename);
@@ -566,6 +666,29 @@ impl BenchmarkRunner {
         Ok(())
     }
 
+    /// Replace the run's samples, e.g. with those merged from agents
+    pub(crate) fn set_results(&self, results: Vec<TxResult>) {
+        *self.results.lock().unwrap() = results;
+    }
+
+    /// Samples recorded so far
+    pub(crate) fn results(&self) -> Vec<TxResult> {
+        self.results.lock().unwrap().clone()
+    }
+
+    /// Write the HTML report of the run's samples
+    pub fn write_report(
+        &self,
+        metrics: &BenchmarkMetrics,
+        filename: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let results = self.results.lock().unwrap();
+        report::write_html_report(filename, metrics, &results)?;
+        info!("Report written to {}", filename);
+
+        Ok(())
+    }
+
     /// Run cross-shard transaction benchmark
     pub async fn run_cross_shard_benchmark(
         &self,
@@ -624,6 +747,20 @@ impl BenchmarkRunner {
             println!("  Shard Count:     {}", metrics.shards_used.len());
         }
 
+        if !metrics.disruptions.is_empty() {
+            println!("\n🔌 Connection Disruptions:");
+            for disruption in &metrics.disruptions {
+                println!(
+                    "  {:>8.2}s - {:>8.2}s  {} → {} ({} attempts)",
+                    disruption.started_ms as f64 / 1000.0,
+                    disruption.ended_ms as f64 / 1000.0,
+                    disruption.lost,
+                    disruption.restored,
+                    disruption.attempts,
+                );
+            }
+        }
+
         println!("\n💻 Hardware Utilization:");
         println!("  CPU Usage:       {:>8.1}%", metrics.hardware_stats.cpu_usage_percent);
         println!("  Memory Usage:    {:>8} MB", metrics.hardware_stats.memory_usage_mb);
@@ -656,6 +793,14 @@ impl BenchmarkRunner {
     }
 }
 
+/// Benchmark account `index`, derived from the dev seed as `//Bench//<index>`
+///
+/// Distributed runs give every worker its own account so agents never race on nonces;
+/// fund the accounts before the run.
+pub fn bench_account(index: u32) -> sr25519::Pair {
+    sr25519::Pair::from_string(&format!("//Bench//{}", index), None).expect("static derivation path is valid; qed")
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
@@ -671,21 +816,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             duration, 
             batch_size, 
             sharding, 
-            export 
+            export,
+            report,
+            coordinator,
+            agents,
+            agent,
         } => {
-            let metrics = runner.run_tps_benchmark(
-                transactions, 
-                workers, 
-                duration, 
-                batch_size, 
-                sharding
-            ).await?;
+            if let Some(url) = agent {
+                return distributed::run_agent(&runner, &url).await;
+            }
+
+            let metrics = match coordinator {
+                Some(address) => {
+                    let run = distributed::RunParams { transactions, workers, duration, batch_size, sharding };
+                    distributed::run_coordinator(&runner, &address, agents, run).await?
+                },
+                None => runner.run_tps_benchmark(
+                    transactions, 
+                    workers, 
+                    duration, 
+                    batch_size, 
+                    sharding,
+                    None,
+                ).await?,
+            };
             
             runner.print_results(&metrics);
             
             if let Some(filename) = export {
                 runner.export_to_csv(&metrics, &filename)?;
             }
+
+            if let Some(filename) = report {
+                runner.write_report(&metrics, &filename)?;
+            }
         },
         Commands::CrossShard { transactions, shards } => {
             runner.run_cross_shard_benchmark(transactions, shards).await?;
@@ -693,6 +857,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Stress { duration, max_tps } => {
             runner.run_stress_test(duration, max_tps).await?;
         },
+        Commands::Soak {
+            duration,
+            tps,
+            workers,
+            interval,
+            warmup,
+            node_pid,
+            db_path,
+            max_memory_growth,
+            max_db_growth,
+            max_tps_drop,
+            export,
+        } => {
+            let params = soak::SoakParams {
+                duration,
+                tps,
+                workers,
+                interval,
+                warmup,
+                node_pid,
+                db_path,
+                max_memory_growth,
+                max_db_growth,
+                max_tps_drop,
+            };
+            let report = soak::run_soak(&runner, &params).await?;
+            report.print();
+
+            if let Some(filename) = export {
+                report.export_to_csv(&filename)?;
+            }
+            if !report.passed() {
+                std::process::exit(1);
+            }
+        },
         Commands::Contracts { calls, address } => {
             info!("Contract benchmark not yet implemented");
         },
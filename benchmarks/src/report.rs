@@ -0,0 +1,202 @@
+//! HTML benchmark report
+//!
+//! Renders a self-contained HTML page with inline SVG charts from the raw `TxResult`
+//! samples, so a run can be attached to a PR and reviewed in a browser:
+//! - TPS over time: successful transactions finalized in each 1-second window
+//! - Latency histogram: successful transactions by submit-to-finalization latency
+//! - Disruptions: outages of the node connection, when there were any
+
+use crate::{connection::Disruption, BenchmarkMetrics, TxResult};
+use std::{fmt::Write as _, fs, io};
+
+/// Chart canvas size in pixels
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 300.0;
+/// Space left for axis labels
+const MARGIN: f64 = 50.0;
+/// Buckets of the latency histogram
+const LATENCY_BUCKETS: usize = 40;
+
+/// Write the report for `metrics` and its `results` to `path`
+pub fn write_html_report(path: &str, metrics: &BenchmarkMetrics, results: &[TxResult]) -> io::Result<()> {
+    fs::write(path, render_html(metrics, results))
+}
+
+/// Full HTML page: summary table, TPS chart and latency histogram
+pub fn render_html(metrics: &BenchmarkMetrics, results: &[TxResult]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Netchain TPS Benchmark Report</title>\n");
+    html.push_str(
+        "<style>body{font-family:sans-serif;margin:2em}table{border-collapse:collapse}\
+         td{padding:2px 12px;border-bottom:1px solid #ddd}td:last-child{text-align:right}\
+         svg{border:1px solid #ccc;margin-bottom:2em}</style>\n",
+    );
+    html.push_str("</head>\n<body>\n<h1>Netchain TPS Benchmark Report</h1>\n");
+
+    html.push_str("<table>\n");
+    let rows = [
+        ("Total sent", metrics.total_transactions.to_string()),
+        ("Successful", metrics.successful_transactions.to_string()),
+        ("Failed", metrics.failed_transactions.to_string()),
+        ("Duration", format!("{:.2} s", metrics.total_duration_ms as f64 / 1000.0)),
+        ("Average TPS", format!("{:.2}", metrics.average_tps)),
+        ("Peak TPS", format!("{:.2}", metrics.peak_tps)),
+        ("Average latency", format!("{:.2} ms", metrics.average_latency_ms)),
+        ("p95 latency", format!("{:.2} ms", metrics.p95_latency_ms)),
+        ("p99 latency", format!("{:.2} ms", metrics.p99_latency_ms)),
+        ("Blocks processed", metrics.blocks_processed.to_string()),
+        ("Disruptions", metrics.disruptions.len().to_string()),
+        (
+            "Disrupted time",
+            format!("{:.2} s", metrics.disruptions.iter().map(Disruption::duration_ms).sum::<u64>() as f64 / 1000.0),
+        ),
+    ];
+    for (name, value) in rows {
+        let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", name, value);
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>TPS over time</h2>\n");
+    html.push_str(&tps_chart(&tps_series(results)));
+    html.push_str("<h2>Latency histogram</h2>\n");
+    html.push_str(&latency_chart(&latency_histogram(results, LATENCY_BUCKETS)));
+
+    if !metrics.disruptions.is_empty() {
+        html.push_str("<h2>Disruptions</h2>\n<table>\n");
+        html.push_str("<tr><td>From</td><td>To</td><td>Lost</td><td>Restored</td><td>Attempts</td></tr>\n");
+        for disruption in &metrics.disruptions {
+            let _ = writeln!(
+                html,
+                "<tr><td>{:.2} s</td><td>{:.2} s</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                disruption.started_ms as f64 / 1000.0,
+                disruption.ended_ms as f64 / 1000.0,
+                disruption.lost,
+                disruption.restored,
+                disruption.attempts,
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Successful transactions per 1-second window since the start of the run, gaps included
+pub fn tps_series(results: &[TxResult]) -> Vec<u64> {
+    let mut series = Vec::new();
+    for result in results.iter().filter(|r| r.success) {
+        let second = (result.timestamp / 1000) as usize;
+        if series.len() <= second {
+            series.resize(second + 1, 0);
+        }
+        series[second] += 1;
+    }
+    series
+}
+
+/// Latency bucket: lower and upper bound in ms, and successful transactions within
+pub struct LatencyBucket {
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub count: u64,
+}
+
+/// Successful transactions split into `buckets` equal latency ranges from min to max
+pub fn latency_histogram(results: &[TxResult], buckets: usize) -> Vec<LatencyBucket> {
+    let latencies: Vec<u64> = results.iter().filter(|r| r.success).map(|r| r.execution_time_ms).collect();
+    let (Some(&min), Some(&max)) = (latencies.iter().min(), latencies.iter().max()) else {
+        return Vec::new();
+    };
+
+    let width = ((max - min) / buckets as u64).max(1);
+    let buckets = ((max - min) / width + 1) as usize;
+    let mut histogram: Vec<LatencyBucket> = (0..buckets as u64)
+        .map(|i| LatencyBucket { from_ms: min + i * width, to_ms: min + (i + 1) * width, count: 0 })
+        .collect();
+    for latency in latencies {
+        let index = (((latency - min) / width) as usize).min(buckets - 1);
+        histogram[index].count += 1;
+    }
+    histogram
+}
+
+/// Line chart of the TPS series
+fn tps_chart(series: &[u64]) -> String {
+    let peak = series.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let step = (WIDTH - 2.0 * MARGIN) / (series.len().max(2) - 1) as f64;
+    let points: Vec<String> = series
+        .iter()
+        .enumerate()
+        .map(|(second, &tps)| format!("{:.1},{:.1}", MARGIN + second as f64 * step, y(tps as f64, peak)))
+        .collect();
+
+    let mut svg = open_svg(peak, "TPS", "0 s", &format!("{} s", series.len()));
+    let _ = writeln!(
+        svg,
+        "<polyline fill=\"none\" stroke=\"#2a7ae2\" stroke-width=\"2\" points=\"{}\"/>",
+        points.join(" ")
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Bar chart of the latency histogram
+fn latency_chart(histogram: &[LatencyBucket]) -> String {
+    let peak = histogram.iter().map(|bucket| bucket.count).max().unwrap_or(0).max(1) as f64;
+    let bar = (WIDTH - 2.0 * MARGIN) / histogram.len().max(1) as f64;
+    let (from, to) = match (histogram.first(), histogram.last()) {
+        (Some(first), Some(last)) => (format!("{} ms", first.from_ms), format!("{} ms", last.to_ms)),
+        _ => (String::new(), "no samples".to_string()),
+    };
+
+    let mut svg = open_svg(peak, "transactions", &from, &to);
+    for (i, bucket) in histogram.iter().enumerate() {
+        let top = y(bucket.count as f64, peak);
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#e2862a\">\
+             <title>{}-{} ms: {}</title></rect>",
+            MARGIN + i as f64 * bar,
+            top,
+            (bar - 1.0).max(1.0),
+            HEIGHT - MARGIN - top,
+            bucket.from_ms,
+            bucket.to_ms,
+            bucket.count,
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// SVG element with axes, the y-axis peak and label, and the x-axis bounds
+fn open_svg(peak: f64, y_label: &str, x_from: &str, x_to: &str) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">", WIDTH, HEIGHT);
+    let _ = writeln!(
+        svg,
+        "<path d=\"M{m},{top} V{bottom} H{right}\" fill=\"none\" stroke=\"#333\"/>",
+        m = MARGIN,
+        top = MARGIN / 2.0,
+        bottom = HEIGHT - MARGIN,
+        right = WIDTH - MARGIN,
+    );
+    let _ = writeln!(svg, "<text x=\"4\" y=\"{}\" font-size=\"12\">{}</text>", MARGIN / 2.0 + 4.0, peak);
+    let _ = writeln!(svg, "<text x=\"4\" y=\"{}\" font-size=\"12\">{}</text>", HEIGHT / 2.0, y_label);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"12\">{}</text>", MARGIN, HEIGHT - MARGIN / 2.0, x_from);
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"end\">{}</text>",
+        WIDTH - MARGIN,
+        HEIGHT - MARGIN / 2.0,
+        x_to,
+    );
+    svg
+}
+
+/// Vertical position of `value` on a chart whose top is `peak`
+fn y(value: f64, peak: f64) -> f64 {
+    HEIGHT - MARGIN - value / peak * (HEIGHT - 1.5 * MARGIN)
+}
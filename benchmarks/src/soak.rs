@@ -0,0 +1,356 @@
+//! Soak test mode
+//!
+//! A TPS run shows what the node sustains for a minute; a soak run keeps a moderate constant
+//! load on it for hours to catch what only shows over time. Every `--interval` seconds it
+//! samples:
+//! - the transfers finalized per second over the interval
+//! - the resident memory of the node process (`--node-pid`), from the OS
+//! - the size of the node's database directory (`--db-path`)
+//! - the transactions waiting in the node's pool, over `author_pendingExtrinsics`
+//!
+//! Once the run ends, the samples after `--warmup` are checked. Memory or database size that
+//! grew in most intervals and by more than its limit over the run is reported as a leak, a
+//! pool that grew in most intervals to more than an interval's worth of load as a backlog,
+//! and TPS whose last quarter averages more than `--max-tps-drop` percent below the first
+//! quarter as degradation. Any finding fails the run.
+//!
+//! Every worker signs with its own `//Bench//<n>` account, so fund them before the run. Lost
+//! connections are restored as in every mode, and the disruptions listed with the verdict.
+
+use crate::{
+    bench_account,
+    connection::{Connection, Disruption},
+    netchain, BenchmarkRunner,
+};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use subxt::{backend::rpc::rpc_params, tx::PairSigner, PolkadotConfig};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tokio::time::MissedTickBehavior;
+
+/// Share of intervals a resource must grow in to count as growing steadily
+const STEADY_GROWTH_SHARE: f64 = 0.8;
+
+/// Parameters of a soak run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoakParams {
+    pub duration: u64,
+    pub tps: u32,
+    pub workers: u32,
+    pub interval: u64,
+    pub warmup: u64,
+    pub node_pid: Option<u32>,
+    pub db_path: Option<PathBuf>,
+    pub max_memory_growth: f64,
+    pub max_db_growth: f64,
+    pub max_tps_drop: f64,
+}
+
+/// Node state sampled at the end of an interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoakSample {
+    /// Seconds from the start of the run
+    pub elapsed_secs: u64,
+    /// Transfers finalized per second over the interval
+    pub tps: f64,
+    /// Transfers that failed over the interval
+    pub failed: u64,
+    /// Resident memory of the node process
+    pub memory_bytes: Option<u64>,
+    /// Size of the node's database directory
+    pub db_bytes: Option<u64>,
+    /// Transactions in the node's pool
+    pub pool_size: Option<u64>,
+}
+
+/// Samples of a soak run and what the checks found in them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoakReport {
+    pub samples: Vec<SoakSample>,
+    pub findings: Vec<String>,
+    /// Outages of the node connection during the run
+    pub disruptions: Vec<Disruption>,
+}
+
+impl SoakReport {
+    /// Whether the checks found nothing
+    pub fn passed(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Print the samples' range and the verdict
+    pub fn print(&self) {
+        println!("\n🕰️  Netchain Soak Test Results");
+        println!("=====================================");
+        println!("  Samples:         {:>10}", self.samples.len());
+        if let (Some(first), Some(last)) = (self.samples.first(), self.samples.last()) {
+            println!("  Duration:        {:>10.2}h", last.elapsed_secs as f64 / 3600.0);
+            println!("  TPS:             {:>10.2} → {:.2}", first.tps, last.tps);
+            println!("  Failed:          {:>10}", self.samples.iter().map(|s| s.failed).sum::<u64>());
+            if let (Some(from), Some(to)) = (first.memory_bytes, last.memory_bytes) {
+                println!("  Node Memory:     {:>8} MB → {} MB", from / (1024 * 1024), to / (1024 * 1024));
+            }
+            if let (Some(from), Some(to)) = (first.db_bytes, last.db_bytes) {
+                println!("  Database:        {:>8} MB → {} MB", from / (1024 * 1024), to / (1024 * 1024));
+            }
+            if let (Some(from), Some(to)) = (first.pool_size, last.pool_size) {
+                println!("  Pool:            {:>10} → {}", from, to);
+            }
+        }
+        println!("  Disruptions:     {:>10}", self.disruptions.len());
+        for disruption in &self.disruptions {
+            println!(
+                "    {:>8.2}s - {:>8.2}s  {} → {}",
+                disruption.started_ms as f64 / 1000.0,
+                disruption.ended_ms as f64 / 1000.0,
+                disruption.lost,
+                disruption.restored,
+            );
+        }
+
+        if self.passed() {
+            println!("\n  ✅ PASS: no leak or degradation found");
+        } else {
+            println!("\n  ❌ FAIL:");
+            for finding in &self.findings {
+                println!("    - {}", finding);
+            }
+        }
+        println!("\n=====================================");
+    }
+
+    /// Export the samples to CSV
+    pub fn export_to_csv(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = csv::Writer::from_path(filename)?;
+        writer.write_record(["elapsed_secs", "tps", "failed", "memory_bytes", "db_bytes", "pool_size"])?;
+        let optional = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+        for sample in &self.samples {
+            writer.write_record([
+                sample.elapsed_secs.to_string(),
+                format!("{:.2}", sample.tps),
+                sample.failed.to_string(),
+                optional(sample.memory_bytes),
+                optional(sample.db_bytes),
+                optional(sample.pool_size),
+            ])?;
+        }
+        writer.flush()?;
+        info!("Samples exported to {}", filename);
+
+        Ok(())
+    }
+}
+
+/// Transfers finalized and failed so far
+#[derive(Default)]
+struct Counters {
+    finalized: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Keep `params.tps` transfers per second on the node for `params.duration`, sampling it
+/// every `params.interval`, and check the samples
+pub async fn run_soak(runner: &BenchmarkRunner, params: &SoakParams) -> Result<SoakReport, Box<dyn std::error::Error>> {
+    info!("Starting soak test:");
+    info!("  Duration: {}s", params.duration);
+    info!("  Load: {} TPS over {} workers", params.tps, params.workers);
+    info!("  Sampling every {}s after a {}s warmup", params.interval, params.warmup);
+
+    let counters = Arc::new(Counters::default());
+    let start = Instant::now();
+    let deadline = start + Duration::from_secs(params.duration);
+
+    let workers = params.workers.max(1);
+    let period = Duration::from_secs_f64(workers as f64 / params.tps.max(1) as f64);
+    let handles: Vec<_> = (0..workers)
+        .map(|worker| {
+            tokio::spawn(soak_worker(
+                Arc::clone(&runner.connection),
+                bench_account(worker),
+                period,
+                deadline,
+                Arc::clone(&counters),
+            ))
+        })
+        .collect();
+
+    let mut system = System::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(params.interval.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker.tick().await;
+
+    let mut samples = Vec::new();
+    let (mut last_finalized, mut last_failed, mut last_time) = (0, 0, start);
+    while Instant::now() < deadline {
+        ticker.tick().await;
+        let now = Instant::now();
+        let (finalized, failed) = (counters.finalized.load(Ordering::Relaxed), counters.failed.load(Ordering::Relaxed));
+        let (rpc, generation) = runner.connection.rpc();
+
+        let sample = SoakSample {
+            elapsed_secs: now.duration_since(start).as_secs(),
+            tps: (finalized - last_finalized) as f64 / now.duration_since(last_time).as_secs_f64(),
+            failed: failed - last_failed,
+            memory_bytes: params.node_pid.and_then(|pid| process_memory(&mut system, pid)),
+            db_bytes: params.db_path.as_deref().and_then(|path| match directory_size(path) {
+                Ok(size) => Some(size),
+                Err(e) => {
+                    warn!("Failed to measure {}: {}", path.display(), e);
+                    None
+                },
+            }),
+            pool_size: match rpc.request::<Vec<serde_json::Value>>("author_pendingExtrinsics", rpc_params![]).await {
+                Ok(pending) => Some(pending.len() as u64),
+                Err(e) => {
+                    warn!("Failed to read the transaction pool: {}", e);
+                    if let Err(e) = runner.connection.recover(generation, &e).await {
+                        warn!("Failed to reconnect: {}", e);
+                    }
+                    None
+                },
+            },
+        };
+        info!(
+            "Soak {}s: {:.2} TPS, {} failed, memory {:?}, database {:?}, pool {:?}",
+            sample.elapsed_secs, sample.tps, sample.failed, sample.memory_bytes, sample.db_bytes, sample.pool_size,
+        );
+        samples.push(sample);
+        (last_finalized, last_failed, last_time) = (finalized, failed, now);
+    }
+
+    for handle in handles {
+        handle.abort();
+    }
+
+    let findings = check_samples(&samples, params);
+    Ok(SoakReport { samples, findings, disruptions: runner.connection.disruptions() })
+}
+
+/// Submit a transfer every `period` until `deadline`, counting those finalized and failed
+async fn soak_worker(
+    connection: Arc<Connection>,
+    signer: sp_core::sr25519::Pair,
+    period: Duration,
+    deadline: Instant,
+    counters: Arc<Counters>,
+) {
+    let signer = PairSigner::<PolkadotConfig, _>::new(signer);
+    let recipient = sp_keyring::sr25519::sr25519::Keyring::Bob.to_account_id();
+    let mut ticker = tokio::time::interval(period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    while Instant::now() < deadline {
+        ticker.tick().await;
+        let tx = netchain::tx().balances().transfer_allow_death(recipient.clone().into(), 1);
+
+        // Only submission is awaited, so the load does not depend on finality
+        match connection.submit(&tx, &signer).await {
+            Ok((progress, generation)) => {
+                let (connection, counters) = (Arc::clone(&connection), Arc::clone(&counters));
+                tokio::spawn(async move {
+                    match progress.wait_for_finalized_success().await {
+                        Ok(_) => {
+                            counters.finalized.fetch_add(1, Ordering::Relaxed);
+                        },
+                        Err(e) => {
+                            debug!("Soak transfer failed: {:?}", e);
+                            counters.failed.fetch_add(1, Ordering::Relaxed);
+                            if let Err(e) = connection.recover(generation, &e).await {
+                                warn!("Failed to reconnect: {}", e);
+                            }
+                        },
+                    }
+                });
+            },
+            Err(e) => {
+                warn!("Failed to submit soak transfer: {:?}", e);
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+            },
+        }
+    }
+}
+
+/// Resident memory of process `pid`, if it is running
+fn process_memory(system: &mut System, pid: u32) -> Option<u64> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    let memory = system.process(pid).map(|process| process.memory());
+    if memory.is_none() {
+        warn!("Node process {} is not running", pid);
+    }
+    memory
+}
+
+/// Total size of the files under `path`
+fn directory_size(path: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() { directory_size(&entry.path())? } else { metadata.len() };
+    }
+    Ok(size)
+}
+
+/// Leaks and degradation found in the samples after the warmup
+pub fn check_samples(samples: &[SoakSample], params: &SoakParams) -> Vec<String> {
+    let checked: Vec<&SoakSample> = samples.iter().filter(|sample| sample.elapsed_secs > params.warmup).collect();
+    let series = |value: fn(&SoakSample) -> Option<u64>| -> Vec<u64> { checked.iter().filter_map(|s| value(s)).collect() };
+
+    let mut findings = Vec::new();
+    findings.extend(check_growth("Node memory", &series(|s| s.memory_bytes), params.max_memory_growth));
+    findings.extend(check_growth("Database size", &series(|s| s.db_bytes), params.max_db_growth));
+
+    // A pool growing by more than an interval's worth of load never catches up
+    let pool = series(|s| s.pool_size);
+    if let (Some(&last), true) = (pool.last(), grows_steadily(&pool)) {
+        if last > u64::from(params.tps) * params.interval {
+            findings.push(format!(
+                "Transaction pool grew in most intervals, to {} transactions; the node falls behind the load",
+                last
+            ));
+        }
+    }
+
+    let tps: Vec<f64> = checked.iter().map(|s| s.tps).collect();
+    let quarter = tps.len() / 4;
+    if quarter > 0 {
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        let (first, last) = (mean(&tps[..quarter]), mean(&tps[tps.len() - quarter..]));
+        let drop = if first > 0.0 { (first - last) / first * 100.0 } else { 0.0 };
+        if drop > params.max_tps_drop {
+            findings.push(format!(
+                "TPS fell by {:.1}% from {:.2} in the first quarter to {:.2} in the last (limit {:.1}%)",
+                drop, first, last, params.max_tps_drop
+            ));
+        }
+    }
+
+    findings
+}
+
+/// Whether `values` rose or held in most intervals
+fn grows_steadily(values: &[u64]) -> bool {
+    if values.len() < 3 {
+        return false;
+    }
+    let rising = values.windows(2).filter(|pair| pair[1] >= pair[0]).count();
+    rising as f64 >= STEADY_GROWTH_SHARE * (values.len() - 1) as f64
+}
+
+/// Finding if `values` grew steadily and by more than `max_growth` percent
+fn check_growth(name: &str, values: &[u64], max_growth: f64) -> Option<String> {
+    let (&first, &last) = (values.first()?, values.last()?);
+    let growth = if first > 0 { (last as f64 - first as f64) / first as f64 * 100.0 } else { 0.0 };
+    (grows_steadily(values) && growth > max_growth).then(|| {
+        format!("{} grew in most intervals, by {:.1}% over the run (limit {:.1}%)", name, growth, max_growth)
+    })
+}
@@ -0,0 +1,95 @@
+//! Benchmark of the `netchain_storage` example contract on the Netchain runtime.
+//!
+//! Criterion times instantiating the contract and its `set` and `get` messages at each of
+//! `PAYLOAD_SIZES`. The weights the runtime charges for the same cases are then written to
+//! `target/contract-weights/netchain_storage.json`. With `NETCHAIN_CONTRACT_BASELINE` set to a
+//! previous report, the run fails if any case got more than `NETCHAIN_CONTRACT_TOLERANCE`
+//! percent (5 by default) heavier in ref time or proof size.
+//!
+//! Run with `cargo bench -p netchain-contract-bench --bench netchain_storage` after building
+//! the contract with `cargo contract build`.
+
+use criterion::{criterion_group, BatchSize, BenchmarkId, Criterion, Throughput};
+use netchain_contract_bench::{
+	call, get_input, instantiate, measure_storage, new_ext, new_input, regressions, set_input, storage_wasm,
+	Baseline, PAYLOAD_SIZES,
+};
+use std::path::PathBuf;
+
+/// Percent a case may grow over the baseline before the run fails
+const DEFAULT_TOLERANCE: u64 = 5;
+
+fn instantiate_storage(c: &mut Criterion) {
+	let code = storage_wasm();
+
+	c.bench_function("netchain_storage/instantiate", |b| {
+		b.iter_batched(
+			new_ext,
+			|mut ext| ext.execute_with(|| instantiate(code.clone(), new_input(), Vec::new())),
+			BatchSize::SmallInput,
+		)
+	});
+}
+
+fn set_and_get(c: &mut Criterion) {
+	let code = storage_wasm();
+	let mut ext = new_ext();
+	let contract = ext.execute_with(|| instantiate(code, new_input(), Vec::new()).0);
+
+	let mut group = c.benchmark_group("netchain_storage");
+	for size in PAYLOAD_SIZES {
+		group.throughput(Throughput::Bytes(size as u64));
+		group.bench_with_input(BenchmarkId::new("set", size), &size, |b, &size| {
+			b.iter(|| ext.execute_with(|| call(&contract, set_input(size))))
+		});
+		group.bench_with_input(BenchmarkId::new("get", size), &size, |b, &size| {
+			ext.execute_with(|| call(&contract, set_input(size)));
+			b.iter(|| ext.execute_with(|| call(&contract, get_input())))
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, instantiate_storage, set_and_get);
+
+fn report_dir() -> PathBuf {
+	std::env::var_os("CARGO_TARGET_DIR")
+		.map(PathBuf::from)
+		.unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target"))
+		.join("contract-weights")
+}
+
+/// Write this run's weights and compare them against `NETCHAIN_CONTRACT_BASELINE`, if set
+fn check_weights() {
+	let current = measure_storage(&storage_wasm());
+
+	let dir = report_dir();
+	std::fs::create_dir_all(&dir).expect("cannot create the report directory");
+	let report = dir.join("netchain_storage.json");
+	std::fs::write(&report, serde_json::to_string_pretty(&current).expect("weights serialize"))
+		.expect("cannot write the weight report");
+	println!("netchain_storage weights written to {}", report.display());
+
+	let Some(path) = std::env::var_os("NETCHAIN_CONTRACT_BASELINE") else { return };
+	let baseline: Baseline = serde_json::from_slice(&std::fs::read(&path).expect("cannot read the baseline"))
+		.expect("baseline is not a weight report");
+	let tolerance = std::env::var("NETCHAIN_CONTRACT_TOLERANCE")
+		.ok()
+		.map(|value| value.parse().expect("NETCHAIN_CONTRACT_TOLERANCE must be a percentage"))
+		.unwrap_or(DEFAULT_TOLERANCE);
+
+	let regressed = regressions(&baseline, &current, tolerance);
+	if !regressed.is_empty() {
+		eprintln!("netchain_storage weights regressed by more than {tolerance}%:");
+		for case in &regressed {
+			eprintln!("  {case}");
+		}
+		std::process::exit(1);
+	}
+}
+
+fn main() {
+	benches();
+	Criterion::default().configure_from_args().final_summary();
+	check_weights();
+}
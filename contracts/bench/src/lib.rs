@@ -0,0 +1,195 @@
+//! # Netchain contract benchmarks
+//!
+//! Deploys the example contracts into a test externality of the Netchain runtime, with its
+//! `pallet_contracts` configuration and `Schedule`, and measures what their constructors and
+//! messages consume. `benches/netchain_storage.rs` times `netchain_storage` with criterion
+//! and writes the weights [`measure_storage`] returns as a JSON [`Baseline`], which
+//! [`regressions`] compares against a previous one.
+//!
+//! Build the contract first:
+//!
+//! ```bash
+//! cargo contract build --release --manifest-path contracts/netchain_storage/Cargo.toml
+//! cargo bench -p netchain-contract-bench
+//! ```
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use codec::Encode;
+use frame_support::traits::Get;
+use netchain_runtime::{
+	AccountId, ContractPermissions, Contracts, Runtime, RuntimeOrigin, System, UNIT,
+};
+use pallet_contracts::{Code, CollectEvents, DebugInfo};
+use serde::{Deserialize, Serialize};
+use sp_core::hashing::blake2_256;
+use sp_runtime::{BuildStorage, Weight};
+
+/// Value sizes `set` and `get` are measured with; the contract accepts up to 1024 bytes
+pub const PAYLOAD_SIZES: [usize; 4] = [0, 64, 256, 1024];
+
+/// Entries per user `netchain_storage` is instantiated with
+pub const MAX_ENTRIES_PER_USER: u32 = 100;
+
+/// Environment variable overriding where the `netchain_storage` blob is read from
+pub const STORAGE_WASM_VAR: &str = "NETCHAIN_STORAGE_WASM";
+
+/// Key every measured `set` and `get` uses
+const KEY: &str = "bench";
+
+/// Account deploying and calling the contracts
+pub fn deployer() -> AccountId {
+	AccountId::new([1u8; 32])
+}
+
+/// The `netchain_storage` blob `cargo contract build` wrote, or the one at
+/// [`STORAGE_WASM_VAR`]
+pub fn storage_wasm() -> Vec<u8> {
+	let path = std::env::var_os(STORAGE_WASM_VAR).map(PathBuf::from).unwrap_or_else(|| {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../netchain_storage/target/ink/netchain_storage.wasm")
+	});
+	std::fs::read(&path).unwrap_or_else(|error| {
+		panic!("cannot read {}: {error}; build the contract with `cargo contract build` first", path.display())
+	})
+}
+
+/// Selector of the ink! constructor or message `name`
+pub fn selector(name: &str) -> [u8; 4] {
+	let hash = blake2_256(name.as_bytes());
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Input of `new(MAX_ENTRIES_PER_USER)`
+pub fn new_input() -> Vec<u8> {
+	(selector("new"), MAX_ENTRIES_PER_USER).encode()
+}
+
+/// Input of `set` storing a value of `size` bytes
+pub fn set_input(size: usize) -> Vec<u8> {
+	(selector("set"), KEY, "x".repeat(size)).encode()
+}
+
+/// Input of `get` reading the key `set_input` stores
+pub fn get_input() -> Vec<u8> {
+	(selector("get"), KEY).encode()
+}
+
+/// Runtime state with a funded [`deployer`] allowed to upload code
+pub fn new_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::<Runtime>::default().build_storage().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![(deployer(), 1_000_000 * UNIT)],
+		..Default::default()
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		ContractPermissions::grant_upload(RuntimeOrigin::root(), deployer()).unwrap();
+	});
+	ext
+}
+
+fn gas_limit() -> Weight {
+	<Runtime as frame_system::Config>::BlockWeights::get().max_block
+}
+
+/// Upload `code` and instantiate it with `input`, returning the contract and the weight
+/// consumed
+///
+/// Panics if instantiation fails or reverts.
+pub fn instantiate(code: Vec<u8>, input: Vec<u8>, salt: Vec<u8>) -> (AccountId, Weight) {
+	let result = Contracts::bare_instantiate(
+		deployer(),
+		0,
+		gas_limit(),
+		None,
+		Code::Upload(code),
+		input,
+		salt,
+		DebugInfo::Skip,
+		CollectEvents::Skip,
+	);
+	let instantiated = result.result.expect("instantiation failed");
+	assert!(!instantiated.result.did_revert(), "constructor reverted");
+	(instantiated.account_id, result.gas_consumed)
+}
+
+/// Call `contract` with `input`, returning the weight consumed
+///
+/// Panics if the call fails or reverts.
+pub fn call(contract: &AccountId, input: Vec<u8>) -> Weight {
+	let result = Contracts::bare_call(
+		deployer(),
+		contract.clone(),
+		0,
+		gas_limit(),
+		None,
+		input,
+		DebugInfo::Skip,
+		CollectEvents::Skip,
+		pallet_contracts::Determinism::Enforced,
+	);
+	let returned = result.result.expect("call failed");
+	assert!(!returned.did_revert(), "call reverted");
+	result.gas_consumed
+}
+
+/// Weight of one measured case
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractWeight {
+	pub ref_time: u64,
+	pub proof_size: u64,
+}
+
+impl From<Weight> for ContractWeight {
+	fn from(weight: Weight) -> Self {
+		Self { ref_time: weight.ref_time(), proof_size: weight.proof_size() }
+	}
+}
+
+/// Weights of a contract's cases, e.g. `instantiate` or `set/256`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+	pub contract: String,
+	pub weights: BTreeMap<String, ContractWeight>,
+}
+
+/// Weights of instantiating `netchain_storage` and of `set` and `get` at every
+/// [`PAYLOAD_SIZES`], each measured on fresh state
+pub fn measure_storage(code: &[u8]) -> Baseline {
+	let mut weights = BTreeMap::new();
+
+	new_ext().execute_with(|| {
+		let (_, weight) = instantiate(code.to_vec(), new_input(), Vec::new());
+		weights.insert("instantiate".into(), weight.into());
+	});
+	for size in PAYLOAD_SIZES {
+		new_ext().execute_with(|| {
+			let (contract, _) = instantiate(code.to_vec(), new_input(), Vec::new());
+			weights.insert(format!("set/{size}"), call(&contract, set_input(size)).into());
+			weights.insert(format!("get/{size}"), call(&contract, get_input()).into());
+		});
+	}
+
+	Baseline { contract: "netchain_storage".into(), weights }
+}
+
+/// Cases of `current` whose ref time or proof size exceeds `baseline`'s by more than
+/// `tolerance_percent`, or that `baseline` does not have, described for a report
+pub fn regressions(baseline: &Baseline, current: &Baseline, tolerance_percent: u64) -> Vec<String> {
+	let exceeds = |now: u64, before: u64| u128::from(now) * 100 > u128::from(before) * u128::from(100 + tolerance_percent);
+
+	current
+		.weights
+		.iter()
+		.filter_map(|(case, now)| match baseline.weights.get(case) {
+			None => Some(format!("{case}: not in the baseline")),
+			Some(before) if exceeds(now.ref_time, before.ref_time) || exceeds(now.proof_size, before.proof_size) =>
+				Some(format!("{case}: {before:?} -> {now:?}")),
+			Some(_) => None,
+		})
+		.collect()
+}
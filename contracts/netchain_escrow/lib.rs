@@ -0,0 +1,631 @@
+//! # Netchain Cross-Chain Escrow Contract
+//!
+//! Reference integration for Netchain's chain extension. Funds deposited into an escrow are
+//! released to the beneficiary once a condition observed natively on-chain holds:
+//! - an oracle price crosses a threshold (via the oracle pallet), or
+//! - an IBC packet sent by Netchain is acknowledged by the counterparty (via the IBC pallet)
+//! - a cross-shard transfer settled, paying at least an amount to an account (via the
+//!   sharding pallet's transfer receipts)
+//!
+//! No off-chain relayer or trusted keeper is needed: anyone may call `release`, and the
+//! contract checks the condition itself. Escrows that are never satisfied can be refunded
+//! after their deadline, or as soon as the awaited packet times out.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+use ink::env::{DefaultEnvironment, Environment};
+use ink::prelude::vec::Vec;
+
+/// Latest aggregated oracle reading: (value, confidence, block aggregated at, first block
+/// readable, last block fresh)
+pub type OracleReading = (Vec<u8>, u8, u32, u32, u32);
+
+/// Receipt of a settled cross-shard transfer (mirrors `pallet_sharding::CrossShardTxReceipt`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct CrossShardReceipt {
+    /// Account the amount was taken from
+    pub sender: <DefaultEnvironment as Environment>::AccountId,
+    /// Account the amount was paid to
+    pub recipient: <DefaultEnvironment as Environment>::AccountId,
+    /// Amount delivered
+    pub amount: <DefaultEnvironment as Environment>::Balance,
+    /// Shard of the sender
+    pub from_shard: u8,
+    /// Shard of the recipient
+    pub to_shard: u8,
+    /// Block in which the transfer settled
+    pub settled_at: u32,
+}
+
+/// Lifecycle status of an IBC packet sent by Netchain (mirrors `pallet_ibc_core::PacketStatus`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum PacketStatus {
+    /// No packet with this sequence was sent
+    Unknown,
+    /// Sent and awaiting acknowledgment or timeout
+    Pending,
+    /// Acknowledged by the counterparty
+    Acknowledged,
+    /// Timed out without delivery
+    TimedOut,
+}
+
+/// Netchain chain extension (see `runtime/src/chain_extension.rs`)
+#[ink::chain_extension(extension = 0x4e43)]
+pub trait NetchainExtension {
+    type ErrorCode = NetchainExtensionError;
+
+    /// Latest aggregated oracle value for `key`
+    #[ink(function = 0x0001, handle_status = false)]
+    fn oracle_latest(key: Vec<u8>) -> Option<OracleReading>;
+
+    /// Status of the packet sent from `port` with `sequence`
+    #[ink(function = 0x0101, handle_status = false)]
+    fn ibc_packet_status(port: Vec<u8>, sequence: u64) -> PacketStatus;
+
+    /// Receipt of the cross-shard transfer `tx_hash`, once it settled
+    #[ink(function = 0x0204, handle_status = false)]
+    fn verify_cross_shard_receipt(tx_hash: <DefaultEnvironment as Environment>::Hash) -> Option<CrossShardReceipt>;
+}
+
+/// Errors reported by the Netchain chain extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum NetchainExtensionError {
+    /// The runtime rejected the call
+    Failed,
+}
+
+impl ink::env::chain_extension::FromStatusCode for NetchainExtensionError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            _ => Err(Self::Failed),
+        }
+    }
+}
+
+/// Contract environment with the Netchain chain extension enabled
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(TypeInfo)]
+pub enum NetchainEnvironment {}
+
+impl Environment for NetchainEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+    type ChainExtension = NetchainExtension;
+}
+
+/// Parse an oracle value such as `b"50000.25"` into millionths (`50_000_250_000`)
+///
+/// Digits beyond the sixth decimal are truncated. Returns `None` for anything that is not a
+/// plain non-negative decimal number.
+pub fn parse_price(value: &[u8]) -> Option<u128> {
+    let (whole, fraction) = match value.iter().position(|&c| c == b'.') {
+        Some(dot) => (&value[..dot], &value[dot + 1..]),
+        None => (value, &value[value.len()..]),
+    };
+    if whole.is_empty() {
+        return None;
+    }
+
+    let mut price: u128 = 0;
+    for &digit in whole {
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+        price = price.checked_mul(10)?.checked_add(u128::from(digit - b'0'))?;
+    }
+    for i in 0..6 {
+        let digit = match fraction.get(i) {
+            Some(d) if d.is_ascii_digit() => d - b'0',
+            Some(_) => return None,
+            None => 0,
+        };
+        price = price.checked_mul(10)?.checked_add(u128::from(digit))?;
+    }
+    if !fraction.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    Some(price)
+}
+
+#[ink::contract(env = crate::NetchainEnvironment)]
+mod netchain_escrow {
+    use super::{parse_price, PacketStatus};
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    /// Escrow identifier
+    pub type EscrowId = u64;
+
+    /// Condition under which escrowed funds are released
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum ReleaseCondition {
+        /// Oracle value for `key` is at least `threshold` (millionths)
+        PriceAtLeast { key: Vec<u8>, threshold: u128, min_confidence: u8 },
+        /// Oracle value for `key` is at most `threshold` (millionths)
+        PriceAtMost { key: Vec<u8>, threshold: u128, min_confidence: u8 },
+        /// IBC packet sent from `port` with `sequence` was acknowledged
+        PacketAcknowledged { port: Vec<u8>, sequence: u64 },
+        /// Cross-shard transfer `tx_hash` settled, paying at least `min_amount` to `recipient`
+        TransferSettled { tx_hash: Hash, recipient: AccountId, min_amount: Balance },
+    }
+
+    /// Escrow lifecycle state
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub enum EscrowState {
+        /// Funds held, waiting for the condition or the deadline
+        Active,
+        /// Funds paid to the beneficiary
+        Released,
+        /// Funds returned to the depositor
+        Refunded,
+    }
+
+    /// A single escrow
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct Escrow {
+        /// Account that deposited the funds
+        pub depositor: AccountId,
+        /// Account paid when the condition holds
+        pub beneficiary: AccountId,
+        /// Escrowed amount
+        pub amount: Balance,
+        /// Release condition
+        pub condition: ReleaseCondition,
+        /// Block after which the depositor can be refunded
+        pub deadline: BlockNumber,
+        /// Current state
+        pub state: EscrowState,
+    }
+
+    /// The escrow contract state
+    #[ink(storage)]
+    pub struct NetchainEscrow {
+        /// All escrows by id
+        escrows: Mapping<EscrowId, Escrow>,
+        /// Id assigned to the next escrow
+        next_escrow_id: EscrowId,
+    }
+
+    #[ink(event)]
+    pub struct EscrowCreated {
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        #[ink(topic)]
+        depositor: AccountId,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        amount: Balance,
+        deadline: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct EscrowReleased {
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct EscrowRefunded {
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        #[ink(topic)]
+        depositor: AccountId,
+        amount: Balance,
+    }
+
+    /// Contract errors
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum ContractError {
+        /// Escrow id not found
+        EscrowNotFound,
+        /// No funds were transferred with `create`
+        ZeroAmount,
+        /// Deadline is not in the future
+        DeadlineInPast,
+        /// Escrow was already released or refunded
+        NotActive,
+        /// Release condition does not hold yet
+        ConditionNotMet,
+        /// Deadline not reached and the awaited packet has not timed out
+        RefundNotAllowed,
+        /// Native transfer out of the contract failed
+        TransferFailed,
+    }
+
+    /// Result type for contract operations
+    pub type Result<T> = core::result::Result<T, ContractError>;
+
+    impl NetchainEscrow {
+        /// Constructor: create an empty escrow registry
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                escrows: Mapping::default(),
+                next_escrow_id: 0,
+            }
+        }
+
+        /// Lock the transferred value until `condition` holds or `deadline` passes
+        #[ink(message, payable)]
+        pub fn create(
+            &mut self,
+            beneficiary: AccountId,
+            condition: ReleaseCondition,
+            deadline: BlockNumber,
+        ) -> Result<EscrowId> {
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(ContractError::ZeroAmount);
+            }
+            if deadline <= self.env().block_number() {
+                return Err(ContractError::DeadlineInPast);
+            }
+
+            let depositor = self.env().caller();
+            let escrow_id = self.next_escrow_id;
+            self.next_escrow_id = escrow_id.saturating_add(1);
+
+            self.escrows.insert(
+                escrow_id,
+                &Escrow {
+                    depositor,
+                    beneficiary,
+                    amount,
+                    condition,
+                    deadline,
+                    state: EscrowState::Active,
+                },
+            );
+
+            self.env().emit_event(EscrowCreated {
+                escrow_id,
+                depositor,
+                beneficiary,
+                amount,
+                deadline,
+            });
+
+            Ok(escrow_id)
+        }
+
+        /// Pay the beneficiary if the release condition holds (callable by anyone)
+        #[ink(message)]
+        pub fn release(&mut self, escrow_id: EscrowId) -> Result<()> {
+            let mut escrow = self.active_escrow(escrow_id)?;
+            if !self.condition_holds(&escrow.condition) {
+                return Err(ContractError::ConditionNotMet);
+            }
+
+            // Effects before interaction
+            escrow.state = EscrowState::Released;
+            self.escrows.insert(escrow_id, &escrow);
+
+            self.env()
+                .transfer(escrow.beneficiary, escrow.amount)
+                .map_err(|_| ContractError::TransferFailed)?;
+
+            self.env().emit_event(EscrowReleased {
+                escrow_id,
+                beneficiary: escrow.beneficiary,
+                amount: escrow.amount,
+            });
+
+            Ok(())
+        }
+
+        /// Return the funds to the depositor (callable by anyone)
+        ///
+        /// Allowed once the deadline has passed, or immediately if the awaited packet
+        /// timed out, provided the release condition does not hold.
+        #[ink(message)]
+        pub fn refund(&mut self, escrow_id: EscrowId) -> Result<()> {
+            let mut escrow = self.active_escrow(escrow_id)?;
+
+            let packet_timed_out = match &escrow.condition {
+                ReleaseCondition::PacketAcknowledged { port, sequence } => {
+                    self.env().extension().ibc_packet_status(port.clone(), *sequence)
+                        == PacketStatus::TimedOut
+                }
+                _ => false,
+            };
+            let expired = self.env().block_number() > escrow.deadline;
+            if !(expired || packet_timed_out) || self.condition_holds(&escrow.condition) {
+                return Err(ContractError::RefundNotAllowed);
+            }
+
+            // Effects before interaction
+            escrow.state = EscrowState::Refunded;
+            self.escrows.insert(escrow_id, &escrow);
+
+            self.env()
+                .transfer(escrow.depositor, escrow.amount)
+                .map_err(|_| ContractError::TransferFailed)?;
+
+            self.env().emit_event(EscrowRefunded {
+                escrow_id,
+                depositor: escrow.depositor,
+                amount: escrow.amount,
+            });
+
+            Ok(())
+        }
+
+        /// Get an escrow by id
+        #[ink(message)]
+        pub fn escrow(&self, escrow_id: EscrowId) -> Option<Escrow> {
+            self.escrows.get(escrow_id)
+        }
+
+        /// Whether the release condition of an escrow currently holds
+        #[ink(message)]
+        pub fn condition_met(&self, escrow_id: EscrowId) -> Result<bool> {
+            let escrow = self.escrows.get(escrow_id).ok_or(ContractError::EscrowNotFound)?;
+            Ok(self.condition_holds(&escrow.condition))
+        }
+
+        /// Load an escrow that is still holding funds
+        fn active_escrow(&self, escrow_id: EscrowId) -> Result<Escrow> {
+            let escrow = self.escrows.get(escrow_id).ok_or(ContractError::EscrowNotFound)?;
+            if escrow.state != EscrowState::Active {
+                return Err(ContractError::NotActive);
+            }
+            Ok(escrow)
+        }
+
+        /// Evaluate a release condition against on-chain oracle, IBC and sharding state
+        fn condition_holds(&self, condition: &ReleaseCondition) -> bool {
+            match condition {
+                ReleaseCondition::PriceAtLeast { key, threshold, min_confidence } => {
+                    self.oracle_price(key, *min_confidence).map_or(false, |p| p >= *threshold)
+                }
+                ReleaseCondition::PriceAtMost { key, threshold, min_confidence } => {
+                    self.oracle_price(key, *min_confidence).map_or(false, |p| p <= *threshold)
+                }
+                ReleaseCondition::PacketAcknowledged { port, sequence } => {
+                    self.env().extension().ibc_packet_status(port.clone(), *sequence)
+                        == PacketStatus::Acknowledged
+                }
+                ReleaseCondition::TransferSettled { tx_hash, recipient, min_amount } => {
+                    self.env().extension().verify_cross_shard_receipt(*tx_hash).map_or(false, |receipt| {
+                        receipt.recipient == *recipient && receipt.amount >= *min_amount
+                    })
+                }
+            }
+        }
+
+        /// Latest oracle price for `key` in millionths, if fresh, confident enough and
+        /// well-formed
+        fn oracle_price(&self, key: &[u8], min_confidence: u8) -> Option<u128> {
+            let (value, confidence, _, _, valid_until) = self.env().extension().oracle_latest(key.to_vec())?;
+            if confidence < min_confidence || self.env().block_number() > valid_until {
+                return None;
+            }
+            parse_price(&value)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{CrossShardReceipt, NetchainEnvironment, OracleReading};
+        use ink::env::test;
+        use ink::scale::Encode;
+
+        /// Mock of the runtime chain extension with fixed responses
+        struct MockExtension {
+            oracle: Option<OracleReading>,
+            packet_status: PacketStatus,
+            receipt: Option<CrossShardReceipt>,
+        }
+
+        impl test::ChainExtension for MockExtension {
+            fn ext_id(&self) -> u16 {
+                0x4e43
+            }
+
+            fn call(&mut self, func_id: u16, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                match func_id {
+                    0x0001 => self.oracle.encode_to(output),
+                    0x0101 => self.packet_status.encode_to(output),
+                    0x0204 => self.receipt.encode_to(output),
+                    _ => panic!("unexpected chain extension function {func_id}"),
+                }
+                0
+            }
+        }
+
+        fn mock(oracle: Option<OracleReading>, packet_status: PacketStatus) {
+            test::register_chain_extension(MockExtension { oracle, packet_status, receipt: None });
+        }
+
+        fn mock_receipt(receipt: Option<CrossShardReceipt>) {
+            test::register_chain_extension(MockExtension { oracle: None, packet_status: PacketStatus::Unknown, receipt });
+        }
+
+        fn accounts() -> test::DefaultAccounts<NetchainEnvironment> {
+            test::default_accounts::<NetchainEnvironment>()
+        }
+
+        /// Create an escrow from alice to bob, funding the contract account
+        fn create_escrow(contract: &mut NetchainEscrow, condition: ReleaseCondition) -> EscrowId {
+            let accounts = accounts();
+            let contract_id = ink::env::account_id::<NetchainEnvironment>();
+            let balance = test::get_account_balance::<NetchainEnvironment>(contract_id).unwrap_or(0);
+            test::set_account_balance::<NetchainEnvironment>(contract_id, balance + 1_000);
+            test::set_caller::<NetchainEnvironment>(accounts.alice);
+            test::set_value_transferred::<NetchainEnvironment>(1_000);
+            let escrow_id = contract.create(accounts.bob, condition, 10).unwrap();
+            test::set_value_transferred::<NetchainEnvironment>(0);
+            escrow_id
+        }
+
+        fn btc_above(threshold: u128) -> ReleaseCondition {
+            ReleaseCondition::PriceAtLeast {
+                key: b"BTC/USD".to_vec(),
+                threshold,
+                min_confidence: 50,
+            }
+        }
+
+        #[ink::test]
+        fn parse_price_works() {
+            assert_eq!(parse_price(b"50000"), Some(50_000_000_000));
+            assert_eq!(parse_price(b"50000.25"), Some(50_000_250_000));
+            assert_eq!(parse_price(b"0.1234567"), Some(123_456));
+            assert_eq!(parse_price(b""), None);
+            assert_eq!(parse_price(b".5"), None);
+            assert_eq!(parse_price(b"-1"), None);
+            assert_eq!(parse_price(b"1.2x"), None);
+        }
+
+        #[ink::test]
+        fn create_requires_funds_and_future_deadline() {
+            let accounts = accounts();
+            let mut contract = NetchainEscrow::new();
+
+            assert_eq!(
+                contract.create(accounts.bob, btc_above(1), 10),
+                Err(ContractError::ZeroAmount)
+            );
+
+            test::set_value_transferred::<NetchainEnvironment>(100);
+            assert_eq!(
+                contract.create(accounts.bob, btc_above(1), 0),
+                Err(ContractError::DeadlineInPast)
+            );
+            assert_eq!(contract.create(accounts.bob, btc_above(1), 10), Ok(0));
+            assert_eq!(contract.escrow(0).unwrap().amount, 100);
+        }
+
+        #[ink::test]
+        fn release_on_oracle_price() {
+            let accounts = accounts();
+            let mut contract = NetchainEscrow::new();
+            let escrow_id = create_escrow(&mut contract, btc_above(60_000_000_000));
+
+            // Below threshold
+            mock(Some((b"50000.00".to_vec(), 90, 1, 1, 1_201)), PacketStatus::Unknown);
+            assert_eq!(contract.release(escrow_id), Err(ContractError::ConditionNotMet));
+
+            // Above threshold but not confident enough
+            mock(Some((b"65000.00".to_vec(), 10, 2, 2, 1_202)), PacketStatus::Unknown);
+            assert_eq!(contract.condition_met(escrow_id), Ok(false));
+
+            // Above threshold but no longer fresh
+            test::advance_block::<NetchainEnvironment>();
+            mock(Some((b"65000.00".to_vec(), 90, 0, 0, 0)), PacketStatus::Unknown);
+            assert_eq!(contract.condition_met(escrow_id), Ok(false));
+
+            mock(Some((b"65000.00".to_vec(), 90, 3, 3, 1_203)), PacketStatus::Unknown);
+            let bob_before = test::get_account_balance::<NetchainEnvironment>(accounts.bob).unwrap();
+            assert_eq!(contract.release(escrow_id), Ok(()));
+            assert_eq!(
+                test::get_account_balance::<NetchainEnvironment>(accounts.bob).unwrap(),
+                bob_before + 1_000
+            );
+            assert_eq!(contract.escrow(escrow_id).unwrap().state, EscrowState::Released);
+            assert_eq!(contract.release(escrow_id), Err(ContractError::NotActive));
+        }
+
+        #[ink::test]
+        fn release_on_ibc_acknowledgment() {
+            let mut contract = NetchainEscrow::new();
+            let condition = ReleaseCondition::PacketAcknowledged {
+                port: b"transfer".to_vec(),
+                sequence: 1,
+            };
+            let escrow_id = create_escrow(&mut contract, condition);
+
+            mock(None, PacketStatus::Pending);
+            assert_eq!(contract.release(escrow_id), Err(ContractError::ConditionNotMet));
+
+            mock(None, PacketStatus::Acknowledged);
+            assert_eq!(contract.release(escrow_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn release_on_settled_cross_shard_transfer() {
+            let accounts = accounts();
+            let mut contract = NetchainEscrow::new();
+            let tx_hash = Hash::from([7; 32]);
+            let condition = ReleaseCondition::TransferSettled { tx_hash, recipient: accounts.charlie, min_amount: 500 };
+            let escrow_id = create_escrow(&mut contract, condition);
+            let receipt = |recipient, amount| CrossShardReceipt {
+                sender: accounts.django,
+                recipient,
+                amount,
+                from_shard: 0,
+                to_shard: 1,
+                settled_at: 2,
+            };
+
+            // Not settled yet
+            mock_receipt(None);
+            assert_eq!(contract.release(escrow_id), Err(ContractError::ConditionNotMet));
+
+            // Settled, but to someone else or for less
+            mock_receipt(Some(receipt(accounts.eve, 500)));
+            assert_eq!(contract.condition_met(escrow_id), Ok(false));
+            mock_receipt(Some(receipt(accounts.charlie, 499)));
+            assert_eq!(contract.condition_met(escrow_id), Ok(false));
+
+            mock_receipt(Some(receipt(accounts.charlie, 500)));
+            assert_eq!(contract.release(escrow_id), Ok(()));
+            assert_eq!(contract.escrow(escrow_id).unwrap().state, EscrowState::Released);
+        }
+
+        #[ink::test]
+        fn refund_after_deadline_or_packet_timeout() {
+            let accounts = accounts();
+            let mut contract = NetchainEscrow::new();
+            let oracle_escrow = create_escrow(&mut contract, btc_above(60_000_000_000));
+            let packet_escrow = create_escrow(
+                &mut contract,
+                ReleaseCondition::PacketAcknowledged { port: b"transfer".to_vec(), sequence: 1 },
+            );
+
+            // Nothing is refundable before the deadline while the packet is in flight
+            mock(None, PacketStatus::Pending);
+            assert_eq!(contract.refund(oracle_escrow), Err(ContractError::RefundNotAllowed));
+            assert_eq!(contract.refund(packet_escrow), Err(ContractError::RefundNotAllowed));
+
+            // A timed-out packet can be refunded immediately
+            mock(None, PacketStatus::TimedOut);
+            let alice_before = test::get_account_balance::<NetchainEnvironment>(accounts.alice).unwrap();
+            assert_eq!(contract.refund(packet_escrow), Ok(()));
+            assert_eq!(
+                test::get_account_balance::<NetchainEnvironment>(accounts.alice).unwrap(),
+                alice_before + 1_000
+            );
+
+            // The oracle escrow becomes refundable once the deadline has passed
+            for _ in 0..11 {
+                test::advance_block::<NetchainEnvironment>();
+            }
+            assert_eq!(contract.refund(oracle_escrow), Ok(()));
+            assert_eq!(contract.escrow(oracle_escrow).unwrap().state, EscrowState::Refunded);
+        }
+    }
+}
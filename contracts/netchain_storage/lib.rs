@@ -14,6 +14,9 @@ mod netchain_storage {
     use ink::storage::Mapping;
     use ink::prelude::{string::String, vec::Vec};
 
+    /// Maximum number of keys in a single batch call
+    pub const MAX_BATCH_SIZE: u32 = 50;
+
     /// The main storage contract state
     #[ink(storage)]
     pub struct NetchainStorage {
@@ -27,6 +30,14 @@ mod netchain_storage {
         max_entries_per_user: u32,
         /// Per-user entry count tracking
         user_entries: Mapping<AccountId, u32>,
+        /// Account that created each key (charged against its entry limit)
+        key_creators: Mapping<String, AccountId>,
+        /// Per-user index of created keys, for enumeration
+        user_keys: Mapping<AccountId, Vec<String>>,
+        /// Emergency stop: rejects all writes while set
+        paused: bool,
+        /// Accounts granted the writer role by the owner
+        writers: Mapping<AccountId, ()>,
     }
 
     /// Events emitted by the contract
@@ -47,6 +58,43 @@ mod netchain_storage {
         caller: AccountId,
     }
 
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: Option<AccountId>,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct MaxEntriesPerUserUpdated {
+        max_entries_per_user: u32,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct WriterGranted {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct WriterRevoked {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
     /// Contract errors - type-safe error handling
     #[derive(Debug, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -61,6 +109,14 @@ mod netchain_storage {
         ValueTooLong,
         /// User has reached maximum entries limit
         UserLimitReached,
+        /// Contract is paused for an emergency stop
+        ContractPaused,
+        /// Caller does not hold the writer role
+        NotWriter,
+        /// Only the key's creator or the owner can remove it
+        NotKeyCreator,
+        /// Batch exceeds MAX_BATCH_SIZE keys
+        BatchTooLarge,
     }
 
     /// Result type for contract operations
@@ -76,6 +132,10 @@ mod netchain_storage {
                 total_entries: 0,
                 max_entries_per_user,
                 user_entries: Mapping::default(),
+                key_creators: Mapping::default(),
+                user_keys: Mapping::default(),
+                paused: false,
+                writers: Mapping::default(),
             }
         }
 
@@ -85,9 +145,58 @@ mod netchain_storage {
             Self::new(100) // Allow 100 entries per user by default
         }
 
-        /// Store a key-value pair
+        /// Store a key-value pair (writers only, rejected while paused)
         #[ink(message)]
         pub fn set(&mut self, key: String, value: String) -> Result<()> {
+            let caller = self.ensure_can_write()?;
+            self.set_entry(caller, key, value)
+        }
+
+        /// Store several key-value pairs at once
+        ///
+        /// All-or-nothing: if any pair is rejected, none of them are stored.
+        #[ink(message)]
+        pub fn set_many(&mut self, entries: Vec<(String, String)>) -> Result<()> {
+            if entries.len() > MAX_BATCH_SIZE as usize {
+                return Err(ContractError::BatchTooLarge);
+            }
+
+            let caller = self.ensure_can_write()?;
+            for (key, value) in entries {
+                self.set_entry(caller, key, value)?;
+            }
+
+            Ok(())
+        }
+
+        /// Remove a key (its creator or the owner only)
+        #[ink(message)]
+        pub fn remove(&mut self, key: String) -> Result<()> {
+            let caller = self.ensure_can_write()?;
+
+            let creator = self.key_creators.get(&key).ok_or(ContractError::KeyNotFound)?;
+            if caller != creator && self.owner != Some(caller) {
+                return Err(ContractError::NotKeyCreator);
+            }
+
+            self.storage.remove(&key);
+            self.key_creators.remove(&key);
+
+            // Release the creator's slot and drop the key from its index
+            let user_count = self.user_entries.get(creator).unwrap_or(0);
+            self.user_entries.insert(creator, &user_count.saturating_sub(1));
+            let mut keys = self.user_keys.get(creator).unwrap_or_default();
+            keys.retain(|k| k != &key);
+            self.user_keys.insert(creator, &keys);
+            self.total_entries = self.total_entries.saturating_sub(1);
+
+            self.env().emit_event(ValueRemoved { key, caller });
+
+            Ok(())
+        }
+
+        /// Store a key-value pair on behalf of `caller`
+        fn set_entry(&mut self, caller: AccountId, key: String, value: String) -> Result<()> {
             // Input validation - prevents vulnerabilities
             if key.len() > 128 {
                 return Err(ContractError::KeyTooLong);
@@ -96,8 +205,6 @@ mod netchain_storage {
                 return Err(ContractError::ValueTooLong);
             }
 
-            let caller = self.env().caller();
-            
             // Check if this is a new key
             let is_new_key = !self.storage.contains(&key);
             
@@ -108,9 +215,13 @@ mod netchain_storage {
                     return Err(ContractError::UserLimitReached);
                 }
                 
-                // Update counters for new entries
+                // Update counters and index for new entries
                 self.user_entries.insert(caller, &user_count.saturating_add(1));
                 self.total_entries = self.total_entries.saturating_add(1);
+                self.key_creators.insert(&key, &caller);
+                let mut keys = self.user_keys.get(caller).unwrap_or_default();
+                keys.push(key.clone());
+                self.user_keys.insert(caller, &keys);
             }
 
             // Store the value - memory safe operation
@@ -134,6 +245,31 @@ mod netchain_storage {
                 .ok_or(ContractError::KeyNotFound)
         }
 
+        /// Retrieve several values at once (`None` for missing keys)
+        #[ink(message)]
+        pub fn get_many(&self, keys: Vec<String>) -> Result<Vec<Option<String>>> {
+            if keys.len() > MAX_BATCH_SIZE as usize {
+                return Err(ContractError::BatchTooLarge);
+            }
+
+            Ok(keys.iter().map(|key| self.storage.get(key)).collect())
+        }
+
+        /// Keys created by `user`, in creation order
+        #[ink(message)]
+        pub fn keys_of(&self, user: AccountId) -> Vec<String> {
+            self.user_keys.get(user).unwrap_or_default()
+        }
+
+        /// Keys created by `user` that start with `prefix`, in creation order
+        #[ink(message)]
+        pub fn keys_of_with_prefix(&self, user: AccountId, prefix: String) -> Vec<String> {
+            self.keys_of(user)
+                .into_iter()
+                .filter(|key| key.starts_with(prefix.as_str()))
+                .collect()
+        }
+
         /// Check if a key exists in storage
         #[ink(message)]
         pub fn contains_key(&self, key: String) -> bool {
@@ -163,11 +299,132 @@ mod netchain_storage {
         pub fn owner(&self) -> Option<AccountId> {
             self.owner
         }
+
+        /// Whether the contract is paused
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Whether `account` may write (the owner always may)
+        #[ink(message)]
+        pub fn is_writer(&self, account: AccountId) -> bool {
+            self.owner == Some(account) || self.writers.contains(account)
+        }
+
+        /// Hand the contract over to `new_owner` (owner only)
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+
+            let previous_owner = self.owner;
+            self.owner = Some(new_owner);
+
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            });
+
+            Ok(())
+        }
+
+        /// Change the per-user entry limit (owner only)
+        ///
+        /// Lowering the limit does not remove existing entries; it only blocks new keys.
+        #[ink(message)]
+        pub fn set_max_entries_per_user(&mut self, max_entries_per_user: u32) -> Result<()> {
+            self.ensure_owner()?;
+
+            self.max_entries_per_user = max_entries_per_user;
+
+            self.env().emit_event(MaxEntriesPerUserUpdated { max_entries_per_user });
+
+            Ok(())
+        }
+
+        /// Stop all writes in an emergency (owner only)
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            let by = self.ensure_owner()?;
+
+            self.paused = true;
+
+            self.env().emit_event(Paused { by });
+
+            Ok(())
+        }
+
+        /// Resume writes after an emergency stop (owner only)
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            let by = self.ensure_owner()?;
+
+            self.paused = false;
+
+            self.env().emit_event(Unpaused { by });
+
+            Ok(())
+        }
+
+        /// Grant `account` the writer role (owner only)
+        #[ink(message)]
+        pub fn grant_writer(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+
+            self.writers.insert(account, &());
+
+            self.env().emit_event(WriterGranted { account });
+
+            Ok(())
+        }
+
+        /// Revoke the writer role from `account` (owner only)
+        #[ink(message)]
+        pub fn revoke_writer(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+
+            self.writers.remove(account);
+
+            self.env().emit_event(WriterRevoked { account });
+
+            Ok(())
+        }
+
+        /// Ensure the contract is not paused and the caller is a writer, returning the caller
+        fn ensure_can_write(&self) -> Result<AccountId> {
+            if self.paused {
+                return Err(ContractError::ContractPaused);
+            }
+
+            let caller = self.env().caller();
+            if !self.is_writer(caller) {
+                return Err(ContractError::NotWriter);
+            }
+            Ok(caller)
+        }
+
+        /// Ensure the caller is the owner, returning the caller
+        fn ensure_owner(&self) -> Result<AccountId> {
+            let caller = self.env().caller();
+            if self.owner != Some(caller) {
+                return Err(ContractError::OnlyOwner);
+            }
+            Ok(caller)
+        }
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use ink::env::{test, DefaultEnvironment};
+
+        fn accounts() -> test::DefaultAccounts<DefaultEnvironment> {
+            test::default_accounts::<DefaultEnvironment>()
+        }
+
+        fn set_caller(caller: AccountId) {
+            test::set_caller::<DefaultEnvironment>(caller);
+        }
 
         #[ink::test]
         fn constructor_works() {
@@ -215,5 +472,177 @@ mod netchain_storage {
                 Err(ContractError::UserLimitReached)
             );
         }
+
+        #[ink::test]
+        fn ownership_transfer_works() {
+            let accounts = accounts();
+            let mut contract = NetchainStorage::default();
+            assert_eq!(contract.owner(), Some(accounts.alice));
+
+            // Non-owner cannot take over
+            set_caller(accounts.bob);
+            assert_eq!(contract.transfer_ownership(accounts.bob), Err(ContractError::OnlyOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(contract.owner(), Some(accounts.bob));
+
+            // Previous owner lost admin rights
+            assert_eq!(contract.set_max_entries_per_user(1), Err(ContractError::OnlyOwner));
+            assert_eq!(test::recorded_events().count(), 1);
+        }
+
+        #[ink::test]
+        fn set_max_entries_per_user_owner_only() {
+            let accounts = accounts();
+            let mut contract = NetchainStorage::new(1);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_max_entries_per_user(5), Err(ContractError::OnlyOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.set("key1".to_string(), "value1".to_string()), Ok(()));
+            assert_eq!(
+                contract.set("key2".to_string(), "value2".to_string()),
+                Err(ContractError::UserLimitReached)
+            );
+
+            assert_eq!(contract.set_max_entries_per_user(2), Ok(()));
+            assert_eq!(contract.max_entries_per_user(), 2);
+            assert_eq!(contract.set("key2".to_string(), "value2".to_string()), Ok(()));
+        }
+
+        #[ink::test]
+        fn pause_blocks_writes() {
+            let accounts = accounts();
+            let mut contract = NetchainStorage::default();
+            assert_eq!(contract.set("key".to_string(), "value".to_string()), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.pause(), Err(ContractError::OnlyOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.pause(), Ok(()));
+            assert!(contract.is_paused());
+            assert_eq!(
+                contract.set("other".to_string(), "value".to_string()),
+                Err(ContractError::ContractPaused)
+            );
+
+            // Reads keep working during an emergency stop
+            assert_eq!(contract.get("key".to_string()), Ok("value".to_string()));
+
+            assert_eq!(contract.unpause(), Ok(()));
+            assert!(!contract.is_paused());
+            assert_eq!(contract.set("other".to_string(), "value".to_string()), Ok(()));
+        }
+
+        #[ink::test]
+        fn writer_role_required_for_writes() {
+            let accounts = accounts();
+            let mut contract = NetchainStorage::default();
+
+            set_caller(accounts.bob);
+            assert!(!contract.is_writer(accounts.bob));
+            assert_eq!(
+                contract.set("key".to_string(), "value".to_string()),
+                Err(ContractError::NotWriter)
+            );
+            assert_eq!(contract.grant_writer(accounts.bob), Err(ContractError::OnlyOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.grant_writer(accounts.bob), Ok(()));
+
+            set_caller(accounts.bob);
+            assert!(contract.is_writer(accounts.bob));
+            assert_eq!(contract.set("key".to_string(), "value".to_string()), Ok(()));
+            assert_eq!(contract.user_entry_count(accounts.bob), 1);
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.revoke_writer(accounts.bob), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.set("key2".to_string(), "value".to_string()),
+                Err(ContractError::NotWriter)
+            );
+        }
+
+        #[ink::test]
+        fn set_many_and_get_many_work() {
+            let mut contract = NetchainStorage::default();
+
+            assert_eq!(
+                contract.set_many(vec![
+                    ("a".to_string(), "1".to_string()),
+                    ("b".to_string(), "2".to_string()),
+                ]),
+                Ok(())
+            );
+            assert_eq!(contract.total_entries(), 2);
+
+            assert_eq!(
+                contract.get_many(vec!["a".to_string(), "missing".to_string(), "b".to_string()]),
+                Ok(vec![Some("1".to_string()), None, Some("2".to_string())])
+            );
+
+            let oversized = (0..=MAX_BATCH_SIZE).map(|i| (i.to_string(), String::new())).collect();
+            assert_eq!(contract.set_many(oversized), Err(ContractError::BatchTooLarge));
+        }
+
+        #[ink::test]
+        fn remove_updates_counters_and_index() {
+            let accounts = accounts();
+            let mut contract = NetchainStorage::default();
+            assert_eq!(contract.grant_writer(accounts.bob), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set("bob/1".to_string(), "x".to_string()), Ok(()));
+            assert_eq!(contract.set("bob/2".to_string(), "y".to_string()), Ok(()));
+
+            // Another writer cannot remove bob's key
+            set_caller(accounts.alice);
+            assert_eq!(contract.grant_writer(accounts.charlie), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(contract.remove("bob/1".to_string()), Err(ContractError::NotKeyCreator));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.remove("bob/1".to_string()), Ok(()));
+            assert_eq!(contract.remove("bob/1".to_string()), Err(ContractError::KeyNotFound));
+            assert!(!contract.contains_key("bob/1".to_string()));
+            assert_eq!(contract.user_entry_count(accounts.bob), 1);
+            assert_eq!(contract.total_entries(), 1);
+            assert_eq!(contract.keys_of(accounts.bob), vec!["bob/2".to_string()]);
+
+            // The owner can remove any key
+            set_caller(accounts.alice);
+            assert_eq!(contract.remove("bob/2".to_string()), Ok(()));
+            assert!(contract.keys_of(accounts.bob).is_empty());
+        }
+
+        #[ink::test]
+        fn keys_enumerated_per_user_and_prefix() {
+            let accounts = accounts();
+            let mut contract = NetchainStorage::default();
+
+            assert_eq!(
+                contract.set_many(vec![
+                    ("profile/name".to_string(), "alice".to_string()),
+                    ("settings/theme".to_string(), "dark".to_string()),
+                    ("profile/bio".to_string(), "hi".to_string()),
+                ]),
+                Ok(())
+            );
+
+            // Overwriting does not duplicate the key in the index
+            assert_eq!(contract.set("profile/name".to_string(), "alice2".to_string()), Ok(()));
+
+            assert_eq!(contract.keys_of(accounts.alice).len(), 3);
+            assert_eq!(
+                contract.keys_of_with_prefix(accounts.alice, "profile/".to_string()),
+                vec!["profile/name".to_string(), "profile/bio".to_string()]
+            );
+            assert!(contract.keys_of(accounts.bob).is_empty());
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,617 @@
+//! # Netchain Shard-Aware PSP22 Token
+//!
+//! A PSP22 fungible token template that can move balances between Netchain shards.
+//!
+//! Transfers to a recipient on the token's own shard are ordinary PSP22 transfers. Transfers
+//! to another shard follow the asynchronous settlement pattern:
+//! 1. `transfer_cross_shard` locks the amount (moved to `in_flight`) and forwards a
+//!    `receive_cross_shard` call to the destination shard through the chain extension.
+//! 2. The destination shard executes the call, crediting the recipient, and publishes a
+//!    receipt.
+//! 3. Anyone calls `settle_cross_shard` with the call id: a successful receipt finalizes the
+//!    transfer, a failed one refunds the sender.
+//!
+//! At all times `total_supply == sum(balances) + in_flight`.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+use ink::env::{DefaultEnvironment, Environment};
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+
+/// Netchain chain extension (see `runtime/src/chain_extension.rs`), sharding functions
+#[ink::chain_extension(extension = 0x4e43)]
+pub trait NetchainExtension {
+    type ErrorCode = NetchainExtensionError;
+
+    /// Shard `account` is assigned to
+    #[ink(function = 0x0201, handle_status = false)]
+    fn account_shard(account: AccountId) -> u8;
+
+    /// Forward a call to `dest` on `to_shard` on behalf of this contract
+    #[ink(function = 0x0202, handle_status = false)]
+    fn forward_call(
+        to_shard: u8,
+        dest: AccountId,
+        data: Vec<u8>,
+        ref_time: u64,
+        proof_size: u64,
+        value: u128,
+    ) -> Option<u64>;
+
+    /// Receipt of an executed cross-shard call: (success, output)
+    #[ink(function = 0x0203, handle_status = false)]
+    fn call_receipt(call_id: u64) -> Option<(bool, Vec<u8>)>;
+}
+
+/// Errors reported by the Netchain chain extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum NetchainExtensionError {
+    /// The runtime rejected the call
+    Failed,
+}
+
+impl ink::env::chain_extension::FromStatusCode for NetchainExtensionError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            _ => Err(Self::Failed),
+        }
+    }
+}
+
+/// Contract environment with the Netchain chain extension enabled
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(TypeInfo)]
+pub enum NetchainEnvironment {}
+
+impl Environment for NetchainEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+    type ChainExtension = NetchainExtension;
+}
+
+#[ink::contract(env = crate::NetchainEnvironment)]
+mod netchain_token {
+    use ink::prelude::{string::String, vec::Vec};
+    use ink::scale::Encode;
+    use ink::storage::Mapping;
+
+    /// Selector of `receive_cross_shard`, used to build the forwarded call
+    pub const RECEIVE_SELECTOR: [u8; 4] = ink::selector_bytes!("receive_cross_shard");
+    /// Gas limit of the settlement call on the destination shard: (ref_time, proof_size)
+    pub const SETTLEMENT_GAS_LIMIT: (u64, u64) = (5_000_000_000, 256 * 1024);
+
+    /// Cross-shard transfer awaiting settlement
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PendingTransfer {
+        /// Sender whose balance is locked
+        pub from: AccountId,
+        /// Recipient on the destination shard
+        pub to: AccountId,
+        /// Locked amount
+        pub value: Balance,
+        /// Destination shard
+        pub to_shard: u8,
+    }
+
+    /// The token state
+    #[ink(storage)]
+    pub struct NetchainToken {
+        /// Total token supply (including amounts in flight between shards)
+        total_supply: Balance,
+        /// Balance of each account
+        balances: Mapping<AccountId, Balance>,
+        /// Allowance granted by an owner to a spender
+        allowances: Mapping<(AccountId, AccountId), Balance>,
+        /// Token name
+        name: Option<String>,
+        /// Token symbol
+        symbol: Option<String>,
+        /// Token decimals
+        decimals: u8,
+        /// Cross-shard transfers by forwarded call id
+        pending: Mapping<u64, PendingTransfer>,
+        /// Total amount locked in unsettled cross-shard transfers
+        in_flight: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CrossShardTransferInitiated {
+        #[ink(topic)]
+        call_id: u64,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        to_shard: u8,
+    }
+
+    #[ink(event)]
+    pub struct CrossShardTransferSettled {
+        #[ink(topic)]
+        call_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct CrossShardTransferReverted {
+        #[ink(topic)]
+        call_id: u64,
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance,
+    }
+
+    /// PSP22 errors
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum PSP22Error {
+        /// Custom error (used for cross-shard failures)
+        Custom(String),
+        /// Sender balance is too low
+        InsufficientBalance,
+        /// Spender allowance is too low
+        InsufficientAllowance,
+        /// Recipient rejected the transfer
+        SafeTransferCheckFailed(String),
+    }
+
+    /// Result type for token operations
+    pub type Result<T> = core::result::Result<T, PSP22Error>;
+
+    impl NetchainToken {
+        /// Constructor: mint `total_supply` to the caller
+        #[ink(constructor)]
+        pub fn new(
+            total_supply: Balance,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimals: u8,
+        ) -> Self {
+            let caller = Self::env().caller();
+            let mut balances = Mapping::default();
+            balances.insert(caller, &total_supply);
+
+            Self::env().emit_event(Transfer {
+                from: None,
+                to: Some(caller),
+                value: total_supply,
+            });
+
+            Self {
+                total_supply,
+                balances,
+                allowances: Mapping::default(),
+                name,
+                symbol,
+                decimals,
+                pending: Mapping::default(),
+                in_flight: 0,
+            }
+        }
+
+        /// PSP22::total_supply
+        #[ink(message, selector = 0x162df8c2)]
+        pub fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        /// PSP22::balance_of
+        #[ink(message, selector = 0x6568382f)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        /// PSP22::allowance
+        #[ink(message, selector = 0x4d47d921)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get((owner, spender)).unwrap_or(0)
+        }
+
+        /// PSP22::transfer
+        #[ink(message, selector = 0xdb20f9f5)]
+        pub fn transfer(&mut self, to: AccountId, value: Balance, _data: Vec<u8>) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_from_to(from, to, value)
+        }
+
+        /// PSP22::transfer_from
+        #[ink(message, selector = 0x54b3c76e)]
+        pub fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<()> {
+            let spender = self.env().caller();
+            if spender != from {
+                let allowance = self.allowance(from, spender);
+                if allowance < value {
+                    return Err(PSP22Error::InsufficientAllowance);
+                }
+                self.set_allowance(from, spender, allowance - value);
+            }
+            self.transfer_from_to(from, to, value)
+        }
+
+        /// PSP22::approve
+        #[ink(message, selector = 0xb20f1bbd)]
+        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            self.set_allowance(owner, spender, value);
+            Ok(())
+        }
+
+        /// PSP22::increase_allowance
+        #[ink(message, selector = 0x96d6b57a)]
+        pub fn increase_allowance(&mut self, spender: AccountId, delta_value: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let allowance = self.allowance(owner, spender);
+            self.set_allowance(owner, spender, allowance.saturating_add(delta_value));
+            Ok(())
+        }
+
+        /// PSP22::decrease_allowance
+        #[ink(message, selector = 0xfecb57d5)]
+        pub fn decrease_allowance(&mut self, spender: AccountId, delta_value: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let allowance = self.allowance(owner, spender);
+            if allowance < delta_value {
+                return Err(PSP22Error::InsufficientAllowance);
+            }
+            self.set_allowance(owner, spender, allowance - delta_value);
+            Ok(())
+        }
+
+        /// PSP22Metadata::token_name
+        #[ink(message, selector = 0x3d261bd4)]
+        pub fn token_name(&self) -> Option<String> {
+            self.name.clone()
+        }
+
+        /// PSP22Metadata::token_symbol
+        #[ink(message, selector = 0x34205be5)]
+        pub fn token_symbol(&self) -> Option<String> {
+            self.symbol.clone()
+        }
+
+        /// PSP22Metadata::token_decimals
+        #[ink(message, selector = 0x7271b782)]
+        pub fn token_decimals(&self) -> u8 {
+            self.decimals
+        }
+
+        /// Transfer to `to` wherever it lives
+        ///
+        /// Same-shard recipients are paid immediately and `None` is returned. Otherwise the
+        /// amount is locked and the id of the forwarded call is returned; pass it to
+        /// `settle_cross_shard` once the destination shard has executed it.
+        #[ink(message)]
+        pub fn transfer_cross_shard(&mut self, to: AccountId, value: Balance) -> Result<Option<u64>> {
+            let from = self.env().caller();
+            let this = self.env().account_id();
+            let home_shard = self.env().extension().account_shard(this);
+            let to_shard = self.env().extension().account_shard(to);
+
+            if to_shard == home_shard {
+                self.transfer_from_to(from, to, value)?;
+                return Ok(None);
+            }
+
+            // Lock the amount until the destination shard settles
+            let balance = self.balance_of(from);
+            if balance < value {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            self.balances.insert(from, &(balance - value));
+            self.in_flight = self.in_flight.saturating_add(value);
+
+            let mut data = RECEIVE_SELECTOR.to_vec();
+            (from, to, value).encode_to(&mut data);
+            let (ref_time, proof_size) = SETTLEMENT_GAS_LIMIT;
+            let call_id = self
+                .env()
+                .extension()
+                .forward_call(to_shard, this, data, ref_time, proof_size, 0)
+                .ok_or_else(|| PSP22Error::Custom(String::from("CrossShardForwardFailed")))?;
+
+            self.pending.insert(call_id, &PendingTransfer { from, to, value, to_shard });
+
+            self.env().emit_event(CrossShardTransferInitiated {
+                call_id,
+                from,
+                to,
+                value,
+                to_shard,
+            });
+
+            Ok(Some(call_id))
+        }
+
+        /// Credit a cross-shard transfer on the destination shard
+        ///
+        /// Only callable by this contract itself, through a forwarded cross-shard call.
+        #[ink(message)]
+        pub fn receive_cross_shard(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            if self.env().caller() != self.env().account_id() {
+                return Err(PSP22Error::Custom(String::from("OnlySelf")));
+            }
+
+            self.in_flight = self.in_flight.saturating_sub(value);
+            let balance = self.balance_of(to);
+            self.balances.insert(to, &balance.saturating_add(value));
+
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value,
+            });
+
+            Ok(())
+        }
+
+        /// Finalize or revert a cross-shard transfer from its receipt (callable by anyone)
+        #[ink(message)]
+        pub fn settle_cross_shard(&mut self, call_id: u64) -> Result<()> {
+            let transfer = self
+                .pending
+                .get(call_id)
+                .ok_or_else(|| PSP22Error::Custom(String::from("UnknownTransfer")))?;
+            let (success, _output) = self
+                .env()
+                .extension()
+                .call_receipt(call_id)
+                .ok_or_else(|| PSP22Error::Custom(String::from("NotExecuted")))?;
+
+            self.pending.remove(call_id);
+
+            if success {
+                self.env().emit_event(CrossShardTransferSettled { call_id });
+            } else {
+                // The recipient was never credited: return the locked amount
+                self.in_flight = self.in_flight.saturating_sub(transfer.value);
+                let balance = self.balance_of(transfer.from);
+                self.balances.insert(transfer.from, &balance.saturating_add(transfer.value));
+
+                self.env().emit_event(CrossShardTransferReverted {
+                    call_id,
+                    from: transfer.from,
+                    value: transfer.value,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Cross-shard transfer awaiting settlement
+        #[ink(message)]
+        pub fn pending_transfer(&self, call_id: u64) -> Option<PendingTransfer> {
+            self.pending.get(call_id)
+        }
+
+        /// Total amount locked in unsettled cross-shard transfers
+        #[ink(message)]
+        pub fn in_flight(&self) -> Balance {
+            self.in_flight
+        }
+
+        /// Move `value` from `from` to `to` on this shard
+        fn transfer_from_to(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            if from == to || value == 0 {
+                return Ok(());
+            }
+
+            let from_balance = self.balance_of(from);
+            if from_balance < value {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            self.balances.insert(from, &(from_balance - value));
+            let to_balance = self.balance_of(to);
+            self.balances.insert(to, &to_balance.saturating_add(value));
+
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value,
+            });
+
+            Ok(())
+        }
+
+        /// Set an allowance and emit `Approval`
+        fn set_allowance(&mut self, owner: AccountId, spender: AccountId, amount: Balance) {
+            self.allowances.insert((owner, spender), &amount);
+            self.env().emit_event(Approval { owner, spender, amount });
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::NetchainEnvironment;
+        use ink::env::test;
+        use ink::scale::Decode;
+        use std::{cell::RefCell, rc::Rc};
+
+        /// Mock of the runtime chain extension: `remote` accounts live on shard 1,
+        /// everything else on shard 0
+        struct MockExtension {
+            remote: Vec<AccountId>,
+            receipt: Option<(bool, Vec<u8>)>,
+            forwarded: Rc<RefCell<Vec<Vec<u8>>>>,
+        }
+
+        impl test::ChainExtension for MockExtension {
+            fn ext_id(&self) -> u16 {
+                0x4e43
+            }
+
+            fn call(&mut self, func_id: u16, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
+                match func_id {
+                    0x0201 => {
+                        let account = AccountId::decode(&mut input).unwrap();
+                        let shard: u8 = if self.remote.contains(&account) { 1 } else { 0 };
+                        shard.encode_to(output);
+                    }
+                    0x0202 => {
+                        let (_, _, data, _, _, _): (u8, AccountId, Vec<u8>, u64, u64, Balance) =
+                            Decode::decode(&mut input).unwrap();
+                        let mut forwarded = self.forwarded.borrow_mut();
+                        forwarded.push(data);
+                        Some(forwarded.len() as u64 - 1).encode_to(output);
+                    }
+                    0x0203 => self.receipt.encode_to(output),
+                    _ => panic!("unexpected chain extension function {func_id}"),
+                }
+                0
+            }
+        }
+
+        fn mock(remote: Vec<AccountId>, receipt: Option<(bool, Vec<u8>)>) -> Rc<RefCell<Vec<Vec<u8>>>> {
+            let forwarded = Rc::new(RefCell::new(Vec::new()));
+            test::register_chain_extension(MockExtension {
+                remote,
+                receipt,
+                forwarded: forwarded.clone(),
+            });
+            forwarded
+        }
+
+        fn accounts() -> test::DefaultAccounts<NetchainEnvironment> {
+            test::default_accounts::<NetchainEnvironment>()
+        }
+
+        fn token() -> NetchainToken {
+            NetchainToken::new(1_000, Some(String::from("Netchain Token")), Some(String::from("NTK")), 12)
+        }
+
+        #[ink::test]
+        fn psp22_transfer_and_allowance_work() {
+            let accounts = accounts();
+            let mut token = token();
+            assert_eq!(token.total_supply(), 1_000);
+            assert_eq!(token.balance_of(accounts.alice), 1_000);
+            assert_eq!(token.token_symbol(), Some(String::from("NTK")));
+
+            assert_eq!(token.transfer(accounts.bob, 100, Vec::new()), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(
+                token.transfer(accounts.bob, 1_000, Vec::new()),
+                Err(PSP22Error::InsufficientBalance)
+            );
+
+            assert_eq!(token.approve(accounts.charlie, 50), Ok(()));
+            test::set_caller::<NetchainEnvironment>(accounts.charlie);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 60, Vec::new()),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert_eq!(token.transfer_from(accounts.alice, accounts.charlie, 50, Vec::new()), Ok(()));
+            assert_eq!(token.allowance(accounts.alice, accounts.charlie), 0);
+            assert_eq!(token.balance_of(accounts.alice), 850);
+        }
+
+        #[ink::test]
+        fn same_shard_transfer_is_immediate() {
+            let accounts = accounts();
+            let mut token = token();
+            let forwarded = mock(Vec::new(), None);
+
+            assert_eq!(token.transfer_cross_shard(accounts.bob, 100), Ok(None));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert!(forwarded.borrow().is_empty());
+        }
+
+        #[ink::test]
+        fn cross_shard_transfer_locks_and_forwards() {
+            let accounts = accounts();
+            let mut token = token();
+            let forwarded = mock(vec![accounts.bob], None);
+
+            assert_eq!(token.transfer_cross_shard(accounts.bob, 100), Ok(Some(0)));
+            assert_eq!(token.balance_of(accounts.alice), 900);
+            assert_eq!(token.balance_of(accounts.bob), 0);
+            assert_eq!(token.in_flight(), 100);
+            assert_eq!(token.pending_transfer(0).unwrap().to_shard, 1);
+
+            // The forwarded call targets receive_cross_shard with the transfer details
+            let mut expected = RECEIVE_SELECTOR.to_vec();
+            (accounts.alice, accounts.bob, 100 as Balance).encode_to(&mut expected);
+            assert_eq!(forwarded.borrow()[0], expected);
+
+            // Not settleable before the destination shard executed the call
+            assert_eq!(
+                token.settle_cross_shard(0),
+                Err(PSP22Error::Custom(String::from("NotExecuted")))
+            );
+        }
+
+        #[ink::test]
+        fn receive_only_from_self_and_settle_success() {
+            let accounts = accounts();
+            let mut token = token();
+            mock(vec![accounts.bob], Some((true, Vec::new())));
+            assert_eq!(token.transfer_cross_shard(accounts.bob, 100), Ok(Some(0)));
+
+            assert_eq!(
+                token.receive_cross_shard(accounts.alice, accounts.alice, 100),
+                Err(PSP22Error::Custom(String::from("OnlySelf")))
+            );
+
+            // Destination shard executes the forwarded call as the contract itself
+            test::set_caller::<NetchainEnvironment>(test::callee::<NetchainEnvironment>());
+            assert_eq!(token.receive_cross_shard(accounts.alice, accounts.bob, 100), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(token.in_flight(), 0);
+
+            assert_eq!(token.settle_cross_shard(0), Ok(()));
+            assert_eq!(token.pending_transfer(0), None);
+            assert_eq!(token.balance_of(accounts.alice) + token.balance_of(accounts.bob), 1_000);
+        }
+
+        #[ink::test]
+        fn failed_settlement_refunds_sender() {
+            let accounts = accounts();
+            let mut token = token();
+            mock(vec![accounts.bob], Some((false, Vec::new())));
+            assert_eq!(token.transfer_cross_shard(accounts.bob, 100), Ok(Some(0)));
+
+            assert_eq!(token.settle_cross_shard(0), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 1_000);
+            assert_eq!(token.in_flight(), 0);
+            assert_eq!(
+                token.settle_cross_shard(0),
+                Err(PSP22Error::Custom(String::from("UnknownTransfer")))
+            );
+        }
+    }
+}
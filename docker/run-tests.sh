@@ -64,7 +64,7 @@ cargo test --package tests --test fee_benchmarks --verbose 2>&1 | tee /results/f
 echo ""
 echo "🌐 Running Integration Tests"
 echo "============================"
-cargo test --package tests --test comprehensive_integration_tests --verbose 2>&1 | tee /results/integration_tests.log
+NETCHAIN_E2E_WS="ws://netchain-alice:9944" cargo test -p netchain-e2e --verbose -- --ignored --test-threads 1 2>&1 | tee /results/integration_tests.log
 cargo test --package tests --test interoperability_test --verbose 2>&1 | tee /results/interoperability_tests.log
 
 echo ""
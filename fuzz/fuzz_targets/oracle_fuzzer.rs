@@ -6,6 +6,7 @@
 //! - Data source manipulation
 //! - Price feed injection attacks
 //! - Confidence score manipulation
+//! - Source tier bypass
 //! - Aggregation algorithm testing
 //! - Timestamp manipulation
 
@@ -39,6 +40,14 @@ pub struct FuzzDataSource {
     pub endpoint: Vec<u8>,
     pub reliability: u8,
     pub active: bool,
+    pub tier: FuzzSourceTier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Arbitrary)]
+pub enum FuzzSourceTier {
+    Open,
+    Verified,
+    Critical,
 }
 
 #[derive(Debug, Clone, Arbitrary)]
@@ -151,9 +160,10 @@ fn fuzz_provide_data(state: &mut MockOracleState, data: FuzzOracleData) {
         return;
     }
     
-    if data.source_id.is_empty() || !state.data_sources.contains_key(&data.source_id) {
-        return; // Invalid source
-    }
+    let tier = match state.data_sources.get(&data.source_id) {
+        Some(source) if !data.source_id.is_empty() => source.tier,
+        _ => return, // Invalid source
+    };
     
     if data.value.len() > 1024 {
         return; // Data too large
@@ -163,8 +173,8 @@ fn fuzz_provide_data(state: &mut MockOracleState, data: FuzzOracleData) {
         return; // Invalid confidence
     }
     
-    // Check if provider is trusted for high confidence data
-    if data.confidence > 80 && !state.trusted_providers.contains(&data.provider) {
+    // Only open sources take untrusted providers
+    if tier != FuzzSourceTier::Open && !state.trusted_providers.contains(&data.provider) {
         return; // Provider not trusted
     }
     
@@ -6,6 +6,7 @@
 //! - Combined cross-chain + oracle scenarios
 
 use subxt::{OnlineClient, PolkadotConfig, tx::TxPayload};
+use subxt::ext::codec::Encode;
 use subxt::ext::sp_core::{sr25519::Pair as Sr25519Pair, Pair};
 use tokio::time::{sleep, Duration};
 use serde_json::json;
@@ -15,11 +16,17 @@ use serde_json::json;
 pub mod netchain_runtime {}
 
 use netchain_runtime::runtime_types::{
+    pallet_ibc_core::light_client::{mock::MockHeader, ClientType},
     pallet_ibc_core::pallet::Call as IbcCall,
     pallet_oracle::pallet::Call as OracleCall,
     netchain_runtime::RuntimeCall,
 };
 
+/// Identifier `n` this chain assigns with `prefix`: the prefix and the big-endian `u32`
+fn local_id(prefix: &[u8], n: u32) -> Vec<u8> {
+    [prefix, &n.to_be_bytes()].concat()
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🌐 Netchain Cross-Chain & Oracle Interoperability Demo");
@@ -77,13 +84,16 @@ async fn demo_ibc_setup(
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("📡 Creating IBC client for Cosmos testnet...");
     
-    // Create IBC client
-    let create_client_tx = api.tx().ibc_core().create_client(
-        b"cosmos-testnet-4".to_vec(),
-        1000, // initial_height
-        67,   // trust_level (2/3)
-        1800, // unbonding_period (30 minutes)
-    )?;
+    // Create IBC client; mock clients are created and updated by root, through Alice's sudo
+    // key on the development chain
+    let create_client_call = RuntimeCall::IbcCore(IbcCall::create_client {
+        client_type: ClientType::Mock, // trusts the demo's own updates
+        chain_id: b"cosmos-testnet-4".to_vec(),
+        initial_height: 1000,
+        trust_level: 67,        // 2/3
+        unbonding_period: 1800, // 30 minutes
+    });
+    let create_client_tx = api.tx().sudo().sudo(create_client_call)?;
 
     let events = create_client_tx.sign_and_submit_then_watch(&alice).await?;
     println!("✅ IBC Client created! Fee: ~$0.0001");
@@ -92,10 +102,15 @@ async fn demo_ibc_setup(
     sleep(Duration::from_secs(2)).await;
     println!("🔄 Updating IBC client height...");
     
-    let update_client_tx = api.tx().ibc_core().update_client(
-        b"client-0".to_vec(),
-        1050, // new_height
-    )?;
+    let update_client_call = RuntimeCall::IbcCore(IbcCall::update_client {
+        client_id: local_id(b"client-", 0),
+        header: MockHeader {
+            height: 1050,
+            root: [0u8; 32].into(), // commitment root reported by the relayer
+        }
+        .encode(),
+    });
+    let update_client_tx = api.tx().sudo().sudo(update_client_call)?;
 
     let events = update_client_tx.sign_and_submit_then_watch(&alice).await?;
     println!("✅ IBC Client updated to height 1050");
@@ -104,7 +119,7 @@ async fn demo_ibc_setup(
     println!("🔗 Opening IBC connection...");
     
     let connection_tx = api.tx().ibc_core().connection_open_init(
-        b"client-0".to_vec(),
+        local_id(b"client-", 0),
         b"counterparty-client-0".to_vec(),
         b"1.0".to_vec(), // version
     )?;
@@ -115,9 +130,18 @@ async fn demo_ibc_setup(
     // Initialize channel (simulated as open for demo)
     println!("📺 Opening IBC channel for token transfers...");
     
+    // Channels can only be opened by the owner of their port, which governance binds; Alice
+    // holds the sudo key of the development chain
+    let bind_call = RuntimeCall::IbcCore(IbcCall::bind_port {
+        port_id: b"transfer".to_vec(),
+        owner: subxt::utils::AccountId32::from(alice.public().0),
+    });
+    let bind_tx = api.tx().sudo().sudo(bind_call)?;
+    bind_tx.sign_and_submit_then_watch(&alice).await?;
+
     let channel_tx = api.tx().ibc_core().channel_open_init(
         b"transfer".to_vec(),     // port_id
-        b"connection-0".to_vec(), // connection_id  
+        local_id(b"connection-", 0), // connection_id  
         b"transfer".to_vec(),     // counterparty_port_id
         b"ics20-1".to_vec(),      // version
     )?;
@@ -218,7 +242,7 @@ async fn demo_cross_chain_oracle(&api: &OnlineClient<PolkadotConfig>, alice: &Sr
 
     let send_packet_tx = api.tx().ibc_core().send_packet(
         b"transfer".to_vec(),           // source_port
-        b"channel-0".to_vec(),          // source_channel
+        local_id(b"channel-", 0),       // source_channel
         b"oracle".to_vec(),             // destination_port
         b"channel-1".to_vec(),          // destination_channel
         packet_data.to_string().into_bytes(),
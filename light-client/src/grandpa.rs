@@ -0,0 +1,189 @@
+//! GRANDPA finality proofs, as returned by the `grandpa_proveFinality` RPC
+//!
+//! Types mirror the SCALE encoding of `sp_runtime::generic::Header<u32, BlakeTwo256>`,
+//! `sc_consensus_grandpa::GrandpaJustification` and `sc_consensus_grandpa::FinalityProof`, so
+//! proofs decode without the runtime or host functions.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use codec::{Decode, Encode};
+use sp_core::{ed25519, hashing::blake2_256, Pair as _, H256};
+
+use crate::Error;
+
+/// Engine id of GRANDPA consensus digests
+pub const GRANDPA_ENGINE_ID: [u8; 4] = *b"FRNK";
+
+/// Block number of the Netchain runtime
+pub type BlockNumber = u32;
+
+/// GRANDPA authorities and their voting weights
+pub type AuthorityList = Vec<(ed25519::Public, u64)>;
+
+/// Digest item of a block header
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum DigestItem {
+    #[codec(index = 0)]
+    Other(Vec<u8>),
+    #[codec(index = 4)]
+    Consensus([u8; 4], Vec<u8>),
+    #[codec(index = 5)]
+    Seal([u8; 4], Vec<u8>),
+    #[codec(index = 6)]
+    PreRuntime([u8; 4], Vec<u8>),
+    #[codec(index = 8)]
+    RuntimeEnvironmentUpdated,
+}
+
+/// Block header
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Header {
+    pub parent_hash: H256,
+    #[codec(compact)]
+    pub number: BlockNumber,
+    pub state_root: H256,
+    pub extrinsics_root: H256,
+    pub digest: Vec<DigestItem>,
+}
+
+impl Header {
+    /// Block hash
+    pub fn hash(&self) -> H256 {
+        H256(blake2_256(&self.encode()))
+    }
+
+    /// Authority set change this block schedules, if any
+    pub fn scheduled_change(&self) -> Option<ScheduledChange> {
+        self.digest.iter().find_map(|item| match item {
+            DigestItem::Consensus(GRANDPA_ENGINE_ID, log) => match ConsensusLog::decode(&mut &log[..]) {
+                Ok(ConsensusLog::ScheduledChange(change)) => Some(change),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+}
+
+/// Authority set change enacted `delay` blocks after the block scheduling it is finalized
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct ScheduledChange {
+    pub next_authorities: AuthorityList,
+    pub delay: BlockNumber,
+}
+
+/// GRANDPA consensus digest; forced changes, disabling and pauses fail to decode
+#[derive(Decode)]
+enum ConsensusLog {
+    #[codec(index = 1)]
+    ScheduledChange(ScheduledChange),
+}
+
+/// Vote for a block and, implicitly, its ancestors
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Precommit {
+    pub target_hash: H256,
+    pub target_number: BlockNumber,
+}
+
+/// Precommit signed by an authority
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct SignedPrecommit {
+    pub precommit: Precommit,
+    pub signature: ed25519::Signature,
+    pub id: ed25519::Public,
+}
+
+/// Precommits finalizing a block
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Commit {
+    pub target_hash: H256,
+    pub target_number: BlockNumber,
+    pub precommits: Vec<SignedPrecommit>,
+}
+
+/// Commit of a round, with the headers linking precommits for descendants to its target
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct Justification {
+    pub round: u64,
+    pub commit: Commit,
+    pub votes_ancestries: Vec<Header>,
+}
+
+/// Justification of `block`, with the headers between the requested block and `block`
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct FinalityProof {
+    pub block: H256,
+    pub justification: Vec<u8>,
+    pub unknown_headers: Vec<Header>,
+}
+
+/// Message an authority signs for `precommit` in `round` of set `set_id`
+pub fn precommit_payload(precommit: &Precommit, round: u64, set_id: u64) -> Vec<u8> {
+    // `finality_grandpa::Message::Precommit`
+    (1u8, precommit, round, set_id).encode()
+}
+
+/// Smallest weight of votes that finalizes a block: more than two thirds of `authorities`,
+/// and never nothing
+pub fn threshold(authorities: &AuthorityList) -> u64 {
+    let total = authorities.iter().map(|(_, weight)| weight).sum::<u64>();
+    (total - total.saturating_sub(1) / 3).max(1)
+}
+
+impl Justification {
+    /// Check that the commit finalizes block `number` with `hash` and is signed by authorities
+    /// of set `set_id` holding at least [`threshold`] of the weight
+    ///
+    /// An authority signing several precommits counts once.
+    pub fn verify(
+        &self,
+        hash: H256,
+        number: BlockNumber,
+        set_id: u64,
+        authorities: &AuthorityList,
+    ) -> Result<(), Error> {
+        if (self.commit.target_hash, self.commit.target_number) != (hash, number) {
+            return Err(Error::WrongTarget)
+        }
+
+        let ancestry: BTreeMap<H256, &Header> =
+            self.votes_ancestries.iter().map(|header| (header.hash(), header)).collect();
+        let mut voters = BTreeSet::new();
+        let mut weight = 0u64;
+        for signed in &self.commit.precommits {
+            let (_, voter_weight) =
+                authorities.iter().find(|(id, _)| *id == signed.id).ok_or(Error::UnknownAuthority)?;
+            let payload = precommit_payload(&signed.precommit, self.round, set_id);
+            if !ed25519::Pair::verify(&signed.signature, payload, &signed.id) {
+                return Err(Error::BadSignature)
+            }
+            if !descends_from(&ancestry, &signed.precommit, hash, number) {
+                return Err(Error::UnrelatedPrecommit)
+            }
+            if voters.insert(signed.id) {
+                weight = weight.saturating_add(*voter_weight);
+            }
+        }
+
+        if weight < threshold(authorities) {
+            return Err(Error::NotEnoughVotes)
+        }
+        Ok(())
+    }
+}
+
+/// Whether `precommit` is for block `number` with `hash` or, through `ancestry`, a descendant
+fn descends_from(ancestry: &BTreeMap<H256, &Header>, precommit: &Precommit, hash: H256, number: BlockNumber) -> bool {
+    let mut current = precommit.target_hash;
+    loop {
+        if current == hash {
+            return true
+        }
+        match ancestry.get(&current) {
+            Some(header) if header.number > number => current = header.parent_hash,
+            _ => return false,
+        }
+    }
+}
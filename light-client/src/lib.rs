@@ -0,0 +1,471 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Netchain Light Client
+//!
+//! Verifies Netchain state without trusting an RPC node, for dashboards and other clients
+//! that only talk to untrusted endpoints:
+//! - [`LightClient::import_finality_proof`] checks a `grandpa_proveFinality` proof against the
+//!   tracked GRANDPA authority set and keeps the header as the latest finalized one
+//! - [`LightClient::read_storage`] checks a `state_getReadProof` proof against the state root
+//!   of that header; [`LightClient::shard_metrics`], [`LightClient::shard_state_root`] and
+//!   [`LightClient::oracle_value`] decode the values dashboards show
+//! - [`LightClient::shard_snapshot`] checks a `shardSync_snapshot` snapshot against the
+//!   finalized state commitment root of its shard, for validators syncing a single shard
+//!
+//! The client starts from a trusted authority set, e.g. read at a checkpoint through
+//! `GrandpaApi_grandpa_authorities` and `GrandpaApi_current_set_id`. It follows scheduled
+//! authority set changes, which requires importing every block that enacts one (nodes
+//! always keep a justification for those). Forced changes are not followed; after one the
+//! client has to be restarted from a new checkpoint.
+//!
+//! Nothing here uses host functions, so the crate builds for `wasm32-unknown-unknown`; see
+//! the `netchain-light-client-wasm` crate for browser bindings.
+
+extern crate alloc;
+
+pub mod grandpa;
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use sp_core::{
+    hashing::{blake2_128, blake2_256, twox_128},
+    Blake2Hasher, H256, U256,
+};
+use sp_trie::{LayoutV1, StorageProof, TrieConfiguration};
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+pub use grandpa::{AuthorityList, BlockNumber, FinalityProof, Header, Justification};
+
+/// Reasons a proof is rejected
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// Proof, justification or value does not decode
+    Undecodable,
+    /// Proof is not for the imported header
+    WrongTarget,
+    /// Header is not newer than the latest finalized one
+    NotNewer,
+    /// Precommit signed by an authority outside the current set
+    UnknownAuthority,
+    /// Precommit signature does not verify for the current set and round
+    BadSignature,
+    /// Precommit is for a block that does not descend from the finalized one
+    UnrelatedPrecommit,
+    /// Precommits hold less than two thirds of the authority set's weight
+    NotEnoughVotes,
+    /// An authority set change is enacted at this block, which must be imported first
+    SetChangeNotImported(BlockNumber),
+    /// No header has been finalized yet
+    NothingFinalized,
+    /// State proof is incomplete or does not match the finalized state root
+    InvalidStateProof,
+    /// Shard snapshot does not match the shard's finalized state commitment root
+    SnapshotMismatch,
+}
+
+impl From<codec::Error> for Error {
+    fn from(_: codec::Error) -> Self {
+        Error::Undecodable
+    }
+}
+
+/// Network-wide performance metrics, as stored in `Sharding::Metrics`
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct PerformanceMetrics {
+    /// Total transactions processed
+    pub total_transactions: u64,
+    /// Transactions per second
+    pub current_tps: u32,
+    /// Average block time
+    pub avg_block_time: u64,
+    /// Cross-shard transaction count
+    pub cross_shard_txs: u32,
+    /// Parallel processing utilization, in percent
+    pub parallel_utilization: u8,
+    /// Cross-shard settlements queued across all shards
+    pub cross_shard_backlog: u32,
+}
+
+/// Aggregated oracle value, as stored in `Oracle::AggregatedDataStorage`
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct AggregatedData {
+    /// Aggregated/median value
+    pub value: Vec<u8>,
+    /// Number of sources used
+    pub source_count: u32,
+    /// Average confidence score
+    pub confidence: u8,
+    /// Block when aggregation was calculated
+    pub aggregated_at: BlockNumber,
+    /// Individual data points used
+    pub data_points: Vec<Vec<u8>>,
+    /// First block the value is readable in
+    pub valid_from: BlockNumber,
+    /// Last block the value counts as fresh
+    pub valid_until: BlockNumber,
+}
+
+/// Account id of the Netchain runtime
+pub type AccountId = [u8; 32];
+
+/// Balance of the Netchain runtime
+pub type Balance = u128;
+
+/// Cross-shard transfer, in the latest layout queued in `Sharding::CrossShardQueue`
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrossShardTx {
+    /// Source shard
+    pub from_shard: u8,
+    /// Destination shard
+    pub to_shard: u8,
+    /// Transaction sender
+    pub sender: AccountId,
+    /// Transaction recipient
+    pub recipient: AccountId,
+    /// Amount to transfer
+    pub amount: Balance,
+    /// Transaction nonce
+    pub nonce: u64,
+    /// Cross-shard fee paid
+    pub fee: Balance,
+    /// Tip paid to the settling validator
+    pub tip: Balance,
+}
+
+/// Accounts and queue of a shard, as returned by `ShardingApi::shard_snapshot`
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ShardSnapshot {
+    /// Accounts assigned to the shard
+    pub accounts: Vec<AccountId>,
+    /// Cross-shard transfers waiting to settle on the shard
+    pub queue: Vec<CrossShardTx>,
+}
+
+impl ShardSnapshot {
+    /// Sum, modulo 2^256, of the Blake2-256 hashes of the accounts, as `Sharding` keeps it
+    pub fn accounts_digest(&self) -> U256 {
+        self.accounts.iter().fold(U256::zero(), |digest, account| {
+            digest.overflowing_add(U256::from_big_endian(&blake2_256(&account.encode()))).0
+        })
+    }
+
+    /// State commitment root of the snapshot, computed as `Sharding` does
+    pub fn root(&self) -> H256 {
+        let queue_root =
+            LayoutV1::<Blake2Hasher>::ordered_trie_root(self.queue.iter().map(Encode::encode).collect::<Vec<_>>());
+        H256(blake2_256(&(self.accounts_digest(), queue_root).encode()))
+    }
+}
+
+/// Finalized chain as seen through verified proofs
+#[derive(Clone, Debug)]
+pub struct LightClient {
+    set_id: u64,
+    authorities: AuthorityList,
+    /// Block enacting the next authority set, and that set
+    pending_change: Option<(BlockNumber, AuthorityList)>,
+    finalized: Option<Header>,
+}
+
+impl LightClient {
+    /// Client trusting authority set `set_id`
+    pub fn new(set_id: u64, authorities: AuthorityList) -> Self {
+        Self { set_id, authorities, pending_change: None, finalized: None }
+    }
+
+    /// Id of the authority set finalizing the next blocks
+    pub fn set_id(&self) -> u64 {
+        self.set_id
+    }
+
+    /// Authority set finalizing the next blocks
+    pub fn authorities(&self) -> &AuthorityList {
+        &self.authorities
+    }
+
+    /// Latest finalized header
+    pub fn finalized(&self) -> Option<&Header> {
+        self.finalized.as_ref()
+    }
+
+    /// Check the SCALE-encoded `FinalityProof` of `header` and keep it as finalized
+    ///
+    /// Authority set changes scheduled by `header`, or by the proof's headers it descends
+    /// from, are enacted once their block is imported.
+    pub fn import_finality_proof(&mut self, header: Header, proof: &[u8]) -> Result<(), Error> {
+        let proof = FinalityProof::decode(&mut &proof[..])?;
+        let hash = header.hash();
+        if proof.block != hash {
+            return Err(Error::WrongTarget)
+        }
+        let last = self.finalized.as_ref().map(|finalized| finalized.number);
+        if last.is_some_and(|last| header.number <= last) {
+            return Err(Error::NotNewer)
+        }
+        if let Some((enact_at, _)) = &self.pending_change {
+            if header.number > *enact_at {
+                return Err(Error::SetChangeNotImported(*enact_at))
+            }
+        }
+
+        Justification::decode(&mut &proof.justification[..])?.verify(
+            hash,
+            header.number,
+            self.set_id,
+            &self.authorities,
+        )?;
+
+        // Headers the finalized one descends from, down to the previous finalized one
+        let mut scheduling = Vec::new();
+        let mut parent = header.parent_hash;
+        while let Some(ancestor) = proof.unknown_headers.iter().find(|ancestor| ancestor.hash() == parent) {
+            if last.is_some_and(|last| ancestor.number <= last) {
+                break
+            }
+            scheduling.push(ancestor);
+            parent = ancestor.parent_hash;
+        }
+        scheduling.reverse();
+        scheduling.push(&header);
+        for scheduled in scheduling {
+            if let (None, Some(change)) = (&self.pending_change, scheduled.scheduled_change()) {
+                self.pending_change = Some((scheduled.number.saturating_add(change.delay), change.next_authorities));
+            }
+        }
+
+        if let Some((enact_at, _)) = &self.pending_change {
+            if header.number >= *enact_at {
+                let (_, authorities) = self.pending_change.take().expect("checked above; qed");
+                self.authorities = authorities;
+                self.set_id = self.set_id.saturating_add(1);
+            }
+        }
+        self.finalized = Some(header);
+        Ok(())
+    }
+
+    /// Value at `key` in the finalized state, from the nodes of a `state_getReadProof` proof
+    pub fn read_storage(&self, key: &[u8], proof: Vec<Vec<u8>>) -> Result<Option<Vec<u8>>, Error> {
+        let root = self.finalized.as_ref().ok_or(Error::NothingFinalized)?.state_root;
+        read_proof_value(root, key, proof)
+    }
+
+    /// Finalized `Sharding::Metrics`, from a proof of [`shard_metrics_key`]
+    pub fn shard_metrics(&self, proof: Vec<Vec<u8>>) -> Result<PerformanceMetrics, Error> {
+        self.read_decoded(&shard_metrics_key(), proof).map(Option::unwrap_or_default)
+    }
+
+    /// Finalized state commitment root of `shard`, from a proof of [`shard_state_root_key`]
+    pub fn shard_state_root(&self, shard: u8, proof: Vec<Vec<u8>>) -> Result<Option<H256>, Error> {
+        self.read_decoded(&shard_state_root_key(shard), proof)
+    }
+
+    /// Decode the SCALE-encoded `snapshot` of `shard` and check it against the shard's
+    /// finalized state commitment root, from a proof of [`shard_state_root_key`]
+    pub fn shard_snapshot(&self, shard: u8, snapshot: &[u8], proof: Vec<Vec<u8>>) -> Result<ShardSnapshot, Error> {
+        let snapshot = ShardSnapshot::decode(&mut &snapshot[..])?;
+        let root = self.shard_state_root(shard, proof)?.ok_or(Error::SnapshotMismatch)?;
+        if snapshot.root() != root {
+            return Err(Error::SnapshotMismatch)
+        }
+        Ok(snapshot)
+    }
+
+    /// Finalized aggregate of `data_key`, from a proof of [`oracle_value_key`]
+    pub fn oracle_value(&self, data_key: &[u8], proof: Vec<Vec<u8>>) -> Result<Option<AggregatedData>, Error> {
+        self.read_decoded(&oracle_value_key(data_key), proof)
+    }
+
+    fn read_decoded<V: Decode>(&self, key: &[u8], proof: Vec<Vec<u8>>) -> Result<Option<V>, Error> {
+        self.read_storage(key, proof)?
+            .map(|value| V::decode(&mut &value[..]))
+            .transpose()
+            .map_err(Into::into)
+    }
+}
+
+/// Value at `key` in the state with `root`, from the trie nodes in `proof`
+pub fn read_proof_value(root: H256, key: &[u8], proof: Vec<Vec<u8>>) -> Result<Option<Vec<u8>>, Error> {
+    let db = StorageProof::new(proof).into_memory_db::<Blake2Hasher>();
+    sp_trie::read_trie_value::<LayoutV1<Blake2Hasher>, _>(&db, &root, key, None, None)
+        .map_err(|_| Error::InvalidStateProof)
+}
+
+/// Storage key of `Sharding::Metrics`
+pub fn shard_metrics_key() -> Vec<u8> {
+    storage_prefix(b"Sharding", b"Metrics")
+}
+
+/// Storage key of `Sharding::ShardStateRoots` for `shard`
+pub fn shard_state_root_key(shard: u8) -> Vec<u8> {
+    let mut key = storage_prefix(b"Sharding", b"ShardStateRoots");
+    key.extend(blake2_128_concat(&shard.encode()));
+    key
+}
+
+/// Storage key of `Oracle::AggregatedDataStorage` for `data_key`
+pub fn oracle_value_key(data_key: &[u8]) -> Vec<u8> {
+    let mut key = storage_prefix(b"Oracle", b"AggregatedDataStorage");
+    key.extend(blake2_128_concat(&data_key.encode()));
+    key
+}
+
+fn storage_prefix(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+    [twox_128(pallet), twox_128(item)].concat()
+}
+
+fn blake2_128_concat(encoded: &[u8]) -> Vec<u8> {
+    [&blake2_128(encoded)[..], encoded].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        grandpa::{precommit_payload, Commit, DigestItem, Precommit, ScheduledChange, SignedPrecommit},
+        *,
+    };
+    use sp_core::{ed25519, Pair};
+    use sp_trie::{MemoryDB, TrieDBMutBuilder, TrieMut};
+
+    fn authority(seed: u8) -> ed25519::Pair {
+        ed25519::Pair::from_seed(&[seed; 32])
+    }
+
+    fn authorities(seeds: &[u8]) -> AuthorityList {
+        seeds.iter().map(|seed| (authority(*seed).public(), 1)).collect()
+    }
+
+    /// State root and proof of a trie holding `entries`
+    fn state(entries: &[(Vec<u8>, Vec<u8>)]) -> (H256, Vec<Vec<u8>>) {
+        let mut db = MemoryDB::<Blake2Hasher>::default();
+        let mut root = H256::default();
+        {
+            let mut trie = TrieDBMutBuilder::<LayoutV1<Blake2Hasher>>::new(&mut db, &mut root).build();
+            for (key, value) in entries {
+                trie.insert(key, value).unwrap();
+            }
+        }
+        (root, db.drain().into_values().map(|(node, _)| node).collect())
+    }
+
+    fn header(number: BlockNumber, state_root: H256, digest: Vec<DigestItem>) -> Header {
+        Header {
+            parent_hash: H256::repeat_byte(number as u8),
+            number,
+            state_root,
+            extrinsics_root: H256::zero(),
+            digest,
+        }
+    }
+
+    /// Finality proof of `header` signed by `signers` in set `set_id`
+    fn finality_proof(header: &Header, signers: &[u8], set_id: u64) -> Vec<u8> {
+        let precommit = Precommit { target_hash: header.hash(), target_number: header.number };
+        let precommits = signers
+            .iter()
+            .map(|seed| SignedPrecommit {
+                precommit: precommit.clone(),
+                signature: authority(*seed).sign(&precommit_payload(&precommit, 1, set_id)),
+                id: authority(*seed).public(),
+            })
+            .collect();
+        let justification = Justification {
+            round: 1,
+            commit: Commit { target_hash: header.hash(), target_number: header.number, precommits },
+            votes_ancestries: Vec::new(),
+        };
+        FinalityProof { block: header.hash(), justification: justification.encode(), unknown_headers: Vec::new() }
+            .encode()
+    }
+
+    fn import(client: &mut LightClient, header: &Header, signers: &[u8], set_id: u64) -> Result<(), Error> {
+        client.import_finality_proof(header.clone(), &finality_proof(header, signers, set_id))
+    }
+
+    #[test]
+    fn finalized_state_is_read_through_proofs() {
+        let metrics = PerformanceMetrics { total_transactions: 1_000, current_tps: 250, ..Default::default() };
+        let aggregated = AggregatedData {
+            value: b"42000".to_vec(),
+            source_count: 3,
+            confidence: 90,
+            aggregated_at: 7,
+            data_points: vec![b"42000".to_vec(); 3],
+            valid_from: 9,
+            valid_until: 1_207,
+        };
+        let (root, proof) = state(&[
+            (shard_metrics_key(), metrics.encode()),
+            (oracle_value_key(b"NET/USD"), aggregated.encode()),
+        ]);
+        let mut client = LightClient::new(0, authorities(&[1, 2, 3]));
+        assert_eq!(client.shard_metrics(proof.clone()), Err(Error::NothingFinalized));
+
+        // Two of three authorities are not enough
+        let block = header(1, root, Vec::new());
+        assert_eq!(import(&mut client, &block, &[1, 2], 0), Err(Error::NotEnoughVotes));
+        assert_eq!(import(&mut client, &block, &[1, 2, 4], 0), Err(Error::UnknownAuthority));
+        assert_eq!(import(&mut client, &block, &[1, 2, 3], 1), Err(Error::BadSignature));
+
+        assert_eq!(import(&mut client, &block, &[1, 2, 3], 0), Ok(()));
+        assert_eq!(client.finalized(), Some(&block));
+        assert_eq!(client.shard_metrics(proof.clone()), Ok(metrics));
+        assert_eq!(client.oracle_value(b"NET/USD", proof.clone()), Ok(Some(aggregated)));
+        assert_eq!(client.oracle_value(b"BTC/USD", proof.clone()), Ok(None));
+        assert_eq!(client.shard_metrics(proof[..1].to_vec()), Err(Error::InvalidStateProof));
+        assert_eq!(import(&mut client, &block, &[1, 2, 3], 0), Err(Error::NotNewer));
+    }
+
+    #[test]
+    fn shard_snapshots_are_checked_against_commitment_roots() {
+        let snapshot = ShardSnapshot {
+            accounts: vec![[1; 32], [2; 32]],
+            queue: vec![CrossShardTx {
+                from_shard: 0,
+                to_shard: 3,
+                sender: [4; 32],
+                recipient: [2; 32],
+                amount: 1_000,
+                nonce: 1,
+                fee: 10,
+                tip: 0,
+            }],
+        };
+        let (root, proof) = state(&[(shard_state_root_key(3), snapshot.root().encode())]);
+        let mut client = LightClient::new(0, authorities(&[1]));
+        assert_eq!(import(&mut client, &header(1, root, Vec::new()), &[1], 0), Ok(()));
+
+        assert_eq!(client.shard_snapshot(3, &snapshot.encode(), proof.clone()), Ok(snapshot.clone()));
+
+        let mut tampered = snapshot.clone();
+        tampered.accounts.pop();
+        assert_eq!(client.shard_snapshot(3, &tampered.encode(), proof.clone()), Err(Error::SnapshotMismatch));
+        assert_eq!(client.shard_snapshot(2, &snapshot.encode(), proof), Err(Error::SnapshotMismatch));
+    }
+
+    #[test]
+    fn scheduled_authority_set_changes_are_followed() {
+        let mut client = LightClient::new(0, authorities(&[1, 2, 3]));
+        let change = ScheduledChange { next_authorities: authorities(&[4]), delay: 2 };
+        let log = (1u8, change).encode();
+        let scheduling = header(1, H256::zero(), vec![DigestItem::Consensus(grandpa::GRANDPA_ENGINE_ID, log)]);
+        assert_eq!(import(&mut client, &scheduling, &[1, 2, 3], 0), Ok(()));
+        assert_eq!(client.set_id(), 0);
+
+        // The enacting block, still finalized by the old set, must be imported before later ones
+        let later = header(4, H256::zero(), Vec::new());
+        assert_eq!(import(&mut client, &later, &[4], 1), Err(Error::SetChangeNotImported(3)));
+        let enacting = header(3, H256::zero(), Vec::new());
+        assert_eq!(import(&mut client, &enacting, &[1, 2, 3], 0), Ok(()));
+        assert_eq!(client.set_id(), 1);
+        assert_eq!(client.authorities(), &authorities(&[4]));
+
+        assert_eq!(import(&mut client, &later, &[4], 1), Ok(()));
+    }
+}
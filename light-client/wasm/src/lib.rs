@@ -0,0 +1,122 @@
+//! # Netchain Light Client for browsers
+//!
+//! wasm-bindgen bindings of `netchain-light-client`. Byte strings go in as `0x`-prefixed
+//! hex, as the node's RPC returns them, and decoded values come out as plain JS objects:
+//!
+//! ```js
+//! const authorities = await api.rpc.state.call("GrandpaApi_grandpa_authorities", "0x");
+//! const client = new LightClient(setId, authorities.toHex());
+//!
+//! const hash = await api.rpc.chain.getFinalizedHead();
+//! const header = await api.rpc.chain.getHeader(hash);
+//! const proof = await api.rpc.grandpa.proveFinality(header.number);
+//! client.importFinalityProof(header.toHex(), proof.toHex());
+//!
+//! const read = await api.rpc.state.getReadProof([LightClient.shardMetricsKey()], hash);
+//! const metrics = client.shardMetrics(read.proof.map((node) => node.toHex()));
+//! ```
+//!
+//! Every call throws a JS error if a proof does not verify.
+
+use codec::Decode;
+use netchain_light_client::{self as light_client, AuthorityList, Error, Header};
+use wasm_bindgen::prelude::*;
+
+/// Light client following Netchain finality from a trusted authority set
+#[wasm_bindgen]
+pub struct LightClient(light_client::LightClient);
+
+#[wasm_bindgen]
+impl LightClient {
+    /// Client trusting authority set `set_id`, given as the hex SCALE encoding of its
+    /// authority list
+    #[wasm_bindgen(constructor)]
+    pub fn new(set_id: u64, authorities: &str) -> Result<LightClient, JsError> {
+        let authorities =
+            AuthorityList::decode(&mut &from_hex(authorities)?[..]).map_err(|_| js_error(Error::Undecodable))?;
+        Ok(LightClient(light_client::LightClient::new(set_id, authorities)))
+    }
+
+    /// Check the hex `grandpa_proveFinality` proof of the hex SCALE-encoded `header`
+    #[wasm_bindgen(js_name = importFinalityProof)]
+    pub fn import_finality_proof(&mut self, header: &str, proof: &str) -> Result<(), JsError> {
+        let header = Header::decode(&mut &from_hex(header)?[..]).map_err(|_| js_error(Error::Undecodable))?;
+        self.0.import_finality_proof(header, &from_hex(proof)?).map_err(js_error)
+    }
+
+    /// Number of the latest finalized block, if any
+    #[wasm_bindgen(js_name = finalizedNumber)]
+    pub fn finalized_number(&self) -> Option<u32> {
+        self.0.finalized().map(|header| header.number)
+    }
+
+    /// Id of the authority set finalizing the next blocks
+    #[wasm_bindgen(js_name = setId)]
+    pub fn set_id(&self) -> u64 {
+        self.0.set_id()
+    }
+
+    /// Storage key to request a proof of for [`Self::shard_metrics`]
+    #[wasm_bindgen(js_name = shardMetricsKey)]
+    pub fn shard_metrics_key() -> String {
+        to_hex(&light_client::shard_metrics_key())
+    }
+
+    /// Storage key to request a proof of for [`Self::shard_state_root`]
+    #[wasm_bindgen(js_name = shardStateRootKey)]
+    pub fn shard_state_root_key(shard: u8) -> String {
+        to_hex(&light_client::shard_state_root_key(shard))
+    }
+
+    /// Storage key to request a proof of for [`Self::oracle_value`]
+    #[wasm_bindgen(js_name = oracleValueKey)]
+    pub fn oracle_value_key(data_key: &str) -> String {
+        to_hex(&light_client::oracle_value_key(data_key.as_bytes()))
+    }
+
+    /// Finalized shard performance metrics
+    #[wasm_bindgen(js_name = shardMetrics)]
+    pub fn shard_metrics(&self, proof: Vec<String>) -> Result<JsValue, JsError> {
+        let metrics = self.0.shard_metrics(nodes(proof)?).map_err(js_error)?;
+        Ok(serde_wasm_bindgen::to_value(&metrics)?)
+    }
+
+    /// Finalized state commitment root of `shard` as hex, or `undefined`
+    #[wasm_bindgen(js_name = shardStateRoot)]
+    pub fn shard_state_root(&self, shard: u8, proof: Vec<String>) -> Result<Option<String>, JsError> {
+        let root = self.0.shard_state_root(shard, nodes(proof)?).map_err(js_error)?;
+        Ok(root.map(|root| to_hex(root.as_bytes())))
+    }
+
+    /// Hex `shardSync_snapshot` snapshot of `shard`, checked against its finalized state
+    /// commitment root
+    #[wasm_bindgen(js_name = shardSnapshot)]
+    pub fn shard_snapshot(&self, shard: u8, snapshot: &str, proof: Vec<String>) -> Result<JsValue, JsError> {
+        let snapshot = self.0.shard_snapshot(shard, &from_hex(snapshot)?, nodes(proof)?).map_err(js_error)?;
+        Ok(serde_wasm_bindgen::to_value(&snapshot)?)
+    }
+
+    /// Finalized aggregate of the oracle `data_key`, e.g. `NET/USD`, or `undefined`
+    #[wasm_bindgen(js_name = oracleValue)]
+    pub fn oracle_value(&self, data_key: &str, proof: Vec<String>) -> Result<JsValue, JsError> {
+        let aggregated = self.0.oracle_value(data_key.as_bytes(), nodes(proof)?).map_err(js_error)?;
+        Ok(serde_wasm_bindgen::to_value(&aggregated)?)
+    }
+}
+
+fn js_error(error: Error) -> JsError {
+    JsError::new(&format!("{error:?}"))
+}
+
+fn from_hex(value: &str) -> Result<Vec<u8>, JsError> {
+    Ok(hex::decode(value.trim_start_matches("0x"))?)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Trie nodes of a `state_getReadProof` proof
+fn nodes(proof: Vec<String>) -> Result<Vec<Vec<u8>>, JsError> {
+    proof.iter().map(|node| from_hex(node)).collect()
+}
@@ -0,0 +1,163 @@
+//! Subxt configuration for Netchain
+//!
+//! Netchain replaces `CheckNonce` with `CheckShardNonce` and adds `CheckShardAffinity`, so
+//! subxt's default extrinsic params cannot sign for it. Both extensions are implemented here;
+//! the remaining ones are subxt's own, and extensions that encode to nothing are skipped.
+
+use codec::{Compact, Encode};
+use subxt::{
+    client::ClientState,
+    config::{
+        signed_extensions::{
+            AnyOf, ChargeAssetTxPayment, ChargeAssetTxPaymentParams, CheckGenesis,
+            CheckMetadataHash, CheckMortality, CheckMortalityParams, CheckSpecVersion,
+            CheckTxVersion, SignedExtension,
+        },
+        substrate::{BlakeTwo256, SubstrateHeader},
+        Config, ExtrinsicParams, ExtrinsicParamsEncoder, ExtrinsicParamsError, RefineParams,
+        RefineParamsData,
+    },
+    dynamic::DecodedValue,
+    ext::scale_info::PortableRegistry,
+    utils::{AccountId32, MultiAddress, MultiSignature, H256},
+};
+
+/// Number of shards of the Netchain runtime, and the virtual shard governance can reserve
+/// for system transactions
+pub use netchain_primitives::{SHARD_COUNT, SYSTEM_SHARD};
+
+/// Shard the runtime assigns `account` to, as in `pallet_sharding::get_account_shard`, unless
+/// rebalancing moved it or the chain predates the current shard mapping version
+pub fn shard_of(account: &AccountId32) -> u8 {
+    netchain_primitives::account_shard(&account.encode())
+}
+
+/// Subxt configuration of a Netchain node
+pub enum NetchainConfig {}
+
+impl Config for NetchainConfig {
+    type Hash = H256;
+    type AccountId = AccountId32;
+    type Address = MultiAddress<AccountId32, ()>;
+    type Signature = MultiSignature;
+    type Hasher = BlakeTwo256;
+    type Header = SubstrateHeader<u32, BlakeTwo256>;
+    type ExtrinsicParams = NetchainExtrinsicParams;
+    type AssetId = u32;
+}
+
+/// Transaction extensions of the Netchain runtime that carry data
+pub type NetchainExtrinsicParams = AnyOf<
+    NetchainConfig,
+    (
+        CheckSpecVersion,
+        CheckTxVersion,
+        CheckGenesis<NetchainConfig>,
+        CheckMortality<NetchainConfig>,
+        CheckShardNonce,
+        ChargeAssetTxPayment<NetchainConfig>,
+        CheckMetadataHash,
+        CheckShardAffinity,
+    ),
+>;
+
+/// Params of [`NetchainExtrinsicParams`]
+pub type NetchainParams = <NetchainExtrinsicParams as ExtrinsicParams<NetchainConfig>>::Params;
+
+/// Immortal, tip-free params for a transaction signed by `signer`
+pub fn params_for(signer: &AccountId32) -> NetchainParams {
+    params_on_shard(shard_of(signer))
+}
+
+/// Immortal, tip-free params for a transaction tagged with `shard`
+///
+/// System transactions (staking, governance, IBC handshakes) must be tagged with the system
+/// shard while governance reserves one; `ShardingApi_transaction_shard` tells the shard of a
+/// call.
+pub fn params_on_shard(shard: u8) -> NetchainParams {
+    (
+        (),
+        (),
+        (),
+        CheckMortalityParams::default(),
+        CheckShardNonceParams::default(),
+        ChargeAssetTxPaymentParams::no_tip(),
+        (),
+        CheckShardAffinityParams(Some(shard)),
+    )
+}
+
+/// `pallet_sharding::CheckShardNonce`, always using the account's global nonce
+#[derive(Debug)]
+pub struct CheckShardNonce(u64);
+
+/// Params of [`CheckShardNonce`]; the nonce defaults to the account's next nonce
+#[derive(Debug, Clone, Default)]
+pub struct CheckShardNonceParams(pub Option<u64>);
+
+impl<T: Config> RefineParams<T> for CheckShardNonceParams {
+    fn refine(&mut self, data: &RefineParamsData<T>) {
+        if self.0.is_none() {
+            self.0 = Some(data.account_nonce());
+        }
+    }
+}
+
+impl<T: Config> ExtrinsicParams<T> for CheckShardNonce {
+    type Params = CheckShardNonceParams;
+
+    fn new(_client: &ClientState<T>, params: Self::Params) -> Result<Self, ExtrinsicParamsError> {
+        Ok(CheckShardNonce(params.0.unwrap_or(0)))
+    }
+}
+
+impl ExtrinsicParamsEncoder for CheckShardNonce {
+    fn encode_extra_to(&self, v: &mut Vec<u8>) {
+        // `ShardNonce::Global`
+        0u8.encode_to(v);
+        Compact(self.0).encode_to(v);
+    }
+}
+
+impl<T: Config> SignedExtension<T> for CheckShardNonce {
+    type Decoded = DecodedValue;
+
+    fn matches(identifier: &str, _type_id: u32, _types: &PortableRegistry) -> bool {
+        identifier == "CheckShardNonce"
+    }
+}
+
+/// `pallet_sharding::CheckShardAffinity`, tagging a transaction with its signer's shard
+#[derive(Debug)]
+pub struct CheckShardAffinity(u8);
+
+/// Params of [`CheckShardAffinity`]; the shard must be set, see [`params_for`]
+#[derive(Debug, Clone, Default)]
+pub struct CheckShardAffinityParams(pub Option<u8>);
+
+impl<T: Config> RefineParams<T> for CheckShardAffinityParams {}
+
+impl<T: Config> ExtrinsicParams<T> for CheckShardAffinity {
+    type Params = CheckShardAffinityParams;
+
+    fn new(_client: &ClientState<T>, params: Self::Params) -> Result<Self, ExtrinsicParamsError> {
+        params
+            .0
+            .map(CheckShardAffinity)
+            .ok_or_else(|| ExtrinsicParamsError::Custom("transaction has no shard affinity".into()))
+    }
+}
+
+impl ExtrinsicParamsEncoder for CheckShardAffinity {
+    fn encode_extra_to(&self, v: &mut Vec<u8>) {
+        self.0.encode_to(v);
+    }
+}
+
+impl<T: Config> SignedExtension<T> for CheckShardAffinity {
+    type Decoded = DecodedValue;
+
+    fn matches(identifier: &str, _type_id: u32, _types: &PortableRegistry) -> bool {
+        identifier == "CheckShardAffinity"
+    }
+}
@@ -0,0 +1,243 @@
+//! # Netchain Client
+//!
+//! Typed [subxt](https://docs.rs/subxt) client for dapp backends. The bindings in [`netchain`]
+//! are generated from the metadata of the runtime built in this repository, as the benchmark
+//! tool's are, so build the runtime first:
+//!
+//! ```bash
+//! cargo build --release -p netchain-runtime
+//! ```
+//!
+//! [`NetchainClient`] signs with Netchain's transaction extensions (see [`config`]) and wraps
+//! the calls backends use most:
+//! - [`NetchainClient::transfer_cross_shard`] queues a cross-shard transfer and returns its hash
+//! - [`NetchainClient::watch_cross_shard_status`] follows that transfer until it settles or fails
+//! - [`NetchainClient::send_ibc_packet`] sends a packet over an open IBC channel
+//! - [`NetchainClient::request_oracle_data`] opens an oracle data request
+//! - [`NetchainClient::submit_batch`] dispatches several calls in one extrinsic and reports
+//!   the outcome and events of each
+//!
+//! Anything else can be submitted through [`NetchainClient::submit`] with the generated
+//! `netchain::tx()` payloads.
+
+pub mod config;
+
+pub use config::{shard_of, NetchainConfig, SHARD_COUNT, SYSTEM_SHARD};
+pub use subxt_signer::sr25519::Keypair;
+
+use futures::{Stream, StreamExt};
+use subxt::{
+    blocks::ExtrinsicEvents,
+    events::{EventDetails, StaticEvent},
+    tx::Payload,
+    utils::{AccountId32, H256},
+    OnlineClient,
+};
+
+#[subxt::subxt(
+    runtime_metadata_path = "../target/release/wbuild/netchain-runtime/netchain_runtime.compact.scale",
+    derive_for_type(path = "pallet_sharding::CrossShardTxStatus", derive = "PartialEq, Eq"),
+    derive_for_type(path = "netchain_primitives::FailureReason", derive = "PartialEq, Eq")
+)]
+pub mod netchain {}
+
+use netchain::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+
+/// Lifecycle of a cross-shard transfer, as stored by the sharding pallet
+pub type CrossShardTxStatus = netchain::runtime_types::pallet_sharding::CrossShardTxStatus;
+
+/// Call of any pallet, as batched by [`NetchainClient::submit_batch`]
+pub type RuntimeCall = netchain::runtime_types::netchain_runtime::RuntimeCall;
+
+/// Error a call failed with
+pub type DispatchError = netchain::runtime_types::sp_runtime::DispatchError;
+
+/// Result of a client call
+pub type Result<T> = core::result::Result<T, subxt::Error>;
+
+/// IBC packet to send, identifiers as raw bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IbcPacket {
+    pub source_port: Vec<u8>,
+    pub source_channel: Vec<u8>,
+    pub destination_port: Vec<u8>,
+    pub destination_channel: Vec<u8>,
+    pub data: Vec<u8>,
+    /// Counterparty height after which the packet times out, 0 for none
+    pub timeout_height: u64,
+    /// Counterparty timestamp after which the packet times out, 0 for none
+    pub timeout_timestamp: u64,
+}
+
+/// Outcome of one call of a batch
+#[derive(Debug)]
+pub struct BatchItem {
+    /// Position of the call in the batch
+    pub index: u32,
+    /// Whether the call succeeded, with its error otherwise
+    pub result: core::result::Result<(), DispatchError>,
+    /// Events the call emitted, empty for a failed call as its changes were rolled back
+    pub events: Vec<EventDetails<NetchainConfig>>,
+}
+
+/// Connection to a Netchain node
+pub struct NetchainClient {
+    client: OnlineClient<NetchainConfig>,
+}
+
+impl NetchainClient {
+    /// Connect to the node at `url`, e.g. `ws://127.0.0.1:9944`
+    pub async fn from_url(url: impl AsRef<str>) -> Result<Self> {
+        Ok(Self { client: OnlineClient::<NetchainConfig>::from_url(url).await? })
+    }
+
+    /// Wrap an existing subxt client
+    pub fn new(client: OnlineClient<NetchainConfig>) -> Self {
+        Self { client }
+    }
+
+    /// Underlying subxt client, for queries the helpers do not cover
+    pub fn client(&self) -> &OnlineClient<NetchainConfig> {
+        &self.client
+    }
+
+    /// Sign `call` with `signer`, tagged with the signer's shard, wait for it to be finalized
+    /// and return its events
+    ///
+    /// Fails if the extrinsic is rejected by the pool or its dispatch fails.
+    pub async fn submit<Call: Payload>(
+        &self,
+        call: &Call,
+        signer: &Keypair,
+    ) -> Result<ExtrinsicEvents<NetchainConfig>> {
+        let params = config::params_for(&signer.public_key().to_account_id());
+        self.client
+            .tx()
+            .sign_and_submit_then_watch(call, signer, params)
+            .await?
+            .wait_for_finalized_success()
+            .await
+    }
+
+    /// Queue a transfer of `amount` from `signer` to `recipient` on `to_shard`, returning the
+    /// hash [`Self::watch_cross_shard_status`] follows it by
+    pub async fn transfer_cross_shard(
+        &self,
+        signer: &Keypair,
+        to_shard: u8,
+        recipient: AccountId32,
+        amount: u128,
+    ) -> Result<H256> {
+        let call = netchain::tx().sharding().execute_cross_shard_tx(to_shard, recipient, amount);
+        let events = self.submit(&call, signer).await?;
+        let executed: netchain::sharding::events::CrossShardExecuted = expect_event(&events)?;
+        Ok(executed.tx_hash)
+    }
+
+    /// Send `packet` from `signer`, returning its sequence on the source channel
+    pub async fn send_ibc_packet(&self, signer: &Keypair, packet: IbcPacket) -> Result<u64> {
+        let call = netchain::tx().ibc_core().send_packet(
+            BoundedVec(packet.source_port),
+            BoundedVec(packet.source_channel),
+            BoundedVec(packet.destination_port),
+            BoundedVec(packet.destination_channel),
+            packet.data,
+            packet.timeout_height,
+            packet.timeout_timestamp,
+        );
+        let events = self.submit(&call, signer).await?;
+        let sent: netchain::ibc_core::events::PacketSent = expect_event(&events)?;
+        Ok(sent.sequence)
+    }
+
+    /// Request `data_key` from `sources` on behalf of `signer`, paying the standard or premium
+    /// query fee, and return the request id
+    pub async fn request_oracle_data(
+        &self,
+        signer: &Keypair,
+        data_key: Vec<u8>,
+        sources: Vec<Vec<u8>>,
+        premium: bool,
+        callback: Option<Vec<u8>>,
+    ) -> Result<u64> {
+        let call = netchain::tx().oracle().request_data(data_key, sources, premium, callback);
+        let events = self.submit(&call, signer).await?;
+        let requested: netchain::oracle::events::DataRequested = expect_event(&events)?;
+        Ok(requested.request_id)
+    }
+
+    /// Dispatch `calls` from `signer` in one `Utility.force_batch` extrinsic and return the
+    /// outcome of each, in order
+    ///
+    /// A failing call does not stop the ones after it. The extrinsic's fee payment events are
+    /// not attributed to any item.
+    pub async fn submit_batch(&self, signer: &Keypair, calls: Vec<RuntimeCall>) -> Result<Vec<BatchItem>> {
+        let call = netchain::tx().utility().force_batch(calls);
+        let events = self.submit(&call, signer).await?;
+        split_batch_events(&events)
+    }
+
+    /// Status of the cross-shard transfer `tx_hash` at every finalized block it changes in,
+    /// ending once the transfer is settled or has failed
+    ///
+    /// A dead-lettered transfer is followed on until it is claimed or refunded.
+    pub async fn watch_cross_shard_status(
+        &self,
+        tx_hash: H256,
+    ) -> Result<impl Stream<Item = Result<CrossShardTxStatus>>> {
+        let blocks = self.client.blocks().subscribe_finalized().await?;
+        Ok(futures::stream::try_unfold((blocks, None, false), move |(mut blocks, last, done)| async move {
+            if done {
+                return Ok(None);
+            }
+            while let Some(block) = blocks.next().await {
+                let address = netchain::storage().sharding().cross_shard_tx_statuses(tx_hash);
+                let Some(status) = block?.storage().fetch(&address).await? else {
+                    continue;
+                };
+                if last.as_ref() != Some(&status) {
+                    let done = matches!(status, CrossShardTxStatus::Settled | CrossShardTxStatus::Failed(_));
+                    return Ok(Some((status.clone(), (blocks, Some(status), done))));
+                }
+            }
+            Ok(None)
+        }))
+    }
+}
+
+/// The first `E` event among `events`, failing if there is none
+fn expect_event<E: StaticEvent>(events: &ExtrinsicEvents<NetchainConfig>) -> Result<E> {
+    events
+        .find_first::<E>()?
+        .ok_or_else(|| subxt::Error::Other(format!("no {}.{} event", E::PALLET, E::EVENT)))
+}
+
+/// Group the events of a `force_batch` extrinsic by the item that emitted them
+///
+/// `force_batch` emits `ItemCompleted` or `ItemFailed` after each item's own events, so every
+/// marker closes the item at the next index. The extrinsic's first event is the fee
+/// withdrawal and belongs to no item.
+fn split_batch_events(events: &ExtrinsicEvents<NetchainConfig>) -> Result<Vec<BatchItem>> {
+    use netchain::{
+        balances::events::Withdraw,
+        utility::events::{ItemCompleted, ItemFailed},
+    };
+
+    let mut items = Vec::new();
+    let mut pending = Vec::new();
+    for (position, event) in events.iter().enumerate() {
+        let event = event?;
+        let result = if event.as_event::<ItemCompleted>()?.is_some() {
+            Ok(())
+        } else if let Some(failed) = event.as_event::<ItemFailed>()? {
+            Err(failed.error)
+        } else {
+            if position > 0 || event.as_event::<Withdraw>()?.is_none() {
+                pending.push(event);
+            }
+            continue;
+        };
+        items.push(BatchItem { index: items.len() as u32, result, events: std::mem::take(&mut pending) });
+    }
+    Ok(items)
+}
@@ -0,0 +1,188 @@
+//! Benchmark of the shard-segmented pre-execution stage.
+//!
+//! A block body of balance transfers between accounts of the same shard, interleaved across
+//! the shards, is executed natively on a genesis state endowing the accounts: sequentially in
+//! block order, as block import does, and as per-shard segments on a rayon pool with one worker
+//! per shard, as the pre-execution stage does. Both start from the initialized block and run
+//! on an in-memory state, so the figures compare execution alone; on a node the stage also
+//! reads the database concurrently for the sequential execution.
+//!
+//! Run with `cargo bench -p netchain-node --bench shard_segments`.
+
+use codec::Encode;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use netchain_primitives::SHARD_COUNT;
+use netchain_runtime::{
+	AccountId, BalancesCall, BalancesConfig, Executive, Header, Runtime, RuntimeCall, RuntimeGenesisConfig,
+	Signature, SignedPayload, TxExtension, UncheckedExtrinsic, VERSION,
+};
+use pallet_sharding::{CheckShardAffinity, CheckShardNonce, ShardId};
+use rayon::prelude::*;
+use sp_core::{blake2_256, sr25519, storage::well_known_keys::EXTRINSIC_INDEX, Pair};
+use sp_runtime::{generic::Era, traits::Header as HeaderT, BuildStorage};
+use sp_state_machine::{Ext, InMemoryBackend, OverlayedChanges};
+use std::collections::BTreeMap;
+
+type Backend = InMemoryBackend<sp_runtime::traits::BlakeTwo256>;
+
+/// Storage written on top of the genesis state
+type Changes = Vec<(Vec<u8>, Option<Vec<u8>>)>;
+
+/// Accounts signing transfers on each shard
+const SIGNERS_PER_SHARD: usize = 16;
+
+/// Run `f` on `backend` with `seed` applied, returning the writes
+fn execute(backend: &Backend, seed: &Changes, f: impl FnOnce()) -> Changes {
+	let mut overlay = OverlayedChanges::default();
+	for (key, value) in seed {
+		overlay.set_storage(key.clone(), value.clone());
+	}
+	{
+		let mut ext = Ext::new(&mut overlay, backend, None);
+		sp_externalities::set_and_run_with_externalities(&mut ext, f);
+	}
+	let changes = overlay.drain_storage_changes(backend, VERSION.state_version()).expect("changes drain");
+	changes.main_storage_changes
+}
+
+/// Signed transfer of `pair` to `dest`
+fn transfer(
+	pair: &sr25519::Pair,
+	shard: ShardId,
+	nonce: u32,
+	dest: AccountId,
+	genesis: sp_core::H256,
+) -> UncheckedExtrinsic {
+	let call = RuntimeCall::Balances(BalancesCall::transfer_keep_alive { dest: dest.into(), value: 1 << 40 });
+	let tx_ext: TxExtension = (
+		frame_system::AuthorizeCall::<Runtime>::new(),
+		frame_system::CheckNonZeroSender::<Runtime>::new(),
+		frame_system::CheckSpecVersion::<Runtime>::new(),
+		frame_system::CheckTxVersion::<Runtime>::new(),
+		frame_system::CheckGenesis::<Runtime>::new(),
+		frame_system::CheckEra::<Runtime>::from(Era::Immortal),
+		CheckShardNonce::<Runtime>::global(nonce),
+		frame_system::CheckWeight::<Runtime>::new(),
+		pallet_asset_tx_payment::ChargeAssetTxPayment::<Runtime>::from(0, None),
+		frame_metadata_hash_extension::CheckMetadataHash::<Runtime>::new(false),
+		CheckShardAffinity::<Runtime>::new(shard),
+		frame_system::WeightReclaim::<Runtime>::new(),
+	);
+	let payload = SignedPayload::from_raw(
+		call.clone(),
+		tx_ext.clone(),
+		((), (), VERSION.spec_version, VERSION.transaction_version, genesis, genesis, (), (), (), None, (), ()),
+	);
+	let signature = payload.using_encoded(|e| pair.sign(e));
+	UncheckedExtrinsic::new_signed(
+		call,
+		AccountId::from(pair.public()).into(),
+		Signature::Sr25519(signature),
+		tx_ext,
+	)
+}
+
+/// Initialized block of `extrinsics` transfers, with a state backend per shard
+struct BenchBlock {
+	backends: Vec<Backend>,
+	seed: Changes,
+	extrinsics: Vec<UncheckedExtrinsic>,
+	segments: Vec<Vec<usize>>,
+}
+
+fn bench_block(extrinsics: usize) -> BenchBlock {
+	let pairs: Vec<sr25519::Pair> =
+		(0..64 * SIGNERS_PER_SHARD as u32).map(|i| sr25519::Pair::from_seed(&blake2_256(&i.to_le_bytes()))).collect();
+	let genesis = RuntimeGenesisConfig {
+		balances: BalancesConfig {
+			balances: pairs.iter().map(|pair| (pair.public().into(), 1u128 << 60)).collect(),
+			..Default::default()
+		},
+		..Default::default()
+	}
+	.build_storage()
+	.expect("genesis state builds");
+	let backends: Vec<Backend> =
+		(0..SHARD_COUNT).map(|_| Backend::from((genesis.clone(), VERSION.state_version()))).collect();
+
+	let mut signers: BTreeMap<ShardId, Vec<sr25519::Pair>> = BTreeMap::new();
+	let mut genesis_hash = Default::default();
+	execute(&backends[0], &Changes::new(), || {
+		genesis_hash = frame_system::BlockHash::<Runtime>::get(0);
+		for pair in &pairs {
+			let shard = pallet_sharding::Pallet::<Runtime>::get_account_shard(&pair.public().into());
+			signers.entry(shard).or_default().push(pair.clone());
+		}
+	});
+	for signers in signers.values_mut() {
+		signers.truncate(SIGNERS_PER_SHARD);
+	}
+
+	// Round robin over the shards, each signer paying the next account of its shard
+	let mut nonces: BTreeMap<AccountId, u32> = BTreeMap::new();
+	let mut segments: BTreeMap<ShardId, Vec<usize>> = BTreeMap::new();
+	let body = (0..extrinsics)
+		.map(|index| {
+			let shard = index as ShardId % SHARD_COUNT;
+			let signers = &signers[&shard];
+			let nth = index / SHARD_COUNT as usize;
+			let pair = &signers[nth % signers.len()];
+			let dest = AccountId::from(signers[(nth + 1) % signers.len()].public());
+			let nonce = nonces.entry(pair.public().into()).or_default();
+			let xt = transfer(pair, shard, *nonce, dest, genesis_hash);
+			*nonce += 1;
+			segments.entry(shard).or_default().push(index);
+			xt
+		})
+		.collect();
+
+	let header = Header::new(1, Default::default(), Default::default(), genesis_hash, Default::default());
+	let seed = execute(&backends[0], &Changes::new(), || {
+		Executive::initialize_block(&header);
+	});
+	BenchBlock { backends, seed, extrinsics: body, segments: segments.into_values().collect() }
+}
+
+/// Apply extrinsic `index` of `extrinsics`, which must be valid
+fn apply(index: usize, extrinsics: &[UncheckedExtrinsic]) {
+	sp_io::storage::set(EXTRINSIC_INDEX, &(index as u32).encode());
+	Executive::apply_extrinsic(extrinsics[index].clone()).expect("transfer is valid");
+}
+
+fn bench_segment_pre_execution(c: &mut Criterion) {
+	let workers = rayon::ThreadPoolBuilder::new()
+		.num_threads(SHARD_COUNT as usize)
+		.build()
+		.expect("thread pool builds");
+
+	let mut group = c.benchmark_group("shard_segment_pre_execution");
+	group.sample_size(10);
+	for extrinsics in [256, 1024, 4096] {
+		let mut block = bench_block(extrinsics);
+		group.throughput(Throughput::Elements(extrinsics as u64));
+
+		group.bench_function(BenchmarkId::new("sequential", extrinsics), |b| {
+			b.iter(|| {
+				execute(&block.backends[0], &block.seed, || {
+					(0..block.extrinsics.len()).for_each(|index| apply(index, &block.extrinsics))
+				})
+			})
+		});
+
+		group.bench_function(BenchmarkId::new("parallel", extrinsics), |b| {
+			let BenchBlock { backends, seed, extrinsics, segments } = &mut block;
+			let (seed, extrinsics, segments) = (&*seed, &*extrinsics, &*segments);
+			b.iter(|| {
+				workers.install(|| {
+					segments.par_iter().zip(backends.par_iter_mut()).for_each(|(indices, backend)| {
+						execute(backend, seed, || indices.iter().for_each(|&index| apply(index, extrinsics)));
+					})
+				})
+			})
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, bench_segment_pre_execution);
+criterion_main!(benches);
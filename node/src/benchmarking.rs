@@ -109,6 +109,8 @@ pub fn create_benchmark_extrinsic(
 		.checked_next_power_of_two()
 		.map(|c| c / 2)
 		.unwrap_or(2) as u64;
+	let account = sp_runtime::AccountId32::from(sender.public());
+	let shard = pallet_sharding::Pallet::<runtime::Runtime>::get_account_shard(&account);
 	let tx_ext: runtime::TxExtension = (
 		frame_system::AuthorizeCall::<runtime::Runtime>::new(),
 		frame_system::CheckNonZeroSender::<runtime::Runtime>::new(),
@@ -119,10 +121,11 @@ pub fn create_benchmark_extrinsic(
 			period,
 			best_block.saturated_into(),
 		)),
-		frame_system::CheckNonce::<runtime::Runtime>::from(nonce),
+		pallet_sharding::CheckShardNonce::<runtime::Runtime>::global(nonce),
 		frame_system::CheckWeight::<runtime::Runtime>::new(),
-		pallet_transaction_payment::ChargeTransactionPayment::<runtime::Runtime>::from(0),
+		pallet_asset_tx_payment::ChargeAssetTxPayment::<runtime::Runtime>::from(0, None),
 		frame_metadata_hash_extension::CheckMetadataHash::<runtime::Runtime>::new(false),
+		pallet_sharding::CheckShardAffinity::<runtime::Runtime>::new(shard),
 		frame_system::WeightReclaim::<runtime::Runtime>::new(),
 	);
 
@@ -141,13 +144,14 @@ pub fn create_benchmark_extrinsic(
 			(),
 			None,
 			(),
+			(),
 		),
 	);
 	let signature = raw_payload.using_encoded(|e| sender.sign(e));
 
 	runtime::UncheckedExtrinsic::new_signed(
 		call,
-		sp_runtime::AccountId32::from(sender.public()).into(),
+		account.into(),
 		runtime::Signature::Sr25519(signature),
 		tx_ext,
 	)
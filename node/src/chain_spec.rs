@@ -195,3 +195,16 @@ pub fn local_chain_spec() -> Result<ChainSpec, String> {
 		None,
 	))
 }
+
+pub fn shard_dev_chain_spec() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::builder(wasm_binary, None)
+		.with_name("Netchain Shard Devnet")
+		.with_id("netchain_shard_dev")
+		.with_chain_type(ChainType::Local)
+		// Sharding, oracle sources and a loopback IBC channel pair set up at genesis
+		.with_genesis_config_preset_name(netchain_runtime::genesis_config_presets::SHARD_DEV_RUNTIME_PRESET)
+		.with_protocol_id("netchain-shard-dev")
+		.build())
+}
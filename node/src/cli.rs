@@ -5,6 +5,12 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: sc_cli::RunCmd,
+
+	/// Keep the off-chain history (execution results, oracle aggregates) of this many blocks
+	/// behind the finalized one, dropping older blocks; all of it is kept if unset. Needs
+	/// `--enable-offchain-indexing true`.
+	#[arg(long, value_name = "BLOCKS")]
+	pub history_retention: Option<u32>,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -49,4 +55,13 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Export the accounts, balances and cross-shard queue of a shard to JSON.
+	ExportShard(crate::shard_export::ExportShardCmd),
+
+	/// Import a shard export into the genesis of a chain spec, for test networks.
+	ImportShard(crate::shard_export::ImportShardCmd),
+
+	/// Re-execute a block sequentially and through the parallel scheduler and compare state roots.
+	VerifyParallel(crate::verify_parallel::VerifyParallelCmd),
 }
@@ -0,0 +1,102 @@
+//! Prometheus export of the parallel executor's load.
+//!
+//! On every imported best block the executor's metrics and queue depth are read through
+//! `ParallelExecutorApi` and published as gauges on the node's Prometheus registry, so
+//! operators can alert on a saturated executor. The conflict rate is derived from the growth
+//! of the cumulative conflict counter between two readings.
+
+use crate::service::FullClient;
+use futures::StreamExt;
+use netchain_runtime::opaque::Block;
+use pallet_parallel_executor::runtime_api::ParallelExecutorApi;
+use sc_client_api::BlockchainEvents;
+use sp_api::ProvideRuntimeApi;
+use std::{sync::Arc, time::Instant};
+use substrate_prometheus_endpoint::{register, Gauge, PrometheusError, Registry, F64, U64};
+
+const LOG_TARGET: &str = "executor-metrics";
+
+/// Gauges derived from the parallel executor's on-chain state
+pub struct ExecutorMetrics {
+	batches_pending: Gauge<U64>,
+	transactions_pending: Gauge<U64>,
+	conflicts_per_second: Gauge<F64>,
+	efficiency: Gauge<U64>,
+	active_workers: Gauge<U64>,
+}
+
+impl ExecutorMetrics {
+	/// Register the gauges with `registry`
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			batches_pending: register(
+				Gauge::new("netchain_executor_batches_pending", "Batches waiting in the parallel executor")?,
+				registry,
+			)?,
+			transactions_pending: register(
+				Gauge::new(
+					"netchain_executor_transactions_pending",
+					"Transactions across the pending parallel executor batches",
+				)?,
+				registry,
+			)?,
+			conflicts_per_second: register(
+				Gauge::new(
+					"netchain_executor_conflicts_per_second",
+					"Transaction conflicts recorded by the parallel executor per second",
+				)?,
+				registry,
+			)?,
+			efficiency: register(
+				Gauge::new(
+					"netchain_executor_efficiency_percent",
+					"Rolling share of successfully executed transactions per batch",
+				)?,
+				registry,
+			)?,
+			active_workers: register(
+				Gauge::new("netchain_executor_active_workers", "Configured parallel executor workers")?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Update the gauges on every new best block until the import stream ends
+	pub async fn run(self, client: Arc<FullClient>) {
+		let mut imports = client.import_notification_stream();
+		let mut last_conflicts: Option<(u32, Instant)> = None;
+
+		while let Some(notification) = imports.next().await {
+			if !notification.is_new_best {
+				continue;
+			}
+
+			let api = client.runtime_api();
+			let (metrics, depth) = match (
+				api.metrics(notification.hash),
+				api.queue_depth(notification.hash),
+			) {
+				(Ok(metrics), Ok(depth)) => (metrics, depth),
+				(Err(e), _) | (_, Err(e)) => {
+					log::debug!(target: LOG_TARGET, "Failed to read executor metrics: {e}");
+					continue;
+				},
+			};
+
+			self.batches_pending.set(depth.pending_batches.into());
+			self.transactions_pending.set(depth.pending_transactions.into());
+			self.efficiency.set(metrics.parallel_efficiency.into());
+			self.active_workers.set(metrics.active_workers.into());
+
+			let now = Instant::now();
+			if let Some((conflicts, at)) = last_conflicts {
+				let elapsed = now.duration_since(at).as_secs_f64();
+				if elapsed > 0.0 {
+					let new_conflicts = metrics.conflicts_resolved.saturating_sub(conflicts);
+					self.conflicts_per_second.set(new_conflicts as f64 / elapsed);
+				}
+			}
+			last_conflicts = Some((metrics.conflicts_resolved, now));
+		}
+	}
+}
@@ -0,0 +1,50 @@
+//! Netchain health in Substrate telemetry.
+//!
+//! On every imported best block the runtime's `HealthApi` summary is read and sent as a
+//! `netchain.health` telemetry message next to the standard block import messages, so the
+//! telemetry dashboard shows cross-shard queue depths, unusable IBC clients and stale oracle
+//! feeds for each node, not just its block height.
+
+use crate::service::FullClient;
+use futures::StreamExt;
+use netchain_runtime::health::HealthApi;
+use sc_client_api::BlockchainEvents;
+use sc_telemetry::{telemetry, TelemetryHandle, SUBSTRATE_INFO};
+use sp_api::ProvideRuntimeApi;
+use sp_runtime::traits::Header as _;
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "health-telemetry";
+
+/// Send the health summary of every new best block to `telemetry` until the import stream ends
+pub async fn run(client: Arc<FullClient>, telemetry: TelemetryHandle) {
+	let mut imports = client.import_notification_stream();
+
+	while let Some(notification) = imports.next().await {
+		if !notification.is_new_best {
+			continue;
+		}
+
+		let health = match client.runtime_api().health_summary(notification.hash) {
+			Ok(health) => health,
+			Err(e) => {
+				log::debug!(target: LOG_TARGET, "Failed to read health summary: {e}");
+				continue;
+			},
+		};
+
+		telemetry!(
+			Some(telemetry.clone());
+			SUBSTRATE_INFO;
+			"netchain.health";
+			"height" => *notification.header.number(),
+			"best" => ?notification.hash,
+			"shard_queue_depths" => health.shard_queue_depths,
+			"overloaded_shards" => health.overloaded_shards,
+			"pending_batches" => health.pending_batches,
+			"frozen_clients" => health.frozen_clients,
+			"expired_clients" => health.expired_clients,
+			"stale_feeds" => health.stale_feeds,
+		);
+	}
+}
@@ -5,8 +5,15 @@ mod benchmarking;
 mod chain_spec;
 mod cli;
 mod command;
+mod executor_metrics;
+mod health_telemetry;
+mod offchain_history;
+mod parallel_import;
 mod rpc;
 mod service;
+mod shard_export;
+mod shard_pool;
+mod verify_parallel;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -0,0 +1,79 @@
+//! Retention of the off-chain history.
+//!
+//! With `--enable-offchain-indexing true` the parallel executor and oracle pallets mirror
+//! execution results and oracle aggregates into the off-chain database as blocks are imported
+//! (see `netchain_primitives::offchain_history`), where `history_*` RPC methods read them.
+//! With `--history-retention <BLOCKS>` the node drops the items of every block more than that
+//! many blocks behind the finalized one, walking each block's journal; without it the history
+//! is kept forever.
+//!
+//! Progress is stored next to the history, so a restarted node resumes where it stopped. A
+//! node enabling retention on an existing database walks the journals from genesis, at most
+//! [`MAX_BLOCKS_PER_FINALITY`] blocks per finalized block. Blocks of abandoned forks journal
+//! under the same numbers as the canonical ones and are dropped with them.
+
+use crate::service::FullClient;
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use netchain_primitives::offchain_history::{journal_key, PREFIX, JOURNALED};
+use sc_client_api::BlockchainEvents;
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use sp_runtime::traits::Header as _;
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "offchain-history";
+
+/// Blocks pruned at most per finality notification, so catching up does not stall the task
+pub const MAX_BLOCKS_PER_FINALITY: u32 = 4096;
+
+/// Key of the first block whose history has not been pruned yet
+fn pruned_until_key() -> Vec<u8> {
+	[PREFIX, b"pruned_until"].concat()
+}
+
+/// Drop the history of blocks more than `retention` blocks behind the finalized block, on every
+/// finality notification until the stream ends
+pub async fn run<S: OffchainStorage>(client: Arc<FullClient>, mut storage: S, retention: u32) {
+	let mut finality = client.finality_notification_stream();
+
+	while let Some(notification) = finality.next().await {
+		let Some(horizon) = notification.header.number().checked_sub(retention) else { continue };
+
+		let mut next = storage
+			.get(STORAGE_PREFIX, &pruned_until_key())
+			.and_then(|value| u32::decode(&mut &value[..]).ok())
+			.unwrap_or_default();
+		let until = horizon.min(next.saturating_add(MAX_BLOCKS_PER_FINALITY));
+		if next >= until {
+			continue;
+		}
+
+		let mut items = 0;
+		while next < until {
+			items += prune_block(&mut storage, next);
+			next += 1;
+		}
+		storage.set(STORAGE_PREFIX, &pruned_until_key(), &next.encode());
+		log::debug!(target: LOG_TARGET, "Pruned {items} history items of blocks before #{next}");
+	}
+}
+
+/// Remove the items journaled in `block`, and its journal, returning how many were journaled
+fn prune_block<S: OffchainStorage>(storage: &mut S, block: u32) -> usize {
+	let mut pruned = 0;
+	for table in JOURNALED {
+		for n in 0u32.. {
+			let journal = journal_key(table, block, n);
+			let Some(key) = storage.get(STORAGE_PREFIX, &journal) else { break };
+
+			// An item overwritten in a later block is that block's to drop
+			let written_in = storage.get(STORAGE_PREFIX, &key).and_then(|value| u32::decode(&mut &value[..]).ok());
+			if written_in == Some(block) {
+				storage.remove(STORAGE_PREFIX, &key);
+			}
+			storage.remove(STORAGE_PREFIX, &journal);
+			pruned += 1;
+		}
+	}
+	pruned
+}
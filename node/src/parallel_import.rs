@@ -0,0 +1,203 @@
+//! Shard-segmented pre-execution stage for block import.
+//!
+//! Before a block received from the network is executed, its extrinsics are split into
+//! per-shard segments by their `CheckShardAffinity` tag. After the block is initialized and its
+//! leading untagged extrinsics (the inherents) applied, every segment is executed natively on
+//! a rayon pool, one worker per shard, each on its own overlay of the parent state. Extrinsics
+//! of different shards have different signers, so the segments do not contend for nonces or for
+//! the balances fees are drawn from. System transactions are left out: their segment would
+//! share signers with the others.
+//!
+//! The sequential execution by the inner block import stays authoritative and commits the
+//! block. What the pre-execution gives it is the state it needs: every value the segments read
+//! is fetched from the database by the workers concurrently and kept in the shared trie cache,
+//! so the sequential execution reads it from memory. A segment that writes keys another one
+//! also writes, beyond the per-block bookkeeping of `frame_system` and the total issuance fees
+//! are burnt from, did not run on the state it will see in the block; it is still pre-executed,
+//! as most of what it reads is the same, and reported in the debug log.
+//!
+//! Pre-execution never rejects a block: extrinsics it cannot apply are skipped and left to the
+//! sequential execution, and blocks are imported as usual when the native runtime differs from
+//! the one on chain or the stage fails.
+
+use crate::{
+	service::FullClient,
+	shard_pool::extrinsic_load,
+	verify_parallel::{is_bookkeeping, replay, Changes},
+};
+use codec::{Decode, Encode};
+use frame_support::storage::StorageValue;
+use netchain_primitives::{ShardId, SHARD_COUNT, SYSTEM_SHARD};
+use netchain_runtime::{opaque::Block, Executive, Runtime, UncheckedExtrinsic, VERSION};
+use rayon::prelude::*;
+use sc_consensus::{BlockCheckParams, BlockImport, BlockImportParams, ImportResult, StateAction};
+use sp_consensus::Error as ConsensusError;
+use sp_core::storage::well_known_keys::EXTRINSIC_INDEX;
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT},
+	OpaqueExtrinsic,
+};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	panic::{self, AssertUnwindSafe},
+	sync::Arc,
+	time::Instant,
+};
+
+const LOG_TARGET: &str = "shard-import";
+
+/// A block's extrinsics, by index, in the order the stage runs them
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Segments {
+	/// Untagged extrinsics before the first tagged one, applied before the segments
+	pub inherents: Vec<usize>,
+	/// Tagged extrinsics by shard, in block order
+	pub shards: BTreeMap<ShardId, Vec<usize>>,
+	/// Extrinsics left to the sequential execution: system transactions and untagged
+	/// extrinsics after the first tagged one
+	pub skipped: usize,
+}
+
+/// Split a block's extrinsics into the inherents and a segment per shard
+pub fn segment_by_shard(extrinsics: &[OpaqueExtrinsic]) -> Segments {
+	let mut segments = Segments::default();
+	for (index, xt) in extrinsics.iter().enumerate() {
+		match extrinsic_load(xt).and_then(|(shard, _)| shard) {
+			Some(shard) if shard != SYSTEM_SHARD => segments.shards.entry(shard).or_default().push(index),
+			None if segments.shards.is_empty() && segments.skipped == 0 => segments.inherents.push(index),
+			_ => segments.skipped += 1,
+		}
+	}
+	segments
+}
+
+/// Keys a pre-executed segment wrote that may conflict with another segment
+fn conflicting_writes(seed: &Changes, after: Changes) -> BTreeSet<Vec<u8>> {
+	let total_issuance = pallet_balances::TotalIssuance::<Runtime>::hashed_key();
+	after
+		.into_iter()
+		.filter(|(key, value)| seed.get(key) != Some(value))
+		.map(|(key, _)| key)
+		.filter(|key| !is_bookkeeping(key) && key[..] != total_issuance[..])
+		.collect()
+}
+
+/// Segments writing a key another segment also writes
+fn count_conflicts(writes: &[BTreeSet<Vec<u8>>]) -> usize {
+	(0..writes.len())
+		.filter(|&i| (0..writes.len()).any(|j| i != j && !writes[i].is_disjoint(&writes[j])))
+		.count()
+}
+
+/// Block import that pre-executes shard segments in parallel before delegating to `inner`
+pub struct ShardSegmentedImport<I> {
+	inner: I,
+	client: Arc<FullClient>,
+	workers: Arc<rayon::ThreadPool>,
+}
+
+impl<I> ShardSegmentedImport<I> {
+	/// Wrap `inner` with a worker per shard
+	pub fn new(inner: I, client: Arc<FullClient>) -> Result<Self, rayon::ThreadPoolBuildError> {
+		let workers = rayon::ThreadPoolBuilder::new()
+			.num_threads(SHARD_COUNT as usize)
+			.thread_name(|index| format!("shard-import-{index}"))
+			.build()?;
+		Ok(Self { inner, client, workers: Arc::new(workers) })
+	}
+
+	/// Pre-execute the shard segments of the block with `header` and `body`
+	fn pre_execute(&self, header: &<Block as BlockT>::Header, body: &[OpaqueExtrinsic]) -> Result<(), String> {
+		let segments = segment_by_shard(body);
+		if segments.shards.len() < 2 {
+			// Nothing to gain over the sequential execution
+			return Ok(());
+		}
+
+		let parent = *header.parent_hash();
+		let on_chain = self.client.runtime_version_at(parent).map_err(|e| e.to_string())?;
+		if on_chain.spec_name != VERSION.spec_name || on_chain.spec_version != VERSION.spec_version {
+			return Err(format!("native runtime differs from {}-{}", on_chain.spec_name, on_chain.spec_version));
+		}
+
+		let started = Instant::now();
+		let extrinsics = body
+			.iter()
+			.map(|xt| UncheckedExtrinsic::decode(&mut &xt.encode()[..]))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("undecodable extrinsic: {e}"))?;
+
+		let client = &self.client;
+		let seed = replay(client, parent, &Changes::new(), || {
+			Executive::initialize_block(header);
+			for &index in &segments.inherents {
+				Executive::apply_extrinsic(extrinsics[index].clone())
+					.map_err(|e| format!("inherent {index} is invalid: {e:?}"))?;
+			}
+			Ok(())
+		})?;
+
+		let writes = self.workers.install(|| {
+			segments
+				.shards
+				.par_iter()
+				.map(|(_, indices)| {
+					let after = replay(client, parent, &seed, || {
+						for &index in indices {
+							sp_io::storage::set(EXTRINSIC_INDEX, &(index as u32).encode());
+							// Left to the sequential execution to reject
+							let _ = Executive::apply_extrinsic(extrinsics[index].clone());
+						}
+						Ok(())
+					})?;
+					Ok(conflicting_writes(&seed, after))
+				})
+				.collect::<Result<Vec<_>, String>>()
+		})?;
+
+		log::debug!(
+			target: LOG_TARGET,
+			"Pre-executed {} extrinsics in {} shard segments in {:?}, {} conflicting, {} left to sequential execution",
+			segments.shards.values().map(Vec::len).sum::<usize>(),
+			segments.shards.len(),
+			started.elapsed(),
+			count_conflicts(&writes),
+			segments.skipped,
+		);
+		Ok(())
+	}
+}
+
+#[async_trait::async_trait]
+impl<I> BlockImport<Block> for ShardSegmentedImport<I>
+where
+	I: BlockImport<Block, Error = ConsensusError> + Send + Sync,
+{
+	type Error = ConsensusError;
+
+	async fn check_block(
+		&self,
+		block: BlockCheckParams<Block>,
+	) -> Result<ImportResult, Self::Error> {
+		self.inner.check_block(block).await
+	}
+
+	async fn import_block(
+		&self,
+		block: BlockImportParams<Block>,
+	) -> Result<ImportResult, Self::Error> {
+		// Locally authored blocks arrive already executed, and blocks imported without their
+		// parent state cannot be pre-executed
+		let executes = matches!(block.state_action, StateAction::Execute);
+		if let (true, Some(body)) = (executes, &block.body) {
+			// A panicking extrinsic must not take the import down with it
+			let result = panic::catch_unwind(AssertUnwindSafe(|| self.pre_execute(&block.header, body)))
+				.unwrap_or_else(|_| Err("pre-execution panicked".into()));
+			if let Err(e) = result {
+				log::debug!(target: LOG_TARGET, "Importing {:?} without pre-execution: {e}", block.post_hash());
+			}
+		}
+
+		self.inner.import_block(block).await
+	}
+}
@@ -7,42 +7,74 @@
 
 use std::sync::Arc;
 
+pub mod health;
+pub mod history;
+pub mod shard_sync;
+pub mod trace;
+pub mod validator;
+
 use jsonrpsee::RpcModule;
 use sc_transaction_pool_api::TransactionPool;
-use netchain_runtime::{opaque::Block, AccountId, Balance, Nonce};
-use sp_api::ProvideRuntimeApi;
+use netchain_runtime::{opaque::Block, AccountId, Balance, Hash, Nonce};
+use sc_client_api::{BlockBackend, ProofProvider};
+use sp_api::{CallApiAt, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_core::offchain::OffchainStorage;
+use sp_keystore::KeystorePtr;
 
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, S, O> {
 	/// The client instance to use.
 	pub client: Arc<C>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
+	/// Keystore that rotated session keys are stored in.
+	pub keystore: KeystorePtr,
+	/// Sync state of the node.
+	pub sync: Arc<S>,
+	/// Off-chain database holding the indexed history, if off-chain indexing is enabled.
+	pub offchain_storage: Option<O>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(
-	deps: FullDeps<C, P>,
+pub fn create_full<C, P, S, O>(
+	deps: FullDeps<C, P, S, O>,
 ) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
 where
-	C: ProvideRuntimeApi<Block>,
+	C: ProvideRuntimeApi<Block> + ProofProvider<Block> + BlockBackend<Block> + CallApiAt<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: sp_session::SessionKeys<Block>,
+	C::Api: pallet_sharding::runtime_api::ShardingApi<Block, AccountId, Balance, Hash>,
+	C::Api: netchain_runtime::health::HealthApi<Block>,
+	C::Api: pallet_parallel_executor::runtime_api::ParallelExecutorApi<Block>,
+	C::Api: netchain_runtime::trace::TraceApi<Block, netchain_runtime::RuntimeEvent>,
 	P: TransactionPool + 'static,
+	S: sp_consensus::SyncOracle + Send + Sync + 'static,
+	O: OffchainStorage + 'static,
 {
+	use health::{Health, NetchainHealthApiServer};
+	use history::{History, HistoryApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use shard_sync::{ShardSync, ShardSyncApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
+	use trace::{Trace, TraceApiServer};
+	use validator::{Validator, ValidatorApiServer};
 
 	let mut module = RpcModule::new(());
-	let FullDeps { client, pool } = deps;
+	let FullDeps { client, pool, keystore, sync, offchain_storage } = deps;
 
 	module.merge(System::new(client.clone(), pool).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	module.merge(Health::new(client.clone(), sync).into_rpc())?;
+	module.merge(History::new(offchain_storage).into_rpc())?;
+	module.merge(ShardSync::new(client.clone()).into_rpc())?;
+	module.merge(Trace::new(client.clone()).into_rpc())?;
+	module.merge(Validator::new(client, keystore).into_rpc())?;
 
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
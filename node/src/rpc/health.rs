@@ -0,0 +1,107 @@
+//! Consolidated node and chain health for status pages and load balancers.
+//!
+//! `netchain_health` combines what the node knows about itself (whether it is major syncing,
+//! how far finality trails its best block) with the runtime's `HealthApi` summary and the
+//! parallel executor's efficiency at the best block, in one JSON document. `healthy` is what a
+//! load balancer should check: the node is caught up and finality is no more than
+//! [`MAX_FINALIZED_LAG`] blocks behind.
+
+use std::sync::Arc;
+
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, types::error::ErrorObjectOwned};
+use netchain_runtime::{health::HealthApi, opaque::Block, BlockNumber};
+use pallet_parallel_executor::runtime_api::ParallelExecutorApi;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::SyncOracle;
+
+/// Blocks finality may trail the best block by before the node reports itself unhealthy
+pub const MAX_FINALIZED_LAG: BlockNumber = 32;
+
+/// Health document returned by `netchain_health`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetchainHealth {
+	/// Whether the node is caught up and finality keeps up with it
+	pub healthy: bool,
+	/// Whether the node is major syncing
+	pub syncing: bool,
+	/// Best block number
+	pub best_number: BlockNumber,
+	/// Finalized block number
+	pub finalized_number: BlockNumber,
+	/// Blocks between the best and the finalized block
+	pub finalized_lag: BlockNumber,
+	/// Cross-shard transfers waiting to settle, indexed by shard
+	pub shard_queue_depths: Vec<u32>,
+	/// Shards whose load has reached their capacity
+	pub overloaded_shards: u32,
+	/// IBC clients frozen after misbehaviour
+	pub frozen_clients: u32,
+	/// IBC clients past their unbonding period
+	pub expired_clients: u32,
+	/// Aggregated oracle values older than the oracle's `MaxDataAge`
+	pub stale_feeds: u32,
+	/// Batches waiting in the parallel executor
+	pub pending_batches: u32,
+	/// Rolling share of successfully executed transactions per batch, in percent
+	pub parallel_efficiency: u8,
+}
+
+/// Node health RPC methods
+#[rpc(client, server)]
+pub trait NetchainHealthApi {
+	/// Node and chain health at the best block
+	#[method(name = "netchain_health")]
+	fn health(&self) -> RpcResult<NetchainHealth>;
+}
+
+/// Implementation of [`NetchainHealthApiServer`]
+pub struct Health<C, S> {
+	client: Arc<C>,
+	sync: Arc<S>,
+}
+
+impl<C, S> Health<C, S> {
+	/// Create the RPC handler over `client`, reading the sync state from `sync`
+	pub fn new(client: Arc<C>, sync: Arc<S>) -> Self {
+		Self { client, sync }
+	}
+}
+
+fn rpc_error(message: impl ToString) -> ErrorObjectOwned {
+	ErrorObjectOwned::owned(1, message.to_string(), None::<()>)
+}
+
+impl<C, S> NetchainHealthApiServer for Health<C, S>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: HealthApi<Block> + ParallelExecutorApi<Block>,
+	S: SyncOracle + Send + Sync + 'static,
+{
+	fn health(&self) -> RpcResult<NetchainHealth> {
+		let info = self.client.info();
+		let api = self.client.runtime_api();
+		let summary = api.health_summary(info.best_hash).map_err(rpc_error)?;
+		let executor = api.metrics(info.best_hash).map_err(rpc_error)?;
+
+		let syncing = self.sync.is_major_syncing();
+		let finalized_lag = info.best_number.saturating_sub(info.finalized_number);
+
+		Ok(NetchainHealth {
+			healthy: !syncing && finalized_lag <= MAX_FINALIZED_LAG,
+			syncing,
+			best_number: info.best_number,
+			finalized_number: info.finalized_number,
+			finalized_lag,
+			shard_queue_depths: summary.shard_queue_depths,
+			overloaded_shards: summary.overloaded_shards,
+			frozen_clients: summary.frozen_clients,
+			expired_clients: summary.expired_clients,
+			stale_feeds: summary.stale_feeds,
+			pending_batches: summary.pending_batches,
+			parallel_efficiency: executor.parallel_efficiency,
+		})
+	}
+}
@@ -0,0 +1,134 @@
+//! Execution results and oracle aggregates kept in the off-chain database.
+//!
+//! The parallel executor and oracle pallets mirror these into the off-chain database of nodes
+//! running with `--enable-offchain-indexing true`, as laid out in
+//! `netchain_primitives::offchain_history`. They remain available here after the on-chain
+//! copies are overwritten and the state holding them is pruned, until the node's
+//! `--history-retention` window drops them.
+
+use codec::Decode;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, types::error::ErrorObjectOwned};
+use netchain_primitives::offchain_history::{
+	execution_result_key, oracle_latest_key, oracle_round_key, Indexed,
+};
+use netchain_runtime::{BlockNumber, Hash};
+use pallet_oracle::Observation;
+use pallet_parallel_executor::ExecutionResult;
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	offchain::{OffchainStorage, STORAGE_PREFIX},
+	Bytes,
+};
+
+/// Most aggregates `history_oracleRounds` returns
+pub const MAX_ORACLE_ROUNDS: u32 = 1024;
+
+/// Execution result returned by `history_executionResult`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionRecord {
+	/// Block the result was reported in
+	pub block_number: BlockNumber,
+	/// Whether the transaction executed successfully
+	pub success: bool,
+	/// Gas the transaction used
+	pub gas_used: u64,
+	/// Error the transaction failed with
+	pub error: Option<Bytes>,
+}
+
+/// Oracle aggregate returned by `history_oracleRounds`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OracleRound {
+	/// Position of the aggregate in the data key's history, from its first aggregate
+	pub index: u64,
+	/// Aggregated value
+	pub value: Bytes,
+	/// Average confidence score of the sources
+	pub confidence: u8,
+	/// Block the value was aggregated at
+	pub aggregated_at: BlockNumber,
+}
+
+/// Off-chain history RPC methods
+#[rpc(client, server)]
+pub trait HistoryApi {
+	/// Execution result reported for transaction `tx_hash`
+	#[method(name = "history_executionResult")]
+	fn execution_result(&self, tx_hash: Hash) -> RpcResult<Option<ExecutionRecord>>;
+
+	/// Up to `count` aggregates of `data_key` before position `before`, the newest ones by
+	/// default, newest first
+	#[method(name = "history_oracleRounds")]
+	fn oracle_rounds(&self, data_key: Bytes, count: u32, before: Option<u64>) -> RpcResult<Vec<OracleRound>>;
+}
+
+/// Implementation of [`HistoryApiServer`]
+pub struct History<S> {
+	storage: Option<S>,
+}
+
+impl<S> History<S> {
+	/// Create the RPC handler over the off-chain `storage`, `None` when indexing is disabled
+	pub fn new(storage: Option<S>) -> Self {
+		Self { storage }
+	}
+}
+
+fn rpc_error(message: impl ToString) -> ErrorObjectOwned {
+	ErrorObjectOwned::owned(1, message.to_string(), None::<()>)
+}
+
+impl<S: OffchainStorage> History<S> {
+	/// Decoded value stored under `key`
+	fn get<T: Decode>(&self, key: &[u8]) -> RpcResult<Option<T>> {
+		let storage = self
+			.storage
+			.as_ref()
+			.ok_or_else(|| rpc_error("off-chain indexing is disabled; restart with --enable-offchain-indexing true"))?;
+		storage
+			.get(STORAGE_PREFIX, key)
+			.map(|value| T::decode(&mut &value[..]))
+			.transpose()
+			.map_err(|e| rpc_error(format!("undecodable history item: {e}")))
+	}
+}
+
+impl<S: OffchainStorage + 'static> HistoryApiServer for History<S> {
+	fn execution_result(&self, tx_hash: Hash) -> RpcResult<Option<ExecutionRecord>> {
+		let indexed: Option<Indexed<ExecutionResult<Hash>>> = self.get(&execution_result_key(tx_hash.as_ref()))?;
+		Ok(indexed.map(|Indexed { block, item }| ExecutionRecord {
+			block_number: block,
+			success: item.success,
+			gas_used: item.gas_used,
+			error: item.error.map(Into::into),
+		}))
+	}
+
+	fn oracle_rounds(&self, data_key: Bytes, count: u32, before: Option<u64>) -> RpcResult<Vec<OracleRound>> {
+		let end = match before {
+			Some(before) => before,
+			None => match self.get::<u64>(&oracle_latest_key(&data_key))? {
+				Some(latest) => latest.saturating_add(1),
+				None => return Ok(Vec::new()),
+			},
+		};
+
+		// Pruned aggregates end the walk
+		let mut rounds = Vec::new();
+		for index in (0..end).rev().take(count.min(MAX_ORACLE_ROUNDS) as usize) {
+			let Some(indexed) = self.get::<Indexed<Observation<BlockNumber>>>(&oracle_round_key(&data_key, index))?
+			else {
+				break;
+			};
+			rounds.push(OracleRound {
+				index,
+				value: indexed.item.value.into(),
+				confidence: indexed.item.confidence,
+				aggregated_at: indexed.item.aggregated_at,
+			});
+		}
+		Ok(rounds)
+	}
+}
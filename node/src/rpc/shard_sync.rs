@@ -0,0 +1,158 @@
+//! Per-shard state sync for validators joining a shard.
+//!
+//! `shardSync_snapshot` returns the accounts assigned to a shard and its cross-shard queue at
+//! a block, as read through `ShardingApi::shard_snapshot`, together with a storage proof of the
+//! shard's entry in `Sharding::ShardStateRoots`. A joining validator checks the proof against
+//! the state root of a header it knows to be finalized, e.g. with
+//! `netchain_light_client::LightClient::shard_snapshot`, and recomputes the commitment root from
+//! the snapshot, so it only has to trust finality rather than the node serving the snapshot
+//! or a full warp sync of every shard.
+//!
+//! `shardSync_dataChunk` serves one erasure-coded chunk of a shard's block data with its
+//! Merkle proof, the shard's `Sharding::ShardDataCommitments` entry and a storage proof of that
+//! entry, for validators sampling the availability of the shard's data with
+//! `pallet_sharding::availability::verify_chunk`.
+
+use std::sync::Arc;
+
+use codec::Encode;
+use frame_support::storage::StorageMap;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, types::error::ErrorObjectOwned};
+use netchain_primitives::{ShardId, SHARD_COUNT};
+use netchain_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Runtime};
+use pallet_sharding::{runtime_api::ShardingApi, DataChunk, DataCommitment, ShardDataCommitments, ShardStateRoots};
+use sc_client_api::ProofProvider;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+
+/// Shard snapshot returned by `shardSync_snapshot`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardSnapshotProof {
+	/// Hash of the block the snapshot was taken at
+	pub block_hash: Hash,
+	/// Number of the block the snapshot was taken at
+	pub block_number: BlockNumber,
+	/// SCALE-encoded `pallet_sharding::ShardSnapshot`
+	pub snapshot: Bytes,
+	/// Trie nodes proving the shard's `Sharding::ShardStateRoots` entry at the block
+	pub proof: Vec<Bytes>,
+}
+
+/// Availability chunk returned by `shardSync_dataChunk`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardDataChunkProof {
+	/// Hash of the block the chunk was read at
+	pub block_hash: Hash,
+	/// Number of the block the chunk was read at
+	pub block_number: BlockNumber,
+	/// Commitment of the shard's block data the chunk belongs to
+	pub commitment: DataCommitment<Hash>,
+	/// The chunk and its Merkle proof against `commitment.root`
+	pub chunk: DataChunk<Hash>,
+	/// Trie nodes proving the shard's `Sharding::ShardDataCommitments` entry at the block
+	pub proof: Vec<Bytes>,
+}
+
+/// Shard sync RPC methods
+#[rpc(client, server)]
+pub trait ShardSyncApi {
+	/// Snapshot of `shard_id` at block `at`, the latest finalized block by default
+	#[method(name = "shardSync_snapshot")]
+	fn snapshot(&self, shard_id: ShardId, at: Option<Hash>) -> RpcResult<ShardSnapshotProof>;
+
+	/// Availability chunk `index` of the block data of `shard_id` at block `at`, the latest
+	/// finalized block by default
+	#[method(name = "shardSync_dataChunk")]
+	fn data_chunk(&self, shard_id: ShardId, index: u16, at: Option<Hash>) -> RpcResult<ShardDataChunkProof>;
+}
+
+/// Implementation of [`ShardSyncApiServer`]
+pub struct ShardSync<C> {
+	client: Arc<C>,
+}
+
+impl<C> ShardSync<C> {
+	/// Create the RPC handler over `client`
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+fn rpc_error(message: impl ToString) -> ErrorObjectOwned {
+	ErrorObjectOwned::owned(1, message.to_string(), None::<()>)
+}
+
+impl<C: HeaderBackend<Block>> ShardSync<C> {
+	/// Hash and number of block `at`, the latest finalized block by default
+	fn block(&self, at: Option<Hash>) -> RpcResult<(Hash, BlockNumber)> {
+		let block_hash = at.unwrap_or_else(|| self.client.info().finalized_hash);
+		let block_number = self
+			.client
+			.number(block_hash)
+			.map_err(rpc_error)?
+			.ok_or_else(|| rpc_error(format!("block {block_hash:?} not found")))?;
+		Ok((block_hash, block_number))
+	}
+}
+
+impl<C> ShardSyncApiServer for ShardSync<C>
+where
+	C: ProvideRuntimeApi<Block> + ProofProvider<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: ShardingApi<Block, AccountId, Balance, Hash>,
+{
+	fn snapshot(&self, shard_id: ShardId, at: Option<Hash>) -> RpcResult<ShardSnapshotProof> {
+		if shard_id >= SHARD_COUNT {
+			return Err(rpc_error(format!("shard {shard_id} does not exist")));
+		}
+
+		let (block_hash, block_number) = self.block(at)?;
+
+		let snapshot = self.client.runtime_api().shard_snapshot(block_hash, shard_id).map_err(rpc_error)?;
+		let key = ShardStateRoots::<Runtime>::hashed_key_for(shard_id);
+		let proof = self
+			.client
+			.read_proof(block_hash, &mut std::iter::once(&key[..]))
+			.map_err(rpc_error)?;
+
+		Ok(ShardSnapshotProof {
+			block_hash,
+			block_number,
+			snapshot: snapshot.encode().into(),
+			proof: proof.into_iter_nodes().map(Into::into).collect(),
+		})
+	}
+	fn data_chunk(&self, shard_id: ShardId, index: u16, at: Option<Hash>) -> RpcResult<ShardDataChunkProof> {
+		if shard_id >= SHARD_COUNT {
+			return Err(rpc_error(format!("shard {shard_id} does not exist")));
+		}
+
+		let (block_hash, block_number) = self.block(at)?;
+
+		let api = self.client.runtime_api();
+		let commitment = api
+			.shard_data_commitment(block_hash, shard_id)
+			.map_err(rpc_error)?
+			.ok_or_else(|| rpc_error(format!("no data commitment for shard {shard_id} at {block_hash:?}")))?;
+		let chunk = api
+			.shard_data_chunk(block_hash, shard_id, index)
+			.map_err(rpc_error)?
+			.ok_or_else(|| rpc_error(format!("chunk {index} does not exist")))?;
+		let key = ShardDataCommitments::<Runtime>::hashed_key_for(shard_id);
+		let proof = self
+			.client
+			.read_proof(block_hash, &mut std::iter::once(&key[..]))
+			.map_err(rpc_error)?;
+
+		Ok(ShardDataChunkProof {
+			block_hash,
+			block_number,
+			commitment,
+			chunk,
+			proof: proof.into_iter_nodes().map(Into::into).collect(),
+		})
+	}
+}
@@ -0,0 +1,333 @@
+//! Replay of single extrinsics for debugging.
+//!
+//! `trace_extrinsic` replays one extrinsic of a block on the block's parent state and returns
+//! its dispatch outcome and events, as `netchain_runtime::trace::TraceApi` reports them, and the
+//! storage keys it read and wrote. The runtime cannot observe its own storage accesses, so the
+//! node replays the block natively a second time and records the accesses of the traced
+//! extrinsic through an [`AccessRecorder`] wrapped around the externalities.
+//!
+//! The per-block bookkeeping of `frame_system` that every extrinsic writes (events, extrinsic
+//! index, block weight and length) is left out of the key sets, as the parallel scheduler
+//! accumulates it outside the declared access lists; the events are returned separately.
+//! Replaying a block is expensive, so this is an unsafe method.
+
+use std::{any::TypeId, cell::RefCell, collections::BTreeSet, sync::Arc};
+
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::ErrorObjectOwned,
+	Extensions,
+};
+use netchain_runtime::{
+	opaque::Block,
+	trace::{self, TraceApi as TraceRuntimeApi},
+	Hash, RuntimeEvent, UncheckedExtrinsic,
+};
+use sc_client_api::BlockBackend;
+use sc_rpc_api::check_if_safe;
+use serde::{Deserialize, Serialize};
+use sp_api::{CallApiAt, ProvideRuntimeApi};
+use sp_core::{
+	storage::{ChildInfo, StateVersion, TrackedStorageKey},
+	Bytes,
+};
+use sp_externalities::{Extension, ExtensionStore, Externalities, MultiRemovalResults};
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, Header as HeaderT};
+use sp_state_machine::{Ext, OverlayedChanges};
+
+use crate::verify_parallel::is_bookkeeping;
+
+/// Extrinsic replayed by `trace_extrinsic`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicTrace {
+	/// Whether its call dispatched successfully
+	pub success: bool,
+	/// Dispatch error of its call, if it failed
+	pub error: Option<String>,
+	/// SCALE-encoded `RuntimeEvent`s it deposited, in order
+	pub events: Vec<Bytes>,
+	/// Storage keys it read, in key order
+	pub reads: Vec<Bytes>,
+	/// Storage keys it wrote or removed, and prefixes it cleared, in key order
+	pub writes: Vec<Bytes>,
+}
+
+/// Extrinsic tracing RPC methods
+#[rpc(client, server)]
+pub trait TraceApi {
+	/// Replay extrinsic `index` of block `block` and report its outcome, events and storage
+	/// accesses
+	#[method(name = "trace_extrinsic", with_extensions)]
+	fn trace_extrinsic(&self, block: Hash, index: u32) -> RpcResult<ExtrinsicTrace>;
+}
+
+/// Implementation of [`TraceApiServer`]
+pub struct Trace<C> {
+	client: Arc<C>,
+}
+
+impl<C> Trace<C> {
+	/// Create the RPC handler over `client`
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+fn rpc_error(message: impl ToString) -> ErrorObjectOwned {
+	ErrorObjectOwned::owned(1, message.to_string(), None::<()>)
+}
+
+impl<C> TraceApiServer for Trace<C>
+where
+	C: ProvideRuntimeApi<Block> + BlockBackend<Block> + CallApiAt<Block> + Send + Sync + 'static,
+	C::Api: TraceRuntimeApi<Block, RuntimeEvent>,
+{
+	fn trace_extrinsic(&self, ext: &Extensions, block: Hash, index: u32) -> RpcResult<ExtrinsicTrace> {
+		check_if_safe(ext)?;
+
+		let block = self
+			.client
+			.block(block)
+			.map_err(rpc_error)?
+			.ok_or_else(|| rpc_error(format!("block {block:?} not found")))?
+			.block;
+		let parent = *block.header().parent_hash();
+
+		let traced = self
+			.client
+			.runtime_api()
+			.trace_extrinsic(parent, block.clone(), index)
+			.map_err(rpc_error)?
+			.map_err(|e| rpc_error(format!("cannot trace extrinsic {index}: {e:?}")))?;
+		let (reads, writes) = self.record_accesses(parent, block, index).map_err(rpc_error)?;
+
+		let keys = |keys: BTreeSet<Vec<u8>>| -> Vec<Bytes> {
+			keys.into_iter().filter(|key| !is_bookkeeping(key)).map(Into::into).collect()
+		};
+		Ok(ExtrinsicTrace {
+			success: traced.outcome.is_ok(),
+			error: traced.outcome.err().map(|e| format!("{e:?}")),
+			events: traced.events.iter().map(|event| event.encode().into()).collect(),
+			reads: keys(reads),
+			writes: keys(writes),
+		})
+	}
+}
+
+impl<C: CallApiAt<Block>> Trace<C> {
+	/// Keys read and written by extrinsic `index` of `block`, replayed natively on `parent`
+	fn record_accesses(
+		&self,
+		parent: Hash,
+		block: Block,
+		index: u32,
+	) -> Result<(BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>), String> {
+		let (header, body) = block.deconstruct();
+		let extrinsics = body
+			.iter()
+			.map(|xt| UncheckedExtrinsic::decode(&mut &xt.encode()[..]))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("undecodable extrinsic: {e}"))?;
+		let traced = extrinsics.get(index as usize).cloned().ok_or("no such extrinsic")?;
+
+		let state = self.client.state_at(parent).map_err(|e| e.to_string())?;
+		let mut overlay = OverlayedChanges::<BlakeTwo256>::default();
+		let mut ext = Ext::new(&mut overlay, &state, None);
+		sp_externalities::set_and_run_with_externalities(&mut ext, || {
+			trace::prepare(&header, &extrinsics[..index as usize])
+		})
+		.map_err(|e| format!("{e:?}"))?;
+
+		let mut recorder = AccessRecorder::new(&mut ext);
+		sp_externalities::set_and_run_with_externalities(&mut recorder, || trace::apply(index, traced))
+			.map_err(|e| format!("{e:?}"))?;
+		Ok(recorder.into_accesses())
+	}
+}
+
+/// Externalities recording the storage keys read and written through them
+///
+/// Child trie accesses are recorded under the child trie's prefixed storage key.
+pub struct AccessRecorder<'a, E: Externalities> {
+	inner: &'a mut E,
+	reads: RefCell<BTreeSet<Vec<u8>>>,
+	writes: BTreeSet<Vec<u8>>,
+}
+
+impl<'a, E: Externalities> AccessRecorder<'a, E> {
+	/// Record the accesses made through `inner`
+	pub fn new(inner: &'a mut E) -> Self {
+		Self { inner, reads: Default::default(), writes: Default::default() }
+	}
+
+	/// Keys read and keys written
+	pub fn into_accesses(self) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
+		(self.reads.into_inner(), self.writes)
+	}
+
+	fn read(&self, key: &[u8]) {
+		self.reads.borrow_mut().insert(key.to_vec());
+	}
+
+	fn read_child(&self, child_info: &ChildInfo) {
+		self.reads.borrow_mut().insert(child_info.prefixed_storage_key().into_inner());
+	}
+
+	fn write_child(&mut self, child_info: &ChildInfo) {
+		self.writes.insert(child_info.prefixed_storage_key().into_inner());
+	}
+}
+
+impl<E: Externalities> ExtensionStore for AccessRecorder<'_, E> {
+	fn extension_by_type_id(&mut self, type_id: TypeId) -> Option<&mut dyn std::any::Any> {
+		self.inner.extension_by_type_id(type_id)
+	}
+
+	fn register_extension_with_type_id(
+		&mut self,
+		type_id: TypeId,
+		extension: Box<dyn Extension>,
+	) -> Result<(), sp_externalities::Error> {
+		self.inner.register_extension_with_type_id(type_id, extension)
+	}
+
+	fn deregister_extension_by_type_id(&mut self, type_id: TypeId) -> Result<(), sp_externalities::Error> {
+		self.inner.deregister_extension_by_type_id(type_id)
+	}
+}
+
+impl<E: Externalities> Externalities for AccessRecorder<'_, E> {
+	fn set_offchain_storage(&mut self, key: &[u8], value: Option<&[u8]>) {
+		self.inner.set_offchain_storage(key, value)
+	}
+
+	fn storage(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.read(key);
+		self.inner.storage(key)
+	}
+
+	fn storage_hash(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.read(key);
+		self.inner.storage_hash(key)
+	}
+
+	fn child_storage_hash(&self, child_info: &ChildInfo, key: &[u8]) -> Option<Vec<u8>> {
+		self.read_child(child_info);
+		self.inner.child_storage_hash(child_info, key)
+	}
+
+	fn child_storage(&self, child_info: &ChildInfo, key: &[u8]) -> Option<Vec<u8>> {
+		self.read_child(child_info);
+		self.inner.child_storage(child_info, key)
+	}
+
+	fn next_storage_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+		let next = self.inner.next_storage_key(key);
+		if let Some(next) = &next {
+			self.read(next);
+		}
+		next
+	}
+
+	fn next_child_storage_key(&self, child_info: &ChildInfo, key: &[u8]) -> Option<Vec<u8>> {
+		self.read_child(child_info);
+		self.inner.next_child_storage_key(child_info, key)
+	}
+
+	fn kill_child_storage(
+		&mut self,
+		child_info: &ChildInfo,
+		maybe_limit: Option<u32>,
+		maybe_cursor: Option<&[u8]>,
+	) -> MultiRemovalResults {
+		self.write_child(child_info);
+		self.inner.kill_child_storage(child_info, maybe_limit, maybe_cursor)
+	}
+
+	fn clear_prefix(
+		&mut self,
+		prefix: &[u8],
+		maybe_limit: Option<u32>,
+		maybe_cursor: Option<&[u8]>,
+	) -> MultiRemovalResults {
+		self.writes.insert(prefix.to_vec());
+		self.inner.clear_prefix(prefix, maybe_limit, maybe_cursor)
+	}
+
+	fn clear_child_prefix(
+		&mut self,
+		child_info: &ChildInfo,
+		prefix: &[u8],
+		maybe_limit: Option<u32>,
+		maybe_cursor: Option<&[u8]>,
+	) -> MultiRemovalResults {
+		self.write_child(child_info);
+		self.inner.clear_child_prefix(child_info, prefix, maybe_limit, maybe_cursor)
+	}
+
+	fn place_storage(&mut self, key: Vec<u8>, value: Option<Vec<u8>>) {
+		self.writes.insert(key.clone());
+		self.inner.place_storage(key, value)
+	}
+
+	fn place_child_storage(&mut self, child_info: &ChildInfo, key: Vec<u8>, value: Option<Vec<u8>>) {
+		self.write_child(child_info);
+		self.inner.place_child_storage(child_info, key, value)
+	}
+
+	fn storage_root(&mut self, state_version: StateVersion) -> Vec<u8> {
+		self.inner.storage_root(state_version)
+	}
+
+	fn child_storage_root(&mut self, child_info: &ChildInfo, state_version: StateVersion) -> Vec<u8> {
+		self.inner.child_storage_root(child_info, state_version)
+	}
+
+	fn storage_append(&mut self, key: Vec<u8>, value: Vec<u8>) {
+		self.writes.insert(key.clone());
+		self.inner.storage_append(key, value)
+	}
+
+	fn storage_start_transaction(&mut self) {
+		self.inner.storage_start_transaction()
+	}
+
+	fn storage_rollback_transaction(&mut self) -> Result<(), ()> {
+		self.inner.storage_rollback_transaction()
+	}
+
+	fn storage_commit_transaction(&mut self) -> Result<(), ()> {
+		self.inner.storage_commit_transaction()
+	}
+
+	fn wipe(&mut self) {
+		self.inner.wipe()
+	}
+
+	fn commit(&mut self) {
+		self.inner.commit()
+	}
+
+	fn read_write_count(&self) -> (u32, u32, u32, u32) {
+		self.inner.read_write_count()
+	}
+
+	fn reset_read_write_count(&mut self) {
+		self.inner.reset_read_write_count()
+	}
+
+	fn get_whitelist(&self) -> Vec<TrackedStorageKey> {
+		self.inner.get_whitelist()
+	}
+
+	fn set_whitelist(&mut self, new: Vec<TrackedStorageKey>) {
+		self.inner.set_whitelist(new)
+	}
+
+	fn get_read_and_written_keys(&self) -> Vec<(Vec<u8>, u32, u32, bool)> {
+		self.inner.get_read_and_written_keys()
+	}
+}
@@ -0,0 +1,88 @@
+//! RPC helpers for shard validator operators.
+//!
+//! `validator_rotateKeys` generates new BABE and GRANDPA keys in the node's keystore and
+//! returns them together with the encoded `Sharding::rotate_and_register_keys` call, which
+//! registers the keys with the session pallet and confirms the validator's shard in a single
+//! extrinsic. The operator only has to sign and submit that call.
+
+use std::sync::Arc;
+
+use codec::Encode;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::ErrorObjectOwned,
+	Extensions,
+};
+use netchain_primitives::{ShardId, SHARD_COUNT};
+use netchain_runtime::{opaque::Block, RuntimeCall};
+use sc_rpc_api::check_if_safe;
+use serde::{Deserialize, Serialize};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_keystore::{KeystoreExt, KeystorePtr};
+use sp_session::SessionKeys;
+
+/// Keys generated by `validator_rotateKeys`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotatedKeys {
+	/// SCALE-encoded session keys
+	pub keys: Bytes,
+	/// Encoded `Sharding::rotate_and_register_keys` call registering `keys`
+	pub call: Bytes,
+}
+
+/// Shard validator RPC methods
+#[rpc(client, server)]
+pub trait ValidatorApi {
+	/// Generate new session keys for a validator of `shard_id`
+	///
+	/// The keys are stored in the node's keystore, so this is an unsafe method.
+	#[method(name = "validator_rotateKeys", with_extensions)]
+	fn rotate_keys(&self, shard_id: ShardId) -> RpcResult<RotatedKeys>;
+}
+
+/// Implementation of [`ValidatorApiServer`]
+pub struct Validator<C> {
+	client: Arc<C>,
+	keystore: KeystorePtr,
+}
+
+impl<C> Validator<C> {
+	/// Create the RPC handler over `client`, storing generated keys in `keystore`
+	pub fn new(client: Arc<C>, keystore: KeystorePtr) -> Self {
+		Self { client, keystore }
+	}
+}
+
+fn rpc_error(message: impl ToString) -> ErrorObjectOwned {
+	ErrorObjectOwned::owned(1, message.to_string(), None::<()>)
+}
+
+impl<C> ValidatorApiServer for Validator<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: SessionKeys<Block>,
+{
+	fn rotate_keys(&self, ext: &Extensions, shard_id: ShardId) -> RpcResult<RotatedKeys> {
+		check_if_safe(ext)?;
+
+		if shard_id >= SHARD_COUNT {
+			return Err(rpc_error(format!("shard {shard_id} does not exist")));
+		}
+
+		let best = self.client.info().best_hash;
+		let mut api = self.client.runtime_api();
+		api.register_extension(KeystoreExt::from(self.keystore.clone()));
+		let keys = api.generate_session_keys(best, None).map_err(rpc_error)?;
+
+		let call = RuntimeCall::Sharding(pallet_sharding::Call::rotate_and_register_keys {
+			shard_id,
+			keys: keys.clone(),
+		});
+
+		Ok(RotatedKeys { keys: keys.into(), call: call.encode().into() })
+	}
+}
@@ -11,6 +11,11 @@ use netchain_runtime::{self, apis::RuntimeApi, opaque::Block};
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
 use std::{sync::Arc, time::Duration};
 
+use crate::{
+	executor_metrics::ExecutorMetrics, health_telemetry, offchain_history,
+	parallel_import::ShardSegmentedImport, shard_pool::ShardPartitionedPool,
+};
+
 pub(crate) type FullClient = sc_service::TFullClient<
 	Block,
 	RuntimeApi,
@@ -86,7 +91,8 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 	let cidp_client = client.clone();
 	let import_queue =
 		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _>(ImportQueueParams {
-			block_import: grandpa_block_import.clone(),
+			block_import: ShardSegmentedImport::new(grandpa_block_import.clone(), client.clone())
+				.map_err(|e| ServiceError::Other(e.to_string()))?,
 			justification_import: Some(Box::new(grandpa_block_import.clone())),
 			client: client.clone(),
 			create_inherent_data_providers: move |parent_hash, _| {
@@ -127,10 +133,14 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
 }
 
 /// Builds a new service for a full client.
+///
+/// `history_retention` is the number of blocks behind the finalized one whose off-chain history
+/// is kept; all of it is kept if `None`.
 pub fn new_full<
 	N: sc_network::NetworkBackend<Block, <Block as sp_runtime::traits::Block>::Hash>,
 >(
 	config: Configuration,
+	history_retention: Option<u32>,
 ) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
@@ -204,6 +214,13 @@ pub fn new_full<
 		);
 	}
 
+	// Execution results and oracle history mirrored by the runtime are only written when
+	// off-chain indexing is enabled
+	let history_storage = backend.offchain_storage().filter(|_| config.offchain_worker.indexing_enabled);
+	if history_retention.is_some() && history_storage.is_none() {
+		log::warn!("--history-retention has no effect without --enable-offchain-indexing true");
+	}
+
 	let role = config.role;
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks: Option<()> = None;
@@ -214,9 +231,18 @@ pub fn new_full<
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let keystore = keystore_container.keystore();
+		let sync = sync_service.clone();
+		let offchain_storage = history_storage.clone();
 
 		Box::new(move |_| {
-			let deps = crate::rpc::FullDeps { client: client.clone(), pool: pool.clone() };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				keystore: keystore.clone(),
+				sync: sync.clone(),
+				offchain_storage: offchain_storage.clone(),
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
@@ -236,11 +262,39 @@ pub fn new_full<
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(registry) = prometheus_registry.as_ref() {
+		match ExecutorMetrics::register(registry) {
+			Ok(metrics) => task_manager.spawn_handle().spawn(
+				"executor-metrics",
+				None,
+				metrics.run(client.clone()),
+			),
+			Err(e) => log::warn!("Failed to register parallel executor metrics: {e}"),
+		}
+	}
+
+	if let (Some(storage), Some(retention)) = (history_storage, history_retention) {
+		task_manager.spawn_handle().spawn(
+			"offchain-history-pruning",
+			None,
+			offchain_history::run(client.clone(), storage, retention),
+		);
+	}
+
+	if let Some(telemetry) = telemetry.as_ref() {
+		task_manager.spawn_handle().spawn(
+			"health-telemetry",
+			None,
+			health_telemetry::run(client.clone(), telemetry.handle()),
+		);
+	}
+
 	if role.is_authority() {
+		// Blocks are filled from per-shard sub-pools so no single shard can take all blockspace
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
 			client.clone(),
-			transaction_pool.clone(),
+			Arc::new(ShardPartitionedPool::new(transaction_pool.clone())),
 			prometheus_registry.as_ref(),
 			telemetry.as_ref().map(|x| x.handle()),
 		);
@@ -0,0 +1,292 @@
+//! Shard archive subcommands.
+//!
+//! `export-shard` dumps the accounts assigned to a shard in `Sharding::AccountToShard`, their
+//! balances and the shard's cross-shard queue at a given block to JSON. It is meant for
+//! debugging shard imbalance and as the basis of shard-split tooling.
+//!
+//! `import-shard` writes such a dump into the genesis of a chain spec, so the state of a shard
+//! can be replayed on a test network. Imported balances are added to the total issuance and the
+//! fee, amount and tip of imported queue entries are held on their senders until settlement.
+//! Holds are not exported, so a sender exported along with the queue has them counted twice.
+
+use std::{fs::File, io::Write, path::PathBuf, sync::Arc};
+
+use codec::{Decode, Encode};
+use frame_support::{
+	storage::{StorageMap, StoragePrefixedMap, StorageValue},
+	traits::{tokens::IdAmount, Get},
+};
+use netchain_primitives::{ShardId, SHARD_COUNT};
+use netchain_runtime::{AccountId, Balance, BlockNumber, Hash, Nonce, Runtime, RuntimeHoldReason};
+use pallet_sharding::{
+	account_digest, AccountToShard, CrossShardQueue, CrossShardTx, ShardAccountsDigests, ShardProcessingState,
+	VersionedCrossShardTx,
+};
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_client_api::StorageProvider;
+use sc_service::ChainSpec;
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	storage::{Storage, StorageKey},
+	U256,
+};
+
+use crate::service::FullClient;
+
+type AccountInfo = frame_system::AccountInfo<Nonce, pallet_balances::AccountData<Balance>>;
+
+/// State of one shard at a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardExport {
+	/// Exported shard
+	pub shard: ShardId,
+	/// Number of the block the state was read at
+	pub block_number: BlockNumber,
+	/// Hash of the block the state was read at
+	pub block_hash: Hash,
+	/// Accounts assigned to the shard
+	pub accounts: Vec<ExportedAccount>,
+	/// Cross-shard transactions waiting to settle on the shard
+	pub queue: Vec<CrossShardTx<AccountId, Balance>>,
+}
+
+/// Nonce and balances of an exported account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedAccount {
+	/// Exported account
+	pub account: AccountId,
+	/// Account nonce
+	pub nonce: Nonce,
+	/// Free balance
+	pub free: Balance,
+	/// Reserved balance
+	pub reserved: Balance,
+	/// Frozen part of the balance
+	pub frozen: Balance,
+}
+
+/// Export the accounts, balances and queue of a shard to JSON
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportShardCmd {
+	/// Shard to export
+	#[arg(long)]
+	pub shard: ShardId,
+
+	/// Block number to read the state at; defaults to the best block
+	#[arg(long)]
+	pub at: Option<BlockNumber>,
+
+	/// Output file; defaults to stdout
+	#[arg(long)]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ExportShardCmd {
+	/// Read the shard's state from `client` and write it out
+	pub fn run(&self, client: Arc<FullClient>) -> sc_cli::Result<()> {
+		if self.shard >= SHARD_COUNT {
+			return Err(format!("shard {} does not exist", self.shard).into());
+		}
+
+		let block_number = self.at.unwrap_or(client.info().best_number);
+		let block_hash = client
+			.hash(block_number)?
+			.ok_or_else(|| format!("block {block_number} not found"))?;
+
+		let prefix = AccountToShard::<Runtime>::final_prefix();
+		let mut accounts = Vec::new();
+		for key in client.storage_keys(block_hash, Some(&StorageKey(prefix.to_vec())), None)? {
+			let shard: Option<ShardId> = read(&client, block_hash, &key.0)?;
+			if shard != Some(self.shard) {
+				continue;
+			}
+
+			// Keys are the map prefix, the 16 byte blake2 hash and the encoded account
+			let account = AccountId::decode(&mut &key.0[prefix.len() + 16..]).map_err(undecodable)?;
+			let info: AccountInfo =
+				read(&client, block_hash, &frame_system::Account::<Runtime>::hashed_key_for(&account))?
+					.unwrap_or_default();
+
+			accounts.push(ExportedAccount {
+				account,
+				nonce: info.nonce,
+				free: info.data.free,
+				reserved: info.data.reserved,
+				frozen: info.data.frozen,
+			});
+		}
+
+		let queue: Vec<VersionedCrossShardTx<AccountId, Balance>> =
+			read(&client, block_hash, &CrossShardQueue::<Runtime>::hashed_key_for(self.shard))?
+				.unwrap_or_default();
+		let queue = queue.into_iter().map(VersionedCrossShardTx::into_latest).collect();
+
+		let export = ShardExport { shard: self.shard, block_number, block_hash, accounts, queue };
+		let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+		write_output(self.output.as_ref(), &json)
+	}
+}
+
+impl CliConfiguration for ExportShardCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+/// Write a shard export into the genesis of a chain spec for a test network
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ImportShardCmd {
+	/// Shard export produced by `export-shard`
+	#[arg(long)]
+	pub input: PathBuf,
+
+	/// Chain spec to import into
+	#[arg(long, default_value = "local")]
+	pub chain: String,
+
+	/// Output file for the raw chain spec; defaults to stdout
+	#[arg(long)]
+	pub output: Option<PathBuf>,
+}
+
+impl ImportShardCmd {
+	/// Merge the export into the genesis storage of `spec` and write the raw spec out
+	pub fn run(&self, mut spec: Box<dyn ChainSpec>) -> sc_cli::Result<()> {
+		let export: ShardExport =
+			serde_json::from_reader(File::open(&self.input)?).map_err(|e| e.to_string())?;
+		if export.shard >= SHARD_COUNT {
+			return Err(format!("shard {} does not exist", export.shard).into());
+		}
+
+		let mut storage = spec.as_storage_builder().build_storage()?;
+		import_into(&mut storage, &export)?;
+		spec.set_storage(storage);
+
+		write_output(self.output.as_ref(), &spec.as_json(true)?)
+	}
+}
+
+/// Add the accounts and queue of `export` to genesis `storage`
+fn import_into(storage: &mut Storage, export: &ShardExport) -> sc_cli::Result<()> {
+	let issuance_key = pallet_balances::TotalIssuance::<Runtime>::hashed_key().to_vec();
+	let mut issuance: Balance = decode_entry(storage, &issuance_key)?.unwrap_or_default();
+
+	for exported in &export.accounts {
+		let account_key = frame_system::Account::<Runtime>::hashed_key_for(&exported.account);
+
+		// Replaced accounts no longer count towards the issuance
+		if let Some(existing) = decode_entry::<AccountInfo>(storage, &account_key)? {
+			issuance = issuance.saturating_sub(existing.data.free.saturating_add(existing.data.reserved));
+		}
+
+		let info = AccountInfo {
+			nonce: exported.nonce,
+			consumers: (exported.reserved > 0).into(),
+			providers: 1,
+			sufficients: 0,
+			data: pallet_balances::AccountData {
+				free: exported.free,
+				reserved: exported.reserved,
+				frozen: exported.frozen,
+				..Default::default()
+			},
+		};
+		issuance = issuance.saturating_add(exported.free.saturating_add(exported.reserved));
+
+		storage.top.insert(account_key, info.encode());
+
+		// The account's digest moves from the shard it was on to the imported one
+		let shard_key = AccountToShard::<Runtime>::hashed_key_for(&exported.account);
+		let digest = account_digest(&exported.account);
+		if let Some(previous) = decode_entry::<ShardId>(storage, &shard_key)? {
+			let digest_key = ShardAccountsDigests::<Runtime>::hashed_key_for(previous);
+			let sum: U256 = decode_entry(storage, &digest_key)?.unwrap_or_default();
+			storage.top.insert(digest_key, sum.overflowing_sub(digest).0.encode());
+		}
+		let digest_key = ShardAccountsDigests::<Runtime>::hashed_key_for(export.shard);
+		let sum: U256 = decode_entry(storage, &digest_key)?.unwrap_or_default();
+		storage.top.insert(digest_key, sum.overflowing_add(digest).0.encode());
+		storage.top.insert(shard_key, export.shard.encode());
+	}
+
+	if !export.queue.is_empty() {
+		let queue_key = CrossShardQueue::<Runtime>::hashed_key_for(export.shard);
+		let mut queue: Vec<VersionedCrossShardTx<AccountId, Balance>> =
+			decode_entry(storage, &queue_key)?.unwrap_or_default();
+		queue.extend(export.queue.iter().cloned().map(Into::into));
+		let max_len = <Runtime as pallet_sharding::Config>::MaxCrossShardQueueLen::get();
+		if queue.len() > max_len as usize {
+			return Err(sc_cli::Error::Input(format!(
+				"shard {} would queue {} cross-shard transfers, more than the {max_len} allowed",
+				export.shard,
+				queue.len(),
+			)));
+		}
+		storage.top.insert(queue_key, queue.encode());
+
+		let load_key = ShardProcessingState::<Runtime>::hashed_key_for(export.shard);
+		let load: u32 = decode_entry(storage, &load_key)?.unwrap_or_default();
+		storage
+			.top
+			.insert(load_key, load.saturating_add(export.queue.len() as u32).encode());
+
+		// Senders keep the fee, amount and tip of queued transfers on hold until they settle
+		let hold_reason = RuntimeHoldReason::from(pallet_sharding::HoldReason::CrossShardEscrow);
+		for tx in &export.queue {
+			let held = tx.amount.saturating_add(tx.fee).saturating_add(tx.tip);
+
+			let sender_key = frame_system::Account::<Runtime>::hashed_key_for(&tx.sender);
+			let mut sender: AccountInfo = decode_entry(storage, &sender_key)?.unwrap_or_default();
+			sender.providers = sender.providers.max(1);
+			sender.consumers = sender.consumers.max(1);
+			sender.data.reserved = sender.data.reserved.saturating_add(held);
+			storage.top.insert(sender_key, sender.encode());
+
+			let holds_key = pallet_balances::Holds::<Runtime>::hashed_key_for(&tx.sender);
+			let mut holds: Vec<IdAmount<RuntimeHoldReason, Balance>> =
+				decode_entry(storage, &holds_key)?.unwrap_or_default();
+			match holds.iter_mut().find(|hold| hold.id == hold_reason) {
+				Some(hold) => hold.amount = hold.amount.saturating_add(held),
+				None => holds.push(IdAmount { id: hold_reason, amount: held }),
+			}
+			storage.top.insert(holds_key, holds.encode());
+
+			issuance = issuance.saturating_add(held);
+		}
+	}
+
+	storage.top.insert(issuance_key, issuance.encode());
+
+	Ok(())
+}
+
+fn read<V: Decode>(client: &FullClient, at: Hash, key: &[u8]) -> sc_cli::Result<Option<V>> {
+	client
+		.storage(at, &StorageKey(key.to_vec()))?
+		.map(|data| V::decode(&mut &data.0[..]))
+		.transpose()
+		.map_err(undecodable)
+}
+
+fn decode_entry<V: Decode>(storage: &Storage, key: &[u8]) -> sc_cli::Result<Option<V>> {
+	storage.top.get(key).map(|value| V::decode(&mut &value[..])).transpose().map_err(undecodable)
+}
+
+fn undecodable(e: codec::Error) -> sc_cli::Error {
+	sc_cli::Error::Input(format!("undecodable storage entry: {e}"))
+}
+
+fn write_output(output: Option<&PathBuf>, json: &str) -> sc_cli::Result<()> {
+	match output {
+		Some(path) => File::create(path)?.write_all(json.as_bytes())?,
+		None => println!("{json}"),
+	}
+	Ok(())
+}
@@ -0,0 +1,411 @@
+//! Shard-partitioned view of the transaction pool for block authoring.
+//!
+//! Transactions are grouped into per-shard sub-pools using the shard tag carried by the
+//! `CheckShardAffinity` transaction extension. The block proposer then pulls from the
+//! sub-pools in proportion to the weight each has pending, so a shard's share of the block
+//! follows its load and capacity left unused by quiet shards falls through to busy ones.
+//! Without this a single hot shard, being first in priority order, could fill every block.
+//! System transactions tagged with a reserved system shard form a sub-pool of their own, so
+//! user load cannot crowd them out.
+//!
+//! Transactions are pulled from the pool and decoded only as the proposer asks for them,
+//! with a bounded lookahead to compare the shards' pending weight. The shard tag is found by
+//! the position of `CheckShardAffinity` in the runtime's extension metadata, so reordering the
+//! extensions does not silently misread it.
+//!
+//! Only the proposer sees the partitioned view; RPC, networking and pool maintenance keep
+//! using the underlying pool directly.
+
+use codec::{Compact, Decode, DecodeLimit, Encode, Input};
+use frame_support::{dispatch::GetDispatchInfo, weights::Weight, MAX_EXTRINSIC_DEPTH};
+use netchain_runtime::{opaque::Block, Address, Runtime, RuntimeCall, Signature, TxExtension};
+use pallet_sharding::{CheckShardAffinity, ShardId};
+use sc_transaction_pool_api::{
+	ImportNotificationStream, InPoolTransaction, PoolStatus, ReadyIteratorFor, ReadyTransactions,
+	TransactionFor, TransactionPool, TransactionSource, TransactionStatusStreamFor, TxHash,
+	TxInvalidityReportMap,
+};
+use scale_info::{PortableRegistry, Registry, TypeDef, TypeDefPrimitive};
+use sp_runtime::{
+	generic::Preamble,
+	traits::{Block as BlockT, TransactionExtension},
+	transaction_validity::TransactionTag,
+	OpaqueExtrinsic,
+};
+use std::{
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	pin::Pin,
+	sync::{Arc, OnceLock},
+	time::Duration,
+};
+
+/// Transactions pulled from the pool ahead of the proposer to compare the shards' pending
+/// weight
+const LOOKAHEAD: usize = 1024;
+
+/// Shard tag and dispatch weight of an extrinsic; the tag is `None` for extrinsics without
+/// one (e.g. unsigned)
+pub fn extrinsic_load(xt: &OpaqueExtrinsic) -> Option<(Option<ShardId>, Weight)> {
+	let encoded = xt.encode();
+	let mut input = &encoded[..];
+	// An opaque extrinsic encodes as a length-prefixed byte vector
+	Compact::<u32>::skip(&mut input).ok()?;
+	let shard = match Preamble::<Address, Signature, ShardTag>::decode(&mut input).ok()? {
+		Preamble::Signed(_, _, tag) | Preamble::General(_, tag) => Some(tag.0),
+		Preamble::Bare(_) => None,
+	};
+	let call = RuntimeCall::decode_with_depth_limit(MAX_EXTRINSIC_DEPTH, &mut input).ok()?;
+	Some((shard, call.get_dispatch_info().call_weight))
+}
+
+/// Where the shard tag sits in the runtime's `TxExtension`, and the types around it
+struct ExtensionLayout {
+	registry: PortableRegistry,
+	/// Type of each extension, in encoding order
+	types: Vec<u32>,
+	/// Position of `CheckShardAffinity` among them
+	shard_tag: usize,
+}
+
+impl ExtensionLayout {
+	/// Layout of the runtime's extensions, read once from their metadata
+	fn get() -> Option<&'static Self> {
+		static LAYOUT: OnceLock<Option<ExtensionLayout>> = OnceLock::new();
+		LAYOUT
+			.get_or_init(|| {
+				let extensions = <TxExtension as TransactionExtension<RuntimeCall>>::metadata();
+				let shard_tag = extensions.iter().position(|extension| {
+					extension.identifier ==
+						<CheckShardAffinity<Runtime> as TransactionExtension<RuntimeCall>>::IDENTIFIER
+				})?;
+				let mut registry = Registry::new();
+				let types = extensions.iter().map(|extension| registry.register_type(&extension.ty).id).collect();
+				Some(Self { registry: registry.into(), types, shard_tag })
+			})
+			.as_ref()
+	}
+}
+
+/// Shard tag of an encoded `TxExtension`, decoded without the extensions around it
+struct ShardTag(ShardId);
+
+impl Decode for ShardTag {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let layout = ExtensionLayout::get().ok_or("runtime has no CheckShardAffinity extension")?;
+		let mut shard = None;
+		for (position, ty) in layout.types.iter().enumerate() {
+			if position == layout.shard_tag {
+				shard = Some(CheckShardAffinity::<Runtime>::decode(input)?.shard());
+			} else {
+				skip_type(&layout.registry, *ty, input)?;
+			}
+		}
+		shard.map(Self).ok_or_else(|| "shard tag missing from extension".into())
+	}
+}
+
+/// Advance `input` past one encoded value of type `ty`
+fn skip_type<I: Input>(registry: &PortableRegistry, ty: u32, input: &mut I) -> Result<(), codec::Error> {
+	let ty = registry.resolve(ty).ok_or("unknown extension type")?;
+	match &ty.type_def {
+		TypeDef::Composite(composite) =>
+			composite.fields.iter().try_for_each(|field| skip_type(registry, field.ty.id, input)),
+		TypeDef::Variant(variants) => {
+			let index = input.read_byte()?;
+			let variant = variants.variants.iter().find(|variant| variant.index == index).ok_or("unknown variant")?;
+			variant.fields.iter().try_for_each(|field| skip_type(registry, field.ty.id, input))
+		},
+		TypeDef::Sequence(sequence) => {
+			let len = Compact::<u32>::decode(input)?.0;
+			(0..len).try_for_each(|_| skip_type(registry, sequence.type_param.id, input))
+		},
+		TypeDef::Array(array) => (0..array.len).try_for_each(|_| skip_type(registry, array.type_param.id, input)),
+		TypeDef::Tuple(tuple) => tuple.fields.iter().try_for_each(|field| skip_type(registry, field.id, input)),
+		TypeDef::Primitive(primitive) => {
+			let len = match primitive {
+				TypeDefPrimitive::Bool | TypeDefPrimitive::U8 | TypeDefPrimitive::I8 => 1,
+				TypeDefPrimitive::U16 | TypeDefPrimitive::I16 => 2,
+				TypeDefPrimitive::Char | TypeDefPrimitive::U32 | TypeDefPrimitive::I32 => 4,
+				TypeDefPrimitive::U64 | TypeDefPrimitive::I64 => 8,
+				TypeDefPrimitive::U128 | TypeDefPrimitive::I128 => 16,
+				TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => 32,
+				TypeDefPrimitive::Str => Compact::<u32>::decode(input)?.0 as usize,
+			};
+			skip_bytes(input, len)
+		},
+		TypeDef::Compact(_) => Compact::<u128>::skip(input),
+		TypeDef::BitSequence(_) => Err("bit sequence in extension".into()),
+	}
+}
+
+/// Advance `input` past `len` bytes
+fn skip_bytes<I: Input>(input: &mut I, mut len: usize) -> Result<(), codec::Error> {
+	let mut buffer = [0u8; 64];
+	while len > 0 {
+		let chunk = len.min(buffer.len());
+		input.read(&mut buffer[..chunk])?;
+		len -= chunk;
+	}
+	Ok(())
+}
+
+/// Transaction pool wrapper whose ready set is interleaved across shards
+pub struct ShardPartitionedPool<P> {
+	inner: Arc<P>,
+}
+
+impl<P> ShardPartitionedPool<P> {
+	/// Wrap `inner`
+	pub fn new(inner: Arc<P>) -> Self {
+		Self { inner }
+	}
+}
+
+impl<P: TransactionPool<Block = Block> + 'static> ShardPartitionedPool<P> {
+	fn partition(&self, ready: ReadyIteratorFor<P>) -> ReadyIteratorFor<P> {
+		Box::new(ShardInterleaved::new(ready, |tx: &P::InPoolTransaction| {
+			extrinsic_load(tx.data()).map_or((None, 0), |(shard, weight)| (shard, weight.ref_time()))
+		}))
+	}
+}
+
+#[async_trait::async_trait]
+impl<P: TransactionPool<Block = Block> + 'static> TransactionPool for ShardPartitionedPool<P> {
+	type Block = P::Block;
+	type Hash = P::Hash;
+	type InPoolTransaction = P::InPoolTransaction;
+	type Error = P::Error;
+
+	async fn submit_at(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xts: Vec<TransactionFor<Self>>,
+	) -> Result<Vec<Result<TxHash<Self>, Self::Error>>, Self::Error> {
+		self.inner.submit_at(at, source, xts).await
+	}
+
+	async fn submit_one(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> Result<TxHash<Self>, Self::Error> {
+		self.inner.submit_one(at, source, xt).await
+	}
+
+	async fn submit_and_watch(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> Result<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
+		self.inner.submit_and_watch(at, source, xt).await
+	}
+
+	async fn ready_at(&self, at: <Self::Block as BlockT>::Hash) -> ReadyIteratorFor<Self> {
+		let ready = self.inner.ready_at(at).await;
+		self.partition(ready)
+	}
+
+	fn ready(&self) -> ReadyIteratorFor<Self> {
+		self.partition(self.inner.ready())
+	}
+
+	async fn report_invalid(
+		&self,
+		at: Option<<Self::Block as BlockT>::Hash>,
+		invalid_tx_errors: TxInvalidityReportMap<TxHash<Self>>,
+	) -> Vec<Arc<Self::InPoolTransaction>> {
+		self.inner.report_invalid(at, invalid_tx_errors).await
+	}
+
+	fn futures(&self) -> Vec<Self::InPoolTransaction> {
+		self.inner.futures()
+	}
+
+	fn status(&self) -> PoolStatus {
+		self.inner.status()
+	}
+
+	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
+		self.inner.import_notification_stream()
+	}
+
+	fn on_broadcasted(&self, propagations: HashMap<TxHash<Self>, Vec<String>>) {
+		self.inner.on_broadcasted(propagations)
+	}
+
+	fn hash_of(&self, xt: &TransactionFor<Self>) -> TxHash<Self> {
+		self.inner.hash_of(xt)
+	}
+
+	fn ready_transaction(&self, hash: &TxHash<Self>) -> Option<Arc<Self::InPoolTransaction>> {
+		self.inner.ready_transaction(hash)
+	}
+
+	async fn ready_at_with_timeout(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		timeout: Duration,
+	) -> ReadyIteratorFor<Self> {
+		let ready = self.inner.ready_at_with_timeout(at, timeout).await;
+		self.partition(ready)
+	}
+}
+
+/// Ready transactions regrouped into per-shard queues, each served in proportion to its
+/// pending weight
+///
+/// Transactions are pulled from the pool only as the queues drain, keeping at most
+/// [`LOOKAHEAD`] buffered. Each step yields the ready queue head of the shard that has had
+/// the smallest part of its weight served so far, so a shard with twice the pending weight of
+/// another gets twice its share of the block. Each queue keeps the pool's priority order. A
+/// transaction is only yielded once every tag it requires has been provided by an earlier
+/// yielded transaction, so dependencies across shards keep their order; if no queue head is
+/// ready, the head that came first in the pool's order is yielded, as the pool itself would
+/// have.
+struct ShardInterleaved<T, F> {
+	/// The pool's ready transactions, in its priority order
+	ready: Box<dyn ReadyTransactions<Item = Arc<T>> + Send>,
+	/// Shard tag and weight of a transaction
+	load_of: F,
+	queues: BTreeMap<Option<ShardId>, ShardQueue<T>>,
+	/// Transactions pulled from the pool so far
+	pulled: usize,
+	/// Transactions pulled but neither yielded nor dropped
+	buffered: usize,
+	/// Tags provided by transactions not yet yielded
+	unyielded: HashSet<TransactionTag>,
+	/// Tags provided by transactions reported invalid (and their dependants)
+	invalid: HashSet<TransactionTag>,
+}
+
+/// Buffered transactions of one shard and the weight they carry
+struct ShardQueue<T> {
+	/// Position in the pool's order, weight and transaction
+	txs: VecDeque<(usize, u64, Arc<T>)>,
+	/// Weight of the buffered transactions
+	pending: u128,
+	/// Weight of the transactions yielded so far
+	served: u128,
+}
+
+impl<T> Default for ShardQueue<T> {
+	fn default() -> Self {
+		Self { txs: VecDeque::new(), pending: 0, served: 0 }
+	}
+}
+
+impl<T, F> ShardInterleaved<T, F>
+where
+	T: InPoolTransaction,
+	F: Fn(&T) -> (Option<ShardId>, u64),
+{
+	fn new(ready: Box<dyn ReadyTransactions<Item = Arc<T>> + Send>, load_of: F) -> Self {
+		Self {
+			ready,
+			load_of,
+			queues: BTreeMap::new(),
+			pulled: 0,
+			buffered: 0,
+			unyielded: HashSet::new(),
+			invalid: HashSet::new(),
+		}
+	}
+
+	/// Pull from the pool until [`LOOKAHEAD`] transactions are buffered or it runs dry
+	fn fill(&mut self) {
+		while self.buffered < LOOKAHEAD {
+			let Some(tx) = self.ready.next() else { break };
+			let (shard, weight) = (self.load_of)(&tx);
+			// Weightless transactions still take a share, or they would always go first
+			let weight = weight.max(1);
+			self.unyielded.extend(tx.provides().iter().cloned());
+			let queue = self.queues.entry(shard).or_default();
+			queue.pending += u128::from(weight);
+			queue.txs.push_back((self.pulled, weight, tx));
+			self.pulled += 1;
+			self.buffered += 1;
+		}
+	}
+
+	fn take(&mut self, shard: Option<ShardId>, invalid: bool) -> Option<Arc<T>> {
+		let queue = self.queues.get_mut(&shard)?;
+		let (_, weight, tx) = queue.txs.pop_front()?;
+		queue.pending -= u128::from(weight);
+		if !invalid {
+			queue.served += u128::from(weight);
+		}
+		self.buffered -= 1;
+		for tag in tx.provides() {
+			self.unyielded.remove(tag);
+			if invalid {
+				self.invalid.insert(tag.clone());
+			}
+		}
+		Some(tx)
+	}
+}
+
+impl<T, F> Iterator for ShardInterleaved<T, F>
+where
+	T: InPoolTransaction,
+	F: Fn(&T) -> (Option<ShardId>, u64),
+{
+	type Item = Arc<T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			self.fill();
+
+			// Shard, served and total weight, and pool position of the best ready head
+			let mut best: Option<(Option<ShardId>, u128, u128, usize)> = None;
+			let mut fallback: Option<(Option<ShardId>, usize)> = None;
+			let mut dropped = None;
+
+			for (shard, queue) in &self.queues {
+				let Some((index, _, tx)) = queue.txs.front() else { continue };
+
+				if tx.requires().iter().any(|tag| self.invalid.contains(tag)) {
+					dropped = Some(*shard);
+					break;
+				}
+
+				if tx.requires().iter().all(|tag| !self.unyielded.contains(tag)) {
+					// Compare served / (served + pending) across shards without dividing
+					let total = queue.served + queue.pending;
+					if best.is_none_or(|(_, served, best_total, earliest)| {
+						let (share, best_share) = (queue.served * best_total, served * total);
+						share < best_share || (share == best_share && *index < earliest)
+					}) {
+						best = Some((*shard, queue.served, total, *index));
+					}
+				} else if fallback.is_none_or(|(_, earliest)| *index < earliest) {
+					fallback = Some((*shard, *index));
+				}
+			}
+
+			if let Some(shard) = dropped {
+				// Depends on an invalid transaction; drop it and its dependants
+				self.take(shard, true);
+				continue;
+			}
+
+			let shard = best.map(|(shard, ..)| shard).or(fallback.map(|(shard, _)| shard))?;
+			return self.take(shard, false);
+		}
+	}
+}
+
+impl<T, F> ReadyTransactions for ShardInterleaved<T, F>
+where
+	T: InPoolTransaction,
+	F: Fn(&T) -> (Option<ShardId>, u64),
+{
+	fn report_invalid(&mut self, tx: &Self::Item) {
+		self.invalid.extend(tx.provides().iter().cloned());
+		// Transactions not pulled yet are the pool's to skip
+		self.ready.report_invalid(tx);
+	}
+}
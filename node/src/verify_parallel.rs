@@ -0,0 +1,219 @@
+//! Deterministic replay of a block through the parallel scheduler.
+//!
+//! `verify-parallel` re-executes the extrinsics of a block natively on its parent state twice:
+//! - sequentially, in block order, as block import does
+//! - in the waves `pallet_parallel_executor::schedule_waves` builds from the access lists
+//!   declared through `submit_with_access_list`: every extrinsic of a wave runs on its own
+//!   worker against the state left by the previous waves, and the workers' writes are merged
+//!   in block order
+//!
+//! It prints both state roots and, when they differ, the first storage key whose value
+//! diverges, which points at the access list that missed a conflict.
+//!
+//! Every extrinsic also writes the per-block bookkeeping of `frame_system` (events, extrinsic
+//! index and data, block weight and length). Those items are accumulated outside the schedule,
+//! so the parallel replay takes them from the sequential one. `on_finalize` hooks run after the
+//! schedule and are left out of both replays; the calls `submit_with_access_list` queues run
+//! there, in the waves the pallet schedules from the same access lists.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	sync::Arc,
+};
+
+use codec::{Decode, Encode};
+use netchain_runtime::{Executive, Hash, RuntimeCall, UncheckedExtrinsic, VERSION};
+use pallet_parallel_executor::{schedule_waves, AccessList};
+use rayon::prelude::*;
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_client_api::BlockBackend;
+use sp_core::{hexdisplay::HexDisplay, storage::well_known_keys::EXTRINSIC_INDEX};
+use sp_runtime::traits::{BlakeTwo256, Block as BlockT, Header as HeaderT};
+use sp_state_machine::{Backend, Ext, OverlayedChanges};
+
+use crate::service::FullClient;
+
+/// Storage written by a replay on top of the parent state, `None` for removed keys
+pub(crate) type Changes = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// `frame_system` items every extrinsic writes, accumulated outside the parallel schedule
+const BOOKKEEPING: &[&[u8]] = &[
+	b"Events",
+	b"EventCount",
+	b"EventTopics",
+	b"ExecutionPhase",
+	b"BlockWeight",
+	b"AllExtrinsicsLen",
+	b"ExtrinsicData",
+	b"InherentsApplied",
+];
+
+/// Re-execute a block sequentially and through the parallel scheduler and compare state roots
+#[derive(Debug, Clone, clap::Parser)]
+pub struct VerifyParallelCmd {
+	/// Hash of the block to replay
+	#[arg(long)]
+	pub block: Hash,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl VerifyParallelCmd {
+	/// Replay the block both ways, failing if the state roots diverge
+	pub fn run(&self, client: Arc<FullClient>) -> sc_cli::Result<()> {
+		let block = client
+			.block(self.block)?
+			.ok_or_else(|| format!("block {:?} not found", self.block))?
+			.block;
+		let (header, body) = block.deconstruct();
+		let parent = *header.parent_hash();
+		let extrinsics = body
+			.iter()
+			.map(|xt| UncheckedExtrinsic::decode(&mut &xt.encode()[..]))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("undecodable extrinsic: {e}"))?;
+
+		let initialized = replay(&client, parent, &Changes::new(), || {
+			Executive::initialize_block(&header);
+			Ok(())
+		})?;
+
+		let sequential = replay(&client, parent, &initialized, || {
+			extrinsics.iter().enumerate().try_for_each(|(index, xt)| apply(index, xt))
+		})?;
+
+		let access_lists: Vec<_> = extrinsics.iter().map(declared_access_list).collect();
+		let waves = schedule_waves(&access_lists);
+		let mut parallel = initialized;
+		for wave in &waves {
+			let writes = wave
+				.par_iter()
+				.map(|&index| {
+					let index = index as usize;
+					let mut seed = parallel.clone();
+					seed.insert(EXTRINSIC_INDEX.to_vec(), Some((index as u32).encode()));
+					let after = replay(&client, parent, &seed, || apply(index, &extrinsics[index]))?;
+					Ok(after.into_iter().filter(|(key, value)| seed.get(key) != Some(value)).collect::<Vec<_>>())
+				})
+				.collect::<Result<Vec<_>, String>>()?;
+
+			for (key, value) in writes.into_iter().flatten() {
+				if !is_bookkeeping(&key) {
+					parallel.insert(key, value);
+				}
+			}
+		}
+		parallel.retain(|key, _| !is_bookkeeping(key));
+		parallel.extend(sequential.iter().filter(|(key, _)| is_bookkeeping(key)).map(|(k, v)| (k.clone(), v.clone())));
+
+		let sequential_root = state_root(&client, parent, &sequential)?;
+		let parallel_root = state_root(&client, parent, &parallel)?;
+		println!("Block {:?}: {} extrinsics in {} waves", self.block, extrinsics.len(), waves.len());
+		println!("Sequential state root: {sequential_root:?}");
+		println!("Parallel state root:   {parallel_root:?}");
+		if sequential_root == parallel_root {
+			println!("State roots match");
+			return Ok(())
+		}
+
+		let state = client.state_at(parent)?;
+		let stored = |key: &[u8]| state.storage(key).ok().flatten();
+		if let Some((key, expected, found)) = first_divergence(&sequential, &parallel, stored) {
+			println!("First diverging key: 0x{}", HexDisplay::from(&key));
+			println!("  sequential: {}", display_value(&expected));
+			println!("  parallel:   {}", display_value(&found));
+		}
+		Err("parallel execution diverges from sequential execution".into())
+	}
+}
+
+impl CliConfiguration for VerifyParallelCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+/// Access list declared by `submit_with_access_list`; any other call conflicts with everything
+fn declared_access_list(xt: &UncheckedExtrinsic) -> Option<AccessList> {
+	match &xt.function {
+		RuntimeCall::ParallelExecutor(pallet_parallel_executor::Call::submit_with_access_list {
+			reads,
+			writes,
+			..
+		}) => Some(AccessList { reads: reads.clone(), writes: writes.clone() }),
+		_ => None,
+	}
+}
+
+/// Whether `key` belongs to the per-block bookkeeping of `frame_system`
+pub(crate) fn is_bookkeeping(key: &[u8]) -> bool {
+	key == EXTRINSIC_INDEX ||
+		BOOKKEEPING
+			.iter()
+			.any(|item| key.starts_with(&frame_support::storage::storage_prefix(b"System", item)))
+}
+
+/// Apply extrinsic `index`; dispatch errors are part of the replayed state, invalidity is not
+fn apply(index: usize, xt: &UncheckedExtrinsic) -> Result<(), String> {
+	Executive::apply_extrinsic(xt.clone())
+		.map(|_| ())
+		.map_err(|e| format!("extrinsic {index} is invalid: {e:?}"))
+}
+
+/// Overlay holding `changes` on top of the parent state
+fn overlay_of(changes: &Changes) -> OverlayedChanges<BlakeTwo256> {
+	let mut overlay = OverlayedChanges::default();
+	for (key, value) in changes {
+		overlay.set_storage(key.clone(), value.clone());
+	}
+	overlay
+}
+
+/// Run `f` natively on the state of `parent` with `seed` applied, returning `seed` updated with
+/// the writes of `f`
+pub(crate) fn replay(
+	client: &FullClient,
+	parent: Hash,
+	seed: &Changes,
+	f: impl FnOnce() -> Result<(), String>,
+) -> Result<Changes, String> {
+	let state = client.state_at(parent).map_err(|e| e.to_string())?;
+	let mut overlay = overlay_of(seed);
+	{
+		let mut ext = Ext::new(&mut overlay, &state, None);
+		sp_externalities::set_and_run_with_externalities(&mut ext, f)?;
+	}
+	let changes = overlay.drain_storage_changes(&state, VERSION.state_version())?;
+	Ok(changes.main_storage_changes.into_iter().collect())
+}
+
+/// State root of the parent state with `changes` applied
+fn state_root(client: &FullClient, parent: Hash, changes: &Changes) -> Result<Hash, String> {
+	let state = client.state_at(parent).map_err(|e| e.to_string())?;
+	let mut overlay = overlay_of(changes);
+	Ok(overlay.drain_storage_changes(&state, VERSION.state_version())?.transaction_storage_root)
+}
+
+/// First key, in storage order, whose value differs between the two replays, with both values;
+/// keys a replay did not write read as `stored` in the parent state
+fn first_divergence(
+	sequential: &Changes,
+	parallel: &Changes,
+	stored: impl Fn(&[u8]) -> Option<Vec<u8>>,
+) -> Option<(Vec<u8>, Option<Vec<u8>>, Option<Vec<u8>>)> {
+	let keys: BTreeSet<&Vec<u8>> = sequential.keys().chain(parallel.keys()).collect();
+	keys.into_iter().find_map(|key| {
+		let value = |changes: &Changes| changes.get(key).cloned().unwrap_or_else(|| stored(key));
+		let (expected, found) = (value(sequential), value(parallel));
+		(expected != found).then(|| (key.clone(), expected, found))
+	})
+}
+
+fn display_value(value: &Option<Vec<u8>>) -> String {
+	match value {
+		Some(value) => format!("0x{}", HexDisplay::from(value)),
+		None => "(none)".to_string(),
+	}
+}
@@ -0,0 +1,260 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Collateral Demo Pallet
+//!
+//! A minimal lending market showing how pallets consume oracle prices through
+//! [`pallet_oracle::PriceProvider`].
+//!
+//! Accounts reserve native tokens as collateral and borrow against them, with debt recorded
+//! in the quote currency of the collateral's price feed. A position is healthy while its debt
+//! is at most `LiquidationThreshold` of the collateral's value at the oracle price; anyone can
+//! liquidate an unhealthy position and receives its collateral. Oracle prices are per whole
+//! token, so collateral is valued in whole tokens of `CollateralDecimals` decimals. Prices
+//! older than `MaxPriceAge`, or that the oracle no longer counts as fresh, are not acted upon.
+//!
+//! This pallet is a reference for DeFi teams, not a production lending protocol: there is no
+//! interest, no partial liquidation and the borrowed amount is only recorded, not minted.
+
+pub use pallet::*;
+
+use frame_support::{
+    pallet_prelude::*,
+    traits::{BalanceStatus, Get, ReservableCurrency},
+};
+use frame_system::pallet_prelude::*;
+use pallet_oracle::{DataKey, PriceProvider};
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    FixedPointNumber, FixedU128, Perbill, SaturatedConversion,
+};
+
+/// Collateral and debt of an account
+#[derive(Clone, PartialEq, Eq, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo, Default)]
+pub struct Position<Balance> {
+    /// Reserved native tokens
+    pub collateral: Balance,
+    /// Debt in the quote currency of the collateral price feed
+    pub debt: FixedU128,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Currency posted as collateral
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Source of the collateral price
+        type PriceProvider: PriceProvider<BlockNumberFor<Self>>;
+
+        /// Oracle feed pricing one whole token of `Currency`
+        type CollateralAsset: Get<DataKey>;
+
+        /// Decimals of `Currency`: one whole token is `10^CollateralDecimals` of its base units
+        #[pallet::constant]
+        type CollateralDecimals: Get<u8>;
+
+        /// Share of the collateral value that can be borrowed before liquidation
+        #[pallet::constant]
+        type LiquidationThreshold: Get<Perbill>;
+
+        /// Maximum age of a price in blocks
+        #[pallet::constant]
+        type MaxPriceAge: Get<BlockNumberFor<Self>>;
+
+        /// WeightInfo for benchmarking
+        type WeightInfo: WeightInfo;
+    }
+
+    pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// Open positions
+    #[pallet::storage]
+    #[pallet::getter(fn positions)]
+    pub type Positions<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, Position<BalanceOf<T>>>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Collateral reserved
+        CollateralDeposited { who: T::AccountId, amount: BalanceOf<T> },
+        /// Collateral released
+        CollateralWithdrawn { who: T::AccountId, amount: BalanceOf<T> },
+        /// Debt taken against collateral
+        Borrowed { who: T::AccountId, amount: FixedU128 },
+        /// Debt repaid
+        Repaid { who: T::AccountId, amount: FixedU128 },
+        /// Unhealthy position closed; its collateral went to the liquidator
+        Liquidated { who: T::AccountId, liquidator: T::AccountId, collateral: BalanceOf<T>, debt: FixedU128 },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Oracle has no price for the collateral asset
+        PriceUnavailable,
+        /// Oracle price is older than `MaxPriceAge`
+        StalePrice,
+        /// Position would exceed the liquidation threshold
+        Undercollateralized,
+        /// Not enough collateral in the position
+        InsufficientCollateral,
+        /// Account has no position
+        NoPosition,
+        /// Position is within the liquidation threshold
+        PositionHealthy,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Reserve `amount` as collateral
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::deposit_collateral())]
+        pub fn deposit_collateral(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            T::Currency::reserve(&who, amount)?;
+            <Positions<T>>::mutate(&who, |position| {
+                let position = position.get_or_insert_with(Default::default);
+                position.collateral = position.collateral.saturating_add(amount);
+            });
+
+            Self::deposit_event(Event::CollateralDeposited { who, amount });
+
+            Ok(())
+        }
+
+        /// Release `amount` of collateral, provided the position stays healthy
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::withdraw_collateral())]
+        pub fn withdraw_collateral(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut position = <Positions<T>>::get(&who).ok_or(Error::<T>::NoPosition)?;
+            ensure!(position.collateral >= amount, Error::<T>::InsufficientCollateral);
+            position.collateral = position.collateral.saturating_sub(amount);
+            if !position.debt.is_zero() {
+                ensure!(Self::is_healthy(&position)?, Error::<T>::Undercollateralized);
+            }
+
+            T::Currency::unreserve(&who, amount);
+            Self::store(&who, position);
+
+            Self::deposit_event(Event::CollateralWithdrawn { who, amount });
+
+            Ok(())
+        }
+
+        /// Take on `amount` of debt against the position's collateral
+        #[pallet::call_index(2)]
+        #[pallet::weight(T::WeightInfo::borrow())]
+        pub fn borrow(origin: OriginFor<T>, amount: FixedU128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut position = <Positions<T>>::get(&who).ok_or(Error::<T>::NoPosition)?;
+            position.debt = position.debt.saturating_add(amount);
+            ensure!(Self::is_healthy(&position)?, Error::<T>::Undercollateralized);
+
+            <Positions<T>>::insert(&who, position);
+
+            Self::deposit_event(Event::Borrowed { who, amount });
+
+            Ok(())
+        }
+
+        /// Repay up to `amount` of debt
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::repay())]
+        pub fn repay(origin: OriginFor<T>, amount: FixedU128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut position = <Positions<T>>::get(&who).ok_or(Error::<T>::NoPosition)?;
+            let amount = amount.min(position.debt);
+            position.debt = position.debt.saturating_sub(amount);
+            Self::store(&who, position);
+
+            Self::deposit_event(Event::Repaid { who, amount });
+
+            Ok(())
+        }
+
+        /// Close `who`'s position if it exceeds the liquidation threshold, taking its collateral
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::liquidate())]
+        pub fn liquidate(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            let liquidator = ensure_signed(origin)?;
+
+            let position = <Positions<T>>::get(&who).ok_or(Error::<T>::NoPosition)?;
+            ensure!(!Self::is_healthy(&position)?, Error::<T>::PositionHealthy);
+
+            T::Currency::repatriate_reserved(&who, &liquidator, position.collateral, BalanceStatus::Free)?;
+            <Positions<T>>::remove(&who);
+
+            Self::deposit_event(Event::Liquidated {
+                who,
+                liquidator,
+                collateral: position.collateral,
+                debt: position.debt,
+            });
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Fresh oracle price of the collateral asset
+        pub fn collateral_price() -> Result<FixedU128, DispatchError> {
+            let (price, at) = T::PriceProvider::price(&T::CollateralAsset::get())
+                .ok_or(Error::<T>::PriceUnavailable)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(now.saturating_sub(at) <= T::MaxPriceAge::get(), Error::<T>::StalePrice);
+            Ok(price)
+        }
+
+        /// Whether the position's debt is within the liquidation threshold of its collateral value
+        pub fn is_healthy(position: &Position<BalanceOf<T>>) -> Result<bool, DispatchError> {
+            // Base units to whole tokens, the unit the price is quoted for
+            let collateral = FixedU128::saturating_from_rational(
+                position.collateral.saturated_into::<u128>(),
+                10u128.saturating_pow(T::CollateralDecimals::get().into()),
+            );
+            let value = Self::collateral_price()?.saturating_mul(collateral);
+            let limit = FixedU128::from_inner(T::LiquidationThreshold::get().mul_floor(value.into_inner()));
+            Ok(position.debt <= limit)
+        }
+
+        /// Store a position, dropping it once empty
+        fn store(who: &T::AccountId, position: Position<BalanceOf<T>>) {
+            if position.collateral.is_zero() && position.debt.is_zero() {
+                <Positions<T>>::remove(who);
+            } else {
+                <Positions<T>>::insert(who, position);
+            }
+        }
+    }
+}
+
+/// Weight functions needed for benchmarking
+pub trait WeightInfo {
+    fn deposit_collateral() -> Weight;
+    fn withdraw_collateral() -> Weight;
+    fn borrow() -> Weight;
+    fn repay() -> Weight;
+    fn liquidate() -> Weight;
+}
+
+/// Default weights (based on complexity analysis)
+impl WeightInfo for () {
+    fn deposit_collateral() -> Weight { Weight::from_parts(40_000, 0) }
+    fn withdraw_collateral() -> Weight { Weight::from_parts(60_000, 0) }
+    fn borrow() -> Weight { Weight::from_parts(60_000, 0) }
+    fn repay() -> Weight { Weight::from_parts(30_000, 0) }
+    fn liquidate() -> Weight { Weight::from_parts(80_000, 0) }
+}
@@ -0,0 +1,241 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Contract Permissions Pallet
+//!
+//! Decides who may upload contract code to Netchain.
+//!
+//! `pallet_contracts` checks uploads against its `UploadOrigin`. Each runtime picks one of
+//! these models, or combines them with `EitherOf`:
+//! - Open uploads: `EnsureSigned`, where the deposit `pallet_contracts` holds per byte of
+//!   code is the only barrier
+//! - Allowlist: [`EnsureAllowlisted`], admitting accounts `ManagerOrigin` granted the right
+//!   to upload, with an event for every grant and revocation
+//! - Staking threshold: [`EnsureStakeAtLeast`], admitting accounts with at least `MinStake`
+//!   actively bonded
+
+pub use pallet::*;
+
+use frame_support::{pallet_prelude::*, traits::EnsureOrigin};
+use frame_system::{pallet_prelude::*, RawOrigin};
+use sp_staking::StakingInterface;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Origin granting and revoking upload rights
+        type ManagerOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Weight information for extrinsics
+        type WeightInfo: WeightInfo;
+    }
+
+    /// Accounts allowed to upload contract code under [`EnsureAllowlisted`]
+    #[pallet::storage]
+    #[pallet::getter(fn uploaders)]
+    pub type Uploaders<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Accounts allowed to upload from genesis
+        pub uploaders: Vec<T::AccountId>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            for uploader in &self.uploaders {
+                Uploaders::<T>::insert(uploader, ());
+            }
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Account allowed to upload contract code
+        UploaderGranted { who: T::AccountId },
+        /// Account no longer allowed to upload contract code
+        UploaderRevoked { who: T::AccountId },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Account is already allowed to upload
+        AlreadyUploader,
+        /// Account is not allowed to upload
+        NotUploader,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Allow `who` to upload contract code
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::grant_upload())]
+        pub fn grant_upload(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::ManagerOrigin::ensure_origin(origin)?;
+
+            ensure!(!Uploaders::<T>::contains_key(&who), Error::<T>::AlreadyUploader);
+            Uploaders::<T>::insert(&who, ());
+
+            Self::deposit_event(Event::UploaderGranted { who });
+            Ok(())
+        }
+
+        /// Stop `who` from uploading contract code; code it already uploaded stays usable
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::revoke_upload())]
+        pub fn revoke_upload(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            T::ManagerOrigin::ensure_origin(origin)?;
+
+            Uploaders::<T>::take(&who).ok_or(Error::<T>::NotUploader)?;
+
+            Self::deposit_event(Event::UploaderRevoked { who });
+            Ok(())
+        }
+    }
+}
+
+/// Signed origin of an account on the [`Uploaders`] allowlist
+pub struct EnsureAllowlisted<T>(PhantomData<T>);
+
+impl<T: Config, O> EnsureOrigin<O> for EnsureAllowlisted<T>
+where
+    O: Into<Result<RawOrigin<T::AccountId>, O>> + From<RawOrigin<T::AccountId>>,
+{
+    type Success = T::AccountId;
+
+    fn try_origin(o: O) -> Result<Self::Success, O> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Signed(who) if Uploaders::<T>::contains_key(&who) => Ok(who),
+            r => Err(O::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn try_successful_origin() -> Result<O, ()> {
+        let who = T::AccountId::decode(&mut sp_runtime::traits::TrailingZeroInput::zeroes()).map_err(|_| ())?;
+        Uploaders::<T>::insert(&who, ());
+        Ok(O::from(RawOrigin::Signed(who)))
+    }
+}
+
+/// Signed origin of an account with at least `MinStake` actively bonded in `Staking`
+pub struct EnsureStakeAtLeast<Staking, MinStake>(PhantomData<(Staking, MinStake)>);
+
+impl<Staking, MinStake, O> EnsureOrigin<O> for EnsureStakeAtLeast<Staking, MinStake>
+where
+    Staking: StakingInterface,
+    MinStake: Get<Staking::Balance>,
+    O: Into<Result<RawOrigin<Staking::AccountId>, O>> + From<RawOrigin<Staking::AccountId>>,
+{
+    type Success = Staking::AccountId;
+
+    fn try_origin(o: O) -> Result<Self::Success, O> {
+        o.into().and_then(|o| match o {
+            RawOrigin::Signed(who)
+                if Staking::stake(&who).map_or(false, |stake| stake.active >= MinStake::get()) =>
+            {
+                Ok(who)
+            },
+            r => Err(O::from(r)),
+        })
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn try_successful_origin() -> Result<O, ()> {
+        Err(())
+    }
+}
+
+/// Weight functions needed for benchmarking
+pub trait WeightInfo {
+    fn grant_upload() -> Weight;
+    fn revoke_upload() -> Weight;
+}
+
+/// Default weights (based on complexity analysis)
+impl WeightInfo for () {
+    fn grant_upload() -> Weight { Weight::from_parts(15_000, 0) }
+    fn revoke_upload() -> Weight { Weight::from_parts(15_000, 0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as pallet_contract_permissions;
+    use frame_support::{assert_noop, assert_ok, derive_impl};
+    use sp_runtime::{BuildStorage, DispatchError};
+
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test
+        {
+            System: frame_system,
+            ContractPermissions: pallet_contract_permissions,
+        }
+    );
+
+    #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+    impl frame_system::Config for Test {
+        type Block = Block;
+    }
+
+    impl Config for Test {
+        type RuntimeEvent = RuntimeEvent;
+        type ManagerOrigin = frame_system::EnsureRoot<u64>;
+        type WeightInfo = ();
+    }
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        pallet_contract_permissions::GenesisConfig::<Test> { uploaders: vec![1] }
+            .assimilate_storage(&mut t)
+            .unwrap();
+
+        let mut ext: sp_io::TestExternalities = t.into();
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    fn can_upload(who: u64) -> bool {
+        EnsureAllowlisted::<Test>::try_origin(RuntimeOrigin::signed(who)).is_ok()
+    }
+
+    #[test]
+    fn genesis_uploaders_are_allowlisted() {
+        new_test_ext().execute_with(|| {
+            assert!(can_upload(1));
+            assert!(!can_upload(2));
+            assert!(EnsureAllowlisted::<Test>::try_origin(RuntimeOrigin::root()).is_err());
+        });
+    }
+
+    #[test]
+    fn manager_grants_and_revokes_upload_rights() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(ContractPermissions::grant_upload(RuntimeOrigin::signed(1), 2), DispatchError::BadOrigin);
+
+            assert_ok!(ContractPermissions::grant_upload(RuntimeOrigin::root(), 2));
+            System::assert_last_event(Event::UploaderGranted { who: 2 }.into());
+            assert!(can_upload(2));
+            assert_noop!(ContractPermissions::grant_upload(RuntimeOrigin::root(), 2), Error::<Test>::AlreadyUploader);
+
+            assert_ok!(ContractPermissions::revoke_upload(RuntimeOrigin::root(), 2));
+            System::assert_last_event(Event::UploaderRevoked { who: 2 }.into());
+            assert!(!can_upload(2));
+            assert_noop!(ContractPermissions::revoke_upload(RuntimeOrigin::root(), 2), Error::<Test>::NotUploader);
+        });
+    }
+}
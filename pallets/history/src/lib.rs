@@ -0,0 +1,376 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # History Pallet
+//!
+//! A lightweight on-chain index of Netchain activity for explorers and wallets.
+//!
+//! Other pallets report compact entries (kind, account, key hash, item hash) through the
+//! [`HistoryRecorder`] trait. Entries are kept in a ring buffer of `HistoryDepth` slots, so
+//! storage stays bounded and the oldest entries are overwritten first. When an upgrade lowers
+//! the depth, the slots beyond it are pruned a few at a time in idle blocks.
+//!
+//! Indexed activity:
+//! - Cross-shard transfers (key: destination shard id)
+//! - IBC packets sent and received (key: `<port>/<channel>`)
+//! - Oracle data updates (key: oracle data key)
+//!
+//! The index is queried by account or by key through [`runtime_api::HistoryApi`], so
+//! "show me this account's cross-shard transfers" no longer needs a scan of every block's
+//! events.
+
+pub use pallet::*;
+
+pub mod migrations;
+
+use frame_support::pallet_prelude::*;
+use frame_system::pallet_prelude::*;
+use sp_std::vec::Vec;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use sp_core::H256;
+
+/// Position of an entry in the global history sequence
+pub type HistoryIndex = u64;
+
+/// Kind of activity an entry indexes
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum HistoryKind {
+    /// Balance transfer between two shards
+    CrossShardTransfer,
+    /// IBC packet sent or received
+    IbcPacket,
+    /// Oracle data provided for a key
+    OracleUpdate,
+}
+
+/// Compact history entry
+#[derive(Clone, PartialEq, Eq, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct HistoryEntry<AccountId, BlockNumber> {
+    /// Position in the global history sequence
+    pub index: HistoryIndex,
+    /// Block the activity happened in
+    pub block: BlockNumber,
+    /// Kind of activity
+    pub kind: HistoryKind,
+    /// Account involved in the activity
+    pub account: AccountId,
+    /// Hash of the kind-specific key
+    pub key: H256,
+    /// Hash identifying the indexed item (transaction hash, packet commitment, data hash)
+    pub hash: H256,
+}
+
+/// Sink for history entries, implemented by this pallet and by `()` as a no-op
+pub trait HistoryRecorder<AccountId> {
+    /// Record that `account` took part in `kind` activity on `key`, identified by `hash`
+    fn record(kind: HistoryKind, account: &AccountId, key: &[u8], hash: H256);
+}
+
+impl<AccountId> HistoryRecorder<AccountId> for () {
+    fn record(_kind: HistoryKind, _account: &AccountId, _key: &[u8], _hash: H256) {}
+}
+
+/// Hash under which a raw key is indexed
+pub fn key_hash(key: &[u8]) -> H256 {
+    BlakeTwo256::hash(key)
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    /// The in-code storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+    #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// Number of ring buffer slots (entries kept before the oldest is overwritten)
+        #[pallet::constant]
+        type HistoryDepth: Get<u32>;
+    }
+
+    /// Ring buffer of history entries, keyed by slot
+    #[pallet::storage]
+    #[pallet::getter(fn entries)]
+    pub type Entries<T: Config> =
+        StorageMap<_, Twox64Concat, u32, HistoryEntry<T::AccountId, BlockNumberFor<T>>>;
+
+    /// Total number of entries ever recorded (index of the next entry)
+    #[pallet::storage]
+    #[pallet::getter(fn entry_count)]
+    pub type EntryCount<T> = StorageValue<_, HistoryIndex, ValueQuery>;
+
+    /// End of the slots left to prune beyond `HistoryDepth` after the depth was lowered;
+    /// pruning walks down from it to the depth
+    #[pallet::storage]
+    #[pallet::getter(fn prune_cursor)]
+    pub type PruneCursor<T> = StorageValue<_, u32>;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            Self::prune_beyond_depth(remaining_weight)
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Append an entry, overwriting the oldest one once the buffer is full
+        pub fn push(kind: HistoryKind, account: &T::AccountId, key: H256, hash: H256) {
+            let depth = T::HistoryDepth::get();
+            if depth == 0 {
+                return;
+            }
+
+            let index = <EntryCount<T>>::get();
+            let slot = (index % depth as u64) as u32;
+            let entry = HistoryEntry {
+                index,
+                block: frame_system::Pallet::<T>::block_number(),
+                kind,
+                account: account.clone(),
+                key,
+                hash,
+            };
+
+            <Entries<T>>::insert(slot, entry);
+            <EntryCount<T>>::put(index.saturating_add(1));
+        }
+
+        /// Retained entries matching `filter`, newest first, at most `limit` of them
+        fn query(
+            limit: u32,
+            filter: impl Fn(&HistoryEntry<T::AccountId, BlockNumberFor<T>>) -> bool,
+        ) -> Vec<HistoryEntry<T::AccountId, BlockNumberFor<T>>> {
+            let depth = T::HistoryDepth::get() as u64;
+            let count = <EntryCount<T>>::get();
+            let retained = count.min(depth);
+
+            let mut result = Vec::new();
+            for index in (count - retained..count).rev() {
+                if result.len() >= limit as usize {
+                    break;
+                }
+                // After the depth changed, a slot may still hold an entry of another index
+                if let Some(entry) = <Entries<T>>::get((index % depth) as u32) {
+                    if entry.index == index && filter(&entry) {
+                        result.push(entry);
+                    }
+                }
+            }
+            result
+        }
+
+        /// Remove the entries of slots at or beyond `HistoryDepth`, from [`PruneCursor`] down,
+        /// until `remaining_weight` is used up; the next idle block resumes where this one
+        /// stopped
+        pub fn prune_beyond_depth(remaining_weight: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            let mut used = db.reads(1);
+            if used.any_gt(remaining_weight) {
+                return Weight::zero();
+            }
+            let Some(mut cursor) = <PruneCursor<T>>::get() else {
+                return used;
+            };
+
+            // The cursor is written once, after the slots
+            let depth = T::HistoryDepth::get();
+            while cursor > depth && !used.saturating_add(db.writes(2)).any_gt(remaining_weight) {
+                cursor -= 1;
+                <Entries<T>>::remove(cursor);
+                used.saturating_accrue(db.writes(1));
+            }
+
+            if cursor > depth {
+                <PruneCursor<T>>::put(cursor);
+            } else {
+                <PruneCursor<T>>::kill();
+            }
+            used.saturating_add(db.writes(1))
+        }
+
+        /// Entries involving `account`, optionally restricted to one kind
+        pub fn history_by_account(
+            account: &T::AccountId,
+            kind: Option<HistoryKind>,
+            limit: u32,
+        ) -> Vec<HistoryEntry<T::AccountId, BlockNumberFor<T>>> {
+            Self::query(limit, |entry| {
+                &entry.account == account && kind.map_or(true, |k| entry.kind == k)
+            })
+        }
+
+        /// Entries recorded under the raw `key`, optionally restricted to one kind
+        pub fn history_by_key(
+            key: &[u8],
+            kind: Option<HistoryKind>,
+            limit: u32,
+        ) -> Vec<HistoryEntry<T::AccountId, BlockNumberFor<T>>> {
+            let key = key_hash(key);
+            Self::query(limit, |entry| entry.key == key && kind.map_or(true, |k| entry.kind == k))
+        }
+    }
+}
+
+impl<T: Config> HistoryRecorder<T::AccountId> for Pallet<T> {
+    fn record(kind: HistoryKind, account: &T::AccountId, key: &[u8], hash: H256) {
+        Self::push(kind, account, key_hash(key), hash);
+    }
+}
+
+/// Runtime API for explorers and wallets
+pub mod runtime_api {
+    use super::*;
+    use codec::Codec;
+
+    sp_api::decl_runtime_apis! {
+        /// API for querying the history index
+        pub trait HistoryApi<AccountId, BlockNumber>
+        where
+            AccountId: Codec,
+            BlockNumber: Codec,
+        {
+            /// Newest entries involving `account`, at most `limit`
+            fn history_by_account(
+                account: AccountId,
+                kind: Option<HistoryKind>,
+                limit: u32,
+            ) -> Vec<HistoryEntry<AccountId, BlockNumber>>;
+
+            /// Newest entries recorded under the raw `key`, at most `limit`
+            fn history_by_key(
+                key: Vec<u8>,
+                kind: Option<HistoryKind>,
+                limit: u32,
+            ) -> Vec<HistoryEntry<AccountId, BlockNumber>>;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as pallet_history;
+    use frame_support::{
+        derive_impl, parameter_types,
+        traits::{GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion},
+        weights::constants::RocksDbWeight,
+    };
+    use sp_runtime::BuildStorage;
+
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test
+        {
+            System: frame_system,
+            History: pallet_history,
+        }
+    );
+
+    parameter_types! {
+        pub static HistoryDepth: u32 = 3;
+        pub const OldHistoryDepth: u32 = 6;
+    }
+
+    #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+    impl frame_system::Config for Test {
+        type Block = Block;
+        type DbWeight = RocksDbWeight;
+    }
+
+    impl Config for Test {
+        type HistoryDepth = HistoryDepth;
+    }
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        let mut ext: sp_io::TestExternalities = t.into();
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    fn record(kind: HistoryKind, account: u64, key: &[u8], item: u8) {
+        <History as HistoryRecorder<u64>>::record(kind, &account, key, H256::repeat_byte(item));
+    }
+
+    fn items(entries: Vec<HistoryEntry<u64, u64>>) -> Vec<H256> {
+        entries.into_iter().map(|entry| entry.hash).collect()
+    }
+
+    #[test]
+    fn entries_are_queried_newest_first_and_the_oldest_overwritten() {
+        new_test_ext().execute_with(|| {
+            record(HistoryKind::CrossShardTransfer, 1, b"shard-1", 1);
+            record(HistoryKind::IbcPacket, 1, b"transfer/channel-0", 2);
+            record(HistoryKind::OracleUpdate, 2, b"NET/USD", 3);
+
+            assert_eq!(History::entry_count(), 3);
+            assert_eq!(
+                items(History::history_by_account(&1, None, 10)),
+                vec![H256::repeat_byte(2), H256::repeat_byte(1)]
+            );
+            assert_eq!(
+                items(History::history_by_account(&1, Some(HistoryKind::IbcPacket), 10)),
+                vec![H256::repeat_byte(2)]
+            );
+            assert_eq!(items(History::history_by_key(b"NET/USD", None, 10)), vec![H256::repeat_byte(3)]);
+            assert_eq!(items(History::history_by_account(&1, None, 1)), vec![H256::repeat_byte(2)]);
+
+            // The fourth entry takes the first one's slot
+            System::set_block_number(2);
+            record(HistoryKind::OracleUpdate, 1, b"NET/USD", 4);
+            assert_eq!(History::entries(0).map(|entry| (entry.index, entry.block)), Some((3, 2)));
+            assert_eq!(
+                items(History::history_by_account(&1, None, 10)),
+                vec![H256::repeat_byte(4), H256::repeat_byte(2)]
+            );
+            assert!(History::history_by_key(b"shard-1", None, 10).is_empty());
+        });
+    }
+
+    #[test]
+    fn slots_beyond_a_lowered_depth_are_pruned_in_idle_blocks() {
+        new_test_ext().execute_with(|| {
+            HistoryDepth::set(6);
+            for item in 1..=5 {
+                record(HistoryKind::OracleUpdate, 1, b"NET/USD", item);
+            }
+
+            // Slots 2 to 4 are left beyond the lowered depth
+            HistoryDepth::set(2);
+            StorageVersion::new(0).put::<History>();
+            migrations::v1::LowerHistoryDepth::<Test, OldHistoryDepth>::on_runtime_upgrade();
+            assert_eq!(History::on_chain_storage_version(), 1);
+            assert_eq!(History::prune_cursor(), Some(5));
+
+            // Entries recorded under the old depth are not returned from the wrong slots
+            assert!(History::history_by_account(&1, None, 10).is_empty());
+
+            // Pruning stops when the idle weight runs out and resumes in the next idle block
+            let db = <Test as frame_system::Config>::DbWeight::get();
+            let used = History::on_idle(2, db.reads(1).saturating_add(db.writes(3)));
+            assert_eq!(used, db.reads(1).saturating_add(db.writes(3)));
+            assert_eq!(History::prune_cursor(), Some(3));
+            assert!(History::entries(3).is_none() && History::entries(4).is_none());
+            assert!(History::entries(2).is_some());
+
+            History::on_idle(3, Weight::MAX);
+            assert_eq!(History::prune_cursor(), None);
+            assert!(History::entries(2).is_none());
+            assert!(History::entries(1).is_some());
+            assert_eq!(History::on_idle(4, Weight::MAX), db.reads(1));
+
+            // New entries wrap over the old ones below the depth
+            record(HistoryKind::OracleUpdate, 1, b"NET/USD", 6);
+            record(HistoryKind::OracleUpdate, 1, b"NET/USD", 7);
+            assert_eq!(
+                items(History::history_by_account(&1, None, 10)),
+                vec![H256::repeat_byte(7), H256::repeat_byte(6)]
+            );
+        });
+    }
+}
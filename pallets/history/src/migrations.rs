@@ -0,0 +1,36 @@
+//! Storage migrations for the history pallet
+
+use super::*;
+
+/// Version 1: slots beyond a lowered `HistoryDepth` are pruned in idle blocks
+pub mod v1 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Schedule pruning of the slots `OldDepth` left in use beyond the current `HistoryDepth`
+    ///
+    /// Entries are not moved: the slots below the depth keep theirs until the ring buffer
+    /// wraps over them, and queries skip those recorded under the old depth.
+    pub struct InnerLowerHistoryDepth<T, OldDepth>(PhantomData<(T, OldDepth)>);
+
+    impl<T: Config, OldDepth: Get<u32>> UncheckedOnRuntimeUpgrade for InnerLowerHistoryDepth<T, OldDepth> {
+        fn on_runtime_upgrade() -> Weight {
+            let in_use = <EntryCount<T>>::get().min(OldDepth::get().into()) as u32;
+            if in_use > T::HistoryDepth::get() {
+                <PruneCursor<T>>::put(in_use);
+            }
+
+            T::DbWeight::get().reads_writes(1, 1)
+        }
+    }
+
+    /// [`InnerLowerHistoryDepth`], run once when upgrading from storage version 0
+    pub type LowerHistoryDepth<T, OldDepth> = VersionedMigration<
+        0,
+        1,
+        InnerLowerHistoryDepth<T, OldDepth>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
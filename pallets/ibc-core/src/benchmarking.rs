@@ -0,0 +1,154 @@
+//! Benchmarking setup for pallet-ibc-core
+//!
+//! The packet benchmarks run over a channel that is already open, with its client and
+//! connection written straight to storage: the handshake is not what is being measured.
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as IbcCore;
+use frame_benchmarking::v2::*;
+use frame_support::traits::Currency;
+use frame_system::RawOrigin;
+use sp_std::vec;
+
+const PORT: &[u8] = b"transfer";
+const COUNTERPARTY_CHANNEL: &[u8] = b"channel-7";
+
+/// Bounded identifier; readable local identifiers like `channel-0` are converted to the
+/// sequential form the pallet assigns
+fn id(raw: &[u8]) -> Identifier {
+    let raw = [CLIENT_PREFIX, CONNECTION_PREFIX, CHANNEL_PREFIX]
+        .iter()
+        .find_map(|prefix| parse_display_id(raw, prefix))
+        .unwrap_or_else(|| raw.to_vec());
+    Identifier::truncate_from(raw)
+}
+
+/// A funded caller owning [`PORT`] and an open, unordered `channel-0` on it over an active
+/// client
+fn open_channel<T: Config>() -> (T::AccountId, ChannelId) {
+    let caller: T::AccountId = whitelisted_caller();
+    let balance = T::Currency::minimum_balance().saturating_mul(1_000_000u32.into());
+    T::Currency::make_free_balance_be(&caller, balance);
+    T::Currency::make_free_balance_be(&IbcCore::<T>::account_id(), T::Currency::minimum_balance());
+
+    let client_id = id(b"client-0");
+    Clients::<T>::insert(&client_id, ClientState {
+        client_type: ClientType::Mock,
+        chain_id: ChainId::truncate_from(b"counterparty".to_vec()),
+        latest_height: 1,
+        frozen: false,
+        trust_level: DEFAULT_TRUST_LEVEL,
+        unbonding_period: u64::MAX,
+        last_updated: 0,
+        expired: false,
+    });
+
+    let connection_id = id(b"connection-0");
+    Connections::<T>::insert(&connection_id, ConnectionEnd {
+        state: ConnectionState::Open,
+        client_id: client_id.clone(),
+        counterparty_client_id: client_id,
+        version: Version::truncate_from(b"1".to_vec()),
+    });
+
+    PortOwners::<T>::insert(id(PORT), &caller);
+    let channel_id = id(b"channel-0");
+    Channels::<T>::insert(id(PORT), &channel_id, ChannelEnd {
+        state: ChannelState::Open,
+        connection_id,
+        port_id: id(PORT),
+        counterparty_port_id: id(PORT),
+        version: Version::truncate_from(b"ics20-1".to_vec()),
+        ordering: ChannelOrder::Unordered,
+        upgrade_sequence: 0,
+        next_sequence_send: 1,
+        next_sequence_recv: 1,
+        next_sequence_ack: 1,
+    });
+
+    (caller, channel_id)
+}
+
+/// Packet 1 carrying `d` bytes from `channel-0` to the counterparty
+fn outgoing_packet(channel_id: &ChannelId, d: u32) -> Packet {
+    Packet {
+        sequence: 1,
+        source_port: id(PORT),
+        source_channel: channel_id.clone(),
+        destination_port: id(PORT),
+        destination_channel: id(COUNTERPARTY_CHANNEL),
+        data: PacketData::truncate_from(vec![1u8; d as usize]),
+        timeout_height: 0,
+        timeout_timestamp: 0,
+    }
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn send_packet(d: Linear<0, { T::MaxPacketDataSize::get() }>) {
+        let (caller, channel_id) = open_channel::<T>();
+        let data = vec![1u8; d as usize];
+
+        #[extrinsic_call]
+        send_packet(
+            RawOrigin::Signed(caller),
+            id(PORT),
+            channel_id,
+            id(PORT),
+            id(COUNTERPARTY_CHANNEL),
+            data,
+            0,
+            0,
+        );
+
+        assert!(PacketCommitments::<T>::contains_key(id(PORT), 1));
+    }
+
+    #[benchmark]
+    fn recv_packet(d: Linear<0, { T::MaxPacketDataSize::get() }>) {
+        let (caller, channel_id) = open_channel::<T>();
+        let packet = Packet {
+            sequence: 1,
+            source_port: id(PORT),
+            source_channel: id(COUNTERPARTY_CHANNEL),
+            destination_port: id(PORT),
+            destination_channel: channel_id,
+            data: PacketData::truncate_from(vec![1u8; d as usize]),
+            timeout_height: 0,
+            timeout_timestamp: 0,
+        };
+
+        // An empty proof verifies against a root equal to the commitment's leaf
+        let path = commitment_path(PORT, &id(COUNTERPARTY_CHANNEL), 1);
+        let root = membership_leaf(&path, &BlakeTwo256::hash_of(&packet));
+        ConsensusStates::<T>::insert(id(b"client-0"), 1, ConsensusState { timestamp: 0, root });
+
+        #[extrinsic_call]
+        recv_packet(RawOrigin::Signed(caller), packet, vec![], 1);
+
+        assert!(PacketAcknowledgments::<T>::contains_key(id(PORT), 1));
+    }
+
+    #[benchmark]
+    fn acknowledge_packet(d: Linear<0, { T::MaxPacketDataSize::get() }>) {
+        let (caller, channel_id) = open_channel::<T>();
+        let packet = outgoing_packet(&channel_id, d);
+        PacketCommitments::<T>::insert(id(PORT), 1, BlakeTwo256::hash_of(&packet));
+
+        // An empty proof verifies against a root equal to the acknowledgement's leaf
+        let acknowledgment = encode_acknowledgement(&Ok(Vec::new()));
+        let path = acknowledgement_path(PORT, &id(COUNTERPARTY_CHANNEL), 1);
+        let root = membership_leaf(&path, &BlakeTwo256::hash(&acknowledgment));
+        ConsensusStates::<T>::insert(id(b"client-0"), 1, ConsensusState { timestamp: 0, root });
+
+        #[extrinsic_call]
+        acknowledge_packet(RawOrigin::Signed(caller), packet, acknowledgment, Vec::new(), 1);
+
+        assert_eq!(PacketOutcomes::<T>::get(id(PORT), 1), Some(PacketStatus::Acknowledged));
+    }
+}
@@ -5,24 +5,51 @@
 //! A simplified Inter-Blockchain Communication (IBC) protocol implementation for Netchain.
 //! 
 //! This pallet provides:
-//! - Cross-chain client management
+//! - Cross-chain client management, each client verifying headers with the light client of
+//!   its counterparty's consensus ([`ClientType`]: Tendermint, GRANDPA, mock or localhost)
 //! - Connection establishment between chains
+//! - Port ownership: channels on a port can only be opened, and packets on them sent, by the
+//!   account or pallet bound to it
 //! - Channel creation for application-specific communication
-//! - Packet routing and acknowledgments
+//! - ICS-09 localhost client, so applications on this chain can open channels to each other
+//!   and exercise the full packet lifecycle without an external counterparty
+//! - Channel upgrades renegotiating version and ordering without closing the channel
+//! - Packet routing to application modules and ICS-04 acknowledgments
 //! - Ultra-low fees for cross-chain operations
+//! - Host consensus-state export so counterparties can run a Netchain light client
+//! - Per-channel traffic counters for monitoring channel health
+//!
+//! Client, connection and channel identifiers are assigned in sequence as
+//! [`sequential_id`]s, e.g. `channel-` followed by the big-endian `u32` 3; [`display_id`]
+//! renders them as `channel-3` and [`parse_display_id`] parses that form back.
+//!
+//! Packet handling logs to the [`LOG_TARGET`] target within tracing spans named after the
+//! packet call, identifying each packet by its port, channel (in readable form) and sequence.
 //!
 //! ## Security Features
 //! - Replay attack prevention through sequence numbers
 //! - Timeout handling for failed packets
 //! - Client state verification
+//! - Client expiry after the unbonding period and pruning of stale consensus states
 //! - Connection and channel state validation
+//! - Packet size bounds and per-channel rate limiting
+//! - Bounded storage: chain identifiers, versions, packet data, acknowledgements and GRANDPA
+//!   authority sets all have a maximum length
 
 pub use pallet::*;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod light_client;
+pub use light_client::{ClientError, ClientType, Context, GrandpaClient, LightClient, MockHeader, TendermintClient};
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
+
 use frame_support::{
     dispatch::{DispatchResult, DispatchResultWithPostInfo},
     pallet_prelude::*,
-    traits::{Get, ReservableCurrency, ExistenceRequirement},
+    traits::{EnsureOrigin, Get, ReservableCurrency, ExistenceRequirement, StorageVersion},
     PalletId,
 };
 use frame_system::pallet_prelude::*;
@@ -32,15 +59,226 @@ use sp_runtime::{
     SaturatedConversion,
 };
 use sp_core::H256;
+use pallet_history::{HistoryKind, HistoryRecorder};
+use pallet_safeguard::{PauseGuard, Subsystem};
+pub use netchain_primitives::{display_id, parse_display_id, sequential_id, FailureReason};
+use netchain_primitives::{decimal, split_sequential_id, Printable};
+
+/// Target of the pallet's log lines
+pub const LOG_TARGET: &str = "runtime::ibc";
 
+/// Current storage version
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(5);
+
+/// Maximum length of an IBC identifier
+pub type MaxIdentifierLen = ConstU32<64>;
+/// Bounded IBC identifier
+pub type Identifier = BoundedVec<u8, MaxIdentifierLen>;
 /// IBC client identifier
-pub type ClientId = Vec<u8>;
+pub type ClientId = Identifier;
 /// IBC connection identifier  
-pub type ConnectionId = Vec<u8>;
+pub type ConnectionId = Identifier;
 /// IBC channel identifier
-pub type ChannelId = Vec<u8>;
+pub type ChannelId = Identifier;
 /// IBC port identifier
-pub type PortId = Vec<u8>;
+pub type PortId = Identifier;
+
+/// Maximum length of a chain identifier
+pub type MaxChainIdLen = ConstU32<64>;
+/// Bounded chain identifier
+pub type ChainId = BoundedVec<u8, MaxChainIdLen>;
+/// Maximum length of a connection or channel version
+pub type MaxVersionLen = ConstU32<512>;
+/// Bounded connection or channel version
+pub type Version = BoundedVec<u8, MaxVersionLen>;
+/// Maximum length of a packet's data; `MaxPacketDataSize` may only lower it
+pub type MaxPacketDataLen = ConstU32<{ 64 * 1024 }>;
+/// Bounded packet data
+pub type PacketData = BoundedVec<u8, MaxPacketDataLen>;
+
+/// Prefix of client identifiers generated by this chain
+pub const CLIENT_PREFIX: &[u8] = b"client-";
+/// Prefix of connection identifiers generated by this chain
+pub const CONNECTION_PREFIX: &[u8] = b"connection-";
+/// Prefix of channel identifiers generated by this chain
+pub const CHANNEL_PREFIX: &[u8] = b"channel-";
+/// Identifier of the ICS-09 localhost client, through which this chain is its own counterparty
+pub const LOCALHOST_CLIENT_ID: &[u8] = b"09-localhost";
+/// Identifier of the connection over the localhost client
+pub const LOCALHOST_CONNECTION_ID: &[u8] = b"connection-localhost";
+
+/// Whether `id` is a well-formed identifier: ICS-024 charset and at least 2 bytes, or a
+/// [`sequential_id`] as generated by Netchain counterparties
+///
+/// Used for identifiers chosen by users or counterparties, such as port IDs.
+pub fn is_valid_identifier(id: &[u8]) -> bool {
+    (id.len() >= 2 && id.iter().all(|c| c.is_ascii_alphanumeric() || b"._+-#[]<>".contains(c)))
+        || split_sequential_id(id).is_some()
+}
+
+/// Whether `id` is an identifier generated by this chain, i.e. `<prefix>` followed by a
+/// big-endian `u32` (see [`sequential_id`])
+pub fn is_valid_local_identifier(id: &[u8], prefix: &[u8]) -> bool {
+    netchain_primitives::sequential_id_number(id, prefix).is_some()
+}
+
+/// Trust level suggested to counterparties tracking Netchain (2/3, in percent)
+pub const DEFAULT_TRUST_LEVEL: u32 = 67;
+
+/// Maximum depth of a membership proof
+pub const MAX_PROOF_DEPTH: usize = 64;
+
+/// Consensus states of a closed or removed client cleared by the call itself; the rest are
+/// cleared on idle blocks
+pub const MAX_CONSENSUS_STATES_CLEARED: u32 = 32;
+
+/// Storage cursor to resume clearing a removed client's consensus states from
+pub type ClearCursor = BoundedVec<u8, ConstU32<256>>;
+
+/// One level of a binary Merkle membership proof
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct ProofStep {
+    /// Hash of the sibling node
+    pub sibling: H256,
+    /// Whether the sibling is the left child
+    pub sibling_is_left: bool,
+}
+
+/// Leaf committing to `value` stored under `path` in a counterparty's commitment tree
+pub fn membership_leaf(path: &[u8], value: &H256) -> H256 {
+    BlakeTwo256::hash(&[path, value.as_bytes()].concat())
+}
+
+/// Whether `proof` shows `value` stored under `path` in the tree with root `root`
+///
+/// Nodes are `blake2_256(left ++ right)`, leaves are [`membership_leaf`].
+pub fn verify_membership(root: &H256, path: &[u8], value: &H256, proof: &[ProofStep]) -> bool {
+    if proof.len() > MAX_PROOF_DEPTH {
+        return false;
+    }
+    let computed = proof.iter().fold(membership_leaf(path, value), |node, step| {
+        if step.sibling_is_left {
+            BlakeTwo256::hash(&[step.sibling.as_bytes(), node.as_bytes()].concat())
+        } else {
+            BlakeTwo256::hash(&[node.as_bytes(), step.sibling.as_bytes()].concat())
+        }
+    });
+    computed == *root
+}
+
+/// ICS-24 path under which a chain stores the acknowledgement of a received packet
+pub fn acknowledgement_path(port_id: &[u8], channel_id: &[u8], sequence: u64) -> Vec<u8> {
+    let mut path = b"acks/ports/".to_vec();
+    path.extend_from_slice(port_id);
+    path.extend_from_slice(b"/channels/");
+    path.extend_from_slice(channel_id);
+    path.extend_from_slice(b"/sequences/");
+    path.extend_from_slice(decimal(sequence).as_slice());
+    path
+}
+
+/// ICS-24 path under which a chain stores the commitment of a sent packet
+pub fn commitment_path(port_id: &[u8], channel_id: &[u8], sequence: u64) -> Vec<u8> {
+    let mut path = b"commitments/ports/".to_vec();
+    path.extend_from_slice(port_id);
+    path.extend_from_slice(b"/channels/");
+    path.extend_from_slice(channel_id);
+    path.extend_from_slice(b"/sequences/");
+    path.extend_from_slice(decimal(sequence).as_slice());
+    path
+}
+
+/// ICS-24 path under which a chain stores the hash of a channel end
+pub fn channel_end_path(port_id: &[u8], channel_id: &[u8]) -> Vec<u8> {
+    let mut path = b"channelEnds/ports/".to_vec();
+    path.extend_from_slice(port_id);
+    path.extend_from_slice(b"/channels/");
+    path.extend_from_slice(channel_id);
+    path
+}
+
+/// Acknowledgement returned by an application module: `Ok(result)` or `Err(error)`
+pub type Acknowledgement = Result<Vec<u8>, Vec<u8>>;
+
+/// Routes received packets to the application module bound to their destination port
+pub trait IbcRouter<AccountId> {
+    /// Deliver `packet`, relayed by `relayer`, and return the application's acknowledgement
+    fn on_recv_packet(packet: &Packet, relayer: &AccountId) -> Acknowledgement;
+
+    /// Upper bound on the weight an application spends handling one received packet
+    ///
+    /// Charged by `recv_packet` on top of its own weight.
+    fn max_recv_packet_weight() -> Weight {
+        Weight::zero()
+    }
+}
+
+/// Without application modules every packet is acknowledged with an empty result
+impl<AccountId> IbcRouter<AccountId> for () {
+    fn on_recv_packet(_packet: &Packet, _relayer: &AccountId) -> Acknowledgement {
+        Ok(Vec::new())
+    }
+}
+
+/// Encode an acknowledgement as the ICS-04 generic acknowledgement JSON
+///
+/// `{"result":"<base64 result>"}` on success, `{"error":"<error>"}` on failure.
+pub fn encode_acknowledgement(ack: &Acknowledgement) -> Vec<u8> {
+    let mut out = Vec::new();
+    match ack {
+        Ok(result) => {
+            out.extend_from_slice(b"{\"result\":\"");
+            base64_encode(result, &mut out);
+        },
+        Err(error) => {
+            out.extend_from_slice(b"{\"error\":\"");
+            json_escape(error, &mut out);
+        },
+    }
+    out.extend_from_slice(b"\"}");
+    out
+}
+
+/// Whether `ack` is an ICS-04 error acknowledgement, as [`encode_acknowledgement`] writes
+/// for a failure
+pub fn is_error_acknowledgement(ack: &[u8]) -> bool {
+    ack.starts_with(b"{\"error\":")
+}
+
+/// Standard padded base64
+fn base64_encode(data: &[u8], out: &mut Vec<u8>) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize]);
+            } else {
+                out.push(b'=');
+            }
+        }
+    }
+}
+
+/// JSON string body for `data`, escaping quotes, backslashes and control characters
+fn json_escape(data: &[u8], out: &mut Vec<u8>) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    for &c in data {
+        match c {
+            b'"' | b'\\' => out.extend_from_slice(&[b'\\', c]),
+            0x00..=0x1f | 0x7f..=0xff => {
+                // Non-ASCII bytes are escaped too, so invalid UTF-8 stays valid JSON
+                out.extend_from_slice(b"\\u00");
+                out.push(HEX[(c >> 4) as usize]);
+                out.push(HEX[(c & 0xf) as usize]);
+            },
+            _ => out.push(c),
+        }
+    }
+}
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -48,6 +286,7 @@ pub mod pallet {
 
     #[pallet::pallet]
     #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::config]
@@ -70,14 +309,81 @@ pub mod pallet {
         #[pallet::constant]
         type MaxChannels: Get<u32>;
 
-        /// Fee for creating an IBC client (ultra-low)
+        /// Deposit reserved for each client, returned when its owner closes it
         #[pallet::constant]
-        type ClientCreationFee: Get<BalanceOf<Self>>;
+        type ClientDeposit: Get<BalanceOf<Self>>;
 
         /// Fee for cross-chain packet transmission (ultra-low)
         #[pallet::constant]
         type PacketTransmissionFee: Get<BalanceOf<Self>>;
 
+        /// Maximum size of a packet's data payload in bytes, at most [`MaxPacketDataLen`]
+        #[pallet::constant]
+        type MaxPacketDataSize: Get<u32>;
+
+        /// Maximum length of the encoded acknowledgement of a received packet; packets the
+        /// application acknowledges with a longer one are not received
+        #[pallet::constant]
+        type MaxAcknowledgementLen: Get<u32>;
+
+        /// Length of a channel rate-limit window in blocks
+        #[pallet::constant]
+        type RateLimitWindow: Get<BlockNumberFor<Self>>;
+
+        /// Maximum packets a channel may carry per rate-limit window
+        #[pallet::constant]
+        type MaxPacketsPerWindow: Get<u32>;
+
+        /// Maximum packet payload bytes a channel may carry per rate-limit window
+        #[pallet::constant]
+        type MaxBytesPerWindow: Get<u32>;
+
+        /// Chain identifier counterparties use for Netchain, at most [`MaxChainIdLen`] bytes
+        #[pallet::constant]
+        type HostChainId: Get<Vec<u8>>;
+
+        /// Unbonding period of Netchain's validator set (seconds)
+        #[pallet::constant]
+        type HostUnbondingPeriod: Get<u64>;
+
+        /// Hash of the next validator set, committed to in the host consensus state
+        type HostNextValidatorsHash: Get<H256>;
+
+        /// Id and authorities of the GRANDPA authority set currently finalizing Netchain
+        type HostAuthoritySet: Get<(u64, light_client::grandpa::AuthorityList)>;
+
+        /// Index of sent and received packets for explorer queries
+        type History: HistoryRecorder<Self::AccountId>;
+
+        /// Emergency pause switch; while `Subsystem::Ibc` is paused packets are neither sent
+        /// nor received, but sent packets are still acknowledged or timed out
+        type Safeguard: PauseGuard;
+
+        /// Application modules receiving packets, by destination port
+        type Router: IbcRouter<Self::AccountId>;
+
+        /// Origin allowed to propose, accept, apply or confirm a channel upgrade, attesting
+        /// the counterparty's side of it
+        type ChannelUpgradeOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Origin allowed to remove abandoned clients, slashing their deposit
+        type ClientRemovalOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Origin allowed to remove relayers, slashing their bond
+        type RelayerOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Origin allowed to bind ports to their owners
+        type PortOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Least bond a relayer reserves to register
+        #[pallet::constant]
+        type MinRelayerBond: Get<BalanceOf<Self>>;
+
+        /// Successful `recv_packet` and `acknowledge_packet` calls from registered relayers
+        /// refunded per block; further ones, and failed ones, pay fees
+        #[pallet::constant]
+        type MaxFeelessRelaysPerBlock: Get<u32>;
+
         /// Pallet identifier for account derivation
         #[pallet::constant]
         type PalletId: Get<PalletId>;
@@ -89,22 +395,52 @@ pub mod pallet {
     pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
     /// IBC client state information
-    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub struct ClientState {
+        /// Light client verifying the tracked chain's headers, with its own state
+        pub client_type: ClientType,
         /// Chain identifier this client tracks
-        pub chain_id: Vec<u8>,
+        pub chain_id: ChainId,
         /// Latest height processed
         pub latest_height: u64,
         /// Client is frozen (security incident)
         pub frozen: bool,
         /// Trust level threshold
         pub trust_level: u32,
-        /// Unbonding period
+        /// Unbonding period (seconds)
         pub unbonding_period: u64,
+        /// Timestamp of the last successful update (seconds)
+        pub last_updated: u64,
+        /// Client expired (no update within the unbonding period)
+        pub expired: bool,
     }
 
-    /// IBC connection state
+    /// Consensus state recorded for a client at a given height
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct ConsensusState {
+        /// Timestamp at which this consensus state was recorded (seconds)
+        pub timestamp: u64,
+        /// Root of the counterparty's commitment tree at this height
+        pub root: H256,
+    }
+
+    /// Netchain's own consensus state, as needed by a counterparty light client
     #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    pub struct HostConsensusState {
+        /// Height of the block this state belongs to
+        pub height: u64,
+        /// Hash of the parent block (the block hash itself is the hash the API was queried at)
+        pub parent_hash: H256,
+        /// State root after executing the block
+        pub state_root: H256,
+        /// Block timestamp (seconds)
+        pub timestamp: u64,
+        /// Hash of the validator set that will sign the next blocks
+        pub next_validators_hash: H256,
+    }
+
+    /// IBC connection state
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub enum ConnectionState {
         /// Connection initialization started
         Init,
@@ -117,7 +453,7 @@ pub mod pallet {
     }
 
     /// IBC connection end information
-    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub struct ConnectionEnd {
         /// Current connection state
         pub state: ConnectionState,
@@ -126,11 +462,11 @@ pub mod pallet {
         /// Counterparty connection details
         pub counterparty_client_id: ClientId,
         /// Connection version for compatibility
-        pub version: Vec<u8>,
+        pub version: Version,
     }
 
     /// IBC channel state
-    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub enum ChannelState {
         /// Channel initialization started
         Init,
@@ -140,10 +476,30 @@ pub mod pallet {
         Open,  
         /// Channel closed
         Closed,
+        /// Upgrade accepted, sending paused until the counterparty confirms
+        Flushing,
+    }
+
+    /// Packet delivery order of a channel
+    #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ChannelOrder {
+        /// Packets are received strictly in sequence order
+        Ordered,
+        /// Packets may be received in any order, each at most once
+        Unordered,
+    }
+
+    /// Channel parameters renegotiated by an upgrade
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct UpgradeFields {
+        /// Packet delivery order after the upgrade
+        pub ordering: ChannelOrder,
+        /// Channel version after the upgrade
+        pub version: Version,
     }
 
     /// IBC channel end information
-    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub struct ChannelEnd {
         /// Current channel state
         pub state: ChannelState,
@@ -154,7 +510,11 @@ pub mod pallet {
         /// Counterparty port identifier
         pub counterparty_port_id: PortId,
         /// Channel version
-        pub version: Vec<u8>,
+        pub version: Version,
+        /// Packet delivery order
+        pub ordering: ChannelOrder,
+        /// Number of upgrades started on this channel
+        pub upgrade_sequence: u64,
         /// Next sequence number for sending packets
         pub next_sequence_send: u64,
         /// Next sequence number for receiving packets
@@ -163,9 +523,16 @@ pub mod pallet {
         pub next_sequence_ack: u64,
     }
 
-    /// IBC packet for cross-chain communication
-    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
-    pub struct Packet {
+    /// IBC packet for cross-chain communication, in its latest layout
+    ///
+    /// Packets are committed to by the hash of this encoding, on this chain and by its
+    /// counterparties, so a new layout is added as a `PacketV2` with commitments of its own
+    /// rather than by changing this one.
+    pub type Packet = PacketV1;
+
+    /// IBC packet for cross-chain communication, first layout
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct PacketV1 {
         /// Sequence number for ordering
         pub sequence: u64,
         /// Source port identifier
@@ -177,18 +544,90 @@ pub mod pallet {
         /// Destination channel identifier
         pub destination_channel: ChannelId,
         /// Packet data payload
-        pub data: Vec<u8>,
+        pub data: PacketData,
         /// Timeout height (0 = no timeout)
         pub timeout_height: u64,
         /// Timeout timestamp
         pub timeout_timestamp: u64,
     }
 
+    /// Lifecycle status of a packet sent by this chain
+    #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum PacketStatus {
+        /// No packet with this sequence was sent
+        Unknown,
+        /// Sent and awaiting acknowledgment or timeout
+        Pending,
+        /// Acknowledged by the counterparty
+        Acknowledged,
+        /// Timed out without delivery
+        TimedOut,
+    }
+
+    /// Per-channel rate-limit bucket for the current window
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen, Default)]
+    pub struct RateLimitBucket<BlockNumber> {
+        /// Block at which the current window started
+        pub window_start: BlockNumber,
+        /// Packets carried in the current window
+        pub packets: u32,
+        /// Payload bytes carried in the current window
+        pub bytes: u32,
+    }
+
+    /// Lifetime traffic counters of a channel end
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen, Default)]
+    pub struct ChannelStats<BlockNumber> {
+        /// Packets sent on the channel
+        pub packets_sent: u64,
+        /// Packets received on the channel
+        pub packets_received: u64,
+        /// Sent packets acknowledged by the counterparty
+        pub packets_acknowledged: u64,
+        /// Sent packets timed out without delivery
+        pub packets_timed_out: u64,
+        /// Payload bytes sent
+        pub bytes_sent: u64,
+        /// Payload bytes received
+        pub bytes_received: u64,
+        /// Block of the channel's latest packet activity
+        pub last_activity: BlockNumber,
+    }
+
+    /// Lifetime delivery counters of a registered relayer
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen, Default)]
+    pub struct RelayerStats<BlockNumber> {
+        /// Packets it delivered to this chain
+        pub packets_received: u64,
+        /// Acknowledgements it delivered for packets sent from this chain
+        pub packets_acknowledged: u64,
+        /// Timeouts it submitted for packets sent from this chain
+        pub packets_timed_out: u64,
+        /// Block of its latest delivery
+        pub last_active: BlockNumber,
+    }
+
+    impl<BlockNumber> RelayerStats<BlockNumber> {
+        /// Packets, acknowledgements and timeouts delivered
+        pub fn deliveries(&self) -> u64 {
+            self.packets_received.saturating_add(self.packets_acknowledged).saturating_add(self.packets_timed_out)
+        }
+    }
+
     /// Storage for IBC clients
     #[pallet::storage]
     #[pallet::getter(fn clients)]
     pub type Clients<T: Config> = StorageMap<_, Blake2_128Concat, ClientId, ClientState>;
 
+    /// Storage for client consensus states by height
+    #[pallet::storage]
+    #[pallet::getter(fn consensus_states)]
+    pub type ConsensusStates<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, ClientId,
+        Blake2_128Concat, u64, // height
+        ConsensusState,
+    >;
+
     /// Storage for IBC connections
     #[pallet::storage]
     #[pallet::getter(fn connections)]
@@ -203,6 +642,20 @@ pub mod pallet {
         ChannelEnd
     >;
 
+    /// Owner of each bound port, holding the capability to open channels on it
+    #[pallet::storage]
+    #[pallet::getter(fn port_owner)]
+    pub type PortOwners<T: Config> = StorageMap<_, Blake2_128Concat, PortId, T::AccountId>;
+
+    /// Upgrades in progress, by channel
+    #[pallet::storage]
+    #[pallet::getter(fn channel_upgrades)]
+    pub type ChannelUpgrades<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, PortId,
+        Blake2_128Concat, ChannelId,
+        UpgradeFields,
+    >;
+
     /// Storage for packet commitments (prevents replay attacks)
     #[pallet::storage]
     #[pallet::getter(fn packet_commitments)]
@@ -218,7 +671,36 @@ pub mod pallet {
     pub type PacketAcknowledgments<T: Config> = StorageDoubleMap<
         _, Blake2_128Concat, PortId,
         Blake2_128Concat, u64, // sequence number
-        Vec<u8>, // acknowledgment data
+        BoundedVec<u8, T::MaxAcknowledgementLen>, // ICS-04 encoded acknowledgment
+    >;
+
+    /// Final outcome of sent packets, recorded when their commitment is cleared
+    #[pallet::storage]
+    #[pallet::getter(fn packet_outcomes)]
+    pub type PacketOutcomes<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, PortId,
+        Blake2_128Concat, u64, // sequence number
+        PacketStatus,
+    >;
+
+    /// Rate-limit buckets per channel
+    #[pallet::storage]
+    #[pallet::getter(fn channel_rate_limits)]
+    pub type ChannelRateLimits<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, PortId,
+        Blake2_128Concat, ChannelId,
+        RateLimitBucket<BlockNumberFor<T>>,
+        ValueQuery,
+    >;
+
+    /// Traffic counters per channel, for monitoring channel health
+    #[pallet::storage]
+    #[pallet::getter(fn channel_metrics)]
+    pub type ChannelMetrics<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, PortId,
+        Blake2_128Concat, ChannelId,
+        ChannelStats<BlockNumberFor<T>>,
+        ValueQuery,
     >;
 
     /// Next client identifier to assign
@@ -226,6 +708,25 @@ pub mod pallet {
     #[pallet::getter(fn next_client_id)]
     pub type NextClientId<T> = StorageValue<_, u32, ValueQuery>;
 
+    /// Number of clients closed or removed, whose capacity is available again
+    ///
+    /// Identifiers themselves are never reused, so a counterparty's connection can never end
+    /// up tracking a different chain than it was opened against.
+    #[pallet::storage]
+    #[pallet::getter(fn removed_clients)]
+    pub type RemovedClients<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// Creator of each client and the deposit reserved from it
+    #[pallet::storage]
+    #[pallet::getter(fn client_deposit)]
+    pub type ClientDeposits<T: Config> =
+        StorageMap<_, Blake2_128Concat, ClientId, (T::AccountId, BalanceOf<T>)>;
+
+    /// Closed or removed clients whose consensus states are still being cleared, with the
+    /// cursor clearing resumes from
+    #[pallet::storage]
+    pub type ConsensusStateCleanup<T> = StorageMap<_, Blake2_128Concat, ClientId, ClearCursor>;
+
     /// Next connection identifier to assign  
     #[pallet::storage]
     #[pallet::getter(fn next_connection_id)]
@@ -236,6 +737,47 @@ pub mod pallet {
     #[pallet::getter(fn next_channel_id)]
     pub type NextChannelId<T> = StorageValue<_, u32, ValueQuery>;
 
+    /// Registered relayers
+    #[pallet::storage]
+    #[pallet::getter(fn relayers)]
+    pub type Relayers<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+    /// Bond reserved from each registered relayer
+    #[pallet::storage]
+    #[pallet::getter(fn relayer_bond)]
+    pub type RelayerBonds<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    /// Deliveries of each registered relayer, for channel owners picking relayers and fee
+    /// payouts
+    #[pallet::storage]
+    #[pallet::getter(fn relayer_stats)]
+    pub type RelayerMetrics<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, RelayerStats<BlockNumberFor<T>>, ValueQuery>;
+
+    /// Relays refunded in the current block
+    #[pallet::storage]
+    #[pallet::getter(fn feeless_relays)]
+    pub type FeelessRelays<T> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Port, and its owner, of a loopback channel pair opened at genesis (see
+        /// [`Pallet::open_loopback`]), so development chains can send packets to themselves
+        pub loopback: Option<(Vec<u8>, T::AccountId)>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            if let Some((port_id, owner)) = &self.loopback {
+                let port_id = PortId::try_from(port_id.clone()).expect("loopback port identifier is bounded; qed");
+                Pallet::<T>::open_loopback(port_id, owner.clone()).expect("no IBC state exists at genesis; qed");
+            }
+            Pallet::<T>::endow_pot();
+        }
+    }
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -243,10 +785,35 @@ pub mod pallet {
         ClientCreated { client_id: ClientId, chain_id: Vec<u8> },
         /// IBC client updated with new state
         ClientUpdated { client_id: ClientId, height: u64 },
+        /// IBC client expired after its unbonding period elapsed without updates
+        ClientExpired { client_id: ClientId },
+        /// Client frozen on evidence of its counterparty's validators misbehaving
+        ClientFrozen { client_id: ClientId },
+        /// Stale consensus states pruned for a client
+        ConsensusStatesPruned { client_id: ClientId, count: u32 },
+        /// Expired or frozen client closed by its owner; the deposit was returned
+        ClientClosed { client_id: ClientId, owner: T::AccountId, refunded: BalanceOf<T> },
+        /// Abandoned client removed by governance; the deposit was slashed
+        ClientRemoved { client_id: ClientId, slashed: BalanceOf<T> },
+        /// Relayer registered with `bond`; its successful packet deliveries are refunded up to
+        /// the block quota
+        RelayerRegistered { relayer: T::AccountId, bond: BalanceOf<T> },
+        /// Relayer deregistered itself; its bond was returned
+        RelayerDeregistered { relayer: T::AccountId, refunded: BalanceOf<T> },
+        /// Relayer removed by governance; its bond was slashed
+        RelayerRemoved { relayer: T::AccountId, slashed: BalanceOf<T> },
         /// IBC connection opened
         ConnectionOpened { connection_id: ConnectionId, client_id: ClientId },
+        /// Port bound to its owner
+        PortBound { port_id: PortId, owner: T::AccountId },
         /// IBC channel opened
         ChannelOpened { port_id: PortId, channel_id: ChannelId, connection_id: ConnectionId },
+        /// Channel upgrade proposed
+        ChannelUpgradeInit { port_id: PortId, channel_id: ChannelId, upgrade_sequence: u64, fields: UpgradeFields },
+        /// Counterparty's channel upgrade accepted; sending paused until it is confirmed
+        ChannelUpgradeTry { port_id: PortId, channel_id: ChannelId, upgrade_sequence: u64, fields: UpgradeFields },
+        /// Channel upgrade applied and the channel reopened
+        ChannelUpgraded { port_id: PortId, channel_id: ChannelId, upgrade_sequence: u64, fields: UpgradeFields },
         /// Cross-chain packet sent
         PacketSent { 
             sequence: u64, 
@@ -256,19 +823,25 @@ pub mod pallet {
             destination_channel: ChannelId,
             data: Vec<u8>
         },
-        /// Cross-chain packet received
+        /// Cross-chain packet received and acknowledged by the application
         PacketReceived { 
             sequence: u64, 
             source_port: PortId, 
             source_channel: ChannelId,
             destination_port: PortId,
             destination_channel: ChannelId,
-            data: Vec<u8>
+            data: Vec<u8>,
+            acknowledgement: Acknowledgement,
         },
         /// Packet acknowledgment processed
         PacketAcknowledged { sequence: u64, port_id: PortId, channel_id: ChannelId },
         /// Packet timed out and removed
         PacketTimeout { sequence: u64, port_id: PortId, channel_id: ChannelId },
+        /// Packet refused by the application it was addressed to: a local one when received,
+        /// the counterparty's when acknowledged; port and channel are this chain's end
+        PacketRejected { sequence: u64, port_id: PortId, channel_id: ChannelId, reason: FailureReason },
+        /// Channel exhausted its rate limit for the current window
+        ChannelRateLimited { port_id: PortId, channel_id: ChannelId, window_start: BlockNumberFor<T> },
     }
 
     #[pallet::error]
@@ -301,45 +874,151 @@ pub mod pallet {
         MaxConnectionsReached,
         /// Maximum channels reached
         MaxChannelsReached,
+        /// Packet data exceeds the maximum allowed size
+        PacketDataTooLarge,
+        /// Channel rate limit exceeded for the current window
+        ChannelRateLimited,
+        /// Client has expired and can no longer be used
+        ClientExpired,
+        /// Client is frozen
+        ClientFrozen,
+        /// Identifier is malformed
+        InvalidIdentifier,
+        /// Packet does not match the commitment stored when it was sent
+        PacketCommitmentMismatch,
+        /// Client has no consensus state at the proof height
+        ConsensusStateNotFound,
+        /// Membership proof does not verify against the consensus state root
+        InvalidProof,
+        /// Port is already bound to an owner
+        PortAlreadyBound,
+        /// Port has not been bound
+        PortNotBound,
+        /// Caller does not own the port
+        NotPortOwner,
+        /// Channel already has an upgrade in progress
+        UpgradeInProgress,
+        /// Channel has no upgrade in progress
+        NoUpgradeInProgress,
+        /// Counterparty's upgrade does not match the one proposed on this end
+        UpgradeMismatch,
+        /// Upgrade sequence is behind the channel's
+        StaleUpgradeSequence,
+        /// Caller did not create the client
+        NotClientOwner,
+        /// Client can only be closed once expired or frozen
+        ClientStillActive,
+        /// IBC packet flow is paused by governance
+        SystemPaused,
+        /// Relayer is already registered
+        RelayerAlreadyRegistered,
+        /// Relayer is not registered
+        RelayerNotRegistered,
+        /// Bond is below `MinRelayerBond`
+        InsufficientRelayerBond,
+        /// Header is not an encoded header of the client's type
+        InvalidHeader,
+        /// Header is for another chain or does not follow from the client's trusted state
+        UntrustedHeader,
+        /// Header is not signed or finalized by enough of the client's trusted set
+        InvalidJustification,
+        /// Clients of this type cannot be created or updated by extrinsic
+        UnsupportedClientType,
+        /// Evidence is not of conflicting headers both verifying against the client's state
+        InvalidMisbehaviour,
+        /// Packet or channel end does not match the channel's counterparty
+        CounterpartyMismatch,
+        /// Chain identifier exceeds `MaxChainIdLen`
+        ChainIdTooLong,
+        /// Connection or channel version exceeds `MaxVersionLen`
+        VersionTooLong,
+        /// Acknowledgement of the application exceeds `MaxAcknowledgementLen`
+        AcknowledgementTooLarge,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+            <FeelessRelays<T>>::kill();
+            T::DbWeight::get().writes(1)
+        }
+
+        fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let used = Self::clear_removed_consensus_states(remaining_weight);
+            used.saturating_add(Self::expire_and_prune(remaining_weight.saturating_sub(used)))
+        }
+
+        fn integrity_test() {
+            assert!(
+                T::MaxPacketDataSize::get() <= MaxPacketDataLen::get(),
+                "MaxPacketDataSize must not exceed MaxPacketDataLen"
+            );
+            assert!(
+                T::HostChainId::get().len() <= MaxChainIdLen::get() as usize,
+                "HostChainId must not exceed MaxChainIdLen"
+            );
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// Create a new IBC client for cross-chain communication
+        ///
+        /// `client_type` is the light client verifying `chain_id`'s headers, with its trusted
+        /// initial state. Localhost clients are created by the pallet only, and mock clients,
+        /// which verify nothing, by root only. Root-created clients hold no deposit.
         #[pallet::call_index(0)]
         #[pallet::weight(T::WeightInfo::create_client())]
         pub fn create_client(
             origin: OriginFor<T>,
+            client_type: ClientType,
             chain_id: Vec<u8>,
             initial_height: u64,
             trust_level: u32,
             unbonding_period: u64,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            let who = ensure_signed_or_root(origin)?;
+            ensure!(who.is_none() || !client_type.is_mock(), DispatchError::BadOrigin);
+            ensure!(!client_type.is_localhost(), Error::<T>::UnsupportedClientType);
+            ensure!(client_type.is_valid(trust_level, unbonding_period), Error::<T>::InvalidClientState);
+            let bounded_chain_id = ChainId::try_from(chain_id.clone()).map_err(|_| Error::<T>::ChainIdTooLong)?;
 
-            // Check limits
+            // Check limits; closed and removed clients free their slot
             let current_clients = <NextClientId<T>>::get();
-            ensure!(current_clients < T::MaxClients::get(), Error::<T>::MaxClientsReached);
-
-            // Charge ultra-low fee
-            let fee = T::ClientCreationFee::get();
-            T::Currency::transfer(&who, &Self::account_id(), fee, ExistenceRequirement::KeepAlive)?;
+            let live_clients = current_clients.saturating_sub(<RemovedClients<T>>::get());
+            ensure!(live_clients < T::MaxClients::get(), Error::<T>::MaxClientsReached);
 
             // Generate client ID
-            let client_id = format!("client-{}", current_clients).into_bytes();
+            let client_id = ClientId::truncate_from(sequential_id(CLIENT_PREFIX, current_clients));
             <NextClientId<T>>::put(current_clients.saturating_add(1));
 
+            // Reserve the deposit of a signed creator until the client is closed
+            if let Some(who) = who {
+                let deposit = T::ClientDeposit::get();
+                T::Currency::reserve(&who, deposit)?;
+                <ClientDeposits<T>>::insert(&client_id, (who, deposit));
+            }
+
             // Create client state
+            let now = Self::now_secs();
             let client_state = ClientState {
-                chain_id: chain_id.clone(),
+                client_type,
+                chain_id: bounded_chain_id,
                 latest_height: initial_height,
                 frozen: false,
                 trust_level,
                 unbonding_period,
+                last_updated: now,
+                expired: false,
             };
 
-            // Store client
+            // Store client and its initial consensus state
             <Clients<T>>::insert(&client_id, &client_state);
+            <ConsensusStates<T>>::insert(
+                &client_id,
+                initial_height,
+                ConsensusState { timestamp: now, root: H256::zero() },
+            );
 
             // Emit event
             Self::deposit_event(Event::ClientCreated { client_id, chain_id });
@@ -347,30 +1026,55 @@ pub mod pallet {
             Ok(())
         }
 
-        /// Update an existing IBC client with new state
+        /// Update an existing IBC client with a header of the tracked chain
+        ///
+        /// `header` is the SCALE encoding of a header of the client's type, verified by its
+        /// light client (see [`ClientType::verify_header`]). Mock clients are updated by root
+        /// only.
         #[pallet::call_index(1)]
-        #[pallet::weight(T::WeightInfo::update_client())]
+        #[pallet::weight(T::WeightInfo::update_client(header.len().saturated_into()))]
         pub fn update_client(
             origin: OriginFor<T>,
             client_id: ClientId,
-            new_height: u64,
+            header: Vec<u8>,
         ) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
+            let who = ensure_signed_or_root(origin)?;
+
+            ensure!(is_valid_local_identifier(&client_id, CLIENT_PREFIX), Error::<T>::InvalidIdentifier);
 
             // Get and update client state
-            <Clients<T>>::try_mutate(&client_id, |client_opt| -> DispatchResult {
+            let now = Self::now_secs();
+            let verified = <Clients<T>>::try_mutate(&client_id, |client_opt| -> Result<_, DispatchError> {
                 let client = client_opt.as_mut().ok_or(Error::<T>::ClientNotFound)?;
-                
-                // Ensure height progression
-                ensure!(new_height > client.latest_height, Error::<T>::InvalidClientState);
-                
-                client.latest_height = new_height;
-                
-                Ok(())
+                ensure!(who.is_none() || !client.client_type.is_mock(), DispatchError::BadOrigin);
+
+                // Expired clients cannot be revived by an update, nor frozen ones thawed
+                ensure!(!Self::is_expired(client, now), Error::<T>::ClientExpired);
+                ensure!(!client.frozen, Error::<T>::ClientFrozen);
+
+                let context = Context {
+                    chain_id: &client.chain_id,
+                    latest_height: client.latest_height,
+                    trust_level: client.trust_level,
+                    now,
+                };
+                let verified = client.client_type.verify_header(&context, &header).map_err(Error::<T>::from)?;
+
+                client.latest_height = verified.height;
+                client.last_updated = now;
+
+                Ok(verified)
             })?;
 
+            // Record consensus state at the new height
+            <ConsensusStates<T>>::insert(
+                &client_id,
+                verified.height,
+                ConsensusState { timestamp: now, root: verified.root },
+            );
+
             // Emit event
-            Self::deposit_event(Event::ClientUpdated { client_id, height: new_height });
+            Self::deposit_event(Event::ClientUpdated { client_id, height: verified.height });
 
             Ok(())
         }
@@ -386,6 +1090,11 @@ pub mod pallet {
         ) -> DispatchResult {
             let _who = ensure_signed(origin)?;
 
+            // Validate identifiers
+            ensure!(is_valid_local_identifier(&client_id, CLIENT_PREFIX), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_identifier(&counterparty_client_id), Error::<T>::InvalidIdentifier);
+            let version = Version::try_from(version).map_err(|_| Error::<T>::VersionTooLong)?;
+
             // Validate client exists
             ensure!(<Clients<T>>::contains_key(&client_id), Error::<T>::ClientNotFound);
 
@@ -394,7 +1103,7 @@ pub mod pallet {
             ensure!(current_connections < T::MaxConnections::get(), Error::<T>::MaxConnectionsReached);
 
             // Generate connection ID
-            let connection_id = format!("connection-{}", current_connections).into_bytes();
+            let connection_id = ConnectionId::truncate_from(sequential_id(CONNECTION_PREFIX, current_connections));
             <NextConnectionId<T>>::put(current_connections.saturating_add(1));
 
             // Create connection end
@@ -415,6 +1124,8 @@ pub mod pallet {
         }
 
         /// Open an IBC channel for application communication
+        ///
+        /// The caller must own `port_id` (see [`Pallet::bind_port`]).
         #[pallet::call_index(3)]
         #[pallet::weight(T::WeightInfo::channel_open_init())]
         pub fn channel_open_init(
@@ -424,45 +1135,21 @@ pub mod pallet {
             counterparty_port_id: PortId,
             version: Vec<u8>,
         ) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
-
-            // Validate connection exists and is open
-            let connection = <Connections<T>>::get(&connection_id)
-                .ok_or(Error::<T>::ConnectionNotFound)?;
-            ensure!(connection.state == ConnectionState::Open, Error::<T>::InvalidConnectionState);
-
-            // Check channel limit
-            let current_channels = <NextChannelId<T>>::get();
-            ensure!(current_channels < T::MaxChannels::get(), Error::<T>::MaxChannelsReached);
-
-            // Generate channel ID
-            let channel_id = format!("channel-{}", current_channels).into_bytes();
-            <NextChannelId<T>>::put(current_channels.saturating_add(1));
-
-            // Create channel end
-            let channel_end = ChannelEnd {
-                state: ChannelState::Init,
-                connection_id: connection_id.clone(),
-                port_id: port_id.clone(),
-                counterparty_port_id,
-                version,
-                next_sequence_send: 1,
-                next_sequence_recv: 1,
-                next_sequence_ack: 1,
-            };
+            let who = ensure_signed(origin)?;
 
-            // Store channel
-            <Channels<T>>::insert(&port_id, &channel_id, &channel_end);
+            // Only the port's owner may open channels on it
+            Self::ensure_port_owner(&port_id, &who)?;
 
-            // Emit event
-            Self::deposit_event(Event::ChannelOpened { port_id, channel_id, connection_id });
+            Self::open_channel(port_id, connection_id, counterparty_port_id, version, ChannelState::Init)?;
 
             Ok(())
         }
 
         /// Send a cross-chain packet
+        ///
+        /// The caller must own `source_port` (see [`Pallet::bind_port`]).
         #[pallet::call_index(4)]
-        #[pallet::weight(T::WeightInfo::send_packet())]
+        #[pallet::weight(T::WeightInfo::send_packet(data.len() as u32))]
         pub fn send_packet(
             origin: OriginFor<T>,
             source_port: PortId,
@@ -475,66 +1162,109 @@ pub mod pallet {
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            // Charge ultra-low transmission fee
-            let fee = T::PacketTransmissionFee::get();
-            T::Currency::transfer(&who, &Self::account_id(), fee, ExistenceRequirement::KeepAlive)?;
-
-            // Get channel and validate state
-            let mut channel = <Channels<T>>::get(&source_port, &source_channel)
-                .ok_or(Error::<T>::ChannelNotFound)?;
-            ensure!(channel.state == ChannelState::Open, Error::<T>::InvalidChannelState);
-
-            // Create packet
-            let packet = Packet {
-                sequence: channel.next_sequence_send,
-                source_port: source_port.clone(),
-                source_channel: source_channel.clone(),
-                destination_port: destination_port.clone(),
-                destination_channel: destination_channel.clone(),
-                data: data.clone(),
-                timeout_height,
-                timeout_timestamp,
-            };
-
-            // Generate packet commitment (hash for integrity)
-            let packet_hash = BlakeTwo256::hash_of(&packet);
-
-            // Store packet commitment (prevents replay)
-            <PacketCommitments<T>>::insert(&source_port, channel.next_sequence_send, packet_hash);
-
-            // Update channel sequence
-            channel.next_sequence_send = channel.next_sequence_send.saturating_add(1);
-            <Channels<T>>::insert(&source_port, &source_channel, &channel);
+            // Only the port's owner may send on its channels
+            Self::ensure_port_owner(&source_port, &who)?;
 
-            // Emit event
-            Self::deposit_event(Event::PacketSent {
-                sequence: packet.sequence,
+            Self::send_packet_from(
+                &who,
                 source_port,
                 source_channel,
                 destination_port,
                 destination_channel,
                 data,
-            });
+                timeout_height,
+                timeout_timestamp,
+            )?;
 
             Ok(())
         }
 
         /// Receive and process a cross-chain packet
+        ///
+        /// `proof_commitment` must show the counterparty stored the hash of `packet` under its
+        /// ICS-24 commitment path, against the consensus state the destination channel's client
+        /// recorded at `proof_height`. Over the localhost connection and loopback clients the
+        /// commitment is read from this chain's storage instead, and the proof is ignored.
+        ///
+        /// The fee is refunded to registered relayers when the call succeeds, up to
+        /// `MaxFeelessRelaysPerBlock` per block.
         #[pallet::call_index(5)]
-        #[pallet::weight(T::WeightInfo::recv_packet())]
+        #[pallet::weight(
+            T::WeightInfo::recv_packet(packet.data.len() as u32).saturating_add(T::Router::max_recv_packet_weight())
+        )]
         pub fn recv_packet(
             origin: OriginFor<T>,
             packet: Packet,
-        ) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
+            proof_commitment: Vec<ProofStep>,
+            proof_height: u64,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "recv_packet");
+            ensure!(!T::Safeguard::is_paused(Subsystem::Ibc), Error::<T>::SystemPaused);
+
+            // Validate identifiers
+            ensure!(is_valid_identifier(&packet.source_port), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_identifier(&packet.source_channel), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_identifier(&packet.destination_port), Error::<T>::InvalidIdentifier);
+            ensure!(
+                is_valid_local_identifier(&packet.destination_channel, CHANNEL_PREFIX),
+                Error::<T>::InvalidIdentifier
+            );
+
+            // Bound payload size
+            ensure!(packet.data.len() <= T::MaxPacketDataSize::get() as usize, Error::<T>::PacketDataTooLarge);
+            ensure!(proof_commitment.len() <= MAX_PROOF_DEPTH, Error::<T>::InvalidProof);
 
             // Get destination channel
             let mut channel = <Channels<T>>::get(&packet.destination_port, &packet.destination_channel)
                 .ok_or(Error::<T>::ChannelNotFound)?;
-            ensure!(channel.state == ChannelState::Open, Error::<T>::InvalidChannelState);
+            // A flushing channel only pauses sending; in-flight packets are still delivered
+            ensure!(
+                matches!(channel.state, ChannelState::Open | ChannelState::Flushing),
+                Error::<T>::InvalidChannelState
+            );
 
-            // Validate sequence number (prevent replay and ensure ordering)
-            ensure!(packet.sequence == channel.next_sequence_recv, Error::<T>::InvalidSequence);
+            // Only the channel's counterparty port can send on it
+            ensure!(packet.source_port == channel.counterparty_port_id, Error::<T>::CounterpartyMismatch);
+
+            // Packets cannot flow over an expired or frozen client
+            Self::ensure_client_active(&channel.connection_id)?;
+
+            // Verify the sending end committed to the packet. When the counterparty is this
+            // chain the commitment is in its own storage.
+            if Self::counterparty_is_self(&channel.connection_id) {
+                ensure!(
+                    <PacketCommitments<T>>::get(&packet.source_port, packet.sequence)
+                        == Some(BlakeTwo256::hash_of(&packet)),
+                    Error::<T>::PacketCommitmentMismatch
+                );
+            } else {
+                let path = commitment_path(&packet.source_port, &packet.source_channel, packet.sequence);
+                Self::verify_counterparty(
+                    &channel.connection_id,
+                    proof_height,
+                    &path,
+                    &BlakeTwo256::hash_of(&packet),
+                    &proof_commitment,
+                )?;
+            }
+
+            // Enforce channel rate limit
+            Self::consume_rate_limit(
+                &packet.destination_port,
+                &packet.destination_channel,
+                packet.data.len() as u32,
+            )?;
+
+            // Validate sequence number (prevent replay and, on ordered channels, ensure ordering)
+            match channel.ordering {
+                ChannelOrder::Ordered =>
+                    ensure!(packet.sequence == channel.next_sequence_recv, Error::<T>::InvalidSequence),
+                ChannelOrder::Unordered => ensure!(
+                    !<PacketAcknowledgments<T>>::contains_key(&packet.destination_port, packet.sequence),
+                    Error::<T>::PacketAlreadyExists
+                ),
+            }
 
             // Check timeout conditions
             let current_height = frame_system::Pallet::<T>::block_number().saturated_into::<u64>();
@@ -543,51 +1273,154 @@ pub mod pallet {
             }
 
             // Update channel sequence
-            channel.next_sequence_recv = channel.next_sequence_recv.saturating_add(1);
+            channel.next_sequence_recv = channel.next_sequence_recv.max(packet.sequence.saturating_add(1));
             <Channels<T>>::insert(&packet.destination_port, &packet.destination_channel, &channel);
+            Self::note_channel_activity(&packet.destination_port, &packet.destination_channel, |stats| {
+                stats.packets_received.saturating_inc();
+                stats.bytes_received.saturating_accrue(packet.data.len() as u64);
+            });
 
-            // Store acknowledgment (simple success acknowledgment)
-            let ack_data = b"success".to_vec();
-            <PacketAcknowledgments<T>>::insert(&packet.destination_port, packet.sequence, &ack_data);
+            // Deliver to the application and store its acknowledgment
+            let acknowledgement = T::Router::on_recv_packet(&packet, &who);
+            let encoded = BoundedVec::try_from(encode_acknowledgement(&acknowledgement))
+                .map_err(|_| Error::<T>::AcknowledgementTooLarge)?;
+            <PacketAcknowledgments<T>>::insert(&packet.destination_port, packet.sequence, encoded);
+
+            // Index the packet under `<port>/<channel>`
+            T::History::record(
+                HistoryKind::IbcPacket,
+                &who,
+                &Self::channel_path(&packet.destination_port, &packet.destination_channel),
+                BlakeTwo256::hash_of(&packet),
+            );
 
             // Emit event
+            log::debug!(
+                target: LOG_TARGET,
+                "received packet {} on {}/{} from {}/{}, {} bytes, {}",
+                packet.sequence,
+                Printable(&packet.destination_port),
+                Printable(&packet.destination_channel),
+                Printable(&packet.source_port),
+                Printable(&packet.source_channel),
+                packet.data.len(),
+                if acknowledgement.is_ok() { "accepted" } else { "rejected by the application" },
+            );
+            if acknowledgement.is_err() {
+                Self::deposit_event(Event::PacketRejected {
+                    sequence: packet.sequence,
+                    port_id: packet.destination_port.clone(),
+                    channel_id: packet.destination_channel.clone(),
+                    reason: FailureReason::ApplicationRejected,
+                });
+            }
             Self::deposit_event(Event::PacketReceived {
                 sequence: packet.sequence,
                 source_port: packet.source_port,
                 source_channel: packet.source_channel,
                 destination_port: packet.destination_port,
                 destination_channel: packet.destination_channel,
-                data: packet.data,
+                data: packet.data.into_inner(),
+                acknowledgement,
             });
+            let pays = Self::note_relay(&who);
+            Self::note_delivery(&who, |stats| stats.packets_received.saturating_inc());
 
-            Ok(())
+            Ok(pays.into())
         }
 
         /// Process packet acknowledgment
+        ///
+        /// `proof_acked` must show the counterparty stored the hash of `acknowledgment` under
+        /// the packet's ICS-24 acknowledgement path, against the consensus state the sending
+        /// channel's client recorded at `proof_height`. Over the localhost client the
+        /// acknowledgement is read from this chain's storage instead, and the proof is ignored.
+        ///
+        /// The fee is refunded to registered relayers when the call succeeds, up to
+        /// `MaxFeelessRelaysPerBlock` per block.
         #[pallet::call_index(6)]
-        #[pallet::weight(T::WeightInfo::acknowledge_packet())]
+        #[pallet::weight(T::WeightInfo::acknowledge_packet(packet.data.len() as u32))]
         pub fn acknowledge_packet(
             origin: OriginFor<T>,
-            port_id: PortId,
-            channel_id: ChannelId,
-            sequence: u64,
+            packet: Packet,
             acknowledgment: Vec<u8>,
-        ) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
+            proof_acked: Vec<ProofStep>,
+            proof_height: u64,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "acknowledge_packet");
 
-            // Verify packet commitment exists
+            // Validate identifiers
+            ensure!(is_valid_identifier(&packet.source_port), Error::<T>::InvalidIdentifier);
             ensure!(
-                <PacketCommitments<T>>::contains_key(&port_id, sequence),
-                Error::<T>::PacketNotFound
+                is_valid_local_identifier(&packet.source_channel, CHANNEL_PREFIX),
+                Error::<T>::InvalidIdentifier
             );
+            ensure!(proof_acked.len() <= MAX_PROOF_DEPTH, Error::<T>::InvalidProof);
 
-            // Remove packet commitment (cleanup)
-            <PacketCommitments<T>>::remove(&port_id, sequence);
+            // Verify the packet is the one committed to when it was sent
+            let commitment = <PacketCommitments<T>>::get(&packet.source_port, packet.sequence)
+                .ok_or(Error::<T>::PacketNotFound)?;
+            ensure!(commitment == BlakeTwo256::hash_of(&packet), Error::<T>::PacketCommitmentMismatch);
+
+            // Verify the acknowledgment against the counterparty's committed state
+            let channel = <Channels<T>>::get(&packet.source_port, &packet.source_channel)
+                .ok_or(Error::<T>::ChannelNotFound)?;
+            Self::ensure_client_active(&channel.connection_id)?;
+            if Self::counterparty_is_self(&channel.connection_id) {
+                ensure!(
+                    <PacketAcknowledgments<T>>::get(&packet.destination_port, packet.sequence)
+                        .is_some_and(|stored| stored == acknowledgment),
+                    Error::<T>::InvalidProof
+                );
+            } else {
+                let path = acknowledgement_path(
+                    &packet.destination_port,
+                    &packet.destination_channel,
+                    packet.sequence,
+                );
+                Self::verify_counterparty(
+                    &channel.connection_id,
+                    proof_height,
+                    &path,
+                    &BlakeTwo256::hash(&acknowledgment),
+                    &proof_acked,
+                )?;
+            }
+
+            // Remove packet commitment (cleanup) and record the outcome
+            <PacketCommitments<T>>::remove(&packet.source_port, packet.sequence);
+            <PacketOutcomes<T>>::insert(&packet.source_port, packet.sequence, PacketStatus::Acknowledged);
+            Self::note_channel_activity(&packet.source_port, &packet.source_channel, |stats| {
+                stats.packets_acknowledged.saturating_inc();
+            });
 
             // Emit event
-            Self::deposit_event(Event::PacketAcknowledged { sequence, port_id, channel_id });
+            log::debug!(
+                target: LOG_TARGET,
+                "packet {} on {}/{} acknowledged{}",
+                packet.sequence,
+                Printable(&packet.source_port),
+                Printable(&packet.source_channel),
+                if is_error_acknowledgement(&acknowledgment) { " with an error" } else { "" },
+            );
+            if is_error_acknowledgement(&acknowledgment) {
+                Self::deposit_event(Event::PacketRejected {
+                    sequence: packet.sequence,
+                    port_id: packet.source_port.clone(),
+                    channel_id: packet.source_channel.clone(),
+                    reason: FailureReason::ApplicationRejected,
+                });
+            }
+            Self::deposit_event(Event::PacketAcknowledged {
+                sequence: packet.sequence,
+                port_id: packet.source_port,
+                channel_id: packet.source_channel,
+            });
+            let pays = Self::note_relay(&who);
+            Self::note_delivery(&who, |stats| stats.packets_acknowledged.saturating_inc());
 
-            Ok(())
+            Ok(pays.into())
         }
 
         /// Handle packet timeout
@@ -599,7 +1432,12 @@ pub mod pallet {
             channel_id: ChannelId,
             sequence: u64,
         ) -> DispatchResult {
-            let _who = ensure_signed(origin)?;
+            let who = ensure_signed(origin)?;
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "timeout_packet");
+
+            // Validate identifiers
+            ensure!(is_valid_identifier(&port_id), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_local_identifier(&channel_id, CHANNEL_PREFIX), Error::<T>::InvalidIdentifier);
 
             // Verify packet commitment exists
             ensure!(
@@ -607,11 +1445,303 @@ pub mod pallet {
                 Error::<T>::PacketNotFound
             );
 
-            // Remove packet commitment (cleanup)
+            // Remove packet commitment (cleanup) and record the outcome
             <PacketCommitments<T>>::remove(&port_id, sequence);
+            <PacketOutcomes<T>>::insert(&port_id, sequence, PacketStatus::TimedOut);
+            Self::note_channel_activity(&port_id, &channel_id, |stats| stats.packets_timed_out.saturating_inc());
 
             // Emit event  
+            log::debug!(
+                target: LOG_TARGET,
+                "packet {} on {}/{} timed out",
+                sequence,
+                Printable(&port_id),
+                Printable(&channel_id),
+            );
             Self::deposit_event(Event::PacketTimeout { sequence, port_id, channel_id });
+            Self::note_delivery(&who, |stats| stats.packets_timed_out.saturating_inc());
+
+            Ok(())
+        }
+
+        /// Propose renegotiating an open channel's version and ordering (ICS-004 upgrade)
+        ///
+        /// The channel stays open; its sequences are kept across the upgrade.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::channel_upgrade_init())]
+        pub fn channel_upgrade_init(
+            origin: OriginFor<T>,
+            port_id: PortId,
+            channel_id: ChannelId,
+            fields: UpgradeFields,
+        ) -> DispatchResult {
+            T::ChannelUpgradeOrigin::ensure_origin(origin)?;
+
+            let mut channel = Self::upgradable_channel(&port_id, &channel_id)?;
+            ensure!(channel.state == ChannelState::Open, Error::<T>::InvalidChannelState);
+
+            channel.upgrade_sequence = channel.upgrade_sequence.saturating_add(1);
+            <Channels<T>>::insert(&port_id, &channel_id, &channel);
+            <ChannelUpgrades<T>>::insert(&port_id, &channel_id, &fields);
+
+            Self::deposit_event(Event::ChannelUpgradeInit {
+                port_id,
+                channel_id,
+                upgrade_sequence: channel.upgrade_sequence,
+                fields,
+            });
+
+            Ok(())
+        }
+
+        /// Accept the upgrade the counterparty proposed, pausing sends until it confirms
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::channel_upgrade_try())]
+        pub fn channel_upgrade_try(
+            origin: OriginFor<T>,
+            port_id: PortId,
+            channel_id: ChannelId,
+            counterparty_fields: UpgradeFields,
+            counterparty_upgrade_sequence: u64,
+        ) -> DispatchResult {
+            T::ChannelUpgradeOrigin::ensure_origin(origin)?;
+
+            let mut channel = Self::upgradable_channel(&port_id, &channel_id)?;
+            ensure!(channel.state == ChannelState::Open, Error::<T>::InvalidChannelState);
+            ensure!(
+                counterparty_upgrade_sequence > channel.upgrade_sequence,
+                Error::<T>::StaleUpgradeSequence
+            );
+
+            channel.state = ChannelState::Flushing;
+            channel.upgrade_sequence = counterparty_upgrade_sequence;
+            <Channels<T>>::insert(&port_id, &channel_id, &channel);
+            <ChannelUpgrades<T>>::insert(&port_id, &channel_id, &counterparty_fields);
+
+            Self::deposit_event(Event::ChannelUpgradeTry {
+                port_id,
+                channel_id,
+                upgrade_sequence: counterparty_upgrade_sequence,
+                fields: counterparty_fields,
+            });
+
+            Ok(())
+        }
+
+        /// Apply an upgrade proposed on this end once the counterparty has accepted it
+        ///
+        /// The counterparty's acceptance is not proven, so `ChannelUpgradeOrigin` attests it.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::channel_upgrade_ack())]
+        pub fn channel_upgrade_ack(
+            origin: OriginFor<T>,
+            port_id: PortId,
+            channel_id: ChannelId,
+            counterparty_fields: UpgradeFields,
+            counterparty_upgrade_sequence: u64,
+        ) -> DispatchResult {
+            T::ChannelUpgradeOrigin::ensure_origin(origin)?;
+
+            let channel = <Channels<T>>::get(&port_id, &channel_id).ok_or(Error::<T>::ChannelNotFound)?;
+            ensure!(channel.state == ChannelState::Open, Error::<T>::InvalidChannelState);
+            let fields = <ChannelUpgrades<T>>::get(&port_id, &channel_id)
+                .ok_or(Error::<T>::NoUpgradeInProgress)?;
+            ensure!(
+                counterparty_fields == fields && counterparty_upgrade_sequence == channel.upgrade_sequence,
+                Error::<T>::UpgradeMismatch
+            );
+
+            Self::apply_upgrade(port_id, channel_id, channel, fields);
+
+            Ok(())
+        }
+
+        /// Reopen a flushing channel with the upgrade once the counterparty has applied it
+        ///
+        /// The counterparty's upgrade is not proven, so `ChannelUpgradeOrigin` attests it.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::channel_upgrade_confirm())]
+        pub fn channel_upgrade_confirm(
+            origin: OriginFor<T>,
+            port_id: PortId,
+            channel_id: ChannelId,
+            counterparty_upgrade_sequence: u64,
+        ) -> DispatchResult {
+            T::ChannelUpgradeOrigin::ensure_origin(origin)?;
+
+            let channel = <Channels<T>>::get(&port_id, &channel_id).ok_or(Error::<T>::ChannelNotFound)?;
+            ensure!(channel.state == ChannelState::Flushing, Error::<T>::InvalidChannelState);
+            let fields = <ChannelUpgrades<T>>::get(&port_id, &channel_id)
+                .ok_or(Error::<T>::NoUpgradeInProgress)?;
+            ensure!(counterparty_upgrade_sequence == channel.upgrade_sequence, Error::<T>::UpgradeMismatch);
+
+            Self::apply_upgrade(port_id, channel_id, channel, fields);
+
+            Ok(())
+        }
+
+        /// Reserve `port_id` to `owner`, who alone can then open channels and send packets on it
+        ///
+        /// Ports are a namespace shared with counterparties, so binding one is left to
+        /// `PortOrigin` rather than to whoever asks first.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::bind_port())]
+        pub fn bind_port(origin: OriginFor<T>, port_id: PortId, owner: T::AccountId) -> DispatchResult {
+            T::PortOrigin::ensure_origin(origin)?;
+            Self::bind_port_to(port_id, owner)
+        }
+
+        /// Close an expired or frozen client, returning its deposit to its creator
+        #[pallet::call_index(13)]
+        #[pallet::weight(
+            T::WeightInfo::close_client()
+                .saturating_add(T::DbWeight::get().writes(MAX_CONSENSUS_STATES_CLEARED.into()))
+        )]
+        pub fn close_client(origin: OriginFor<T>, client_id: ClientId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let client = <Clients<T>>::get(&client_id).ok_or(Error::<T>::ClientNotFound)?;
+            let (owner, deposit) = <ClientDeposits<T>>::get(&client_id).ok_or(Error::<T>::NotClientOwner)?;
+            ensure!(owner == who, Error::<T>::NotClientOwner);
+            ensure!(
+                client.frozen || Self::is_expired(&client, Self::now_secs()),
+                Error::<T>::ClientStillActive
+            );
+
+            Self::remove_client_state(&client_id);
+            let refunded = deposit.saturating_sub(T::Currency::unreserve(&owner, deposit));
+
+            Self::deposit_event(Event::ClientClosed { client_id, owner, refunded });
+
+            Ok(())
+        }
+
+        /// Remove an abandoned client, slashing its creator's deposit
+        #[pallet::call_index(14)]
+        #[pallet::weight(
+            T::WeightInfo::remove_client()
+                .saturating_add(T::DbWeight::get().writes(MAX_CONSENSUS_STATES_CLEARED.into()))
+        )]
+        pub fn remove_client(origin: OriginFor<T>, client_id: ClientId) -> DispatchResult {
+            T::ClientRemovalOrigin::ensure_origin(origin)?;
+
+            ensure!(client_id[..] != *LOCALHOST_CLIENT_ID, Error::<T>::InvalidIdentifier);
+            ensure!(<Clients<T>>::contains_key(&client_id), Error::<T>::ClientNotFound);
+
+            let slashed = match <ClientDeposits<T>>::get(&client_id) {
+                Some((owner, deposit)) => deposit.saturating_sub(T::Currency::slash_reserved(&owner, deposit).1),
+                // Clients created before deposits were introduced
+                None => Zero::zero(),
+            };
+            Self::remove_client_state(&client_id);
+
+            Self::deposit_event(Event::ClientRemoved { client_id, slashed });
+
+            Ok(())
+        }
+
+        /// Register the caller as a relayer, reserving `bond`, at least `MinRelayerBond`
+        ///
+        /// Its successful `recv_packet` and `acknowledge_packet` calls are refunded up to
+        /// `MaxFeelessRelaysPerBlock` per block, and its deliveries are counted in
+        /// [`RelayerMetrics`].
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::register_relayer())]
+        pub fn register_relayer(origin: OriginFor<T>, bond: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(!<Relayers<T>>::contains_key(&who), Error::<T>::RelayerAlreadyRegistered);
+            ensure!(bond >= T::MinRelayerBond::get(), Error::<T>::InsufficientRelayerBond);
+            T::Currency::reserve(&who, bond)?;
+            <Relayers<T>>::insert(&who, ());
+            <RelayerBonds<T>>::insert(&who, bond);
+
+            Self::deposit_event(Event::RelayerRegistered { relayer: who, bond });
+
+            Ok(())
+        }
+
+        /// Deregister the caller as a relayer, returning its bond; its deliveries pay fees
+        /// again and are no longer counted
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::deregister_relayer())]
+        pub fn deregister_relayer(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let bond = Self::take_relayer(&who)?;
+            let refunded = bond.saturating_sub(T::Currency::unreserve(&who, bond));
+
+            Self::deposit_event(Event::RelayerDeregistered { relayer: who, refunded });
+
+            Ok(())
+        }
+
+        /// Open a pair of channels between `port_id` and `counterparty_port_id` over the
+        /// localhost connection; packets sent on one are received on the other
+        ///
+        /// The caller must own both ports. The localhost client and connection are created
+        /// with the first pair.
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::WeightInfo::channel_open_localhost())]
+        pub fn channel_open_localhost(
+            origin: OriginFor<T>,
+            port_id: PortId,
+            counterparty_port_id: PortId,
+            version: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::ensure_port_owner(&port_id, &who)?;
+            Self::ensure_port_owner(&counterparty_port_id, &who)?;
+
+            Self::open_localhost_channels(port_id, counterparty_port_id, version)?;
+
+            Ok(())
+        }
+
+        /// Freeze a client on evidence of its counterparty's validators misbehaving
+        ///
+        /// `misbehaviour` is the SCALE encoding of the client type's evidence, e.g. two
+        /// conflicting Tendermint headers (see [`ClientType::check_misbehaviour`]). A frozen
+        /// client takes no more updates and carries no more packets.
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::WeightInfo::submit_misbehaviour(misbehaviour.len().saturated_into()))]
+        pub fn submit_misbehaviour(origin: OriginFor<T>, client_id: ClientId, misbehaviour: Vec<u8>) -> DispatchResult {
+            let _who = ensure_signed(origin)?;
+
+            let now = Self::now_secs();
+            <Clients<T>>::try_mutate(&client_id, |client_opt| -> DispatchResult {
+                let client = client_opt.as_mut().ok_or(Error::<T>::ClientNotFound)?;
+                ensure!(!client.frozen, Error::<T>::ClientFrozen);
+
+                let context = Context {
+                    chain_id: &client.chain_id,
+                    latest_height: client.latest_height,
+                    trust_level: client.trust_level,
+                    now,
+                };
+                client.client_type.check_misbehaviour(&context, &misbehaviour).map_err(Error::<T>::from)?;
+
+                client.frozen = true;
+                Ok(())
+            })?;
+
+            log::warn!(target: LOG_TARGET, "client {} frozen on misbehaviour", Printable(&client_id));
+            Self::deposit_event(Event::ClientFrozen { client_id });
+
+            Ok(())
+        }
+
+        /// Remove a relayer, e.g. one relaying invalid data, slashing its bond
+        #[pallet::call_index(19)]
+        #[pallet::weight(T::WeightInfo::remove_relayer())]
+        pub fn remove_relayer(origin: OriginFor<T>, relayer: T::AccountId) -> DispatchResult {
+            T::RelayerOrigin::ensure_origin(origin)?;
+
+            let bond = Self::take_relayer(&relayer)?;
+            let slashed = bond.saturating_sub(T::Currency::slash_reserved(&relayer, bond).1);
+
+            Self::deposit_event(Event::RelayerRemoved { relayer, slashed });
 
             Ok(())
         }
@@ -622,29 +1752,720 @@ pub mod pallet {
         pub fn account_id() -> T::AccountId {
             T::PalletId::get().into_account_truncating()
         }
+
+        /// Balance of the pallet account, which collects relay fees
+        pub fn pallet_pot_balance() -> BalanceOf<T> {
+            T::Currency::free_balance(&Self::account_id())
+        }
+
+        /// Fund the pallet account with the existential deposit, so that fees below it can be
+        /// collected
+        pub fn endow_pot() {
+            let pot = Self::account_id();
+            let minimum = T::Currency::minimum_balance();
+            if T::Currency::free_balance(&pot) < minimum {
+                T::Currency::make_free_balance_be(&pot, minimum);
+            }
+        }
+
+        /// Whether a successful relay by `who` is refunded: it is a registered relayer and the
+        /// block's quota is not used up
+        pub fn relayer_within_quota(who: &T::AccountId) -> bool {
+            <Relayers<T>>::contains_key(who) && <FeelessRelays<T>>::get() < T::MaxFeelessRelaysPerBlock::get()
+        }
+
+        /// Count a successful relay by `who` against the block's quota, returning whether it
+        /// pays its fee
+        ///
+        /// Failed relays roll this back with the rest of the call, so they always pay.
+        fn note_relay(who: &T::AccountId) -> Pays {
+            if Self::relayer_within_quota(who) {
+                <FeelessRelays<T>>::mutate(|relays| relays.saturating_inc());
+                Pays::No
+            } else {
+                Pays::Yes
+            }
+        }
+
+        /// Attribute a delivery to `who` if it is a registered relayer, applying `count` to its
+        /// stats and marking it active in this block
+        fn note_delivery(who: &T::AccountId, count: impl FnOnce(&mut RelayerStats<BlockNumberFor<T>>)) {
+            if !<Relayers<T>>::contains_key(who) {
+                return;
+            }
+            <RelayerMetrics<T>>::mutate(who, |stats| {
+                count(stats);
+                stats.last_active = frame_system::Pallet::<T>::block_number();
+            });
+        }
+
+        /// Deregister `relayer`, returning its bond, still reserved
+        fn take_relayer(relayer: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+            ensure!(<Relayers<T>>::take(relayer).is_some(), Error::<T>::RelayerNotRegistered);
+            <RelayerMetrics<T>>::remove(relayer);
+            Ok(<RelayerBonds<T>>::take(relayer))
+        }
+
+        /// Registered relayers with their stats, those with the most deliveries first and,
+        /// among equals, the most recently active; at most `limit` of them
+        pub fn relayer_leaderboard(limit: u32) -> Vec<(T::AccountId, RelayerStats<BlockNumberFor<T>>)> {
+            let mut relayers: Vec<_> = <Relayers<T>>::iter_keys()
+                .map(|relayer| {
+                    let stats = <RelayerMetrics<T>>::get(&relayer);
+                    (relayer, stats)
+                })
+                .collect();
+            relayers.sort_by(|(_, a), (_, b)| {
+                b.deliveries().cmp(&a.deliveries()).then_with(|| b.last_active.cmp(&a.last_active))
+            });
+            relayers.truncate(limit as usize);
+            relayers
+        }
+
+        /// Netchain's consensus state at the current block
+        ///
+        /// Computes the state root from the storage overlay, so it must only be called
+        /// outside of block execution (i.e. from the runtime API).
+        pub fn host_consensus_state() -> HostConsensusState {
+            let root = sp_io::storage::root(sp_runtime::StateVersion::V1);
+            HostConsensusState {
+                height: frame_system::Pallet::<T>::block_number().saturated_into::<u64>(),
+                parent_hash: H256::decode(&mut frame_system::Pallet::<T>::parent_hash().as_ref())
+                    .unwrap_or_default(),
+                state_root: H256::decode(&mut &root[..]).unwrap_or_default(),
+                timestamp: Self::now_secs(),
+                next_validators_hash: T::HostNextValidatorsHash::get(),
+            }
+        }
+
+        /// Client state a counterparty should instantiate to track Netchain, a GRANDPA client
+        /// of its current authority set
+        pub fn client_state_for_self() -> ClientState {
+            let (set_id, authorities) = T::HostAuthoritySet::get();
+            ClientState {
+                client_type: ClientType::Grandpa(GrandpaClient::new(set_id, authorities)),
+                chain_id: ChainId::truncate_from(T::HostChainId::get()),
+                latest_height: frame_system::Pallet::<T>::block_number().saturated_into::<u64>(),
+                frozen: false,
+                trust_level: DEFAULT_TRUST_LEVEL,
+                unbonding_period: T::HostUnbondingPeriod::get(),
+                last_updated: Self::now_secs(),
+                expired: false,
+            }
+        }
+
+        /// Lifecycle status of the packet sent from `port_id` with `sequence`
+        pub fn packet_status(port_id: &PortId, sequence: u64) -> PacketStatus {
+            if <PacketCommitments<T>>::contains_key(port_id, sequence) {
+                return PacketStatus::Pending;
+            }
+            <PacketOutcomes<T>>::get(port_id, sequence).unwrap_or(PacketStatus::Unknown)
+        }
+
+        /// Traffic counters of a channel, or `None` if the channel does not exist
+        pub fn channel_stats(port_id: &PortId, channel_id: &ChannelId) -> Option<ChannelStats<BlockNumberFor<T>>> {
+            <Channels<T>>::contains_key(port_id, channel_id).then(|| <ChannelMetrics<T>>::get(port_id, channel_id))
+        }
+
+        /// Update a channel's traffic counters and stamp its latest activity
+        fn note_channel_activity(
+            port_id: &PortId,
+            channel_id: &ChannelId,
+            update: impl FnOnce(&mut ChannelStats<BlockNumberFor<T>>),
+        ) {
+            <ChannelMetrics<T>>::mutate(port_id, channel_id, |stats| {
+                update(stats);
+                stats.last_activity = frame_system::Pallet::<T>::block_number();
+            });
+        }
+
+        /// Bind `port_id` to `owner`
+        ///
+        /// Application pallets bind their ports to their own (`PalletId`) account, typically
+        /// from genesis or a migration.
+        pub fn bind_port_to(port_id: PortId, owner: T::AccountId) -> DispatchResult {
+            ensure!(is_valid_identifier(&port_id), Error::<T>::InvalidIdentifier);
+            ensure!(!<PortOwners<T>>::contains_key(&port_id), Error::<T>::PortAlreadyBound);
+
+            <PortOwners<T>>::insert(&port_id, &owner);
+            Self::deposit_event(Event::PortBound { port_id, owner });
+
+            Ok(())
+        }
+
+        /// Open a channel in `state` on `port_id` over the open `connection_id`
+        ///
+        /// Port ownership is not checked; callers are `channel_open_init` and application
+        /// pallets opening channels on the ports they own. Applications answering a handshake
+        /// the counterparty started may open the channel directly in `Open` state.
+        pub fn open_channel(
+            port_id: PortId,
+            connection_id: ConnectionId,
+            counterparty_port_id: PortId,
+            version: Vec<u8>,
+            state: ChannelState,
+        ) -> Result<ChannelId, DispatchError> {
+            // Validate identifiers
+            ensure!(is_valid_identifier(&port_id), Error::<T>::InvalidIdentifier);
+            ensure!(
+                is_valid_local_identifier(&connection_id, CONNECTION_PREFIX) || Self::is_localhost(&connection_id),
+                Error::<T>::InvalidIdentifier
+            );
+            ensure!(is_valid_identifier(&counterparty_port_id), Error::<T>::InvalidIdentifier);
+            let version = Version::try_from(version).map_err(|_| Error::<T>::VersionTooLong)?;
+
+            // Validate connection exists and is open
+            let connection = <Connections<T>>::get(&connection_id)
+                .ok_or(Error::<T>::ConnectionNotFound)?;
+            ensure!(connection.state == ConnectionState::Open, Error::<T>::InvalidConnectionState);
+
+            // Check channel limit
+            let current_channels = <NextChannelId<T>>::get();
+            ensure!(current_channels < T::MaxChannels::get(), Error::<T>::MaxChannelsReached);
+
+            // Generate channel ID
+            let channel_id = ChannelId::truncate_from(sequential_id(CHANNEL_PREFIX, current_channels));
+            <NextChannelId<T>>::put(current_channels.saturating_add(1));
+
+            // Create channel end
+            let channel_end = ChannelEnd {
+                state,
+                connection_id: connection_id.clone(),
+                port_id: port_id.clone(),
+                counterparty_port_id,
+                version,
+                ordering: ChannelOrder::Ordered,
+                upgrade_sequence: 0,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            };
+
+            // Store channel
+            <Channels<T>>::insert(&port_id, &channel_id, &channel_end);
+
+            // Emit event
+            Self::deposit_event(Event::ChannelOpened {
+                port_id,
+                channel_id: channel_id.clone(),
+                connection_id,
+            });
+
+            Ok(channel_id)
+        }
+
+        /// Send a packet on behalf of `who`, who pays the transmission fee, returning its
+        /// sequence
+        ///
+        /// Port ownership is not checked, as with [`Self::open_channel`]; application pallets
+        /// send their packets through this.
+        #[allow(clippy::too_many_arguments)]
+        pub fn send_packet_from(
+            who: &T::AccountId,
+            source_port: PortId,
+            source_channel: ChannelId,
+            destination_port: PortId,
+            destination_channel: ChannelId,
+            data: Vec<u8>,
+            timeout_height: u64,
+            timeout_timestamp: u64,
+        ) -> Result<u64, DispatchError> {
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "send_packet");
+            ensure!(!T::Safeguard::is_paused(Subsystem::Ibc), Error::<T>::SystemPaused);
+
+            // Validate identifiers
+            ensure!(is_valid_identifier(&source_port), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_local_identifier(&source_channel, CHANNEL_PREFIX), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_identifier(&destination_port), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_identifier(&destination_channel), Error::<T>::InvalidIdentifier);
+
+            // Bound payload size
+            ensure!(data.len() <= T::MaxPacketDataSize::get() as usize, Error::<T>::PacketDataTooLarge);
+            let data = PacketData::try_from(data).map_err(|_| Error::<T>::PacketDataTooLarge)?;
+
+            // Charge ultra-low transmission fee
+            let fee = T::PacketTransmissionFee::get();
+            T::Currency::transfer(who, &Self::account_id(), fee, ExistenceRequirement::KeepAlive)?;
+
+            // Get channel and validate state
+            let mut channel = <Channels<T>>::get(&source_port, &source_channel)
+                .ok_or(Error::<T>::ChannelNotFound)?;
+            ensure!(channel.state == ChannelState::Open, Error::<T>::InvalidChannelState);
+
+            // Packets cannot flow over an expired or frozen client
+            Self::ensure_client_active(&channel.connection_id)?;
+
+            // Enforce channel rate limit
+            Self::consume_rate_limit(&source_port, &source_channel, data.len() as u32)?;
+
+            // Create packet
+            let packet = Packet {
+                sequence: channel.next_sequence_send,
+                source_port: source_port.clone(),
+                source_channel: source_channel.clone(),
+                destination_port: destination_port.clone(),
+                destination_channel: destination_channel.clone(),
+                data: data.clone(),
+                timeout_height,
+                timeout_timestamp,
+            };
+
+            // Generate packet commitment (hash for integrity)
+            let packet_hash = BlakeTwo256::hash_of(&packet);
+
+            // Store packet commitment (prevents replay)
+            <PacketCommitments<T>>::insert(&source_port, channel.next_sequence_send, packet_hash);
+
+            // Update channel sequence
+            channel.next_sequence_send = channel.next_sequence_send.saturating_add(1);
+            <Channels<T>>::insert(&source_port, &source_channel, &channel);
+            Self::note_channel_activity(&source_port, &source_channel, |stats| {
+                stats.packets_sent.saturating_inc();
+                stats.bytes_sent.saturating_accrue(data.len() as u64);
+            });
+
+            // Index the packet under `<port>/<channel>`
+            T::History::record(
+                HistoryKind::IbcPacket,
+                who,
+                &Self::channel_path(&source_port, &source_channel),
+                packet_hash,
+            );
+
+            // Emit event
+            log::debug!(
+                target: LOG_TARGET,
+                "sent packet {} on {}/{} to {}/{}, {} bytes",
+                packet.sequence,
+                Printable(&source_port),
+                Printable(&source_channel),
+                Printable(&destination_port),
+                Printable(&destination_channel),
+                data.len(),
+            );
+            Self::deposit_event(Event::PacketSent {
+                sequence: packet.sequence,
+                source_port,
+                source_channel,
+                destination_port,
+                destination_channel,
+                data: data.into_inner(),
+            });
+
+            Ok(packet.sequence)
+        }
+
+        /// Open a pair of channels on `port_id` whose counterparty is this chain itself
+        ///
+        /// Creates two clients tracking Netchain, an open connection on each with the other
+        /// as counterparty, and an open channel on each connection; `owner` is bound to the
+        /// port. Packets sent on one channel are received on the other. The clients never
+        /// expire, since the chain they track is the one verifying them.
+        pub fn open_loopback(port_id: PortId, owner: T::AccountId) -> Result<(ChannelId, ChannelId), DispatchError> {
+            let first_client = <NextClientId<T>>::get();
+            let first_connection = <NextConnectionId<T>>::get();
+            ensure!(
+                first_client.saturating_sub(<RemovedClients<T>>::get()).saturating_add(2) <= T::MaxClients::get(),
+                Error::<T>::MaxClientsReached
+            );
+            ensure!(first_connection.saturating_add(2) <= T::MaxConnections::get(), Error::<T>::MaxConnectionsReached);
+
+            let client_ids = [first_client, first_client.saturating_add(1)]
+                .map(|n| ClientId::truncate_from(sequential_id(CLIENT_PREFIX, n)));
+            let connection_ids = [first_connection, first_connection.saturating_add(1)]
+                .map(|n| ConnectionId::truncate_from(sequential_id(CONNECTION_PREFIX, n)));
+            <NextClientId<T>>::put(first_client.saturating_add(2));
+            <NextConnectionId<T>>::put(first_connection.saturating_add(2));
+
+            let client_state = ClientState {
+                client_type: ClientType::Localhost,
+                unbonding_period: u64::MAX,
+                ..Self::client_state_for_self()
+            };
+            for (side, client_id) in client_ids.iter().enumerate() {
+                <Clients<T>>::insert(client_id, &client_state);
+                <ConsensusStates<T>>::insert(
+                    client_id,
+                    client_state.latest_height,
+                    ConsensusState { timestamp: client_state.last_updated, root: H256::zero() },
+                );
+                Self::deposit_event(Event::ClientCreated {
+                    client_id: client_id.clone(),
+                    chain_id: client_state.chain_id.to_vec(),
+                });
+
+                let connection_id = &connection_ids[side];
+                <Connections<T>>::insert(connection_id, ConnectionEnd {
+                    state: ConnectionState::Open,
+                    client_id: client_id.clone(),
+                    counterparty_client_id: client_ids[1 - side].clone(),
+                    version: Version::truncate_from(b"1".to_vec()),
+                });
+                Self::deposit_event(Event::ConnectionOpened {
+                    connection_id: connection_id.clone(),
+                    client_id: client_id.clone(),
+                });
+            }
+
+            Self::bind_port_to(port_id.clone(), owner)?;
+            let [first, second] = connection_ids.map(|connection_id| {
+                Self::open_channel(port_id.clone(), connection_id, port_id.clone(), b"loopback-1".to_vec(), ChannelState::Open)
+            });
+
+            Ok((first?, second?))
+        }
+
+        /// Open a channel on `port_id` and one on `counterparty_port_id` over the localhost
+        /// connection, each with the other's port as counterparty
+        ///
+        /// Port ownership is not checked, as with [`Self::open_channel`]; application pallets
+        /// use this to connect the ports they own.
+        pub fn open_localhost_channels(
+            port_id: PortId,
+            counterparty_port_id: PortId,
+            version: Vec<u8>,
+        ) -> Result<(ChannelId, ChannelId), DispatchError> {
+            let connection_id = Self::ensure_localhost();
+            let first = Self::open_channel(
+                port_id.clone(),
+                connection_id.clone(),
+                counterparty_port_id.clone(),
+                version.clone(),
+                ChannelState::Open,
+            )?;
+            let second = Self::open_channel(counterparty_port_id, connection_id, port_id, version, ChannelState::Open)?;
+
+            Ok((first, second))
+        }
+
+        /// The localhost connection, created together with its client if it does not exist
+        ///
+        /// The localhost client tracks this chain and never expires. It is not counted
+        /// against `MaxClients`, and neither is its connection against `MaxConnections`.
+        pub fn ensure_localhost() -> ConnectionId {
+            let client_id = ClientId::truncate_from(LOCALHOST_CLIENT_ID.to_vec());
+            let connection_id = ConnectionId::truncate_from(LOCALHOST_CONNECTION_ID.to_vec());
+            if <Connections<T>>::contains_key(&connection_id) {
+                return connection_id;
+            }
+
+            let client_state = ClientState {
+                client_type: ClientType::Localhost,
+                unbonding_period: u64::MAX,
+                ..Self::client_state_for_self()
+            };
+            Self::deposit_event(Event::ClientCreated {
+                client_id: client_id.clone(),
+                chain_id: client_state.chain_id.to_vec(),
+            });
+            <Clients<T>>::insert(&client_id, client_state);
+
+            <Connections<T>>::insert(&connection_id, ConnectionEnd {
+                state: ConnectionState::Open,
+                client_id: client_id.clone(),
+                counterparty_client_id: client_id.clone(),
+                version: Version::truncate_from(b"1".to_vec()),
+            });
+            Self::deposit_event(Event::ConnectionOpened { connection_id: connection_id.clone(), client_id });
+
+            connection_id
+        }
+
+        /// Whether `connection_id` is the localhost connection
+        pub fn is_localhost(connection_id: &ConnectionId) -> bool {
+            connection_id[..] == *LOCALHOST_CONNECTION_ID
+        }
+
+        /// Whether the counterparty of `connection_id` is this chain, over the localhost
+        /// connection or a loopback client, so its commitments are read from storage
+        pub fn counterparty_is_self(connection_id: &ConnectionId) -> bool {
+            Self::is_localhost(connection_id) ||
+                <Connections<T>>::get(connection_id)
+                    .and_then(|connection| <Clients<T>>::get(&connection.client_id))
+                    .is_some_and(|client| client.client_type.is_localhost())
+        }
+
+        /// Ensure `proof` shows `value` stored under `path` by the counterparty of
+        /// `connection_id`, against the consensus state its client recorded at `proof_height`
+        pub fn verify_counterparty(
+            connection_id: &ConnectionId,
+            proof_height: u64,
+            path: &[u8],
+            value: &H256,
+            proof: &[ProofStep],
+        ) -> DispatchResult {
+            let connection = <Connections<T>>::get(connection_id).ok_or(Error::<T>::ConnectionNotFound)?;
+            let consensus = <ConsensusStates<T>>::get(&connection.client_id, proof_height)
+                .ok_or(Error::<T>::ConsensusStateNotFound)?;
+            ensure!(verify_membership(&consensus.root, path, value, proof), Error::<T>::InvalidProof);
+            Ok(())
+        }
+
+        /// Ensure `who` holds the capability of `port_id`
+        pub fn ensure_port_owner(port_id: &PortId, who: &T::AccountId) -> DispatchResult {
+            let owner = <PortOwners<T>>::get(port_id).ok_or(Error::<T>::PortNotBound)?;
+            ensure!(owner == *who, Error::<T>::NotPortOwner);
+            Ok(())
+        }
+
+        /// Validated channel without an upgrade in progress
+        fn upgradable_channel(port_id: &PortId, channel_id: &ChannelId) -> Result<ChannelEnd, DispatchError> {
+            ensure!(is_valid_identifier(port_id), Error::<T>::InvalidIdentifier);
+            ensure!(is_valid_local_identifier(channel_id, CHANNEL_PREFIX), Error::<T>::InvalidIdentifier);
+
+            let channel = <Channels<T>>::get(port_id, channel_id).ok_or(Error::<T>::ChannelNotFound)?;
+            ensure!(!<ChannelUpgrades<T>>::contains_key(port_id, channel_id), Error::<T>::UpgradeInProgress);
+            Self::ensure_client_active(&channel.connection_id)?;
+            Ok(channel)
+        }
+
+        /// Replace a channel's version and ordering, keeping its sequences, and reopen it
+        fn apply_upgrade(port_id: PortId, channel_id: ChannelId, mut channel: ChannelEnd, fields: UpgradeFields) {
+            channel.state = ChannelState::Open;
+            channel.version = fields.version.clone();
+            channel.ordering = fields.ordering;
+            <Channels<T>>::insert(&port_id, &channel_id, &channel);
+            <ChannelUpgrades<T>>::remove(&port_id, &channel_id);
+
+            Self::deposit_event(Event::ChannelUpgraded {
+                port_id,
+                channel_id,
+                upgrade_sequence: channel.upgrade_sequence,
+                fields,
+            });
+        }
+
+        /// `<port>/<channel>` key under which packets are indexed in the history pallet
+        pub fn channel_path(port_id: &PortId, channel_id: &ChannelId) -> Vec<u8> {
+            [port_id.as_slice(), b"/", channel_id.as_slice()].concat()
+        }
+
+        /// Current block timestamp in seconds
+        pub fn now_secs() -> u64 {
+            pallet_timestamp::Pallet::<T>::get().saturated_into::<u64>() / 1000
+        }
+
+        /// Delete a client and its consensus states, freeing its slot
+        fn remove_client_state(client_id: &ClientId) {
+            <Clients<T>>::remove(client_id);
+            <ClientDeposits<T>>::remove(client_id);
+            let cleared = <ConsensusStates<T>>::clear_prefix(client_id, MAX_CONSENSUS_STATES_CLEARED, None);
+            if let Some(cursor) = cleared.maybe_cursor {
+                <ConsensusStateCleanup<T>>::insert(client_id, ClearCursor::truncate_from(cursor));
+            }
+            <RemovedClients<T>>::mutate(|removed| *removed = removed.saturating_add(1));
+        }
+
+        /// Clear consensus states left over by closed and removed clients, as many as
+        /// `remaining_weight` allows, one client per call
+        pub fn clear_removed_consensus_states(remaining_weight: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            let overhead = db.reads_writes(1, 1);
+            let per_state = db.writes(1);
+            if remaining_weight.any_lt(overhead.saturating_add(per_state)) {
+                return Weight::zero();
+            }
+
+            let Some((client_id, cursor)) = <ConsensusStateCleanup<T>>::iter().next() else {
+                return db.reads(1);
+            };
+            let limit = remaining_weight.saturating_sub(overhead).ref_time() / per_state.ref_time().max(1);
+            let cleared = <ConsensusStates<T>>::clear_prefix(
+                &client_id,
+                limit.saturated_into(),
+                Some(cursor.as_slice()),
+            );
+            match cleared.maybe_cursor {
+                Some(cursor) => <ConsensusStateCleanup<T>>::insert(&client_id, ClearCursor::truncate_from(cursor)),
+                None => <ConsensusStateCleanup<T>>::remove(&client_id),
+            }
+
+            overhead.saturating_add(per_state.saturating_mul(cleared.backend.into()))
+        }
+
+        /// Whether a client's unbonding period has elapsed since its last update
+        pub fn is_expired(client: &ClientState, now: u64) -> bool {
+            client.expired || now.saturating_sub(client.last_updated) > client.unbonding_period
+        }
+
+        /// Ensure the client behind a connection is neither frozen nor expired
+        pub fn ensure_client_active(connection_id: &ConnectionId) -> DispatchResult {
+            let connection = <Connections<T>>::get(connection_id).ok_or(Error::<T>::ConnectionNotFound)?;
+            let client = <Clients<T>>::get(&connection.client_id).ok_or(Error::<T>::ClientNotFound)?;
+            ensure!(!client.frozen, Error::<T>::ClientFrozen);
+            ensure!(!Self::is_expired(&client, Self::now_secs()), Error::<T>::ClientExpired);
+            Ok(())
+        }
+
+        /// Mark expired clients and prune consensus states older than each client's
+        /// unbonding period, keeping the latest one
+        ///
+        /// Clients are walked from [`ExpiryCursor`], examining at most
+        /// [`MAX_CONSENSUS_STATES_EXAMINED`] consensus states of each, until `remaining_weight`
+        /// is used up or every client was visited once; the next idle block resumes after the
+        /// last client visited.
+        pub fn expire_and_prune(remaining_weight: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            let now = Self::now_secs();
+
+            // The cursor is read and written once per call
+            let mut used = db.reads_writes(1, 1);
+            if used.any_gt(remaining_weight) {
+                return Weight::zero();
+            }
+
+            let mut cursor = <ExpiryCursor<T>>::get();
+            let mut visited = 0u32;
+            loop {
+                if used.saturating_add(db.reads_writes(1, 1)).any_gt(remaining_weight) {
+                    break;
+                }
+                let next = match &cursor {
+                    Some(key) => <Clients<T>>::iter_from(key.to_vec()).next(),
+                    None => <Clients<T>>::iter().next(),
+                };
+                used = used.saturating_add(db.reads(1));
+                let Some((client_id, mut client)) = next else {
+                    // Past the last client: start over, unless that revisits a client
+                    let restart = cursor.is_some() && visited == 0;
+                    cursor = None;
+                    if restart {
+                        continue;
+                    }
+                    break;
+                };
+                visited += 1;
+                cursor = Some(ClearCursor::truncate_from(<Clients<T>>::hashed_key_for(&client_id)));
+
+                if !client.expired && Self::is_expired(&client, now) {
+                    client.expired = true;
+                    <Clients<T>>::insert(&client_id, &client);
+                    log::info!(target: LOG_TARGET, "client {} expired", Printable(&client_id));
+                    used = used.saturating_add(db.writes(1));
+                    Self::deposit_event(Event::ClientExpired { client_id: client_id.clone() });
+                }
+
+                let mut stale_heights = Vec::new();
+                let examined = <ConsensusStates<T>>::iter_prefix(&client_id).take(MAX_CONSENSUS_STATES_EXAMINED);
+                for (height, state) in examined {
+                    if used.saturating_add(db.reads_writes(1, 1)).any_gt(remaining_weight) {
+                        break;
+                    }
+                    used = used.saturating_add(db.reads_writes(1, 1));
+
+                    let stale = now.saturating_sub(state.timestamp) > client.unbonding_period;
+                    if height < client.latest_height && stale {
+                        stale_heights.push(height);
+                    }
+                }
+
+                let pruned = stale_heights.len() as u32;
+                for height in stale_heights {
+                    <ConsensusStates<T>>::remove(&client_id, height);
+                }
+
+                if pruned > 0 {
+                    Self::deposit_event(Event::ConsensusStatesPruned { client_id, count: pruned });
+                }
+            }
+
+            match cursor {
+                Some(cursor) => <ExpiryCursor<T>>::put(cursor),
+                None => <ExpiryCursor<T>>::kill(),
+            }
+            used
+        }
+
+        /// Charge one packet of `data_len` bytes against a channel's rate-limit bucket
+        ///
+        /// The bucket refills at the start of every `RateLimitWindow`. The packet that
+        /// exhausts the bucket is accepted and emits `ChannelRateLimited` so relayers can
+        /// back off; further packets are rejected until the window rolls over.
+        pub fn consume_rate_limit(port_id: &PortId, channel_id: &ChannelId, data_len: u32) -> DispatchResult {
+            let now = frame_system::Pallet::<T>::block_number();
+
+            <ChannelRateLimits<T>>::try_mutate(port_id, channel_id, |bucket| -> DispatchResult {
+                if now.saturating_sub(bucket.window_start) >= T::RateLimitWindow::get() {
+                    *bucket = RateLimitBucket { window_start: now, packets: 0, bytes: 0 };
+                }
+
+                let packets = bucket.packets.saturating_add(1);
+                let bytes = bucket.bytes.saturating_add(data_len);
+                ensure!(
+                    packets <= T::MaxPacketsPerWindow::get() && bytes <= T::MaxBytesPerWindow::get(),
+                    Error::<T>::ChannelRateLimited
+                );
+
+                bucket.packets = packets;
+                bucket.bytes = bytes;
+
+                if packets == T::MaxPacketsPerWindow::get() || bytes == T::MaxBytesPerWindow::get() {
+                    Self::deposit_event(Event::ChannelRateLimited {
+                        port_id: port_id.clone(),
+                        channel_id: channel_id.clone(),
+                        window_start: bucket.window_start,
+                    });
+                }
+
+                Ok(())
+            })
+        }
     }
 }
 
-/// Weight functions needed for benchmarking
-pub trait WeightInfo {
-    fn create_client() -> Weight;
-    fn update_client() -> Weight;
-    fn connection_open_init() -> Weight;
-    fn channel_open_init() -> Weight;
-    fn send_packet() -> Weight;
-    fn recv_packet() -> Weight;
-    fn acknowledge_packet() -> Weight;
-    fn timeout_packet() -> Weight;
+impl<T> From<ClientError> for Error<T> {
+    fn from(error: ClientError) -> Self {
+        match error {
+            ClientError::UndecodableHeader => Error::InvalidHeader,
+            ClientError::StaleHeader => Error::InvalidClientState,
+            ClientError::ChainIdMismatch | ClientError::UntrustedHeader => Error::UntrustedHeader,
+            ClientError::InvalidJustification => Error::InvalidJustification,
+            ClientError::TrustingPeriodExpired => Error::ClientExpired,
+            ClientError::InvalidMisbehaviour => Error::InvalidMisbehaviour,
+            ClientError::UpdateNotSupported | ClientError::MisbehaviourNotSupported => Error::UnsupportedClientType,
+        }
+    }
 }
 
-/// Default weights (based on complexity analysis)
-impl WeightInfo for () {
-    fn create_client() -> Weight { Weight::from_parts(50_000, 0) }
-    fn update_client() -> Weight { Weight::from_parts(30_000, 0) }
-    fn connection_open_init() -> Weight { Weight::from_parts(40_000, 0) }
-    fn channel_open_init() -> Weight { Weight::from_parts(40_000, 0) }
-    fn send_packet() -> Weight { Weight::from_parts(100_000, 0) }
-    fn recv_packet() -> Weight { Weight::from_parts(80_000, 0) }
-    fn acknowledge_packet() -> Weight { Weight::from_parts(20_000, 0) }
-    fn timeout_packet() -> Weight { Weight::from_parts(20_000, 0) }
-}
\ No newline at end of file
+/// Runtime API for counterparty chains and relayers
+pub mod runtime_api {
+    use super::*;
+    use codec::Codec;
+
+    sp_api::decl_runtime_apis! {
+        /// API exposing Netchain as an IBC host
+        pub trait IbcHostApi {
+            /// Netchain's consensus state at the queried block
+            fn host_consensus_state() -> HostConsensusState;
+
+            /// Client state a counterparty needs to instantiate a Netchain light client
+            fn client_state_for_self() -> ClientState;
+        }
+
+        /// API for operators and relayers monitoring channel health
+        pub trait IbcChannelApi<BlockNumber, Balance>
+        where
+            BlockNumber: Codec,
+            Balance: Codec,
+        {
+            /// Traffic counters of a channel, or `None` if the channel does not exist
+            fn channel_stats(port_id: PortId, channel_id: ChannelId) -> Option<ChannelStats<BlockNumber>>;
+
+            /// Balance of the IBC pallet account collecting relay fees
+            fn pallet_pot_balance() -> Balance;
+        }
+
+        /// API for channel owners choosing relayers and fee middleware paying them
+        pub trait IbcRelayerApi<AccountId, BlockNumber>
+        where
+            AccountId: Codec,
+            BlockNumber: Codec,
+        {
+            /// Delivery counters of a relayer, or `None` if it is not registered
+            fn relayer_stats(relayer: AccountId) -> Option<RelayerStats<BlockNumber>>;
+
+            /// Up to `limit` registered relayers, those with the most deliveries first
+            fn relayer_leaderboard(limit: u32) -> Vec<(AccountId, RelayerStats<BlockNumber>)>;
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! GRANDPA light client for Substrate counterparties
+//!
+//! The client tracks the counterparty's current GRANDPA authority set. A header is accepted
+//! with a justification finalizing it: precommits for the header or its descendants, shown
+//! through the justification's vote ancestries, signed for the set's id by authorities
+//! holding more than two thirds of the set's weight. The header's state root becomes the
+//! consensus state root at its height.
+//!
+//! Authority set changes are read from the `FRNK` consensus digests of accepted headers.
+//! A scheduled change is enacted once the header `delay` blocks past the signalling one is
+//! finalized, which must then be submitted before any later header, since later headers are
+//! justified by the next set. A forced change is enacted at its delay whether or not the
+//! current set finalized that far: headers from the effective block on are justified by the
+//! next set. A change with no delay is enacted by the signalling header itself.
+//!
+//! A justification carries at most one precommit per authority of the set, so the signatures
+//! verified for a header are bounded by the set's size. Clients track sets of at most
+//! [`MaxAuthorities`]; headers changing to a larger set are rejected.
+
+use super::*;
+use sp_core::ed25519;
+use sp_runtime::{
+    generic,
+    traits::{BlakeTwo256, Header as _},
+    ConsensusEngineId,
+};
+use sp_std::{
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+    vec::Vec,
+};
+
+/// Engine id of GRANDPA's consensus digests
+pub const GRANDPA_ENGINE_ID: ConsensusEngineId = *b"FRNK";
+
+/// Key of a GRANDPA authority
+pub type AuthorityId = ed25519::Public;
+/// Signature of a GRANDPA authority
+pub type AuthoritySignature = ed25519::Signature;
+/// Authorities of a set with their voting weights
+pub type AuthorityList = Vec<(AuthorityId, u64)>;
+/// Largest authority set a client tracks
+pub type MaxAuthorities = ConstU32<1024>;
+/// Authorities of a set a client tracks, with their voting weights
+pub type BoundedAuthorityList = BoundedVec<(AuthorityId, u64), MaxAuthorities>;
+/// Header of a Substrate counterparty block
+pub type SubstrateHeader = generic::Header<u32, BlakeTwo256>;
+
+/// Authority set change signalled by a finalized header, waiting for its enacting header
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PendingChange {
+    /// Authorities of the next set
+    pub next_authorities: BoundedAuthorityList,
+    /// Block whose finality enacts the change, or from which a forced change applies
+    pub effective_at: u32,
+    /// Whether the change was forced, and so applies at `effective_at` without its finality
+    pub forced: bool,
+}
+
+/// State of a GRANDPA client
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct GrandpaClient {
+    /// Id of the authority set finalizing the counterparty's blocks
+    pub authority_set_id: u64,
+    /// Authorities of that set with their voting weights
+    pub authorities: BoundedAuthorityList,
+    /// Change to the next set signalled by an accepted header, if not yet enacted
+    pub pending_change: Option<PendingChange>,
+}
+
+/// Vote for a block to be finalized
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Precommit {
+    /// Hash of the block voted for
+    pub target_hash: H256,
+    /// Number of the block voted for
+    pub target_number: u32,
+}
+
+/// Precommit signed by an authority
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct SignedPrecommit {
+    /// The vote
+    pub precommit: Precommit,
+    /// Signature of the vote's localized payload
+    pub signature: AuthoritySignature,
+    /// Authority who signed it
+    pub id: AuthorityId,
+}
+
+/// Precommits finalizing a block
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Commit {
+    /// Hash of the finalized block
+    pub target_hash: H256,
+    /// Number of the finalized block
+    pub target_number: u32,
+    /// Precommits for the block or its descendants
+    pub precommits: Vec<SignedPrecommit>,
+}
+
+/// GRANDPA justification, encoded as Substrate nodes encode them
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Justification {
+    /// Round the commit was made in
+    pub round: u64,
+    /// The commit
+    pub commit: Commit,
+    /// Headers between the finalized block and the blocks precommitted for
+    pub votes_ancestries: Vec<SubstrateHeader>,
+}
+
+/// Header updating a GRANDPA client: a finalized header and its justification
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct FinalizedHeader {
+    /// The finalized header
+    pub header: SubstrateHeader,
+    /// Justification finalizing it
+    pub justification: Justification,
+}
+
+/// Authority set change scheduled by a header
+#[derive(Decode)]
+struct ScheduledChange {
+    next_authorities: AuthorityList,
+    delay: u32,
+}
+
+/// GRANDPA consensus digest, the variants announcing set changes
+#[derive(Decode)]
+enum ConsensusLog {
+    #[codec(index = 1)]
+    ScheduledChange(ScheduledChange),
+    #[codec(index = 2)]
+    ForcedChange(u32, ScheduledChange),
+}
+
+/// Message signed by a voter
+#[derive(Encode)]
+enum Message<'a> {
+    #[codec(index = 1)]
+    Precommit(&'a Precommit),
+}
+
+/// Weight a set must precommit with to finalize a block: more than two thirds of `total`
+pub fn threshold(total: u64) -> u64 {
+    total.saturating_sub(total.saturating_sub(1) / 3)
+}
+
+impl GrandpaClient {
+    /// Client trusting the set `authority_set_id` of `authorities`, with no pending change
+    ///
+    /// Authorities beyond [`MaxAuthorities`] are dropped.
+    pub fn new(authority_set_id: u64, authorities: AuthorityList) -> Self {
+        Self { authority_set_id, authorities: BoundedVec::truncate_from(authorities), pending_change: None }
+    }
+
+    /// Whether a client may trust the set it was created with
+    pub fn is_valid(&self) -> bool {
+        is_valid_set(&self.authorities)
+    }
+
+    /// Check that `justification` finalizes the block `hash` under the set `set_id` of
+    /// `authorities`
+    fn verify_justification(
+        set_id: u64,
+        authorities: &[(AuthorityId, u64)],
+        hash: H256,
+        number: u32,
+        justification: &Justification,
+    ) -> Result<(), ClientError> {
+        let commit = &justification.commit;
+        if commit.target_hash != hash || commit.target_number != number {
+            return Err(ClientError::InvalidJustification);
+        }
+        // Any precommit beyond one per authority is a duplicate; reject before verifying
+        if commit.precommits.len() > authorities.len() {
+            return Err(ClientError::InvalidJustification);
+        }
+
+        let ancestries: BTreeMap<H256, &SubstrateHeader> =
+            justification.votes_ancestries.iter().map(|header| (header.hash(), header)).collect();
+        let descends_from_target = |mut block: H256| {
+            for _ in 0..=ancestries.len() {
+                if block == commit.target_hash {
+                    return true;
+                }
+                match ancestries.get(&block) {
+                    Some(header) => block = header.parent_hash,
+                    None => return false,
+                }
+            }
+            false
+        };
+
+        let voters: BTreeMap<AuthorityId, u64> = authorities.iter().copied().collect();
+        let mut signers = BTreeSet::new();
+        let mut weight = 0u64;
+        for signed in &commit.precommits {
+            let Some(&voter_weight) = voters.get(&signed.id) else {
+                return Err(ClientError::InvalidJustification);
+            };
+            let payload = (Message::Precommit(&signed.precommit), justification.round, set_id).encode();
+            if !signers.insert(signed.id)
+                || !descends_from_target(signed.precommit.target_hash)
+                || !sp_io::crypto::ed25519_verify(&signed.signature, &payload, &signed.id)
+            {
+                return Err(ClientError::InvalidJustification);
+            }
+            weight = weight.saturating_add(voter_weight);
+        }
+
+        let total = authorities.iter().fold(0u64, |total, (_, weight)| total.saturating_add(*weight));
+        if weight < threshold(total) {
+            return Err(ClientError::InvalidJustification);
+        }
+        Ok(())
+    }
+
+    /// Set changes signalled by `header`, rejecting changes to a set no header could be
+    /// finalized by or larger than [`MaxAuthorities`]
+    fn signalled_changes(header: &SubstrateHeader) -> Result<Vec<PendingChange>, ClientError> {
+        let mut changes = Vec::new();
+        for log in header.digest.logs() {
+            let (change, forced) = match log.consensus_try_to::<ConsensusLog>(&GRANDPA_ENGINE_ID) {
+                Some(ConsensusLog::ScheduledChange(change)) => (change, false),
+                Some(ConsensusLog::ForcedChange(_, change)) => (change, true),
+                None => continue,
+            };
+            if !is_valid_set(&change.next_authorities) {
+                return Err(ClientError::UntrustedHeader);
+            }
+            let next_authorities =
+                BoundedVec::try_from(change.next_authorities).map_err(|_| ClientError::UntrustedHeader)?;
+            changes.push(PendingChange {
+                next_authorities,
+                effective_at: header.number.saturating_add(change.delay),
+                forced,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Enact the pending change `number` is the effective block of, then record `changes`,
+    /// enacting the last one at once if it has no delay
+    fn track_set_changes(&mut self, number: u32, changes: Vec<PendingChange>) {
+        self.enact_due_change(number);
+        if let Some(change) = changes.into_iter().last() {
+            self.pending_change = Some(change);
+        }
+        self.enact_due_change(number);
+    }
+
+    /// Move to the next set if the pending change takes effect at block `number`
+    fn enact_due_change(&mut self, number: u32) {
+        if self.pending_change.as_ref().is_some_and(|change| change.effective_at == number) {
+            self.enact_pending_change();
+        }
+    }
+
+    /// Move to the set of the pending change
+    fn enact_pending_change(&mut self) {
+        if let Some(change) = self.pending_change.take() {
+            self.authority_set_id = self.authority_set_id.saturating_add(1);
+            self.authorities = change.next_authorities;
+        }
+    }
+}
+
+/// Whether `authorities` is a set blocks can be finalized by: not empty, with some weight and
+/// no authority listed twice
+fn is_valid_set(authorities: &[(AuthorityId, u64)]) -> bool {
+    let ids: BTreeSet<&AuthorityId> = authorities.iter().map(|(id, _)| id).collect();
+    ids.len() == authorities.len() && authorities.iter().any(|(_, weight)| *weight > 0)
+}
+
+impl LightClient for GrandpaClient {
+    type Header = FinalizedHeader;
+
+    fn verify_header(&mut self, context: &Context, finalized: FinalizedHeader) -> Result<VerifiedHeader, ClientError> {
+        let header = &finalized.header;
+        if u64::from(header.number) <= context.latest_height {
+            return Err(ClientError::StaleHeader);
+        }
+        // Blocks past a scheduled change are finalized by the next set, which is only trusted
+        // once the change's effective block is; a forced change applies from its effective
+        // block on
+        let forced = match &self.pending_change {
+            Some(change) if change.forced => header.number >= change.effective_at,
+            Some(change) if header.number > change.effective_at => return Err(ClientError::UntrustedHeader),
+            _ => false,
+        };
+        let (set_id, authorities) = match (&self.pending_change, forced) {
+            (Some(change), true) => (self.authority_set_id.saturating_add(1), &change.next_authorities),
+            _ => (self.authority_set_id, &self.authorities),
+        };
+        Self::verify_justification(set_id, authorities, header.hash(), header.number, &finalized.justification)?;
+        let changes = Self::signalled_changes(header)?;
+
+        if forced {
+            self.enact_pending_change();
+        }
+        self.track_set_changes(header.number, changes);
+        Ok(VerifiedHeader { height: header.number.into(), root: header.state_root })
+    }
+}
@@ -0,0 +1,30 @@
+//! Mock light client
+//!
+//! Trusts whoever updates it: any header above the latest height is accepted with the root
+//! it claims. Meant for development networks and tests, where no counterparty consensus
+//! exists to verify.
+
+use super::*;
+
+/// Header of a mock client: the height and commitment root to record
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct MockHeader {
+    /// Height of the header
+    pub height: u64,
+    /// Commitment root at that height
+    pub root: H256,
+}
+
+/// Verifier of [`MockHeader`]s; mock clients keep no state of their own
+pub struct MockClient;
+
+impl LightClient for MockClient {
+    type Header = MockHeader;
+
+    fn verify_header(&mut self, context: &Context, header: MockHeader) -> Result<VerifiedHeader, ClientError> {
+        if header.height <= context.latest_height {
+            return Err(ClientError::StaleHeader);
+        }
+        Ok(VerifiedHeader { height: header.height, root: header.root })
+    }
+}
@@ -0,0 +1,150 @@
+//! # Light Clients
+//!
+//! Every IBC client follows its counterparty with the light client of the counterparty's
+//! consensus, its [`ClientType`]:
+//! - [`tendermint`]: ICS-07 Tendermint clients for Cosmos-SDK chains
+//! - [`grandpa`]: GRANDPA clients for other Substrate chains
+//! - [`mock`]: clients trusting whoever updates them, for development and testing
+//! - localhost: the ICS-09 client through which this chain is its own counterparty, never
+//!   updated since the state it tracks is this chain's
+//!
+//! The state shared by every type is kept in [`ClientState`](crate::ClientState).
+//! `update_client` passes the SCALE-encoded header of the client's type to
+//! [`ClientType::verify_header`], which decodes it and hands it to the type's
+//! [`LightClient`] implementation. Types able to detect misbehaviour of their counterparty's
+//! consensus, so far Tendermint, check the evidence `submit_misbehaviour` passes to
+//! [`ClientType::check_misbehaviour`].
+
+use codec::{Decode, DecodeAll, Encode, MaxEncodedLen};
+use frame_support::{traits::ConstU32, BoundedVec};
+use scale_info::TypeInfo;
+use sp_core::H256;
+use sp_runtime::RuntimeDebug;
+
+pub mod grandpa;
+pub mod mock;
+pub mod tendermint;
+
+pub use grandpa::GrandpaClient;
+pub use mock::MockHeader;
+pub use tendermint::TendermintClient;
+
+/// Why a header was rejected
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum ClientError {
+    /// The header is not the SCALE encoding of a header of the client's type
+    UndecodableHeader,
+    /// The header is not above the client's latest height
+    StaleHeader,
+    /// The header is not for the chain the client tracks
+    ChainIdMismatch,
+    /// The header does not follow from the validator or authority set the client trusts
+    UntrustedHeader,
+    /// The header is not signed or finalized by enough of the set the client trusts
+    InvalidJustification,
+    /// The set the client trusts may have unbonded since its latest header
+    TrustingPeriodExpired,
+    /// The evidence is not the SCALE encoding of conflicting headers of the client's type
+    InvalidMisbehaviour,
+    /// Clients of this type are not updated with headers
+    UpdateNotSupported,
+    /// Clients of this type do not detect misbehaviour
+    MisbehaviourNotSupported,
+}
+
+/// State shared by every client type that headers are verified against
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Context<'a> {
+    /// Chain the client tracks
+    pub chain_id: &'a [u8],
+    /// Latest height of the client
+    pub latest_height: u64,
+    /// Trust level of the client, in percent
+    pub trust_level: u32,
+    /// Current time on this chain (seconds)
+    pub now: u64,
+}
+
+/// Consensus state a verified header moves its client to
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct VerifiedHeader {
+    /// Height of the header
+    pub height: u64,
+    /// Root of the counterparty's commitment tree at that height
+    pub root: H256,
+}
+
+/// Header verification of one client type
+pub trait LightClient {
+    /// Header updating a client of this type
+    type Header: Decode;
+
+    /// Verify `header` for a client in `context`
+    ///
+    /// On success the type's own state, e.g. its trusted validator set, is advanced past the
+    /// header; on failure it is left unchanged.
+    fn verify_header(&mut self, context: &Context, header: Self::Header) -> Result<VerifiedHeader, ClientError>;
+}
+
+/// Light client a client verifies its counterparty's headers with, and the state it keeps
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum ClientType {
+    /// ICS-07 Tendermint client
+    Tendermint(TendermintClient),
+    /// GRANDPA client
+    Grandpa(GrandpaClient),
+    /// Client accepting any header above its latest height, created and updated by root only
+    Mock,
+    /// ICS-09 localhost client tracking this chain
+    Localhost,
+}
+
+impl ClientType {
+    /// Verify the SCALE-encoded `header` with the client's light client
+    pub fn verify_header(&mut self, context: &Context, header: &[u8]) -> Result<VerifiedHeader, ClientError> {
+        match self {
+            Self::Tendermint(client) => verify(client, context, header),
+            Self::Grandpa(client) => verify(client, context, header),
+            Self::Mock => verify(&mut mock::MockClient, context, header),
+            Self::Localhost => Err(ClientError::UpdateNotSupported),
+        }
+    }
+
+    /// Check the SCALE-encoded `misbehaviour` evidence against the client's trusted state
+    pub fn check_misbehaviour(&self, context: &Context, misbehaviour: &[u8]) -> Result<(), ClientError> {
+        match self {
+            Self::Tendermint(client) => {
+                let misbehaviour = tendermint::Misbehaviour::decode_all(&mut &misbehaviour[..])
+                    .map_err(|_| ClientError::InvalidMisbehaviour)?;
+                client.check_misbehaviour(context, &misbehaviour)
+            }
+            Self::Grandpa(_) | Self::Mock | Self::Localhost => Err(ClientError::MisbehaviourNotSupported),
+        }
+    }
+
+    /// Whether a client of this type may be created with `trust_level` and `unbonding_period`
+    pub fn is_valid(&self, trust_level: u32, unbonding_period: u64) -> bool {
+        match self {
+            Self::Tendermint(client) => client.is_valid(trust_level, unbonding_period),
+            Self::Grandpa(client) => client.is_valid(),
+            Self::Mock => true,
+            Self::Localhost => false,
+        }
+    }
+
+    /// Whether the client is the light client of this chain itself
+    pub fn is_localhost(&self) -> bool {
+        matches!(self, Self::Localhost)
+    }
+
+    /// Whether the client trusts its headers unverified, so only root may create or update it
+    pub fn is_mock(&self) -> bool {
+        matches!(self, Self::Mock)
+    }
+}
+
+/// Decode `header` as a header of `C` and verify it
+fn verify<C: LightClient>(client: &mut C, context: &Context, header: &[u8]) -> Result<VerifiedHeader, ClientError> {
+    let header = C::Header::decode_all(&mut &header[..]).map_err(|_| ClientError::UndecodableHeader)?;
+    client.verify_header(context, header)
+}
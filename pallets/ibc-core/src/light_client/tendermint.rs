@@ -0,0 +1,253 @@
+//! Tendermint light client (ICS-07) for Cosmos-SDK counterparties
+//!
+//! The client keeps the hash of the validator set the latest trusted header announced as the
+//! next one and that header's time. A header is accepted when it is for the tracked chain,
+//! above the latest height, later than the trusted header and the trusting period since that
+//! header has not elapsed, and its commit is signed by more than two thirds of the voting
+//! power of the header's own validator set. An adjacent header's set must be the trusted next
+//! set; a header further ahead must in addition be signed by more than the client's trust
+//! level, in percent, of the power of the trusted next set, which lets the client skip over
+//! validator set changes while that set is still bonded.
+//!
+//! Headers and votes are the SCALE form of Tendermint's: a block is identified by the
+//! Blake2-256 hash of its encoded [`BlockHeader`], a validator set by that of its encoded
+//! [`Validator`]s, and validators sign the encoded [`Vote`] for the block.
+//!
+//! A commit carries at most one signature per validator of the header's set, and signatures
+//! of validators outside it are rejected before any is verified, so the signatures verified
+//! for a header are bounded by its set's size. `update_client` is weighed by the header's
+//! size, which grows with both.
+//!
+//! Two headers that both verify against the trusted state but conflict, either different
+//! blocks at one height or times going backwards, are [`Misbehaviour`] of the validators,
+//! freezing the client.
+
+use super::*;
+use sp_core::ed25519;
+use sp_io::hashing::blake2_256;
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+/// State of a Tendermint client
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct TendermintClient {
+    /// Hash of the validator set trusted to produce the next header
+    pub next_validators_hash: H256,
+    /// Time of the latest trusted header (seconds)
+    pub latest_timestamp: u64,
+    /// Time after the latest trusted header within which its validator set is trusted
+    /// (seconds); shorter than the unbonding period
+    pub trusting_period: u64,
+}
+
+/// Validator of a Tendermint chain
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Validator {
+    /// Consensus key of the validator
+    pub pub_key: ed25519::Public,
+    /// Voting power of the validator
+    pub voting_power: u64,
+}
+
+/// Tendermint block header, reduced to the fields the client checks
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct BlockHeader {
+    /// Chain identifier of the block
+    pub chain_id: Vec<u8>,
+    /// Block height
+    pub height: u64,
+    /// Block time (seconds)
+    pub time: u64,
+    /// Application state root after the previous block
+    pub app_hash: H256,
+    /// Hash of the validator set that produced the block
+    pub validators_hash: H256,
+    /// Hash of the validator set producing the following blocks
+    pub next_validators_hash: H256,
+}
+
+/// Precommit a validator signs for a block
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Vote {
+    /// Chain identifier of the block
+    pub chain_id: Vec<u8>,
+    /// Height of the block
+    pub height: u64,
+    /// Round the block was committed in
+    pub round: u32,
+    /// Hash of the block
+    pub block_hash: H256,
+}
+
+/// Signature of a validator in a commit
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct CommitSig {
+    /// Validator who signed
+    pub validator: ed25519::Public,
+    /// Signature of the commit's [`Vote`]
+    pub signature: ed25519::Signature,
+}
+
+/// Precommits committing a block
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Commit {
+    /// Round the block was committed in
+    pub round: u32,
+    /// Hash of the committed block
+    pub block_hash: H256,
+    /// Signatures of the validators who precommitted it
+    pub signatures: Vec<CommitSig>,
+}
+
+/// Header updating a Tendermint client
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Header {
+    /// The block header
+    pub header: BlockHeader,
+    /// Commit of the block
+    pub commit: Commit,
+    /// Validator set that produced the block, hashing to its `validators_hash`
+    pub validator_set: Vec<Validator>,
+    /// Validator set the client trusts, hashing to its `next_validators_hash`; only checked
+    /// for non-adjacent headers
+    pub trusted_validators: Vec<Validator>,
+}
+
+/// Two conflicting headers verifying against the client's trusted state
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct Misbehaviour {
+    /// First header
+    pub header_1: Header,
+    /// Second header
+    pub header_2: Header,
+}
+
+impl BlockHeader {
+    /// Hash identifying the block
+    pub fn hash(&self) -> H256 {
+        blake2_256(&self.encode()).into()
+    }
+}
+
+/// Hash of a validator set, committed to by headers
+pub fn validator_set_hash(validators: &[Validator]) -> H256 {
+    blake2_256(&validators.encode()).into()
+}
+
+/// Total voting power of `validators`
+fn total_power(validators: &[Validator]) -> u64 {
+    validators.iter().fold(0u64, |total, validator| total.saturating_add(validator.voting_power))
+}
+
+/// Voting power of `validators` among `signers`
+fn signed_power(validators: &[Validator], signers: &BTreeSet<ed25519::Public>) -> u64 {
+    validators
+        .iter()
+        .filter(|validator| signers.contains(&validator.pub_key))
+        .fold(0u64, |total, validator| total.saturating_add(validator.voting_power))
+}
+
+impl TendermintClient {
+    /// Whether the client may be created with `trust_level` and `unbonding_period`
+    ///
+    /// The trust level must be at least a third and the trusting period end before the
+    /// trusted validators can unbond.
+    pub fn is_valid(&self, trust_level: u32, unbonding_period: u64) -> bool {
+        (33..=100).contains(&trust_level) && self.trusting_period > 0 && self.trusting_period < unbonding_period
+    }
+
+    /// Check that `header` is committed by its validator set and follows from the trusted
+    /// state
+    fn verify_commit(&self, context: &Context, header: &Header) -> Result<(), ClientError> {
+        let block = &header.header;
+        if block.chain_id != context.chain_id {
+            return Err(ClientError::ChainIdMismatch);
+        }
+        if context.now >= self.latest_timestamp.saturating_add(self.trusting_period) {
+            return Err(ClientError::TrustingPeriodExpired);
+        }
+        if block.time <= self.latest_timestamp || validator_set_hash(&header.validator_set) != block.validators_hash {
+            return Err(ClientError::UntrustedHeader);
+        }
+
+        let commit = &header.commit;
+        if commit.block_hash != block.hash() {
+            return Err(ClientError::InvalidJustification);
+        }
+
+        // Only members of the block's set sign its commit, each once; check before verifying
+        let members: BTreeSet<ed25519::Public> =
+            header.validator_set.iter().map(|validator| validator.pub_key).collect();
+        let mut signers = BTreeSet::new();
+        if commit.signatures.len() > members.len() ||
+            !commit.signatures.iter().all(|sig| members.contains(&sig.validator) && signers.insert(sig.validator))
+        {
+            return Err(ClientError::InvalidJustification);
+        }
+
+        let vote =
+            Vote { chain_id: block.chain_id.clone(), height: block.height, round: commit.round, block_hash: commit.block_hash }
+                .encode();
+        if !commit.signatures.iter().all(|sig| sp_io::crypto::ed25519_verify(&sig.signature, &vote, &sig.validator)) {
+            return Err(ClientError::InvalidJustification);
+        }
+
+        // More than two thirds of the block's own set committed it
+        let (signed, total) = (signed_power(&header.validator_set, &signers), total_power(&header.validator_set));
+        if u128::from(signed) * 3 <= u128::from(total) * 2 {
+            return Err(ClientError::InvalidJustification);
+        }
+
+        if block.height == context.latest_height.saturating_add(1) {
+            // The trusted next set produced the block
+            if block.validators_hash != self.next_validators_hash {
+                return Err(ClientError::UntrustedHeader);
+            }
+        } else {
+            // Enough of the trusted next set vouches for the block
+            if validator_set_hash(&header.trusted_validators) != self.next_validators_hash {
+                return Err(ClientError::UntrustedHeader);
+            }
+            let (signed, total) =
+                (signed_power(&header.trusted_validators, &signers), total_power(&header.trusted_validators));
+            if u128::from(signed) * 100 <= u128::from(total) * u128::from(context.trust_level) {
+                return Err(ClientError::InvalidJustification);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `misbehaviour` holds two conflicting headers both verifying against the
+    /// trusted state
+    pub fn check_misbehaviour(&self, context: &Context, misbehaviour: &Misbehaviour) -> Result<(), ClientError> {
+        let (first, second) = (&misbehaviour.header_1.header, &misbehaviour.header_2.header);
+        let conflicting = if first.height == second.height {
+            first.hash() != second.hash()
+        } else {
+            // Block times must increase with height
+            let (lower, higher) = if first.height < second.height { (first, second) } else { (second, first) };
+            higher.time <= lower.time
+        };
+        if !conflicting {
+            return Err(ClientError::InvalidMisbehaviour);
+        }
+
+        self.verify_commit(context, &misbehaviour.header_1)?;
+        self.verify_commit(context, &misbehaviour.header_2)
+    }
+}
+
+impl LightClient for TendermintClient {
+    type Header = Header;
+
+    fn verify_header(&mut self, context: &Context, header: Header) -> Result<VerifiedHeader, ClientError> {
+        if header.header.height <= context.latest_height {
+            return Err(ClientError::StaleHeader);
+        }
+        self.verify_commit(context, &header)?;
+
+        let block = header.header;
+        self.next_validators_hash = block.next_validators_hash;
+        self.latest_timestamp = block.time;
+        Ok(VerifiedHeader { height: block.height, root: block.app_hash })
+    }
+}
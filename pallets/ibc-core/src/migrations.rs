@@ -0,0 +1,505 @@
+//! Storage migrations for the IBC core pallet
+
+use super::*;
+
+/// Version 1: clients verify headers with the light client of their [`ClientType`]
+pub mod v1 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Client state as stored up to version 0
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldClientState {
+        pub chain_id: Vec<u8>,
+        pub latest_height: u64,
+        pub frozen: bool,
+        pub trust_level: u32,
+        pub unbonding_period: u64,
+    }
+
+    impl OldClientState {
+        /// The client with type `client_type`, counted as updated at `now`
+        fn upgrade(self, client_type: v5::OldClientType, now: u64) -> v5::OldClientState {
+            v5::OldClientState {
+                client_type,
+                chain_id: self.chain_id,
+                latest_height: self.latest_height,
+                frozen: self.frozen,
+                trust_level: self.trust_level,
+                unbonding_period: self.unbonding_period,
+                last_updated: now,
+                expired: false,
+            }
+        }
+    }
+
+    /// Give every client a type
+    ///
+    /// Clients tracking this chain, the localhost client and those of loopback channels,
+    /// become localhost clients. Version 0 accepted any update from anyone, so every other
+    /// client becomes a mock client, updated with [`MockHeader`]s. Version 0 kept no update
+    /// times, so every client's unbonding period starts running at the upgrade.
+    pub struct InnerAddClientTypes<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerAddClientTypes<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let host_chain_id = T::HostChainId::get();
+            let now = Pallet::<T>::now_secs();
+            let mut translated = 0u64;
+
+            v5::Clients::<T>::translate::<OldClientState, _>(|client_id, old| {
+                translated += 1;
+                let tracks_host = client_id[..] == *LOCALHOST_CLIENT_ID
+                    || (old.chain_id == host_chain_id && old.unbonding_period == u64::MAX);
+                let client_type = if tracks_host { v5::OldClientType::Localhost } else { v5::OldClientType::Mock };
+                Some(old.upgrade(client_type, now))
+            });
+
+            T::DbWeight::get().reads_writes(translated.saturating_add(1), translated)
+        }
+    }
+
+    /// [`InnerAddClientTypes`], run once when upgrading from storage version 0
+    pub type AddClientTypes<T> = VersionedMigration<
+        0,
+        1,
+        InnerAddClientTypes<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 2: consensus states carry the counterparty's commitment root
+pub mod v2 {
+    use super::*;
+    use codec::DecodeAll;
+    use frame_support::{migrations::VersionedMigration, storage::unhashed, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Consensus state as stored before commitment roots
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldConsensusState {
+        pub timestamp: u64,
+    }
+
+    impl OldConsensusState {
+        /// The consensus state with a zero root, which no membership proof verifies against
+        fn upgrade(self) -> ConsensusState {
+            ConsensusState { timestamp: self.timestamp, root: H256::zero() }
+        }
+    }
+
+    /// Give every consensus state a commitment root
+    ///
+    /// Consensus states stored without one get a zero root, so packets proven at their
+    /// height are rejected until the client is updated. Version 0 kept no consensus states
+    /// at all, so every client other than localhost is also given one at its latest height,
+    /// as `create_client` does.
+    pub struct InnerAddConsensusRoots<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerAddConsensusRoots<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            // Both layouts may be stored; they are told apart by their length
+            for (client_id, height) in <ConsensusStates<T>>::iter_keys() {
+                reads += 1;
+                let key = <ConsensusStates<T>>::hashed_key_for(&client_id, height);
+                let old = unhashed::get_raw(&key).and_then(|raw| OldConsensusState::decode_all(&mut &raw[..]).ok());
+                if let Some(old) = old {
+                    <ConsensusStates<T>>::insert(&client_id, height, old.upgrade());
+                    writes += 1;
+                }
+            }
+
+            for (client_id, client) in v5::Clients::<T>::iter() {
+                reads += 2;
+                if matches!(client.client_type, v5::OldClientType::Localhost) ||
+                    <ConsensusStates<T>>::contains_key(&client_id, client.latest_height)
+                {
+                    continue;
+                }
+                let seeded = OldConsensusState { timestamp: client.last_updated }.upgrade();
+                <ConsensusStates<T>>::insert(&client_id, client.latest_height, seeded);
+                writes += 1;
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerAddConsensusRoots`], run once when upgrading from storage version 1
+    pub type AddConsensusRoots<T> = VersionedMigration<
+        1,
+        2,
+        InnerAddConsensusRoots<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 3: channels carry their ordering and upgrade sequence
+pub mod v3 {
+    use super::*;
+    use codec::DecodeAll;
+    use frame_support::{migrations::VersionedMigration, storage::unhashed, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Channel end as stored before ordering and upgrades
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldChannelEnd {
+        pub state: ChannelState,
+        pub connection_id: ConnectionId,
+        pub port_id: PortId,
+        pub counterparty_port_id: PortId,
+        pub version: Vec<u8>,
+        pub next_sequence_send: u64,
+        pub next_sequence_recv: u64,
+        pub next_sequence_ack: u64,
+    }
+
+    impl OldChannelEnd {
+        /// The channel as an ordered one that was never upgraded
+        fn upgrade(self) -> v5::OldChannelEnd {
+            v5::OldChannelEnd {
+                state: self.state,
+                connection_id: self.connection_id,
+                port_id: self.port_id,
+                counterparty_port_id: self.counterparty_port_id,
+                version: self.version,
+                ordering: ChannelOrder::Ordered,
+                upgrade_sequence: 0,
+                next_sequence_send: self.next_sequence_send,
+                next_sequence_recv: self.next_sequence_recv,
+                next_sequence_ack: self.next_sequence_ack,
+            }
+        }
+    }
+
+    /// Give every channel an ordering and upgrade sequence
+    ///
+    /// Channels stored without them received packets strictly in sequence, so they become
+    /// ordered channels, with no upgrade started yet.
+    pub struct InnerAddChannelOrdering<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerAddChannelOrdering<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            // Both layouts may be stored; the old one is shorter by the new fields
+            for (port_id, channel_id) in <Channels<T>>::iter_keys() {
+                reads += 1;
+                let key = <Channels<T>>::hashed_key_for(&port_id, &channel_id);
+                let old = unhashed::get_raw(&key).and_then(|raw| OldChannelEnd::decode_all(&mut &raw[..]).ok());
+                if let Some(old) = old {
+                    v5::Channels::<T>::insert(&port_id, &channel_id, old.upgrade());
+                    writes += 1;
+                }
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerAddChannelOrdering`], run once when upgrading from storage version 2
+    pub type AddChannelOrdering<T> = VersionedMigration<
+        2,
+        3,
+        InnerAddChannelOrdering<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 4: ports are bound by `PortOrigin` rather than by whoever asks first
+pub mod v4 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Bind the ports of the chain's own applications, `Ports`, to their owners
+    ///
+    /// Up to version 3 any signed account could bind a free port, so one an application
+    /// pallet binds only on first use may have been taken by someone else; such a binding is
+    /// replaced. Channels the squatter opened stay, but it can no longer send on them.
+    pub struct InnerBindSystemPorts<T, Ports>(PhantomData<(T, Ports)>);
+
+    impl<T: Config, Ports> UncheckedOnRuntimeUpgrade for InnerBindSystemPorts<T, Ports>
+    where
+        Ports: Get<Vec<(PortId, T::AccountId)>>,
+    {
+        fn on_runtime_upgrade() -> Weight {
+            let ports = Ports::get();
+            let mut writes = 0u64;
+
+            for (port_id, owner) in &ports {
+                if <PortOwners<T>>::get(port_id).as_ref() != Some(owner) {
+                    <PortOwners<T>>::insert(port_id, owner);
+                    writes += 1;
+                }
+            }
+
+            T::DbWeight::get().reads_writes(ports.len() as u64, writes)
+        }
+    }
+
+    /// [`InnerBindSystemPorts`], run once when upgrading from storage version 3
+    pub type BindSystemPorts<T, Ports> = VersionedMigration<
+        3,
+        4,
+        InnerBindSystemPorts<T, Ports>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 5: chain identifiers, versions, acknowledgements and authority sets are bounded
+pub mod v5 {
+    use super::*;
+    use crate::light_client::grandpa::{AuthorityList, BoundedAuthorityList, MaxAuthorities, PendingChange};
+    use frame_support::{migrations::VersionedMigration, storage_alias, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// GRANDPA authority set change as stored before version 5, of any size
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldPendingChange {
+        pub next_authorities: AuthorityList,
+        pub effective_at: u32,
+        pub forced: bool,
+    }
+
+    /// GRANDPA client as stored before version 5, tracking a set of any size
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldGrandpaClient {
+        pub authority_set_id: u64,
+        pub authorities: AuthorityList,
+        pub pending_change: Option<OldPendingChange>,
+    }
+
+    /// Client type as stored before version 5
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub enum OldClientType {
+        Tendermint(TendermintClient),
+        Grandpa(OldGrandpaClient),
+        Mock,
+        Localhost,
+    }
+
+    /// Client state as stored before version 5, with a chain identifier of any length
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldClientState {
+        pub client_type: OldClientType,
+        pub chain_id: Vec<u8>,
+        pub latest_height: u64,
+        pub frozen: bool,
+        pub trust_level: u32,
+        pub unbonding_period: u64,
+        pub last_updated: u64,
+        pub expired: bool,
+    }
+
+    /// Connection end as stored before version 5, with a version of any length
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldConnectionEnd {
+        pub state: ConnectionState,
+        pub client_id: ClientId,
+        pub counterparty_client_id: ClientId,
+        pub version: Vec<u8>,
+    }
+
+    /// Upgrade fields as stored before version 5, with a version of any length
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldUpgradeFields {
+        pub ordering: ChannelOrder,
+        pub version: Vec<u8>,
+    }
+
+    /// Channel end as stored before version 5, with a version of any length
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldChannelEnd {
+        pub state: ChannelState,
+        pub connection_id: ConnectionId,
+        pub port_id: PortId,
+        pub counterparty_port_id: PortId,
+        pub version: Vec<u8>,
+        pub ordering: ChannelOrder,
+        pub upgrade_sequence: u64,
+        pub next_sequence_send: u64,
+        pub next_sequence_recv: u64,
+        pub next_sequence_ack: u64,
+    }
+
+    #[storage_alias]
+    pub type Clients<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, ClientId, OldClientState, OptionQuery>;
+
+    #[storage_alias]
+    pub type Connections<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, ConnectionId, OldConnectionEnd, OptionQuery>;
+
+    #[storage_alias]
+    pub type Channels<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        PortId,
+        Blake2_128Concat,
+        ChannelId,
+        OldChannelEnd,
+        OptionQuery,
+    >;
+
+    #[storage_alias]
+    pub type ChannelUpgrades<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        PortId,
+        Blake2_128Concat,
+        ChannelId,
+        OldUpgradeFields,
+        OptionQuery,
+    >;
+
+    #[storage_alias]
+    pub type PacketAcknowledgments<T: Config> =
+        StorageDoubleMap<Pallet<T>, Blake2_128Concat, PortId, Blake2_128Concat, u64, Vec<u8>, OptionQuery>;
+
+    /// `authorities` cut to [`MaxAuthorities`], and whether any were dropped
+    fn bound_authorities(authorities: AuthorityList) -> (BoundedAuthorityList, bool) {
+        let cut = authorities.len() > MaxAuthorities::get() as usize;
+        (BoundedVec::truncate_from(authorities), cut)
+    }
+
+    impl OldClientState {
+        /// The client with its chain identifier and authority sets cut to their bounds,
+        /// frozen if anything was cut: it could no longer verify its counterparty's headers
+        fn bound(self) -> (ClientState, bool) {
+            let mut cut = self.chain_id.len() > MaxChainIdLen::get() as usize;
+            let client_type = match self.client_type {
+                OldClientType::Tendermint(client) => ClientType::Tendermint(client),
+                OldClientType::Grandpa(client) => {
+                    let (authorities, cut_set) = bound_authorities(client.authorities);
+                    cut |= cut_set;
+                    let pending_change = client.pending_change.map(|change| {
+                        let (next_authorities, cut_next) = bound_authorities(change.next_authorities);
+                        cut |= cut_next;
+                        PendingChange { next_authorities, effective_at: change.effective_at, forced: change.forced }
+                    });
+                    ClientType::Grandpa(GrandpaClient {
+                        authority_set_id: client.authority_set_id,
+                        authorities,
+                        pending_change,
+                    })
+                }
+                OldClientType::Mock => ClientType::Mock,
+                OldClientType::Localhost => ClientType::Localhost,
+            };
+            let client = ClientState {
+                client_type,
+                chain_id: ChainId::truncate_from(self.chain_id),
+                latest_height: self.latest_height,
+                frozen: self.frozen || cut,
+                trust_level: self.trust_level,
+                unbonding_period: self.unbonding_period,
+                last_updated: self.last_updated,
+                expired: self.expired,
+            };
+            (client, cut)
+        }
+    }
+
+    /// Bound the vectors the pallet stored without a bound
+    ///
+    /// Chain identifiers, versions and acknowledgements over their bound are cut to it, and
+    /// GRANDPA authority sets to [`MaxAuthorities`]. A client whose chain identifier or
+    /// authority set was cut can no longer be trusted to verify its counterparty, so it is
+    /// frozen. Only the entries over a bound are rewritten.
+    pub struct InnerBoundStoredVecs<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerBoundStoredVecs<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+            let max_version = MaxVersionLen::get() as usize;
+
+            for (client_id, old) in Clients::<T>::iter().collect::<Vec<_>>() {
+                reads += 1;
+                let (client, cut) = old.bound();
+                if cut {
+                    log::warn!(target: LOG_TARGET, "client {} frozen beyond its bounds", Printable(&client_id));
+                    crate::Clients::<T>::insert(&client_id, client);
+                    writes += 1;
+                }
+            }
+
+            for (connection_id, old) in Connections::<T>::iter().collect::<Vec<_>>() {
+                reads += 1;
+                if old.version.len() > max_version {
+                    crate::Connections::<T>::insert(
+                        &connection_id,
+                        ConnectionEnd {
+                            state: old.state,
+                            client_id: old.client_id,
+                            counterparty_client_id: old.counterparty_client_id,
+                            version: Version::truncate_from(old.version),
+                        },
+                    );
+                    writes += 1;
+                }
+            }
+
+            for (port_id, channel_id, old) in Channels::<T>::iter().collect::<Vec<_>>() {
+                reads += 1;
+                if old.version.len() > max_version {
+                    crate::Channels::<T>::insert(
+                        &port_id,
+                        &channel_id,
+                        ChannelEnd {
+                            state: old.state,
+                            connection_id: old.connection_id,
+                            port_id: old.port_id,
+                            counterparty_port_id: old.counterparty_port_id,
+                            version: Version::truncate_from(old.version),
+                            ordering: old.ordering,
+                            upgrade_sequence: old.upgrade_sequence,
+                            next_sequence_send: old.next_sequence_send,
+                            next_sequence_recv: old.next_sequence_recv,
+                            next_sequence_ack: old.next_sequence_ack,
+                        },
+                    );
+                    writes += 1;
+                }
+            }
+
+            for (port_id, channel_id, old) in ChannelUpgrades::<T>::iter().collect::<Vec<_>>() {
+                reads += 1;
+                if old.version.len() > max_version {
+                    let fields = UpgradeFields { ordering: old.ordering, version: Version::truncate_from(old.version) };
+                    crate::ChannelUpgrades::<T>::insert(&port_id, &channel_id, fields);
+                    writes += 1;
+                }
+            }
+
+            let max_ack = T::MaxAcknowledgementLen::get() as usize;
+            for (port_id, sequence, old) in PacketAcknowledgments::<T>::iter().collect::<Vec<_>>() {
+                reads += 1;
+                if old.len() > max_ack {
+                    crate::PacketAcknowledgments::<T>::insert(&port_id, sequence, BoundedVec::truncate_from(old));
+                    writes += 1;
+                }
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerBoundStoredVecs`], run once when upgrading from storage version 4
+    pub type BoundStoredVecs<T> = VersionedMigration<
+        4,
+        5,
+        InnerBoundStoredVecs<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
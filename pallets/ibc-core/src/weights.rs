@@ -0,0 +1,230 @@
+//! Weights for pallet_ibc_core
+//!
+//! Packet extrinsics are linear in the packet payload size `d` (bytes): the payload is
+//! hashed into the commitment, copied into the event and, on receipt, handed to the router.
+//! `update_client` and `submit_misbehaviour` are linear in the header or evidence size `h`,
+//! `m` (bytes): every signature a header of any client type carries takes at least 64 bytes,
+//! so one signature verification is charged per 64 bytes. The remaining extrinsics touch a
+//! fixed number of storage items.
+//!
+//! All figures are estimates, not benchmark results: each counts the storage accesses of its
+//! extrinsic on top of a fixed execution estimate. The packet extrinsics have benchmarks in
+//! `benchmarking.rs`, but no run on reference hardware has replaced their estimates yet. To
+//! do so, run `netchain-node benchmark pallet --chain dev --pallet pallet_ibc_core
+//! --extrinsic '*' --steps 50 --repeat 20` with the `runtime-benchmarks` feature and copy the
+//! figures over.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_ibc_core.
+pub trait WeightInfo {
+	fn create_client() -> Weight;
+	fn update_client(h: u32) -> Weight;
+	fn connection_open_init() -> Weight;
+	fn channel_open_init() -> Weight;
+	fn send_packet(d: u32) -> Weight;
+	fn recv_packet(d: u32) -> Weight;
+	fn acknowledge_packet(d: u32) -> Weight;
+	fn timeout_packet() -> Weight;
+	fn channel_upgrade_init() -> Weight;
+	fn channel_upgrade_try() -> Weight;
+	fn channel_upgrade_ack() -> Weight;
+	fn channel_upgrade_confirm() -> Weight;
+	fn bind_port() -> Weight;
+	fn close_client() -> Weight;
+	fn remove_client() -> Weight;
+	fn register_relayer() -> Weight;
+	fn deregister_relayer() -> Weight;
+	fn channel_open_localhost() -> Weight;
+	fn submit_misbehaviour(m: u32) -> Weight;
+	fn remove_relayer() -> Weight;
+}
+
+/// Weights for pallet_ibc_core using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: IbcCore NextClientId (r:1 w:1), IbcCore RemovedClients (r:1 w:0), System Account (r:1 w:1),
+	/// IbcCore ClientDeposits (r:0 w:1), IbcCore Clients (r:0 w:1), IbcCore ConsensusStates (r:0 w:1)
+	fn create_client() -> Weight {
+		Weight::from_parts(32_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: IbcCore Clients (r:1 w:1), Timestamp Now (r:1 w:0), IbcCore ConsensusStates (r:0 w:1)
+	fn update_client(h: u32) -> Weight {
+		Weight::from_parts(18_000_000, 3_540)
+			// One ed25519 verification (~48µs) per 64 bytes of header
+			.saturating_add(Weight::from_parts(750_000, 0).saturating_mul(h.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: IbcCore Clients (r:1 w:0), IbcCore NextConnectionId (r:1 w:1), IbcCore Connections (r:0 w:1)
+	fn connection_open_init() -> Weight {
+		Weight::from_parts(17_000_000, 3_540)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: IbcCore PortOwners (r:1 w:0), IbcCore Connections (r:1 w:0), IbcCore NextChannelId (r:1 w:1),
+	/// IbcCore Channels (r:0 w:1)
+	fn channel_open_init() -> Weight {
+		Weight::from_parts(21_000_000, 3_560)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: System Account (r:2 w:2), IbcCore Channels (r:1 w:1), IbcCore Connections (r:1 w:0),
+	/// IbcCore Clients (r:1 w:0), Timestamp Now (r:1 w:0), IbcCore ChannelRateLimits (r:1 w:1),
+	/// IbcCore PacketCommitments (r:0 w:1), IbcCore ChannelMetrics (r:1 w:1)
+	/// The range of component `d` is `[0, 65536]`.
+	fn send_packet(d: u32) -> Weight {
+		Weight::from_parts(48_000_000, 6_196)
+			.saturating_add(Weight::from_parts(2_100, 0).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: IbcCore Channels (r:1 w:1), IbcCore Connections (r:1 w:0), IbcCore Clients (r:1 w:0),
+	/// Timestamp Now (r:1 w:0), IbcCore ChannelRateLimits (r:1 w:1), IbcCore PacketAcknowledgments (r:1 w:1),
+	/// IbcCore ChannelMetrics (r:1 w:1), IbcCore Relayers (r:1 w:0), IbcCore RelayerMetrics (r:1 w:1)
+	/// The range of component `d` is `[0, 65536]`.
+	fn recv_packet(d: u32) -> Weight {
+		Weight::from_parts(42_000_000, 3_593)
+			.saturating_add(Weight::from_parts(2_300, 0).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: IbcCore PacketCommitments (r:1 w:1), IbcCore Channels (r:1 w:0), IbcCore Connections (r:1 w:0),
+	/// IbcCore Clients (r:1 w:0), Timestamp Now (r:1 w:0), IbcCore ConsensusStates (r:1 w:0),
+	/// IbcCore PacketOutcomes (r:0 w:1), IbcCore ChannelMetrics (r:1 w:1), IbcCore Relayers (r:1 w:0),
+	/// IbcCore RelayerMetrics (r:1 w:1)
+	/// The range of component `d` is `[0, 65536]`.
+	fn acknowledge_packet(d: u32) -> Weight {
+		Weight::from_parts(40_000_000, 3_593)
+			.saturating_add(Weight::from_parts(1_200, 0).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: IbcCore PacketCommitments (r:1 w:1), IbcCore PacketOutcomes (r:0 w:1), IbcCore ChannelMetrics (r:1 w:1),
+	/// IbcCore Relayers (r:1 w:0), IbcCore RelayerMetrics (r:1 w:1)
+	fn timeout_packet() -> Weight {
+		Weight::from_parts(19_000_000, 3_530)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: IbcCore Channels (r:1 w:1), IbcCore ChannelUpgrades (r:1 w:1)
+	fn channel_upgrade_init() -> Weight {
+		Weight::from_parts(17_000_000, 3_560)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: IbcCore Channels (r:1 w:1), IbcCore ChannelUpgrades (r:1 w:1)
+	fn channel_upgrade_try() -> Weight {
+		Weight::from_parts(17_000_000, 3_560)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: IbcCore Channels (r:1 w:1), IbcCore ChannelUpgrades (r:1 w:1)
+	fn channel_upgrade_ack() -> Weight {
+		Weight::from_parts(17_000_000, 3_560)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: IbcCore Channels (r:1 w:1), IbcCore ChannelUpgrades (r:1 w:1)
+	fn channel_upgrade_confirm() -> Weight {
+		Weight::from_parts(17_000_000, 3_560)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: IbcCore PortOwners (r:1 w:1)
+	fn bind_port() -> Weight {
+		Weight::from_parts(11_000_000, 3_530)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: IbcCore Clients (r:1 w:1), IbcCore ClientDeposits (r:1 w:1), System Account (r:1 w:1),
+	/// IbcCore RemovedClients (r:1 w:1), IbcCore ConsensusStates (r:0 w:1)
+	fn close_client() -> Weight {
+		Weight::from_parts(40_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: IbcCore Clients (r:1 w:1), IbcCore ClientDeposits (r:1 w:1), System Account (r:1 w:1),
+	/// IbcCore RemovedClients (r:1 w:1), IbcCore ConsensusStates (r:0 w:1)
+	fn remove_client() -> Weight {
+		Weight::from_parts(40_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: IbcCore Relayers (r:1 w:1), System Account (r:1 w:1), IbcCore RelayerBonds (r:0 w:1)
+	fn register_relayer() -> Weight {
+		Weight::from_parts(24_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: IbcCore Relayers (r:1 w:1), IbcCore RelayerBonds (r:1 w:1), IbcCore RelayerMetrics (r:0 w:1),
+	/// System Account (r:1 w:1)
+	fn deregister_relayer() -> Weight {
+		Weight::from_parts(26_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: IbcCore PortOwners (r:2 w:0), IbcCore Connections (r:3 w:1), Timestamp Now (r:1 w:0),
+	/// IbcCore Clients (r:0 w:1), IbcCore NextChannelId (r:2 w:2), IbcCore Channels (r:0 w:2)
+	fn channel_open_localhost() -> Weight {
+		Weight::from_parts(45_000_000, 3_560)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: IbcCore Clients (r:1 w:1), Timestamp Now (r:1 w:0)
+	fn submit_misbehaviour(m: u32) -> Weight {
+		Weight::from_parts(60_000_000, 3_540)
+			// One ed25519 verification (~48µs) per 64 bytes of evidence
+			.saturating_add(Weight::from_parts(750_000, 0).saturating_mul(m.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: IbcCore Relayers (r:1 w:1), IbcCore RelayerBonds (r:1 w:1), IbcCore RelayerMetrics (r:0 w:1),
+	/// System Account (r:1 w:1)
+	fn remove_relayer() -> Weight {
+		Weight::from_parts(30_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn create_client() -> Weight { Weight::from_parts(50_000, 0) }
+	fn update_client(h: u32) -> Weight {
+		Weight::from_parts(30_000, 0).saturating_add(Weight::from_parts(10, 0).saturating_mul(h.into()))
+	}
+	fn connection_open_init() -> Weight { Weight::from_parts(40_000, 0) }
+	fn channel_open_init() -> Weight { Weight::from_parts(40_000, 0) }
+	fn send_packet(d: u32) -> Weight {
+		Weight::from_parts(100_000, 0).saturating_add(Weight::from_parts(10, 0).saturating_mul(d.into()))
+	}
+	fn recv_packet(d: u32) -> Weight {
+		Weight::from_parts(80_000, 0).saturating_add(Weight::from_parts(10, 0).saturating_mul(d.into()))
+	}
+	fn acknowledge_packet(d: u32) -> Weight {
+		Weight::from_parts(20_000, 0).saturating_add(Weight::from_parts(5, 0).saturating_mul(d.into()))
+	}
+	fn timeout_packet() -> Weight { Weight::from_parts(20_000, 0) }
+	fn channel_upgrade_init() -> Weight { Weight::from_parts(30_000, 0) }
+	fn channel_upgrade_try() -> Weight { Weight::from_parts(30_000, 0) }
+	fn channel_upgrade_ack() -> Weight { Weight::from_parts(30_000, 0) }
+	fn channel_upgrade_confirm() -> Weight { Weight::from_parts(30_000, 0) }
+	fn bind_port() -> Weight { Weight::from_parts(20_000, 0) }
+	fn close_client() -> Weight { Weight::from_parts(60_000, 0) }
+	fn remove_client() -> Weight { Weight::from_parts(60_000, 0) }
+	fn register_relayer() -> Weight { Weight::from_parts(20_000, 0) }
+	fn deregister_relayer() -> Weight { Weight::from_parts(20_000, 0) }
+	fn channel_open_localhost() -> Weight { Weight::from_parts(80_000, 0) }
+	fn submit_misbehaviour(m: u32) -> Weight {
+		Weight::from_parts(60_000, 0).saturating_add(Weight::from_parts(10, 0).saturating_mul(m.into()))
+	}
+	fn remove_relayer() -> Weight { Weight::from_parts(40_000, 0) }
+}
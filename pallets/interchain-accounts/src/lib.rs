@@ -0,0 +1,807 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Interchain Accounts Pallet
+//!
+//! IBC interchain accounts (ICS-27) host for Netchain.
+//!
+//! A controller chain opens a channel from one of its `icacontroller-<owner>` ports to
+//! Netchain's `icahost` port. Netchain derives an interchain account for the owner from the
+//! connection and the controller port, and executes the calls carried by packets on the
+//! channel with that account as signed origin. This lets contracts and users on other chains
+//! move funds, bond stake or request oracle data on Netchain without a bridge.
+//!
+//! ## Call Filtering
+//! - Only calls admitted by `AllowedCalls` can be executed; the runtime admits transfers,
+//!   staking bonds and oracle requests
+//! - `FilterOrigin` can narrow each channel further to a list of `(pallet index, call index)`
+//!   pairs
+//!
+//! ## Execution
+//! The calls of a packet are executed atomically: if one is filtered or fails, none of them
+//! takes effect and the packet is acknowledged with the error. Packets are bounded by
+//! `MaxCallsPerPacket` and by `MaxPacketWeight`, which `recv_packet` charges up front.
+
+pub use pallet::*;
+
+use codec::DecodeLimit;
+use frame_support::{
+    dispatch::{GetDispatchInfo, PostDispatchInfo},
+    pallet_prelude::*,
+    storage::{with_transaction, TransactionOutcome},
+    traits::Contains,
+    PalletId,
+};
+use frame_system::{pallet_prelude::*, RawOrigin};
+use pallet_ibc_core::{
+    channel_end_path, Acknowledgement, ChannelEnd, ChannelId, ChannelState, ConnectionId, IbcRouter, Packet, PortId,
+    ProofStep,
+};
+use sp_runtime::traits::{AccountIdConversion, BlakeTwo256, Dispatchable, Hash};
+use sp_std::vec::Vec;
+
+/// Port interchain account channels are opened on
+pub const HOST_PORT: &[u8] = netchain_primitives::ICA_HOST_PORT;
+/// Prefix of the counterparty ports controlling interchain accounts
+pub const CONTROLLER_PORT_PREFIX: &[u8] = b"icacontroller-";
+/// Application version negotiated on interchain account channels
+pub const VERSION: &[u8] = b"ics27-1";
+
+/// Nesting limit when decoding the calls of a packet
+const MAX_CALL_DECODE_DEPTH: u32 = 256;
+
+/// Data of a packet sent to the host port: calls to execute from the interchain account
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct InterchainAccountPacketData<Call> {
+    /// Calls to dispatch, in order
+    pub calls: Vec<Call>,
+    /// Free-form note from the controller
+    pub memo: Vec<u8>,
+}
+
+/// Host end of an interchain account channel
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct HostChannel<AccountId> {
+    /// Connection to the controller chain
+    pub connection_id: ConnectionId,
+    /// Controller port on the counterparty
+    pub controller_port: PortId,
+    /// Interchain account the channel's packets are executed from
+    pub account: AccountId,
+}
+
+/// `(pallet index, call index)` of a runtime call, as used by channel call filters
+pub type CallIndex = (u8, u8);
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_ibc_core::Config {
+        /// The overarching event type
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Calls interchain accounts can execute
+        type RuntimeCall: Parameter
+            + Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo;
+
+        /// Calls admitted on every channel
+        type AllowedCalls: Contains<<Self as Config>::RuntimeCall>;
+
+        /// Origin allowed to set per-channel call filters
+        type FilterOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Maximum calls in one packet
+        #[pallet::constant]
+        type MaxCallsPerPacket: Get<u32>;
+
+        /// Maximum entries of a channel call filter
+        #[pallet::constant]
+        type MaxFilterEntries: Get<u32>;
+
+        /// Maximum total weight of the calls in one packet
+        #[pallet::constant]
+        type MaxPacketWeight: Get<Weight>;
+
+        /// Identifier interchain accounts are derived from
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// Weight information for extrinsics
+        type WeightInfo: WeightInfo;
+    }
+
+    /// Host channels, keyed by channel
+    #[pallet::storage]
+    #[pallet::getter(fn host_channels)]
+    pub type HostChannels<T: Config> =
+        StorageMap<_, Blake2_128Concat, ChannelId, HostChannel<T::AccountId>>;
+
+    /// Open host channel of each controller, keyed by connection and controller port
+    #[pallet::storage]
+    #[pallet::getter(fn active_channel)]
+    pub type ActiveChannels<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, ConnectionId,
+        Blake2_128Concat, PortId,
+        ChannelId
+    >;
+
+    /// Calls a channel is narrowed to, on top of `AllowedCalls`
+    #[pallet::storage]
+    #[pallet::getter(fn channel_call_filter)]
+    pub type ChannelCallFilters<T: Config> =
+        StorageMap<_, Blake2_128Concat, ChannelId, BoundedVec<CallIndex, T::MaxFilterEntries>>;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        #[serde(skip)]
+        pub _config: core::marker::PhantomData<T>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            // Reserve the host port before anyone else can bind it
+            pallet_ibc_core::Pallet::<T>::bind_port_to(Pallet::<T>::host_port(), Pallet::<T>::account_id())
+                .expect("host port is free at genesis; qed");
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A controller opened a channel to its interchain account
+        InterchainAccountRegistered {
+            channel_id: ChannelId,
+            connection_id: ConnectionId,
+            controller_port: PortId,
+            account: T::AccountId,
+        },
+        /// The calls of a packet were executed
+        PacketExecuted { channel_id: ChannelId, account: T::AccountId, calls: u32 },
+        /// A packet was rejected or one of its calls failed; none of its calls took effect
+        PacketFailed { channel_id: ChannelId, error: Vec<u8> },
+        /// A channel was narrowed to `allowed` calls
+        ChannelFilterSet { channel_id: ChannelId, allowed: Vec<CallIndex> },
+        /// A channel's filter was removed
+        ChannelFilterCleared { channel_id: ChannelId },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Counterparty port is not an `icacontroller-<owner>` port
+        InvalidControllerPort,
+        /// Channel version is not `ics27-1`
+        InvalidVersion,
+        /// The controller already has an open channel on this connection
+        ChannelAlreadyActive,
+        /// Channel is not an interchain account channel
+        UnknownChannel,
+        /// Counterparty channel end is not a `ChanOpenInit` towards the host port
+        InvalidCounterpartyChannel,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Open the host end of a channel a controller chain started
+        ///
+        /// `counterparty` is the controller's channel end `counterparty_channel_id`, which must
+        /// be in `Init` state towards `icahost`. `proof_init` must show its hash stored under
+        /// the channel's ICS-24 path, against the consensus state the connection's client
+        /// recorded at `proof_height`; when the counterparty is this chain it is read from
+        /// storage instead.
+        ///
+        /// The channel is opened on `icahost` and immediately usable. Its interchain account is
+        /// derived from `connection_id` and the controller port only.
+        #[pallet::call_index(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::open_host_channel())]
+        pub fn open_host_channel(
+            origin: OriginFor<T>,
+            connection_id: ConnectionId,
+            counterparty_channel_id: ChannelId,
+            counterparty: ChannelEnd,
+            proof_init: Vec<ProofStep>,
+            proof_height: u64,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let controller_port = counterparty.port_id.clone();
+            ensure!(
+                controller_port.len() > CONTROLLER_PORT_PREFIX.len() &&
+                    controller_port.starts_with(CONTROLLER_PORT_PREFIX),
+                Error::<T>::InvalidControllerPort
+            );
+            ensure!(counterparty.version[..] == *VERSION, Error::<T>::InvalidVersion);
+            ensure!(
+                counterparty.state == ChannelState::Init && counterparty.counterparty_port_id[..] == *HOST_PORT,
+                Error::<T>::InvalidCounterpartyChannel
+            );
+            ensure!(
+                !<ActiveChannels<T>>::contains_key(&connection_id, &controller_port),
+                Error::<T>::ChannelAlreadyActive
+            );
+
+            // The controller must have started the handshake
+            if pallet_ibc_core::Pallet::<T>::counterparty_is_self(&connection_id) {
+                ensure!(
+                    pallet_ibc_core::Channels::<T>::get(&controller_port, &counterparty_channel_id).as_ref()
+                        == Some(&counterparty),
+                    Error::<T>::InvalidCounterpartyChannel
+                );
+            } else {
+                pallet_ibc_core::Pallet::<T>::verify_counterparty(
+                    &connection_id,
+                    proof_height,
+                    &channel_end_path(&controller_port, &counterparty_channel_id),
+                    &BlakeTwo256::hash_of(&counterparty),
+                    &proof_init,
+                )?;
+            }
+
+            // Chains that added the pallet after genesis bind the host port on first use
+            let host_port = Self::host_port();
+            let owner = Self::account_id();
+            if pallet_ibc_core::PortOwners::<T>::contains_key(&host_port) {
+                pallet_ibc_core::Pallet::<T>::ensure_port_owner(&host_port, &owner)?;
+            } else {
+                pallet_ibc_core::Pallet::<T>::bind_port_to(host_port.clone(), owner)?;
+            }
+
+            let channel_id = pallet_ibc_core::Pallet::<T>::open_channel(
+                host_port,
+                connection_id.clone(),
+                controller_port.clone(),
+                counterparty.version.into_inner(),
+                ChannelState::Open,
+            )?;
+
+            let account = Self::interchain_account(&connection_id, &controller_port);
+            <HostChannels<T>>::insert(
+                &channel_id,
+                HostChannel {
+                    connection_id: connection_id.clone(),
+                    controller_port: controller_port.clone(),
+                    account: account.clone(),
+                },
+            );
+            <ActiveChannels<T>>::insert(&connection_id, &controller_port, &channel_id);
+
+            Self::deposit_event(Event::InterchainAccountRegistered {
+                channel_id,
+                connection_id,
+                controller_port,
+                account,
+            });
+
+            Ok(())
+        }
+
+        /// Restrict a channel to `allowed` calls, on top of `AllowedCalls`
+        #[pallet::call_index(1)]
+        #[pallet::weight(<T as Config>::WeightInfo::set_channel_filter())]
+        pub fn set_channel_filter(
+            origin: OriginFor<T>,
+            channel_id: ChannelId,
+            allowed: BoundedVec<CallIndex, T::MaxFilterEntries>,
+        ) -> DispatchResult {
+            T::FilterOrigin::ensure_origin(origin)?;
+            ensure!(<HostChannels<T>>::contains_key(&channel_id), Error::<T>::UnknownChannel);
+
+            <ChannelCallFilters<T>>::insert(&channel_id, &allowed);
+            Self::deposit_event(Event::ChannelFilterSet { channel_id, allowed: allowed.into_inner() });
+
+            Ok(())
+        }
+
+        /// Remove a channel's filter, admitting every call in `AllowedCalls` again
+        #[pallet::call_index(2)]
+        #[pallet::weight(<T as Config>::WeightInfo::clear_channel_filter())]
+        pub fn clear_channel_filter(origin: OriginFor<T>, channel_id: ChannelId) -> DispatchResult {
+            T::FilterOrigin::ensure_origin(origin)?;
+            ensure!(<HostChannels<T>>::contains_key(&channel_id), Error::<T>::UnknownChannel);
+
+            <ChannelCallFilters<T>>::remove(&channel_id);
+            Self::deposit_event(Event::ChannelFilterCleared { channel_id });
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Account owning the host port
+        pub fn account_id() -> T::AccountId {
+            <T as Config>::PalletId::get().into_account_truncating()
+        }
+
+        /// The `icahost` port identifier
+        pub fn host_port() -> PortId {
+            PortId::truncate_from(HOST_PORT.to_vec())
+        }
+
+        /// Interchain account of the controller at `controller_port` over `connection_id`
+        pub fn interchain_account(connection_id: &ConnectionId, controller_port: &PortId) -> T::AccountId {
+            let owner = BlakeTwo256::hash_of(&(connection_id, controller_port));
+            <T as Config>::PalletId::get().into_sub_account_truncating(owner)
+        }
+
+        /// Execute the calls in `data` from the interchain account of `channel_id`
+        ///
+        /// Returns the number of executed calls. On error no call takes effect.
+        fn execute_packet(channel_id: &ChannelId, data: &[u8]) -> Result<u32, &'static str> {
+            let channel = <HostChannels<T>>::get(channel_id).ok_or("unknown interchain account channel")?;
+            let packet = InterchainAccountPacketData::<<T as Config>::RuntimeCall>::decode_all_with_depth_limit(
+                MAX_CALL_DECODE_DEPTH,
+                &mut &data[..],
+            )
+            .map_err(|_| "undecodable packet data")?;
+
+            if packet.calls.len() > T::MaxCallsPerPacket::get() as usize {
+                return Err("too many calls");
+            }
+
+            let filter = <ChannelCallFilters<T>>::get(channel_id);
+            let mut weight = Weight::zero();
+            for call in &packet.calls {
+                let index = call.using_encoded(|encoded| (encoded[0], encoded[1]));
+                if !T::AllowedCalls::contains(call) || filter.as_ref().is_some_and(|f| !f.contains(&index)) {
+                    return Err("call not allowed");
+                }
+                weight = weight.saturating_add(call.get_dispatch_info().call_weight);
+            }
+            if weight.any_gt(T::MaxPacketWeight::get()) {
+                return Err("packet exceeds weight limit");
+            }
+
+            let calls = packet.calls.len() as u32;
+            with_transaction(|| {
+                for call in packet.calls {
+                    if let Err(e) = call.dispatch(RawOrigin::Signed(channel.account.clone()).into()) {
+                        return TransactionOutcome::Rollback(Err(e.error));
+                    }
+                }
+                TransactionOutcome::Commit(Ok(()))
+            })
+            .map_err(|e: DispatchError| -> &'static str { e.into() })?;
+
+            Self::deposit_event(Event::PacketExecuted {
+                channel_id: channel_id.clone(),
+                account: channel.account,
+                calls,
+            });
+
+            Ok(calls)
+        }
+    }
+}
+
+/// Packets on the host port are executed; every other port keeps the default empty result
+impl<T: Config> IbcRouter<T::AccountId> for Pallet<T> {
+    fn on_recv_packet(packet: &Packet, _relayer: &T::AccountId) -> Acknowledgement {
+        if packet.destination_port.as_slice() != HOST_PORT {
+            return Ok(Vec::new());
+        }
+
+        match Self::execute_packet(&packet.destination_channel, &packet.data) {
+            Ok(calls) => Ok(calls.encode()),
+            Err(error) => {
+                Self::deposit_event(Event::PacketFailed {
+                    channel_id: packet.destination_channel.clone(),
+                    error: error.as_bytes().to_vec(),
+                });
+                Err(error.as_bytes().to_vec())
+            },
+        }
+    }
+
+    fn max_recv_packet_weight() -> Weight {
+        T::MaxPacketWeight::get()
+    }
+}
+
+/// Weight functions needed for benchmarking
+pub trait WeightInfo {
+    fn open_host_channel() -> Weight;
+    fn set_channel_filter() -> Weight;
+    fn clear_channel_filter() -> Weight;
+}
+
+/// Default weights (based on complexity analysis)
+impl WeightInfo for () {
+    fn open_host_channel() -> Weight { Weight::from_parts(60_000, 0) }
+    fn set_channel_filter() -> Weight { Weight::from_parts(15_000, 0) }
+    fn clear_channel_filter() -> Weight { Weight::from_parts(12_000, 0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as pallet_interchain_accounts;
+    use frame_support::{
+        assert_err, assert_noop, assert_ok, derive_impl, parameter_types,
+        traits::{ConstU32, ConstU64},
+    };
+    use frame_system::EnsureRoot;
+    use pallet_ibc_core::{ChannelOrder, ConnectionState, MockHeader};
+    use sp_core::H256;
+    use sp_runtime::{BuildStorage, DispatchError};
+
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test
+        {
+            System: frame_system,
+            Timestamp: pallet_timestamp,
+            Balances: pallet_balances,
+            IbcCore: pallet_ibc_core,
+            InterchainAccounts: pallet_interchain_accounts,
+        }
+    );
+
+    #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+    impl frame_system::Config for Test {
+        type Block = Block;
+        type AccountData = pallet_balances::AccountData<u64>;
+    }
+
+    impl pallet_timestamp::Config for Test {
+        type Moment = u64;
+        type OnTimestampSet = ();
+        type MinimumPeriod = ConstU64<1>;
+        type WeightInfo = ();
+    }
+
+    #[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+    impl pallet_balances::Config for Test {
+        type AccountStore = System;
+        type ExistentialDeposit = ConstU64<5>;
+    }
+
+    parameter_types! {
+        pub const IbcPalletId: PalletId = PalletId(*b"test/ibc");
+        pub const IcaPalletId: PalletId = PalletId(*b"test/ica");
+        pub HostChainId: Vec<u8> = b"netchain".to_vec();
+        pub HostNextValidatorsHash: H256 = H256::zero();
+        pub HostAuthoritySet: (u64, pallet_ibc_core::light_client::grandpa::AuthorityList) = (0, Vec::new());
+        pub MaxPacketWeight: Weight = Weight::from_parts(1_000_000_000_000, u64::MAX);
+    }
+
+    impl pallet_ibc_core::Config for Test {
+        type RuntimeEvent = RuntimeEvent;
+        type Currency = Balances;
+        type MaxClients = ConstU32<10>;
+        type MaxConnections = ConstU32<10>;
+        type MaxChannels = ConstU32<10>;
+        type ClientDeposit = ConstU64<10>;
+        type PacketTransmissionFee = ConstU64<1>;
+        type MaxPacketDataSize = ConstU32<4096>;
+        type MaxAcknowledgementLen = ConstU32<1024>;
+        type RateLimitWindow = ConstU64<10>;
+        type MaxPacketsPerWindow = ConstU32<100>;
+        type MaxBytesPerWindow = ConstU32<100_000>;
+        type HostChainId = HostChainId;
+        type HostUnbondingPeriod = ConstU64<1_000_000>;
+        type HostNextValidatorsHash = HostNextValidatorsHash;
+        type HostAuthoritySet = HostAuthoritySet;
+        type History = ();
+        type Safeguard = ();
+        type Router = InterchainAccounts;
+        type ChannelUpgradeOrigin = EnsureRoot<u64>;
+        type ClientRemovalOrigin = EnsureRoot<u64>;
+        type RelayerOrigin = EnsureRoot<u64>;
+        type PortOrigin = EnsureRoot<u64>;
+        type MaxFeelessRelaysPerBlock = ConstU32<10>;
+        type MinRelayerBond = ConstU64<10>;
+        type PalletId = IbcPalletId;
+        type WeightInfo = ();
+    }
+
+    /// Transfers and remarks, like the runtime's transfers, bonds and oracle requests
+    pub struct TransfersAndRemarks;
+    impl Contains<RuntimeCall> for TransfersAndRemarks {
+        fn contains(call: &RuntimeCall) -> bool {
+            matches!(
+                call,
+                RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death { .. }) |
+                    RuntimeCall::System(frame_system::Call::remark { .. })
+            )
+        }
+    }
+
+    impl Config for Test {
+        type RuntimeEvent = RuntimeEvent;
+        type RuntimeCall = RuntimeCall;
+        type AllowedCalls = TransfersAndRemarks;
+        type FilterOrigin = EnsureRoot<u64>;
+        type MaxCallsPerPacket = ConstU32<4>;
+        type MaxFilterEntries = ConstU32<4>;
+        type MaxPacketWeight = MaxPacketWeight;
+        type PalletId = IcaPalletId;
+        type WeightInfo = ();
+    }
+
+    const RELAYER: u64 = 1;
+    const CONTROLLER: &[u8] = b"icacontroller-alice";
+
+    fn id(bytes: &[u8]) -> BoundedVec<u8, pallet_ibc_core::MaxIdentifierLen> {
+        let bytes = [pallet_ibc_core::CLIENT_PREFIX, pallet_ibc_core::CONNECTION_PREFIX, pallet_ibc_core::CHANNEL_PREFIX]
+            .iter()
+            .find_map(|prefix| pallet_ibc_core::parse_display_id(bytes, prefix))
+            .unwrap_or_else(|| bytes.to_vec());
+        BoundedVec::truncate_from(bytes)
+    }
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        pallet_balances::GenesisConfig::<Test> { balances: vec![(RELAYER, 1_000)], ..Default::default() }
+            .assimilate_storage(&mut t)
+            .unwrap();
+
+        let mut ext: sp_io::TestExternalities = t.into();
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    /// Create a client and an open connection to the controller chain
+    fn open_connection() -> ConnectionId {
+        assert_ok!(IbcCore::create_client(
+            RuntimeOrigin::root(),
+            pallet_ibc_core::ClientType::Mock,
+            b"controller".to_vec(),
+            1,
+            67,
+            1_000_000,
+        ));
+        assert_ok!(IbcCore::connection_open_init(
+            RuntimeOrigin::signed(RELAYER),
+            id(b"client-0"),
+            id(b"07-tendermint-0"),
+            b"1".to_vec(),
+        ));
+
+        // The handshake is completed by the counterparty
+        let connection_id = id(b"connection-0");
+        pallet_ibc_core::Connections::<Test>::mutate(&connection_id, |connection| {
+            connection.as_mut().unwrap().state = ConnectionState::Open;
+        });
+        connection_id
+    }
+
+    /// Update the controller chain's client to a root committing to `value` under `path` only,
+    /// returning the proof height
+    fn commit(path: &[u8], value: H256) -> u64 {
+        let height = IbcCore::clients(id(b"client-0")).unwrap().latest_height + 1;
+        let root = pallet_ibc_core::membership_leaf(path, &value);
+        assert_ok!(IbcCore::update_client(
+            RuntimeOrigin::root(),
+            id(b"client-0"),
+            MockHeader { height, root }.encode(),
+        ));
+        height
+    }
+
+    /// Controller channel end `channel-7` starting a handshake from `port` with `version`
+    fn controller_channel(port: &[u8], version: &[u8]) -> ChannelEnd {
+        ChannelEnd {
+            state: ChannelState::Init,
+            connection_id: id(b"connection-0"),
+            port_id: id(port),
+            counterparty_port_id: id(HOST_PORT),
+            version: BoundedVec::truncate_from(version.to_vec()),
+            ordering: ChannelOrder::Ordered,
+            upgrade_sequence: 0,
+            next_sequence_send: 1,
+            next_sequence_recv: 1,
+            next_sequence_ack: 1,
+        }
+    }
+
+    /// Answer the controller's `ChanOpenInit` from `counterparty`, proving it under a new
+    /// consensus state first
+    fn open_proven(connection_id: &ConnectionId, counterparty: ChannelEnd) -> DispatchResult {
+        let path = pallet_ibc_core::channel_end_path(&counterparty.port_id, b"channel-7");
+        let proof_height = commit(&path, BlakeTwo256::hash_of(&counterparty));
+        InterchainAccounts::open_host_channel(
+            RuntimeOrigin::signed(RELAYER),
+            connection_id.clone(),
+            id(b"channel-7"),
+            counterparty,
+            Vec::new(),
+            proof_height,
+        )
+    }
+
+    /// Open the controller's host channel and fund its interchain account with `balance`
+    fn open_host_channel(balance: u64) -> (ChannelId, u64) {
+        let connection_id = open_connection();
+        assert_ok!(open_proven(&connection_id, controller_channel(CONTROLLER, VERSION)));
+
+        let account = InterchainAccounts::interchain_account(&connection_id, &id(CONTROLLER));
+        assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), account, balance));
+        (id(b"channel-0"), account)
+    }
+
+    /// Relay a packet carrying `calls` and return the host's acknowledgement
+    fn deliver(channel_id: &ChannelId, sequence: u64, calls: Vec<RuntimeCall>) -> Acknowledgement {
+        let packet = Packet {
+            sequence,
+            source_port: id(CONTROLLER),
+            source_channel: id(b"channel-7"),
+            destination_port: id(HOST_PORT),
+            destination_channel: channel_id.clone(),
+            data: BoundedVec::truncate_from(InterchainAccountPacketData { calls, memo: Vec::new() }.encode()),
+            timeout_height: 0,
+            timeout_timestamp: 0,
+        };
+        let path = pallet_ibc_core::commitment_path(CONTROLLER, b"channel-7", sequence);
+        let proof_height = commit(&path, BlakeTwo256::hash_of(&packet));
+        assert_ok!(IbcCore::recv_packet(RuntimeOrigin::signed(RELAYER), packet, Vec::new(), proof_height));
+
+        System::events()
+            .into_iter()
+            .rev()
+            .find_map(|record| match record.event {
+                RuntimeEvent::IbcCore(pallet_ibc_core::Event::PacketReceived { acknowledgement, .. }) =>
+                    Some(acknowledgement),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn transfer(dest: u64, value: u64) -> RuntimeCall {
+        RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death { dest, value })
+    }
+
+    fn remark() -> RuntimeCall {
+        RuntimeCall::System(frame_system::Call::remark { remark: b"hello".to_vec() })
+    }
+
+    #[test]
+    fn controllers_open_one_host_channel_per_connection() {
+        new_test_ext().execute_with(|| {
+            let connection_id = open_connection();
+
+            assert_err!(
+                open_proven(&connection_id, controller_channel(b"transfer", VERSION)),
+                Error::<Test>::InvalidControllerPort
+            );
+            assert_err!(
+                open_proven(&connection_id, controller_channel(CONTROLLER, b"ics20-1")),
+                Error::<Test>::InvalidVersion
+            );
+            let mut open = controller_channel(CONTROLLER, VERSION);
+            open.state = ChannelState::Open;
+            assert_err!(open_proven(&connection_id, open), Error::<Test>::InvalidCounterpartyChannel);
+
+            // The controller's channel end must be proven
+            let proof_height = commit(b"unrelated", H256::repeat_byte(1));
+            assert_noop!(
+                InterchainAccounts::open_host_channel(
+                    RuntimeOrigin::signed(RELAYER),
+                    connection_id.clone(),
+                    id(b"channel-7"),
+                    controller_channel(CONTROLLER, VERSION),
+                    Vec::new(),
+                    proof_height,
+                ),
+                pallet_ibc_core::Error::<Test>::InvalidProof
+            );
+
+            assert_ok!(open_proven(&connection_id, controller_channel(CONTROLLER, VERSION)));
+
+            let channel_id = id(b"channel-0");
+            let account = InterchainAccounts::interchain_account(&connection_id, &id(CONTROLLER));
+            System::assert_has_event(
+                Event::InterchainAccountRegistered {
+                    channel_id: channel_id.clone(),
+                    connection_id: connection_id.clone(),
+                    controller_port: id(CONTROLLER),
+                    account,
+                }
+                .into(),
+            );
+            assert_eq!(IbcCore::port_owner(id(HOST_PORT)), Some(InterchainAccounts::account_id()));
+            assert_eq!(IbcCore::channels(id(HOST_PORT), &channel_id).unwrap().state, ChannelState::Open);
+            assert_eq!(InterchainAccounts::active_channel(&connection_id, id(CONTROLLER)), Some(channel_id));
+
+            // Each owner has its own account, and only one channel to it
+            assert_ne!(account, InterchainAccounts::interchain_account(&connection_id, &id(b"icacontroller-bob")));
+            assert_err!(
+                open_proven(&connection_id, controller_channel(CONTROLLER, VERSION)),
+                Error::<Test>::ChannelAlreadyActive
+            );
+        });
+    }
+
+    #[test]
+    fn packets_execute_allowed_calls_from_the_interchain_account() {
+        new_test_ext().execute_with(|| {
+            let (channel_id, account) = open_host_channel(500);
+
+            let ack = deliver(&channel_id, 1, vec![transfer(7, 100), remark()]);
+
+            assert_eq!(ack, Ok(2u32.encode()));
+            assert_eq!(Balances::free_balance(account), 400);
+            assert_eq!(Balances::free_balance(7), 100);
+            System::assert_has_event(Event::PacketExecuted { channel_id, account, calls: 2 }.into());
+        });
+    }
+
+    #[test]
+    fn packets_with_filtered_calls_are_rejected() {
+        new_test_ext().execute_with(|| {
+            let (channel_id, account) = open_host_channel(500);
+
+            // Not admitted by `AllowedCalls`
+            let force = RuntimeCall::Balances(pallet_balances::Call::force_set_balance { who: 7, new_free: 100 });
+            assert_eq!(deliver(&channel_id, 1, vec![remark(), force]), Err(b"call not allowed".to_vec()));
+            System::assert_has_event(
+                Event::PacketFailed { channel_id: channel_id.clone(), error: b"call not allowed".to_vec() }.into(),
+            );
+
+            // Narrowed to remarks on this channel
+            let remark_index = remark().using_encoded(|encoded| (encoded[0], encoded[1]));
+            assert_noop!(
+                InterchainAccounts::set_channel_filter(
+                    RuntimeOrigin::signed(RELAYER),
+                    channel_id.clone(),
+                    BoundedVec::truncate_from(vec![remark_index]),
+                ),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(InterchainAccounts::set_channel_filter(
+                RuntimeOrigin::root(),
+                channel_id.clone(),
+                BoundedVec::truncate_from(vec![remark_index]),
+            ));
+            assert_eq!(deliver(&channel_id, 2, vec![transfer(7, 100)]), Err(b"call not allowed".to_vec()));
+            assert_eq!(deliver(&channel_id, 3, vec![remark()]), Ok(1u32.encode()));
+
+            assert_ok!(InterchainAccounts::clear_channel_filter(RuntimeOrigin::root(), channel_id.clone()));
+            assert_eq!(deliver(&channel_id, 4, vec![transfer(7, 100)]), Ok(1u32.encode()));
+            assert_eq!(Balances::free_balance(account), 400);
+
+            // Bounded number of calls
+            assert_eq!(deliver(&channel_id, 5, vec![remark(); 5]), Err(b"too many calls".to_vec()));
+        });
+    }
+
+    #[test]
+    fn failing_calls_revert_the_whole_packet() {
+        new_test_ext().execute_with(|| {
+            let (channel_id, account) = open_host_channel(500);
+
+            let ack = deliver(&channel_id, 1, vec![transfer(7, 100), transfer(8, 1_000)]);
+
+            assert!(ack.is_err());
+            assert_eq!(Balances::free_balance(account), 500);
+            assert_eq!(Balances::free_balance(7), 0);
+
+            // The ordered channel still moves on
+            assert_eq!(deliver(&channel_id, 2, vec![transfer(7, 100)]), Ok(1u32.encode()));
+        });
+    }
+
+    #[test]
+    fn packets_on_other_ports_get_an_empty_result() {
+        new_test_ext().execute_with(|| {
+            let packet = Packet {
+                sequence: 1,
+                source_port: id(b"transfer"),
+                source_channel: id(b"channel-7"),
+                destination_port: id(b"transfer"),
+                destination_channel: id(b"channel-0"),
+                data: BoundedVec::truncate_from(b"not calls".to_vec()),
+                timeout_height: 0,
+                timeout_timestamp: 0,
+            };
+
+            assert_eq!(InterchainAccounts::on_recv_packet(&packet, &RELAYER), Ok(Vec::new()));
+        });
+    }
+}
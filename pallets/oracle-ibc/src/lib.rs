@@ -0,0 +1,314 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Oracle IBC Pallet
+//!
+//! IBC application making Netchain an oracle hub for other chains.
+//!
+//! Governance opens feed channels on the `oracle` port with `open_feed_channel`, recording the
+//! counterparty's end of the channel. Over a feed channel:
+//! - `publish_feed_over_ibc` sends the latest aggregate of a local data key as an
+//!   [`OraclePacketData`] packet, which times out `PacketTimeout` blocks past the counterparty
+//!   height the channel's client last saw
+//! - received packets are stored by the oracle under `remote/<chain>/<key>`
+//!   (`pallet_oracle::remote_data_key`), `<chain>` being the chain identifier of the channel's
+//!   client, so other chains' values are read like local feeds but never shadow them
+//!
+//! Feeds only arrive through `recv_packet`, which proves the counterparty committed to the
+//! packet, and are only accepted from the counterparty port and channel recorded for the feed
+//! channel.
+//!
+//! Packets on other ports are left to the runtime's other IBC applications.
+
+pub use pallet::*;
+
+use codec::DecodeAll;
+use frame_support::{pallet_prelude::*, PalletId};
+use frame_system::pallet_prelude::*;
+use pallet_ibc_core::{
+    is_valid_identifier, Acknowledgement, ChannelId, ChannelState, ClientState, ConnectionId, IbcRouter, Packet,
+    PortId,
+};
+use pallet_oracle::{DataKey, DataValue};
+use sp_runtime::{traits::AccountIdConversion, SaturatedConversion};
+use sp_std::vec::Vec;
+
+/// Port feed channels are opened on
+pub use netchain_primitives::ORACLE_PORT;
+/// Application version negotiated on feed channels
+pub const VERSION: &[u8] = b"netchain-oracle-1";
+
+/// Data of a feed packet: one aggregated value of the sending chain
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct OraclePacketData {
+    /// Data key on the sending chain
+    pub data_key: DataKey,
+    /// Aggregated value
+    pub value: DataValue,
+    /// Number of sources the value was aggregated from
+    pub source_count: u32,
+    /// Average confidence score of the sources
+    pub confidence: u8,
+}
+
+/// Local end of a feed channel and the chain at the other end
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct FeedChannel {
+    /// Connection to the counterparty chain
+    pub connection_id: ConnectionId,
+    /// Port of the channel on the counterparty
+    pub counterparty_port: PortId,
+    /// Channel identifier on the counterparty
+    pub counterparty_channel: ChannelId,
+    /// Chain identifier of the connection's client, the `<chain>` of the keys received
+    pub chain_id: Vec<u8>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_oracle::Config + pallet_ibc_core::Config {
+        /// The overarching event type
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Origin allowed to open feed channels
+        type ChannelOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Counterparty blocks past the height its client last recorded within which a feed
+        /// packet must be received
+        #[pallet::constant]
+        type PacketTimeout: Get<u64>;
+
+        /// Identifier of the account owning the oracle port
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// Weight information for extrinsics
+        type WeightInfo: WeightInfo;
+    }
+
+    /// Feed channels, keyed by local channel
+    #[pallet::storage]
+    #[pallet::getter(fn feed_channels)]
+    pub type FeedChannels<T: Config> = StorageMap<_, Blake2_128Concat, ChannelId, FeedChannel>;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        #[serde(skip)]
+        pub _config: core::marker::PhantomData<T>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            // Reserve the oracle port before anyone else can bind it
+            pallet_ibc_core::Pallet::<T>::bind_port_to(Pallet::<T>::oracle_port(), Pallet::<T>::account_id())
+                .expect("oracle port is free at genesis; qed");
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A feed channel to `chain_id` was opened
+        FeedChannelOpened { channel_id: ChannelId, connection_id: ConnectionId, chain_id: Vec<u8> },
+        /// The latest aggregate of a data key was sent over a feed channel
+        FeedPublished { data_key: DataKey, channel_id: ChannelId, sequence: u64 },
+        /// A value received over a feed channel was stored under `data_key`
+        RemoteFeedReceived { channel_id: ChannelId, data_key: DataKey },
+        /// A packet received on the oracle port was rejected
+        FeedRejected { channel_id: ChannelId, error: Vec<u8> },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Channel is not a feed channel
+        UnknownChannel,
+        /// The data key has no aggregated value to publish
+        NoAggregate,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Open a feed channel on the `oracle` port to `counterparty_channel` on
+        /// `counterparty_port`, over the open `connection_id`
+        ///
+        /// The channel is opened directly and immediately usable in both directions.
+        #[pallet::call_index(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::open_feed_channel())]
+        pub fn open_feed_channel(
+            origin: OriginFor<T>,
+            connection_id: ConnectionId,
+            counterparty_port: PortId,
+            counterparty_channel: ChannelId,
+        ) -> DispatchResult {
+            T::ChannelOrigin::ensure_origin(origin)?;
+            ensure!(
+                is_valid_identifier(&counterparty_channel),
+                pallet_ibc_core::Error::<T>::InvalidIdentifier
+            );
+            let chain_id = Self::client_of(&connection_id)?.chain_id.into_inner();
+
+            // Chains that added the pallet after genesis bind the oracle port on first use
+            let port = Self::oracle_port();
+            let owner = Self::account_id();
+            if pallet_ibc_core::PortOwners::<T>::contains_key(&port) {
+                pallet_ibc_core::Pallet::<T>::ensure_port_owner(&port, &owner)?;
+            } else {
+                pallet_ibc_core::Pallet::<T>::bind_port_to(port.clone(), owner)?;
+            }
+
+            let channel_id = pallet_ibc_core::Pallet::<T>::open_channel(
+                port,
+                connection_id.clone(),
+                counterparty_port.clone(),
+                VERSION.to_vec(),
+                ChannelState::Open,
+            )?;
+            <FeedChannels<T>>::insert(
+                &channel_id,
+                FeedChannel {
+                    connection_id: connection_id.clone(),
+                    counterparty_port,
+                    counterparty_channel,
+                    chain_id: chain_id.clone(),
+                },
+            );
+
+            Self::deposit_event(Event::FeedChannelOpened { channel_id, connection_id, chain_id });
+
+            Ok(())
+        }
+
+        /// Send the latest aggregated value of `data_key` over the feed channel `channel_id`
+        ///
+        /// The caller pays the packet transmission fee.
+        #[pallet::call_index(1)]
+        #[pallet::weight(<T as Config>::WeightInfo::publish_feed_over_ibc())]
+        pub fn publish_feed_over_ibc(origin: OriginFor<T>, data_key: DataKey, channel_id: ChannelId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let channel = <FeedChannels<T>>::get(&channel_id).ok_or(Error::<T>::UnknownChannel)?;
+            let aggregate =
+                pallet_oracle::AggregatedDataStorage::<T>::get(&data_key).ok_or(Error::<T>::NoAggregate)?;
+            let timeout_height = Self::counterparty_height(&channel.connection_id)?.saturating_add(T::PacketTimeout::get());
+
+            let data = OraclePacketData {
+                data_key: data_key.clone(),
+                value: aggregate.value,
+                source_count: aggregate.source_count,
+                confidence: aggregate.confidence,
+            };
+            let sequence = pallet_ibc_core::Pallet::<T>::send_packet_from(
+                &who,
+                Self::oracle_port(),
+                channel_id.clone(),
+                channel.counterparty_port,
+                channel.counterparty_channel,
+                data.encode(),
+                timeout_height,
+                0,
+            )?;
+
+            Self::deposit_event(Event::FeedPublished { data_key, channel_id, sequence });
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Account owning the oracle port
+        pub fn account_id() -> T::AccountId {
+            <T as Config>::PalletId::get().into_account_truncating()
+        }
+
+        /// The `oracle` port identifier
+        pub fn oracle_port() -> PortId {
+            PortId::truncate_from(ORACLE_PORT.to_vec())
+        }
+
+        /// Client of the connection `connection_id`
+        fn client_of(connection_id: &ConnectionId) -> Result<ClientState, DispatchError> {
+            let connection = pallet_ibc_core::Connections::<T>::get(connection_id)
+                .ok_or(pallet_ibc_core::Error::<T>::ConnectionNotFound)?;
+            pallet_ibc_core::Clients::<T>::get(&connection.client_id)
+                .ok_or_else(|| pallet_ibc_core::Error::<T>::ClientNotFound.into())
+        }
+
+        /// Latest known height of the chain at the other end of `connection_id`
+        ///
+        /// The localhost client is never updated, so over it this chain's own height is used.
+        fn counterparty_height(connection_id: &ConnectionId) -> Result<u64, DispatchError> {
+            if pallet_ibc_core::Pallet::<T>::is_localhost(connection_id) {
+                return Ok(frame_system::Pallet::<T>::block_number().saturated_into());
+            }
+            Ok(Self::client_of(connection_id)?.latest_height)
+        }
+
+        /// Store the value carried by `packet`, received over a feed channel
+        ///
+        /// Returns the local key it was stored under.
+        fn receive_feed(packet: &Packet) -> Result<DataKey, &'static str> {
+            let channel_id = &packet.destination_channel;
+            let channel = <FeedChannels<T>>::get(channel_id).ok_or("unknown feed channel")?;
+            if packet.source_port != channel.counterparty_port || packet.source_channel != channel.counterparty_channel {
+                return Err("unexpected counterparty channel");
+            }
+            let packet = OraclePacketData::decode_all(&mut &packet.data[..]).map_err(|_| "undecodable packet data")?;
+
+            let data_key = pallet_oracle::Pallet::<T>::publish_remote(
+                &channel.chain_id,
+                &packet.data_key,
+                packet.value,
+                packet.source_count,
+                packet.confidence,
+            )
+            .map_err(|e: DispatchError| -> &'static str { e.into() })?;
+
+            Self::deposit_event(Event::RemoteFeedReceived { channel_id: channel_id.clone(), data_key: data_key.clone() });
+
+            Ok(data_key)
+        }
+    }
+}
+
+/// Packets on the oracle port are stored as remote feeds and acknowledged with the key they
+/// were stored under; every other port keeps the default empty result
+impl<T: Config> IbcRouter<T::AccountId> for Pallet<T> {
+    fn on_recv_packet(packet: &Packet, _relayer: &T::AccountId) -> Acknowledgement {
+        if packet.destination_port.as_slice() != ORACLE_PORT {
+            return Ok(Vec::new());
+        }
+
+        Self::receive_feed(packet).map_err(|error| {
+            Self::deposit_event(Event::FeedRejected {
+                channel_id: packet.destination_channel.clone(),
+                error: error.as_bytes().to_vec(),
+            });
+            error.as_bytes().to_vec()
+        })
+    }
+
+    fn max_recv_packet_weight() -> Weight {
+        <T as Config>::WeightInfo::receive_feed()
+    }
+}
+
+/// Weight functions needed for benchmarking
+pub trait WeightInfo {
+    fn open_feed_channel() -> Weight;
+    fn publish_feed_over_ibc() -> Weight;
+    fn receive_feed() -> Weight;
+}
+
+/// Default weights (based on complexity analysis)
+impl WeightInfo for () {
+    fn open_feed_channel() -> Weight { Weight::from_parts(60_000, 0) }
+    fn publish_feed_over_ibc() -> Weight { Weight::from_parts(90_000, 0) }
+    fn receive_feed() -> Weight { Weight::from_parts(70_000, 0) }
+}
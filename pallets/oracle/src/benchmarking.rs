@@ -0,0 +1,157 @@
+//! Benchmarking setup for pallet-oracle
+//!
+//! Requests are premium, so each one also opens an SLA, and submissions land on a key
+//! that already holds `s` other sources' values for aggregation to read back. Signed rounds
+//! need a keystore to generate their signers' keys in.
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Oracle;
+use frame_benchmarking::v2::*;
+use frame_support::traits::Currency;
+use frame_system::RawOrigin;
+use sp_std::vec;
+
+const KEY: &[u8] = b"NET/USD";
+
+/// Key type the round signers' keys are generated under
+const KEY_TYPE: sp_core::crypto::KeyTypeId = sp_core::crypto::KeyTypeId(*b"orcl");
+
+/// Source identifier `i`
+fn source(i: u32) -> SourceId {
+    [b"source-".as_slice(), &i.to_le_bytes()].concat()
+}
+
+/// A funded caller, with the pallet account kept alive to receive fees and pay rewards
+fn funded_caller<T: Config>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    let balance = T::Currency::minimum_balance().saturating_mul(1_000_000u32.into());
+    T::Currency::make_free_balance_be(&caller, balance);
+    T::Currency::make_free_balance_be(&Oracle::<T>::account_id(), balance);
+    caller
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn request_data(s: Linear<0, { T::MaxDataSources::get() }>) {
+        let caller = funded_caller::<T>();
+        let sources: Vec<SourceId> = (0..s).map(source).collect();
+
+        #[extrinsic_call]
+        request_data(RawOrigin::Signed(caller), KEY.to_vec(), sources, true, None);
+
+        assert!(OracleRequests::<T>::contains_key(0));
+    }
+
+    #[benchmark]
+    fn provide_data(
+        d: Linear<0, { T::MaxDataSize::get() }>,
+        s: Linear<0, { T::MaxDataSources::get() - 1 }>,
+    ) {
+        let caller = funded_caller::<T>();
+        let submitter = source(s);
+        DataSources::<T>::insert(&submitter, DataSource {
+            id: submitter.clone(),
+            name: b"bench".to_vec(),
+            endpoint: Vec::new(),
+            reliability: 100,
+            active: true,
+            tier: SourceTier::Open,
+        });
+        for i in 0..s {
+            OracleDataStorage::<T>::insert(KEY.to_vec(), source(i), OracleData {
+                value: vec![1u8; d as usize],
+                provider: caller.clone(),
+                timestamp: frame_system::Pallet::<T>::block_number(),
+                source: source(i),
+                confidence: 50,
+                signature: None,
+            });
+        }
+
+        #[extrinsic_call]
+        provide_data(RawOrigin::Signed(caller), KEY.to_vec(), submitter.clone(), vec![1u8; d as usize], 50, None);
+
+        assert!(OracleDataStorage::<T>::contains_key(KEY.to_vec(), submitter));
+    }
+
+    #[benchmark]
+    fn provide_data_batch(
+        n: Linear<1, { T::MaxBatchSubmissions::get() }>,
+        d: Linear<0, { T::MaxDataSize::get() }>,
+        s: Linear<0, { T::MaxDataSources::get() - 1 }>,
+    ) {
+        let caller = funded_caller::<T>();
+        let submitter = source(s);
+        DataSources::<T>::insert(&submitter, DataSource {
+            id: submitter.clone(),
+            name: b"bench".to_vec(),
+            endpoint: Vec::new(),
+            reliability: 100,
+            active: true,
+            tier: SourceTier::Open,
+        });
+        let keys: Vec<DataKey> = (0..n).map(|k| [KEY, &k.to_le_bytes()].concat()).collect();
+        for key in &keys {
+            for i in 0..s {
+                OracleDataStorage::<T>::insert(key, source(i), OracleData {
+                    value: vec![1u8; d as usize],
+                    provider: caller.clone(),
+                    timestamp: frame_system::Pallet::<T>::block_number(),
+                    source: source(i),
+                    confidence: 50,
+                    signature: None,
+                });
+            }
+        }
+        let entries = keys.iter().map(|key| (key.clone(), vec![1u8; d as usize], 50)).collect::<Vec<_>>();
+
+        #[extrinsic_call]
+        provide_data_batch(RawOrigin::Signed(caller), submitter.clone(), entries);
+
+        assert!(keys.iter().all(|key| OracleDataStorage::<T>::contains_key(key, &submitter)));
+    }
+
+    #[benchmark]
+    fn submit_signed_round(
+        n: Linear<{ T::MinAggregationSources::get() }, { T::MaxRoundSigners::get() }>,
+        d: Linear<0, { T::MaxDataSize::get() }>,
+    ) {
+        let caller = funded_caller::<T>();
+        let value = vec![1u8; d as usize];
+        let round = Oracle::<T>::current_round();
+        let payload = Oracle::<T>::round_payload(&KEY.to_vec(), round, &value, 90);
+        let signatures = (0..n)
+            .map(|i| {
+                let signer: T::AccountId = account("signer", i, 0);
+                let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+                let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &key, &payload).expect("key was just generated; qed");
+                TrustedProviders::<T>::insert(&signer, 90);
+                SigningKeys::<T>::insert(&signer, key);
+                (signer, signature)
+            })
+            .collect::<Vec<_>>();
+
+        #[extrinsic_call]
+        submit_signed_round(RawOrigin::Signed(caller), KEY.to_vec(), round, value, 90, signatures);
+
+        assert_eq!(LastSignedRounds::<T>::get(KEY.to_vec()), Some(round));
+    }
+
+    #[benchmark]
+    fn batch_requests(    #[benchmark]
+    fn batch_requests(n: Linear<1, 100>, s: Linear<0, { T::MaxDataSources::get() }>) {
+        let caller = funded_caller::<T>();
+        let sources: Vec<SourceId> = (0..s).map(source).collect();
+        let requests = (0..n).map(|_| (KEY.to_vec(), sources.clone(), true)).collect::<Vec<_>>();
+
+        #[extrinsic_call]
+        batch_requests(RawOrigin::Signed(caller), requests);
+
+        assert_eq!(NextRequestId::<T>::get(), n as RequestId);
+    }
+}
@@ -11,6 +11,47 @@
 //! - Ultra-low fees for oracle queries
 //! - Data validation and aggregation
 //! - Request batching for efficiency
+//! - A bounded history of recent aggregated values per key, for TWAP and volatility; every
+//!   value is also mirrored into the node's off-chain database (see
+//!   [`netchain_primitives::offchain_history`]), where it outlives the on-chain ring buffer
+//! - Registered feeds with typed values (fixed-point numbers, booleans, bytes) and decimals,
+//!   unit and category metadata; submissions to a registered feed must be SCALE-encoded
+//!   [`TypedValue`]s of its kind, while unregistered keys keep accepting opaque bytes
+//! - [`PriceProvider`]: the price of a registered numeric feed, for pallets to code against
+//! - Aggregation strategies per data key ([`AggregationStrategy`]): median, mean or
+//!   reliability-weighted mean for numeric feeds, majority vote or latest value for any key
+//! - Committee mode: per round, only a committee of trusted providers drawn from BABE
+//!   randomness may submit for a key, so colluding providers cannot count on being selected
+//! - Signed rounds: trusted providers sign a round's value off-chain with their registered
+//!   sr25519 keys and one aggregator submits it with all their signatures
+//!   (`submit_signed_round`), one transaction per round instead of one per provider
+//! - Premium SLAs: a premium request must be answered by `PremiumMinSources` of its sources
+//!   within `PremiumMaxLatency` blocks; otherwise the premium over the basic fee is refunded
+//!   and the assigned sources that did not deliver lose reliability
+//! - Stale-feed watchdog: feeds the runtime depends on are checked every block; one without
+//!   an aggregate younger than `MaxDataAge` is flagged with [`Event::FeedStale`], prices
+//!   nothing until it aggregates again, and its committee's provider bonds are slashed
+//! - Remote feeds: aggregates other chains publish to Netchain are stored under
+//!   `remote/<chain>/<key>` ([`remote_data_key`]) by [`Pallet::publish_remote`], a namespace
+//!   local providers cannot submit into
+//! - Critical feeds: `provide_critical_data` for keys the runtime prices with dispatches as
+//!   `Operational` within a per-block `CriticalFeedWeight`, so the feeds keep updating in
+//!   blocks full of normal transactions
+//! - Source tiers ([`SourceTier`]): every source is `Open`, `Verified` or `Critical`, and each
+//!   tier sets the reputation as a trusted provider and the bond a provider needs to submit
+//!   through its sources, and the multiplier applied to their rewards ([`TierRequirements`]);
+//!   the runtime configures the requirements, governance can override them
+//! - Request quotas: an account may make `MaxRequestsPerEra` requests per `RequestEraLength`
+//!   blocks unless its provider bond reaches `QuotaExemptBond`, and every request is pruned
+//!   `RequestTtl` blocks after it was made, so `OracleRequests` stays bounded
+//! - Finality delay: an aggregate becomes readable `MinAggregationDelay` blocks after its
+//!   round closed, leaving a window in which governance can discard it
+//!   (`dispute_aggregate`); [`AggregatedData`] carries the blocks it
+//!   is readable from (`valid_from`) and fresh until (`valid_until`, `MaxDataAge` after
+//!   aggregation), so consumers can apply their own freshness policies
+//!
+//! Aggregation, the watchdog and SLA settlement log to the [`LOG_TARGET`] target, naming the
+//! data key or request; aggregation runs in an `aggregate` tracing span.
 //!
 //! ## Security Features
 //! - Multiple data source validation
@@ -21,35 +62,81 @@
 
 pub use pallet::*;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::WeightInfo;
+pub mod migrations;
+
 use frame_support::{
     dispatch::{DispatchResult, DispatchResultWithPostInfo},
     pallet_prelude::*,
-    traits::{Get, ReservableCurrency, ExistenceRequirement},
+    traits::{Get, Randomness, ReservableCurrency, ExistenceRequirement},
     PalletId,
 };
 use frame_system::pallet_prelude::*;
-use sp_std::{vec::Vec, collections::btree_map::BTreeMap};
+use sp_std::{vec::Vec, collections::{btree_map::BTreeMap, btree_set::BTreeSet}};
 use sp_runtime::{
-    traits::{BlakeTwo256, Hash, Saturating, Zero, AccountIdConversion},
-    SaturatedConversion,
+    traits::{BlakeTwo256, Hash, Saturating, TrailingZeroInput, Zero, AccountIdConversion},
+    FixedPointNumber, FixedU128, SaturatedConversion,
 };
-use sp_core::H256;
+use codec::DecodeAll;
+use sp_core::{sr25519, H256};
+use pallet_history::{HistoryKind, HistoryRecorder};
+use pallet_safeguard::{PauseGuard, Subsystem};
+use netchain_primitives::offchain_history::{self, Indexed, Table};
 
 /// Oracle request identifier
 pub type RequestId = u64;
 /// Data source identifier  
 pub type SourceId = Vec<u8>;
-/// Oracle data key (e.g., "BTC/USD", "weather/london")
-pub type DataKey = Vec<u8>;
-/// Oracle data value (JSON string or encoded data)  
-pub type DataValue = Vec<u8>;
+/// Oracle data key (e.g., "BTC/USD", "weather/london") and value (JSON string or encoded data)
+pub use netchain_primitives::{DataKey, DataValue};
+use netchain_primitives::Printable;
+
+/// Target of the pallet's log lines
+pub const LOG_TARGET: &str = "runtime::oracle";
+
+/// Context prefixed to the round values providers sign, see [`Pallet::round_payload`]
+pub const ROUND_SIGNING_CONTEXT: &[u8] = b"netchain/oracle-round";
+
+/// Oracle round index (block number divided by the round length)
+pub type RoundIndex = u64;
+
+/// Decimal places a `FixedU128` can represent
+pub const MAX_FEED_DECIMALS: u8 = 18;
+
+/// Prefix of the data keys holding values received from other chains
+pub const REMOTE_KEY_PREFIX: &[u8] = b"remote/";
+
+/// Data key `data_key` of chain `chain_id` is stored under: `remote/<chain>/<key>`
+pub fn remote_data_key(chain_id: &[u8], data_key: &[u8]) -> DataKey {
+    [REMOTE_KEY_PREFIX, chain_id, b"/", data_key].concat()
+}
+
+/// Source of asset prices for other pallets
+pub trait PriceProvider<BlockNumber> {
+    /// Latest aggregated price of one whole unit of `asset` and the block it was aggregated
+    /// at, `None` once past its `valid_until`
+    fn price(asset: &DataKey) -> Option<(FixedU128, BlockNumber)>;
+}
+
+impl<BlockNumber> PriceProvider<BlockNumber> for () {
+    fn price(_asset: &DataKey) -> Option<(FixedU128, BlockNumber)> {
+        None
+    }
+}
 
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
 
+    /// The in-code storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
     #[pallet::pallet]
     #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::config]
@@ -68,6 +155,10 @@ pub mod pallet {
         #[pallet::constant]
         type MaxDataSize: Get<u32>;
 
+        /// Maximum number of entries in one `provide_data_batch` call
+        #[pallet::constant]
+        type MaxBatchSubmissions: Get<u32>;
+
         /// Fee for basic oracle query (ultra-low)
         #[pallet::constant]
         type OracleQueryFee: Get<BalanceOf<Self>>;
@@ -88,10 +179,101 @@ pub mod pallet {
         #[pallet::constant]
         type MinAggregationSources: Get<u32>;
 
+        /// Blocks an aggregate waits after its round closed before it becomes readable, the
+        /// window in which `dispute_aggregate` can discard it; zero makes aggregates readable
+        /// at once
+        #[pallet::constant]
+        type MinAggregationDelay: Get<BlockNumberFor<Self>>;
+
         /// Pallet identifier for account derivation
         #[pallet::constant]
         type PalletId: Get<PalletId>;
 
+        /// Index of oracle updates for explorer queries
+        type History: HistoryRecorder<Self::AccountId>;
+
+        /// Emergency pause switch; while `Subsystem::Oracle` is paused submissions are rejected
+        type Safeguard: PauseGuard;
+
+        /// Aggregated values kept per data key before the oldest is overwritten
+        #[pallet::constant]
+        type HistoryDepth: Get<u32>;
+
+        /// Source of randomness for committee selection
+        type Randomness: Randomness<Self::Hash, BlockNumberFor<Self>>;
+
+        /// Providers selected per key and round in committee mode
+        #[pallet::constant]
+        type CommitteeSize: Get<u32>;
+
+        /// Trusted providers governance may register, which bounds the candidates a
+        /// committee is drawn from
+        #[pallet::constant]
+        type MaxTrustedProviders: Get<u32>;
+
+        /// Length of an oracle round in blocks; committees rotate every round
+        #[pallet::constant]
+        type RoundLength: Get<BlockNumberFor<Self>>;
+
+        /// Assigned sources that must deliver for a premium request's SLA to be met
+        #[pallet::constant]
+        type PremiumMinSources: Get<u32>;
+
+        /// Blocks after a premium request within which its sources must deliver
+        #[pallet::constant]
+        type PremiumMaxLatency: Get<BlockNumberFor<Self>>;
+
+        /// Reliability an assigned source loses when it misses a premium SLA
+        #[pallet::constant]
+        type SlaReliabilityPenalty: Get<u8>;
+
+        /// Feeds the watchdog can check for staleness
+        #[pallet::constant]
+        type MaxWatchedFeeds: Get<u32>;
+
+        /// Successful `provide_data` calls from bonded trusted providers refunded per block;
+        /// further ones, and failed ones, pay fees
+        #[pallet::constant]
+        type MaxFeelessSubmissionsPerBlock: Get<u32>;
+
+        /// Provider signatures accepted in one `submit_signed_round` call
+        #[pallet::constant]
+        type MaxRoundSigners: Get<u32>;
+
+        /// Weight per block reserved for `provide_critical_data`, which dispatches as
+        /// `Operational`, outside the normal class's limit
+        #[pallet::constant]
+        type CriticalFeedWeight: Get<Weight>;
+
+        /// Length in blocks of a request era, the period request quotas are counted over
+        #[pallet::constant]
+        type RequestEraLength: Get<BlockNumberFor<Self>>;
+
+        /// Requests an account may make per request era through `request_data` and
+        /// `batch_requests`
+        #[pallet::constant]
+        type MaxRequestsPerEra: Get<u32>;
+
+        /// Blocks a request is kept after it was made before it is pruned
+        #[pallet::constant]
+        type RequestTtl: Get<BlockNumberFor<Self>>;
+
+        /// Provider bond from which an account's requests are not counted against a quota
+        #[pallet::constant]
+        type QuotaExemptBond: Get<BalanceOf<Self>>;
+
+        /// Requirements of `Open` sources unless governance overrides them
+        #[pallet::constant]
+        type OpenTier: Get<TierRequirements<BalanceOf<Self>>>;
+
+        /// Requirements of `Verified` sources unless governance overrides them
+        #[pallet::constant]
+        type VerifiedTier: Get<TierRequirements<BalanceOf<Self>>>;
+
+        /// Requirements of `Critical` sources unless governance overrides them
+        #[pallet::constant]
+        type CriticalTier: Get<TierRequirements<BalanceOf<Self>>>;
+
         /// WeightInfo for benchmarking
         type WeightInfo: WeightInfo;
     }
@@ -115,6 +297,17 @@ pub mod pallet {
         pub callback: Option<Vec<u8>>,
     }
 
+    /// Service level of an open premium request
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    pub struct PremiumSla<BlockNumber> {
+        /// Distinct assigned sources that must deliver
+        pub min_sources: u32,
+        /// Last block a delivery counts towards the SLA
+        pub deadline: BlockNumber,
+        /// Assigned sources that delivered so far
+        pub delivered: Vec<SourceId>,
+    }
+
     /// Oracle data entry with metadata
     #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
     pub struct OracleData<AccountId, BlockNumber> {
@@ -145,6 +338,69 @@ pub mod pallet {
         pub aggregated_at: BlockNumber,
         /// Individual data points used
         pub data_points: Vec<DataValue>,
+        /// First block the value is readable in, `MinAggregationDelay` after aggregation
+        pub valid_from: BlockNumber,
+        /// Last block the value counts as fresh, `MaxDataAge` after aggregation
+        pub valid_until: BlockNumber,
+    }
+
+    /// Aggregated value recorded in a data key's history
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    pub struct Observation<BlockNumber> {
+        /// Aggregated value
+        pub value: DataValue,
+        /// Average confidence score of the sources
+        pub confidence: u8,
+        /// Block the value was aggregated at
+        pub aggregated_at: BlockNumber,
+    }
+
+    /// Typed oracle value, SCALE-encoded into [`DataValue`] for registered feeds
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    pub enum TypedValue {
+        /// Fixed-point number, carrying at most the feed's `decimals`
+        Numeric(FixedU128),
+        /// Boolean outcome (e.g. a sports result)
+        Bool(bool),
+        /// Free-form bytes
+        Bytes(Vec<u8>),
+    }
+
+    /// Kind of value a feed carries
+    #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum ValueKind {
+        /// [`TypedValue::Numeric`]
+        Numeric,
+        /// [`TypedValue::Bool`]
+        Bool,
+        /// [`TypedValue::Bytes`]
+        Bytes,
+    }
+
+    /// Category of a feed
+    #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum FeedCategory {
+        /// Token and asset prices
+        Price,
+        /// Weather observations
+        Weather,
+        /// Sports results
+        Sports,
+        /// Anything else
+        General,
+    }
+
+    /// Metadata registered with a feed
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+    pub struct FeedMetadata {
+        /// Kind of value submissions must carry
+        pub kind: ValueKind,
+        /// Decimal places of numeric values (at most [`MAX_FEED_DECIMALS`])
+        pub decimals: u8,
+        /// Unit of the value (e.g. `USD`, `celsius`)
+        pub unit: Vec<u8>,
+        /// Feed category
+        pub category: FeedCategory,
     }
 
     /// Data source configuration
@@ -160,6 +416,63 @@ pub mod pallet {
         pub reliability: u8,
         /// Whether source is active
         pub active: bool,
+        /// Tier setting who may submit through the source
+        pub tier: SourceTier,
+    }
+
+    /// Tier of a data source, setting who may submit through it and how they are rewarded
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum SourceTier {
+        /// Sources anyone may submit through
+        #[default]
+        Open,
+        /// Sources of established providers
+        Verified,
+        /// Sources the runtime depends on, held to the strictest requirements
+        Critical,
+    }
+
+    /// What a provider needs to submit through the sources of a tier, and what it earns
+    #[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct TierRequirements<Balance> {
+        /// Reputation the provider must hold as a trusted provider; `None` admits any account
+        pub min_reputation: Option<u8>,
+        /// Bond the provider must have reserved with `bond_provider`
+        pub min_bond: Balance,
+        /// Multiplier applied to `OracleReward` for each submission
+        pub reward_multiplier: FixedU128,
+    }
+
+    /// How the values sources submitted for a data key are combined
+    #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum AggregationStrategy {
+        /// Median of the numeric values, the mean of the middle two for an even count
+        Median,
+        /// Mean of the numeric values
+        Mean,
+        /// Value submitted by more than half of the sources; without such a majority nothing
+        /// is aggregated
+        Mode,
+        /// Most recently submitted value
+        Latest,
+        /// Mean of the numeric values weighted by the reliability of their sources
+        WeightedByReliability,
+    }
+
+    impl AggregationStrategy {
+        /// Whether the strategy only applies to numeric feeds
+        pub fn is_numeric(&self) -> bool {
+            matches!(self, Self::Median | Self::Mean | Self::WeightedByReliability)
+        }
+    }
+
+    /// Who may manage a data source: governance, or the owner governance assigned to it
+    #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub enum SourceAuthority {
+        /// Root origin
+        Governance,
+        /// Account set by `set_source_owner`
+        Owner,
     }
 
     /// Storage for oracle requests
@@ -183,21 +496,202 @@ pub mod pallet {
     pub type AggregatedDataStorage<T: Config> = 
         StorageMap<_, Blake2_128Concat, DataKey, AggregatedData<BlockNumberFor<T>>>;
 
+    /// Aggregates waiting out `MinAggregationDelay`, keyed by the block they become readable in
+    #[pallet::storage]
+    #[pallet::getter(fn pending_aggregate)]
+    pub type PendingAggregates<T: Config> = StorageDoubleMap<
+        _, Twox64Concat, BlockNumberFor<T>,
+        Blake2_128Concat, DataKey,
+        AggregatedData<BlockNumberFor<T>>,
+    >;
+
+    /// Ring buffer of recent aggregated values per data key, keyed by slot
+    #[pallet::storage]
+    pub type ObservationHistory<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, DataKey,
+        Twox64Concat, u32,
+        Observation<BlockNumberFor<T>>,
+    >;
+
+    /// Number of aggregated values ever recorded per data key (index of the next one)
+    #[pallet::storage]
+    #[pallet::getter(fn observation_count)]
+    pub type ObservationCount<T: Config> = StorageMap<_, Blake2_128Concat, DataKey, u64, ValueQuery>;
+
+    /// Metadata of registered feeds
+    #[pallet::storage]
+    #[pallet::getter(fn feed_metadata)]
+    pub type Feeds<T: Config> = StorageMap<_, Blake2_128Concat, DataKey, FeedMetadata>;
+
+    /// Aggregation strategy of data keys; keys without one aggregate to the value of the first
+    /// source in storage order
+    #[pallet::storage]
+    #[pallet::getter(fn aggregation_strategy)]
+    pub type AggregationStrategies<T: Config> = StorageMap<_, Blake2_128Concat, DataKey, AggregationStrategy>;
+
+    /// sr25519 keys trusted providers sign round values with off-chain
+    #[pallet::storage]
+    #[pallet::getter(fn signing_key)]
+    pub type SigningKeys<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, sr25519::Public>;
+
+    /// Last round a signed round value was accepted in, per data key
+    #[pallet::storage]
+    #[pallet::getter(fn last_signed_round)]
+    pub type LastSignedRounds<T: Config> = StorageMap<_, Blake2_128Concat, DataKey, RoundIndex>;
+
+    /// Data keys only the round's committee may submit for
+    #[pallet::storage]
+    #[pallet::getter(fn committee_mode)]
+    pub type CommitteeMode<T: Config> = StorageMap<_, Blake2_128Concat, DataKey, ()>;
+
+    /// Data keys the runtime depends on, accepted by `provide_critical_data`
+    #[pallet::storage]
+    #[pallet::getter(fn critical_feed)]
+    pub type CriticalFeeds<T: Config> = StorageMap<_, Blake2_128Concat, DataKey, ()>;
+
+    /// Weight of `provide_critical_data` submissions in the current block
+    #[pallet::storage]
+    #[pallet::getter(fn critical_weight_used)]
+    pub type CriticalWeightUsed<T> = StorageValue<_, Weight, ValueQuery>;
+
+    /// Committee selected for a key in a round; only the current round is kept
+    #[pallet::storage]
+    #[pallet::getter(fn committees)]
+    pub type Committees<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, DataKey,
+        Twox64Concat, RoundIndex,
+        BoundedVec<T::AccountId, T::CommitteeSize>,
+    >;
+
     /// Storage for data sources
     #[pallet::storage]
     #[pallet::getter(fn data_sources)]
     pub type DataSources<T: Config> = StorageMap<_, Blake2_128Concat, SourceId, DataSource>;
 
-    /// Storage for trusted oracle providers
+    /// Accounts allowed to update, suspend and retire a data source alongside governance
+    #[pallet::storage]
+    #[pallet::getter(fn source_owners)]
+    pub type SourceOwners<T: Config> = StorageMap<_, Blake2_128Concat, SourceId, T::AccountId>;
+
+    /// Suspended data sources, and who suspended them
+    #[pallet::storage]
+    #[pallet::getter(fn source_suspensions)]
+    pub type SourceSuspensions<T: Config> = StorageMap<_, Blake2_128Concat, SourceId, SourceAuthority>;
+
+    /// Contracts allowed to publish into a data key, and the source they publish as
+    #[pallet::storage]
+    #[pallet::getter(fn contract_feeds)]
+    pub type ContractFeeds<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (DataKey, SourceId)>;
+
+    /// Storage for trusted oracle providers, at most `MaxTrustedProviders`
     #[pallet::storage]
     #[pallet::getter(fn trusted_providers)]
-    pub type TrustedProviders<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u8>; // reputation score
+    pub type TrustedProviders<T: Config> = CountedStorageMap<_, Blake2_128Concat, T::AccountId, u8>; // reputation score
+
+    /// SLAs of premium requests that are neither met nor expired
+    #[pallet::storage]
+    #[pallet::getter(fn premium_slas)]
+    pub type PremiumSlas<T: Config> = StorageMap<_, Blake2_128Concat, RequestId, PremiumSla<BlockNumberFor<T>>>;
+
+    /// Open premium requests by data key, so submissions can be matched against them
+    #[pallet::storage]
+    pub type OpenSlas<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, DataKey,
+        Twox64Concat, RequestId,
+        (),
+    >;
+
+    /// Premium requests whose SLA is settled at the start of a block
+    #[pallet::storage]
+    pub type SlaExpiries<T: Config> = StorageMap<_, Twox64Concat, BlockNumberFor<T>, Vec<RequestId>, ValueQuery>;
+
+    /// Feeds checked for staleness every block, with the bond slashed from each committee
+    /// member when one goes stale
+    #[pallet::storage]
+    #[pallet::getter(fn watched_feeds)]
+    pub type WatchedFeeds<T: Config> = CountedStorageMap<_, Blake2_128Concat, DataKey, BalanceOf<T>>;
+
+    /// Watched feeds found stale, and the block they were flagged at
+    #[pallet::storage]
+    #[pallet::getter(fn stale_feeds)]
+    pub type StaleFeeds<T: Config> = StorageMap<_, Blake2_128Concat, DataKey, BlockNumberFor<T>>;
+
+    /// Balance providers reserved as a bond against their watched feeds going stale
+    #[pallet::storage]
+    #[pallet::getter(fn provider_bonds)]
+    pub type ProviderBonds<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
 
     /// Next request ID to assign
     #[pallet::storage]
     #[pallet::getter(fn next_request_id)]
     pub type NextRequestId<T> = StorageValue<_, RequestId, ValueQuery>;
 
+    /// Request era an account last requested in, and the requests it made in that era
+    #[pallet::storage]
+    #[pallet::getter(fn request_quotas)]
+    pub type RequestQuotas<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, (BlockNumberFor<T>, u32), ValueQuery>;
+
+    /// Requests pruned at each block, `RequestTtl` blocks after they were made
+    #[pallet::storage]
+    #[pallet::getter(fn request_expiries)]
+    pub type RequestExpiries<T: Config> = StorageMap<_, Twox64Concat, BlockNumberFor<T>, Vec<RequestId>, ValueQuery>;
+
+    /// Requirements of source tiers overridden by governance; the others follow the `Config`
+    #[pallet::storage]
+    pub type TierRequirementOverrides<T: Config> =
+        StorageMap<_, Twox64Concat, SourceTier, TierRequirements<BalanceOf<T>>>;
+
+    /// Observations mirrored to the off-chain history in the current block
+    #[pallet::storage]
+    pub type HistoryIndexed<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// Submissions refunded in the current block
+    #[pallet::storage]
+    #[pallet::getter(fn feeless_submissions)]
+    pub type FeelessSubmissions<T> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Data sources registered at genesis: identifier, name, endpoint and reliability; they
+        /// start `Open`
+        pub sources: Vec<(SourceId, Vec<u8>, Vec<u8>, u8)>,
+        /// Trusted providers and their reputation
+        pub trusted_providers: Vec<(T::AccountId, u8)>,
+        /// Data keys flagged critical
+        pub critical_feeds: Vec<DataKey>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            for (id, name, endpoint, reliability) in &self.sources {
+                assert!(*reliability <= 100, "source reliability is a score out of 100");
+                <DataSources<T>>::insert(id, DataSource {
+                    id: id.clone(),
+                    name: name.clone(),
+                    endpoint: endpoint.clone(),
+                    reliability: *reliability,
+                    active: true,
+                    tier: SourceTier::Open,
+                });
+            }
+            for (provider, reputation) in &self.trusted_providers {
+                assert!(*reputation <= 100, "provider reputation is a score out of 100");
+                <TrustedProviders<T>>::insert(provider, reputation);
+            }
+            assert!(
+                <TrustedProviders<T>>::count() <= T::MaxTrustedProviders::get(),
+                "more trusted providers than MaxTrustedProviders",
+            );
+            for data_key in &self.critical_feeds {
+                <CriticalFeeds<T>>::insert(data_key, ());
+            }
+            Pallet::<T>::endow_pot();
+        }
+    }
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -224,14 +718,71 @@ pub mod pallet {
             source_count: u32, 
             confidence: u8,
         },
+        /// Aggregate of `aggregated_at` became readable after `MinAggregationDelay`
+        AggregateReadable { data_key: DataKey, aggregated_at: BlockNumberFor<T> },
+        /// Typed feed registered for a data key
+        FeedRegistered { data_key: DataKey, metadata: FeedMetadata },
+        /// Committee mode switched on or off for a data key
+        CommitteeModeSet { data_key: DataKey, enabled: bool },
+        /// Data key flagged critical or back to normal
+        FeedCriticalitySet { data_key: DataKey, critical: bool },
+        /// Committee drawn for a data key and round
+        CommitteeSelected { data_key: DataKey, round: RoundIndex, members: Vec<T::AccountId> },
         /// Data source registered
         SourceRegistered { source_id: SourceId, name: Vec<u8> },
+        /// Data source owner set, or cleared when `None`
+        SourceOwnerSet { source_id: SourceId, owner: Option<T::AccountId> },
+        /// Data source name, endpoint or reliability changed; carries the new values
+        SourceUpdated { source_id: SourceId, name: Vec<u8>, endpoint: Vec<u8>, reliability: u8 },
+        /// Data source suspended; submissions from it are refused until it is resumed
+        SourceSuspended { source_id: SourceId, by: SourceAuthority },
+        /// Suspended data source accepts submissions again
+        SourceResumed { source_id: SourceId },
+        /// Data source removed for good
+        SourceRetired { source_id: SourceId },
+        /// Aggregation strategy of a data key set, or cleared with `None`
+        AggregationStrategySet { data_key: DataKey, strategy: Option<AggregationStrategy> },
         /// Oracle provider added to trusted list
         ProviderTrusted { provider: T::AccountId, reputation: u8 },
+        /// Contract allowed to publish into a data key
+        ContractFeedAuthorized { contract: T::AccountId, data_key: DataKey, source_id: SourceId },
+        /// Contract no longer allowed to publish
+        ContractFeedRevoked { contract: T::AccountId },
         /// Oracle data expired and removed
         DataExpired { data_key: DataKey, expired_at: BlockNumberFor<T> },
         /// Batch request processed
         BatchProcessed { request_count: u32, total_fee: BalanceOf<T> },
+        /// Enough assigned sources answered a premium request in time
+        PremiumSlaMet { request_id: RequestId, delivered: u32 },
+        /// A premium request expired unanswered; the premium was refunded and the
+        /// sources that did not deliver were penalized
+        PremiumSlaMissed { request_id: RequestId, refund: BalanceOf<T>, missing: Vec<SourceId> },
+        /// Feed watched, or its slash changed; `None` when it is no longer watched
+        FeedWatchSet { data_key: DataKey, slash: Option<BalanceOf<T>> },
+        /// A watched feed has no aggregate younger than `MaxDataAge`; prices are withheld
+        /// until it aggregates again
+        FeedStale { data_key: DataKey, last_updated: Option<BlockNumberFor<T>> },
+        /// A stale feed aggregated again
+        FeedRecovered { data_key: DataKey },
+        /// Provider reserved a bond
+        ProviderBonded { provider: T::AccountId, amount: BalanceOf<T> },
+        /// Provider released part of its bond
+        ProviderUnbonded { provider: T::AccountId, amount: BalanceOf<T> },
+        /// Committee member of a feed that went stale lost part of its bond
+        ProviderSlashed { provider: T::AccountId, data_key: DataKey, amount: BalanceOf<T> },
+        /// Provider registered the key it signs round values with
+        SigningKeySet { provider: T::AccountId, key: sr25519::Public },
+        /// Round value signed by `signers` providers submitted by `aggregator`
+        SignedRoundSubmitted { data_key: DataKey, round: RoundIndex, aggregator: T::AccountId, signers: u32 },
+        /// Request removed `RequestTtl` blocks after it was made; `fulfilled` if its key
+        /// aggregated since
+        RequestPruned { request_id: RequestId, fulfilled: bool },
+        /// Data source moved to a tier
+        SourceTierSet { source_id: SourceId, tier: SourceTier },
+        /// Requirements of a tier overridden, or back to the runtime's with `None`
+        TierRequirementsSet { tier: SourceTier, requirements: Option<TierRequirements<BalanceOf<T>>> },
+        /// Pending aggregate of `aggregated_at` discarded before it became readable
+        AggregateDisputed { data_key: DataKey, aggregated_at: BlockNumberFor<T> },
     }
 
     #[pallet::error]
@@ -260,13 +811,112 @@ pub mod pallet {
         TooManySources,
         /// Invalid signature
         InvalidSignature,
+        /// A feed is already registered for the data key
+        FeedAlreadyRegistered,
+        /// Feed metadata is invalid (e.g. more decimals than `FixedU128` holds)
+        InvalidFeedMetadata,
+        /// Value is not a SCALE-encoded `TypedValue`
+        MalformedValue,
+        /// Value kind differs from the feed's
+        ValueTypeMismatch,
+        /// Numeric value has more decimal places than the feed allows
+        ValueExceedsPrecision,
+        /// Provider is not in the key's committee for the current round
+        NotInCommittee,
+        /// Contract is not allowed to publish into the data key
+        ContractNotAuthorized,
+        /// `MaxWatchedFeeds` feeds are already watched
+        TooManyWatchedFeeds,
+        /// Provider's bond is smaller than the amount
+        InsufficientBond,
+        /// Batch holds more than `MaxBatchSubmissions` entries
+        TooManySubmissions,
+        /// Provider sits on the current committee of a watched feed and cannot unbond
+        ProviderAssigned,
+        /// Oracle submissions are paused by governance
+        SystemPaused,
+        /// Caller neither is governance nor owns the data source
+        NotSourceOwner,
+        /// Only governance may change a source's reliability or lift a suspension it imposed
+        GovernanceOnly,
+        /// Data source is already suspended
+        SourceAlreadySuspended,
+        /// Data source is not suspended
+        SourceNotSuspended,
+        /// Median, mean and weighted strategies need a registered numeric feed
+        StrategyRequiresNumericFeed,
+        /// Signed round is not the current round
+        WrongRound,
+        /// A signed value was already accepted for the data key in this round
+        RoundAlreadySubmitted,
+        /// Signed round carries more than `MaxRoundSigners` signatures
+        TooManySigners,
+        /// Signed round carries fewer than `MinAggregationSources` signatures
+        NotEnoughSigners,
+        /// Provider signed the round more than once
+        DuplicateSigner,
+        /// Provider has not registered a signing key
+        SigningKeyNotSet,
+        /// Data keys under `remote/` only hold values received from other chains
+        ReservedDataKey,
+        /// Account already made `MaxRequestsPerEra` requests in the current request era
+        QuotaExceeded,
+        /// Provider's reputation is below the minimum of the source's tier
+        ReputationTooLow,
+        /// Provider's bond is below the minimum of the source's tier
+        TierBondTooLow,
+        /// `MaxTrustedProviders` providers are already trusted
+        TooManyTrustedProviders,
+        /// No aggregate of the data key is pending to become readable in that block
+        AggregateNotPending,
+        /// Data key is not flagged critical
+        NotCriticalFeed,
+        /// The block's `CriticalFeedWeight` has no room for the submission
+        CriticalFeedWeightExhausted,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            <FeelessSubmissions<T>>::kill();
+            <CriticalWeightUsed<T>>::kill();
+            <HistoryIndexed<T>>::kill();
+            let expiring = <SlaExpiries<T>>::take(n);
+            let weight = T::DbWeight::get().reads_writes(2, 5);
+
+            let weight = expiring.into_iter().fold(weight, |weight, request_id| {
+                Self::settle_expired_sla(request_id);
+                weight.saturating_add(T::WeightInfo::settle_premium_sla())
+            });
+
+            let weight = <RequestExpiries<T>>::take(n).into_iter().fold(weight, |weight, request_id| {
+                weight.saturating_add(Self::prune_request(request_id))
+            });
+
+            let weight = <PendingAggregates<T>>::drain_prefix(n).fold(weight, |weight, (data_key, aggregated)| {
+                Self::finalize_aggregate(data_key, aggregated);
+                weight.saturating_add(T::WeightInfo::finalize_aggregate())
+            });
+
+            <WatchedFeeds<T>>::iter().fold(weight, |weight, (data_key, slash)| {
+                let drawn = Self::check_watched_feed(data_key, slash);
+                weight.saturating_add(T::WeightInfo::check_watched_feed()).saturating_add(drawn)
+            })
+        }
+
+        fn integrity_test() {
+            assert!(
+                T::MinAggregationDelay::get().saturated_into::<u64>() < T::MaxDataAge::get(),
+                "aggregates must become readable before they are stale",
+            );
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// Request oracle data from off-chain sources
         #[pallet::call_index(0)]
-        #[pallet::weight(T::WeightInfo::request_data())]
+        #[pallet::weight(T::WeightInfo::request_data(sources.len() as u32))]
         pub fn request_data(
             origin: OriginFor<T>,
             data_key: DataKey,
@@ -278,6 +928,7 @@ pub mod pallet {
 
             // Validate sources limit
             ensure!(sources.len() <= T::MaxDataSources::get() as usize, Error::<T>::TooManySources);
+            Self::consume_request_quota(&who, 1)?;
 
             // Charge appropriate fee
             let fee = if premium { T::PremiumQueryFee::get() } else { T::OracleQueryFee::get() };
@@ -298,7 +949,7 @@ pub mod pallet {
             };
 
             // Store request
-            <OracleRequests<T>>::insert(request_id, &request);
+            Self::store_request(request_id, &request);
 
             // Emit event
             Self::deposit_event(Event::DataRequested {
@@ -313,8 +964,12 @@ pub mod pallet {
         }
 
         /// Provide oracle data for a specific key and source
+        ///
+        /// The fee is refunded to trusted providers with a bond when the call succeeds, up to
+        /// `MaxFeelessSubmissionsPerBlock` per block. Drawing the round's committee of a key in
+        /// committee mode is charged up front and refunded unless the call drew it.
         #[pallet::call_index(1)]
-        #[pallet::weight(T::WeightInfo::provide_data())]
+        #[pallet::weight(Pallet::<T>::provide_data_weight(value.len() as u32))]
         pub fn provide_data(
             origin: OriginFor<T>,
             data_key: DataKey,
@@ -322,59 +977,15 @@ pub mod pallet {
             value: DataValue,
             confidence: u8,
             signature: Option<Vec<u8>>,
-        ) -> DispatchResult {
+        ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
 
-            // Validate data size
-            ensure!(value.len() <= T::MaxDataSize::get() as usize, Error::<T>::DataTooLarge);
-
-            // Validate confidence score
-            ensure!(confidence <= 100, Error::<T>::InvalidConfidence);
-
-            // Validate source exists and is active
-            let source_info = <DataSources<T>>::get(&source).ok_or(Error::<T>::SourceNotFound)?;
-            ensure!(source_info.active, Error::<T>::InvalidSource);
-
-            // Check if provider is trusted for premium data
-            if confidence > 80 {
-                ensure!(<TrustedProviders<T>>::contains_key(&who), Error::<T>::ProviderNotTrusted);
-            }
-
-            // Create oracle data entry
-            let oracle_data = OracleData {
-                value: value.clone(),
-                provider: who.clone(),
-                timestamp: frame_system::Pallet::<T>::block_number(),
-                source: source.clone(),
-                confidence,
-                signature,
-            };
-
-            // Store data
-            <OracleDataStorage<T>>::insert(&data_key, &source, &oracle_data);
-
-            // Reward provider (ultra-low to maintain sustainability)
-            let reward = T::OracleReward::get();
-            let _ = T::Currency::transfer(&Self::account_id(), &who, reward, ExistenceRequirement::AllowDeath);
-
-            // Emit event
-            Self::deposit_event(Event::DataProvided {
-                data_key: data_key.clone(),
-                source,
-                provider: who,
-                value,
-                confidence,
-            });
-
-            // Try to aggregate data if enough sources
-            Self::try_aggregate_data(&data_key)?;
-
-            Ok(())
+            Self::do_provide_data(who, data_key, source, value, confidence, signature)
         }
 
         /// Register a new data source
         #[pallet::call_index(2)]
-        #[pallet::weight(T::WeightInfo::register_source())]
+        #[pallet::weight((T::WeightInfo::register_source(), DispatchClass::Operational, Pays::No))]
         pub fn register_source(
             origin: OriginFor<T>,
             source_id: SourceId,
@@ -394,10 +1005,12 @@ pub mod pallet {
                 endpoint,
                 reliability,
                 active: true,
+                tier: SourceTier::Open,
             };
 
-            // Store source
+            // Store source, replacing any suspended registration
             <DataSources<T>>::insert(&source_id, &source);
+            <SourceSuspensions<T>>::remove(&source_id);
 
             // Emit event
             Self::deposit_event(Event::SourceRegistered { source_id, name });
@@ -405,9 +1018,9 @@ pub mod pallet {
             Ok(())
         }
 
-        /// Add a trusted oracle provider
+        /// Add a trusted oracle provider, or update the reputation of one
         #[pallet::call_index(3)]
-        #[pallet::weight(T::WeightInfo::add_trusted_provider())]
+        #[pallet::weight((T::WeightInfo::add_trusted_provider(), DispatchClass::Operational, Pays::No))]
         pub fn add_trusted_provider(
             origin: OriginFor<T>,
             provider: T::AccountId,
@@ -418,6 +1031,12 @@ pub mod pallet {
             // Validate reputation score
             ensure!(reputation <= 100, Error::<T>::InvalidConfidence);
 
+            ensure!(
+                <TrustedProviders<T>>::contains_key(&provider)
+                    || <TrustedProviders<T>>::count() < T::MaxTrustedProviders::get(),
+                Error::<T>::TooManyTrustedProviders
+            );
+
             // Store trusted provider
             <TrustedProviders<T>>::insert(&provider, reputation);
 
@@ -429,15 +1048,20 @@ pub mod pallet {
 
         /// Batch multiple oracle requests for efficiency
         #[pallet::call_index(4)]
-        #[pallet::weight(T::WeightInfo::batch_requests())]
+        #[pallet::weight(T::WeightInfo::batch_requests(
+            requests.len() as u32,
+            requests.iter().map(|(_, sources, _)| sources.len() as u32).max().unwrap_or(0),
+        ))]
         pub fn batch_requests(
             origin: OriginFor<T>,
             requests: Vec<(DataKey, Vec<SourceId>, bool)>, // (key, sources, premium)
-        ) -> DispatchResult {
+        ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
+            Self::consume_request_quota(&who, requests.len() as u32)?;
 
             let mut total_fee = BalanceOf::<T>::zero();
             let mut request_count = 0u32;
+            let mut source_count = 0u32;
 
             // Process each request
             for (data_key, sources, premium) in requests {
@@ -463,9 +1087,10 @@ pub mod pallet {
                 };
 
                 // Store request
-                <OracleRequests<T>>::insert(request_id, &request);
+                Self::store_request(request_id, &request);
 
                 request_count = request_count.saturating_add(1);
+                source_count = source_count.saturating_add(sources.len() as u32);
             }
 
             // Charge total fee
@@ -474,7 +1099,9 @@ pub mod pallet {
             // Emit event
             Self::deposit_event(Event::BatchProcessed { request_count, total_fee });
 
-            Ok(())
+            // Charged for the average request rather than the one with the most sources
+            let average_sources = source_count.div_ceil(request_count.max(1));
+            Ok(Some(T::WeightInfo::batch_requests(request_count, average_sources)).into())
         }
 
         /// Clean up expired oracle data
@@ -508,86 +1135,1384 @@ pub mod pallet {
 
             Ok(())
         }
-    }
 
-    impl<T: Config> Pallet<T> {
-        /// Get the account ID for the pallet
-        pub fn account_id() -> T::AccountId {
-            T::PalletId::get().into_account_truncating()
-        }
+        /// Register a typed feed for a data key
+        #[pallet::call_index(6)]
+        #[pallet::weight((T::WeightInfo::register_feed(), DispatchClass::Operational, Pays::No))]
+        pub fn register_feed(
+            origin: OriginFor<T>,
+            data_key: DataKey,
+            metadata: FeedMetadata,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
 
-        /// Try to aggregate data from multiple sources
-        fn try_aggregate_data(data_key: &DataKey) -> DispatchResult {
-            let min_sources = T::MinAggregationSources::get();
-            let mut data_points = Vec::new();
-            let mut total_confidence = 0u32;
-            let mut source_count = 0u32;
+            ensure!(!<Feeds<T>>::contains_key(&data_key), Error::<T>::FeedAlreadyRegistered);
+            ensure!(metadata.decimals <= MAX_FEED_DECIMALS, Error::<T>::InvalidFeedMetadata);
 
-            // Collect data from all sources for this key
-            for (_source_id, oracle_data) in <OracleDataStorage<T>>::iter_prefix(data_key) {
-                data_points.push(oracle_data.value.clone());
-                total_confidence = total_confidence.saturating_add(oracle_data.confidence as u32);
-                source_count = source_count.saturating_add(1);
-            }
+            <Feeds<T>>::insert(&data_key, &metadata);
 
-            // Only aggregate if we have enough sources
-            if source_count >= min_sources {
-                // Simple aggregation: use the first value (in production, implement median/average)
-                let aggregated_value = data_points.first().cloned().unwrap_or_default();
-                let average_confidence = (total_confidence / source_count) as u8;
+            Self::deposit_event(Event::FeedRegistered { data_key, metadata });
 
-                // Create aggregated data
-                let aggregated = AggregatedData {
-                    value: aggregated_value.clone(),
-                    source_count,
-                    confidence: average_confidence,
-                    aggregated_at: frame_system::Pallet::<T>::block_number(),
-                    data_points,
-                };
+            Ok(())
+        }
 
-                // Store aggregated data
-                <AggregatedDataStorage<T>>::insert(data_key, &aggregated);
+        /// Switch committee mode on or off for a data key
+        #[pallet::call_index(7)]
+        #[pallet::weight((T::WeightInfo::set_committee_mode(), DispatchClass::Operational, Pays::No))]
+        pub fn set_committee_mode(
+            origin: OriginFor<T>,
+            data_key: DataKey,
+            enabled: bool,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
 
-                // Emit event
-                Self::deposit_event(Event::DataAggregated {
-                    data_key: data_key.clone(),
-                    value: aggregated_value,
-                    source_count,
-                    confidence: average_confidence,
-                });
+            if enabled {
+                <CommitteeMode<T>>::insert(&data_key, ());
+            } else {
+                <CommitteeMode<T>>::remove(&data_key);
+                let _ = <Committees<T>>::clear_prefix(&data_key, u32::MAX, None);
             }
 
+            Self::deposit_event(Event::CommitteeModeSet { data_key, enabled });
+
             Ok(())
         }
 
-        /// Get latest oracle data for a key (public interface)
-        pub fn get_latest_data(data_key: &DataKey) -> Option<DataValue> {
-            <AggregatedDataStorage<T>>::get(data_key).map(|data| data.value)
-        }
+        /// Allow `contract` to publish into `data_key` through the chain extension
+        ///
+        /// The contract gets its own data source, with the given reliability as the
+        /// confidence of its submissions, so its values are aggregated like any other source.
+        #[pallet::call_index(8)]
+        #[pallet::weight((T::WeightInfo::authorize_contract_feed(), DispatchClass::Operational, Pays::No))]
+        pub fn authorize_contract_feed(
+            origin: OriginFor<T>,
+            contract: T::AccountId,
+            data_key: DataKey,
+            reliability: u8,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
 
-        /// Get data with confidence score
-        pub fn get_data_with_confidence(data_key: &DataKey) -> Option<(DataValue, u8)> {
-            <AggregatedDataStorage<T>>::get(data_key).map(|data| (data.value, data.confidence))
-        }
-    }
-}
+            ensure!(reliability <= 100, Error::<T>::InvalidConfidence);
 
-/// Weight functions needed for benchmarking
-pub trait WeightInfo {
-    fn request_data() -> Weight;
-    fn provide_data() -> Weight;
-    fn register_source() -> Weight;
-    fn add_trusted_provider() -> Weight;
-    fn batch_requests() -> Weight;
-    fn cleanup_expired_data() -> Weight;
+            let source_id = Self::contract_source_id(&contract);
+            <DataSources<T>>::insert(&source_id, DataSource {
+                id: source_id.clone(),
+                name: b"contract".to_vec(),
+                endpoint: data_key.clone(),
+                reliability,
+                active: true,
+                tier: SourceTier::Open,
+            });
+            <ContractFeeds<T>>::insert(&contract, (data_key.clone(), source_id.clone()));
+
+            Self::deposit_event(Event::ContractFeedAuthorized { contract, data_key, source_id });
+
+            Ok(())
+        }
+
+        /// Stop `contract` from publishing; its source is deactivated
+        #[pallet::call_index(9)]
+        #[pallet::weight((T::WeightInfo::authorize_contract_feed(), DispatchClass::Operational, Pays::No))]
+        pub fn revoke_contract_feed(origin: OriginFor<T>, contract: T::AccountId) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let (_, source_id) = <ContractFeeds<T>>::take(&contract).ok_or(Error::<T>::ContractNotAuthorized)?;
+            <DataSources<T>>::mutate(&source_id, |source| {
+                if let Some(source) = source {
+                    source.active = false;
+                }
+            });
+
+            Self::deposit_event(Event::ContractFeedRevoked { contract });
+
+            Ok(())
+        }
+
+        /// Watch a feed for staleness, slashing up to `slash` from each committee member's
+        /// bond when it goes stale; `None` stops watching it
+        #[pallet::call_index(10)]
+        #[pallet::weight((T::WeightInfo::set_feed_watch(), DispatchClass::Operational, Pays::No))]
+        pub fn set_feed_watch(
+            origin: OriginFor<T>,
+            data_key: DataKey,
+            slash: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            match slash {
+                Some(slash) => {
+                    ensure!(
+                        <WatchedFeeds<T>>::contains_key(&data_key)
+                            || <WatchedFeeds<T>>::count() < T::MaxWatchedFeeds::get(),
+                        Error::<T>::TooManyWatchedFeeds
+                    );
+                    <WatchedFeeds<T>>::insert(&data_key, slash);
+                },
+                None => {
+                    <WatchedFeeds<T>>::remove(&data_key);
+                    <StaleFeeds<T>>::remove(&data_key);
+                },
+            }
+
+            Self::deposit_event(Event::FeedWatchSet { data_key, slash });
+
+            Ok(())
+        }
+
+        /// Reserve `amount` as a bond against the watched feeds the caller serves going stale
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::bond_provider())]
+        pub fn bond_provider(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            T::Currency::reserve(&who, amount)?;
+            <ProviderBonds<T>>::mutate(&who, |bond| *bond = bond.saturating_add(amount));
+
+            Self::deposit_event(Event::ProviderBonded { provider: who, amount });
+
+            Ok(())
+        }
+
+        /// Provide values for several data keys from one source in a single extrinsic
+        ///
+        /// Every entry is checked as by `provide_data` and the whole basket is stored, or none
+        /// of it if any entry is rejected. Each key is aggregated as its entry is stored, and the
+        /// provider is rewarded once per entry. A committee draw is charged up front per entry
+        /// and refunded for the entries that did not draw one.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::provide_data_batch(
+            entries.len() as u32,
+            entries.iter().map(|(_, value, _)| value.len() as u32).max().unwrap_or(0),
+            T::MaxDataSources::get(),
+        ).saturating_add(
+            T::WeightInfo::select_committee(T::MaxTrustedProviders::get()).saturating_mul(entries.len() as u64)
+        ))]
+        pub fn provide_data_batch(
+            origin: OriginFor<T>,
+            source: SourceId,
+            entries: Vec<(DataKey, DataValue, u8)>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            ensure!(entries.len() <= T::MaxBatchSubmissions::get() as usize, Error::<T>::TooManySubmissions);
+            let tier = Self::ensure_active_source(&source)?.tier;
+            Self::ensure_meets_tier(&who, tier)?;
+            let mut weight = T::WeightInfo::provide_data_batch(
+                entries.len() as u32,
+                entries.iter().map(|(_, value, _)| value.len() as u32).max().unwrap_or(0),
+                T::MaxDataSources::get(),
+            );
+            for (data_key, value, confidence) in &entries {
+                Self::validate_submission(data_key, value, *confidence)?;
+                weight.saturating_accrue(Self::ensure_may_submit(&who, data_key)?);
+            }
+
+            let reward = Self::tier_reward(tier, entries.len() as u32);
+            let _ = T::Currency::transfer(&Self::account_id(), &who, reward, ExistenceRequirement::KeepAlive);
+
+            for (data_key, value, confidence) in entries {
+                Self::store_submission(data_key, source.clone(), who.clone(), value, confidence, None)?;
+            }
+
+            Ok(Some(weight).into())
+        }
+
+        /// Release `amount` of the caller's bond; refused while it sits on the current
+        /// committee of a watched feed
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::unbond_provider())]
+        pub fn unbond_provider(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let bond = <ProviderBonds<T>>::get(&who);
+            ensure!(bond >= amount, Error::<T>::InsufficientBond);
+            let round = Self::current_round();
+            let assigned = <WatchedFeeds<T>>::iter_keys().any(|data_key| {
+                <Committees<T>>::get(&data_key, round).map_or(false, |committee| committee.contains(&who))
+            });
+            ensure!(!assigned, Error::<T>::ProviderAssigned);
+
+            T::Currency::unreserve(&who, amount);
+            if bond == amount {
+                <ProviderBonds<T>>::remove(&who);
+            } else {
+                <ProviderBonds<T>>::insert(&who, bond.saturating_sub(amount));
+            }
+
+            Self::deposit_event(Event::ProviderUnbonded { provider: who, amount });
+
+            Ok(())
+        }
+
+        /// Let `owner` update, suspend and retire a data source alongside governance;
+        /// `None` leaves it to governance alone
+        #[pallet::call_index(14)]
+        #[pallet::weight((T::WeightInfo::set_source_owner(), DispatchClass::Operational, Pays::No))]
+        pub fn set_source_owner(
+            origin: OriginFor<T>,
+            source_id: SourceId,
+            owner: Option<T::AccountId>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            ensure!(<DataSources<T>>::contains_key(&source_id), Error::<T>::SourceNotFound);
+            match &owner {
+                Some(owner) => <SourceOwners<T>>::insert(&source_id, owner),
+                None => <SourceOwners<T>>::remove(&source_id),
+            }
+
+            Self::deposit_event(Event::SourceOwnerSet { source_id, owner });
+
+            Ok(())
+        }
+
+        /// Rename a data source, rotate its endpoint or rescore its reliability; fields left
+        /// `None` are kept
+        ///
+        /// Only governance may change the reliability score.
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::update_source())]
+        pub fn update_source(
+            origin: OriginFor<T>,
+            source_id: SourceId,
+            name: Option<Vec<u8>>,
+            endpoint: Option<Vec<u8>>,
+            reliability: Option<u8>,
+        ) -> DispatchResult {
+            let authority = Self::ensure_source_admin(origin, &source_id)?;
+
+            if let Some(reliability) = reliability {
+                ensure!(authority == SourceAuthority::Governance, Error::<T>::GovernanceOnly);
+                ensure!(reliability <= 100, Error::<T>::InvalidConfidence);
+            }
+
+            let source = <DataSources<T>>::try_mutate(&source_id, |source| -> Result<_, DispatchError> {
+                let source = source.as_mut().ok_or(Error::<T>::SourceNotFound)?;
+                if let Some(name) = name {
+                    source.name = name;
+                }
+                if let Some(endpoint) = endpoint {
+                    source.endpoint = endpoint;
+                }
+                if let Some(reliability) = reliability {
+                    source.reliability = reliability;
+                }
+                Ok(source.clone())
+            })?;
+
+            Self::deposit_event(Event::SourceUpdated {
+                source_id,
+                name: source.name,
+                endpoint: source.endpoint,
+                reliability: source.reliability,
+            });
+
+            Ok(())
+        }
+
+        /// Refuse submissions from a data source until it is resumed
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::suspend_source())]
+        pub fn suspend_source(origin: OriginFor<T>, source_id: SourceId) -> DispatchResult {
+            let authority = Self::ensure_source_admin(origin, &source_id)?;
+
+            ensure!(!<SourceSuspensions<T>>::contains_key(&source_id), Error::<T>::SourceAlreadySuspended);
+            <DataSources<T>>::mutate(&source_id, |source| {
+                if let Some(source) = source {
+                    source.active = false;
+                }
+            });
+            <SourceSuspensions<T>>::insert(&source_id, authority);
+
+            Self::deposit_event(Event::SourceSuspended { source_id, by: authority });
+
+            Ok(())
+        }
+
+        /// Accept submissions from a suspended data source again; a suspension imposed by
+        /// governance can only be lifted by governance
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::WeightInfo::resume_source())]
+        pub fn resume_source(origin: OriginFor<T>, source_id: SourceId) -> DispatchResult {
+            let authority = Self::ensure_source_admin(origin, &source_id)?;
+
+            let suspended_by = <SourceSuspensions<T>>::get(&source_id).ok_or(Error::<T>::SourceNotSuspended)?;
+            ensure!(
+                suspended_by == SourceAuthority::Owner || authority == SourceAuthority::Governance,
+                Error::<T>::GovernanceOnly
+            );
+            <DataSources<T>>::mutate(&source_id, |source| {
+                if let Some(source) = source {
+                    source.active = true;
+                }
+            });
+            <SourceSuspensions<T>>::remove(&source_id);
+
+            Self::deposit_event(Event::SourceResumed { source_id });
+
+            Ok(())
+        }
+
+        /// Remove a data source; values it already submitted stay until they expire
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::WeightInfo::retire_source())]
+        pub fn retire_source(origin: OriginFor<T>, source_id: SourceId) -> DispatchResult {
+            Self::ensure_source_admin(origin, &source_id)?;
+
+            <DataSources<T>>::remove(&source_id);
+            <SourceOwners<T>>::remove(&source_id);
+            <SourceSuspensions<T>>::remove(&source_id);
+
+            Self::deposit_event(Event::SourceRetired { source_id });
+
+            Ok(())
+        }
+
+        /// Set how the values of `data_key` are aggregated, or `None` for the default
+        ///
+        /// Numeric strategies are refused unless the key is a registered numeric feed.
+        #[pallet::call_index(19)]
+        #[pallet::weight((T::WeightInfo::set_aggregation_strategy(), DispatchClass::Operational, Pays::No))]
+        pub fn set_aggregation_strategy(
+            origin: OriginFor<T>,
+            data_key: DataKey,
+            strategy: Option<AggregationStrategy>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            match strategy {
+                Some(strategy) => {
+                    if strategy.is_numeric() {
+                        let kind = <Feeds<T>>::get(&data_key).map(|metadata| metadata.kind);
+                        ensure!(kind == Some(ValueKind::Numeric), Error::<T>::StrategyRequiresNumericFeed);
+                    }
+                    <AggregationStrategies<T>>::insert(&data_key, strategy);
+                },
+                None => <AggregationStrategies<T>>::remove(&data_key),
+            }
+
+            Self::deposit_event(Event::AggregationStrategySet { data_key, strategy });
+
+            Ok(())
+        }
+
+        /// Register the sr25519 key the caller, a trusted provider, signs round values with
+        #[pallet::call_index(20)]
+        #[pallet::weight(T::WeightInfo::set_signing_key())]
+        pub fn set_signing_key(origin: OriginFor<T>, key: sr25519::Public) -> DispatchResult {
+            let provider = ensure_signed(origin)?;
+            ensure!(<TrustedProviders<T>>::contains_key(&provider), Error::<T>::ProviderNotTrusted);
+
+            <SigningKeys<T>>::insert(&provider, key);
+            Self::deposit_event(Event::SigningKeySet { provider, key });
+
+            Ok(())
+        }
+
+        /// Submit the value of `data_key` for the current `round`, signed off-chain by trusted
+        /// providers
+        ///
+        /// Each signature is checked against its provider's signing key over
+        /// [`Pallet::round_payload`], and each provider must be allowed to submit into the key,
+        /// as by `provide_data`; rounds go through no source, so no tier applies. At least
+        /// `MinAggregationSources` providers must sign. The value is published as the key's
+        /// aggregate without waiting for individual submissions, signers are rewarded as if they
+        /// had submitted it, and the key accepts no other signed value until the next round.
+        #[pallet::call_index(21)]
+        #[pallet::weight(T::WeightInfo::submit_signed_round(signatures.len() as u32, value.len() as u32)
+            .saturating_add(T::WeightInfo::select_committee(T::MaxTrustedProviders::get())))]
+        pub fn submit_signed_round(
+            origin: OriginFor<T>,
+            data_key: DataKey,
+            round: RoundIndex,
+            value: DataValue,
+            confidence: u8,
+            signatures: Vec<(T::AccountId, sr25519::Signature)>,
+        ) -> DispatchResultWithPostInfo {
+            let aggregator = ensure_signed(origin)?;
+            let mut weight = T::WeightInfo::submit_signed_round(signatures.len() as u32, value.len() as u32);
+
+            ensure!(signatures.len() <= T::MaxRoundSigners::get() as usize, Error::<T>::TooManySigners);
+            ensure!(round == Self::current_round(), Error::<T>::WrongRound);
+            ensure!(
+                <LastSignedRounds<T>>::get(&data_key).map_or(true, |last| last < round),
+                Error::<T>::RoundAlreadySubmitted
+            );
+            Self::validate_submission(&data_key, &value, confidence)?;
+
+            let payload = Self::round_payload(&data_key, round, &value, confidence);
+            let mut signers = BTreeSet::new();
+            for (signer, signature) in &signatures {
+                ensure!(signers.insert(signer.clone()), Error::<T>::DuplicateSigner);
+                ensure!(<TrustedProviders<T>>::contains_key(signer), Error::<T>::ProviderNotTrusted);
+                let key = <SigningKeys<T>>::get(signer).ok_or(Error::<T>::SigningKeyNotSet)?;
+                ensure!(sp_io::crypto::sr25519_verify(signature, &payload, &key), Error::<T>::InvalidSignature);
+                weight.saturating_accrue(Self::ensure_may_submit(signer, &data_key)?);
+            }
+            let signer_count = signers.len() as u32;
+            ensure!(signer_count >= T::MinAggregationSources::get(), Error::<T>::NotEnoughSigners);
+            ensure!(!T::Safeguard::is_paused(Subsystem::Oracle), Error::<T>::SystemPaused);
+
+            <LastSignedRounds<T>>::insert(&data_key, round);
+            let reward = T::OracleReward::get();
+            for signer in &signers {
+                let _ = T::Currency::transfer(&Self::account_id(), signer, reward, ExistenceRequirement::KeepAlive);
+            }
+
+            T::History::record(HistoryKind::OracleUpdate, &aggregator, &data_key, BlakeTwo256::hash(&value));
+            Self::deposit_event(Event::SignedRoundSubmitted {
+                data_key: data_key.clone(),
+                round,
+                aggregator,
+                signers: signer_count,
+            });
+            Self::publish_aggregate(&data_key, Self::new_aggregate(
+                value.clone(),
+                signer_count,
+                confidence,
+                sp_std::vec![value],
+            ));
+
+            Ok(Some(weight).into())
+        }
+
+        /// Flag a data key as critical, or return it to normal
+        ///
+        /// `provide_critical_data` accepts submissions for critical keys from the
+        /// `CriticalFeedWeight` slice, so only keys the runtime prices with should be flagged.
+        #[pallet::call_index(22)]
+        #[pallet::weight((T::WeightInfo::set_feed_critical(), DispatchClass::Operational, Pays::No))]
+        pub fn set_feed_critical(origin: OriginFor<T>, data_key: DataKey, critical: bool) -> DispatchResult {
+            ensure_root(origin)?;
+
+            if critical {
+                <CriticalFeeds<T>>::insert(&data_key, ());
+            } else {
+                <CriticalFeeds<T>>::remove(&data_key);
+            }
+
+            Self::deposit_event(Event::FeedCriticalitySet { data_key, critical });
+
+            Ok(())
+        }
+
+        /// Move a data source to `tier`; submissions through it must meet the tier's
+        /// requirements from then on
+        #[pallet::call_index(23)]
+        #[pallet::weight((T::WeightInfo::set_source_tier(), DispatchClass::Operational, Pays::No))]
+        pub fn set_source_tier(origin: OriginFor<T>, source_id: SourceId, tier: SourceTier) -> DispatchResult {
+            ensure_root(origin)?;
+
+            <DataSources<T>>::try_mutate(&source_id, |source| -> DispatchResult {
+                source.as_mut().ok_or(Error::<T>::SourceNotFound)?.tier = tier;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::SourceTierSet { source_id, tier });
+
+            Ok(())
+        }
+
+        /// Override the requirements of `tier`, or return it to the runtime's with `None`
+        #[pallet::call_index(24)]
+        #[pallet::weight((T::WeightInfo::set_tier_requirements(), DispatchClass::Operational, Pays::No))]
+        pub fn set_tier_requirements(
+            origin: OriginFor<T>,
+            tier: SourceTier,
+            requirements: Option<TierRequirements<BalanceOf<T>>>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            match &requirements {
+                Some(requirements) => {
+                    ensure!(
+                        requirements.min_reputation.map_or(true, |reputation| reputation <= 100),
+                        Error::<T>::InvalidConfidence
+                    );
+                    <TierRequirementOverrides<T>>::insert(tier, requirements);
+                },
+                None => <TierRequirementOverrides<T>>::remove(tier),
+            }
+
+            Self::deposit_event(Event::TierRequirementsSet { tier, requirements });
+
+            Ok(())
+        }
+
+        /// Discard the aggregate of `data_key` pending to become readable in `valid_from`
+        ///
+        /// The key keeps its current readable value, and providers submit again in a later
+        /// round.
+        #[pallet::call_index(25)]
+        #[pallet::weight((T::WeightInfo::dispute_aggregate(), DispatchClass::Operational, Pays::No))]
+        pub fn dispute_aggregate(
+            origin: OriginFor<T>,
+            data_key: DataKey,
+            valid_from: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let aggregated =
+                <PendingAggregates<T>>::take(valid_from, &data_key).ok_or(Error::<T>::AggregateNotPending)?;
+
+            log::info!(
+                target: LOG_TARGET,
+                "{}: aggregate of block {:?} disputed",
+                Printable(&data_key),
+                aggregated.aggregated_at,
+            );
+            Self::deposit_event(Event::AggregateDisputed { data_key, aggregated_at: aggregated.aggregated_at });
+
+            Ok(())
+        }
+
+        /// Provide oracle data for a critical feed, as `provide_data` but `Operational`
+        ///
+        /// Lands in blocks full of normal transactions, within the block's `CriticalFeedWeight`;
+        /// submissions for keys not flagged critical, or that would exceed it, are rejected.
+        #[pallet::call_index(26)]
+        #[pallet::weight((Pallet::<T>::provide_data_weight(value.len() as u32), DispatchClass::Operational))]
+        pub fn provide_critical_data(
+            origin: OriginFor<T>,
+            data_key: DataKey,
+            source: SourceId,
+            value: DataValue,
+            confidence: u8,
+            signature: Option<Vec<u8>>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            ensure!(<CriticalFeeds<T>>::contains_key(&data_key), Error::<T>::NotCriticalFeed);
+            let declared = Self::provide_data_weight(value.len() as u32);
+            ensure!(
+                <CriticalWeightUsed<T>>::get().saturating_add(declared).all_lte(T::CriticalFeedWeight::get()),
+                Error::<T>::CriticalFeedWeightExhausted
+            );
+
+            let post_info = Self::do_provide_data(who, data_key, source, value, confidence, signature)?;
+            // The slice is charged what the submission used, not the worst case it declared
+            let weight = post_info.actual_weight.unwrap_or(declared);
+            <CriticalWeightUsed<T>>::mutate(|used| used.saturating_accrue(weight));
+            Ok(post_info)
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Get the account ID for the pallet
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account_truncating()
+        }
+
+        /// Bytes providers sign to attest `value` at `confidence` for `data_key` in `round`:
+        /// the SCALE encoding of [`ROUND_SIGNING_CONTEXT`] followed by those four
+        pub fn round_payload(data_key: &DataKey, round: RoundIndex, value: &DataValue, confidence: u8) -> Vec<u8> {
+            (ROUND_SIGNING_CONTEXT, data_key, round, value, confidence).encode()
+        }
+
+        /// Balance of the pallet account, which collects query fees and pays rewards
+        pub fn pallet_pot_balance() -> BalanceOf<T> {
+            T::Currency::free_balance(&Self::account_id())
+        }
+
+        /// Fund the pallet account with the existential deposit, so that fees below it can be
+        /// collected; payouts keep it alive from then on
+        pub fn endow_pot() {
+            let pot = Self::account_id();
+            let minimum = T::Currency::minimum_balance();
+            if T::Currency::free_balance(&pot) < minimum {
+                T::Currency::make_free_balance_be(&pot, minimum);
+            }
+        }
+
+        /// Source a contract publishes as
+        pub fn contract_source_id(contract: &T::AccountId) -> SourceId {
+            let mut source_id = b"contract:".to_vec();
+            source_id.extend_from_slice(&contract.encode());
+            source_id
+        }
+
+        /// Publish a value computed by an authorized contract into its designated data key
+        ///
+        /// Called by the runtime's chain extension on behalf of the calling contract. The
+        /// submission enters aggregation with the contract source's reliability as confidence.
+        pub fn publish_contract_value(
+            contract: &T::AccountId,
+            data_key: DataKey,
+            value: DataValue,
+        ) -> DispatchResult {
+            let (designated_key, source_id) =
+                <ContractFeeds<T>>::get(contract).ok_or(Error::<T>::ContractNotAuthorized)?;
+            ensure!(designated_key == data_key, Error::<T>::ContractNotAuthorized);
+
+            ensure!(value.len() <= T::MaxDataSize::get() as usize, Error::<T>::DataTooLarge);
+            if let Some(metadata) = <Feeds<T>>::get(&data_key) {
+                Self::validate_value(&metadata, &value)?;
+            }
+
+            let source = <DataSources<T>>::get(&source_id).ok_or(Error::<T>::SourceNotFound)?;
+            ensure!(source.active, Error::<T>::InvalidSource);
+
+            Self::store_submission(data_key, source_id, contract.clone(), value, source.reliability, None)
+        }
+
+        /// Check a submitted value's size, confidence and, for registered feeds, kind and precision
+        fn validate_submission(data_key: &DataKey, value: &DataValue, confidence: u8) -> DispatchResult {
+            ensure!(!data_key.starts_with(REMOTE_KEY_PREFIX), Error::<T>::ReservedDataKey);
+            ensure!(value.len() <= T::MaxDataSize::get() as usize, Error::<T>::DataTooLarge);
+            ensure!(confidence <= 100, Error::<T>::InvalidConfidence);
+
+            // Registered feeds only accept values of their kind and precision
+            if let Some(metadata) = <Feeds<T>>::get(data_key) {
+                Self::validate_value(&metadata, value)?;
+            }
+
+            Ok(())
+        }
+
+        /// Weight `provide_data` and `provide_critical_data` declare for a value of `len` bytes,
+        /// including the worst-case committee draw
+        pub fn provide_data_weight(len: u32) -> Weight {
+            T::WeightInfo::provide_data(len, T::MaxDataSources::get())
+                .saturating_add(T::WeightInfo::select_committee(T::MaxTrustedProviders::get()))
+        }
+
+        /// Store a submission of `who` through `provide_data` or `provide_critical_data`,
+        /// returning its weight and whether it pays
+        fn do_provide_data(
+            who: T::AccountId,
+            data_key: DataKey,
+            source: SourceId,
+            value: DataValue,
+            confidence: u8,
+            signature: Option<Vec<u8>>,
+        ) -> DispatchResultWithPostInfo {
+            Self::validate_submission(&data_key, &value, confidence)?;
+            let tier = Self::ensure_active_source(&source)?.tier;
+            let drawn = Self::ensure_may_submit(&who, &data_key)?;
+            Self::ensure_meets_tier(&who, tier)?;
+
+            // Reward provider (ultra-low to maintain sustainability)
+            let reward = Self::tier_reward(tier, 1);
+            let _ = T::Currency::transfer(&Self::account_id(), &who, reward, ExistenceRequirement::KeepAlive);
+            // Counted only if the submission is stored; failed ones roll this back and pay
+            let pays = if Self::provider_within_quota(&who) {
+                <FeelessSubmissions<T>>::mutate(|submissions| submissions.saturating_inc());
+                Pays::No
+            } else {
+                Pays::Yes
+            };
+            let weight = T::WeightInfo::provide_data(value.len() as u32, T::MaxDataSources::get());
+            let weight = weight.saturating_add(drawn);
+
+            Self::store_submission(data_key, source, who, value, confidence, signature)?;
+            Ok((Some(weight), pays).into())
+        }
+
+        /// Whether a successful `provide_data` call by `who` is refunded: it is a bonded trusted
+        /// provider and the block's quota is not used up
+        pub fn provider_within_quota(who: &T::AccountId) -> bool {
+            <TrustedProviders<T>>::contains_key(who)
+                && !<ProviderBonds<T>>::get(who).is_zero()
+                && <FeelessSubmissions<T>>::get() < T::MaxFeelessSubmissionsPerBlock::get()
+        }
+
+        /// Check `origin` is governance or the owner of the existing `source_id`
+        fn ensure_source_admin(origin: OriginFor<T>, source_id: &SourceId) -> Result<SourceAuthority, DispatchError> {
+            ensure!(<DataSources<T>>::contains_key(source_id), Error::<T>::SourceNotFound);
+            match ensure_signed_or_root(origin)? {
+                None => Ok(SourceAuthority::Governance),
+                Some(who) => {
+                    ensure!(<SourceOwners<T>>::get(source_id) == Some(who), Error::<T>::NotSourceOwner);
+                    Ok(SourceAuthority::Owner)
+                },
+            }
+        }
+
+        /// Check the source exists and is active, returning it
+        fn ensure_active_source(source: &SourceId) -> Result<DataSource, DispatchError> {
+            let source_info = <DataSources<T>>::get(source).ok_or(Error::<T>::SourceNotFound)?;
+            ensure!(source_info.active, Error::<T>::InvalidSource);
+            Ok(source_info)
+        }
+
+        /// Check `who` may submit into `data_key`, returning the weight of drawing the key's
+        /// committee if the check drew it
+        fn ensure_may_submit(who: &T::AccountId, data_key: &DataKey) -> Result<Weight, DispatchError> {
+            // In committee mode only the round's committee may submit
+            if <CommitteeMode<T>>::contains_key(data_key) {
+                let (committee, drawn) = Self::committee_with_weight(data_key);
+                ensure!(committee.contains(who), Error::<T>::NotInCommittee);
+                return Ok(drawn);
+            }
+
+            Ok(Weight::zero())
+        }
+
+        /// Check `who` meets the requirements of `tier` to submit through its sources
+        fn ensure_meets_tier(who: &T::AccountId, tier: SourceTier) -> DispatchResult {
+            let requirements = Self::tier_requirements(tier);
+            if let Some(min_reputation) = requirements.min_reputation {
+                let reputation = <TrustedProviders<T>>::get(who).ok_or(Error::<T>::ProviderNotTrusted)?;
+                ensure!(reputation >= min_reputation, Error::<T>::ReputationTooLow);
+            }
+            ensure!(<ProviderBonds<T>>::get(who) >= requirements.min_bond, Error::<T>::TierBondTooLow);
+            Ok(())
+        }
+
+        /// Requirements of `tier`: governance's override, or the runtime's
+        pub fn tier_requirements(tier: SourceTier) -> TierRequirements<BalanceOf<T>> {
+            <TierRequirementOverrides<T>>::get(tier).unwrap_or_else(|| match tier {
+                SourceTier::Open => T::OpenTier::get(),
+                SourceTier::Verified => T::VerifiedTier::get(),
+                SourceTier::Critical => T::CriticalTier::get(),
+            })
+        }
+
+        /// Reward for `count` submissions through sources of `tier`
+        fn tier_reward(tier: SourceTier, count: u32) -> BalanceOf<T> {
+            Self::tier_requirements(tier)
+                .reward_multiplier
+                .saturating_mul_int(T::OracleReward::get().saturating_mul(count.into()))
+        }
+
+        /// Store a validated submission, index it and try to aggregate its key
+        ///
+        /// Every submission path ends here, so this is where a paused oracle rejects them.
+        fn store_submission(
+            data_key: DataKey,
+            source: SourceId,
+            provider: T::AccountId,
+            value: DataValue,
+            confidence: u8,
+            signature: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            ensure!(!T::Safeguard::is_paused(Subsystem::Oracle), Error::<T>::SystemPaused);
+
+            // Create oracle data entry
+            let oracle_data = OracleData {
+                value: value.clone(),
+                provider: provider.clone(),
+                timestamp: frame_system::Pallet::<T>::block_number(),
+                source: source.clone(),
+                confidence,
+                signature,
+            };
+
+            // Store data
+            <OracleDataStorage<T>>::insert(&data_key, &source, &oracle_data);
+            Self::record_sla_delivery(&data_key, &source);
+
+            // Index the update under its data key
+            T::History::record(HistoryKind::OracleUpdate, &provider, &data_key, BlakeTwo256::hash(&value));
+
+            // Emit event
+            Self::deposit_event(Event::DataProvided {
+                data_key: data_key.clone(),
+                source,
+                provider,
+                value,
+                confidence,
+            });
+
+            // Try to aggregate data if enough sources
+            Self::try_aggregate_data(&data_key)
+        }
+
+        /// Start tracking the SLA of a premium request
+        ///
+        /// The SLA asks for `PremiumMinSources` distinct assigned sources, or all of them if
+        /// fewer were assigned, and is settled at the start of the block after its deadline.
+        fn open_premium_sla(request_id: RequestId, request: &OracleRequest<T::AccountId, BlockNumberFor<T>>) {
+            let assigned = request.sources.iter().collect::<BTreeSet<_>>().len() as u32;
+            let min_sources = T::PremiumMinSources::get().min(assigned);
+            if min_sources == 0 {
+                return;
+            }
+
+            let deadline = request.requested_at.saturating_add(T::PremiumMaxLatency::get());
+            <PremiumSlas<T>>::insert(request_id, PremiumSla { min_sources, deadline, delivered: Vec::new() });
+            <OpenSlas<T>>::insert(&request.data_key, request_id, ());
+            <SlaExpiries<T>>::append(deadline.saturating_add(1u32.into()), request_id);
+        }
+
+        /// Store a new request, opening its SLA if premium and scheduling it for pruning
+        fn store_request(request_id: RequestId, request: &OracleRequest<T::AccountId, BlockNumberFor<T>>) {
+            <OracleRequests<T>>::insert(request_id, request);
+            if request.premium {
+                Self::open_premium_sla(request_id, request);
+            }
+            <RequestExpiries<T>>::append(request.requested_at.saturating_add(T::RequestTtl::get()), request_id);
+        }
+
+        /// Count `count` requests against the quota of `who` for the current request era
+        ///
+        /// Accounts with a provider bond of at least `QuotaExemptBond` are not counted.
+        fn consume_request_quota(who: &T::AccountId, count: u32) -> DispatchResult {
+            if <ProviderBonds<T>>::get(who) >= T::QuotaExemptBond::get() {
+                return Ok(());
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let era_length = T::RequestEraLength::get().max(1u32.into());
+            let era = now.saturating_sub(now % era_length);
+            <RequestQuotas<T>>::try_mutate(who, |(quota_era, used)| {
+                if *quota_era != era {
+                    *quota_era = era;
+                    *used = 0;
+                }
+                *used = used.saturating_add(count);
+                ensure!(*used <= T::MaxRequestsPerEra::get(), Error::<T>::QuotaExceeded);
+                Ok(())
+            })
+        }
+
+        /// Remove a request whose `RequestTtl` passed, settling its SLA first if still open
+        ///
+        /// Returns the weight used.
+        fn prune_request(request_id: RequestId) -> Weight {
+            let mut weight = T::WeightInfo::prune_request();
+            if <PremiumSlas<T>>::contains_key(request_id) {
+                Self::settle_expired_sla(request_id);
+                weight.saturating_accrue(T::WeightInfo::settle_premium_sla());
+            }
+            let Some(request) = <OracleRequests<T>>::take(request_id) else {
+                return weight;
+            };
+
+            let fulfilled = <AggregatedDataStorage<T>>::get(&request.data_key)
+                .map_or(false, |aggregated| aggregated.aggregated_at >= request.requested_at);
+            Self::deposit_event(Event::RequestPruned { request_id, fulfilled });
+            weight
+        }
+
+        /// Count a submission towards the open premium requests that assigned its source
+        fn record_sla_delivery(data_key: &DataKey, source: &SourceId) {
+            let open: Vec<RequestId> = <OpenSlas<T>>::iter_key_prefix(data_key).collect();
+            for request_id in open {
+                let Some(request) = <OracleRequests<T>>::get(request_id) else {
+                    continue;
+                };
+                let Some(mut sla) = <PremiumSlas<T>>::get(request_id) else {
+                    continue;
+                };
+                if !request.sources.contains(source) || sla.delivered.contains(source) {
+                    continue;
+                }
+
+                sla.delivered.push(source.clone());
+                let delivered = sla.delivered.len() as u32;
+                if delivered >= sla.min_sources {
+                    <PremiumSlas<T>>::remove(request_id);
+                    <OpenSlas<T>>::remove(data_key, request_id);
+                    Self::deposit_event(Event::PremiumSlaMet { request_id, delivered });
+                } else {
+                    <PremiumSlas<T>>::insert(request_id, sla);
+                }
+            }
+        }
+
+        /// Settle a premium request whose deadline passed without its SLA being met
+        ///
+        /// The requester gets back what it paid over the basic fee, and every assigned source
+        /// that did not deliver loses `SlaReliabilityPenalty` reliability. Met SLAs were
+        /// already removed and are skipped.
+        fn settle_expired_sla(request_id: RequestId) {
+            let Some(sla) = <PremiumSlas<T>>::take(request_id) else {
+                return;
+            };
+            let Some(request) = <OracleRequests<T>>::get(request_id) else {
+                return;
+            };
+            <OpenSlas<T>>::remove(&request.data_key, request_id);
+
+            let premium = T::PremiumQueryFee::get().saturating_sub(T::OracleQueryFee::get());
+            let refund = match T::Currency::transfer(
+                &Self::account_id(),
+                &request.requester,
+                premium,
+                ExistenceRequirement::KeepAlive,
+            ) {
+                Ok(()) => premium,
+                Err(_) => Zero::zero(),
+            };
+
+            let penalty = T::SlaReliabilityPenalty::get();
+            let mut missing: Vec<SourceId> = Vec::new();
+            for source in request.sources {
+                if sla.delivered.contains(&source) || missing.contains(&source) {
+                    continue;
+                }
+                <DataSources<T>>::mutate(&source, |info| {
+                    if let Some(info) = info {
+                        info.reliability = info.reliability.saturating_sub(penalty);
+                    }
+                });
+                missing.push(source);
+            }
+
+            log::debug!(
+                target: LOG_TARGET,
+                "premium request {} for {} missed its SLA, {} sources did not deliver",
+                request_id,
+                Printable(&request.data_key),
+                missing.len(),
+            );
+            Self::deposit_event(Event::PremiumSlaMissed { request_id, refund, missing });
+        }
+
+        /// Flag a watched feed whose latest aggregate is missing or older than `MaxDataAge`
+        ///
+        /// A feed is flagged once until it aggregates again: `FeedStale` is emitted and, if
+        /// the key is in committee mode, every member of its current committee loses up to
+        /// `slash` of its bond. Returns the weight of drawing that committee if the check drew it.
+        fn check_watched_feed(data_key: DataKey, slash: BalanceOf<T>) -> Weight {
+            if <StaleFeeds<T>>::contains_key(&data_key) {
+                return Weight::zero();
+            }
+            let now = frame_system::Pallet::<T>::block_number();
+            let last_updated = <AggregatedDataStorage<T>>::get(&data_key).map(|aggregated| aggregated.aggregated_at);
+            let fresh = last_updated.map_or(false, |at| {
+                now.saturating_sub(at).saturated_into::<u64>() <= T::MaxDataAge::get()
+            });
+            if fresh {
+                return Weight::zero();
+            }
+
+            <StaleFeeds<T>>::insert(&data_key, now);
+            log::warn!(
+                target: LOG_TARGET,
+                "{}: watched feed is stale, last aggregated at {:?}",
+                Printable(&data_key),
+                last_updated,
+            );
+            Self::deposit_event(Event::FeedStale { data_key: data_key.clone(), last_updated });
+
+            if slash.is_zero() || !<CommitteeMode<T>>::contains_key(&data_key) {
+                return Weight::zero();
+            }
+            let (committee, drawn) = Self::committee_with_weight(&data_key);
+            for provider in committee {
+                let bond = <ProviderBonds<T>>::get(&provider);
+                let target = slash.min(bond);
+                if target.is_zero() {
+                    continue;
+                }
+                let (_, unslashed) = T::Currency::slash_reserved(&provider, target);
+                let amount = target.saturating_sub(unslashed);
+                <ProviderBonds<T>>::insert(&provider, bond.saturating_sub(amount));
+                Self::deposit_event(Event::ProviderSlashed { provider, data_key: data_key.clone(), amount });
+            }
+            drawn
+        }
+
+        /// Try to aggregate data from multiple sources
+        fn try_aggregate_data(data_key: &DataKey) -> DispatchResult {
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "aggregate");
+            let min_sources = T::MinAggregationSources::get();
+            let mut submissions = Vec::new();
+            let mut total_confidence = 0u32;
+            let mut source_count = 0u32;
+
+            // Collect data from all sources for this key
+            for (_source_id, oracle_data) in <OracleDataStorage<T>>::iter_prefix(data_key) {
+                total_confidence = total_confidence.saturating_add(oracle_data.confidence as u32);
+                source_count = source_count.saturating_add(1);
+                submissions.push(oracle_data);
+            }
+
+            // Only aggregate if we have enough sources
+            if source_count >= min_sources {
+                let Some(aggregated_value) = Self::aggregate_value(data_key, &submissions) else {
+                    log::debug!(
+                        target: LOG_TARGET,
+                        "{}: the aggregation strategy found no value among {} submissions",
+                        Printable(data_key),
+                        source_count,
+                    );
+                    return Ok(());
+                };
+                let average_confidence = (total_confidence / source_count) as u8;
+                let data_points = submissions.into_iter().map(|submission| submission.value).collect();
+
+                // Create aggregated data
+                Self::publish_aggregate(data_key, Self::new_aggregate(
+                    aggregated_value,
+                    source_count,
+                    average_confidence,
+                    data_points,
+                ));
+            }
+
+            Ok(())
+        }
+
+        /// Store `value`, aggregated by chain `chain_id` for its `data_key`, under
+        /// [`remote_data_key`] as of the current block, returning the key
+        ///
+        /// Used by IBC applications delivering other chains' feeds; the caller vouches for
+        /// `chain_id`.
+        pub fn publish_remote(
+            chain_id: &[u8],
+            data_key: &[u8],
+            value: DataValue,
+            source_count: u32,
+            confidence: u8,
+        ) -> Result<DataKey, DispatchError> {
+            ensure!(value.len() <= T::MaxDataSize::get() as usize, Error::<T>::DataTooLarge);
+            ensure!(confidence <= 100, Error::<T>::InvalidConfidence);
+
+            let key = remote_data_key(chain_id, data_key);
+            Self::publish_aggregate(&key, Self::new_aggregate(value.clone(), source_count, confidence, sp_std::vec![value]));
+
+            Ok(key)
+        }
+
+        /// Aggregate of the current block, readable after `MinAggregationDelay` and fresh for
+        /// `MaxDataAge`
+        fn new_aggregate(
+            value: DataValue,
+            source_count: u32,
+            confidence: u8,
+            data_points: Vec<DataValue>,
+        ) -> AggregatedData<BlockNumberFor<T>> {
+            let now = frame_system::Pallet::<T>::block_number();
+            AggregatedData {
+                value,
+                source_count,
+                confidence,
+                aggregated_at: now,
+                data_points,
+                valid_from: now.saturating_add(T::MinAggregationDelay::get()),
+                valid_until: now.saturating_add(T::MaxDataAge::get().saturated_into()),
+            }
+        }
+
+        /// Publish `aggregated` as the value of `data_key`, at once or, with a
+        /// `MinAggregationDelay`, once it is over
+        fn publish_aggregate(data_key: &DataKey, aggregated: AggregatedData<BlockNumberFor<T>>) {
+            log::debug!(
+                target: LOG_TARGET,
+                "{}: aggregated {} from {} sources, confidence {}, readable from {:?}",
+                Printable(data_key),
+                Printable(&aggregated.value),
+                aggregated.source_count,
+                aggregated.confidence,
+                aggregated.valid_from,
+            );
+            let event = Event::DataAggregated {
+                data_key: data_key.clone(),
+                value: aggregated.value.clone(),
+                source_count: aggregated.source_count,
+                confidence: aggregated.confidence,
+            };
+
+            if aggregated.valid_from > aggregated.aggregated_at {
+                <PendingAggregates<T>>::insert(aggregated.valid_from, data_key, aggregated);
+            } else {
+                Self::make_readable(data_key, aggregated);
+            }
+            Self::deposit_event(event);
+        }
+
+        /// Publish an aggregate whose `MinAggregationDelay` is over, unless a newer one of the
+        /// key is already readable
+        fn finalize_aggregate(data_key: DataKey, aggregated: AggregatedData<BlockNumberFor<T>>) {
+            let superseded = <AggregatedDataStorage<T>>::get(&data_key)
+                .map_or(false, |readable| readable.aggregated_at > aggregated.aggregated_at);
+            if superseded {
+                return;
+            }
+            let aggregated_at = aggregated.aggregated_at;
+            Self::make_readable(&data_key, aggregated);
+            Self::deposit_event(Event::AggregateReadable { data_key, aggregated_at });
+        }
+
+        /// Store `aggregated` as the value of `data_key` and append it to the key's history
+        fn make_readable(data_key: &DataKey, aggregated: AggregatedData<BlockNumberFor<T>>) {
+            if <StaleFeeds<T>>::take(data_key).is_some() {
+                log::info!(target: LOG_TARGET, "{}: stale feed recovered", Printable(data_key));
+                Self::deposit_event(Event::FeedRecovered { data_key: data_key.clone() });
+            }
+            Self::record_observation(data_key, Observation {
+                value: aggregated.value.clone(),
+                confidence: aggregated.confidence,
+                aggregated_at: aggregated.aggregated_at,
+            });
+            <AggregatedDataStorage<T>>::insert(data_key, aggregated);
+        }
+
+        /// Combine `submissions` for `data_key` by its [`AggregationStrategy`]
+        ///
+        /// `None` if the strategy finds no value: no majority for `Mode`, or a value that is
+        /// not numeric for the numeric strategies. Numeric results are rounded down to the
+        /// feed's decimals.
+        fn aggregate_value(
+            data_key: &DataKey,
+            submissions: &[OracleData<T::AccountId, BlockNumberFor<T>>],
+        ) -> Option<DataValue> {
+            let values = submissions.iter().map(|submission| &submission.value);
+            let strategy = match <AggregationStrategies<T>>::get(data_key) {
+                None => return values.cloned().next(),
+                Some(AggregationStrategy::Latest) => {
+                    let latest = submissions.iter().max_by_key(|submission| submission.timestamp)?;
+                    return Some(latest.value.clone());
+                },
+                Some(AggregationStrategy::Mode) => {
+                    let majority = values.clone().find(|value| {
+                        values.clone().filter(|other| other == value).count() * 2 > submissions.len()
+                    });
+                    return majority.cloned();
+                },
+                Some(strategy) => strategy,
+            };
+
+            let decimals = <Feeds<T>>::get(data_key)?.decimals;
+            let mut numbers = submissions
+                .iter()
+                .map(|submission| match TypedValue::decode_all(&mut &submission.value[..]) {
+                    Ok(TypedValue::Numeric(number)) => Some(number.into_inner()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<u128>>>()?;
+            if numbers.is_empty() {
+                return None;
+            }
+
+            let mean = |weighted: &[(u128, u128)]| -> u128 {
+                let total_weight = weighted.iter().fold(0u128, |total, (_, weight)| total.saturating_add(*weight));
+                let total = weighted
+                    .iter()
+                    .fold(0u128, |total, (number, weight)| total.saturating_add(number.saturating_mul(*weight)));
+                total / total_weight.max(1)
+            };
+            let aggregated = match strategy {
+                AggregationStrategy::Median => {
+                    numbers.sort();
+                    let mid = numbers.len() / 2;
+                    if numbers.len() % 2 == 0 {
+                        mean(&[(numbers[mid - 1], 1), (numbers[mid], 1)])
+                    } else {
+                        numbers[mid]
+                    }
+                },
+                AggregationStrategy::WeightedByReliability => {
+                    let weights: Vec<u128> = submissions
+                        .iter()
+                        .map(|submission| {
+                            <DataSources<T>>::get(&submission.source).map_or(0, |source| source.reliability as u128)
+                        })
+                        .collect();
+                    // Sources without reliability count equally rather than not at all
+                    let weights = if weights.iter().all(Zero::is_zero) {
+                        weights.iter().map(|_| 1).collect()
+                    } else {
+                        weights
+                    };
+                    mean(&numbers.iter().copied().zip(weights).collect::<Vec<_>>())
+                },
+                _ => mean(&numbers.iter().map(|number| (*number, 1)).collect::<Vec<_>>()),
+            };
+
+            let step = FixedU128::DIV / 10u128.pow(decimals as u32);
+            Some(TypedValue::Numeric(FixedU128::from_inner(aggregated - aggregated % step)).encode())
+        }
+
+        /// Current oracle round
+        pub fn current_round() -> RoundIndex {
+            let now = frame_system::Pallet::<T>::block_number().saturated_into::<u64>();
+            now / T::RoundLength::get().saturated_into::<u64>().max(1)
+        }
+
+        /// Committee of the current round for a key, drawn on first use in the round
+        pub fn current_committee(data_key: &DataKey) -> BoundedVec<T::AccountId, T::CommitteeSize> {
+            Self::committee_with_weight(data_key).0
+        }
+
+        /// [`Self::current_committee`], with the weight of drawing it if this call drew it
+        fn committee_with_weight(data_key: &DataKey) -> (BoundedVec<T::AccountId, T::CommitteeSize>, Weight) {
+            let round = Self::current_round();
+            if let Some(committee) = <Committees<T>>::get(data_key, round) {
+                return (committee, Weight::zero());
+            }
+
+            let (committee, candidates) = Self::select_committee(data_key, round);
+            // Committees of earlier rounds are dropped, however many rounds went by without one
+            let _ = <Committees<T>>::clear_prefix(data_key, u32::MAX, None);
+            <Committees<T>>::insert(data_key, round, &committee);
+
+            Self::deposit_event(Event::CommitteeSelected {
+                data_key: data_key.clone(),
+                round,
+                members: committee.to_vec(),
+            });
+
+            (committee, T::WeightInfo::select_committee(candidates))
+        }
+
+        /// Draw `CommitteeSize` trusted providers with a partial Fisher-Yates shuffle
+        /// seeded by the randomness for this key and round, returning the committee and the
+        /// number of candidates it was drawn from
+        ///
+        /// At most `MaxTrustedProviders` candidates are read, should a migration have left more.
+        fn select_committee(
+            data_key: &DataKey,
+            round: RoundIndex,
+        ) -> (BoundedVec<T::AccountId, T::CommitteeSize>, u32) {
+            let mut candidates: Vec<T::AccountId> =
+                <TrustedProviders<T>>::iter_keys().take(T::MaxTrustedProviders::get() as usize).collect();
+            // Storage iteration order depends on hashed keys; sort so the draw only depends on the seed
+            candidates.sort();
+
+            let (seed, _) = T::Randomness::random(&(b"oracle/committee", data_key, round).encode());
+            let size = (T::CommitteeSize::get() as usize).min(candidates.len());
+            for i in 0..size {
+                let draw = BlakeTwo256::hash_of(&(seed, i as u32));
+                let draw = u64::decode(&mut TrailingZeroInput::new(draw.as_ref())).unwrap_or_default();
+                let j = i + (draw % (candidates.len() - i) as u64) as usize;
+                candidates.swap(i, j);
+            }
+            let candidate_count = candidates.len() as u32;
+            candidates.truncate(size);
+
+            (BoundedVec::truncate_from(candidates), candidate_count)
+        }
+
+        /// Decode `value` as a `TypedValue` and check it against the feed's metadata
+        pub fn validate_value(metadata: &FeedMetadata, value: &[u8]) -> Result<TypedValue, DispatchError> {
+            let typed = TypedValue::decode_all(&mut &value[..]).map_err(|_| Error::<T>::MalformedValue)?;
+
+            match (&typed, metadata.kind) {
+                (TypedValue::Numeric(number), ValueKind::Numeric) => {
+                    let step = FixedU128::DIV / 10u128.pow(metadata.decimals as u32);
+                    ensure!(number.into_inner() % step == 0, Error::<T>::ValueExceedsPrecision);
+                },
+                (TypedValue::Bool(_), ValueKind::Bool) | (TypedValue::Bytes(_), ValueKind::Bytes) => {},
+                _ => return Err(Error::<T>::ValueTypeMismatch.into()),
+            }
+
+            Ok(typed)
+        }
+
+        /// Latest aggregated value of a registered feed, with the feed's metadata
+        pub fn get_typed_data(data_key: &DataKey) -> Option<(TypedValue, FeedMetadata)> {
+            let metadata = <Feeds<T>>::get(data_key)?;
+            let value = Self::get_latest_data(data_key)?;
+            let typed = TypedValue::decode_all(&mut &value[..]).ok()?;
+            Some((typed, metadata))
+        }
+
+        /// Append an observation to the key's history, overwriting the oldest once full, and
+        /// mirror it to the off-chain history
+        fn record_observation(data_key: &DataKey, observation: Observation<BlockNumberFor<T>>) {
+            let index = <ObservationCount<T>>::get(data_key);
+            <ObservationCount<T>>::insert(data_key, index.saturating_add(1));
+            Self::mirror_observation(data_key, index, &observation);
+
+            let depth = T::HistoryDepth::get();
+            if depth > 0 {
+                <ObservationHistory<T>>::insert(data_key, (index % depth as u64) as u32, observation);
+            }
+        }
+
+        /// Write observation `index` of `data_key` to the off-chain history, journaled in the
+        /// current block, and make it the key's newest there
+        fn mirror_observation(data_key: &DataKey, index: u64, observation: &Observation<BlockNumberFor<T>>) {
+            let block = frame_system::Pallet::<T>::block_number().saturated_into::<u32>();
+            let n = <HistoryIndexed<T>>::mutate(|n| {
+                let current = *n;
+                *n = n.saturating_add(1);
+                current
+            });
+
+            let key = offchain_history::oracle_round_key(data_key, index);
+            sp_io::offchain_index::set(&key, &Indexed { block, item: observation }.encode());
+            sp_io::offchain_index::set(&offchain_history::journal_key(Table::OracleRounds, block, n), &key);
+            sp_io::offchain_index::set(&offchain_history::oracle_latest_key(data_key), &index.encode());
+        }
+
+        /// Up to `count` most recent aggregated values for a key, newest first
+        pub fn get_history(data_key: &DataKey, count: u32) -> Vec<Observation<BlockNumberFor<T>>> {
+            let depth = T::HistoryDepth::get() as u64;
+            if depth == 0 {
+                return Vec::new();
+            }
+
+            let total = <ObservationCount<T>>::get(data_key);
+            let retained = total.min(depth).min(count as u64);
+            (total - retained..total)
+                .rev()
+                .filter_map(|index| <ObservationHistory<T>>::get(data_key, (index % depth) as u32))
+                .collect()
+        }
+
+        /// Get latest oracle data for a key (public interface)
+        pub fn get_latest_data(data_key: &DataKey) -> Option<DataValue> {
+            <AggregatedDataStorage<T>>::get(data_key).map(|data| data.value)
+        }
+
+        /// Get data with confidence score
+        pub fn get_data_with_confidence(data_key: &DataKey) -> Option<(DataValue, u8)> {
+            <AggregatedDataStorage<T>>::get(data_key).map(|data| (data.value, data.confidence))
+        }
+
+        /// Aggregate of `data_key` currently readable, and the blocks it is valid between
+        pub fn get_aggregate(data_key: &DataKey) -> Option<AggregatedData<BlockNumberFor<T>>> {
+            <AggregatedDataStorage<T>>::get(data_key)
+        }
+    }
 }
 
-/// Default weights (based on complexity analysis)
-impl WeightInfo for () {
-    fn request_data() -> Weight { Weight::from_parts(60_000, 0) }
-    fn provide_data() -> Weight { Weight::from_parts(100_000, 0) }
-    fn register_source() -> Weight { Weight::from_parts(40_000, 0) }
-    fn add_trusted_provider() -> Weight { Weight::from_parts(30_000, 0) }
-    fn batch_requests() -> Weight { Weight::from_parts(200_000, 0) }
-    fn cleanup_expired_data() -> Weight { Weight::from_parts(150_000, 0) }
-}
\ No newline at end of file
+/// Prices are the aggregated values of feeds registered as [`ValueKind::Numeric`]
+///
+/// Watched feeds flagged stale and aggregates past their `valid_until` have no price, so
+/// consumers fall back until they recover.
+impl<T: Config> PriceProvider<BlockNumberFor<T>> for Pallet<T> {
+    fn price(asset: &DataKey) -> Option<(FixedU128, BlockNumberFor<T>)> {
+        if <StaleFeeds<T>>::contains_key(asset) {
+            return None;
+        }
+        let metadata = <Feeds<T>>::get(asset)?;
+        if metadata.kind != ValueKind::Numeric {
+            return None;
+        }
+        let aggregated = <AggregatedDataStorage<T>>::get(asset)?;
+        if frame_system::Pallet::<T>::block_number() > aggregated.valid_until {
+            return None;
+        }
+        match TypedValue::decode_all(&mut &aggregated.value[..]).ok()? {
+            TypedValue::Numeric(price) => Some((price, aggregated.aggregated_at)),
+            _ => None,
+        }
+    }
+}
+
+/// Runtime API for oracle consumers
+pub mod runtime_api {
+    use super::*;
+    use codec::Codec;
+
+    sp_api::decl_runtime_apis! {
+        /// API for querying oracle data
+        pub trait OracleApi<BlockNumber, Balance>
+        where
+            BlockNumber: Codec,
+            Balance: Codec,
+        {
+            /// Up to `count` most recent aggregated values for `data_key`, newest first
+            fn get_history(data_key: DataKey, count: u32) -> Vec<Observation<BlockNumber>>;
+
+            /// Metadata of the feed registered for `data_key`
+            fn feed_metadata(data_key: DataKey) -> Option<FeedMetadata>;
+
+            /// Latest aggregated value of the feed registered for `data_key`, decoded
+            fn get_typed_data(data_key: DataKey) -> Option<(TypedValue, FeedMetadata)>;
+
+            /// Readable aggregate of `data_key`, with the blocks it is readable from
+            /// (`valid_from`) and fresh until (`valid_until`)
+            fn get_aggregate(data_key: DataKey) -> Option<AggregatedData<BlockNumber>>;
+
+            /// Balance of the oracle pallet account paying provider rewards
+            fn pallet_pot_balance() -> Balance;
+        }
+    }
+}
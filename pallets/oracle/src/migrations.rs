@@ -0,0 +1,160 @@
+//! Storage migrations for the oracle pallet
+
+use super::*;
+
+/// Version 1: aggregates carry the blocks they are readable from and fresh until
+pub mod v1 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Aggregated data as stored up to version 0
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldAggregatedData<BlockNumber> {
+        pub value: DataValue,
+        pub source_count: u32,
+        pub confidence: u8,
+        pub aggregated_at: BlockNumber,
+        pub data_points: Vec<DataValue>,
+    }
+
+    impl<BlockNumber: Copy + Saturating> OldAggregatedData<BlockNumber> {
+        fn upgrade(self, max_age: BlockNumber) -> AggregatedData<BlockNumber> {
+            AggregatedData {
+                value: self.value,
+                source_count: self.source_count,
+                confidence: self.confidence,
+                aggregated_at: self.aggregated_at,
+                data_points: self.data_points,
+                valid_from: self.aggregated_at,
+                valid_until: self.aggregated_at.saturating_add(max_age),
+            }
+        }
+    }
+
+    /// Give every stored aggregate its validity
+    ///
+    /// Version 0 published aggregates at once, so each was readable from the block it was
+    /// aggregated in, and stays fresh for `MaxDataAge` from there.
+    pub struct InnerAddAggregateValidity<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerAddAggregateValidity<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let max_age: BlockNumberFor<T> = T::MaxDataAge::get().saturated_into();
+            let mut translated = 0u64;
+
+            <AggregatedDataStorage<T>>::translate::<OldAggregatedData<BlockNumberFor<T>>, _>(|_, old| {
+                translated += 1;
+                Some(old.upgrade(max_age))
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+    }
+
+    /// [`InnerAddAggregateValidity`], run once when upgrading from storage version 0
+    pub type AddAggregateValidity<T> = VersionedMigration<
+        0,
+        1,
+        InnerAddAggregateValidity<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 2: data sources have a tier
+pub mod v2 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Data source as stored up to version 1
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldDataSource {
+        pub id: SourceId,
+        pub name: Vec<u8>,
+        pub endpoint: Vec<u8>,
+        pub reliability: u8,
+        pub active: bool,
+    }
+
+    impl OldDataSource {
+        fn upgrade(self) -> DataSource {
+            DataSource {
+                id: self.id,
+                name: self.name,
+                endpoint: self.endpoint,
+                reliability: self.reliability,
+                active: self.active,
+                tier: SourceTier::Open,
+            }
+        }
+    }
+
+    /// Put every data source in the `Open` tier
+    ///
+    /// Up to version 1 any account could submit through any source, only confidences over 80
+    /// being reserved to trusted providers. Sources stay open to everyone, as newly registered
+    /// ones are, until governance moves them to a stricter tier with `set_source_tier`.
+    pub struct InnerAddSourceTiers<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerAddSourceTiers<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            <DataSources<T>>::translate::<OldDataSource, _>(|_, old| {
+                translated += 1;
+                Some(old.upgrade())
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+    }
+
+    /// [`InnerAddSourceTiers`], run once when upgrading from storage version 1
+    pub type AddSourceTiers<T> = VersionedMigration<
+        1,
+        2,
+        InnerAddSourceTiers<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 3: trusted providers are counted, so they can be bounded by `MaxTrustedProviders`
+pub mod v3 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::marker::PhantomData;
+
+    /// Count the trusted providers already registered
+    ///
+    /// Providers beyond `MaxTrustedProviders` are kept, since governance trusted them, but no
+    /// more can be added, and committees are drawn from the first `MaxTrustedProviders` of them.
+    pub struct InnerCountTrustedProviders<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerCountTrustedProviders<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let count = <TrustedProviders<T>>::initialize_counter();
+            if count > T::MaxTrustedProviders::get() {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "{} trusted providers exceed MaxTrustedProviders ({})",
+                    count,
+                    T::MaxTrustedProviders::get(),
+                );
+            }
+
+            T::DbWeight::get().reads_writes(count.into(), 1)
+        }
+    }
+
+    /// [`InnerCountTrustedProviders`], run once when upgrading from storage version 2
+    pub type CountTrustedProviders<T> = VersionedMigration<
+        2,
+        3,
+        InnerCountTrustedProviders<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
@@ -0,0 +1,347 @@
+//! Weights for pallet_oracle
+//!
+//! Submissions are linear in the value size `d` (bytes), which is stored, hashed into the
+//! history index and copied into the aggregate, and in the number `s` of sources already
+//! submitted for the key, which aggregation reads back. Requests are linear in the number
+//! of sources `s` they name; batches also in their number of requests `n`. A submission
+//! batch pays the source lookup and reward transfer once, so each of its `n` entries costs
+//! less than a `provide_data` call. A committee draw is linear in the number `p` of trusted
+//! providers it reads.
+//!
+//! All figures are estimates, not benchmark results: each counts the storage accesses of its
+//! extrinsic on top of a fixed execution estimate. `request_data`, `provide_data`,
+//! `provide_data_batch` and `batch_requests` have benchmarks in `benchmarking.rs`, but no run
+//! on reference hardware has replaced their estimates yet. To do so, run
+//! `netchain-node benchmark pallet --chain dev --pallet pallet_oracle --extrinsic '*'
+//! --steps 50 --repeat 20` with the `runtime-benchmarks` feature and copy the figures over.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_oracle.
+pub trait WeightInfo {
+	fn request_data(s: u32) -> Weight;
+	fn provide_data(d: u32, s: u32) -> Weight;
+	fn provide_data_batch(n: u32, d: u32, s: u32) -> Weight;
+	fn register_source() -> Weight;
+	fn add_trusted_provider() -> Weight;
+	fn batch_requests(n: u32, s: u32) -> Weight;
+	fn cleanup_expired_data() -> Weight;
+	fn register_feed() -> Weight;
+	fn set_committee_mode() -> Weight;
+	fn authorize_contract_feed() -> Weight;
+	fn settle_premium_sla() -> Weight;
+	fn set_feed_watch() -> Weight;
+	fn bond_provider() -> Weight;
+	fn unbond_provider() -> Weight;
+	fn check_watched_feed() -> Weight;
+	fn set_source_owner() -> Weight;
+	fn update_source() -> Weight;
+	fn suspend_source() -> Weight;
+	fn resume_source() -> Weight;
+	fn retire_source() -> Weight;
+	fn set_aggregation_strategy() -> Weight;
+	fn set_signing_key() -> Weight;
+	fn submit_signed_round(n: u32, d: u32) -> Weight;
+	fn set_feed_critical() -> Weight;
+	fn prune_request() -> Weight;
+	fn finalize_aggregate() -> Weight;
+	fn set_source_tier() -> Weight;
+	fn set_tier_requirements() -> Weight;
+	fn select_committee(p: u32) -> Weight;
+	fn dispute_aggregate() -> Weight;
+}
+
+/// Weights for pallet_oracle using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: Oracle ProviderBonds (r:1 w:0), Oracle RequestQuotas (r:1 w:1), System Account (r:2 w:2),
+	/// Oracle NextRequestId (r:1 w:1), Oracle OracleRequests (r:0 w:1), Oracle PremiumSlas (r:0 w:1),
+	/// Oracle OpenSlas (r:0 w:1), Oracle SlaExpiries (r:1 w:1), Oracle RequestExpiries (r:1 w:1)
+	/// The range of component `s` is `[0, 10]`.
+	fn request_data(s: u32) -> Weight {
+		Weight::from_parts(41_000_000, 6_196)
+			.saturating_add(Weight::from_parts(420_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	/// Storage: Oracle Feeds (r:1 w:0), Oracle DataSources (r:1 w:0), Oracle CommitteeMode (r:1 w:0),
+	/// System Account (r:2 w:2), Oracle OracleDataStorage (r:s w:1), Oracle OpenSlas (r:1 w:0),
+	/// History (r:1 w:2), Oracle AggregatedDataStorage (r:0 w:1), Oracle ObservationCount (r:1 w:1),
+	/// Oracle ObservationHistory (r:0 w:1), Oracle CriticalFeeds (r:1 w:0), Oracle CriticalWeightUsed (r:1 w:1),
+	/// Oracle TierRequirementOverrides (r:1 w:0), Oracle TrustedProviders (r:1 w:0), Oracle ProviderBonds (r:1 w:0)
+	/// The range of component `d` is `[0, 1024]`.
+	/// The range of component `s` is `[0, 10]`.
+	fn provide_data(d: u32, s: u32) -> Weight {
+		Weight::from_parts(62_000_000, 6_196)
+			.saturating_add(Weight::from_parts(3_400, 0).saturating_mul(d.into()))
+			.saturating_add(Weight::from_parts(2_900_000, 2_560).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(13_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	/// Storage: Oracle DataSources (r:1 w:0), System Account (r:2 w:2), Oracle TrustedProviders (r:n w:0),
+	/// Oracle Feeds (r:n w:0), Oracle CommitteeMode (r:n w:0), Oracle OracleDataStorage (r:n*s w:n),
+	/// Oracle OpenSlas (r:n w:0), History (r:n w:2n), Oracle AggregatedDataStorage (r:0 w:n),
+	/// Oracle ObservationCount (r:n w:n), Oracle ObservationHistory (r:0 w:n),
+	/// Oracle TierRequirementOverrides (r:1 w:0), Oracle TrustedProviders (r:1 w:0), Oracle ProviderBonds (r:1 w:0)
+	/// The range of component `n` is `[1, 32]`.
+	/// The range of component `d` is `[0, 1024]`.
+	/// The range of component `s` is `[0, 10]`.
+	fn provide_data_batch(n: u32, d: u32, s: u32) -> Weight {
+		Weight::from_parts(36_000_000, 6_196)
+			.saturating_add(Weight::from_parts(27_000_000, 2_560).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(3_400, 0).saturating_mul(n.saturating_mul(d).into()))
+			.saturating_add(Weight::from_parts(2_900_000, 2_560).saturating_mul(n.saturating_mul(s).into()))
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().reads((6_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.saturating_mul(s).into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((7_u64).saturating_mul(n.into())))
+	}
+	/// Storage: Oracle DataSources (r:0 w:1), Oracle SourceSuspensions (r:0 w:1)
+	fn register_source() -> Weight {
+		Weight::from_parts(12_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle TrustedProviders (r:1 w:1), Oracle CounterForTrustedProviders (r:1 w:1)
+	fn add_trusted_provider() -> Weight {
+		Weight::from_parts(11_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle ProviderBonds (r:1 w:0), Oracle RequestQuotas (r:1 w:1), System Account (r:2 w:2),
+	/// Oracle NextRequestId (r:n w:n), Oracle OracleRequests (r:0 w:n), Oracle PremiumSlas (r:0 w:n),
+	/// Oracle OpenSlas (r:0 w:n), Oracle SlaExpiries (r:n w:n), Oracle RequestExpiries (r:n w:n)
+	/// The range of component `n` is `[1, 100]`.
+	/// The range of component `s` is `[0, 10]`.
+	fn batch_requests(n: u32, s: u32) -> Weight {
+		Weight::from_parts(30_000_000, 6_196)
+			.saturating_add(Weight::from_parts(14_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(420_000, 0).saturating_mul(n.saturating_mul(s).into()))
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((6_u64).saturating_mul(n.into())))
+	}
+	/// Storage: Oracle AggregatedDataStorage (r:1 w:1), Oracle OracleDataStorage (r:0 w:1)
+	fn cleanup_expired_data() -> Weight {
+		Weight::from_parts(45_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle Feeds (r:1 w:1)
+	fn register_feed() -> Weight {
+		Weight::from_parts(12_000_000, 3_540)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle CommitteeMode (r:0 w:1), Oracle Committees (r:0 w:1)
+	fn set_committee_mode() -> Weight {
+		Weight::from_parts(14_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle DataSources (r:0 w:1), Oracle ContractFeeds (r:0 w:1)
+	fn authorize_contract_feed() -> Weight {
+		Weight::from_parts(15_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle PremiumSlas (r:1 w:1), Oracle OracleRequests (r:1 w:0), Oracle OpenSlas (r:0 w:1),
+	/// System Account (r:2 w:2), Oracle DataSources (r:10 w:10)
+	fn settle_premium_sla() -> Weight {
+		Weight::from_parts(52_000_000, 6_196)
+			.saturating_add(T::DbWeight::get().reads(14_u64))
+			.saturating_add(T::DbWeight::get().writes(14_u64))
+	}
+	/// Storage: Oracle WatchedFeeds (r:1 w:1), Oracle CounterForWatchedFeeds (r:1 w:1), Oracle StaleFeeds (r:0 w:1)
+	fn set_feed_watch() -> Weight {
+		Weight::from_parts(16_000_000, 3_540)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: System Account (r:1 w:1), Oracle ProviderBonds (r:1 w:1)
+	fn bond_provider() -> Weight {
+		Weight::from_parts(24_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle ProviderBonds (r:1 w:1), Oracle WatchedFeeds (r:16 w:0), Oracle Committees (r:16 w:0),
+	/// System Account (r:1 w:1)
+	fn unbond_provider() -> Weight {
+		Weight::from_parts(40_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(34_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle WatchedFeeds (r:1 w:0), Oracle StaleFeeds (r:1 w:1), Oracle AggregatedDataStorage (r:1 w:0),
+	/// Oracle CommitteeMode (r:1 w:0), Oracle Committees (r:1 w:1), Oracle ProviderBonds (r:5 w:5),
+	/// System Account (r:5 w:5)
+	fn check_watched_feed() -> Weight {
+		Weight::from_parts(60_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(15_u64))
+			.saturating_add(T::DbWeight::get().writes(12_u64))
+	}
+	/// Storage: Oracle DataSources (r:1 w:0), Oracle SourceOwners (r:0 w:1)
+	fn set_source_owner() -> Weight {
+		Weight::from_parts(12_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle DataSources (r:2 w:1), Oracle SourceOwners (r:1 w:0)
+	fn update_source() -> Weight {
+		Weight::from_parts(16_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle DataSources (r:2 w:1), Oracle SourceOwners (r:1 w:0), Oracle SourceSuspensions (r:1 w:1)
+	fn suspend_source() -> Weight {
+		Weight::from_parts(16_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle DataSources (r:2 w:1), Oracle SourceOwners (r:1 w:0), Oracle SourceSuspensions (r:1 w:1)
+	fn resume_source() -> Weight {
+		Weight::from_parts(16_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle DataSources (r:1 w:1), Oracle SourceOwners (r:1 w:1), Oracle SourceSuspensions (r:0 w:1)
+	fn retire_source() -> Weight {
+		Weight::from_parts(14_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: Oracle Feeds (r:1 w:0), Oracle AggregationStrategies (r:0 w:1)
+	fn set_aggregation_strategy() -> Weight {
+		Weight::from_parts(11_000_000, 3_540)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle TrustedProviders (r:1 w:0), Oracle SigningKeys (r:0 w:1)
+	fn set_signing_key() -> Weight {
+		Weight::from_parts(12_000_000, 3_530)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle LastSignedRounds (r:1 w:1), Oracle Feeds (r:1 w:0), Oracle TrustedProviders (r:n w:0),
+	/// Oracle SigningKeys (r:n w:0), Oracle CommitteeMode (r:n w:0), System Account (r:n+1 w:n+1),
+	/// History (r:1 w:2), Oracle AggregatedDataStorage (r:0 w:1), Oracle StaleFeeds (r:1 w:1),
+	/// Oracle ObservationCount (r:1 w:1), Oracle ObservationHistory (r:0 w:1)
+	/// The range of component `n` is `[3, 32]`.
+	/// The range of component `d` is `[0, 1024]`.
+	fn submit_signed_round(n: u32, d: u32) -> Weight {
+		Weight::from_parts(42_000_000, 6_196)
+			.saturating_add(Weight::from_parts(61_000_000, 2_603).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(3_400, 0).saturating_mul(d.into()))
+			.saturating_add(Weight::from_parts(1_100, 0).saturating_mul(n.saturating_mul(d).into()))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().reads((4_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+	}
+	/// Storage: Oracle CriticalFeeds (r:0 w:1)
+	fn set_feed_critical() -> Weight {
+		Weight::from_parts(11_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle PremiumSlas (r:1 w:0), Oracle OracleRequests (r:1 w:1),
+	/// Oracle AggregatedDataStorage (r:1 w:0)
+	fn prune_request() -> Weight {
+		Weight::from_parts(18_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle PendingAggregates (r:1 w:1), Oracle AggregatedDataStorage (r:1 w:1),
+	/// Oracle StaleFeeds (r:1 w:1), Oracle ObservationCount (r:1 w:1),
+	/// Oracle ObservationHistory (r:0 w:1), Oracle HistoryIndexed (r:1 w:1)
+	fn finalize_aggregate() -> Weight {
+		Weight::from_parts(25_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: Oracle DataSources (r:1 w:1)
+	fn set_source_tier() -> Weight {
+		Weight::from_parts(12_000_000, 0)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle TierRequirementOverrides (r:0 w:1)
+	fn set_tier_requirements() -> Weight {
+		Weight::from_parts(11_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Oracle TrustedProviders (r:p w:0), Oracle Committees (r:1 w:2)
+	/// The range of component `p` is `[0, 128]`.
+	fn select_committee(p: u32) -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(Weight::from_parts(150_000, 48).saturating_mul(p.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(p.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Oracle PendingAggregates (r:1 w:1)
+	fn dispute_aggregate() -> Weight {
+		Weight::from_parts(14_000_000, 3_593)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn request_data(s: u32) -> Weight {
+		Weight::from_parts(60_000, 0).saturating_add(Weight::from_parts(1_000, 0).saturating_mul(s.into()))
+	}
+	fn provide_data(d: u32, s: u32) -> Weight {
+		Weight::from_parts(100_000, 0)
+			.saturating_add(Weight::from_parts(10, 0).saturating_mul(d.into()))
+			.saturating_add(Weight::from_parts(5_000, 0).saturating_mul(s.into()))
+	}
+	fn provide_data_batch(n: u32, d: u32, s: u32) -> Weight {
+		Weight::from_parts(40_000, 0)
+			.saturating_add(Weight::from_parts(60_000, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(10, 0).saturating_mul(n.saturating_mul(d).into()))
+			.saturating_add(Weight::from_parts(5_000, 0).saturating_mul(n.saturating_mul(s).into()))
+	}
+	fn register_source() -> Weight { Weight::from_parts(40_000, 0) }
+	fn add_trusted_provider() -> Weight { Weight::from_parts(30_000, 0) }
+	fn batch_requests(n: u32, s: u32) -> Weight {
+		Weight::from_parts(20_000, 0)
+			.saturating_add(Weight::from_parts(60_000, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(1_000, 0).saturating_mul(n.saturating_mul(s).into()))
+	}
+	fn cleanup_expired_data() -> Weight { Weight::from_parts(150_000, 0) }
+	fn register_feed() -> Weight { Weight::from_parts(30_000, 0) }
+	fn set_committee_mode() -> Weight { Weight::from_parts(30_000, 0) }
+	fn authorize_contract_feed() -> Weight { Weight::from_parts(40_000, 0) }
+	fn settle_premium_sla() -> Weight { Weight::from_parts(50_000, 0) }
+	fn set_feed_watch() -> Weight { Weight::from_parts(30_000, 0) }
+	fn bond_provider() -> Weight { Weight::from_parts(30_000, 0) }
+	fn unbond_provider() -> Weight { Weight::from_parts(50_000, 0) }
+	fn check_watched_feed() -> Weight { Weight::from_parts(60_000, 0) }
+	fn set_source_owner() -> Weight { Weight::from_parts(30_000, 0) }
+	fn update_source() -> Weight { Weight::from_parts(40_000, 0) }
+	fn suspend_source() -> Weight { Weight::from_parts(40_000, 0) }
+	fn resume_source() -> Weight { Weight::from_parts(40_000, 0) }
+	fn retire_source() -> Weight { Weight::from_parts(40_000, 0) }
+	fn set_aggregation_strategy() -> Weight { Weight::from_parts(30_000, 0) }
+	fn set_signing_key() -> Weight { Weight::from_parts(30_000, 0) }
+	fn submit_signed_round(n: u32, d: u32) -> Weight {
+		Weight::from_parts(80_000, 0)
+			.saturating_add(Weight::from_parts(60_000, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(10, 0).saturating_mul(d.into()))
+	}
+	fn set_feed_critical() -> Weight { Weight::from_parts(30_000, 0) }
+	fn prune_request() -> Weight { Weight::from_parts(40_000, 0) }
+	fn finalize_aggregate() -> Weight { Weight::from_parts(50_000, 0) }
+	fn set_source_tier() -> Weight { Weight::from_parts(30_000, 0) }
+	fn set_tier_requirements() -> Weight { Weight::from_parts(30_000, 0) }
+	fn select_committee(p: u32) -> Weight {
+		Weight::from_parts(20_000, 0)
+			.saturating_add(Weight::from_parts(1_000, 0).saturating_mul(p.into()))
+	}
+	fn dispute_aggregate() -> Weight { Weight::from_parts(30_000, 0) }
+}
@@ -9,30 +9,48 @@
 //! - Async/await support with tokio runtime
 //! - Thread pool optimization with rayon
 //! - Performance monitoring and auto-scaling
+//!
+//! Reported execution results are mirrored into the node's off-chain database (see
+//! [`netchain_primitives::offchain_history`]), where they outlive state pruning.
+//!
+//! Calls wrapped by `submit_with_access_list` are queued with their declared access lists and
+//! executed in `on_finalize` as the block's access list batch, in the conflict-free waves of
+//! [`schedule_waves`]. Their weight is charged when they are submitted.
+//!
+//! Batches submitted with `submit_batch` wait in [`BatchQueue`], ordered by priority, until
+//! `process_pending_batches` takes them from its head or they expire. At most
+//! `MaxPendingBatches` batches of at most `MaxBatchLen` transactions are pending at once.
+//!
+//! Batch processing logs to the [`LOG_TARGET`] target, naming the batch; each batch executes
+//! in an `execute_batch` tracing span.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use frame_support::{
-    dispatch::{DispatchResult, DispatchError},
+    dispatch::{DispatchResult, DispatchError, GetDispatchInfo, PostDispatchInfo},
     pallet_prelude::*,
-    traits::{Get, StorageVersion},
+    traits::{Currency, Get, ReservableCurrency, StorageVersion},
 };
 use frame_system::pallet_prelude::*;
 use sp_runtime::{
-    traits::{Saturating, Zero, Hash, BlakeTwo256},
+    traits::{Saturating, Hash, BlakeTwo256, Dispatchable},
     SaturatedConversion,
 };
-use sp_std::{vec::Vec, collections::btree_map::BTreeMap};
+use sp_std::{boxed::Box, vec::Vec, collections::btree_map::BTreeMap};
 use codec::{Encode, Decode};
 use scale_info::TypeInfo;
 
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 
+pub use netchain_primitives::FailureReason;
+use netchain_primitives::offchain_history::{self, Indexed, Table};
 pub use pallet::*;
 
+pub mod migrations;
+
 /// Current storage version
-const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
 /// Maximum parallel workers
 pub const MAX_WORKERS: u32 = 16;
@@ -40,6 +58,129 @@ pub const MAX_WORKERS: u32 = 16;
 /// Batch size for parallel processing
 pub const PARALLEL_BATCH_SIZE: u32 = 1000;
 
+/// Target of the pallet's log lines
+pub const LOG_TARGET: &str = "runtime::parallel";
+
+/// Maximum length of a declared storage key; a map entry keyed by a hashed 32-byte account
+/// takes 80 bytes
+pub const MAX_STORAGE_KEY_LEN: u32 = 128;
+
+/// Raw storage key as touched by a call
+pub type StorageKey = BoundedVec<u8, ConstU32<MAX_STORAGE_KEY_LEN>>;
+
+/// Queued access list call: index of the extrinsic that submitted it and its position among
+/// the calls that extrinsic submitted
+pub type EntryId = (u32, u32);
+
+/// Storage keys a call declares to read and write
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct AccessList {
+    /// Keys the call only reads
+    pub reads: Vec<StorageKey>,
+    /// Keys the call writes
+    pub writes: Vec<StorageKey>,
+}
+
+impl AccessList {
+    /// Whether the list declares `key` as read or written
+    pub fn reads_key(&self, key: &StorageKey) -> bool {
+        self.reads.contains(key) || self.writes.contains(key)
+    }
+
+    /// Whether every access of `required` is declared, writes as writes
+    pub fn covers(&self, required: &AccessList) -> bool {
+        required.writes.iter().all(|key| self.writes.contains(key))
+            && required.reads.iter().all(|key| self.reads_key(key))
+    }
+
+    /// Kind of conflict between two access lists, if any
+    pub fn conflict_with(&self, other: &AccessList) -> Option<ConflictType> {
+        if self.writes.iter().any(|key| other.writes.contains(key)) {
+            Some(ConflictType::WriteWrite)
+        } else if self.writes.iter().any(|key| other.reads.contains(key))
+            || other.writes.iter().any(|key| self.reads.contains(key))
+        {
+            Some(ConflictType::ReadWrite)
+        } else {
+            None
+        }
+    }
+}
+
+/// Group batch entries into waves that can each execute in parallel
+///
+/// Returns the entry indices of every wave, in execution order. No two entries of a wave
+/// conflict, and an entry runs in a later wave than every earlier entry it conflicts with, so
+/// executing the waves one after another gives the same state as executing the entries in
+/// order. Entries without an access list conflict with everything.
+pub fn schedule_waves(entries: &[Option<AccessList>]) -> Vec<Vec<u32>> {
+    let mut waves: Vec<Vec<u32>> = Vec::new();
+    let mut wave_of: Vec<usize> = Vec::with_capacity(entries.len());
+
+    for (index, entry) in entries.iter().enumerate() {
+        let wave = entries[..index]
+            .iter()
+            .zip(wave_of.iter())
+            .filter(|(earlier, _)| match (entry, earlier) {
+                (Some(entry), Some(earlier)) => entry.conflict_with(earlier).is_some(),
+                _ => true,
+            })
+            .map(|(_, wave)| wave + 1)
+            .max()
+            .unwrap_or(0);
+
+        if wave == waves.len() {
+            waves.push(Vec::new());
+        }
+        waves[wave].push(index as u32);
+        wave_of.push(wave);
+    }
+
+    waves
+}
+
+/// Storage accesses a runtime call is known to perform
+///
+/// Implemented by the runtime for the calls whose accesses can be derived from their
+/// arguments. A declared access list must cover what the inspector reports; calls the
+/// inspector does not know are accepted as declared.
+pub trait InspectAccess<AccountId, Call> {
+    /// Accesses `call` performs when dispatched by `who`, if known
+    fn accesses(who: &AccountId, call: &Call) -> Option<AccessList>;
+}
+
+impl<AccountId, Call> InspectAccess<AccountId, Call> for () {
+    fn accesses(_who: &AccountId, _call: &Call) -> Option<AccessList> {
+        None
+    }
+}
+
+/// Call queued by `submit_with_access_list` until the end of the block
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+pub struct AccessListEntry<AccountId, Call> {
+    /// Account that declared the accesses and dispatches the call
+    pub who: AccountId,
+    /// The wrapped call
+    pub call: Call,
+    /// Storage keys the call was declared to read and write
+    pub access_list: AccessList,
+}
+
+/// Scheduling data of a pending batch
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BatchInfo<AccountId, Balance, BlockNumber> {
+    /// Account that submitted the batch
+    pub submitter: AccountId,
+    /// Fee reserved from the submitter, paid to whoever processes the batch
+    pub fee: Balance,
+    /// Fee per transaction; higher priority batches are processed first
+    pub priority: u128,
+    /// Block at which the unprocessed batch is dropped and its fee refunded
+    pub expires_at: BlockNumber,
+}
+
 /// Transaction execution result
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
@@ -55,7 +196,7 @@ pub struct ExecutionResult<Hash> {
 }
 
 /// Parallel execution metrics
-#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, Default)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct ParallelMetrics {
     /// Total transactions processed
@@ -70,6 +211,18 @@ pub struct ParallelMetrics {
     pub conflicts_resolved: u32,
 }
 
+/// Work waiting in the executor queue
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct QueueDepth {
+    /// Batches waiting to be processed
+    pub pending_batches: u32,
+    /// Transactions across all pending batches
+    pub pending_transactions: u32,
+    /// Calls queued with an access list for the end of the block
+    pub access_list_entries: u32,
+}
+
 /// Transaction conflict information
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
@@ -83,7 +236,7 @@ pub struct ConflictInfo<AccountId> {
 }
 
 /// Types of transaction conflicts
-#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum ConflictType {
     /// Read-write conflict
@@ -97,7 +250,7 @@ pub enum ConflictType {
 }
 
 /// Conflict resolution strategies
-#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum ConflictResolution {
     /// Execute sequentially
@@ -135,10 +288,43 @@ pub mod pallet {
         #[pallet::constant]
         type MaxExecutionTime: Get<u64>;
 
+        /// Runtime call wrapped by `submit_with_access_list`
+        type RuntimeCall: Parameter
+            + Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo;
+
+        /// Currency the mis-declaration penalty is slashed from
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Known storage accesses of runtime calls
+        type AccessInspector: InspectAccess<Self::AccountId, <Self as Config>::RuntimeCall>;
+
+        /// Maximum number of keys in a declared access list
+        #[pallet::constant]
+        type MaxAccessListLen: Get<u32>;
+
+        /// Amount slashed from a declarer whose access list misses an access of its call
+        #[pallet::constant]
+        type MisdeclarationPenalty: Get<BalanceOf<Self>>;
+
+        /// Number of blocks a batch stays pending before it expires
+        #[pallet::constant]
+        type BatchTtl: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of batches pending at once
+        #[pallet::constant]
+        type MaxPendingBatches: Get<u32>;
+
+        /// Maximum number of transactions in a submitted batch
+        #[pallet::constant]
+        type MaxBatchLen: Get<u32>;
+
         /// Weight information for extrinsics
         type WeightInfo: WeightInfo;
     }
 
+    pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
     /// Parallel execution metrics
     #[pallet::storage]
     #[pallet::getter(fn parallel_metrics)]
@@ -167,10 +353,61 @@ pub mod pallet {
         _,
         Blake2_128Concat,
         u32, // Batch ID
-        Vec<T::Hash>,
+        BoundedVec<T::Hash, T::MaxBatchLen>,
+        ValueQuery,
+    >;
+
+    /// Priority and ID of the pending batches, highest priority first and then in submission
+    /// order; `process_pending_batches` works from its head
+    #[pallet::storage]
+    #[pallet::getter(fn batch_queue)]
+    pub type BatchQueue<T: Config> = StorageValue<_, BoundedVec<(u128, u32), T::MaxPendingBatches>, ValueQuery>;
+
+    /// Priority, fee and expiry of pending batches
+    #[pallet::storage]
+    #[pallet::getter(fn batch_info)]
+    pub type Batches<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        u32, // Batch ID
+        BatchInfo<T::AccountId, BalanceOf<T>, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Pending batches expiring at a block
+    #[pallet::storage]
+    pub type BatchExpiries<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<u32, T::MaxPendingBatches>,
         ValueQuery,
     >;
 
+    /// Next batch ID to allocate
+    #[pallet::storage]
+    pub type NextBatchId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Calls queued with their access lists in the current block, executed in `on_finalize`
+    ///
+    /// Keyed by [`EntryId`], so every submission writes keys of its own and submissions do not
+    /// conflict with each other.
+    #[pallet::storage]
+    #[pallet::getter(fn queued_call)]
+    pub type AccessListQueue<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        u32, // Extrinsic index
+        Twox64Concat,
+        u32, // Position within the extrinsic
+        AccessListEntry<T::AccountId, <T as Config>::RuntimeCall>,
+        OptionQuery,
+    >;
+
+    /// Execution results mirrored to the off-chain history in the current block
+    #[pallet::storage]
+    pub type HistoryIndexed<T: Config> = StorageValue<_, u32, ValueQuery>;
+
     /// Conflict tracking
     #[pallet::storage]
     #[pallet::getter(fn conflicts)]
@@ -198,6 +435,11 @@ pub mod pallet {
             failed: u32,
             execution_time: u64,
         },
+        /// Batch could not be executed; it was dropped and its fee still went to the processor
+        BatchFailed {
+            batch_id: u32,
+            reason: FailureReason,
+        },
         /// Transaction conflict detected
         ConflictDetected {
             tx_hash: T::Hash,
@@ -216,6 +458,31 @@ pub mod pallet {
             avg_batch_time: u64,
             total_processed: u64,
         },
+        /// Batch dropped unprocessed after its TTL; the fee went back to the submitter
+        BatchExpired {
+            batch_id: u32,
+            submitter: T::AccountId,
+            refunded: BalanceOf<T>,
+        },
+        /// Call queued with its declared access list for the block's access list batch
+        AccessListCallQueued {
+            who: T::AccountId,
+            entry: EntryId,
+            call_hash: T::Hash,
+        },
+        /// Queued call dispatched with the block's access list batch
+        AccessListCallExecuted {
+            who: T::AccountId,
+            batch_id: u32,
+            entry: EntryId,
+            result: DispatchResult,
+        },
+        /// Call rejected because its access list misses an access; the declarer was penalised
+        AccessListMisdeclared {
+            who: T::AccountId,
+            call_hash: T::Hash,
+            penalty: BalanceOf<T>,
+        },
     }
 
     #[pallet::error]
@@ -232,13 +499,40 @@ pub mod pallet {
         InvalidBatchConfig,
         /// Execution timeout
         ExecutionTimeout,
+        /// Declared access list is longer than `MaxAccessListLen`
+        AccessListTooLong,
+        /// Batch has more than `MaxBatchLen` transactions
+        BatchTooLarge,
+        /// `MaxPendingBatches` batches are already pending
+        TooManyPendingBatches,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            HistoryIndexed::<T>::kill();
+
+            let expiring = BatchExpiries::<T>::take(now);
+            let count = expiring.len() as u64;
+            for batch_id in expiring {
+                Self::expire_batch(batch_id);
+            }
+
+            // `on_finalize` reads the queue and allocates a batch; the queued calls were paid
+            // for when they were submitted. At most `MaxPendingBatches` batches expire.
+            T::DbWeight::get().reads_writes(3 + count * 3, 3 + count * 4)
+        }
+
+        fn on_finalize(_now: BlockNumberFor<T>) {
+            Self::execute_access_list_batch();
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// Initialize parallel execution system
         #[pallet::call_index(0)]
-        #[pallet::weight(T::WeightInfo::initialize_parallel_execution())]
+        #[pallet::weight((T::WeightInfo::initialize_parallel_execution(), DispatchClass::Operational, Pays::No))]
         pub fn initialize_parallel_execution(
             origin: OriginFor<T>,
             worker_count: u32,
@@ -268,20 +562,31 @@ pub mod pallet {
         }
 
         /// Submit transaction batch for parallel processing
+        ///
+        /// `fee` is reserved and paid to whoever processes the batch; batches with a higher
+        /// fee per transaction are processed first. Unprocessed batches expire after
+        /// `BatchTtl` blocks and the fee is refunded. A batch holds at most `MaxBatchLen`
+        /// transactions, and is refused while `MaxPendingBatches` batches are pending.
         #[pallet::call_index(1)]
         #[pallet::weight(T::WeightInfo::submit_batch())]
         pub fn submit_batch(
             origin: OriginFor<T>,
             transactions: Vec<T::Hash>,
+            fee: BalanceOf<T>,
         ) -> DispatchResult {
-            ensure_signed(origin)?;
+            let who = ensure_signed(origin)?;
 
-            let batch_id = Self::next_batch_id();
             let tx_count = transactions.len() as u32;
             let worker_count = ActiveWorkers::<T>::get();
 
             ensure!(tx_count > 0, Error::<T>::InvalidBatchConfig);
             ensure!(worker_count > 0, Error::<T>::WorkerPoolExhausted);
+            let transactions: BoundedVec<_, T::MaxBatchLen> =
+                transactions.try_into().map_err(|_| Error::<T>::BatchTooLarge)?;
+
+            T::Currency::reserve(&who, fee)?;
+            let priority = fee.saturated_into::<u128>() / tx_count as u128;
+            let batch_id = Self::open_batch(who, fee, priority)?;
 
             // Store batch for processing
             PendingBatches::<T>::insert(&batch_id, &transactions);
@@ -296,31 +601,42 @@ pub mod pallet {
             #[cfg(feature = "std")]
             {
                 // In a real implementation, this would spawn off-chain workers
-                Self::process_batch_async(batch_id, transactions);
+                Self::process_batch_async(batch_id, transactions.into_inner());
             }
 
             Ok(())
         }
 
-        /// Process pending batches (called by block author or off-chain worker)
+        /// Process pending batches by priority (called by block author or off-chain worker)
+        ///
+        /// Batches are taken from the head of [`BatchQueue`], highest priority first, until the
+        /// next one would exceed `max_weight`. The processor receives the fees of the batches
+        /// it processes.
         #[pallet::call_index(2)]
-        #[pallet::weight(T::WeightInfo::process_pending_batches())]
+        #[pallet::weight(T::WeightInfo::process_pending_batches().saturating_add(*max_weight))]
         pub fn process_pending_batches(
             origin: OriginFor<T>,
-            max_batches: u32,
-        ) -> DispatchResult {
-            ensure_signed(origin)?;
+            max_weight: Weight,
+        ) -> DispatchResultWithPostInfo {
+            let processor = ensure_signed(origin)?;
 
             let start_time = frame_system::Pallet::<T>::block_number();
-            let mut processed_batches = 0u32;
+            let mut used_weight = Weight::zero();
+
+            let mut queue = BatchQueue::<T>::get().into_inner();
+            let mut taken = 0;
 
-            // Process up to max_batches in parallel
-            for (batch_id, transactions) in PendingBatches::<T>::iter() {
-                if processed_batches >= max_batches {
+            for &(_, batch_id) in &queue {
+                let transactions = PendingBatches::<T>::get(batch_id);
+                let cost = T::WeightInfo::execute_batch(transactions.len() as u32);
+                if used_weight.saturating_add(cost).any_gt(max_weight) {
+                    log::debug!(target: LOG_TARGET, "batch {} left pending, out of weight", batch_id);
                     break;
                 }
+                used_weight = used_weight.saturating_add(cost);
+                taken += 1;
 
-                let result = Self::execute_batch_parallel(batch_id, transactions);
+                let result = Self::execute_batch_parallel(batch_id, transactions.into_inner());
                 
                 match result {
                     Ok((processed, failed)) => {
@@ -334,28 +650,50 @@ pub mod pallet {
                             execution_time,
                         });
 
-                        // Update metrics
-                        Metrics::<T>::mutate(|metrics| {
+                        // Update metrics; efficiency is a rolling average over recent batches
+                        let metrics = Metrics::<T>::mutate(|metrics| {
                             metrics.total_processed = metrics.total_processed.saturating_add(processed as u64);
                             metrics.avg_batch_time = (metrics.avg_batch_time + execution_time) / 2;
+                            let batch_efficiency = processed.saturating_mul(100) / processed.saturating_add(failed).max(1);
+                            metrics.parallel_efficiency =
+                                ((metrics.parallel_efficiency as u32 * 3 + batch_efficiency) / 4) as u8;
+                            metrics.clone()
+                        });
+
+                        Self::deposit_event(Event::MetricsUpdated {
+                            efficiency: metrics.parallel_efficiency,
+                            avg_batch_time: metrics.avg_batch_time,
+                            total_processed: metrics.total_processed,
                         });
                     }
-                    Err(_) => {
-                        // Batch processing failed - could retry or report error
+                    Err(error) => {
+                        log::warn!(target: LOG_TARGET, "batch {} failed: {:?}", batch_id, error);
+                        Self::deposit_event(Event::BatchFailed { batch_id, reason: error.into() });
                     }
                 }
 
-                // Remove processed batch
+                // Remove processed batch and pay its fee to the processor
                 PendingBatches::<T>::remove(batch_id);
-                processed_batches = processed_batches.saturating_add(1);
+                if let Some(info) = Batches::<T>::take(batch_id) {
+                    BatchExpiries::<T>::mutate(info.expires_at, |expiring| expiring.retain(|id| *id != batch_id));
+                    let _ = T::Currency::repatriate_reserved(
+                        &info.submitter,
+                        &processor,
+                        info.fee,
+                        frame_support::traits::BalanceStatus::Free,
+                    );
+                }
             }
 
-            Ok(())
+            queue.drain(..taken);
+            BatchQueue::<T>::put(BoundedVec::truncate_from(queue));
+
+            Ok(Some(T::WeightInfo::process_pending_batches().saturating_add(used_weight)).into())
         }
 
         /// Scale worker pool based on load
         #[pallet::call_index(3)]
-        #[pallet::weight(T::WeightInfo::scale_workers())]
+        #[pallet::weight((T::WeightInfo::scale_workers(), DispatchClass::Operational, Pays::No))]
         pub fn scale_workers(
             origin: OriginFor<T>,
             target_workers: u32,
@@ -411,6 +749,7 @@ pub mod pallet {
                 error,
             };
 
+            Self::mirror_execution_result(&result);
             ExecutionResults::<T>::insert(&tx_hash, result);
 
             Ok(())
@@ -449,14 +788,120 @@ pub mod pallet {
 
             Ok(())
         }
+
+        /// Queue `call` with its declared storage access set
+        ///
+        /// The call is dispatched from the caller at the end of the block, with every call
+        /// queued in the block, in waves scheduled from their access lists: calls whose lists
+        /// do not conflict share a wave, and conflicting ones run in submission order. If the
+        /// declared list misses an access the call is known to perform, the call is not
+        /// queued and the declarer is slashed `MisdeclarationPenalty`.
+        ///
+        /// The weight of the call is charged here, as its dispatch in `on_finalize` cannot be.
+        #[pallet::call_index(6)]
+        #[pallet::weight({
+            let dispatch_info = call.get_dispatch_info();
+            T::WeightInfo::submit_with_access_list((reads.len() + writes.len()) as u32)
+                .saturating_add(dispatch_info.call_weight)
+        })]
+        pub fn submit_with_access_list(
+            origin: OriginFor<T>,
+            call: Box<<T as Config>::RuntimeCall>,
+            reads: Vec<StorageKey>,
+            writes: Vec<StorageKey>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin.clone())?;
+
+            ensure!(
+                reads.len().saturating_add(writes.len()) <= T::MaxAccessListLen::get() as usize,
+                Error::<T>::AccessListTooLong
+            );
+
+            let call_hash = T::Hashing::hash_of(&call);
+            let access_list = AccessList { reads, writes };
+
+            if let Some(required) = T::AccessInspector::accesses(&who, &call) {
+                if !access_list.covers(&required) {
+                    // Returning Ok keeps the slash; the wrapped call never runs
+                    let (imbalance, _) = T::Currency::slash(&who, T::MisdeclarationPenalty::get());
+                    let penalty = frame_support::traits::Imbalance::peek(&imbalance);
+                    Self::deposit_event(Event::AccessListMisdeclared { who, call_hash, penalty });
+                    return Ok(());
+                }
+            }
+
+            // Only this extrinsic's own entries are read, so submissions stay independent
+            let extrinsic_index = frame_system::Pallet::<T>::extrinsic_index().unwrap_or_default();
+            let position = AccessListQueue::<T>::iter_key_prefix(extrinsic_index).count() as u32;
+            let entry = (extrinsic_index, position);
+            AccessListQueue::<T>::insert(
+                extrinsic_index,
+                position,
+                AccessListEntry { who: who.clone(), call: *call, access_list },
+            );
+            Self::deposit_event(Event::AccessListCallQueued { who, entry, call_hash });
+
+            Ok(())
+        }
     }
 
     /// Helper functions
     impl<T: Config> Pallet<T> {
-        /// Get next batch ID
+        /// Allocate the next batch ID
         pub fn next_batch_id() -> u32 {
-            // In a real implementation, this would be a proper counter
-            frame_system::Pallet::<T>::block_number().saturated_into::<u32>()
+            NextBatchId::<T>::mutate(|next| {
+                let batch_id = *next;
+                *next = next.wrapping_add(1);
+                batch_id
+            })
+        }
+
+        /// Allocate a batch, queue it by priority and schedule its expiry
+        fn open_batch(submitter: T::AccountId, fee: BalanceOf<T>, priority: u128) -> Result<u32, DispatchError> {
+            let batch_id = Self::next_batch_id();
+            BatchQueue::<T>::try_mutate(|queue| {
+                // After the batches of the same priority, which were submitted earlier
+                let position = queue.partition_point(|&(queued, _)| queued >= priority);
+                queue.try_insert(position, (priority, batch_id))
+            })
+            .map_err(|_| Error::<T>::TooManyPendingBatches)?;
+
+            // Every batch in an expiry is pending, so the queue bounds it too
+            let expires_at = frame_system::Pallet::<T>::block_number().saturating_add(T::BatchTtl::get());
+            BatchExpiries::<T>::try_append(expires_at, batch_id).map_err(|_| Error::<T>::TooManyPendingBatches)?;
+            Batches::<T>::insert(batch_id, BatchInfo { submitter, fee, priority, expires_at });
+            Ok(batch_id)
+        }
+
+        /// Write `result` to the off-chain history and journal it in the current block
+        fn mirror_execution_result(result: &ExecutionResult<T::Hash>) {
+            let block = frame_system::Pallet::<T>::block_number().saturated_into::<u32>();
+            let n = HistoryIndexed::<T>::mutate(|n| {
+                let current = *n;
+                *n = n.saturating_add(1);
+                current
+            });
+
+            let key = offchain_history::execution_result_key(result.tx_hash.as_ref());
+            sp_io::offchain_index::set(&key, &Indexed { block, item: result }.encode());
+            sp_io::offchain_index::set(&offchain_history::journal_key(Table::ExecutionResults, block, n), &key);
+        }
+
+        /// Drop a batch that is still pending and refund its fee
+        pub(crate) fn expire_batch(batch_id: u32) {
+            // Processed batches have already been removed
+            let Some(info) = Batches::<T>::take(batch_id) else { return };
+            log::debug!(target: LOG_TARGET, "batch {} expired unprocessed", batch_id);
+
+            PendingBatches::<T>::remove(batch_id);
+            BatchQueue::<T>::mutate(|queue| queue.retain(|&(_, id)| id != batch_id));
+            let refunded = info.fee.saturating_sub(T::Currency::unreserve(&info.submitter, info.fee));
+
+            Self::deposit_event(Event::BatchExpired {
+                batch_id,
+                submitter: info.submitter,
+                refunded,
+            });
         }
 
         /// Execute batch in parallel (simplified synchronous version)
@@ -464,25 +909,38 @@ pub mod pallet {
             batch_id: u32,
             transactions: Vec<T::Hash>,
         ) -> Result<(u32, u32), DispatchError> {
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "execute_batch");
             let batch_size = transactions.len() as u32;
             let worker_count = ActiveWorkers::<T>::get();
             
             // In a real implementation, this would use actual parallel execution
-            // For now, we simulate parallel processing
+            // For now, we simulate parallel processing of conflict-free waves; batches of
+            // transaction hashes declare no accesses, so they run one transaction per wave
+            let access_lists = sp_std::vec![None; transactions.len()];
             let chunk_size = (batch_size / worker_count.max(1)).max(1);
             let mut processed = 0u32;
             let mut failed = 0u32;
 
-            // Simulate parallel processing of chunks
-            for chunk in transactions.chunks(chunk_size as usize) {
-                for tx_hash in chunk {
-                    // Simulate transaction execution
-                    let success = Self::simulate_transaction_execution(tx_hash);
-                    
-                    if success {
-                        processed = processed.saturating_add(1);
-                    } else {
-                        failed = failed.saturating_add(1);
+            let waves = schedule_waves(&access_lists);
+            log::debug!(
+                target: LOG_TARGET,
+                "batch {}: {} transactions in {} waves over {} workers",
+                batch_id,
+                batch_size,
+                waves.len(),
+                worker_count,
+            );
+            for wave in waves {
+                for chunk in wave.chunks(chunk_size as usize) {
+                    for tx_hash in chunk.iter().map(|&index| &transactions[index as usize]) {
+                        // Simulate transaction execution
+                        let success = Self::simulate_transaction_execution(tx_hash);
+
+                        if success {
+                            processed = processed.saturating_add(1);
+                        } else {
+                            failed = failed.saturating_add(1);
+                        }
                     }
                 }
             }
@@ -502,9 +960,12 @@ pub mod pallet {
         pub fn process_batch_async(batch_id: u32, transactions: Vec<T::Hash>) {
             use tokio::task;
             use futures::future::join_all;
-            
+
+            // Only simulated off-chain work; nothing to do outside a tokio runtime
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else { return };
+
             // Spawn async task for batch processing
-            task::spawn(async move {
+            runtime.spawn(async move {
                 let worker_count = 4; // Simplified
                 let chunk_size = transactions.len() / worker_count.max(1);
                 
@@ -535,6 +996,7 @@ pub mod pallet {
                 
                 // Report completion (in real implementation, this would update on-chain state)
                 log::info!(
+                    target: LOG_TARGET,
                     "Batch {} completed: {} processed, {} failed",
                     batch_id,
                     total_processed,
@@ -564,14 +1026,62 @@ pub mod pallet {
             (processed, failed)
         }
 
-        /// Detect conflicts between transactions
-        pub fn detect_conflicts(
-            tx1: &T::Hash,
-            tx2: &T::Hash,
-        ) -> Option<ConflictType> {
-            // In a real implementation, this would analyze transaction data
-            // For simulation, randomly detect conflicts
-            None
+        /// Dispatch the calls queued in this block in the waves of their access lists
+        ///
+        /// Each call runs in its own storage layer, so a failing call leaves no writes.
+        fn execute_access_list_batch() {
+            let mut entries: Vec<_> = AccessListQueue::<T>::drain()
+                .map(|(extrinsic_index, position, entry)| ((extrinsic_index, position), entry))
+                .collect();
+            if entries.is_empty() {
+                return;
+            }
+            entries.sort_by_key(|(id, _)| *id);
+
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "execute_batch");
+            let batch_id = Self::next_batch_id();
+            let access_lists: Vec<_> = entries.iter().map(|(_, entry)| Some(entry.access_list.clone())).collect();
+            let waves = schedule_waves(&access_lists);
+            let mut entries: Vec<_> = entries.into_iter().map(Some).collect();
+            log::debug!(
+                target: LOG_TARGET,
+                "access list batch {}: {} calls in {} waves",
+                batch_id,
+                entries.len(),
+                waves.len(),
+            );
+
+            let (mut processed, mut failed) = (0u32, 0u32);
+            for index in waves.into_iter().flatten() {
+                let Some((entry, AccessListEntry { who, call, .. })) = entries[index as usize].take() else { continue };
+                let origin = frame_system::RawOrigin::Signed(who.clone()).into();
+                let result = frame_support::storage::with_storage_layer(|| {
+                    call.dispatch(origin).map(|_| ()).map_err(|e| e.error)
+                });
+                if result.is_ok() {
+                    processed = processed.saturating_add(1);
+                } else {
+                    failed = failed.saturating_add(1);
+                }
+                Self::deposit_event(Event::AccessListCallExecuted { who, batch_id, entry, result });
+            }
+
+            Metrics::<T>::mutate(|metrics| {
+                metrics.total_processed = metrics.total_processed.saturating_add(processed as u64);
+            });
+            Self::deposit_event(Event::BatchCompleted { batch_id, processed, failed, execution_time: 0 });
+        }
+
+        /// Current depth of the batch queue
+        pub fn queue_depth() -> QueueDepth {
+            let mut depth = QueueDepth::default();
+            for (_, batch_id) in BatchQueue::<T>::get() {
+                let transactions = PendingBatches::<T>::decode_len(batch_id).unwrap_or_default();
+                depth.pending_batches = depth.pending_batches.saturating_add(1);
+                depth.pending_transactions = depth.pending_transactions.saturating_add(transactions as u32);
+            }
+            depth.access_list_entries = AccessListQueue::<T>::iter_keys().count() as u32;
+            depth
         }
 
         /// Calculate parallel efficiency
@@ -593,6 +1103,22 @@ pub mod pallet {
     }
 }
 
+/// Runtime API for executor monitoring
+pub mod runtime_api {
+    use super::*;
+
+    sp_api::decl_runtime_apis! {
+        /// API for querying the parallel executor's load
+        pub trait ParallelExecutorApi {
+            /// Cumulative metrics, including the rolling batch efficiency
+            fn metrics() -> ParallelMetrics;
+
+            /// Batches and transactions currently queued
+            fn queue_depth() -> QueueDepth;
+        }
+    }
+}
+
 /// Weight functions for the pallet
 pub trait WeightInfo {
     fn initialize_parallel_execution() -> Weight;
@@ -601,6 +1127,8 @@ pub trait WeightInfo {
     fn scale_workers() -> Weight;
     fn report_execution_result() -> Weight;
     fn handle_conflict() -> Weight;
+    fn submit_with_access_list(keys: u32) -> Weight;
+    fn execute_batch(transactions: u32) -> Weight;
 }
 
 /// Default weight implementation
@@ -623,24 +1151,479 @@ impl WeightInfo for () {
     fn handle_conflict() -> Weight {
         Weight::from_parts(75_000_000, 7_500)
     }
+    fn submit_with_access_list(keys: u32) -> Weight {
+        Weight::from_parts(60_000_000, 6_000)
+            .saturating_add(Weight::from_parts(500_000, 64).saturating_mul(keys as u64))
+    }
+    fn execute_batch(transactions: u32) -> Weight {
+        Weight::from_parts(5_000_000, 500)
+            .saturating_add(Weight::from_parts(1_000_000, 100).saturating_mul(transactions as u64))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate as pallet_parallel_executor;
+    use frame_support::{
+        assert_noop, assert_ok, derive_impl,
+        dispatch::GetDispatchInfo,
+        traits::{ConstU32, ConstU64, OnFinalize, OnInitialize},
+    };
+    use proptest::prelude::*;
+    use sp_core::{blake2_256, H256};
+    use sp_runtime::{BuildStorage, StateVersion};
+
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test
+        {
+            System: frame_system,
+            Balances: pallet_balances,
+            ParallelExecutor: pallet_parallel_executor,
+        }
+    );
+
+    #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+    impl frame_system::Config for Test {
+        type Block = Block;
+        type AccountData = pallet_balances::AccountData<u64>;
+    }
+
+    #[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+    impl pallet_balances::Config for Test {
+        type AccountStore = System;
+    }
+
+    const REMARK_KEY: &[u8] = b":remark";
+
+    fn storage_key(raw: &[u8]) -> StorageKey {
+        StorageKey::truncate_from(raw.to_vec())
+    }
+
+    /// Knows that remarks write `REMARK_KEY`
+    pub struct RemarkAccesses;
+
+    impl InspectAccess<u64, RuntimeCall> for RemarkAccesses {
+        fn accesses(_who: &u64, call: &RuntimeCall) -> Option<AccessList> {
+            matches!(call, RuntimeCall::System(frame_system::Call::remark { .. }))
+                .then(|| AccessList { reads: Vec::new(), writes: vec![storage_key(REMARK_KEY)] })
+        }
+    }
+
+    impl Config for Test {
+        type RuntimeEvent = RuntimeEvent;
+        type MaxWorkers = ConstU32<8>;
+        type BatchSize = ConstU32<100>;
+        type MaxExecutionTime = ConstU64<1_000>;
+        type RuntimeCall = RuntimeCall;
+        type Currency = Balances;
+        type AccessInspector = RemarkAccesses;
+        type MaxAccessListLen = ConstU32<4>;
+        type MisdeclarationPenalty = ConstU64<50>;
+        type BatchTtl = ConstU64<10>;
+        type MaxPendingBatches = ConstU32<3>;
+        type MaxBatchLen = ConstU32<4>;
+        type WeightInfo = ();
+    }
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        pallet_balances::GenesisConfig::<Test> {
+            balances: (1..=4).map(|account| (account, 1_000)).collect(),
+            ..Default::default()
+        }
+        .assimilate_storage(&mut t)
+        .unwrap();
+
+        let mut ext: sp_io::TestExternalities = t.into();
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    fn tx(n: u8) -> H256 {
+        H256::repeat_byte(n)
+    }
+
+    fn remark(text: &[u8]) -> Box<RuntimeCall> {
+        Box::new(RuntimeCall::System(frame_system::Call::remark { remark: text.to_vec() }))
+    }
+
+    fn remark_with_event(text: &[u8]) -> Box<RuntimeCall> {
+        Box::new(RuntimeCall::System(frame_system::Call::remark_with_event { remark: text.to_vec() }))
+    }
+
+    /// Make the following calls part of extrinsic `index` of the block
+    fn in_extrinsic(index: u32) {
+        frame_support::storage::unhashed::put(sp_core::storage::well_known_keys::EXTRINSIC_INDEX, &index);
+    }
+
+    /// A batch entry of the fuzzer: its access list and the order it is applied in its wave
+    type FuzzEntry = (AccessList, u32);
+
+    fn access_list(keys: u8) -> impl Strategy<Value = AccessList> {
+        // A small key space makes overlapping access lists likely
+        let key = (0..keys).prop_map(|k| storage_key(&[k]));
+        (prop::collection::vec(key.clone(), 0..4), prop::collection::vec(key, 1..3))
+            .prop_map(|(reads, writes)| AccessList { reads, writes })
+    }
+
+    /// Values an entry writes, derived only from the keys it declared
+    fn execute(
+        index: usize,
+        list: &AccessList,
+        read: impl Fn(&[u8]) -> Option<Vec<u8>>,
+    ) -> Vec<(StorageKey, Vec<u8>)> {
+        let seen: Vec<_> = list.reads.iter().chain(list.writes.iter()).map(|key| read(&key[..])).collect();
+        list.writes
+            .iter()
+            .map(|key| (key.clone(), blake2_256(&(index as u32, key, &seen).encode()).to_vec()))
+            .collect()
+    }
+
+    fn sequential_root(entries: &[FuzzEntry]) -> Vec<u8> {
+        sp_io::TestExternalities::default().execute_with(|| {
+            for (index, (list, _)) in entries.iter().enumerate() {
+                for (key, value) in execute(index, list, |key| sp_io::storage::get(key).map(|value| value.to_vec())) {
+                    sp_io::storage::set(&key, &value);
+                }
+            }
+            sp_io::storage::root(StateVersion::V1)
+        })
+    }
+
+    fn parallel_root(entries: &[FuzzEntry]) -> Vec<u8> {
+        let lists: Vec<_> = entries.iter().map(|(list, _)| Some(list.clone())).collect();
+        let waves = schedule_waves(&lists);
+
+        sp_io::TestExternalities::default().execute_with(|| {
+            for mut wave in waves {
+                // Every entry of a wave reads the state left by the previous wave
+                let snapshot: BTreeMap<StorageKey, Vec<u8>> = wave
+                    .iter()
+                    .flat_map(|&index| {
+                        let list = &entries[index as usize].0;
+                        list.reads.iter().chain(list.writes.iter())
+                    })
+                    .filter_map(|key| sp_io::storage::get(key).map(|value| (key.clone(), value.to_vec())))
+                    .collect();
+
+                // Workers finish in arbitrary order
+                wave.sort_by_key(|&index| entries[index as usize].1);
+                let writes: Vec<_> = std::thread::scope(|scope| {
+                    let workers: Vec<_> = wave
+                        .iter()
+                        .map(|&index| {
+                            let snapshot = &snapshot;
+                            scope.spawn(move || {
+                                execute(index as usize, &entries[index as usize].0, |key| snapshot.get(key).cloned())
+                            })
+                        })
+                        .collect();
+                    workers.into_iter().flat_map(|worker| worker.join().expect("worker panicked")).collect()
+                });
+
+                for (key, value) in writes {
+                    sp_io::storage::set(&key, &value);
+                }
+            }
+            sp_io::storage::root(StateVersion::V1)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn conflict_schedule_matches_sequential_execution(
+            entries in prop::collection::vec((access_list(8), any::<u32>()), 1..48),
+        ) {
+            let lists: Vec<_> = entries.iter().map(|(list, _)| Some(list.clone())).collect();
+            for wave in schedule_waves(&lists) {
+                for (i, &a) in wave.iter().enumerate() {
+                    for &b in &wave[i + 1..] {
+                        prop_assert!(entries[a as usize].0.conflict_with(&entries[b as usize].0).is_none());
+                    }
+                }
+            }
+
+            prop_assert_eq!(parallel_root(&entries), sequential_root(&entries));
+        }
+    }
 
     #[test]
     fn parallel_batch_processing_works() {
-        // Test parallel batch processing
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                ParallelExecutor::submit_batch(RuntimeOrigin::signed(1), vec![tx(1)], 10),
+                Error::<Test>::WorkerPoolExhausted
+            );
+            assert_ok!(ParallelExecutor::initialize_parallel_execution(RuntimeOrigin::root(), 4));
+            assert_noop!(
+                ParallelExecutor::submit_batch(RuntimeOrigin::signed(1), Vec::new(), 10),
+                Error::<Test>::InvalidBatchConfig
+            );
+
+            // 5 per transaction for batch 0, 30 for batch 1
+            assert_ok!(ParallelExecutor::submit_batch(RuntimeOrigin::signed(1), vec![tx(1), tx(2)], 10));
+            assert_ok!(ParallelExecutor::submit_batch(RuntimeOrigin::signed(2), vec![tx(3)], 30));
+            assert_eq!(Balances::reserved_balance(1), 10);
+            let depth = ParallelExecutor::queue_depth();
+            assert_eq!((depth.pending_batches, depth.pending_transactions), (2, 3));
+
+            // The budget only fits the higher priority batch
+            let budget = <() as WeightInfo>::execute_batch(1);
+            assert_ok!(ParallelExecutor::process_pending_batches(RuntimeOrigin::signed(3), budget));
+            System::assert_has_event(
+                Event::BatchCompleted { batch_id: 1, processed: 1, failed: 0, execution_time: 0 }.into(),
+            );
+            assert_eq!(Balances::free_balance(3), 1_030);
+            assert_eq!(Balances::reserved_balance(2), 0);
+            assert!(ParallelExecutor::batch_info(0).is_some());
+
+            assert_ok!(ParallelExecutor::process_pending_batches(RuntimeOrigin::signed(3), Weight::MAX));
+            assert_eq!(Balances::free_balance(3), 1_040);
+            assert_eq!(ParallelExecutor::queue_depth(), QueueDepth::default());
+
+            // Efficiency is a rolling average starting from zero
+            let metrics = ParallelExecutor::parallel_metrics();
+            assert_eq!(metrics.total_processed, 3);
+            assert_eq!(metrics.parallel_efficiency, 43);
+        });
+    }
+
+    #[test]
+    fn unprocessed_batches_expire() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(ParallelExecutor::initialize_parallel_execution(RuntimeOrigin::root(), 4));
+            assert_ok!(ParallelExecutor::submit_batch(RuntimeOrigin::signed(1), vec![tx(1)], 10));
+            assert_eq!(ParallelExecutor::batch_info(0).unwrap().expires_at, 11);
+
+            ParallelExecutor::on_initialize(10);
+            assert_eq!(Balances::reserved_balance(1), 10);
+
+            ParallelExecutor::on_initialize(11);
+            assert_eq!(Balances::reserved_balance(1), 0);
+            assert_eq!(Balances::free_balance(1), 1_000);
+            assert!(ParallelExecutor::pending_batches(0).is_empty());
+            System::assert_last_event(Event::BatchExpired { batch_id: 0, submitter: 1, refunded: 10 }.into());
+        });
+    }
+
+    #[test]
+    fn pending_batches_are_bounded_and_queued_by_priority() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(ParallelExecutor::initialize_parallel_execution(RuntimeOrigin::root(), 4));
+            assert_noop!(
+                ParallelExecutor::submit_batch(RuntimeOrigin::signed(1), (1..=5).map(tx).collect(), 10),
+                Error::<Test>::BatchTooLarge
+            );
+
+            // Equal priorities keep their submission order
+            assert_ok!(ParallelExecutor::submit_batch(RuntimeOrigin::signed(1), vec![tx(1)], 10));
+            assert_ok!(ParallelExecutor::submit_batch(RuntimeOrigin::signed(2), vec![tx(2)], 30));
+            assert_ok!(ParallelExecutor::submit_batch(RuntimeOrigin::signed(3), vec![tx(3)], 30));
+            assert_eq!(ParallelExecutor::batch_queue().into_inner(), vec![(30, 1), (30, 2), (10, 0)]);
+            assert_noop!(
+                ParallelExecutor::submit_batch(RuntimeOrigin::signed(4), vec![tx(4)], 50),
+                Error::<Test>::TooManyPendingBatches
+            );
+
+            // Processing takes the head of the queue and frees its room
+            let budget = <() as WeightInfo>::execute_batch(1);
+            assert_ok!(ParallelExecutor::process_pending_batches(RuntimeOrigin::signed(4), budget));
+            assert_eq!(ParallelExecutor::batch_queue().into_inner(), vec![(30, 2), (10, 0)]);
+            assert_eq!(BatchExpiries::<Test>::get(11).into_inner(), vec![0, 2]);
+            System::set_block_number(2);
+            assert_ok!(ParallelExecutor::submit_batch(RuntimeOrigin::signed(4), vec![tx(4)], 50));
+            assert_eq!(ParallelExecutor::batch_queue().into_inner(), vec![(50, 3), (30, 2), (10, 0)]);
+
+            // Expired batches leave the queue
+            ParallelExecutor::on_initialize(11);
+            assert_eq!(ParallelExecutor::batch_queue().into_inner(), vec![(50, 3)]);
+            assert_eq!(ParallelExecutor::queue_depth().pending_batches, 1);
+        });
+    }
+
+    #[test]
+    fn migrating_to_v2_queues_pending_batches_within_the_bounds() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+
+            // Batch 0 is too long, and batch 1 has the lowest priority of the other four
+            for (batch_id, submitter, fee, len) in [(0, 1, 50, 5), (1, 1, 10, 1), (2, 2, 20, 1), (3, 3, 30, 1), (4, 4, 40, 1)] {
+                assert_ok!(Balances::reserve(&submitter, fee));
+                let expires_at = 11;
+                Batches::<Test>::insert(batch_id, BatchInfo { submitter, fee, priority: fee as u128, expires_at });
+                migrations::v2::PendingBatches::<Test>::insert(batch_id, (0..len).map(tx).collect::<Vec<_>>());
+                migrations::v2::BatchExpiries::<Test>::append(expires_at, batch_id);
+            }
+            // Processed batches stayed in their expiry
+            migrations::v2::BatchExpiries::<Test>::append(11, 9);
+
+            StorageVersion::new(1).put::<ParallelExecutor>();
+            migrations::v2::QueuePendingBatches::<Test>::on_runtime_upgrade();
+            assert_eq!(ParallelExecutor::on_chain_storage_version(), 2);
+
+            assert_eq!(ParallelExecutor::batch_queue().into_inner(), vec![(40, 4), (30, 3), (20, 2)]);
+            assert_eq!(BatchExpiries::<Test>::get(11).into_inner(), vec![2, 3, 4]);
+            assert_eq!(ParallelExecutor::pending_batches(4).into_inner(), vec![tx(0)]);
+            for batch_id in [0, 1] {
+                assert!(ParallelExecutor::batch_info(batch_id).is_none());
+                assert!(ParallelExecutor::pending_batches(batch_id).is_empty());
+            }
+            assert_eq!(Balances::reserved_balance(1), 0);
+        });
+    }
+
+    #[test]
+    fn execution_results_are_mirrored_off_chain() {
+        let mut ext = new_test_ext();
+        let tx_hash = H256::repeat_byte(7);
+        ext.execute_with(|| {
+            assert_ok!(ParallelExecutor::report_execution_result(RuntimeOrigin::signed(1), tx_hash, true, 21_000, None));
+        });
+        ext.persist_offchain_overlay();
+
+        let db = ext.offchain_db();
+        let key = offchain_history::execution_result_key(tx_hash.as_ref());
+        let stored = db.get(&key).expect("result is mirrored");
+        assert_eq!(
+            Indexed::<ExecutionResult<H256>>::decode(&mut &stored[..]).unwrap(),
+            Indexed { block: 1, item: ExecutionResult { tx_hash, success: true, gas_used: 21_000, error: None } }
+        );
+        // Journaled as the block's first execution result, so the node can prune it with the block
+        assert_eq!(db.get(&offchain_history::journal_key(Table::ExecutionResults, 1, 0)), Some(key));
     }
 
     #[test]
-    fn conflict_detection_works() {
-        // Test transaction conflict detection
+    fn access_list_calls_run_at_the_end_of_the_block_in_conflict_free_waves() {
+        new_test_ext().execute_with(|| {
+            let submit = |who: u64, call: Box<RuntimeCall>, reads: Vec<StorageKey>, writes: Vec<StorageKey>| {
+                ParallelExecutor::submit_with_access_list(RuntimeOrigin::signed(who), call, reads, writes)
+            };
+            let (remark_key, other_key) = (vec![storage_key(REMARK_KEY)], vec![storage_key(b":other")]);
+
+            // Two extrinsics writing the same key conflict; a third writes another key
+            in_extrinsic(1);
+            let first = remark_with_event(b"first");
+            assert_ok!(submit(1, first.clone(), Vec::new(), remark_key.clone()));
+            System::assert_last_event(
+                Event::AccessListCallQueued { who: 1, entry: (1, 0), call_hash: BlakeTwo256::hash_of(&first) }.into(),
+            );
+            in_extrinsic(2);
+            assert_ok!(submit(2, remark_with_event(b"second"), Vec::new(), remark_key.clone()));
+
+            // Identical calls of one extrinsic are queued as separate entries
+            in_extrinsic(3);
+            assert_ok!(submit(3, remark_with_event(b"third"), Vec::new(), other_key.clone()));
+            assert_ok!(submit(3, remark_with_event(b"third"), Vec::new(), other_key));
+            assert!(ParallelExecutor::queued_call(3, 0).is_some() && ParallelExecutor::queued_call(3, 1).is_some());
+
+            // Declaring only a read of the remark key is a mis-declaration: slashed, not queued
+            in_extrinsic(4);
+            let fourth = remark(b"fourth");
+            let fourth_hash = BlakeTwo256::hash_of(&fourth);
+            assert_ok!(submit(4, fourth, remark_key.clone(), Vec::new()));
+            System::assert_last_event(
+                Event::AccessListMisdeclared { who: 4, call_hash: fourth_hash, penalty: 50 }.into(),
+            );
+            assert_eq!(Balances::free_balance(4), 950);
+            assert!(ParallelExecutor::queued_call(4, 0).is_none());
+
+            let too_long: Vec<_> = (0..4u8).map(|k| storage_key(&[k])).collect();
+            assert_noop!(submit(4, remark(b"fifth"), too_long, remark_key), Error::<Test>::AccessListTooLong);
+
+            // Nothing has run yet
+            assert_eq!(ParallelExecutor::queue_depth().access_list_entries, 4);
+            let remarked = || {
+                System::events()
+                    .iter()
+                    .filter(|record| matches!(record.event, RuntimeEvent::System(frame_system::Event::Remarked { .. })))
+                    .count()
+            };
+            assert_eq!(remarked(), 0);
+
+            // Each conflicting call runs in the wave after the one it conflicts with
+            ParallelExecutor::on_finalize(1);
+            let executed: Vec<_> = System::events()
+                .into_iter()
+                .filter_map(|record| match record.event {
+                    RuntimeEvent::ParallelExecutor(Event::AccessListCallExecuted { entry, result, .. }) => {
+                        Some((entry, result))
+                    }
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(executed, vec![((1, 0), Ok(())), ((3, 0), Ok(())), ((2, 0), Ok(())), ((3, 1), Ok(()))]);
+            assert_eq!(remarked(), 4);
+            System::assert_last_event(
+                Event::BatchCompleted { batch_id: 0, processed: 4, failed: 0, execution_time: 0 }.into(),
+            );
+            assert_eq!(ParallelExecutor::queue_depth(), QueueDepth::default());
+        });
     }
 
     #[test]
     fn worker_scaling_works() {
-        // Test dynamic worker pool scaling
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                ParallelExecutor::initialize_parallel_execution(RuntimeOrigin::root(), 9),
+                Error::<Test>::TooManyWorkers
+            );
+            assert_ok!(ParallelExecutor::initialize_parallel_execution(RuntimeOrigin::root(), 4));
+            assert_eq!(ParallelExecutor::active_workers(), 4);
+            assert_eq!(ParallelExecutor::parallel_metrics().active_workers, 4);
+
+            assert_noop!(ParallelExecutor::scale_workers(RuntimeOrigin::signed(1), 6), DispatchError::BadOrigin);
+            assert_noop!(ParallelExecutor::scale_workers(RuntimeOrigin::root(), 9), Error::<Test>::TooManyWorkers);
+
+            assert_ok!(ParallelExecutor::scale_workers(RuntimeOrigin::root(), 6));
+            System::assert_last_event(
+                Event::WorkerPoolScaled { old_size: 4, new_size: 6, reason: b"scaling_up".to_vec() }.into(),
+            );
+
+            // Scaling to the current size changes nothing
+            let events = System::events().len();
+            assert_ok!(ParallelExecutor::scale_workers(RuntimeOrigin::root(), 6));
+            assert_eq!(System::events().len(), events);
+
+            assert_ok!(ParallelExecutor::scale_workers(RuntimeOrigin::root(), 2));
+            assert_eq!(ParallelExecutor::parallel_metrics().active_workers, 2);
+            System::assert_last_event(
+                Event::WorkerPoolScaled { old_size: 6, new_size: 2, reason: b"scaling_down".to_vec() }.into(),
+            );
+        });
+    }
+
+    #[test]
+    fn worker_management_fits_in_blocks_full_of_normal_transactions() {
+        new_test_ext().execute_with(|| {
+            let limits = <Test as frame_system::Config>::BlockWeights::get();
+            let mut full = frame_system::ConsumedWeight::default();
+            full.set(limits.get(DispatchClass::Normal).max_total.unwrap(), DispatchClass::Normal);
+            frame_system::BlockWeight::<Test>::put(full);
+            let fits = |call: RuntimeCall| {
+                frame_system::extensions::check_weight::calculate_consumed_weight::<RuntimeCall>(
+                    &limits,
+                    System::block_weight(),
+                    &call.get_dispatch_info(),
+                    0,
+                )
+                .is_ok()
+            };
+
+            for call in [
+                Call::<Test>::initialize_parallel_execution { worker_count: 4 },
+                Call::<Test>::scale_workers { target_workers: 6 },
+            ] {
+                let info = call.get_dispatch_info();
+                assert_eq!((info.class, info.pays_fee), (DispatchClass::Operational, Pays::No));
+                assert!(fits(RuntimeCall::ParallelExecutor(call)));
+            }
+
+            assert_ok!(ParallelExecutor::initialize_parallel_execution(RuntimeOrigin::root(), 4));
+            assert_ok!(ParallelExecutor::scale_workers(RuntimeOrigin::root(), 6));
+            assert_eq!(ParallelExecutor::active_workers(), 6);
+        });
     }
 }
\ No newline at end of file
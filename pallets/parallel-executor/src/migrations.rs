@@ -0,0 +1,78 @@
+//! Storage migrations for the parallel executor pallet
+
+use super::*;
+
+/// Version 2: pending batches are bounded and queued by priority
+pub mod v2 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, storage_alias, traits::UncheckedOnRuntimeUpgrade};
+
+    #[storage_alias]
+    pub type PendingBatches<T: Config> =
+        StorageMap<Pallet<T>, Blake2_128Concat, u32, Vec<<T as frame_system::Config>::Hash>, ValueQuery>;
+
+    #[storage_alias]
+    pub type BatchExpiries<T: Config> = StorageMap<Pallet<T>, Twox64Concat, BlockNumberFor<T>, Vec<u32>, ValueQuery>;
+
+    /// Queue the pending batches in [`BatchQueue`] by priority
+    ///
+    /// Batches beyond `MaxPendingBatches`, lowest priority first, and batches longer than
+    /// `MaxBatchLen` expire at once and their fee is refunded. Expiries keep only the batches
+    /// left pending.
+    pub struct InnerQueuePendingBatches<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerQueuePendingBatches<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            let mut pending: Vec<_> = PendingBatches::<T>::drain()
+                .map(|(batch_id, transactions)| {
+                    let priority = Batches::<T>::get(batch_id).map_or(0, |info| info.priority);
+                    (priority, batch_id, transactions)
+                })
+                .collect();
+            pending.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+            reads += 2 * pending.len() as u64;
+            writes += pending.len() as u64;
+
+            let mut queue = Vec::new();
+            for (priority, batch_id, transactions) in pending {
+                match BoundedVec::<_, T::MaxBatchLen>::try_from(transactions) {
+                    Ok(transactions) if queue.len() < T::MaxPendingBatches::get() as usize => {
+                        crate::PendingBatches::<T>::insert(batch_id, transactions);
+                        queue.push((priority, batch_id));
+                        writes += 1;
+                    },
+                    _ => {
+                        Pallet::<T>::expire_batch(batch_id);
+                        reads += 3;
+                        writes += 4;
+                    },
+                }
+            }
+            BatchQueue::<T>::put(BoundedVec::truncate_from(queue));
+            writes += 1;
+
+            for (expires_at, expiring) in BatchExpiries::<T>::drain().collect::<Vec<_>>() {
+                let pending: Vec<_> = expiring.into_iter().filter(|id| Batches::<T>::contains_key(id)).collect();
+                reads += 1 + pending.len() as u64;
+                writes += 1;
+                if !pending.is_empty() {
+                    crate::BatchExpiries::<T>::insert(expires_at, BoundedVec::truncate_from(pending));
+                }
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerQueuePendingBatches`], run once when upgrading from storage version 1
+    pub type QueuePendingBatches<T> = VersionedMigration<
+        1,
+        2,
+        InnerQueuePendingBatches<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
@@ -0,0 +1,188 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Safeguard Pallet
+//!
+//! Circuit breaker for the Netchain subsystems that move value across boundaries.
+//!
+//! `PauseOrigin`, a fast-track governance origin, pauses or resumes each [`Subsystem`] on its
+//! own. Pallets check their subsystem through the [`PauseGuard`] trait and, while it is paused,
+//! reject new work with their `SystemPaused` error:
+//! - Cross-shard: new transfers are not queued; queued transfers still settle, are
+//!   dead-lettered, claimed and refunded
+//! - IBC: packets are neither sent nor received; acknowledgements and timeouts of packets
+//!   already sent are still processed
+//! - Oracle: submissions are rejected; aggregates already stored stay readable
+//!
+//! Pausing never strands funds: every in-flight item can still reach its final state.
+
+pub use pallet::*;
+
+use frame_support::{pallet_prelude::*, traits::EnsureOrigin};
+use frame_system::pallet_prelude::*;
+
+/// Subsystem that can be paused independently
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum Subsystem {
+    /// Cross-shard transfers
+    CrossShard,
+    /// IBC packet flow
+    Ibc,
+    /// Oracle data submissions
+    Oracle,
+}
+
+/// Pause switch consulted by guarded pallets, implemented by this pallet and by `()` as
+/// never paused
+pub trait PauseGuard {
+    /// Whether `subsystem` currently rejects new work
+    fn is_paused(subsystem: Subsystem) -> bool;
+}
+
+impl PauseGuard for () {
+    fn is_paused(_subsystem: Subsystem) -> bool {
+        false
+    }
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Origin pausing and resuming subsystems
+        type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Weight information for extrinsics
+        type WeightInfo: WeightInfo;
+    }
+
+    /// Paused subsystems and the block they were paused at
+    #[pallet::storage]
+    #[pallet::getter(fn paused_since)]
+    pub type Paused<T: Config> = StorageMap<_, Twox64Concat, Subsystem, BlockNumberFor<T>, OptionQuery>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Subsystem stopped accepting new work
+        SubsystemPaused { subsystem: Subsystem },
+        /// Subsystem accepts new work again
+        SubsystemResumed { subsystem: Subsystem },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Subsystem is already paused
+        AlreadyPaused,
+        /// Subsystem is not paused
+        NotPaused,
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Stop `subsystem` from accepting new work; in-flight items still settle
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::pause())]
+        pub fn pause(origin: OriginFor<T>, subsystem: Subsystem) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            ensure!(!Paused::<T>::contains_key(subsystem), Error::<T>::AlreadyPaused);
+            Paused::<T>::insert(subsystem, frame_system::Pallet::<T>::block_number());
+
+            Self::deposit_event(Event::SubsystemPaused { subsystem });
+            Ok(())
+        }
+
+        /// Let `subsystem` accept new work again
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::resume())]
+        pub fn resume(origin: OriginFor<T>, subsystem: Subsystem) -> DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            Paused::<T>::take(subsystem).ok_or(Error::<T>::NotPaused)?;
+
+            Self::deposit_event(Event::SubsystemResumed { subsystem });
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> PauseGuard for Pallet<T> {
+    fn is_paused(subsystem: Subsystem) -> bool {
+        Paused::<T>::contains_key(subsystem)
+    }
+}
+
+/// Weight functions needed for benchmarking
+pub trait WeightInfo {
+    fn pause() -> Weight;
+    fn resume() -> Weight;
+}
+
+/// Default weights (based on complexity analysis)
+impl WeightInfo for () {
+    fn pause() -> Weight { Weight::from_parts(15_000, 0) }
+    fn resume() -> Weight { Weight::from_parts(15_000, 0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as pallet_safeguard;
+    use frame_support::{assert_noop, assert_ok, derive_impl};
+    use sp_runtime::{BuildStorage, DispatchError};
+
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test
+        {
+            System: frame_system,
+            Safeguard: pallet_safeguard,
+        }
+    );
+
+    #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+    impl frame_system::Config for Test {
+        type Block = Block;
+    }
+
+    impl Config for Test {
+        type RuntimeEvent = RuntimeEvent;
+        type PauseOrigin = frame_system::EnsureRoot<u64>;
+        type WeightInfo = ();
+    }
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        let mut ext: sp_io::TestExternalities = t.into();
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    #[test]
+    fn subsystems_pause_and_resume_independently() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(Safeguard::pause(RuntimeOrigin::signed(1), Subsystem::Ibc), DispatchError::BadOrigin);
+
+            assert_ok!(Safeguard::pause(RuntimeOrigin::root(), Subsystem::Ibc));
+            System::assert_last_event(Event::SubsystemPaused { subsystem: Subsystem::Ibc }.into());
+            assert!(Safeguard::is_paused(Subsystem::Ibc));
+            assert!(!Safeguard::is_paused(Subsystem::Oracle));
+            assert_eq!(Safeguard::paused_since(Subsystem::Ibc), Some(1));
+            assert_noop!(Safeguard::pause(RuntimeOrigin::root(), Subsystem::Ibc), Error::<Test>::AlreadyPaused);
+
+            assert_ok!(Safeguard::resume(RuntimeOrigin::root(), Subsystem::Ibc));
+            System::assert_last_event(Event::SubsystemResumed { subsystem: Subsystem::Ibc }.into());
+            assert!(!Safeguard::is_paused(Subsystem::Ibc));
+            assert_noop!(Safeguard::resume(RuntimeOrigin::root(), Subsystem::Ibc), Error::<Test>::NotPaused);
+        });
+    }
+}
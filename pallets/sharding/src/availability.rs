@@ -0,0 +1,229 @@
+//! # Shard Data Availability
+//!
+//! Every block, the data of each shard ([`ShardBlockData`]: the transfers settled on it in the
+//! block and the head of its pending cross-shard queue, up to `MaxShardDataLen` bytes in all)
+//! is SCALE-encoded, split into [`DATA_CHUNKS`] chunks and extended with a Reed-Solomon code
+//! over GF(2^8) to [`TOTAL_CHUNKS`] chunks, any [`DATA_CHUNKS`] of which [`reconstruct`] the data. The chunks are the leaves of a binary
+//! Merkle tree whose root is stored in `ShardDataCommitments`.
+//!
+//! A light validator of a shard samples a few random chunks, e.g. from the node's
+//! `shardSync_dataChunk` RPC, and checks each with [`verify_chunk`] against the commitment
+//! of a finalized block. If every sample verifies, the shard's data can be reconstructed from
+//! the chunks the network serves with high probability, without downloading all of it.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_runtime::traits::Hash;
+use sp_std::vec::Vec;
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+use crate::CrossShardTx;
+
+/// Chunks the encoded data is split into
+pub const DATA_CHUNKS: usize = 8;
+
+/// Chunks after erasure coding; a power of two, so the Merkle tree over them is perfect
+pub const TOTAL_CHUNKS: usize = 16;
+
+/// Data of a shard in one block, erasure-coded for availability sampling
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ShardBlockData<AccountId, Balance> {
+    /// Head of the cross-shard transactions waiting to settle on the shard at the end of the
+    /// block
+    pub queue: Vec<CrossShardTx<AccountId, Balance>>,
+    /// Cross-shard transactions settled on the shard in the block
+    pub settled: Vec<CrossShardTx<AccountId, Balance>>,
+}
+
+/// Commitment to the erasure-coded data of a shard in one block
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct DataCommitment<Hash> {
+    /// Root of the Merkle tree over the [`TOTAL_CHUNKS`] chunks
+    pub root: Hash,
+    /// Length of the encoded [`ShardBlockData`], to strip the padding after reconstruction
+    pub data_len: u32,
+}
+
+/// Erasure-coded chunk with its Merkle proof
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct DataChunk<Hash> {
+    /// Position of the chunk; the first [`DATA_CHUNKS`] hold the data itself
+    pub index: u16,
+    /// Chunk bytes
+    pub data: Vec<u8>,
+    /// Sibling hashes from the chunk's leaf up to the root
+    pub proof: Vec<Hash>,
+}
+
+/// Exponentials and logarithms of GF(2^8) with the polynomial x^8 + x^4 + x^3 + x^2 + 1
+const fn gf_tables() -> ([u8; 512], [u8; 256]) {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= 0x11d;
+        }
+        i += 1;
+    }
+    // Doubled, so products index it without reducing the summed logarithms
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+    (exp, log)
+}
+
+const GF_EXP: [u8; 512] = gf_tables().0;
+const GF_LOG: [u8; 256] = gf_tables().1;
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    GF_EXP[GF_LOG[a as usize] as usize + GF_LOG[b as usize] as usize]
+}
+
+fn gf_div(a: u8, b: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    GF_EXP[GF_LOG[a as usize] as usize + 255 - GF_LOG[b as usize] as usize]
+}
+
+/// Coefficients of the chunks at `points` in the interpolation of the chunk at `target`
+///
+/// Chunk `i` holds the evaluations at point `i` of the polynomials of degree below
+/// [`DATA_CHUNKS`] through the data bytes, one polynomial per byte offset.
+fn lagrange_coefficients(points: &[u8], target: u8) -> Vec<u8> {
+    points
+        .iter()
+        .map(|&point| {
+            points.iter().filter(|&&other| other != point).fold(1, |coefficient, &other| {
+                // Subtraction is addition, i.e. XOR, in GF(2^8)
+                gf_mul(coefficient, gf_div(target ^ other, point ^ other))
+            })
+        })
+        .collect()
+}
+
+/// Chunk at `target` interpolated from `chunks` at `points`
+fn interpolate(points: &[u8], chunks: &[&[u8]], target: u8) -> Vec<u8> {
+    let coefficients = lagrange_coefficients(points, target);
+    let mut chunk = sp_std::vec![0u8; chunks[0].len()];
+    for (coefficient, source) in coefficients.iter().zip(chunks) {
+        for (byte, &value) in chunk.iter_mut().zip(source.iter()) {
+            *byte ^= gf_mul(*coefficient, value);
+        }
+    }
+    chunk
+}
+
+/// Split `data` into [`DATA_CHUNKS`] zero-padded chunks and extend them to [`TOTAL_CHUNKS`]
+pub fn encode(data: &[u8]) -> Vec<Vec<u8>> {
+    let chunk_len = data.len().div_ceil(DATA_CHUNKS).max(1);
+    let mut chunks: Vec<Vec<u8>> = (0..DATA_CHUNKS)
+        .map(|i| {
+            let mut chunk = data.iter().skip(i * chunk_len).take(chunk_len).copied().collect::<Vec<_>>();
+            chunk.resize(chunk_len, 0);
+            chunk
+        })
+        .collect();
+
+    let points: Vec<u8> = (0..DATA_CHUNKS as u8).collect();
+    let parity: Vec<Vec<u8>> = {
+        let sources: Vec<&[u8]> = chunks.iter().map(Vec::as_slice).collect();
+        (DATA_CHUNKS..TOTAL_CHUNKS).map(|target| interpolate(&points, &sources, target as u8)).collect()
+    };
+    chunks.extend(parity);
+    chunks
+}
+
+/// The `data_len` bytes [`encode`] was given, from any [`DATA_CHUNKS`] distinct chunks of
+/// equal length
+pub fn reconstruct(chunks: &[(u16, Vec<u8>)], data_len: usize) -> Option<Vec<u8>> {
+    let mut points = Vec::new();
+    let mut sources: Vec<&[u8]> = Vec::new();
+    for (index, chunk) in chunks {
+        let point = u8::try_from(*index).ok().filter(|point| usize::from(*point) < TOTAL_CHUNKS)?;
+        if points.len() < DATA_CHUNKS && !points.contains(&point) {
+            points.push(point);
+            sources.push(chunk.as_slice());
+        }
+    }
+    if points.len() < DATA_CHUNKS || sources.iter().any(|chunk| chunk.len() != sources[0].len()) {
+        return None;
+    }
+
+    let mut data: Vec<u8> = (0..DATA_CHUNKS as u8)
+        .flat_map(|target| match points.iter().position(|&point| point == target) {
+            Some(known) => sources[known].to_vec(),
+            None => interpolate(&points, &sources, target),
+        })
+        .collect();
+    if data.len() < data_len {
+        return None;
+    }
+    data.truncate(data_len);
+    Some(data)
+}
+
+/// Levels of the Merkle tree over `chunks`, from the leaves to the root
+fn tree_levels<H: Hash>(chunks: &[Vec<u8>]) -> Vec<Vec<H::Output>> {
+    let mut levels = sp_std::vec![chunks.iter().map(|chunk| H::hash(chunk)).collect::<Vec<_>>()];
+    while levels.last().map_or(false, |level| level.len() > 1) {
+        let next = levels
+            .last()
+            .expect("checked above; qed")
+            .chunks(2)
+            .map(|pair| H::hash(&[pair[0].as_ref(), pair[pair.len() - 1].as_ref()].concat()))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Root of the Merkle tree over `chunks`, as committed to in [`DataCommitment::root`]
+pub fn chunks_root<H: Hash>(chunks: &[Vec<u8>]) -> H::Output {
+    tree_levels::<H>(chunks).last().and_then(|root| root.first().copied()).unwrap_or_default()
+}
+
+/// Chunk `index` of `chunks` with its Merkle proof, or `None` if there is no such chunk
+pub fn chunk_with_proof<H: Hash>(chunks: &[Vec<u8>], index: u16) -> Option<DataChunk<H::Output>> {
+    let data = chunks.get(usize::from(index))?.clone();
+    let levels = tree_levels::<H>(chunks);
+    let proof = levels[..levels.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(depth, level)| {
+            let position = usize::from(index) >> depth;
+            level[(position ^ 1).min(level.len() - 1)]
+        })
+        .collect();
+    Some(DataChunk { index, data, proof })
+}
+
+/// Whether `chunk` is a leaf of the Merkle tree with root `root`
+pub fn verify_chunk<H: Hash>(root: &H::Output, chunk: &DataChunk<H::Output>) -> bool {
+    // A shorter proof would let an inner node's preimage pass as a chunk
+    if usize::from(chunk.index) >= TOTAL_CHUNKS || chunk.proof.len() != TOTAL_CHUNKS.trailing_zeros() as usize {
+        return false;
+    }
+    let computed = chunk.proof.iter().enumerate().fold(H::hash(&chunk.data), |node, (depth, sibling)| {
+        if (chunk.index >> depth) & 1 == 0 {
+            H::hash(&[node.as_ref(), sibling.as_ref()].concat())
+        } else {
+            H::hash(&[sibling.as_ref(), node.as_ref()].concat())
+        }
+    });
+    computed == *root
+}
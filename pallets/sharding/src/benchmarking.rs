@@ -0,0 +1,151 @@
+//! Benchmarking setup for pallet-sharding
+//!
+//! Queue settlement is benchmarked over the transfers settled `n` and the queue length `q`,
+//! with the queue filled directly in storage, each transfer held on its sender and carrying a
+//! different tip so the queue has to be ordered. Submission is benchmarked into a queue one
+//! short of full.
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Sharding;
+use frame_benchmarking::v2::*;
+use frame_system::RawOrigin;
+use sp_std::vec;
+
+const SEED: u32 = 0;
+
+/// Create every shard, each with one validator
+fn init_shards<T: Config>() {
+    let validators = (0..SHARD_COUNT as u32).map(|shard| vec![account("validator", shard, SEED)]).collect();
+    assert!(Pallet::<T>::initialize_sharding(RawOrigin::Root.into(), validators).is_ok());
+}
+
+/// An account that can pay `transfers` cross-shard transfers of the existential deposit into
+/// `to_shard`, with tips up to `transfers`, and stay alive
+fn funded<T: Config>(name: &'static str, to_shard: ShardId, transfers: u32) -> T::AccountId {
+    let who: T::AccountId = account(name, 0, SEED);
+    let fee = Pallet::<T>::cross_shard_fee(to_shard).expect("shard exists");
+    let per_transfer = T::Currency::minimum_balance().saturating_add(fee).saturating_add(transfers.into());
+    let balance = per_transfer.saturating_mul(transfers.into()).saturating_add(T::Currency::minimum_balance());
+    T::Currency::set_balance(&who, balance);
+    who
+}
+
+/// The shard after the one the account `name` lives on
+fn other_shard<T: Config>(name: &'static str) -> ShardId {
+    (Pallet::<T>::get_account_shard(&account(name, 0, SEED)) + 1) % SHARD_COUNT
+}
+
+/// Queue `q` transfers from `sender` to `recipient` on `to_shard`, each with a higher tip than
+/// the one before
+fn fill_queue<T: Config>(sender: &T::AccountId, recipient: &T::AccountId, to_shard: ShardId, q: u32) {
+    let fee = Pallet::<T>::cross_shard_fee(to_shard).expect("shard exists");
+    let amount = T::Currency::minimum_balance();
+    let now = frame_system::Pallet::<T>::block_number();
+    let queue: Vec<VersionedCrossShardTx<T::AccountId, BalanceOf<T>>> = (0..q)
+        .map(|nonce| {
+            let tx = CrossShardTx {
+                from_shard: Pallet::<T>::get_account_shard(sender),
+                to_shard,
+                sender: sender.clone(),
+                recipient: recipient.clone(),
+                amount,
+                nonce: nonce.into(),
+                fee,
+                tip: nonce.into(),
+            };
+            let held = amount.saturating_add(fee).saturating_add(tx.tip);
+            T::Currency::hold(&HoldReason::CrossShardEscrow.into(), sender, held).expect("sender is funded");
+            let tx_hash = BlakeTwo256::hash_of(&tx);
+            CrossShardTxStatuses::<T>::insert(tx_hash, CrossShardTxStatus::Queued);
+            QueuedAt::<T>::insert(tx_hash, now);
+            tx.into()
+        })
+        .collect();
+    OldestQueuedAt::<T>::insert(to_shard, now);
+    CrossShardQueue::<T>::insert(to_shard, BoundedVec::truncate_from(queue));
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn initialize_sharding() {
+        let validators: Vec<Vec<T::AccountId>> =
+            (0..SHARD_COUNT as u32).map(|shard| vec![account("validator", shard, SEED)]).collect();
+
+        #[extrinsic_call]
+        _(RawOrigin::Root, validators);
+
+        assert!(ShardInfos::<T>::contains_key(SHARD_COUNT - 1));
+    }
+
+    #[benchmark]
+    fn join_shard() {
+        init_shards::<T>();
+        let who: T::AccountId = account("joiner", 0, SEED);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(who.clone()), 0);
+
+        assert!(ShardInfos::<T>::get(0).is_some_and(|info| info.validators.contains(&who)));
+    }
+
+    #[benchmark]
+    fn execute_cross_shard() {
+        init_shards::<T>();
+        let to_shard = other_shard::<T>("sender");
+        let sender = funded::<T>("sender", to_shard, 1);
+        let recipient = funded::<T>("recipient", to_shard, 0);
+        let queued = T::MaxCrossShardQueueLen::get().saturating_sub(1);
+        fill_queue::<T>(&funded::<T>("queued", to_shard, queued), &recipient, to_shard, queued);
+        let amount = T::Currency::minimum_balance();
+
+        #[extrinsic_call]
+        execute_cross_shard_tx(RawOrigin::Signed(sender), to_shard, recipient, amount);
+
+        assert_eq!(CrossShardQueue::<T>::decode_len(to_shard), Some(queued as usize + 1));
+    }
+
+    #[benchmark]
+    fn process_cross_shard_queue(
+        n: Linear<0, { T::MaxSettlementsPerBlock::get() }>,
+        q: Linear<0, { T::MaxCrossShardQueueLen::get() }>,
+    ) {
+        init_shards::<T>();
+        let q = q.max(n);
+        let to_shard = other_shard::<T>("sender");
+        let sender = funded::<T>("sender", to_shard, q);
+        let recipient = funded::<T>("recipient", to_shard, 0);
+        let processor = funded::<T>("processor", to_shard, 0);
+        fill_queue::<T>(&sender, &recipient, to_shard, q);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(processor), to_shard, n);
+
+        assert_eq!(CrossShardQueue::<T>::decode_len(to_shard), Some((q - n) as usize));
+    }
+
+    #[benchmark]
+    fn update_metrics() {
+        init_shards::<T>();
+        let caller: T::AccountId = account("caller", 0, SEED);
+
+        #[extrinsic_call]
+        update_performance_metrics(RawOrigin::Signed(caller), 1_000, 100, 6_000);
+
+        assert_eq!(Metrics::<T>::get().current_tps, 100);
+    }
+
+    #[benchmark]
+    fn set_system_shard() {
+        #[extrinsic_call]
+        _(RawOrigin::Root, Some(SYSTEM_SHARD));
+
+        assert_eq!(ReservedSystemShard::<T>::get(), Some(SYSTEM_SHARD));
+    }
+
+    impl_benchmark_test_suite!(Sharding, crate::tests::new_test_ext(), crate::tests::Test);
+}
@@ -0,0 +1,268 @@
+//! # Sharding Transaction Extensions
+//!
+//! - [`CheckShardAffinity`]: signed transactions carry the shard of their signer. The tag is
+//!   checked against the signer's assignment during validation, so the node can partition its
+//!   transaction pool by shard from the extrinsic bytes alone, without touching runtime state.
+//!   While the signer is moving between shards, either shard of the move is accepted. While
+//!   governance reserves a system shard, system calls must be tagged with it instead.
+//! - [`CheckShardNonce`]: replaces `frame_system::CheckNonce`. Besides the account's global
+//!   nonce, a transaction may use the lane counter of a shard it may be tagged with, so one
+//!   account can have transactions in flight on several shards without them queueing behind
+//!   each other.
+
+use core::marker::PhantomData;
+
+use codec::{Decode, DecodeWithMemTracking, Encode};
+use frame_support::{traits::Get, weights::Weight};
+use scale_info::TypeInfo;
+use sp_runtime::{
+    impl_tx_ext_default,
+    traits::{
+        AsSystemOriginSigner, DispatchInfoOf, Dispatchable, Implication, One,
+        TransactionExtension, ValidateResult, Zero,
+    },
+    transaction_validity::{
+        InvalidTransaction, TransactionLongevity, TransactionSource, TransactionValidityError,
+        ValidTransaction,
+    },
+};
+use sp_std::vec;
+
+use crate::{Config, Pallet, ShardId, ShardNonces};
+
+/// Invalid transaction custom error: the shard tag does not match the signer's shard
+pub const INVALID_SHARD_AFFINITY: u8 = 10;
+
+/// Invalid transaction custom error: the nonce lane names a shard the signer's transactions
+/// may not be tagged with
+pub const INVALID_NONCE_LANE: u8 = 11;
+
+/// Prefix of the pool tags of lane nonces, keeping them apart from global nonce tags
+const LANE_TAG_PREFIX: &[u8] = b"shard-nonce";
+
+/// Tag a signed transaction with the shard of its signer
+#[derive(Encode, Decode, DecodeWithMemTracking, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckShardAffinity<T: Config + Send + Sync>(pub ShardId, PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckShardAffinity<T> {
+    /// Tag a transaction as belonging to `shard`
+    pub fn new(shard: ShardId) -> Self {
+        Self(shard, PhantomData)
+    }
+
+    /// The shard this transaction is tagged with
+    pub fn shard(&self) -> ShardId {
+        self.0
+    }
+}
+
+impl<T: Config + Send + Sync> core::fmt::Debug for CheckShardAffinity<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "CheckShardAffinity({})", self.0)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut core::fmt::Formatter) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Config + Send + Sync> TransactionExtension<T::RuntimeCall> for CheckShardAffinity<T>
+where
+    <T::RuntimeCall as Dispatchable>::RuntimeOrigin: AsSystemOriginSigner<T::AccountId> + Clone,
+{
+    const IDENTIFIER: &'static str = "CheckShardAffinity";
+    type Implicit = ();
+    type Val = ();
+    type Pre = ();
+
+    fn weight(&self, _call: &T::RuntimeCall) -> Weight {
+        // The reserved system shard, and the account's moved assignment and pending move
+        T::DbWeight::get().reads(3)
+    }
+
+    fn validate(
+        &self,
+        origin: <T::RuntimeCall as Dispatchable>::RuntimeOrigin,
+        call: &T::RuntimeCall,
+        _info: &DispatchInfoOf<T::RuntimeCall>,
+        _len: usize,
+        _self_implicit: Self::Implicit,
+        _inherited_implication: &impl Implication,
+        _source: TransactionSource,
+    ) -> ValidateResult<Self::Val, T::RuntimeCall> {
+        let Some(who) = origin.as_system_origin_signer() else {
+            // Unsigned and general transactions are not bound to a shard
+            return Ok((ValidTransaction::default(), (), origin));
+        };
+
+        if !Pallet::<T>::accepts_transaction_tag(who, call, self.0) {
+            return Err(InvalidTransaction::Custom(INVALID_SHARD_AFFINITY).into());
+        }
+
+        Ok((ValidTransaction::default(), (), origin))
+    }
+
+    impl_tx_ext_default!(T::RuntimeCall; prepare);
+}
+
+/// Nonce of a signed transaction
+#[derive(Encode, Decode, DecodeWithMemTracking, Clone, Copy, Eq, PartialEq, Debug, TypeInfo)]
+pub enum ShardNonce<Nonce> {
+    /// The `frame_system` account nonce, exactly as checked by `CheckNonce`
+    Global(#[codec(compact)] Nonce),
+    /// The account's counter on the lane of `shard`
+    ///
+    /// Only the shards [`CheckShardAffinity`] accepts as the transaction's tag have lanes, so a
+    /// lane transaction is pooled and ordered with the shard it is tagged for.
+    Lane {
+        /// Shard the lane belongs to
+        shard: ShardId,
+        /// Expected lane counter
+        #[codec(compact)]
+        counter: u64,
+    },
+}
+
+/// Check and bump the global or per-shard lane nonce of a signed transaction
+#[derive(Encode, Decode, DecodeWithMemTracking, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckShardNonce<T: Config + Send + Sync>(pub ShardNonce<T::Nonce>);
+
+impl<T: Config + Send + Sync> CheckShardNonce<T> {
+    /// Use the account's global nonce
+    pub fn global(nonce: T::Nonce) -> Self {
+        Self(ShardNonce::Global(nonce))
+    }
+
+    /// Use the account's lane on `shard`
+    pub fn lane(shard: ShardId, counter: u64) -> Self {
+        Self(ShardNonce::Lane { shard, counter })
+    }
+}
+
+impl<T: Config + Send + Sync> core::fmt::Debug for CheckShardNonce<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "CheckShardNonce({:?})", self.0)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut core::fmt::Formatter) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+/// Compare a transaction's nonce with the expected one
+fn check_exact<N: PartialOrd>(nonce: N, expected: N) -> Result<(), TransactionValidityError> {
+    if nonce < expected {
+        Err(InvalidTransaction::Stale.into())
+    } else if nonce > expected {
+        Err(InvalidTransaction::Future.into())
+    } else {
+        Ok(())
+    }
+}
+
+impl<T: Config + Send + Sync> TransactionExtension<T::RuntimeCall> for CheckShardNonce<T>
+where
+    <T::RuntimeCall as Dispatchable>::RuntimeOrigin: AsSystemOriginSigner<T::AccountId> + Clone,
+{
+    const IDENTIFIER: &'static str = "CheckShardNonce";
+    type Implicit = ();
+    type Val = Option<(T::AccountId, ShardNonce<T::Nonce>)>;
+    type Pre = ();
+
+    fn weight(&self, _call: &T::RuntimeCall) -> Weight {
+        match self.0 {
+            ShardNonce::Global(_) => T::DbWeight::get().reads_writes(1, 1),
+            // The account, its lane, and the shard tag checks of `CheckShardAffinity`
+            ShardNonce::Lane { .. } => T::DbWeight::get().reads_writes(5, 1),
+        }
+    }
+
+    fn validate(
+        &self,
+        origin: <T::RuntimeCall as Dispatchable>::RuntimeOrigin,
+        call: &T::RuntimeCall,
+        _info: &DispatchInfoOf<T::RuntimeCall>,
+        _len: usize,
+        _self_implicit: Self::Implicit,
+        _inherited_implication: &impl Implication,
+        _source: TransactionSource,
+    ) -> ValidateResult<Self::Val, T::RuntimeCall> {
+        let Some(who) = origin.as_system_origin_signer() else {
+            return Ok((ValidTransaction::default(), None, origin));
+        };
+
+        let account = frame_system::Account::<T>::get(who);
+        if account.providers.is_zero() && account.sufficients.is_zero() {
+            // Nonce storage is not paid for
+            return Err(InvalidTransaction::Payment.into());
+        }
+
+        let (provides, requires) = match self.0 {
+            ShardNonce::Global(nonce) => {
+                if nonce < account.nonce {
+                    return Err(InvalidTransaction::Stale.into());
+                }
+                let requires = if nonce > account.nonce {
+                    vec![(who, nonce - One::one()).encode()]
+                } else {
+                    vec![]
+                };
+                (vec![(who, nonce).encode()], requires)
+            },
+            ShardNonce::Lane { shard, counter } => {
+                if !Pallet::<T>::accepts_transaction_tag(who, call, shard) {
+                    return Err(InvalidTransaction::Custom(INVALID_NONCE_LANE).into());
+                }
+                let current = ShardNonces::<T>::get(who, shard);
+                if counter < current {
+                    return Err(InvalidTransaction::Stale.into());
+                }
+                let requires = if counter > current {
+                    vec![(LANE_TAG_PREFIX, who, shard, counter - 1).encode()]
+                } else {
+                    vec![]
+                };
+                (vec![(LANE_TAG_PREFIX, who, shard, counter).encode()], requires)
+            },
+        };
+
+        let validity = ValidTransaction {
+            priority: 0,
+            requires,
+            provides,
+            longevity: TransactionLongevity::MAX,
+            propagate: true,
+        };
+        Ok((validity, Some((who.clone(), self.0)), origin))
+    }
+
+    fn prepare(
+        self,
+        val: Self::Val,
+        _origin: &<T::RuntimeCall as Dispatchable>::RuntimeOrigin,
+        _call: &T::RuntimeCall,
+        _info: &DispatchInfoOf<T::RuntimeCall>,
+        _len: usize,
+    ) -> Result<Self::Pre, TransactionValidityError> {
+        let Some((who, nonce)) = val else { return Ok(()) };
+
+        match nonce {
+            ShardNonce::Global(nonce) => frame_system::Account::<T>::try_mutate(&who, |account| {
+                check_exact(nonce, account.nonce)?;
+                account.nonce += T::Nonce::one();
+                Ok(())
+            }),
+            ShardNonce::Lane { shard, counter } => ShardNonces::<T>::try_mutate(&who, shard, |current| {
+                check_exact(counter, *current)?;
+                *current = current.saturating_add(1);
+                Ok(())
+            }),
+        }
+    }
+}
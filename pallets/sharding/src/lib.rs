@@ -6,26 +6,95 @@
 //!
 //! ## Features
 //! - 4 parallel shards with automatic state division
-//! - Cross-shard transaction support
+//! - Cross-shard transaction support: fee and amount are held on the sender until settlement,
+//!   and released on failure
+//! - Settlement rewards: fees of delivered transfers go to a fee pot, which pays callers of
+//!   `process_cross_shard_queue` `SettlementReward` per delivered transfer, up to
+//!   `MaxSettlementRewardPerBlock` per block; the pot is endowed with the existential deposit
+//!   at genesis and never paid out below it
+//! - Cross-shard tips (`execute_cross_shard_tx_with_tip`): higher tips settle first and are
+//!   paid to the caller of `process_cross_shard_queue`, to expedite transfers under congestion
+//! - Queue latency escalation: once the oldest transfer of a shard's queue waited longer than
+//!   `MaxQueueLatency`, the queue settles oldest first with operational priority until it is
+//!   back within it, and `QueueLatencyBreached` is emitted; `ShardingApi::oldest_queue_age`
+//!   exposes the age for alerting
+//! - Dead-letter queue for transfers their recipient cannot receive: the recipient can claim
+//!   them later, the sender can take a refund, and they are refunded after `DeadLetterTimeout`
+//! - Cross-shard contract call forwarding with asynchronous receipts, kept for
+//!   `CrossShardCallReceiptLifetime` blocks; calls are capped at `MaxCrossShardCallGas`, which
+//!   `process_cross_shard_calls` charges up front for each call it may execute
+//! - Receipts of settled cross-shard transfers ([`CrossShardTxReceipts`]), which contracts
+//!   read through the chain extension to act once funds arrived on another shard; they and
+//!   the statuses of settled and failed transfers are kept for `CrossShardTxRecordLifetime`
+//!   blocks
 //! - Parallel transaction processing with Rust async
 //! - Validator distribution across shards for decentralization
 //! - High-performance memory pool optimization
+//! - Per-block shard state commitment roots for light-client verification
+//! - Versioned cross-shard transfer layouts ([`CrossShardTxV0`], [`CrossShardTxV1`],
+//!   [`CrossShardTxV2`]): queued, settled and dead-lettered transfers are stored tagged with
+//!   their version in [`VersionedCrossShardTx`], so older encodings still decode after a layout
+//!   change
+//! - Bounded queues: at most `MaxCrossShardQueueLen` transfers and `MaxCrossShardCallQueueLen`
+//!   contract calls wait per shard; further ones are rejected until the queue drains
+//! - Bounded storage: every stored vector has a bound (`MaxValidatorsPerShard` validators,
+//!   `MaxCrossShardCallOutputLen` bytes of call output); at most `MaxExpiringPerBlock` dead
+//!   letters, call receipts and transfer records each expire at a block, further ones at the
+//!   next block with room
+//! - Data availability sampling: each shard's queue and settlements of the block are
+//!   erasure-coded into chunks committed to in [`ShardDataCommitments`] (see [`availability`])
+//! - Load shedding: transactions into a shard at capacity pay a surge fee or are rejected
+//! - Quoted cross-shard fees ([`CrossShardFeeQuote`]), e.g. a fixed fiat cost at an oracle
+//!   price, clamped to governance-set bounds and falling back to `CrossShardFee`
+//! - Shard-tagged transactions ([`CheckShardAffinity`]) for shard-partitioned transaction pools
+//! - Reserved system shard (`set_system_shard`): system transactions, as classified by
+//!   `SystemCalls` (staking, governance, IBC handshakes), must be tagged with a shard
+//!   governance designates, a regular one or the virtual [`SYSTEM_SHARD`] no other transaction
+//!   may use, so user load on the account shards cannot crowd them out of the pool
+//! - Per-shard nonce lanes ([`CheckShardNonce`]) so an account can transact on several shards at once
+//! - Dry runs of cross-shard transfers (`ShardingApi::dry_run_cross_shard`) so wallets can show
+//!   the fee and expected settlement delay before submission
+//! - Shard epochs: rebalancing announces account moves an epoch ahead in
+//!   [`PendingMigrations`] and applies them at the next epoch boundary; until the end of that
+//!   epoch, transactions may be tagged with either shard of the move
+//! - Shard splits (`begin_shard_split`): half of a shard's accounts, chosen by re-hashing them,
+//!   move to another shard over as many blocks as `MaxSplitAccountsPerBlock` takes, while
+//!   rebalancing waits for the split to complete
+//! - Versioned shard mapping: accounts not moved by rebalancing live on the shard their id
+//!   hashes to under [`CurrentShardMapping`]. Upgrading the mapping (`migrations::v4`) first
+//!   pins every account the new mapping would move to its current shard, over as many blocks
+//!   as `MaxSplitAccountsPerBlock` takes, so no account changes shard
+//! - Indexed event topics: every event about a shard is filed under
+//!   [`Pallet::shard_topic`], and cross-shard transfers and calls also under
+//!   [`Pallet::account_topic`] of each party, so clients can follow them through
+//!   `System::EventTopics` instead of filtering every event
+//!
+//! Queue processing logs to the [`LOG_TARGET`] target within `process_cross_shard_queue` and
+//! `process_cross_shard_calls` tracing spans, naming the shard and the transfer hash or call id.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use frame_support::{
     dispatch::{DispatchResult, DispatchError},
     pallet_prelude::*,
-    traits::{Get, StorageVersion},
+    traits::{
+        fungible::{Inspect, InspectHold, Mutate, MutateHold},
+        tokens::{Fortitude, Precision, Preservation, Restriction},
+        Contains, Get, StorageVersion,
+    },
     PalletId,
 };
 use frame_system::pallet_prelude::*;
 use sp_runtime::{
-    traits::{AccountIdConversion, Saturating, Zero, Hash, BlakeTwo256},
-    SaturatedConversion,
+    traits::{AccountIdConversion, AtLeast32BitUnsigned, One, Saturating, Zero, Hash, BlakeTwo256},
+    Perbill, SaturatedConversion, StateVersion,
 };
-use sp_std::{vec::Vec, collections::btree_map::BTreeMap};
+use sp_std::{vec::Vec, marker::PhantomData};
 use codec::{Encode, Decode};
+use sp_core::{U256, hashing::blake2_256};
+use pallet_history::{HistoryKind, HistoryRecorder};
+use pallet_safeguard::{PauseGuard, Subsystem};
+pub use netchain_primitives::FailureReason;
 use scale_info::TypeInfo;
 
 #[cfg(feature = "std")]
@@ -33,26 +102,56 @@ use serde::{Deserialize, Serialize};
 
 pub use pallet::*;
 
+pub mod availability;
+pub use availability::{DataChunk, DataCommitment, ShardBlockData};
+pub mod extension;
+pub use extension::{CheckShardAffinity, CheckShardNonce, ShardNonce};
+pub mod migrations;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::WeightInfo;
+
 /// Current storage version
-const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(8);
 
-/// Shard identifier type
-pub type ShardId = u8;
+pub use netchain_primitives::{ShardId, ShardMappingVersion, SHARD_COUNT, SHARD_MAPPING_VERSION, SYSTEM_SHARD};
+
+/// Shard epoch index
+pub type EpochIndex = u32;
 
 /// Transaction batch size for parallel processing
 pub const BATCH_SIZE: u32 = 100;
 
-/// Number of shards in the network
-pub const SHARD_COUNT: u8 = 4;
+/// Prefix of shard event topics, which hash it followed by the shard id
+pub const SHARD_TOPIC_PREFIX: &[u8] = b"shard";
+
+/// Prefix of account event topics, which hash it followed by the SCALE-encoded account id
+pub const ACCOUNT_TOPIC_PREFIX: &[u8] = b"shard-account";
+
+/// Salt of the hash selecting the accounts a shard split moves
+pub const SHARD_SPLIT_SALT: &[u8] = b"shard-split";
+
+/// Target of the pallet's log lines
+pub const LOG_TARGET: &str = "runtime::sharding";
 
 /// Shard information structure
-#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-pub struct ShardInfo<AccountId, Balance> {
+#[cfg_attr(
+    feature = "std",
+    serde(bound(
+        serialize = "AccountId: Serialize, Balance: Serialize",
+        deserialize = "AccountId: Deserialize<'de>, Balance: Deserialize<'de>, MaxValidators: Get<u32>"
+    ))
+)]
+#[scale_info(skip_type_params(MaxValidators))]
+#[codec(mel_bound(AccountId: MaxEncodedLen, Balance: MaxEncodedLen, MaxValidators: Get<u32>))]
+pub struct ShardInfo<AccountId, Balance, MaxValidators> {
     /// Shard identifier
     pub shard_id: ShardId,
-    /// Active validators in this shard
-    pub validators: Vec<AccountId>,
+    /// Active validators in this shard, at most `MaxValidatorsPerShard`
+    pub validators: BoundedVec<AccountId, MaxValidators>,
     /// Total stake in this shard
     pub total_stake: Balance,
     /// Transactions processed in current block
@@ -61,10 +160,68 @@ pub struct ShardInfo<AccountId, Balance> {
     pub capacity: u32,
 }
 
-/// Cross-shard transaction structure
-#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+/// [`ShardInfo`] as returned by `ShardingApi::shard_info`, whose callers do not know the
+/// runtime's `MaxValidatorsPerShard`; it encodes as the stored one
+pub type ShardInfoOf<AccountId, Balance> = ShardInfo<AccountId, Balance, ConstU32<{ u32::MAX }>>;
+
+impl<AccountId, Balance, MaxValidators: Get<u32>> ShardInfo<AccountId, Balance, MaxValidators> {
+    /// The shard information without its bound on validators
+    pub fn unbounded(self) -> ShardInfoOf<AccountId, Balance> {
+        ShardInfo {
+            shard_id: self.shard_id,
+            validators: BoundedVec::truncate_from(self.validators.into_inner()),
+            total_stake: self.total_stake,
+            tx_count: self.tx_count,
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// Cross-shard transaction structure, in its latest layout
+///
+/// Transfers are identified by the hash of this encoding, so a new layout is added as a
+/// `CrossShardTxV3` and a variant of [`VersionedCrossShardTx`], with a storage migration,
+/// rather than by changing this one.
+pub type CrossShardTx<AccountId, Balance> = CrossShardTxV2<AccountId, Balance>;
+
+/// Cross-shard transaction as stored in storage version 1, before the fee was recorded
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrossShardTxV0<AccountId, Balance> {
+    /// Source shard
+    pub from_shard: ShardId,
+    /// Destination shard
+    pub to_shard: ShardId,
+    /// Transaction sender
+    pub sender: AccountId,
+    /// Transaction recipient
+    pub recipient: AccountId,
+    /// Amount to transfer
+    pub amount: Balance,
+    /// Transaction nonce
+    pub nonce: u64,
+}
+
+impl<AccountId, Balance: Zero> CrossShardTxV0<AccountId, Balance> {
+    /// The transfer with a zero fee: version 1 burnt the fee when queueing, so there is none
+    /// to refund
+    pub fn upgrade(self) -> CrossShardTxV1<AccountId, Balance> {
+        CrossShardTxV1 {
+            from_shard: self.from_shard,
+            to_shard: self.to_shard,
+            sender: self.sender,
+            recipient: self.recipient,
+            amount: self.amount,
+            nonce: self.nonce,
+            fee: Zero::zero(),
+        }
+    }
+}
+
+/// Cross-shard transaction as stored in storage version 2, with its fee but before tips
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-pub struct CrossShardTx<AccountId, Balance> {
+pub struct CrossShardTxV1<AccountId, Balance> {
     /// Source shard
     pub from_shard: ShardId,
     /// Destination shard
@@ -77,10 +234,406 @@ pub struct CrossShardTx<AccountId, Balance> {
     pub amount: Balance,
     /// Transaction nonce
     pub nonce: u64,
+    /// Cross-shard fee paid, refunded if settlement fails
+    pub fee: Balance,
+}
+
+impl<AccountId, Balance: Zero> CrossShardTxV1<AccountId, Balance> {
+    /// The transfer with a zero tip
+    pub fn upgrade(self) -> CrossShardTxV2<AccountId, Balance> {
+        CrossShardTxV2 {
+            from_shard: self.from_shard,
+            to_shard: self.to_shard,
+            sender: self.sender,
+            recipient: self.recipient,
+            amount: self.amount,
+            nonce: self.nonce,
+            fee: self.fee,
+            tip: Zero::zero(),
+        }
+    }
+}
+
+/// Cross-shard transaction as stored from storage version 3, with a tip
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrossShardTxV2<AccountId, Balance> {
+    /// Source shard
+    pub from_shard: ShardId,
+    /// Destination shard
+    pub to_shard: ShardId,
+    /// Transaction sender
+    pub sender: AccountId,
+    /// Transaction recipient
+    pub recipient: AccountId,
+    /// Amount to transfer
+    pub amount: Balance,
+    /// Pallet-wide transfer sequence number, so identical transfers still hash apart
+    pub nonce: u64,
+    /// Cross-shard fee paid, refunded if settlement fails
+    pub fee: Balance,
+    /// Tip paid to whoever settles the transfer; higher tips settle first
+    pub tip: Balance,
+}
+
+/// Cross-shard transaction in any of the layouts this chain has stored, tagged with its version
+///
+/// Variants keep their codec index across upgrades, so a transfer encoded before a layout
+/// change, e.g. by a client or in another chain's proof, still decodes after it and is
+/// upgraded with [`Self::into_latest`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum VersionedCrossShardTx<AccountId, Balance> {
+    /// [`CrossShardTxV0`]
+    #[codec(index = 0)]
+    V0(CrossShardTxV0<AccountId, Balance>),
+    /// [`CrossShardTxV1`]
+    #[codec(index = 1)]
+    V1(CrossShardTxV1<AccountId, Balance>),
+    /// [`CrossShardTxV2`]
+    #[codec(index = 2)]
+    V2(CrossShardTxV2<AccountId, Balance>),
+}
+
+impl<AccountId, Balance: Zero> VersionedCrossShardTx<AccountId, Balance> {
+    /// The transfer in the latest layout
+    pub fn into_latest(self) -> CrossShardTx<AccountId, Balance> {
+        match self {
+            Self::V0(tx) => tx.upgrade().upgrade(),
+            Self::V1(tx) => tx.upgrade(),
+            Self::V2(tx) => tx,
+        }
+    }
+}
+
+impl<AccountId, Balance> From<CrossShardTx<AccountId, Balance>> for VersionedCrossShardTx<AccountId, Balance> {
+    fn from(tx: CrossShardTx<AccountId, Balance>) -> Self {
+        Self::V2(tx)
+    }
+}
+
+/// Lifecycle of a cross-shard transfer
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CrossShardTxStatus {
+    /// Fee and amount held on the sender, waiting in the destination shard's queue
+    Queued,
+    /// Amount delivered to the recipient
+    Settled,
+    /// Settlement failed; the sender was refunded minus the failure penalty
+    Failed(FailureReason),
+    /// The recipient could not receive the amount; fee and amount stay held until the
+    /// transfer is claimed or times out
+    DeadLettered,
+}
+
+/// Proof that a cross-shard transfer reached its recipient, written when it settles
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrossShardTxReceipt<AccountId, Balance, BlockNumber> {
+    /// Account the amount was taken from
+    pub sender: AccountId,
+    /// Account the amount was paid to
+    pub recipient: AccountId,
+    /// Amount delivered
+    pub amount: Balance,
+    /// Shard of the sender
+    pub from_shard: ShardId,
+    /// Shard of the recipient
+    pub to_shard: ShardId,
+    /// Block in which the transfer settled
+    pub settled_at: BlockNumber,
+}
+
+/// Cross-shard transfer its recipient could not receive, see [`Pallet::claim_failed_transfer`]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct DeadLetter<AccountId, Balance, BlockNumber> {
+    /// The undelivered transfer
+    pub tx: VersionedCrossShardTx<AccountId, Balance>,
+    /// Block at which the sender is refunded unless the transfer was claimed before
+    pub expires_at: BlockNumber,
+}
+
+/// Dead-lettered cross-shard transfers, in number and volume
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct DeadLetterMetrics<Balance> {
+    /// Transfers dead-lettered so far
+    pub dead_lettered: u32,
+    /// Amount of the transfers dead-lettered so far
+    pub dead_lettered_volume: Balance,
+    /// Transfers still waiting to be claimed or refunded
+    pub pending: u32,
+    /// Amount of the transfers still waiting to be claimed or refunded
+    pub pending_volume: Balance,
+}
+
+/// Rewards paid from the fee pot for processing cross-shard queues
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SettlementRewardMetrics<Balance> {
+    /// Rewards paid so far
+    pub payouts: u32,
+    /// Deliveries rewarded so far
+    pub rewarded_settlements: u32,
+    /// Amount paid so far
+    pub paid: Balance,
+}
+
+/// Identifier of a forwarded cross-shard contract call
+pub type CrossShardCallId = u64;
+
+/// Maximum length of the input of a forwarded contract call
+pub type MaxCrossShardCallDataLen = ConstU32<{ 16 * 1024 }>;
+
+/// Encoded input of a forwarded contract call
+pub type CrossShardCallData = BoundedVec<u8, MaxCrossShardCallDataLen>;
+
+/// Contract call forwarded to another shard
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrossShardCall<AccountId, Balance> {
+    /// Call identifier, used to look up the receipt
+    pub call_id: CrossShardCallId,
+    /// Source shard
+    pub from_shard: ShardId,
+    /// Destination shard
+    pub to_shard: ShardId,
+    /// Account that initiated the call
+    pub caller: AccountId,
+    /// Contract to call on the destination shard
+    pub dest_contract: AccountId,
+    /// Encoded contract input (selector + arguments)
+    pub data: CrossShardCallData,
+    /// Maximum gas the call may consume, at most `MaxCrossShardCallGas`
+    pub gas_limit: Weight,
+    /// Value transferred to the contract
+    pub value: Balance,
+}
+
+/// Result of a forwarded contract call, written once the destination shard executes it
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "std",
+    serde(bound(
+        serialize = "BlockNumber: Serialize",
+        deserialize = "BlockNumber: Deserialize<'de>, MaxOutputLen: Get<u32>"
+    ))
+)]
+#[scale_info(skip_type_params(MaxOutputLen))]
+#[codec(mel_bound(BlockNumber: MaxEncodedLen, MaxOutputLen: Get<u32>))]
+pub struct CrossShardCallReceipt<BlockNumber, MaxOutputLen> {
+    /// Whether the contract call succeeded
+    pub success: bool,
+    /// Data returned by the contract (or the encoded error on failure), cut to its first
+    /// `MaxCrossShardCallOutputLen` bytes
+    pub output: BoundedVec<u8, MaxOutputLen>,
+    /// Gas consumed by the call
+    pub gas_used: Weight,
+    /// Block in which the call was executed
+    pub executed_at: BlockNumber,
+}
+
+/// Executes contract calls delivered to a destination shard.
+///
+/// Implemented by the runtime on top of `pallet_contracts`; the unit implementation
+/// rejects every call so the pallet can be used without contracts support.
+pub trait CrossShardCallExecutor<AccountId, Balance> {
+    /// Execute `data` against `dest` on behalf of `caller`, returning the output and gas used
+    fn execute(
+        caller: &AccountId,
+        dest: &AccountId,
+        value: Balance,
+        gas_limit: Weight,
+        data: Vec<u8>,
+    ) -> (Result<Vec<u8>, DispatchError>, Weight);
+}
+
+impl<AccountId, Balance> CrossShardCallExecutor<AccountId, Balance> for () {
+    fn execute(
+        _caller: &AccountId,
+        _dest: &AccountId,
+        _value: Balance,
+        _gas_limit: Weight,
+        _data: Vec<u8>,
+    ) -> (Result<Vec<u8>, DispatchError>, Weight) {
+        (Err(DispatchError::Other("no cross-shard call executor")), Weight::zero())
+    }
+}
+
+/// Registers a validator's session keys, e.g. through `pallet_session`.
+pub trait SessionKeyRegistrar<AccountId> {
+    /// Set the SCALE-encoded session `keys` of `who`
+    fn register_keys(who: &AccountId, keys: Vec<u8>) -> DispatchResult;
+}
+
+impl<AccountId> SessionKeyRegistrar<AccountId> for () {
+    fn register_keys(_who: &AccountId, _keys: Vec<u8>) -> DispatchResult {
+        Err(DispatchError::Other("no session key registrar"))
+    }
+}
+
+/// Prices cross-shard transactions into an overloaded shard.
+///
+/// A shard is overloaded once its queued load reaches its capacity. The unit implementation
+/// rejects every transaction into an overloaded shard.
+pub trait SurgeFee<Balance> {
+    /// Fee replacing `base_fee`, or `None` to reject the transaction
+    fn surge_fee(base_fee: Balance, load: u32, capacity: u32) -> Option<Balance>;
+}
+
+impl<Balance> SurgeFee<Balance> for () {
+    fn surge_fee(_base_fee: Balance, _load: u32, _capacity: u32) -> Option<Balance> {
+        None
+    }
+}
+
+/// Quotes the base cross-shard fee, e.g. from an oracle price.
+///
+/// Quotes are only used once governance has set bounds to clamp them to; `None` (the unit
+/// implementation) falls back to the constant `CrossShardFee`.
+pub trait CrossShardFeeQuote<Balance> {
+    /// Quoted base fee, or `None` if no quote is available (e.g. the price feed is stale)
+    fn quote() -> Option<Balance>;
+}
+
+impl<Balance> CrossShardFeeQuote<Balance> for () {
+    fn quote() -> Option<Balance> {
+        None
+    }
+}
+
+/// Surge fee of `base_fee` times the number of capacities the load has reached, plus one
+///
+/// A shard at 1x capacity costs 2x the base fee, at 2x capacity 3x, and so on; transactions
+/// are rejected once the multiplier would exceed `MaxMultiplier`.
+pub struct CapacityMultipleSurgeFee<MaxMultiplier>(PhantomData<MaxMultiplier>);
+
+impl<Balance, MaxMultiplier> SurgeFee<Balance> for CapacityMultipleSurgeFee<MaxMultiplier>
+where
+    Balance: AtLeast32BitUnsigned,
+    MaxMultiplier: Get<u32>,
+{
+    fn surge_fee(base_fee: Balance, load: u32, capacity: u32) -> Option<Balance> {
+        let multiplier = (load / capacity.max(1)).saturating_add(1);
+        (multiplier <= MaxMultiplier::get()).then(|| base_fee.saturating_mul(multiplier.into()))
+    }
+}
+
+/// Simulated cross-shard transfer, see [`Pallet::dry_run_cross_shard`]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrossShardDryRun<Balance> {
+    /// Shard of the sender
+    pub from_shard: ShardId,
+    /// Fee that would be charged, surge included
+    pub fee: Balance,
+    /// Transfers queued ahead of this one on the destination shard
+    pub queue_position: u32,
+    /// Blocks until settlement, counting the block including the transfer, if the
+    /// destination queue is settled up to `MaxSettlementsPerBlock` every block
+    pub settlement_blocks: u32,
+    /// Whether the recipient can receive the amount; if not, the transfer is dead-lettered
+    /// until claimed or refunded minus the `FailurePenalty`
+    pub recipient_accepts: bool,
+}
+
+/// Digest of an account in its shard's accounts digest: its Blake2-256 hash, as a number
+pub fn account_digest<AccountId: Encode>(account: &AccountId) -> U256 {
+    U256::from_big_endian(&blake2_256(&account.encode()))
+}
+
+/// State commitment root of a shard: the hash of its accounts digest and of the ordered trie
+/// root of its cross-shard queue
+///
+/// The accounts digest is the sum, modulo 2^256, of the [`account_digest`]s of the accounts
+/// assigned to the shard, so it is kept up to date as accounts move rather than recomputed.
+pub fn shard_state_root<H: Hash, AccountId: Encode, Balance: Encode>(
+    accounts_digest: U256,
+    queue: &[CrossShardTx<AccountId, Balance>],
+) -> H::Output {
+    let queue_root = H::ordered_trie_root(queue.iter().map(|tx| tx.encode()).collect(), StateVersion::V1);
+    H::hash_of(&(accounts_digest, queue_root))
+}
+
+/// Accounts assigned to a shard and its pending cross-shard queue, committed to by the
+/// shard's state commitment root
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ShardSnapshot<AccountId, Balance> {
+    /// Accounts in `AccountToShard` assigned to the shard, in storage order
+    pub accounts: Vec<AccountId>,
+    /// Cross-shard transactions waiting to settle on the shard
+    pub queue: Vec<CrossShardTx<AccountId, Balance>>,
+}
+
+impl<AccountId: Encode, Balance: Encode> ShardSnapshot<AccountId, Balance> {
+    /// Accounts digest of the snapshot, as stored in `ShardAccountsDigests`
+    pub fn accounts_digest(&self) -> U256 {
+        self.accounts
+            .iter()
+            .fold(U256::zero(), |digest, account| digest.overflowing_add(account_digest(account)).0)
+    }
+
+    /// State commitment root of the snapshot, as stored in `ShardStateRoots`
+    pub fn root<H: Hash>(&self) -> H::Output {
+        shard_state_root::<H, _, _>(self.accounts_digest(), &self.queue)
+    }
+}
+
+/// Announced move of an account to another shard
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct PendingMigration {
+    /// Shard the account is assigned to until the move
+    pub from_shard: ShardId,
+    /// Shard the account is assigned to from `effective_epoch` on
+    pub to_shard: ShardId,
+    /// Epoch at whose start the move takes effect
+    pub effective_epoch: EpochIndex,
+}
+
+/// Maximum length of a raw `System::Account` key, checked against the runtime's account id
+/// in `integrity_test`
+pub type MaxAccountKeyLen = ConstU32<128>;
+
+/// Raw `System::Account` key an account walk resumes after
+pub type AccountKeyCursor = BoundedVec<u8, MaxAccountKeyLen>;
+
+/// Shard split in progress, see `begin_shard_split`
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+pub struct ShardSplit {
+    /// Shard whose accounts are split
+    pub parent: ShardId,
+    /// Shard the split-off half moves to
+    pub new_shard: ShardId,
+    /// Raw `System::Account` key of the last account examined, `None` before the first block
+    pub cursor: Option<AccountKeyCursor>,
+    /// Accounts examined so far
+    pub examined: u32,
+    /// Accounts moved to `new_shard` so far
+    pub moved: u32,
+}
+
+/// Shard mapping upgrade in progress, see [`migrations::v4`]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+pub struct ShardMappingUpgrade {
+    /// Mapping accounts are placed by until the upgrade completes
+    pub from: ShardMappingVersion,
+    /// Mapping being upgraded to
+    pub to: ShardMappingVersion,
+    /// Raw `System::Account` key of the last account examined, `None` before the first block
+    pub cursor: Option<AccountKeyCursor>,
+    /// Accounts examined so far
+    pub examined: u32,
+    /// Accounts pinned to the shard `from` places them on so far
+    pub pinned: u32,
 }
 
 /// Performance metrics for monitoring
-#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, Default)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct PerformanceMetrics {
     /// Total transactions processed
@@ -93,6 +646,8 @@ pub struct PerformanceMetrics {
     pub cross_shard_txs: u32,
     /// Parallel processing utilization
     pub parallel_utilization: u8, // Percentage
+    /// Cross-shard settlements queued across all shards, including those carried over
+    pub cross_shard_backlog: u32,
 }
 
 #[frame_support::pallet]
@@ -103,13 +658,25 @@ pub mod pallet {
     #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
+    pub type BalanceOf<T> = <<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// Reasons for the pallet holding funds
+    #[pallet::composite_enum]
+    pub enum HoldReason {
+        /// Fee and amount of a queued cross-shard transfer, held on the sender until settlement
+        CrossShardEscrow,
+    }
+
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// The overarching event type
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-        /// The currency used for staking
-        type Currency: frame_support::traits::Currency<Self::AccountId>;
+        /// The overarching hold reason
+        type RuntimeHoldReason: From<HoldReason>;
+
+        /// Currency holding the fee and amount of queued cross-shard transfers
+        type Currency: Mutate<Self::AccountId> + MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>;
 
         /// Maximum number of validators per shard
         #[pallet::constant]
@@ -121,12 +688,114 @@ pub mod pallet {
 
         /// Cross-shard transaction fee
         #[pallet::constant]
-        type CrossShardFee: Get<<Self::Currency as frame_support::traits::Currency<Self::AccountId>>::Balance>;
+        type CrossShardFee: Get<BalanceOf<Self>>;
 
-        /// Pallet identifier for generating shard accounts
+        /// Pallet identifier of the fee pot and of the escrow account used before transfers
+        /// were held on senders
         #[pallet::constant]
         type PalletId: Get<PalletId>;
 
+        /// Executor for contract calls forwarded between shards
+        type CallExecutor: CrossShardCallExecutor<
+            Self::AccountId,
+            BalanceOf<Self>,
+        >;
+
+        /// Maximum gas a forwarded contract call can be given
+        #[pallet::constant]
+        type MaxCrossShardCallGas: Get<Weight>;
+
+        /// Blocks the receipt of a forwarded contract call is kept after it executed
+        #[pallet::constant]
+        type CrossShardCallReceiptLifetime: Get<BlockNumberFor<Self>>;
+
+        /// Bytes of a forwarded contract call's output kept in its receipt; longer outputs are
+        /// cut to it
+        #[pallet::constant]
+        type MaxCrossShardCallOutputLen: Get<u32>;
+
+        /// Blocks the status and receipt of a cross-shard transfer are kept after it settled
+        /// or failed
+        #[pallet::constant]
+        type CrossShardTxRecordLifetime: Get<BlockNumberFor<Self>>;
+
+        /// Share of the held amount burnt when settlement of a cross-shard transfer fails
+        #[pallet::constant]
+        type FailurePenalty: Get<Perbill>;
+
+        /// Blocks a dead-lettered transfer waits to be claimed before its sender is refunded
+        #[pallet::constant]
+        type DeadLetterTimeout: Get<BlockNumberFor<Self>>;
+
+        /// Maximum dead letters, call receipts and transfer records each expiring at one block;
+        /// further ones expire at the next block with room, so `on_initialize` prunes a bounded
+        /// number
+        #[pallet::constant]
+        type MaxExpiringPerBlock: Get<u32>;
+
+        /// Quote replacing `CrossShardFee` while `CrossShardFeeBounds` are set
+        type FeeQuote: CrossShardFeeQuote<BalanceOf<Self>>;
+
+        /// Fee for cross-shard transactions into an overloaded shard
+        type SurgeFee: SurgeFee<BalanceOf<Self>>;
+
+        /// Index of cross-shard transfers for explorer queries
+        type History: HistoryRecorder<Self::AccountId>;
+
+        /// Emergency pause switch; while `Subsystem::CrossShard` is paused no new transfers
+        /// or calls are queued, but queued ones still settle
+        type Safeguard: PauseGuard;
+
+        /// Session key registration for `rotate_and_register_keys`
+        type SessionKeys: SessionKeyRegistrar<Self::AccountId>;
+
+        /// Maximum cross-shard settlements per shard per block; the rest carry over
+        #[pallet::constant]
+        type MaxSettlementsPerBlock: Get<u32>;
+
+        /// Blocks the oldest transfer of a shard's queue may wait before the queue is
+        /// escalated: it then settles oldest first and its processing is operational
+        #[pallet::constant]
+        type MaxQueueLatency: Get<BlockNumberFor<Self>>;
+
+        /// Reward paid from the fee pot to the caller of `process_cross_shard_queue` per
+        /// transfer it delivers
+        #[pallet::constant]
+        type SettlementReward: Get<BalanceOf<Self>>;
+
+        /// Maximum settlement rewards paid per block, across shards
+        #[pallet::constant]
+        type MaxSettlementRewardPerBlock: Get<BalanceOf<Self>>;
+
+        /// Blocks per shard epoch; account moves take effect at epoch boundaries
+        #[pallet::constant]
+        type EpochLength: Get<BlockNumberFor<Self>>;
+
+        /// Maximum accounts announced to move or in their grace window at once
+        #[pallet::constant]
+        type MaxMigrationsPerEpoch: Get<u32>;
+
+        /// Accounts an active shard split examines per block; must not be zero
+        #[pallet::constant]
+        type MaxSplitAccountsPerBlock: Get<u32>;
+
+        /// Bytes of [`ShardBlockData`] erasure-coded per shard each block; settled transfers
+        /// and then the head of the queue are committed up to it
+        #[pallet::constant]
+        type MaxShardDataLen: Get<u32>;
+
+        /// Maximum cross-shard transfers waiting in a shard's queue
+        #[pallet::constant]
+        type MaxCrossShardQueueLen: Get<u32>;
+
+        /// Maximum contract calls waiting in a shard's call queue
+        #[pallet::constant]
+        type MaxCrossShardCallQueueLen: Get<u32>;
+
+        /// System calls, e.g. staking, governance and IBC handshakes, tagged with the system
+        /// shard once governance reserves one
+        type SystemCalls: Contains<<Self as frame_system::Config>::RuntimeCall>;
+
         /// Weight information for extrinsics
         type WeightInfo: WeightInfo;
     }
@@ -138,11 +807,12 @@ pub mod pallet {
         _,
         Blake2_128Concat,
         ShardId,
-        ShardInfo<T::AccountId, <T::Currency as frame_support::traits::Currency<T::AccountId>>::Balance>,
+        ShardInfo<T::AccountId, BalanceOf<T>, T::MaxValidatorsPerShard>,
         OptionQuery,
     >;
 
-    /// Mapping from account to shard
+    /// Shard of accounts moved by rebalancing or pinned by a shard mapping upgrade; the others
+    /// stay on the shard their id hashes to
     #[pallet::storage]
     #[pallet::getter(fn account_shard)]
     pub type AccountToShard<T: Config> = StorageMap<
@@ -153,30 +823,95 @@ pub mod pallet {
         ValueQuery,
     >;
 
-    /// Cross-shard transaction queue
+    /// Current shard epoch
+    #[pallet::storage]
+    #[pallet::getter(fn current_epoch)]
+    pub type CurrentEpoch<T: Config> = StorageValue<_, EpochIndex, ValueQuery>;
+
+    /// Account moves, from their announcement until the end of the epoch they take effect in
+    #[pallet::storage]
+    #[pallet::getter(fn pending_migration)]
+    pub type PendingMigrations<T: Config> =
+        CountedStorageMap<_, Blake2_128Concat, T::AccountId, PendingMigration, OptionQuery>;
+
+    /// Shard split in progress, advanced at the start of every block until it completes
+    #[pallet::storage]
+    #[pallet::getter(fn active_shard_split)]
+    pub type ActiveShardSplit<T: Config> = StorageValue<_, ShardSplit, OptionQuery>;
+
+    /// Version of the mapping placing accounts on the shard their id hashes to; chains that
+    /// predate versioning are on version 0
+    #[pallet::storage]
+    #[pallet::getter(fn shard_mapping_version)]
+    pub type CurrentShardMapping<T: Config> = StorageValue<_, ShardMappingVersion, ValueQuery>;
+
+    /// Shard mapping upgrade in progress, advanced at the start of every block until it completes
+    #[pallet::storage]
+    #[pallet::getter(fn active_shard_mapping_upgrade)]
+    pub type ActiveShardMappingUpgrade<T: Config> = StorageValue<_, ShardMappingUpgrade, OptionQuery>;
+
+    /// Shard system transactions must be tagged with, if governance reserved one
+    #[pallet::storage]
+    #[pallet::getter(fn system_shard)]
+    pub type ReservedSystemShard<T: Config> = StorageValue<_, ShardId, OptionQuery>;
+
+    /// Per-shard nonce lanes of each account, used by [`CheckShardNonce`]
+    #[pallet::storage]
+    #[pallet::getter(fn shard_nonce)]
+    pub type ShardNonces<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Twox64Concat,
+        ShardId,
+        u64,
+        ValueQuery,
+    >;
+
+    /// Cross-shard transaction queue, see [`Pallet::cross_shard_queue`]
     #[pallet::storage]
-    #[pallet::getter(fn cross_shard_queue)]
     pub type CrossShardQueue<T: Config> = StorageMap<
         _,
         Blake2_128Concat,
         ShardId,
-        Vec<CrossShardTx<T::AccountId, <T::Currency as frame_support::traits::Currency<T::AccountId>>::Balance>>,
+        BoundedVec<VersionedCrossShardTx<T::AccountId, BalanceOf<T>>, T::MaxCrossShardQueueLen>,
         ValueQuery,
     >;
 
+    /// Block each queued cross-shard transfer was queued at, keyed by transaction hash
+    #[pallet::storage]
+    pub type QueuedAt<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, BlockNumberFor<T>, OptionQuery>;
+
+    /// Block the oldest transfer still queued on each shard was queued at
+    #[pallet::storage]
+    #[pallet::getter(fn oldest_queued_at)]
+    pub type OldestQueuedAt<T: Config> = StorageMap<_, Blake2_128Concat, ShardId, BlockNumberFor<T>, OptionQuery>;
+
+    /// Shards whose oldest queued transfer waited longer than `MaxQueueLatency`, until it is
+    /// back within it
+    #[pallet::storage]
+    #[pallet::getter(fn is_latency_breached)]
+    pub type LatencyBreachedShards<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        ShardId,
+        (),
+        OptionQuery,
+    >;
+
     /// Performance metrics for monitoring
     #[pallet::storage]
     #[pallet::getter(fn performance_metrics)]
     pub type Metrics<T: Config> = StorageValue<_, PerformanceMetrics, ValueQuery>;
 
-    /// Transaction processing batches for parallel execution
+    /// Transaction processing batches for parallel execution, at most a queue's worth each
     #[pallet::storage]
     #[pallet::getter(fn processing_batches)]
     pub type ProcessingBatches<T: Config> = StorageMap<
         _,
         Blake2_128Concat,
         ShardId,
-        Vec<T::Hash>,
+        BoundedVec<T::Hash, T::MaxCrossShardQueueLen>,
         ValueQuery,
     >;
 
@@ -191,42 +926,367 @@ pub mod pallet {
         ValueQuery,
     >;
 
-    #[pallet::event]
-    #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
-        /// A new shard was created
-        ShardCreated {
-            shard_id: ShardId,
-            validators: Vec<T::AccountId>,
-        },
-        /// Validator joined a shard
-        ValidatorJoined {
-            shard_id: ShardId,
-            validator: T::AccountId,
-        },
-        /// Cross-shard transaction executed
-        CrossShardExecuted {
-            from_shard: ShardId,
-            to_shard: ShardId,
-            tx_hash: T::Hash,
-        },
-        /// Performance metrics updated
-        MetricsUpdated {
-            tps: u32,
-            parallel_utilization: u8,
-        },
-        /// Batch processing completed
-        BatchProcessed {
-            shard_id: ShardId,
-            batch_size: u32,
-            processing_time: u64,
-        },
-        /// Load balancing triggered
-        LoadBalanced {
-            from_shard: ShardId,
-            to_shard: ShardId,
+    /// Cross-shard settlements made per shard in the current block
+    #[pallet::storage]
+    #[pallet::getter(fn settled_this_block)]
+    pub type SettledThisBlock<T: Config> = StorageMap<_, Blake2_128Concat, ShardId, u32, ValueQuery>;
+
+    /// Cross-shard transactions settled per shard in the current block, part of the shard's
+    /// availability data
+    #[pallet::storage]
+    #[pallet::getter(fn settlements_this_block)]
+    pub type SettlementsThisBlock<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        ShardId,
+        BoundedVec<VersionedCrossShardTx<T::AccountId, BalanceOf<T>>, T::MaxSettlementsPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Settlement rewards paid in the current block
+    #[pallet::storage]
+    #[pallet::getter(fn rewards_paid_this_block)]
+    pub type RewardsPaidThisBlock<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    /// Settlement rewards paid for processing cross-shard queues
+    #[pallet::storage]
+    #[pallet::getter(fn settlement_reward_metrics)]
+    pub type SettlementRewardStats<T: Config> = StorageValue<_, SettlementRewardMetrics<BalanceOf<T>>, ValueQuery>;
+
+    /// Status of cross-shard transfers, keyed by transaction hash
+    #[pallet::storage]
+    #[pallet::getter(fn cross_shard_tx_status)]
+    pub type CrossShardTxStatuses<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::Hash,
+        CrossShardTxStatus,
+        OptionQuery,
+    >;
+
+    /// Receipts of settled cross-shard transfers, keyed by transaction hash
+    #[pallet::storage]
+    #[pallet::getter(fn cross_shard_tx_receipt)]
+    pub type CrossShardTxReceipts<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::Hash,
+        CrossShardTxReceipt<T::AccountId, BalanceOf<T>, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Settled and failed cross-shard transfers by the block at which their status and
+    /// receipt are pruned
+    #[pallet::storage]
+    pub type CrossShardTxExpiries<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumberFor<T>, BoundedVec<T::Hash, T::MaxExpiringPerBlock>, ValueQuery>;
+
+    /// Cross-shard transfers their recipient could not receive, keyed by transaction hash
+    #[pallet::storage]
+    #[pallet::getter(fn dead_letter)]
+    pub type DeadLetters<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::Hash,
+        DeadLetter<T::AccountId, BalanceOf<T>, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Dead-lettered transfers by the block at which they are refunded
+    #[pallet::storage]
+    pub type DeadLetterExpiries<T: Config> =
+        StorageMap<_, Twox64Concat, BlockNumberFor<T>, BoundedVec<T::Hash, T::MaxExpiringPerBlock>, ValueQuery>;
+
+    /// Dead-lettered transfers, in number and volume
+    #[pallet::storage]
+    #[pallet::getter(fn dead_letter_metrics)]
+    pub type DeadLetterStats<T: Config> = StorageValue<_, DeadLetterMetrics<BalanceOf<T>>, ValueQuery>;
+
+    /// Shards whose load has reached capacity, until it drops back below
+    #[pallet::storage]
+    #[pallet::getter(fn is_overloaded)]
+    pub type OverloadedShards<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        ShardId,
+        (),
+        OptionQuery,
+    >;
+
+    /// Cross-shard contract call queue
+    #[pallet::storage]
+    #[pallet::getter(fn cross_shard_call_queue)]
+    pub type CrossShardCallQueue<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        ShardId,
+        BoundedVec<CrossShardCall<T::AccountId, BalanceOf<T>>, T::MaxCrossShardCallQueueLen>,
+        ValueQuery,
+    >;
+
+    /// Receipts of executed cross-shard contract calls
+    #[pallet::storage]
+    #[pallet::getter(fn cross_shard_call_receipt)]
+    pub type CrossShardCallReceipts<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        CrossShardCallId,
+        CrossShardCallReceipt<BlockNumberFor<T>, T::MaxCrossShardCallOutputLen>,
+        OptionQuery,
+    >;
+
+    /// Cross-shard call receipts by the block at which they are pruned
+    #[pallet::storage]
+    pub type CrossShardCallReceiptExpiries<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<CrossShardCallId, T::MaxExpiringPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Next cross-shard call identifier to assign
+    #[pallet::storage]
+    #[pallet::getter(fn next_cross_shard_call_id)]
+    pub type NextCrossShardCallId<T: Config> = StorageValue<_, CrossShardCallId, ValueQuery>;
+
+    /// Sequence number of the next cross-shard transfer, folded into its hash
+    #[pallet::storage]
+    #[pallet::getter(fn next_cross_shard_tx_nonce)]
+    pub type NextCrossShardTxNonce<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Sum, modulo 2^256, of the [`account_digest`]s of the accounts each shard holds in
+    /// `AccountToShard`, updated whenever an account is assigned
+    #[pallet::storage]
+    #[pallet::getter(fn shard_accounts_digest)]
+    pub type ShardAccountsDigests<T: Config> = StorageMap<_, Blake2_128Concat, ShardId, U256, ValueQuery>;
+
+    /// Per-shard state commitment roots, recomputed at the end of every block.
+    ///
+    /// Each root commits to the accounts assigned to the shard and its pending
+    /// cross-shard queue (see [`shard_state_root`]), and is the anchor for future
+    /// fraud/validity proofs. Accounts enter through their shard's digest in
+    /// `ShardAccountsDigests`, so committing takes the same work however many there are.
+    #[pallet::storage]
+    #[pallet::getter(fn shard_state_root)]
+    pub type ShardStateRoots<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        ShardId,
+        T::Hash,
+        OptionQuery,
+    >;
+
+    /// Per-shard commitments to the erasure-coded [`ShardBlockData`] of the block, recomputed
+    /// at the end of every block for data availability sampling
+    #[pallet::storage]
+    #[pallet::getter(fn shard_data_commitment)]
+    pub type ShardDataCommitments<T: Config> =
+        StorageMap<_, Blake2_128Concat, ShardId, DataCommitment<T::Hash>, OptionQuery>;
+
+    /// Bounds (min, max) quoted cross-shard fees are clamped to; quotes are ignored while unset
+    #[pallet::storage]
+    #[pallet::getter(fn cross_shard_fee_bounds)]
+    pub type CrossShardFeeBounds<T: Config> = StorageValue<
+        _,
+        (
+            BalanceOf<T>,
+            BalanceOf<T>,
+        ),
+        OptionQuery,
+    >;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Validators of each shard, by shard id; empty to leave it to `initialize_sharding`
+        pub shard_validators: Vec<Vec<T::AccountId>>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            if !self.shard_validators.is_empty() {
+                Pallet::<T>::create_shards(self.shard_validators.clone())
+                    .expect("genesis configures at most SHARD_COUNT shards of MaxValidatorsPerShard validators; qed");
+            }
+            CurrentShardMapping::<T>::put(SHARD_MAPPING_VERSION);
+            Pallet::<T>::endow_pot();
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// A new shard was created
+        ShardCreated {
+            shard_id: ShardId,
+            validators: Vec<T::AccountId>,
+        },
+        /// Validator joined a shard
+        ValidatorJoined {
+            shard_id: ShardId,
+            validator: T::AccountId,
+        },
+        /// Cross-shard transaction executed
+        CrossShardExecuted {
+            from_shard: ShardId,
+            to_shard: ShardId,
+            tx_hash: T::Hash,
+        },
+        /// Performance metrics updated
+        MetricsUpdated {
+            tps: u32,
+            parallel_utilization: u8,
+        },
+        /// Batch processing completed
+        BatchProcessed {
+            shard_id: ShardId,
+            batch_size: u32,
+            processing_time: u64,
+        },
+        /// The per-block settlement cap was reached; the rest of the queue waits a block
+        SettlementsCarriedOver {
+            shard_id: ShardId,
+            carried_over: u32,
+        },
+        /// The oldest transfer queued on `shard_id` waited `age` blocks, longer than
+        /// `MaxQueueLatency`; the queue now settles oldest first with operational priority
+        QueueLatencyBreached {
+            shard_id: ShardId,
+            age: BlockNumberFor<T>,
+        },
+        /// The oldest transfer queued on `shard_id` is back within `MaxQueueLatency`
+        QueueLatencyRecovered {
+            shard_id: ShardId,
+        },
+        /// Accounts of the most loaded shard were announced to move to the least loaded one
+        LoadBalanced {
+            from_shard: ShardId,
+            to_shard: ShardId,
             moved_accounts: u32,
         },
+        /// Contract call queued for execution on another shard
+        CrossShardCallQueued {
+            call_id: CrossShardCallId,
+            from_shard: ShardId,
+            to_shard: ShardId,
+            caller: T::AccountId,
+            dest_contract: T::AccountId,
+        },
+        /// Forwarded contract call executed on its destination shard
+        CrossShardCallExecuted {
+            call_id: CrossShardCallId,
+            to_shard: ShardId,
+            success: bool,
+            gas_used: Weight,
+        },
+        /// Cross-shard transfer delivered to its recipient
+        CrossShardSettled {
+            tx_hash: T::Hash,
+            to_shard: ShardId,
+        },
+        /// `who` was rewarded from the fee pot for delivering `settled` transfers of `shard_id`
+        SettlementRewardPaid {
+            who: T::AccountId,
+            shard_id: ShardId,
+            settled: u32,
+            amount: BalanceOf<T>,
+        },
+        /// Cross-shard transfer could not be settled; the sender was refunded
+        CrossShardFailed {
+            tx_hash: T::Hash,
+            sender: T::AccountId,
+            refunded: BalanceOf<T>,
+            reason: FailureReason,
+        },
+        /// Shard load reached capacity; new cross-shard transactions pay a surge fee or are rejected
+        ShardOverloaded {
+            shard_id: ShardId,
+            load: u32,
+            capacity: u32,
+        },
+        /// Shard load dropped back below capacity
+        OverloadCleared {
+            shard_id: ShardId,
+            load: u32,
+        },
+        /// Validator registered new session keys and confirmed its shard membership
+        ValidatorKeysRotated {
+            shard_id: ShardId,
+            validator: T::AccountId,
+            keys_hash: T::Hash,
+        },
+        /// Cross-shard transfer could not be delivered and waits to be claimed until `expires_at`
+        CrossShardDeadLettered {
+            tx_hash: T::Hash,
+            recipient: T::AccountId,
+            amount: BalanceOf<T>,
+            expires_at: BlockNumberFor<T>,
+        },
+        /// `account` moves from `from_shard` to `to_shard` when `effective_epoch` starts
+        MigrationAnnounced {
+            account: T::AccountId,
+            from_shard: ShardId,
+            to_shard: ShardId,
+            effective_epoch: EpochIndex,
+        },
+        /// Announced move took effect
+        AccountMigrated {
+            account: T::AccountId,
+            from_shard: ShardId,
+            to_shard: ShardId,
+            epoch: EpochIndex,
+        },
+        /// A shard epoch started, applying `migrated` announced moves
+        ShardEpochStarted {
+            epoch: EpochIndex,
+            migrated: u32,
+        },
+        /// Bounds of quoted cross-shard fees set, or cleared to use the constant fee
+        CrossShardFeeBoundsSet {
+            bounds: Option<(
+                BalanceOf<T>,
+                BalanceOf<T>,
+            )>,
+        },
+        /// Half of the accounts of `parent` start moving to `new_shard`
+        ShardSplitStarted {
+            parent: ShardId,
+            new_shard: ShardId,
+        },
+        /// A block of the split examined `examined` accounts in total so far, moving `moved`
+        ShardSplitProgress {
+            parent: ShardId,
+            new_shard: ShardId,
+            examined: u32,
+            moved: u32,
+        },
+        /// Every account was examined; `moved` of them now live on `new_shard`
+        ShardSplitCompleted {
+            parent: ShardId,
+            new_shard: ShardId,
+            examined: u32,
+            moved: u32,
+        },
+        /// System transactions must now be tagged with `shard`, or with their signer's shard
+        /// if `None`
+        SystemShardSet {
+            shard: Option<ShardId>,
+        },
+        /// Accounts are being examined to upgrade the shard mapping from `from` to `to`
+        ShardMappingUpgradeStarted {
+            from: ShardMappingVersion,
+            to: ShardMappingVersion,
+        },
+        /// Every account was examined and the shard mapping is now `to`; the `pinned`
+        /// accounts it would have moved stay on the shard `from` placed them on
+        ShardMappingUpgraded {
+            from: ShardMappingVersion,
+            to: ShardMappingVersion,
+            examined: u32,
+            pinned: u32,
+        },
     }
 
     #[pallet::error]
@@ -245,50 +1305,86 @@ pub mod pallet {
         ParallelProcessingError,
         /// Invalid shard configuration
         InvalidShardConfig,
+        /// Destination shard is overloaded and the surge fee policy rejected the transaction
+        ShardOverloaded,
+        /// Minimum cross-shard fee bound exceeds the maximum
+        InvalidFeeBounds,
+        /// No dead-lettered transfer with this hash
+        DeadLetterNotFound,
+        /// The recipient still cannot receive the dead-lettered amount
+        RecipientStillRejected,
+        /// Only the sender or recipient of a dead-lettered transfer may claim it
+        NotTransferParty,
+        /// Cross-shard transfers are paused by governance
+        SystemPaused,
+        /// A shard split is in progress
+        SplitInProgress,
+        /// Gas limit of a forwarded contract call exceeds `MaxCrossShardCallGas`
+        GasLimitTooHigh,
+        /// Input of a forwarded contract call exceeds `MaxCrossShardCallDataLen`
+        CallDataTooLarge,
+        /// Destination shard already queues `MaxCrossShardQueueLen` transfers
+        CrossShardQueueFull,
+        /// Destination shard already queues `MaxCrossShardCallQueueLen` contract calls
+        CrossShardCallQueueFull,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+            let cleared = SettledThisBlock::<T>::clear(SHARD_COUNT as u32, None).backend;
+            let cleared_settlements = SettlementsThisBlock::<T>::clear(SHARD_COUNT as u32, None).backend;
+            RewardsPaidThisBlock::<T>::kill();
+            let mut weight = T::DbWeight::get().writes(u64::from(cleared) + u64::from(cleared_settlements) + 1);
+
+            let epoch_length = T::EpochLength::get();
+            if !epoch_length.is_zero() && (n % epoch_length).is_zero() {
+                weight.saturating_accrue(Self::start_epoch());
+            }
+            weight.saturating_accrue(Self::refund_expired_dead_letters(n));
+            weight.saturating_accrue(Self::prune_cross_shard_call_receipts(n));
+            weight.saturating_accrue(Self::prune_cross_shard_tx_records(n));
+            weight.saturating_accrue(Self::check_queue_latency());
+            weight.saturating_accrue(Self::advance_shard_split());
+            weight.saturating_accrue(Self::advance_shard_mapping_upgrade());
+
+            // Committed in `on_finalize`: the digest and queue of every shard are read and
+            // its root written, and at most `MaxShardDataLen` bytes of its data erasure-coded
+            weight.saturating_accrue(T::DbWeight::get().reads_writes(2 * SHARD_COUNT as u64, SHARD_COUNT as u64));
+            weight.saturating_accrue(T::WeightInfo::commit_shard_data(
+                T::MaxShardDataLen::get().saturating_mul(SHARD_COUNT.into()),
+            ));
+            weight
+        }
+
+        fn on_finalize(_n: BlockNumberFor<T>) {
+            Self::commit_shard_state_roots();
+            Self::commit_shard_data();
+        }
+
+        fn integrity_test() {
+            // `System::Account` keys are a 32 byte prefix and a `Blake2_128Concat` of the account
+            let account_key_len = 32 + 16 + T::AccountId::max_encoded_len();
+            assert!(
+                account_key_len <= MaxAccountKeyLen::get() as usize,
+                "MaxAccountKeyLen must fit the System::Account key of an account"
+            );
+            assert!(T::MaxExpiringPerBlock::get() > 0, "MaxExpiringPerBlock must be positive");
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         /// Initialize sharding system with 4 shards
         #[pallet::call_index(0)]
-        #[pallet::weight(T::WeightInfo::initialize_sharding())]
+        #[pallet::weight((T::WeightInfo::initialize_sharding(), DispatchClass::Operational, Pays::No))]
         pub fn initialize_sharding(
             origin: OriginFor<T>,
             initial_validators: Vec<Vec<T::AccountId>>,
         ) -> DispatchResult {
             ensure_root(origin)?;
 
-            // Create 4 shards with distributed validators
-            for (shard_id, validators) in initial_validators.into_iter().enumerate() {
-                let shard_id = shard_id as ShardId;
-                ensure!(shard_id < SHARD_COUNT, Error::<T>::InvalidShardConfig);
-                
-                let shard_info = ShardInfo {
-                    shard_id,
-                    validators: validators.clone(),
-                    total_stake: Zero::zero(),
-                    tx_count: 0,
-                    capacity: T::TargetTpsPerShard::get(),
-                };
-
-                ShardInfos::<T>::insert(shard_id, &shard_info);
-                
-                // Initialize cross-shard queue
-                CrossShardQueue::<T>::insert(shard_id, Vec::new());
-                
-                // Initialize processing state
-                ShardProcessingState::<T>::insert(shard_id, 0u32);
-
-                Self::deposit_event(Event::ShardCreated {
-                    shard_id,
-                    validators,
-                });
-            }
-
-            // Initialize performance metrics
-            Metrics::<T>::put(PerformanceMetrics::default());
-
-            Ok(())
+            Self::create_shards(initial_validators)
         }
 
         /// Add validator to a specific shard
@@ -309,13 +1405,14 @@ pub mod pallet {
                 );
 
                 if !info.validators.contains(&who) {
-                    info.validators.push(who.clone());
+                    info.validators.try_push(who.clone()).map_err(|_| Error::<T>::ShardAtCapacity)?;
                 }
 
-                Self::deposit_event(Event::ValidatorJoined {
-                    shard_id,
-                    validator: who,
-                });
+                Self::deposit_indexed_event(
+                    Event::ValidatorJoined { shard_id, validator: who },
+                    &[shard_id],
+                    &[],
+                );
 
                 Ok(())
             })
@@ -328,102 +1425,119 @@ pub mod pallet {
             origin: OriginFor<T>,
             to_shard: ShardId,
             recipient: T::AccountId,
-            amount: <T::Currency as frame_support::traits::Currency<T::AccountId>>::Balance,
+            amount: BalanceOf<T>,
         ) -> DispatchResult {
             let sender = ensure_signed(origin)?;
-
-            let from_shard = Self::get_account_shard(&sender);
-            
-            // Ensure cross-shard transaction is valid
-            ensure!(from_shard != to_shard, Error::<T>::InvalidCrossShardTx);
-            ensure!(ShardInfos::<T>::contains_key(to_shard), Error::<T>::ShardNotFound);
-
-            // Charge cross-shard fee
-            let fee = T::CrossShardFee::get();
-            T::Currency::withdraw(
-                &sender,
-                fee,
-                frame_support::traits::WithdrawReasons::FEE,
-                frame_support::traits::ExistenceRequirement::KeepAlive,
-            )?;
-
-            // Create cross-shard transaction
-            let cross_shard_tx = CrossShardTx {
-                from_shard,
-                to_shard,
-                sender: sender.clone(),
-                recipient: recipient.clone(),
-                amount,
-                nonce: frame_system::Pallet::<T>::account_nonce(&sender),
-            };
-
-            // Add to destination shard queue
-            CrossShardQueue::<T>::mutate(to_shard, |queue| {
-                queue.push(cross_shard_tx);
-            });
-
-            // Update metrics
-            Metrics::<T>::mutate(|metrics| {
-                metrics.cross_shard_txs = metrics.cross_shard_txs.saturating_add(1);
-            });
-
-            let tx_hash = BlakeTwo256::hash_of(&(sender, recipient, amount));
-            Self::deposit_event(Event::CrossShardExecuted {
-                from_shard,
-                to_shard,
-                tx_hash,
-            });
-
-            Ok(())
+            Self::queue_cross_shard_tx(sender, to_shard, recipient, amount, Zero::zero())
         }
 
         /// Process pending cross-shard transactions (called by block author)
+        ///
+        /// At most `MaxSettlementsPerBlock` transactions are settled per shard and block, the
+        /// highest tips first and equal tips in queue order; the rest stay queued for the next
+        /// block. The caller is paid the tip of every delivered transfer, and `SettlementReward`
+        /// per delivered transfer from the fee pot while the pot and
+        /// `MaxSettlementRewardPerBlock` allow.
+        ///
+        /// While the shard's latency is breached its queue settles in queue order, oldest
+        /// first whatever the tips, and the call is operational.
         #[pallet::call_index(3)]
-        #[pallet::weight(T::WeightInfo::process_cross_shard_queue())]
+        #[pallet::weight((
+            T::WeightInfo::process_cross_shard_queue(
+                (*max_transactions).min(T::MaxSettlementsPerBlock::get()),
+                T::MaxCrossShardQueueLen::get(),
+            ),
+            Pallet::<T>::queue_dispatch_class(*shard_id),
+        ))]
         pub fn process_cross_shard_queue(
             origin: OriginFor<T>,
             shard_id: ShardId,
             max_transactions: u32,
-        ) -> DispatchResult {
-            ensure_signed(origin)?;
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "process_cross_shard_queue");
 
-            let queue = CrossShardQueue::<T>::get(shard_id);
-            let process_count = (queue.len() as u32).min(max_transactions);
+            let queue = Self::cross_shard_queue(shard_id);
+            let allowance = T::MaxSettlementsPerBlock::get()
+                .saturating_sub(SettledThisBlock::<T>::get(shard_id));
+            let process_count = (queue.len() as u32).min(max_transactions).min(allowance);
 
             if process_count == 0 {
-                return Ok(());
+                return Ok(Some(T::WeightInfo::process_cross_shard_queue(0, queue.len() as u32)).into());
             }
 
             // Process transactions in batches for parallel execution
             let mut processed = 0u32;
+            let mut delivered = 0u32;
             let start_time = frame_system::Pallet::<T>::block_number();
 
-            // In a real implementation, this would use async processing
-            // For now, we simulate batch processing
-            for tx in queue.iter().take(process_count as usize) {
-                // Process cross-shard transaction
-                // This would involve:
-                // 1. Validate transaction
-                // 2. Execute state changes
-                // 3. Update balances
+            let mut selected: Vec<usize> = (0..queue.len()).collect();
+            if !LatencyBreachedShards::<T>::contains_key(shard_id) {
+                selected.sort_by(|a, b| queue[*b].tip.cmp(&queue[*a].tip).then(a.cmp(b)));
+            }
+            selected.truncate(process_count as usize);
+
+            for index in &selected {
+                QueuedAt::<T>::remove(BlakeTwo256::hash_of(&queue[*index]));
+                if Self::settle(&queue[*index], &who) {
+                    delivered = delivered.saturating_add(1);
+                }
                 processed = processed.saturating_add(1);
             }
 
-            // Remove processed transactions
-            CrossShardQueue::<T>::mutate(shard_id, |queue| {
-                queue.drain(0..process_count as usize);
+            // Remove processed transactions, keeping the others in queue order
+            let remaining: Vec<_> = queue
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !selected.contains(index))
+                .map(|(_, tx)| tx.clone())
+                .collect();
+            Self::note_oldest_queued(shard_id, remaining.first());
+            CrossShardQueue::<T>::insert(
+                shard_id,
+                BoundedVec::truncate_from(remaining.into_iter().map(Into::into).collect()),
+            );
+            Self::note_processed(shard_id, processed);
+            SettledThisBlock::<T>::mutate(shard_id, |settled| *settled = settled.saturating_add(processed));
+            // `MaxSettlementsPerBlock` caps the settlements of a block, so they all fit
+            SettlementsThisBlock::<T>::mutate(shard_id, |settled| {
+                for index in &selected {
+                    let _ = settled.try_push(queue[*index].clone().into());
+                }
             });
+            Metrics::<T>::mutate(|metrics| {
+                metrics.cross_shard_backlog = metrics.cross_shard_backlog.saturating_sub(processed);
+            });
+            Self::reward_processor(&who, shard_id, delivered);
 
             let end_time = frame_system::Pallet::<T>::block_number();
             let processing_time = end_time.saturating_sub(start_time).saturated_into::<u64>();
 
-            Self::deposit_event(Event::BatchProcessed {
+            Self::deposit_indexed_event(
+                Event::BatchProcessed { shard_id, batch_size: processed, processing_time },
+                &[shard_id],
+                &[],
+            );
+
+            let carried_over = (queue.len() as u32).saturating_sub(processed);
+            log::debug!(
+                target: LOG_TARGET,
+                "shard {}: settled {} of {} queued transfers, {} delivered, {} carried over",
                 shard_id,
-                batch_size: processed,
-                processing_time,
-            });
+                processed,
+                queue.len(),
+                delivered,
+                carried_over,
+            );
+            if carried_over > 0 && processed == allowance {
+                Self::deposit_indexed_event(
+                    Event::SettlementsCarriedOver { shard_id, carried_over },
+                    &[shard_id],
+                    &[],
+                );
+            }
 
-            Ok(())
+            Ok(Some(T::WeightInfo::process_cross_shard_queue(processed, queue.len() as u32)).into())
         }
 
         /// Update performance metrics (called automatically)
@@ -457,10 +1571,16 @@ pub mod pallet {
         }
 
         /// Rebalance load across shards
+        ///
+        /// Announces moves of the recipients queued on the most loaded shard, in queue order,
+        /// to the least loaded shard. They take effect when the next epoch starts, within
+        /// `MaxMigrationsPerEpoch` moves announced or in their grace window. Fails while a
+        /// shard split is in progress.
         #[pallet::call_index(5)]
-        #[pallet::weight(T::WeightInfo::rebalance_shards())]
+        #[pallet::weight((T::WeightInfo::rebalance_shards(), DispatchClass::Operational, Pays::No))]
         pub fn rebalance_shards(origin: OriginFor<T>) -> DispatchResult {
             ensure_root(origin)?;
+            ensure!(!ActiveShardSplit::<T>::exists(), Error::<T>::SplitInProgress);
 
             // Find the most and least loaded shards
             let mut shard_loads: Vec<(ShardId, u32)> = Vec::new();
@@ -472,197 +1592,3120 @@ pub mod pallet {
 
             shard_loads.sort_by_key(|(_, load)| *load);
             
-            if let (Some(&(least_loaded, _)), Some(&(most_loaded, _))) = 
+            if let (Some(&(least_loaded, least_load)), Some(&(most_loaded, most_load))) =
                 (shard_loads.first(), shard_loads.last()) {
-                
-                // Move some accounts from most loaded to least loaded shard
-                // This is a simplified version - in practice, we'd need more sophisticated logic
-                let moved_accounts = 10u32; // Simplified
-                
-                Self::deposit_event(Event::LoadBalanced {
-                    from_shard: most_loaded,
-                    to_shard: least_loaded,
-                    moved_accounts,
-                });
+                if most_load <= least_load {
+                    return Ok(());
+                }
+
+                let effective_epoch = CurrentEpoch::<T>::get().saturating_add(1);
+                let slots = T::MaxMigrationsPerEpoch::get().saturating_sub(PendingMigrations::<T>::count());
+                let mut moved_accounts = 0u32;
+
+                // Recipients draw the load of the queue they are paid from
+                for tx in Self::cross_shard_queue(most_loaded) {
+                    if moved_accounts >= slots {
+                        break;
+                    }
+                    let account = tx.recipient;
+                    if PendingMigrations::<T>::contains_key(&account) ||
+                        Self::get_account_shard(&account) != most_loaded
+                    {
+                        continue;
+                    }
+
+                    PendingMigrations::<T>::insert(&account, PendingMigration {
+                        from_shard: most_loaded,
+                        to_shard: least_loaded,
+                        effective_epoch,
+                    });
+                    Self::deposit_indexed_event(
+                        Event::MigrationAnnounced {
+                            account: account.clone(),
+                            from_shard: most_loaded,
+                            to_shard: least_loaded,
+                            effective_epoch,
+                        },
+                        &[most_loaded, least_loaded],
+                        &[&account],
+                    );
+                    moved_accounts += 1;
+                }
+
+                if moved_accounts > 0 {
+                    Self::deposit_indexed_event(
+                        Event::LoadBalanced { from_shard: most_loaded, to_shard: least_loaded, moved_accounts },
+                        &[most_loaded, least_loaded],
+                        &[],
+                    );
+                }
             }
 
             Ok(())
         }
-    }
 
-    /// Helper functions
-    impl<T: Config> Pallet<T> {
-        /// Get the shard for a given account
-        pub fn get_account_shard(account: &T::AccountId) -> ShardId {
-            // Use account hash to determine shard
-            let hash = BlakeTwo256::hash_of(account);
-            let hash_bytes = hash.as_ref();
-            hash_bytes[0] % SHARD_COUNT
+        /// Forward a contract call to another shard
+        ///
+        /// The call is queued on the destination shard and executed asynchronously;
+        /// its outcome is published as a receipt and a `CrossShardCallExecuted` event.
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::execute_cross_shard_call())]
+        pub fn execute_cross_shard_call(
+            origin: OriginFor<T>,
+            to_shard: ShardId,
+            dest_contract: T::AccountId,
+            data: Vec<u8>,
+            gas_limit: Weight,
+            value: BalanceOf<T>,
+        ) -> DispatchResult {
+            let caller = ensure_signed(origin)?;
+            Self::queue_cross_shard_call(caller, to_shard, dest_contract, data, gas_limit, value)?;
+            Ok(())
         }
 
-        /// Assign account to shard based on hash
-        pub fn assign_account_to_shard(account: &T::AccountId) {
+        /// Execute pending cross-shard contract calls (called by block author)
+        ///
+        /// Up front, the caller is charged for `max_calls` calls using `MaxCrossShardCallGas`
+        /// each; the charge is then lowered to the calls actually executed and the gas they
+        /// used.
+        #[pallet::call_index(7)]
+        #[pallet::weight(
+            T::WeightInfo::process_cross_shard_calls(*max_calls)
+                .saturating_add(T::MaxCrossShardCallGas::get().saturating_mul(u64::from(*max_calls)))
+        )]
+        pub fn process_cross_shard_calls(
+            origin: OriginFor<T>,
+            shard_id: ShardId,
+            max_calls: u32,
+        ) -> DispatchResultWithPostInfo {
+            ensure_signed(origin)?;
+            sp_tracing::enter_span!(sp_tracing::Level::DEBUG, "process_cross_shard_calls");
+
+            let mut queue = CrossShardCallQueue::<T>::get(shard_id);
+            let process_count = (queue.len() as u32).min(max_calls) as usize;
+
+            if process_count == 0 {
+                return Ok(Some(T::WeightInfo::process_cross_shard_calls(0)).into());
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let expires_at = now.saturating_add(T::CrossShardCallReceiptLifetime::get());
+            let mut total_gas_used = Weight::zero();
+
+            for call in queue.drain(0..process_count) {
+                let (result, gas_used) = T::CallExecutor::execute(
+                    &call.caller,
+                    &call.dest_contract,
+                    call.value,
+                    call.gas_limit,
+                    call.data.into_inner(),
+                );
+                let gas_used = gas_used.min(call.gas_limit);
+                total_gas_used.saturating_accrue(gas_used);
+
+                let success = result.is_ok();
+                if let Err(error) = &result {
+                    log::debug!(
+                        target: LOG_TARGET,
+                        "shard {}: cross-shard call {} from shard {} failed: {:?}",
+                        shard_id,
+                        call.call_id,
+                        call.from_shard,
+                        error,
+                    );
+                }
+                let output = result.unwrap_or_else(|e| e.encode());
+
+                CrossShardCallReceipts::<T>::insert(
+                    call.call_id,
+                    CrossShardCallReceipt {
+                        success,
+                        output: BoundedVec::truncate_from(output),
+                        gas_used,
+                        executed_at: now,
+                    },
+                );
+                Self::schedule_expiry(expires_at, |at| {
+                    CrossShardCallReceiptExpiries::<T>::try_append(at, call.call_id).is_ok()
+                });
+
+                Self::deposit_indexed_event(
+                    Event::CrossShardCallExecuted { call_id: call.call_id, to_shard: shard_id, success, gas_used },
+                    &[call.from_shard, shard_id],
+                    &[&call.caller, &call.dest_contract],
+                );
+            }
+
+            CrossShardCallQueue::<T>::insert(shard_id, queue);
+            Self::note_processed(shard_id, process_count as u32);
+
+            let weight = T::WeightInfo::process_cross_shard_calls(process_count as u32).saturating_add(total_gas_used);
+            Ok(Some(weight).into())
+        }
+
+        /// Register new session keys and confirm membership of `shard_id` in one step
+        ///
+        /// `keys` are the SCALE-encoded session keys, as returned by the node's
+        /// `validator_rotateKeys` RPC. The validator is added to the shard if it is not
+        /// already a member.
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::rotate_and_register_keys())]
+        pub fn rotate_and_register_keys(
+            origin: OriginFor<T>,
+            shard_id: ShardId,
+            keys: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let keys_hash = T::Hashing::hash(&keys);
+            T::SessionKeys::register_keys(&who, keys)?;
+
+            ShardInfos::<T>::try_mutate(shard_id, |maybe_info| -> DispatchResult {
+                let info = maybe_info.as_mut().ok_or(Error::<T>::ShardNotFound)?;
+
+                if !info.validators.contains(&who) {
+                    info.validators.try_push(who.clone()).map_err(|_| Error::<T>::ShardAtCapacity)?;
+                }
+
+                Ok(())
+            })?;
+
+            Self::deposit_indexed_event(
+                Event::ValidatorKeysRotated { shard_id, validator: who, keys_hash },
+                &[shard_id],
+                &[],
+            );
+
+            Ok(())
+        }
+
+        /// Set the bounds quoted cross-shard fees are clamped to
+        ///
+        /// `None` ignores the quote and charges the constant `CrossShardFee` again.
+        #[pallet::call_index(9)]
+        #[pallet::weight((T::WeightInfo::set_cross_shard_fee_bounds(), DispatchClass::Operational, Pays::No))]
+        pub fn set_cross_shard_fee_bounds(
+            origin: OriginFor<T>,
+            bounds: Option<(
+                BalanceOf<T>,
+                BalanceOf<T>,
+            )>,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+
+            if let Some((min, max)) = bounds {
+                ensure!(min <= max, Error::<T>::InvalidFeeBounds);
+            }
+            CrossShardFeeBounds::<T>::set(bounds);
+
+            Self::deposit_event(Event::CrossShardFeeBoundsSet { bounds });
+
+            Ok(())
+        }
+
+        /// Resolve a dead-lettered cross-shard transfer before it times out
+        ///
+        /// The recipient retries delivery, e.g. once its account exists; the sender takes the
+        /// refund, minus the `FailurePenalty`, without waiting for `DeadLetterTimeout`. The
+        /// tip of a claimed transfer goes back to its sender.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::claim_failed_transfer())]
+        pub fn claim_failed_transfer(origin: OriginFor<T>, tx_hash: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let dead_letter = DeadLetters::<T>::get(tx_hash).ok_or(Error::<T>::DeadLetterNotFound)?;
+            let tx = &dead_letter.tx.into_latest();
+
+            if who == tx.recipient {
+                Self::deliver(tx_hash, tx, None).map_err(|_| Error::<T>::RecipientStillRejected)?;
+            } else if who == tx.sender {
+                Self::refund(tx_hash, tx, FailureReason::RecipientRejected);
+            } else {
+                return Err(Error::<T>::NotTransferParty.into());
+            }
+
+            DeadLetters::<T>::remove(tx_hash);
+            DeadLetterExpiries::<T>::mutate(dead_letter.expires_at, |expiring| {
+                expiring.retain(|expiring_hash| *expiring_hash != tx_hash)
+            });
+            Self::note_dead_letter_resolved(tx.amount);
+
+            Ok(())
+        }
+
+        /// Execute a cross-shard transaction with a `tip` for the settling validator
+        ///
+        /// The tip is held with the fee and amount. `process_cross_shard_queue` settles the
+        /// highest tips first, transfers of equal tip in the order they were queued, and pays
+        /// the tip to its caller on delivery; it is refunded if the transfer fails.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::execute_cross_shard())]
+        pub fn execute_cross_shard_tx_with_tip(
+            origin: OriginFor<T>,
+            to_shard: ShardId,
+            recipient: T::AccountId,
+            amount: BalanceOf<T>,
+            tip: BalanceOf<T>,
+        ) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::queue_cross_shard_tx(sender, to_shard, recipient, amount, tip)
+        }
+
+        /// Split half of the accounts of `parent` off to `new_shard`
+        ///
+        /// Accounts of `parent` are selected by re-hashing them with both shard ids, so every
+        /// node moves the same ones. They move from the next block on, `MaxSplitAccountsPerBlock`
+        /// accounts examined per block, effective immediately and without waiting for an
+        /// epoch; accounts with an announced move are left to it. `new_shard` is created
+        /// without validators if it does not exist yet. Until the split completes, accounts of
+        /// either shard may tag transactions with either, and rebalancing is refused.
+        #[pallet::call_index(12)]
+        #[pallet::weight((T::WeightInfo::begin_shard_split(), DispatchClass::Operational, Pays::No))]
+        pub fn begin_shard_split(origin: OriginFor<T>, parent: ShardId, new_shard: ShardId) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(!ActiveShardSplit::<T>::exists(), Error::<T>::SplitInProgress);
+            ensure!(ShardInfos::<T>::contains_key(parent), Error::<T>::ShardNotFound);
+            ensure!(new_shard < SHARD_COUNT && new_shard != parent, Error::<T>::InvalidShardConfig);
+
+            if !ShardInfos::<T>::contains_key(new_shard) {
+                Self::insert_shard(new_shard, BoundedVec::new());
+            }
+            ActiveShardSplit::<T>::put(ShardSplit { parent, new_shard, cursor: None, examined: 0, moved: 0 });
+            Self::deposit_indexed_event(Event::ShardSplitStarted { parent, new_shard }, &[parent, new_shard], &[]);
+
+            Ok(())
+        }
+
+        /// Reserve `shard` for system transactions, or lift the reservation with `None`
+        ///
+        /// Signed transactions making a `SystemCalls` call must then be tagged with `shard`
+        /// instead of their signer's shard, so they have a lane of the pool to themselves.
+        /// [`SYSTEM_SHARD`] is a virtual shard other transactions cannot be tagged with; on a
+        /// regular shard, they share it with the transactions of the accounts living there.
+        /// System transactions already tagged with another shard become invalid.
+        #[pallet::call_index(13)]
+        #[pallet::weight((T::WeightInfo::set_system_shard(), DispatchClass::Operational, Pays::No))]
+        pub fn set_system_shard(origin: OriginFor<T>, shard: Option<ShardId>) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(
+                shard.is_none_or(|shard| shard < SHARD_COUNT || shard == SYSTEM_SHARD),
+                Error::<T>::InvalidShardConfig
+            );
+
+            ReservedSystemShard::<T>::set(shard);
+            Self::deposit_event(Event::SystemShardSet { shard });
+
+            Ok(())
+        }
+    }
+
+    /// Helper functions
+    impl<T: Config> Pallet<T> {
+        /// Check that `sender` can queue a transfer of `amount` with `tip` on `to_shard`,
+        /// returning the sender's shard and the fee, surge included
+        ///
+        /// Shared by submission and [`Self::dry_run_cross_shard`], so a dry run fails wherever
+        /// submitting would.
+        fn validate_cross_shard_tx(
+            sender: &T::AccountId,
+            to_shard: ShardId,
+            amount: BalanceOf<T>,
+            tip: BalanceOf<T>,
+        ) -> Result<(ShardId, BalanceOf<T>), DispatchError> {
+            ensure!(!T::Safeguard::is_paused(Subsystem::CrossShard), Error::<T>::SystemPaused);
+
+            let from_shard = Self::get_account_shard(sender);
+            ensure!(from_shard != to_shard, Error::<T>::InvalidCrossShardTx);
+            ensure!(
+                CrossShardQueue::<T>::decode_len(to_shard).unwrap_or(0) < T::MaxCrossShardQueueLen::get() as usize,
+                Error::<T>::CrossShardQueueFull
+            );
+
+            let fee = Self::cross_shard_fee(to_shard)?;
+            ensure!(
+                T::Currency::reducible_balance(sender, Preservation::Protect, Fortitude::Polite) >=
+                    fee.saturating_add(amount).saturating_add(tip),
+                Error::<T>::InsufficientBalance
+            );
+
+            Ok((from_shard, fee))
+        }
+
+        /// Hold the fee, `amount` and `tip` on `sender` and queue the transfer on `to_shard`
+        fn queue_cross_shard_tx(
+            sender: T::AccountId,
+            to_shard: ShardId,
+            recipient: T::AccountId,
+            amount: BalanceOf<T>,
+            tip: BalanceOf<T>,
+        ) -> DispatchResult {
+            let (from_shard, fee) = Self::validate_cross_shard_tx(&sender, to_shard, amount, tip)?;
+
+            // Hold the cross-shard fee, surged if the destination is overloaded, the amount and
+            // the tip on the sender until the destination shard settles them
+            T::Currency::hold(
+                &HoldReason::CrossShardEscrow.into(),
+                &sender,
+                fee.saturating_add(amount).saturating_add(tip),
+            )?;
+
+            // Create cross-shard transaction. Lane transactions leave the account nonce alone,
+            // so the transfer is numbered from a pallet-wide counter instead
+            let nonce = NextCrossShardTxNonce::<T>::get();
+            NextCrossShardTxNonce::<T>::put(nonce.saturating_add(1));
+            let cross_shard_tx = CrossShardTx {
+                from_shard,
+                to_shard,
+                sender: sender.clone(),
+                recipient: recipient.clone(),
+                amount,
+                nonce,
+                fee,
+                tip,
+            };
+            let tx_hash = BlakeTwo256::hash_of(&cross_shard_tx);
+            CrossShardTxStatuses::<T>::insert(tx_hash, CrossShardTxStatus::Queued);
+            let now = frame_system::Pallet::<T>::block_number();
+            QueuedAt::<T>::insert(tx_hash, now);
+            OldestQueuedAt::<T>::mutate(to_shard, |oldest| {
+                oldest.get_or_insert(now);
+            });
+
+            // Add to destination shard queue
+            CrossShardQueue::<T>::try_mutate(to_shard, |queue| queue.try_push(cross_shard_tx.into()))
+                .map_err(|_| Error::<T>::CrossShardQueueFull)?;
+            Self::note_queued(to_shard);
+
+            // Update metrics
+            Metrics::<T>::mutate(|metrics| {
+                metrics.cross_shard_txs = metrics.cross_shard_txs.saturating_add(1);
+                metrics.cross_shard_backlog = metrics.cross_shard_backlog.saturating_add(1);
+            });
+
+            // Index the transfer for both parties
+            T::History::record(HistoryKind::CrossShardTransfer, &sender, &[to_shard], tx_hash);
+            if recipient != sender {
+                T::History::record(HistoryKind::CrossShardTransfer, &recipient, &[to_shard], tx_hash);
+            }
+
+            Self::deposit_indexed_event(
+                Event::CrossShardExecuted { from_shard, to_shard, tx_hash },
+                &[from_shard, to_shard],
+                &[&sender, &recipient],
+            );
+
+            Ok(())
+        }
+
+        /// Queue a contract call from `caller` for execution on `to_shard`
+        ///
+        /// Charges the cross-shard fee to `caller`. Used by `execute_cross_shard_call` and by
+        /// the contracts chain extension, where `caller` is the calling contract. `gas_limit`
+        /// is capped by `MaxCrossShardCallGas` and `data` by `MaxCrossShardCallDataLen`.
+        pub fn queue_cross_shard_call(
+            caller: T::AccountId,
+            to_shard: ShardId,
+            dest_contract: T::AccountId,
+            data: Vec<u8>,
+            gas_limit: Weight,
+            value: BalanceOf<T>,
+        ) -> Result<CrossShardCallId, DispatchError> {
+            ensure!(!T::Safeguard::is_paused(Subsystem::CrossShard), Error::<T>::SystemPaused);
+            let from_shard = Self::get_account_shard(&caller);
+
+            ensure!(from_shard != to_shard, Error::<T>::InvalidCrossShardTx);
+            ensure!(gas_limit.all_lte(T::MaxCrossShardCallGas::get()), Error::<T>::GasLimitTooHigh);
+            let data = CrossShardCallData::try_from(data).map_err(|_| Error::<T>::CallDataTooLarge)?;
+            // Checked before charging: the chain extension does not roll back a failed call
+            let queued = CrossShardCallQueue::<T>::decode_len(to_shard).unwrap_or(0);
+            ensure!(queued < T::MaxCrossShardCallQueueLen::get() as usize, Error::<T>::CrossShardCallQueueFull);
+
+            // Charge cross-shard fee, surged if the destination is overloaded
+            let fee = Self::cross_shard_fee(to_shard)?;
+            T::Currency::burn_from(&caller, fee, Preservation::Preserve, Precision::Exact, Fortitude::Polite)?;
+
+            let call_id = NextCrossShardCallId::<T>::get();
+            NextCrossShardCallId::<T>::put(call_id.saturating_add(1));
+
+            let call = CrossShardCall {
+                call_id,
+                from_shard,
+                to_shard,
+                caller: caller.clone(),
+                dest_contract: dest_contract.clone(),
+                data,
+                gas_limit,
+                value,
+            };
+
+            CrossShardCallQueue::<T>::try_mutate(to_shard, |queue| queue.try_push(call))
+                .map_err(|_| Error::<T>::CrossShardCallQueueFull)?;
+            Self::note_queued(to_shard);
+
+            Metrics::<T>::mutate(|metrics| {
+                metrics.cross_shard_txs = metrics.cross_shard_txs.saturating_add(1);
+            });
+
+            Self::deposit_indexed_event(
+                Event::CrossShardCallQueued {
+                    call_id,
+                    from_shard,
+                    to_shard,
+                    caller: caller.clone(),
+                    dest_contract: dest_contract.clone(),
+                },
+                &[from_shard, to_shard],
+                &[&caller, &dest_contract],
+            );
+
+            Ok(call_id)
+        }
+
+        /// Event topic of everything touching `shard_id`
+        pub fn shard_topic(shard_id: ShardId) -> T::Hash {
+            T::Hashing::hash(&[SHARD_TOPIC_PREFIX, &[shard_id]].concat())
+        }
+
+        /// Event topic of the cross-shard transfers and calls `who` takes part in
+        pub fn account_topic(who: &T::AccountId) -> T::Hash {
+            let mut preimage = ACCOUNT_TOPIC_PREFIX.to_vec();
+            who.encode_to(&mut preimage);
+            T::Hashing::hash(&preimage)
+        }
+
+        /// Deposit `event` filed under the topics of `shards` and `accounts`
+        fn deposit_indexed_event(event: Event<T>, shards: &[ShardId], accounts: &[&T::AccountId]) {
+            let mut topics: Vec<T::Hash> = shards.iter().map(|shard_id| Self::shard_topic(*shard_id)).collect();
+            topics.extend(accounts.iter().map(|who| Self::account_topic(who)));
+            topics.sort();
+            topics.dedup();
+
+            let event = <T as Config>::RuntimeEvent::from(event);
+            frame_system::Pallet::<T>::deposit_event_indexed(&topics, event.into());
+        }
+
+        /// Account collecting the fees of delivered cross-shard transfers and paying settlement
+        /// rewards from them
+        pub fn fee_pot_account() -> T::AccountId {
+            T::PalletId::get().into_sub_account_truncating(b"fees")
+        }
+
+        /// Balance of the fee pot
+        pub fn pallet_pot_balance() -> BalanceOf<T> {
+            T::Currency::balance(&Self::fee_pot_account())
+        }
+
+        /// Fund the fee pot with the existential deposit, so that fees below it are collected
+        /// rather than burnt; rewards keep it alive from then on
+        pub fn endow_pot() {
+            let pot = Self::fee_pot_account();
+            let minimum = T::Currency::minimum_balance();
+            if T::Currency::balance(&pot) < minimum {
+                let _ = T::Currency::set_balance(&pot, minimum);
+            }
+        }
+
+        /// Deliver a held transfer to its recipient, returning whether it was delivered
+        ///
+        /// On success the held amount moves to the recipient, the held fee to the fee pot and
+        /// the held tip to `processor`. A transfer the recipient cannot receive is
+        /// dead-lettered; one whose hold fell short is released back to the sender as is.
+        fn settle(tx: &CrossShardTx<T::AccountId, BalanceOf<T>>, processor: &T::AccountId) -> bool {
+            let tx_hash = BlakeTwo256::hash_of(tx);
+            let hold_reason: T::RuntimeHoldReason = HoldReason::CrossShardEscrow.into();
+
+            if T::Currency::balance_on_hold(&hold_reason, &tx.sender) < Self::held_for(tx) {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "shard {}: hold of cross-shard transfer {:?} from shard {} fell short, refunding",
+                    tx.to_shard,
+                    tx_hash,
+                    tx.from_shard,
+                );
+                Self::refund(tx_hash, tx, FailureReason::InsufficientFunds);
+                false
+            } else if let Err(error) = Self::deliver(tx_hash, tx, Some(processor)) {
+                log::debug!(
+                    target: LOG_TARGET,
+                    "shard {}: recipient rejected cross-shard transfer {:?}, dead-lettering: {:?}",
+                    tx.to_shard,
+                    tx_hash,
+                    error,
+                );
+                Self::dead_letter(tx_hash, tx);
+                false
+            } else {
+                true
+            }
+        }
+
+        /// Total held on the sender of `tx` until it settles
+        fn held_for(tx: &CrossShardTx<T::AccountId, BalanceOf<T>>) -> BalanceOf<T> {
+            tx.amount.saturating_add(tx.fee).saturating_add(tx.tip)
+        }
+
+        /// Pay the held amount of `tx` to its recipient, its held fee to the fee pot and its
+        /// held tip to `processor`, or back to the sender if nobody processed it
+        fn deliver(
+            tx_hash: T::Hash,
+            tx: &CrossShardTx<T::AccountId, BalanceOf<T>>,
+            processor: Option<&T::AccountId>,
+        ) -> DispatchResult {
+            let hold_reason: T::RuntimeHoldReason = HoldReason::CrossShardEscrow.into();
+            T::Currency::transfer_on_hold(
+                &hold_reason,
+                &tx.sender,
+                &tx.recipient,
+                tx.amount,
+                Precision::Exact,
+                Restriction::Free,
+                Fortitude::Polite,
+            )?;
+            // A fee the pot cannot take, below the existential deposit of an empty pot, is burnt
+            let collected = T::Currency::transfer_on_hold(
+                &hold_reason,
+                &tx.sender,
+                &Self::fee_pot_account(),
+                tx.fee,
+                Precision::BestEffort,
+                Restriction::Free,
+                Fortitude::Force,
+            );
+            if collected.is_err() {
+                let _ = T::Currency::burn_held(&hold_reason, &tx.sender, tx.fee, Precision::BestEffort, Fortitude::Force);
+            }
+            // A tip the processor cannot take goes back to the sender
+            if !tx.tip.is_zero() {
+                let tipped = processor.map_or(false, |processor| {
+                    T::Currency::transfer_on_hold(
+                        &hold_reason,
+                        &tx.sender,
+                        processor,
+                        tx.tip,
+                        Precision::BestEffort,
+                        Restriction::Free,
+                        Fortitude::Force,
+                    )
+                    .is_ok()
+                });
+                if !tipped {
+                    let _ = T::Currency::release(&hold_reason, &tx.sender, tx.tip, Precision::BestEffort);
+                }
+            }
+
+            CrossShardTxStatuses::<T>::insert(tx_hash, CrossShardTxStatus::Settled);
+            Self::expire_tx_record(tx_hash);
+            CrossShardTxReceipts::<T>::insert(
+                tx_hash,
+                CrossShardTxReceipt {
+                    sender: tx.sender.clone(),
+                    recipient: tx.recipient.clone(),
+                    amount: tx.amount,
+                    from_shard: tx.from_shard,
+                    to_shard: tx.to_shard,
+                    settled_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+            Self::deposit_indexed_event(
+                Event::CrossShardSettled { tx_hash, to_shard: tx.to_shard },
+                &[tx.from_shard, tx.to_shard],
+                &[&tx.sender, &tx.recipient],
+            );
+            Ok(())
+        }
+
+        /// Pay `who` the settlement reward for `delivered` transfers of `shard_id` from the fee
+        /// pot, within what the pot and this block's reward cap have left
+        fn reward_processor(who: &T::AccountId, shard_id: ShardId, delivered: u32) {
+            let pot = Self::fee_pot_account();
+            let cap_left = T::MaxSettlementRewardPerBlock::get().saturating_sub(RewardsPaidThisBlock::<T>::get());
+            let amount = T::SettlementReward::get()
+                .saturating_mul(delivered.into())
+                .min(cap_left)
+                .min(T::Currency::reducible_balance(&pot, Preservation::Preserve, Fortitude::Polite));
+            if amount.is_zero() || T::Currency::transfer(&pot, who, amount, Preservation::Preserve).is_err() {
+                return;
+            }
+
+            RewardsPaidThisBlock::<T>::mutate(|paid| *paid = paid.saturating_add(amount));
+            SettlementRewardStats::<T>::mutate(|stats| {
+                stats.payouts = stats.payouts.saturating_add(1);
+                stats.rewarded_settlements = stats.rewarded_settlements.saturating_add(delivered);
+                stats.paid = stats.paid.saturating_add(amount);
+            });
+            Self::deposit_indexed_event(
+                Event::SettlementRewardPaid { who: who.clone(), shard_id, settled: delivered, amount },
+                &[shard_id],
+                &[who],
+            );
+        }
+
+        /// Release what is held for `tx` back to its sender, minus the penalty for `reason`
+        pub(crate) fn refund(
+            tx_hash: T::Hash,
+            tx: &CrossShardTx<T::AccountId, BalanceOf<T>>,
+            reason: FailureReason,
+        ) {
+            let hold_reason: T::RuntimeHoldReason = HoldReason::CrossShardEscrow.into();
+            let held = Self::held_for(tx);
+
+            // Whatever is left of a hold that fell short is released as is
+            let penalty = match reason {
+                FailureReason::RecipientRejected => T::FailurePenalty::get() * tx.amount,
+                _ => Zero::zero(),
+            };
+            let burnt = T::Currency::burn_held(&hold_reason, &tx.sender, penalty, Precision::BestEffort, Fortitude::Force)
+                .unwrap_or_default();
+            let refunded = T::Currency::release(&hold_reason, &tx.sender, held.saturating_sub(burnt), Precision::BestEffort)
+                .unwrap_or_default();
+
+            CrossShardTxStatuses::<T>::insert(tx_hash, CrossShardTxStatus::Failed(reason));
+            Self::expire_tx_record(tx_hash);
+            Self::deposit_indexed_event(
+                Event::CrossShardFailed { tx_hash, sender: tx.sender.clone(), refunded, reason },
+                &[tx.from_shard, tx.to_shard],
+                &[&tx.sender, &tx.recipient],
+            );
+        }
+
+        /// Keep `tx` held until its recipient claims it or it times out
+        fn dead_letter(tx_hash: T::Hash, tx: &CrossShardTx<T::AccountId, BalanceOf<T>>) {
+            let timeout = frame_system::Pallet::<T>::block_number().saturating_add(T::DeadLetterTimeout::get());
+            let expires_at =
+                Self::schedule_expiry(timeout, |at| DeadLetterExpiries::<T>::try_append(at, tx_hash).is_ok());
+            DeadLetters::<T>::insert(tx_hash, DeadLetter { tx: tx.clone().into(), expires_at });
+            CrossShardTxStatuses::<T>::insert(tx_hash, CrossShardTxStatus::DeadLettered);
+            DeadLetterStats::<T>::mutate(|stats| {
+                stats.dead_lettered = stats.dead_lettered.saturating_add(1);
+                stats.dead_lettered_volume = stats.dead_lettered_volume.saturating_add(tx.amount);
+                stats.pending = stats.pending.saturating_add(1);
+                stats.pending_volume = stats.pending_volume.saturating_add(tx.amount);
+            });
+
+            Self::deposit_indexed_event(
+                Event::CrossShardDeadLettered {
+                    tx_hash,
+                    recipient: tx.recipient.clone(),
+                    amount: tx.amount,
+                    expires_at,
+                },
+                &[tx.from_shard, tx.to_shard],
+                &[&tx.sender, &tx.recipient],
+            );
+        }
+
+        /// Refund the dead-lettered transfers timing out at block `n`
+        fn refund_expired_dead_letters(n: BlockNumberFor<T>) -> Weight {
+            let expired = DeadLetterExpiries::<T>::take(n);
+            if !expired.is_empty() {
+                log::debug!(target: LOG_TARGET, "refunding {} expired dead-lettered transfers", expired.len());
+            }
+            for tx_hash in &expired {
+                if let Some(dead_letter) = DeadLetters::<T>::take(tx_hash) {
+                    let tx = dead_letter.tx.into_latest();
+                    Self::refund(*tx_hash, &tx, FailureReason::RecipientRejected);
+                    Self::note_dead_letter_resolved(tx.amount);
+                }
+            }
+
+            // Per transfer: the dead letter, the hold and the account, then the status
+            let transfers = expired.len() as u64;
+            T::DbWeight::get().reads_writes(1 + 3 * transfers, 1 + 4 * transfers)
+        }
+
+        /// Remove the cross-shard call receipts whose lifetime ends at block `n`
+        fn prune_cross_shard_call_receipts(n: BlockNumberFor<T>) -> Weight {
+            let expired = CrossShardCallReceiptExpiries::<T>::take(n);
+            for call_id in &expired {
+                CrossShardCallReceipts::<T>::remove(call_id);
+            }
+
+            T::DbWeight::get().reads_writes(1, 1 + expired.len() as u64)
+        }
+
+        /// Prune the status and receipt of the transfer `tx_hash`, which settled or failed,
+        /// after `CrossShardTxRecordLifetime` blocks
+        pub(crate) fn expire_tx_record(tx_hash: T::Hash) {
+            let expires_at = frame_system::Pallet::<T>::block_number()
+                .saturating_add(T::CrossShardTxRecordLifetime::get());
+            Self::schedule_expiry(expires_at, |at| CrossShardTxExpiries::<T>::try_append(at, tx_hash).is_ok());
+        }
+
+        /// First block from `at` on at which `try_schedule` finds room among the
+        /// `MaxExpiringPerBlock` entries expiring there, having scheduled the entry at it
+        pub(crate) fn schedule_expiry(
+            mut at: BlockNumberFor<T>,
+            mut try_schedule: impl FnMut(BlockNumberFor<T>) -> bool,
+        ) -> BlockNumberFor<T> {
+            while !try_schedule(at) {
+                at = at.saturating_add(One::one());
+            }
+            at
+        }
+
+        /// Remove the statuses and receipts of the cross-shard transfers whose lifetime ends at
+        /// block `n`
+        fn prune_cross_shard_tx_records(n: BlockNumberFor<T>) -> Weight {
+            let expired = CrossShardTxExpiries::<T>::take(n);
+            for tx_hash in &expired {
+                CrossShardTxStatuses::<T>::remove(tx_hash);
+                CrossShardTxReceipts::<T>::remove(tx_hash);
+            }
+
+            T::DbWeight::get().reads_writes(1, 1 + 2 * expired.len() as u64)
+        }
+
+        /// Account for a dead-lettered transfer of `amount` that was claimed or refunded
+        fn note_dead_letter_resolved(amount: BalanceOf<T>) {
+            DeadLetterStats::<T>::mutate(|stats| {
+                stats.pending = stats.pending.saturating_sub(1);
+                stats.pending_volume = stats.pending_volume.saturating_sub(amount);
+            });
+        }
+
+        /// Fee for a cross-shard transaction into `to_shard`
+        ///
+        /// The base fee while the shard is below capacity, otherwise the surge fee, failing
+        /// with `ShardOverloaded` if the surge policy rejects the transaction.
+        pub fn cross_shard_fee(
+            to_shard: ShardId,
+        ) -> Result<BalanceOf<T>, DispatchError> {
+            let capacity = ShardInfos::<T>::get(to_shard)
+                .map(|info| info.capacity)
+                .ok_or(Error::<T>::ShardNotFound)?;
+            let load = ShardProcessingState::<T>::get(to_shard);
+            let base_fee = Self::base_cross_shard_fee();
+
+            if load < capacity {
+                return Ok(base_fee);
+            }
+
+            T::SurgeFee::surge_fee(base_fee, load, capacity)
+                .ok_or_else(|| Error::<T>::ShardOverloaded.into())
+        }
+
+        /// Simulate `execute_cross_shard_tx_with_tip` from `sender` without changing state
+        ///
+        /// Fails where the call would fail on submission: cross-shard transfers paused, a
+        /// destination that is the sender's own shard, unknown, rejecting surged transactions or
+        /// with a full queue, or a balance that cannot cover the fee, amount and tip while
+        /// staying alive.
+        pub fn dry_run_cross_shard(
+            sender: &T::AccountId,
+            to_shard: ShardId,
+            recipient: &T::AccountId,
+            amount: BalanceOf<T>,
+            tip: BalanceOf<T>,
+        ) -> Result<CrossShardDryRun<BalanceOf<T>>, DispatchError> {
+            let (from_shard, fee) = Self::validate_cross_shard_tx(sender, to_shard, amount, tip)?;
+
+            let queue_position = CrossShardQueue::<T>::decode_len(to_shard).unwrap_or_default() as u32;
+            let settlement_blocks = queue_position / T::MaxSettlementsPerBlock::get().max(1) + 1;
+
+            Ok(CrossShardDryRun {
+                from_shard,
+                fee,
+                queue_position,
+                settlement_blocks,
+                recipient_accepts: T::Currency::balance(recipient).saturating_add(amount) >=
+                    T::Currency::minimum_balance(),
+            })
+        }
+
+        /// Fee of a cross-shard transaction into a shard below capacity
+        ///
+        /// The `FeeQuote` clamped to `CrossShardFeeBounds`, or `CrossShardFee` while no bounds
+        /// are set or no quote is available.
+        pub fn base_cross_shard_fee() -> BalanceOf<T> {
+            match (T::FeeQuote::quote(), CrossShardFeeBounds::<T>::get()) {
+                (Some(quote), Some((min, max))) => quote.max(min).min(max),
+                _ => T::CrossShardFee::get(),
+            }
+        }
+
+        /// Account for a transaction queued on `shard_id`
+        fn note_queued(shard_id: ShardId) {
+            let load = ShardProcessingState::<T>::mutate(shard_id, |load| {
+                *load = load.saturating_add(1);
+                *load
+            });
+            let capacity = ShardInfos::<T>::get(shard_id).map(|info| info.capacity).unwrap_or_default();
+
+            if load >= capacity && !OverloadedShards::<T>::contains_key(shard_id) {
+                OverloadedShards::<T>::insert(shard_id, ());
+                Self::deposit_indexed_event(Event::ShardOverloaded { shard_id, load, capacity }, &[shard_id], &[]);
+            }
+        }
+
+        /// Account for `count` transactions processed on `shard_id`
+        pub(crate) fn note_processed(shard_id: ShardId, count: u32) {
+            let load = ShardProcessingState::<T>::mutate(shard_id, |load| {
+                *load = load.saturating_sub(count);
+                *load
+            });
+            let capacity = ShardInfos::<T>::get(shard_id).map(|info| info.capacity).unwrap_or_default();
+
+            if load < capacity && OverloadedShards::<T>::take(shard_id).is_some() {
+                Self::deposit_indexed_event(Event::OverloadCleared { shard_id, load }, &[shard_id], &[]);
+            }
+        }
+
+        /// Record when `oldest`, the first transfer left in `shard_id`'s queue, was queued
+        ///
+        /// Transfers queued before queue ages were tracked keep the shard's previous age.
+        fn note_oldest_queued(shard_id: ShardId, oldest: Option<&CrossShardTx<T::AccountId, BalanceOf<T>>>) {
+            match oldest {
+                Some(tx) => {
+                    if let Some(queued_at) = QueuedAt::<T>::get(BlakeTwo256::hash_of(tx)) {
+                        OldestQueuedAt::<T>::insert(shard_id, queued_at);
+                    }
+                },
+                None => OldestQueuedAt::<T>::remove(shard_id),
+            }
+        }
+
+        /// Blocks the oldest transfer queued on `shard_id` has waited, if any is queued
+        pub fn oldest_queue_age(shard_id: ShardId) -> Option<BlockNumberFor<T>> {
+            let now = frame_system::Pallet::<T>::block_number();
+            OldestQueuedAt::<T>::get(shard_id).map(|queued_at| now.saturating_sub(queued_at))
+        }
+
+        /// Dispatch class of processing `shard_id`'s queue: `Operational` while its latency is
+        /// breached, `Normal` otherwise
+        pub fn queue_dispatch_class(shard_id: ShardId) -> DispatchClass {
+            if LatencyBreachedShards::<T>::contains_key(shard_id) {
+                DispatchClass::Operational
+            } else {
+                DispatchClass::Normal
+            }
+        }
+
+        /// Escalate the queues whose oldest transfer waited longer than `MaxQueueLatency` and
+        /// de-escalate those back within it
+        fn check_queue_latency() -> Weight {
+            let max_latency = T::MaxQueueLatency::get();
+            let mut changed = 0u64;
+            for shard_id in 0..SHARD_COUNT {
+                let breach = Self::oldest_queue_age(shard_id).filter(|age| *age > max_latency);
+                let escalated = LatencyBreachedShards::<T>::contains_key(shard_id);
+                match breach {
+                    Some(age) if !escalated => {
+                        LatencyBreachedShards::<T>::insert(shard_id, ());
+                        log::warn!(
+                            target: LOG_TARGET,
+                            "shard {}: oldest queued transfer waited {:?} blocks, escalating its queue",
+                            shard_id,
+                            age,
+                        );
+                        Self::deposit_indexed_event(Event::QueueLatencyBreached { shard_id, age }, &[shard_id], &[]);
+                        changed += 1;
+                    },
+                    None if escalated => {
+                        LatencyBreachedShards::<T>::remove(shard_id);
+                        Self::deposit_indexed_event(Event::QueueLatencyRecovered { shard_id }, &[shard_id], &[]);
+                        changed += 1;
+                    },
+                    _ => {},
+                }
+            }
+
+            // Per shard: its oldest queued block and whether it is escalated
+            let shards = u64::from(SHARD_COUNT);
+            T::DbWeight::get().reads_writes(2 * shards, changed)
+        }
+
+        /// Create a shard for each entry of `initial_validators`, with those validators
+        fn create_shards(initial_validators: Vec<Vec<T::AccountId>>) -> DispatchResult {
+            // Create 4 shards with distributed validators
+            for (shard_id, validators) in initial_validators.into_iter().enumerate() {
+                let shard_id = shard_id as ShardId;
+                ensure!(shard_id < SHARD_COUNT, Error::<T>::InvalidShardConfig);
+                let validators = BoundedVec::try_from(validators).map_err(|_| Error::<T>::ShardAtCapacity)?;
+
+                Self::insert_shard(shard_id, validators);
+            }
+
+            // Initialize performance metrics
+            Metrics::<T>::put(PerformanceMetrics::default());
+
+            Ok(())
+        }
+
+        /// Create shard `shard_id` with `validators` and an empty queue
+        fn insert_shard(shard_id: ShardId, validators: BoundedVec<T::AccountId, T::MaxValidatorsPerShard>) {
+            let shard_info = ShardInfo {
+                shard_id,
+                validators,
+                total_stake: Zero::zero(),
+                tx_count: 0,
+                capacity: T::TargetTpsPerShard::get(),
+            };
+
+            ShardInfos::<T>::insert(shard_id, &shard_info);
+            
+            // Initialize cross-shard queue
+            CrossShardQueue::<T>::insert(shard_id, BoundedVec::default());
+            
+            // Initialize processing state
+            ShardProcessingState::<T>::insert(shard_id, 0u32);
+
+            let validators = shard_info.validators.into_inner();
+            Self::deposit_indexed_event(Event::ShardCreated { shard_id, validators }, &[shard_id], &[]);
+        }
+
+        /// Get the shard for a given account
+        ///
+        /// The shard rebalancing moved or a mapping upgrade pinned it to, otherwise the one its
+        /// id hashes to under the current mapping. While a mapping upgrade is in progress,
+        /// accounts it examined already hash under the new mapping, which places them where
+        /// the old one did unless they were pinned.
+        pub fn get_account_shard(account: &T::AccountId) -> ShardId {
+            AccountToShard::<T>::try_get(account).unwrap_or_else(|_| Self::mapped_shard(account))
+        }
+
+        /// Shard the shard mapping in force for `account` puts it in, overrides aside
+        pub fn mapped_shard(account: &T::AccountId) -> ShardId {
+            let version = match ActiveShardMappingUpgrade::<T>::get() {
+                Some(upgrade) => match upgrade.cursor {
+                    Some(cursor) if frame_system::Account::<T>::hashed_key_for(account)[..] <= cursor[..] => upgrade.to,
+                    _ => upgrade.from,
+                },
+                None => CurrentShardMapping::<T>::get(),
+            };
+            netchain_primitives::shard_for(version, &account.encode())
+        }
+
+        /// Start upgrading the shard mapping to `to`, unless it is current or an upgrade is in
+        /// progress; returns whether it started
+        pub fn begin_shard_mapping_upgrade(to: ShardMappingVersion) -> bool {
+            let from = CurrentShardMapping::<T>::get();
+            if from == to || ActiveShardMappingUpgrade::<T>::exists() {
+                return false;
+            }
+            ActiveShardMappingUpgrade::<T>::put(ShardMappingUpgrade { from, to, cursor: None, examined: 0, pinned: 0 });
+            Self::deposit_event(Event::ShardMappingUpgradeStarted { from, to });
+            true
+        }
+
+        /// Shard a transaction of `account` making `call` must be tagged with: the reserved
+        /// system shard for system calls, if there is one, otherwise the account's shard
+        pub fn transaction_shard(account: &T::AccountId, call: &<T as frame_system::Config>::RuntimeCall) -> ShardId {
+            match ReservedSystemShard::<T>::get() {
+                Some(system) if T::SystemCalls::contains(call) => system,
+                _ => Self::get_account_shard(account),
+            }
+        }
+
+        /// Whether a transaction of `account` making `call` may be tagged with `shard`
+        ///
+        /// While a system shard is reserved, system calls are only accepted tagged with it;
+        /// other calls are accepted as by [`Self::accepts_shard_tag`], so never on
+        /// [`SYSTEM_SHARD`].
+        pub fn accepts_transaction_tag(
+            account: &T::AccountId,
+            call: &<T as frame_system::Config>::RuntimeCall,
+            shard: ShardId,
+        ) -> bool {
+            match ReservedSystemShard::<T>::get() {
+                Some(system) if T::SystemCalls::contains(call) => shard == system,
+                _ => shard < SHARD_COUNT && Self::accepts_shard_tag(account, shard),
+            }
+        }
+
+        /// Whether transactions of `account` may be tagged with `shard`
+        ///
+        /// Besides its current shard, both shards of an announced move are accepted until the
+        /// end of the epoch it takes effect in, so transactions signed against either
+        /// assignment are not misrouted while wallets catch up. Likewise, accounts of either
+        /// shard of a split in progress are accepted on both.
+        pub fn accepts_shard_tag(account: &T::AccountId, shard: ShardId) -> bool {
+            let current = Self::get_account_shard(account);
+            current == shard ||
+                PendingMigrations::<T>::get(account)
+                    .is_some_and(|migration| migration.from_shard == shard || migration.to_shard == shard) ||
+                ActiveShardSplit::<T>::get().is_some_and(|split| {
+                    let shards = [split.parent, split.new_shard];
+                    shards.contains(&current) && shards.contains(&shard)
+                })
+        }
+
+        /// Whether `account` of `split.parent` belongs to the half split off to `split.new_shard`
+        pub fn splits_off(split: &ShardSplit, account: &T::AccountId) -> bool {
+            let hash = BlakeTwo256::hash_of(&(SHARD_SPLIT_SALT, split.parent, split.new_shard, account));
+            hash.as_ref()[0] & 1 == 1
+        }
+
+        /// Cursor resuming an iteration over `frame_system::Account` after `account`
+        ///
+        /// The key always fits, as `integrity_test` checks.
+        fn account_key_cursor(account: T::AccountId) -> AccountKeyCursor {
+            AccountKeyCursor::truncate_from(frame_system::Account::<T>::hashed_key_for(account))
+        }
+
+        /// Examine the next `MaxSplitAccountsPerBlock` accounts of the active shard split,
+        /// completing it once every account was examined
+        fn advance_shard_split() -> Weight {
+            let Some(mut split) = ActiveShardSplit::<T>::get() else {
+                return T::DbWeight::get().reads(1);
+            };
+
+            let limit = T::MaxSplitAccountsPerBlock::get();
+            let accounts = match split.cursor.take() {
+                Some(cursor) => frame_system::Account::<T>::iter_keys_from(cursor.into_inner()),
+                None => frame_system::Account::<T>::iter_keys(),
+            };
+            let epoch = CurrentEpoch::<T>::get();
+            let (mut examined, mut moved, mut last) = (0u32, 0u32, None);
+
+            for account in accounts.take(limit as usize) {
+                examined += 1;
+                if Self::get_account_shard(&account) == split.parent &&
+                    !PendingMigrations::<T>::contains_key(&account) &&
+                    Self::splits_off(&split, &account)
+                {
+                    Self::set_account_shard(&account, split.new_shard);
+                    Self::deposit_indexed_event(
+                        Event::AccountMigrated {
+                            account: account.clone(),
+                            from_shard: split.parent,
+                            to_shard: split.new_shard,
+                            epoch,
+                        },
+                        &[split.parent, split.new_shard],
+                        &[&account],
+                    );
+                    moved += 1;
+                }
+                last = Some(account);
+            }
+
+            split.examined = split.examined.saturating_add(examined);
+            split.moved = split.moved.saturating_add(moved);
+            let (parent, new_shard) = (split.parent, split.new_shard);
+            if examined < limit {
+                ActiveShardSplit::<T>::kill();
+                Self::deposit_indexed_event(
+                    Event::ShardSplitCompleted { parent, new_shard, examined: split.examined, moved: split.moved },
+                    &[parent, new_shard],
+                    &[],
+                );
+            } else {
+                split.cursor = last.map(Self::account_key_cursor);
+                Self::deposit_indexed_event(
+                    Event::ShardSplitProgress { parent, new_shard, examined: split.examined, moved: split.moved },
+                    &[parent, new_shard],
+                    &[],
+                );
+                ActiveShardSplit::<T>::put(split);
+            }
+
+            T::DbWeight::get().reads_writes(2 * u64::from(examined) + 2 * u64::from(moved) + 2, 3 * u64::from(moved) + 1)
+        }
+
+        /// Examine the next `MaxSplitAccountsPerBlock` accounts of the active shard mapping
+        /// upgrade, pinning those the new mapping would move, and complete it once every
+        /// account was examined
+        fn advance_shard_mapping_upgrade() -> Weight {
+            let Some(mut upgrade) = ActiveShardMappingUpgrade::<T>::get() else {
+                return T::DbWeight::get().reads(1);
+            };
+
+            let limit = T::MaxSplitAccountsPerBlock::get();
+            let accounts = match upgrade.cursor.take() {
+                Some(cursor) => frame_system::Account::<T>::iter_keys_from(cursor.into_inner()),
+                None => frame_system::Account::<T>::iter_keys(),
+            };
+            let (mut examined, mut pinned, mut last) = (0u32, 0u32, None);
+
+            for account in accounts.take(limit as usize) {
+                examined += 1;
+                let encoded = account.encode();
+                let shard = netchain_primitives::shard_for(upgrade.from, &encoded);
+                if !AccountToShard::<T>::contains_key(&account) &&
+                    netchain_primitives::shard_for(upgrade.to, &encoded) != shard
+                {
+                    Self::set_account_shard(&account, shard);
+                    pinned += 1;
+                }
+                last = Some(account);
+            }
+
+            upgrade.examined = upgrade.examined.saturating_add(examined);
+            upgrade.pinned = upgrade.pinned.saturating_add(pinned);
+            if examined < limit {
+                ActiveShardMappingUpgrade::<T>::kill();
+                CurrentShardMapping::<T>::put(upgrade.to);
+                Self::deposit_event(Event::ShardMappingUpgraded {
+                    from: upgrade.from,
+                    to: upgrade.to,
+                    examined: upgrade.examined,
+                    pinned: upgrade.pinned,
+                });
+                log::info!(
+                    target: LOG_TARGET,
+                    "shard mapping upgraded from version {} to {}, {} of {} accounts pinned",
+                    upgrade.from,
+                    upgrade.to,
+                    upgrade.pinned,
+                    upgrade.examined,
+                );
+            } else {
+                upgrade.cursor = last.map(Self::account_key_cursor);
+                ActiveShardMappingUpgrade::<T>::put(upgrade);
+            }
+
+            T::DbWeight::get().reads_writes(2 * u64::from(examined) + 2 * u64::from(pinned) + 1, 2 * u64::from(pinned) + 2)
+        }
+
+        /// Start the next shard epoch
+        ///
+        /// Applies the moves announced for it and ends the grace window of those applied at
+        /// the start of the previous epoch.
+        fn start_epoch() -> Weight {
+            let epoch = CurrentEpoch::<T>::mutate(|epoch| {
+                *epoch = epoch.saturating_add(1);
+                *epoch
+            });
+            let migrations: Vec<_> = PendingMigrations::<T>::iter().collect();
+            let mut migrated = 0u32;
+            let mut writes = 1u64;
+
+            for (account, migration) in &migrations {
+                if migration.effective_epoch == epoch {
+                    Self::set_account_shard(account, migration.to_shard);
+                    Self::deposit_indexed_event(
+                        Event::AccountMigrated {
+                            account: account.clone(),
+                            from_shard: migration.from_shard,
+                            to_shard: migration.to_shard,
+                            epoch,
+                        },
+                        &[migration.from_shard, migration.to_shard],
+                        &[account],
+                    );
+                    migrated += 1;
+                    writes += 3;
+                } else if migration.effective_epoch < epoch {
+                    PendingMigrations::<T>::remove(account);
+                    writes += 2;
+                }
+            }
+
+            Self::deposit_event(Event::ShardEpochStarted { epoch, migrated });
+            T::DbWeight::get().reads_writes(migrations.len() as u64 + 2 * u64::from(migrated) + 2, writes)
+        }
+
+        /// Assign account to shard based on hash
+        pub fn assign_account_to_shard(account: &T::AccountId) {
             let shard_id = Self::get_account_shard(account);
+            Self::set_account_shard(account, shard_id);
+        }
+
+        /// Assign `account` to `shard_id` in `AccountToShard`, moving its digest between the
+        /// shards' accounts digests
+        pub(crate) fn set_account_shard(account: &T::AccountId, shard_id: ShardId) {
+            let digest = account_digest(account);
+            if let Ok(previous) = AccountToShard::<T>::try_get(account) {
+                ShardAccountsDigests::<T>::mutate(previous, |sum| *sum = sum.overflowing_sub(digest).0);
+            }
+            ShardAccountsDigests::<T>::mutate(shard_id, |sum| *sum = sum.overflowing_add(digest).0);
             AccountToShard::<T>::insert(account, shard_id);
         }
 
-        /// Get current network TPS
-        pub fn current_network_tps() -> u32 {
-            Metrics::<T>::get().current_tps
-        }
+        /// Transfers queued on `shard_id`, in queue order and the latest layout
+        pub fn cross_shard_queue(shard_id: ShardId) -> Vec<CrossShardTx<T::AccountId, BalanceOf<T>>> {
+            CrossShardQueue::<T>::get(shard_id).into_iter().map(VersionedCrossShardTx::into_latest).collect()
+        }
+
+        /// Get current network TPS
+        pub fn current_network_tps() -> u32 {
+            Metrics::<T>::get().current_tps
+        }
+
+        /// Get shard capacity utilization
+        pub fn shard_utilization(shard_id: ShardId) -> Option<u8> {
+            ShardInfos::<T>::get(shard_id).map(|info| {
+                let current_load = ShardProcessingState::<T>::get(shard_id);
+                ((current_load * 100) / info.capacity.max(1)) as u8
+            })
+        }
+
+        /// Compute the state commitment root of every shard and store it.
+        ///
+        /// The root hashes the shard's accounts digest with the ordered trie root of its
+        /// cross-shard queue; see [`shard_state_root`]. The work is independent of the number
+        /// of accounts and is weighed in `on_initialize`.
+        pub fn commit_shard_state_roots() {
+            for shard_id in 0..SHARD_COUNT {
+                let root = shard_state_root::<T::Hashing, _, _>(
+                    ShardAccountsDigests::<T>::get(shard_id),
+                    &Self::cross_shard_queue(shard_id),
+                );
+                ShardStateRoots::<T>::insert(shard_id, root);
+            }
+        }
+
+        /// Accounts and queue of `shard_id` committed to by its state root, for validators
+        /// syncing a single shard
+        pub fn shard_snapshot(shard_id: ShardId) -> ShardSnapshot<T::AccountId, BalanceOf<T>> {
+            ShardSnapshot {
+                accounts: AccountToShard::<T>::iter()
+                    .filter(|(_, shard)| *shard == shard_id)
+                    .map(|(account, _)| account)
+                    .collect(),
+                queue: Self::cross_shard_queue(shard_id),
+            }
+        }
+
+        /// Transfers settled on `shard_id` in the current block and the head of its queue, as
+        /// many as fit in `MaxShardDataLen` bytes once encoded
+        pub fn shard_block_data(shard_id: ShardId) -> ShardBlockData<T::AccountId, BalanceOf<T>> {
+            // Both length prefixes are compact `u32`s of at most five bytes
+            let mut budget = (T::MaxShardDataLen::get() as usize).saturating_sub(10);
+            let mut fits = |tx: &CrossShardTx<T::AccountId, BalanceOf<T>>| {
+                let len = tx.encoded_size();
+                let fits = len <= budget;
+                if fits {
+                    budget -= len;
+                }
+                fits
+            };
+
+            let settled: Vec<_> = SettlementsThisBlock::<T>::get(shard_id)
+                .into_iter()
+                .map(VersionedCrossShardTx::into_latest)
+                .take_while(&mut fits)
+                .collect();
+            let queue = Self::cross_shard_queue(shard_id).into_iter().take_while(&mut fits).collect();
+            ShardBlockData { queue, settled }
+        }
+
+        /// Erasure-code the block data of every shard and store the commitments to its chunks
+        ///
+        /// At most `MaxShardDataLen` bytes are encoded per shard, as weighed in `on_initialize`.
+        pub fn commit_shard_data() {
+            for shard_id in 0..SHARD_COUNT {
+                let data = Self::shard_block_data(shard_id).encode();
+                let chunks = availability::encode(&data);
+                ShardDataCommitments::<T>::insert(shard_id, DataCommitment {
+                    root: availability::chunks_root::<T::Hashing>(&chunks),
+                    data_len: data.len() as u32,
+                });
+            }
+        }
+
+        /// Chunk `index` of the erasure-coded block data of `shard_id` with its Merkle proof
+        ///
+        /// Recomputed from the current state, so at a block's state it is the chunk the
+        /// block's `ShardDataCommitments` entry commits to.
+        pub fn shard_data_chunk(shard_id: ShardId, index: u16) -> Option<DataChunk<T::Hash>> {
+            if shard_id >= SHARD_COUNT {
+                return None;
+            }
+            let chunks = availability::encode(&Self::shard_block_data(shard_id).encode());
+            availability::chunk_with_proof::<T::Hashing>(&chunks, index)
+        }
+
+        /// Get the state commitment roots of all shards
+        pub fn shard_state_roots() -> Vec<(ShardId, T::Hash)> {
+            (0..SHARD_COUNT)
+                .filter_map(|shard_id| {
+                    ShardStateRoots::<T>::get(shard_id).map(|root| (shard_id, root))
+                })
+                .collect()
+        }
+
+        /// Check if parallel processing is available
+        #[cfg(feature = "std")]
+        pub fn parallel_processing_available() -> bool {
+            // Check if tokio or rayon is available
+            true
+        }
+
+        /// Process transactions in parallel (off-chain worker or external service)
+        #[cfg(feature = "std")]
+        pub async fn process_parallel_batch(
+            transactions: Vec<T::Hash>,
+            shard_id: ShardId,
+        ) -> Result<u32, DispatchError> {
+            // This would be implemented in an off-chain worker or external service
+            // using tokio for async processing
+            
+            use tokio::task;
+            
+            let batch_size = transactions.len();
+            let mut handles = Vec::new();
+            
+            // Process transactions in parallel
+            for chunk in transactions.chunks(BATCH_SIZE as usize) {
+                let chunk = chunk.to_vec();
+                let handle = task::spawn(async move {
+                    // Validate and process each transaction
+                    chunk.len() as u32
+                });
+                handles.push(handle);
+            }
+            
+            let mut total_processed = 0u32;
+            for handle in handles {
+                match handle.await {
+                    Ok(processed) => total_processed = total_processed.saturating_add(processed),
+                    Err(_) => return Err(Error::<T>::ParallelProcessingError.into()),
+                }
+            }
+            
+            Ok(total_processed)
+        }
+    }
+}
+
+/// Runtime API for external services
+pub mod runtime_api {
+    use super::*;
+
+    sp_api::decl_runtime_apis! {
+        /// API for high-performance operations
+        ///
+        /// Version 2 adds the tip to `dry_run_cross_shard`.
+        #[api_version(2)]
+        pub trait ShardingApi<AccountId, Balance, Hash> where
+            AccountId: codec::Codec,
+            Balance: codec::Codec,
+            Hash: codec::Codec,
+        {
+            /// Get current network TPS
+            fn current_tps() -> u32;
+            
+            /// Get shard information
+            fn shard_info(shard_id: ShardId) -> Option<ShardInfoOf<AccountId, Balance>>;
+            
+            /// Get account's shard
+            fn account_shard(account: AccountId) -> ShardId;
+            
+            /// Get performance metrics
+            fn performance_metrics() -> PerformanceMetrics;
+            
+            /// Check parallel processing capacity
+            fn parallel_capacity() -> u32;
+
+            /// Get the latest state commitment root of each shard
+            fn shard_state_roots() -> Vec<(ShardId, Hash)>;
+
+            /// Get the accounts and queue of a shard, the leaves of its state commitment root
+            fn shard_snapshot(shard_id: ShardId) -> ShardSnapshot<AccountId, Balance>;
+
+            /// Get the receipt of a forwarded cross-shard contract call, once executed
+            fn cross_shard_call_receipt(call_id: CrossShardCallId) -> Option<(bool, Vec<u8>)>;
+
+            /// Simulate a cross-shard transfer: its fee, place in the destination queue and
+            /// projected settlement delay, or the error submitting it would fail with
+            #[changed_in(2)]
+            fn dry_run_cross_shard(
+                sender: AccountId,
+                to_shard: ShardId,
+                recipient: AccountId,
+                amount: Balance,
+            ) -> Result<CrossShardDryRun<Balance>, DispatchError>;
+
+            /// Simulate a cross-shard transfer with `tip`: its fee, place in the destination
+            /// queue and projected settlement delay, or the error submitting it would fail with
+            fn dry_run_cross_shard(
+                sender: AccountId,
+                to_shard: ShardId,
+                recipient: AccountId,
+                amount: Balance,
+                tip: Balance,
+            ) -> Result<CrossShardDryRun<Balance>, DispatchError>;
+
+            /// Balance of the fee pot paying settlement rewards
+            fn pallet_pot_balance() -> Balance;
+
+            /// Get the commitment to the erasure-coded block data of a shard
+            fn shard_data_commitment(shard_id: ShardId) -> Option<DataCommitment<Hash>>;
+
+            /// Get a chunk of the erasure-coded block data of a shard with its Merkle proof
+            fn shard_data_chunk(shard_id: ShardId, index: u16) -> Option<DataChunk<Hash>>;
+
+            /// Get the blocks the oldest transfer queued on a shard has waited, if any is queued
+            fn oldest_queue_age(shard_id: ShardId) -> Option<u32>;
+
+            /// Get the shard a transaction of `account` making the SCALE-encoded `call` must be
+            /// tagged with, or `None` if `call` does not decode
+            fn transaction_shard(account: AccountId, call: Vec<u8>) -> Option<ShardId>;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as pallet_sharding;
+    use frame_support::{
+        assert_ok, assert_noop, derive_impl, parameter_types,
+        dispatch::GetDispatchInfo,
+        traits::{ConstU32, ConstU64, OnFinalize, OnInitialize},
+    };
+    use sp_core::H256;
+    use sp_runtime::{BuildStorage, DispatchError};
+
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test
+        {
+            System: frame_system,
+            Balances: pallet_balances,
+            Sharding: pallet_sharding,
+        }
+    );
+
+    #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+    impl frame_system::Config for Test {
+        type Block = Block;
+        type AccountData = pallet_balances::AccountData<u64>;
+    }
+
+    #[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+    impl pallet_balances::Config for Test {
+        type AccountStore = System;
+        type ExistentialDeposit = ConstU64<5>;
+    }
+
+    parameter_types! {
+        pub const ShardingPalletId: PalletId = PalletId(*b"netshrd!");
+        pub const FailurePenalty: Perbill = Perbill::from_percent(10);
+        pub static QuotedFee: Option<u64> = None;
+        pub static CrossShardPaused: bool = false;
+        pub static SettlementReward: u64 = 0;
+        pub MaxCrossShardCallGas: Weight = Weight::from_parts(1_000_000, 10_000);
+        pub static MaxShardDataLen: u32 = 4096;
+        pub static MaxCrossShardQueueLen: u32 = 16;
+        pub static MaxCrossShardCallQueueLen: u32 = 8;
+        pub static MaxCrossShardCallOutputLen: u32 = 64;
+        pub static MaxExpiringPerBlock: u32 = 16;
+    }
+
+    /// Pauses cross-shard transfers while the test sets `CrossShardPaused`
+    pub struct TestSafeguard;
+    impl PauseGuard for TestSafeguard {
+        fn is_paused(subsystem: Subsystem) -> bool {
+            subsystem == Subsystem::CrossShard && CrossShardPaused::get()
+        }
+    }
+
+    /// Quotes whatever the test put in `QuotedFee`
+    pub struct TestFeeQuote;
+    impl CrossShardFeeQuote<u64> for TestFeeQuote {
+        fn quote() -> Option<u64> {
+            QuotedFee::get()
+        }
+    }
+
+    impl Config for Test {
+        type RuntimeEvent = RuntimeEvent;
+        type RuntimeHoldReason = RuntimeHoldReason;
+        type Currency = Balances;
+        type MaxValidatorsPerShard = ConstU32<2>;
+        type TargetTpsPerShard = ConstU32<4>;
+        type CrossShardFee = ConstU64<10>;
+        type PalletId = ShardingPalletId;
+        type CallExecutor = ();
+        type MaxCrossShardCallGas = MaxCrossShardCallGas;
+        type CrossShardCallReceiptLifetime = ConstU64<5>;
+        type MaxCrossShardCallOutputLen = MaxCrossShardCallOutputLen;
+        type CrossShardTxRecordLifetime = ConstU64<10>;
+        type FailurePenalty = FailurePenalty;
+        type DeadLetterTimeout = ConstU64<5>;
+        type MaxExpiringPerBlock = MaxExpiringPerBlock;
+        type FeeQuote = TestFeeQuote;
+        type SurgeFee = CapacityMultipleSurgeFee<ConstU32<2>>;
+        type History = ();
+        type Safeguard = TestSafeguard;
+        type SessionKeys = ();
+        type MaxSettlementsPerBlock = ConstU32<2>;
+        type MaxQueueLatency = ConstU64<3>;
+        type SettlementReward = SettlementReward;
+        type MaxSettlementRewardPerBlock = ConstU64<3>;
+        type EpochLength = ConstU64<10>;
+        type MaxMigrationsPerEpoch = ConstU32<2>;
+        type MaxSplitAccountsPerBlock = ConstU32<16>;
+        type MaxShardDataLen = MaxShardDataLen;
+        type MaxCrossShardQueueLen = MaxCrossShardQueueLen;
+        type MaxCrossShardCallQueueLen = MaxCrossShardCallQueueLen;
+        type SystemCalls = Remarks;
+        type WeightInfo = ();
+    }
+
+    /// Remarks stand in for system calls
+    pub struct Remarks;
+    impl Contains<RuntimeCall> for Remarks {
+        fn contains(call: &RuntimeCall) -> bool {
+            matches!(call, RuntimeCall::System(frame_system::Call::remark { .. }))
+        }
+    }
+
+    const FEE: u64 = 10;
+
+    /// Balance held on `who` for queued cross-shard transfers
+    fn held(who: u64) -> u64 {
+        Balances::balance_on_hold(&HoldReason::CrossShardEscrow.into(), &who)
+    }
+
+    pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
+        let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        let balances = (1..=64).map(|account| (account, 1_000)).collect();
+        pallet_balances::GenesisConfig::<Test> { balances, ..Default::default() }
+            .assimilate_storage(&mut t)
+            .unwrap();
+
+        let mut ext: sp_io::TestExternalities = t.into();
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    /// Create the four shards with one validator each
+    fn init_shards() {
+        assert_ok!(Sharding::initialize_sharding(
+            RuntimeOrigin::root(),
+            (0..SHARD_COUNT as u64).map(|shard| vec![100 + shard]).collect(),
+        ));
+    }
+
+    /// The `nth` funded account that lives on `shard`
+    fn account_on(shard: ShardId, nth: usize) -> u64 {
+        (1..=64).filter(|account| Sharding::get_account_shard(account) == shard).nth(nth).unwrap()
+    }
+
+    /// Queue a transfer of `amount` from an account of shard 0 to `recipient` on shard 1
+    fn transfer_to_shard_1(nth_sender: usize, recipient: u64, amount: u64) -> H256 {
+        assert_ok!(Sharding::execute_cross_shard_tx(
+            RuntimeOrigin::signed(account_on(0, nth_sender)),
+            1,
+            recipient,
+            amount,
+        ));
+        BlakeTwo256::hash_of(Sharding::cross_shard_queue(1).last().unwrap())
+    }
+
+    fn next_block() {
+        let now = System::block_number();
+        Sharding::on_finalize(now);
+        System::set_block_number(now + 1);
+        Sharding::on_initialize(now + 1);
+    }
+
+    #[test]
+    fn sharding_initialization_works() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Sharding::initialize_sharding(RuntimeOrigin::signed(1), vec![vec![1]]),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                Sharding::initialize_sharding(RuntimeOrigin::root(), vec![vec![]; SHARD_COUNT as usize + 1]),
+                Error::<Test>::InvalidShardConfig
+            );
+
+            init_shards();
+
+            for shard in 0..SHARD_COUNT {
+                let info = Sharding::shard_info(shard).unwrap();
+                assert_eq!(info.validators, vec![100 + shard as u64]);
+                assert_eq!(info.capacity, 4);
+                assert!(Sharding::cross_shard_queue(shard).is_empty());
+                System::assert_has_event(
+                    Event::ShardCreated { shard_id: shard, validators: vec![100 + shard as u64] }.into(),
+                );
+            }
+
+            // Validators join up to the per-shard limit
+            assert_ok!(Sharding::join_shard(RuntimeOrigin::signed(1), 0));
+            assert_noop!(Sharding::join_shard(RuntimeOrigin::signed(2), 0), Error::<Test>::ShardAtCapacity);
+            assert_noop!(Sharding::join_shard(RuntimeOrigin::signed(2), SHARD_COUNT), Error::<Test>::ShardNotFound);
+        });
+    }
+
+    #[test]
+    fn genesis_creates_configured_shards() {
+        let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        pallet_sharding::GenesisConfig::<Test> {
+            shard_validators: (0..SHARD_COUNT as u64).map(|shard| vec![100 + shard]).collect(),
+        }
+        .assimilate_storage(&mut t)
+        .unwrap();
+
+        sp_io::TestExternalities::from(t).execute_with(|| {
+            for shard in 0..SHARD_COUNT {
+                let info = Sharding::shard_info(shard).unwrap();
+                assert_eq!(info.validators, vec![100 + shard as u64]);
+                assert_eq!(info.capacity, 4);
+            }
+            // New chains start on the current shard mapping
+            assert_eq!(Sharding::shard_mapping_version(), SHARD_MAPPING_VERSION);
+        });
+
+        // Sharding stays uninitialized unless configured
+        new_test_ext().execute_with(|| assert!(Sharding::shard_info(0).is_none()));
+    }
+
+    #[test]
+    fn cross_shard_transactions_work() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let sender = account_on(0, 0);
+            let recipient = account_on(1, 0);
+
+            assert_noop!(
+                Sharding::execute_cross_shard_tx(RuntimeOrigin::signed(sender), 0, recipient, 100),
+                Error::<Test>::InvalidCrossShardTx
+            );
+
+            let tx_hash = transfer_to_shard_1(0, recipient, 100);
+
+            // The fee and amount are held on the sender
+            assert_eq!(Balances::free_balance(sender), 1_000 - 100 - FEE);
+            assert_eq!(held(sender), 100 + FEE);
+            let issuance = Balances::total_issuance();
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::Queued));
+            assert_eq!(Sharding::shard_state(1), 1);
+
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+
+            // The amount reaches the recipient and the fee the fee pot
+            assert_eq!(Balances::free_balance(recipient), 1_100);
+            assert_eq!(held(sender), 0);
+            assert_eq!(Balances::free_balance(sender), 1_000 - 100 - FEE);
+            assert_eq!(Balances::free_balance(Sharding::fee_pot_account()), FEE);
+            assert_eq!(Balances::total_issuance(), issuance);
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::Settled));
+            assert!(Sharding::cross_shard_queue(1).is_empty());
+            assert_eq!(Sharding::shard_state(1), 0);
+            System::assert_has_event(Event::CrossShardSettled { tx_hash, to_shard: 1 }.into());
+        });
+    }
+
+    #[test]
+    fn only_delivered_transfers_have_receipts() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            let delivered = transfer_to_shard_1(0, recipient, 100);
+            let dead_lettered = transfer_to_shard_1(1, 1_000, 3);
+            assert!(Sharding::cross_shard_tx_receipt(delivered).is_none());
+
+            System::set_block_number(3);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+
+            assert_eq!(
+                Sharding::cross_shard_tx_receipt(delivered),
+                Some(CrossShardTxReceipt {
+                    sender: account_on(0, 0),
+                    recipient,
+                    amount: 100,
+                    from_shard: 0,
+                    to_shard: 1,
+                    settled_at: 3,
+                }),
+            );
+            assert!(Sharding::cross_shard_tx_receipt(dead_lettered).is_none());
+        });
+    }
+
+    #[test]
+    fn settled_and_failed_transfer_records_are_pruned_after_their_lifetime() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            let settled = transfer_to_shard_1(0, recipient, 100);
+            let failed = transfer_to_shard_1(1, recipient, 100);
+            let dead_lettered = transfer_to_shard_1(2, 1_000, 3);
+            // The hold of the second transfer falls short, so it is refunded
+            assert_ok!(Balances::release(
+                &HoldReason::CrossShardEscrow.into(),
+                &account_on(0, 1),
+                1,
+                Precision::Exact,
+            ));
+
+            System::set_block_number(3);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            next_block();
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            assert_eq!(Sharding::cross_shard_tx_status(settled), Some(CrossShardTxStatus::Settled));
+            assert!(matches!(Sharding::cross_shard_tx_status(failed), Some(CrossShardTxStatus::Failed(_))));
+            assert_eq!(Sharding::cross_shard_tx_status(dead_lettered), Some(CrossShardTxStatus::DeadLettered));
+
+            // Kept for `CrossShardTxRecordLifetime` blocks after settling or failing
+            Sharding::on_initialize(12);
+            assert!(Sharding::cross_shard_tx_status(settled).is_some());
+            assert!(Sharding::cross_shard_tx_receipt(settled).is_some());
+            Sharding::on_initialize(13);
+            assert!(Sharding::cross_shard_tx_status(settled).is_none());
+            assert!(Sharding::cross_shard_tx_receipt(settled).is_none());
+            assert!(Sharding::cross_shard_tx_status(failed).is_none());
+            assert!(CrossShardTxExpiries::<Test>::get(13).is_empty());
+
+            // A dead-lettered transfer is still pending, so its status stays
+            assert_eq!(Sharding::cross_shard_tx_status(dead_lettered), Some(CrossShardTxStatus::DeadLettered));
+        });
+    }
+
+    #[test]
+    fn settlement_rewards_are_paid_from_the_fee_pot_up_to_the_block_cap() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            SettlementReward::set(2);
+            let recipient = account_on(1, 0);
+            let processor = account_on(3, 0);
+
+            // Nothing delivered, nothing paid
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(processor), 1, 10));
+            assert_eq!(Sharding::settlement_reward_metrics(), SettlementRewardMetrics::default());
+
+            // Two deliveries earn 2 each, capped at 3 per block
+            transfer_to_shard_1(0, recipient, 100);
+            transfer_to_shard_1(1, recipient, 100);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(processor), 1, 10));
+            assert_eq!(Balances::free_balance(processor), 1_000 + 3);
+            assert_eq!(Balances::free_balance(Sharding::fee_pot_account()), 2 * FEE - 3);
+            assert_eq!(Sharding::rewards_paid_this_block(), 3);
+            System::assert_has_event(
+                Event::SettlementRewardPaid { who: processor, shard_id: 1, settled: 2, amount: 3 }.into(),
+            );
+
+            // The cap resets every block
+            next_block();
+            assert_eq!(Sharding::rewards_paid_this_block(), 0);
+            transfer_to_shard_1(2, recipient, 100);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(processor), 1, 10));
+            assert_eq!(Balances::free_balance(processor), 1_000 + 3 + 2);
+            assert_eq!(
+                Sharding::settlement_reward_metrics(),
+                SettlementRewardMetrics { payouts: 2, rewarded_settlements: 3, paid: 5 },
+            );
+        });
+    }
+
+    #[test]
+    fn queue_processing_charges_only_for_the_work_done() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+
+            // One transfer queued, two allowed: charged for one settlement
+            transfer_to_shard_1(0, recipient, 100);
+            let post_info = Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10).unwrap();
+            assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::process_cross_shard_queue(1, 1)));
+
+            // One call queued, ten requested: charged for one execution
+            assert_ok!(Sharding::execute_cross_shard_call(
+                RuntimeOrigin::signed(account_on(0, 1)),
+                1,
+                recipient,
+                Vec::new(),
+                Weight::zero(),
+                0,
+            ));
+            let post_info = Sharding::process_cross_shard_calls(RuntimeOrigin::signed(1), 1, 10).unwrap();
+            assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::process_cross_shard_calls(1)));
+            assert!(
+                <() as WeightInfo>::process_cross_shard_calls(1).all_lt(<() as WeightInfo>::process_cross_shard_calls(10))
+            );
+        });
+    }
+
+    #[test]
+    fn cross_shard_calls_are_bounded_and_their_receipts_pruned() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let caller = RuntimeOrigin::signed(account_on(0, 1));
+            let contract = account_on(1, 0);
+            let max_gas = MaxCrossShardCallGas::get();
+            let max_data = MaxCrossShardCallDataLen::get() as usize;
+
+            assert_noop!(
+                Sharding::execute_cross_shard_call(
+                    caller.clone(),
+                    1,
+                    contract,
+                    Vec::new(),
+                    max_gas.saturating_add(Weight::from_parts(1, 0)),
+                    0,
+                ),
+                Error::<Test>::GasLimitTooHigh
+            );
+            assert_noop!(
+                Sharding::execute_cross_shard_call(caller.clone(), 1, contract, vec![0; max_data + 1], max_gas, 0),
+                Error::<Test>::CallDataTooLarge
+            );
+            assert_ok!(Sharding::execute_cross_shard_call(caller, 1, contract, vec![0; max_data], max_gas, 0));
+
+            // Charged up front for the gas every requested call may use
+            let call = Call::<Test>::process_cross_shard_calls { shard_id: 1, max_calls: 2 };
+            assert_eq!(
+                call.get_dispatch_info().call_weight,
+                <() as WeightInfo>::process_cross_shard_calls(2).saturating_add(max_gas.saturating_mul(2))
+            );
+            assert_ok!(Sharding::process_cross_shard_calls(RuntimeOrigin::signed(1), 1, 2));
+            assert!(Sharding::cross_shard_call_receipt(0).is_some());
+
+            // Receipts are kept for `CrossShardCallReceiptLifetime` blocks
+            Sharding::on_initialize(5);
+            assert!(Sharding::cross_shard_call_receipt(0).is_some());
+            Sharding::on_initialize(6);
+            assert!(Sharding::cross_shard_call_receipt(0).is_none());
+            assert!(CrossShardCallReceiptExpiries::<Test>::get(6).is_empty());
+        });
+    }
+
+    #[test]
+    fn cross_shard_events_are_indexed_by_shard_and_account() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let sender = account_on(0, 0);
+            let recipient = account_on(1, 0);
+            let bystander = account_on(2, 0);
+            let indexed = |topic| frame_system::EventTopics::<Test>::get(topic).len();
+            let before = indexed(Sharding::shard_topic(1));
+
+            transfer_to_shard_1(0, recipient, 100);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+
+            // Queued, settled and batch-processed on shard 1; created, queued from and settled from shard 0
+            assert_eq!(indexed(Sharding::shard_topic(1)), before + 3);
+            assert_eq!(indexed(Sharding::shard_topic(0)), 3);
+            assert_eq!(indexed(Sharding::account_topic(&sender)), 2);
+            assert_eq!(indexed(Sharding::account_topic(&recipient)), 2);
+            assert_eq!(indexed(Sharding::account_topic(&bystander)), 0);
+
+            let (_, settled) = *frame_system::EventTopics::<Test>::get(Sharding::account_topic(&recipient))
+                .last()
+                .unwrap();
+            assert!(matches!(
+                System::events()[settled as usize].event,
+                RuntimeEvent::Sharding(Event::CrossShardSettled { to_shard: 1, .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn paused_cross_shard_rejects_new_transfers_but_settles_queued_ones() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            let tx_hash = transfer_to_shard_1(0, recipient, 100);
+
+            CrossShardPaused::set(true);
+            assert_noop!(
+                Sharding::execute_cross_shard_tx(RuntimeOrigin::signed(account_on(0, 1)), 1, recipient, 100),
+                Error::<Test>::SystemPaused
+            );
+            assert_eq!(
+                Sharding::dry_run_cross_shard(&account_on(0, 1), 1, &recipient, 100, 0),
+                Err(Error::<Test>::SystemPaused.into())
+            );
+            assert_noop!(
+                Sharding::execute_cross_shard_call(
+                    RuntimeOrigin::signed(account_on(0, 1)),
+                    1,
+                    recipient,
+                    Vec::new(),
+                    Weight::zero(),
+                    0,
+                ),
+                Error::<Test>::SystemPaused
+            );
+
+            // The transfer queued before the pause still settles
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::Settled));
+            assert_eq!(Balances::free_balance(recipient), 1_100);
+
+            CrossShardPaused::set(false);
+            transfer_to_shard_1(1, recipient, 100);
+        });
+    }
+
+    #[test]
+    fn lane_transfers_hash_apart_and_lanes_follow_the_shard_tag() {
+        use sp_runtime::{
+            traits::{TransactionExtension, TxBaseImplication},
+            transaction_validity::{InvalidTransaction, TransactionSource},
+        };
+
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let sender = account_on(0, 0);
+            let recipient = account_on(1, 0);
+
+            // Like lane transactions, these dispatches leave the account nonce alone, yet
+            // identical transfers still hash apart
+            let first = transfer_to_shard_1(0, recipient, 100);
+            let second = transfer_to_shard_1(0, recipient, 100);
+            assert_ne!(first, second);
+            assert_eq!(Sharding::next_cross_shard_tx_nonce(), 2);
+
+            let call = RuntimeCall::System(frame_system::Call::remark { remark: Vec::new() });
+            let validate = |shard| {
+                extension::CheckShardNonce::<Test>::lane(shard, 0).validate(
+                    RuntimeOrigin::signed(sender),
+                    &call,
+                    &call.get_dispatch_info(),
+                    0,
+                    (),
+                    &TxBaseImplication(()),
+                    TransactionSource::External,
+                )
+            };
+
+            // Only the lane of a shard the transaction may be tagged with is usable
+            assert!(validate(0).is_ok());
+            assert_eq!(
+                validate(1).err(),
+                Some(InvalidTransaction::Custom(extension::INVALID_NONCE_LANE).into())
+            );
+            assert_eq!(
+                validate(SHARD_COUNT).err(),
+                Some(InvalidTransaction::Custom(extension::INVALID_NONCE_LANE).into())
+            );
+        });
+    }
+
+    #[test]
+    fn undeliverable_transfer_is_refunded_after_timeout() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let sender = account_on(0, 0);
+
+            // A new account cannot receive less than the existential deposit
+            let tx_hash = transfer_to_shard_1(0, 1_000, 3);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::DeadLettered));
+            assert_eq!(held(sender), 3 + FEE);
+            System::assert_has_event(
+                Event::CrossShardDeadLettered { tx_hash, recipient: 1_000, amount: 3, expires_at: 6 }.into(),
+            );
+            let metrics = Sharding::dead_letter_metrics();
+            assert_eq!((metrics.dead_lettered, metrics.dead_lettered_volume), (1, 3));
+            assert_eq!((metrics.pending, metrics.pending_volume), (1, 3));
+
+            Sharding::on_initialize(5);
+            assert_eq!(held(sender), 3 + FEE);
+            Sharding::on_initialize(6);
+
+            let reason = FailureReason::RecipientRejected;
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::Failed(reason)));
+            assert_eq!(Sharding::dead_letter(tx_hash), None);
+            // The 10% penalty on 3 rounds down to nothing and the fee is released
+            assert_eq!(Balances::free_balance(sender), 1_000);
+            assert_eq!(held(sender), 0);
+            assert_eq!(Balances::free_balance(1_000), 0);
+            System::assert_has_event(
+                Event::CrossShardFailed { tx_hash, sender, refunded: 3 + FEE, reason }.into(),
+            );
+            let metrics = Sharding::dead_letter_metrics();
+            assert_eq!((metrics.dead_lettered, metrics.pending, metrics.pending_volume), (1, 0, 0));
+        });
+    }
+
+    #[test]
+    fn dead_lettered_transfers_can_be_claimed() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let sender = account_on(0, 0);
+            let other_sender = account_on(0, 1);
+            let delivered = transfer_to_shard_1(0, 1_000, 3);
+            let refunded = transfer_to_shard_1(1, 2_000, 3);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+
+            assert_noop!(
+                Sharding::claim_failed_transfer(RuntimeOrigin::signed(other_sender), delivered),
+                Error::<Test>::NotTransferParty,
+            );
+            assert_noop!(
+                Sharding::claim_failed_transfer(RuntimeOrigin::signed(1_000), delivered),
+                Error::<Test>::RecipientStillRejected,
+            );
+
+            // Once its account exists, the recipient takes delivery
+            assert_ok!(Balances::mint_into(&1_000, 10));
+            assert_ok!(Sharding::claim_failed_transfer(RuntimeOrigin::signed(1_000), delivered));
+            assert_eq!(Sharding::cross_shard_tx_status(delivered), Some(CrossShardTxStatus::Settled));
+            assert_eq!(Balances::free_balance(1_000), 13);
+            assert_eq!(held(sender), 0);
+            assert_eq!(Balances::free_balance(sender), 1_000 - 3 - FEE);
+
+            // The sender takes the refund without waiting for the timeout
+            assert_ok!(Sharding::claim_failed_transfer(RuntimeOrigin::signed(other_sender), refunded));
+            assert_eq!(Balances::free_balance(other_sender), 1_000);
+            assert_noop!(
+                Sharding::claim_failed_transfer(RuntimeOrigin::signed(other_sender), refunded),
+                Error::<Test>::DeadLetterNotFound,
+            );
+
+            // Nothing is left to refund on expiry
+            assert!(DeadLetterExpiries::<Test>::get(6).is_empty());
+            Sharding::on_initialize(6);
+            assert_eq!(Balances::free_balance(other_sender), 1_000);
+            let metrics = Sharding::dead_letter_metrics();
+            assert_eq!((metrics.dead_lettered, metrics.dead_lettered_volume, metrics.pending), (2, 6, 0));
+        });
+    }
+
+    #[test]
+    fn expiries_beyond_the_per_block_bound_move_to_the_next_block() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            MaxExpiringPerBlock::set(1);
+            let first = transfer_to_shard_1(0, 1_000, 3);
+            let second = transfer_to_shard_1(1, 2_000, 3);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+
+            assert_eq!(DeadLetterExpiries::<Test>::get(6), vec![first]);
+            assert_eq!(DeadLetterExpiries::<Test>::get(7), vec![second]);
+            assert_eq!(Sharding::dead_letter(second).unwrap().expires_at, 7);
+
+            Sharding::on_initialize(6);
+            assert!(Sharding::dead_letter(first).is_none());
+            assert!(Sharding::dead_letter(second).is_some());
+            Sharding::on_initialize(7);
+            assert!(Sharding::dead_letter(second).is_none());
+            assert_eq!(Sharding::dead_letter_metrics().pending, 0);
+        });
+    }
+
+    #[test]
+    fn migrating_to_v2_holds_queued_amounts_on_their_senders() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, UncheckedOnRuntimeUpgrade};
+
+            init_shards();
+            let sender = account_on(0, 0);
+            let broke = account_on(0, 1);
+            let recipient = account_on(1, 0);
+            let issuance = Balances::total_issuance();
+
+            // A version 1 queue: the fee was burnt and the amount left free on the sender
+            StorageVersion::new(1).put::<Sharding>();
+            let queued = |sender, amount| CrossShardTxV0 {
+                from_shard: 0,
+                to_shard: 1,
+                sender,
+                recipient,
+                amount,
+                nonce: 0,
+            };
+            migrations::v2::CrossShardQueue::<Test>::insert(1, vec![queued(sender, 100), queued(broke, 2_000)]);
+
+            migrations::v2::HoldQueuedTransfers::<Test>::on_runtime_upgrade();
+            assert_eq!(Sharding::on_chain_storage_version(), 2);
+            let queue = migrations::v3::CrossShardQueue::<Test>::get(1);
+            assert_eq!(queue, vec![queued(sender, 100).upgrade()]);
+            assert_eq!(queue[0].fee, 0);
+            assert_eq!(held(sender), 100);
+            assert_eq!(Balances::free_balance(sender), 900);
+            assert_eq!(Balances::total_issuance(), issuance);
+
+            // The sender who cannot cover the amount has the transfer dropped
+            assert_eq!(held(broke), 0);
+            assert_eq!(
+                Sharding::cross_shard_tx_status(BlakeTwo256::hash_of(&queued(broke, 2_000).upgrade())),
+                Some(CrossShardTxStatus::Failed(FailureReason::InsufficientFunds)),
+            );
+
+            migrations::v3::InnerAddCrossShardTips::<Test>::on_runtime_upgrade();
+            migrations::v6::InnerVersionStoredTransfers::<Test>::on_runtime_upgrade();
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            assert_eq!(held(sender), 0);
+            assert_eq!(Balances::free_balance(recipient), 1_100);
+        });
+    }
+
+    #[test]
+    fn highest_tips_settle_first_and_pay_the_processor() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            let processor = account_on(2, 0);
+            let tipper = |nth| account_on(0, nth);
+
+            transfer_to_shard_1(0, recipient, 50);
+            for (nth, tip) in [(1, 5), (2, 7), (3, 5)] {
+                assert_ok!(Sharding::execute_cross_shard_tx_with_tip(
+                    RuntimeOrigin::signed(tipper(nth)),
+                    1,
+                    recipient,
+                    50,
+                    tip,
+                ));
+            }
+            assert_eq!(held(tipper(2)), 50 + FEE + 7);
+
+            // Two settlements per block: the tip of 7, then the first tip of 5
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(processor), 1, 10));
+            assert_eq!(held(tipper(2)), 0);
+            assert_eq!(held(tipper(1)), 0);
+            assert_eq!(Balances::free_balance(processor), 1_000 + 7 + 5);
+            let queue = Sharding::cross_shard_queue(1);
+            assert_eq!(queue.iter().map(|tx| tx.sender).collect::<Vec<_>>(), vec![tipper(0), tipper(3)]);
+
+            // The remaining tip settles before the untipped transfer queued ahead of it
+            next_block();
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(processor), 1, 1));
+            assert_eq!(Sharding::cross_shard_queue(1)[0].sender, tipper(0));
+            assert_eq!(Balances::free_balance(processor), 1_000 + 7 + 5 + 5);
+        });
+    }
+
+    #[test]
+    fn queues_waiting_past_max_latency_settle_oldest_first() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            let processor = account_on(2, 0);
+            let sender = |nth| account_on(0, nth);
+            let process = |max| Sharding::process_cross_shard_queue(RuntimeOrigin::signed(processor), 1, max);
+            let class = |max| Call::<Test>::process_cross_shard_queue { shard_id: 1, max_transactions: max }
+                .get_dispatch_info()
+                .class;
+
+            transfer_to_shard_1(0, recipient, 50);
+            next_block();
+            for nth in [1, 2] {
+                assert_ok!(Sharding::execute_cross_shard_tx_with_tip(
+                    RuntimeOrigin::signed(sender(nth)),
+                    1,
+                    recipient,
+                    50,
+                    5,
+                ));
+            }
+            assert_eq!(Sharding::oldest_queue_age(1), Some(1));
+
+            // Within `MaxQueueLatency` of 3 blocks nothing changes
+            for _ in 0..2 {
+                next_block();
+            }
+            assert_eq!(Sharding::oldest_queue_age(1), Some(3));
+            assert_eq!(Sharding::is_latency_breached(1), None);
+            assert_eq!(class(2), DispatchClass::Normal);
+
+            next_block();
+            System::assert_has_event(Event::QueueLatencyBreached { shard_id: 1, age: 4 }.into());
+            assert_eq!(class(2), DispatchClass::Operational);
+
+            // The untipped transfer waiting longest settles ahead of the tips
+            assert_ok!(process(2));
+            let queue = Sharding::cross_shard_queue(1);
+            assert_eq!(queue.iter().map(|tx| tx.sender).collect::<Vec<_>>(), vec![sender(2)]);
+            assert_eq!(Sharding::oldest_queued_at(1), Some(2));
 
-        /// Get shard capacity utilization
-        pub fn shard_utilization(shard_id: ShardId) -> Option<u8> {
-            ShardInfos::<T>::get(shard_id).map(|info| {
-                let current_load = ShardProcessingState::<T>::get(shard_id);
-                ((current_load * 100) / info.capacity.max(1)) as u8
-            })
-        }
+            // The breach is reported once, and cleared once the queue is drained
+            next_block();
+            assert_eq!(Sharding::is_latency_breached(1), Some(()));
+            assert_ok!(process(2));
+            assert_eq!(Sharding::oldest_queue_age(1), None);
+            next_block();
+            System::assert_has_event(Event::QueueLatencyRecovered { shard_id: 1 }.into());
+            assert_eq!(class(2), DispatchClass::Normal);
+            assert_eq!(
+                System::events()
+                    .iter()
+                    .filter(|record| matches!(
+                        record.event,
+                        RuntimeEvent::Sharding(Event::QueueLatencyBreached { .. })
+                    ))
+                    .count(),
+                1
+            );
+        });
+    }
 
-        /// Check if parallel processing is available
-        #[cfg(feature = "std")]
-        pub fn parallel_processing_available() -> bool {
-            // Check if tokio or rayon is available
-            true
-        }
+    #[test]
+    fn tips_of_failed_and_claimed_transfers_go_back_to_the_sender() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let sender = account_on(0, 0);
+            let processor = account_on(2, 0);
 
-        /// Process transactions in parallel (off-chain worker or external service)
-        #[cfg(feature = "std")]
-        pub async fn process_parallel_batch(
-            transactions: Vec<T::Hash>,
-            shard_id: ShardId,
-        ) -> Result<u32, DispatchError> {
-            // This would be implemented in an off-chain worker or external service
-            // using tokio for async processing
-            
-            use tokio::task;
-            
-            let batch_size = transactions.len();
-            let mut handles = Vec::new();
-            
-            // Process transactions in parallel
-            for chunk in transactions.chunks(BATCH_SIZE as usize) {
-                let chunk = chunk.to_vec();
-                let handle = task::spawn(async move {
-                    // Validate and process each transaction
-                    chunk.len() as u32
-                });
-                handles.push(handle);
+            // The recipient does not exist and cannot receive 3: the transfer is dead-lettered
+            assert_ok!(Sharding::execute_cross_shard_tx_with_tip(
+                RuntimeOrigin::signed(sender),
+                1,
+                1_000,
+                3,
+                4,
+            ));
+            let tx_hash = BlakeTwo256::hash_of(Sharding::cross_shard_queue(1).last().unwrap());
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(processor), 1, 10));
+            assert_eq!(Balances::free_balance(processor), 1_000);
+            assert_eq!(held(sender), 3 + FEE + 4);
+
+            assert_ok!(Sharding::claim_failed_transfer(RuntimeOrigin::signed(sender), tx_hash));
+            assert_eq!(held(sender), 0);
+            let reason = FailureReason::RecipientRejected;
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::Failed(reason)));
+        });
+    }
+
+    #[test]
+    fn migrating_to_v3_gives_queued_and_dead_lettered_transfers_a_zero_tip() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+            use migrations::v3::{OldCrossShardTx, OldDeadLetter};
+
+            let old = |nonce| OldCrossShardTx {
+                from_shard: 0,
+                to_shard: 1,
+                sender: 2,
+                recipient: 3,
+                amount: 10,
+                nonce,
+                fee: FEE,
+            };
+            let (queued, dead) = (old(0), old(1));
+            let (queued_hash, dead_hash) = (BlakeTwo256::hash_of(&queued), BlakeTwo256::hash_of(&dead));
+            StorageVersion::new(2).put::<Sharding>();
+            migrations::v3::CrossShardQueue::<Test>::insert(1, vec![queued.clone()]);
+            migrations::v3::DeadLetters::<Test>::insert(dead_hash, OldDeadLetter { tx: dead.clone(), expires_at: 9 });
+            migrations::v8::DeadLetterExpiries::<Test>::insert(9, vec![dead_hash]);
+            CrossShardTxStatuses::<Test>::insert(queued_hash, CrossShardTxStatus::Queued);
+            CrossShardTxStatuses::<Test>::insert(dead_hash, CrossShardTxStatus::DeadLettered);
+
+            migrations::v3::AddCrossShardTips::<Test>::on_runtime_upgrade();
+            assert_eq!(Sharding::on_chain_storage_version(), 3);
+
+            let queue = migrations::v6::CrossShardQueue::<Test>::get(1);
+            assert_eq!(queue.len(), 1);
+            assert_eq!(queue[0].tip, 0);
+            let queued_hash = BlakeTwo256::hash_of(&queue[0]);
+            assert_eq!(Sharding::cross_shard_tx_status(queued_hash), Some(CrossShardTxStatus::Queued));
+
+            let (dead_hash, dead_letter) = migrations::v6::DeadLetters::<Test>::iter().next().unwrap();
+            assert_eq!(dead_hash, BlakeTwo256::hash_of(&dead_letter.tx));
+            assert_eq!(dead_letter.tx.nonce, 1);
+            assert_eq!(DeadLetterExpiries::<Test>::get(9), vec![dead_hash]);
+            assert_eq!(Sharding::cross_shard_tx_status(dead_hash), Some(CrossShardTxStatus::DeadLettered));
+        });
+    }
+
+    #[test]
+    fn migrating_to_v6_versions_stored_transfers_and_refunds_what_overflows_the_queues() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+
+            init_shards();
+            let recipient = account_on(1, 0);
+            let first = transfer_to_shard_1(0, recipient, 100);
+            let second = transfer_to_shard_1(1, recipient, 100);
+            for _ in 0..2 {
+                assert_ok!(Sharding::execute_cross_shard_call(
+                    RuntimeOrigin::signed(account_on(0, 2)),
+                    1,
+                    recipient,
+                    Vec::new(),
+                    Weight::zero(),
+                    0,
+                ));
             }
-            
-            let mut total_processed = 0u32;
-            for handle in handles {
-                match handle.await {
-                    Ok(processed) => total_processed = total_processed.saturating_add(processed),
-                    Err(_) => return Err(Error::<T>::ParallelProcessingError.into()),
-                }
+            let calls = Sharding::cross_shard_call_queue(1).into_inner();
+
+            // Version 5 stored the transfers and calls unbounded, the transfers unversioned
+            StorageVersion::new(5).put::<Sharding>();
+            let queue = Sharding::cross_shard_queue(1);
+            migrations::v6::CrossShardQueue::<Test>::insert(1, queue.clone());
+            migrations::v6::CrossShardCallQueue::<Test>::insert(1, calls.clone());
+            let dead = CrossShardTx { nonce: 7, ..queue[0].clone() };
+            let dead_hash = BlakeTwo256::hash_of(&dead);
+            migrations::v6::DeadLetters::<Test>::insert(
+                dead_hash,
+                migrations::v6::OldDeadLetter { tx: dead.clone(), expires_at: 9 },
+            );
+            MaxCrossShardQueueLen::set(1);
+            MaxCrossShardCallQueueLen::set(1);
+
+            migrations::v6::VersionStoredTransfers::<Test>::on_runtime_upgrade();
+            assert_eq!(Sharding::on_chain_storage_version(), 6);
+
+            assert_eq!(Sharding::cross_shard_queue(1), vec![queue[0].clone()]);
+            assert_eq!(Sharding::cross_shard_tx_status(first), Some(CrossShardTxStatus::Queued));
+            let reason = FailureReason::LimitExceeded;
+            assert_eq!(Sharding::cross_shard_tx_status(second), Some(CrossShardTxStatus::Failed(reason)));
+            assert_eq!(held(account_on(0, 1)), 0);
+            assert_eq!(Sharding::performance_metrics().cross_shard_backlog, 1);
+
+            assert_eq!(Sharding::cross_shard_call_queue(1).into_inner(), vec![calls[0].clone()]);
+            let receipt = Sharding::cross_shard_call_receipt(calls[1].call_id).unwrap();
+            assert!(!receipt.success);
+            assert_eq!(receipt.output, DispatchError::from(Error::<Test>::CrossShardCallQueueFull).encode());
+
+            assert_eq!(Sharding::dead_letter(dead_hash).unwrap().tx.into_latest(), dead);
+        });
+    }
+
+    #[test]
+    fn migrating_to_v7_schedules_settled_and_failed_transfer_records_to_expire() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+
+            let hash = |n: u8| H256::repeat_byte(n);
+            CrossShardTxStatuses::<Test>::insert(hash(1), CrossShardTxStatus::Settled);
+            CrossShardTxStatuses::<Test>::insert(hash(2), CrossShardTxStatus::Failed(FailureReason::TimedOut));
+            CrossShardTxStatuses::<Test>::insert(hash(3), CrossShardTxStatus::Settled);
+            CrossShardTxStatuses::<Test>::insert(hash(4), CrossShardTxStatus::Queued);
+            CrossShardTxStatuses::<Test>::insert(hash(5), CrossShardTxStatus::DeadLettered);
+            StorageVersion::new(6).put::<Sharding>();
+
+            migrations::v7::ExpireCrossShardTxRecords::<Test>::on_runtime_upgrade();
+            assert_eq!(Sharding::on_chain_storage_version(), 7);
+
+            // Two records expire per block from block 1 + `CrossShardTxRecordLifetime`
+            let mut scheduled: Vec<_> = (11..=12).flat_map(CrossShardTxExpiries::<Test>::get).collect();
+            assert_eq!(CrossShardTxExpiries::<Test>::get(11).len(), 2);
+            scheduled.sort();
+            assert_eq!(scheduled, vec![hash(1), hash(2), hash(3)]);
+
+            for n in 11..=12 {
+                Sharding::on_initialize(n);
             }
-            
-            Ok(total_processed)
-        }
+            assert_eq!(CrossShardTxStatuses::<Test>::iter_keys().count(), 2);
+            assert_eq!(Sharding::cross_shard_tx_status(hash(4)), Some(CrossShardTxStatus::Queued));
+        });
     }
-}
 
-/// Weight functions for the pallet
-pub trait WeightInfo {
-    fn initialize_sharding() -> Weight;
-    fn join_shard() -> Weight;
-    fn execute_cross_shard() -> Weight;
-    fn process_cross_shard_queue() -> Weight;
-    fn update_metrics() -> Weight;
-    fn rebalance_shards() -> Weight;
-}
+    #[test]
+    fn migrating_to_v8_bounds_stored_vectors() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+            use migrations::v8;
+
+            init_shards();
+            MaxExpiringPerBlock::set(2);
+            let hash = |n: u8| H256::repeat_byte(n);
 
-/// Default weight implementation
-impl WeightInfo for () {
-    fn initialize_sharding() -> Weight {
-        Weight::from_parts(100_000_000, 10_000)
+            // Version 7 stored the validators, receipt outputs and expiries unbounded
+            StorageVersion::new(7).put::<Sharding>();
+            let info = Sharding::shard_info(0).unwrap().unbounded();
+            let validators = BoundedVec::truncate_from(vec![1, 2, 3]);
+            v8::ShardInfos::<Test>::insert(0, v8::OldShardInfo { validators, ..info });
+            v8::CrossShardCallReceipts::<Test>::insert(
+                0,
+                v8::OldCrossShardCallReceipt {
+                    success: true,
+                    output: BoundedVec::truncate_from(vec![7; 100]),
+                    gas_used: Weight::zero(),
+                    executed_at: 1,
+                },
+            );
+            let tx = CrossShardTx {
+                from_shard: 0,
+                to_shard: 1,
+                sender: 2,
+                recipient: 3,
+                amount: 10,
+                nonce: 0,
+                fee: FEE,
+                tip: 0,
+            };
+            DeadLetters::<Test>::insert(hash(3), DeadLetter { tx: tx.into(), expires_at: 9 });
+            v8::DeadLetterExpiries::<Test>::insert(9, vec![hash(1), hash(2), hash(3)]);
+            v8::DeadLetterExpiries::<Test>::insert(10, vec![hash(4), hash(5)]);
+            v8::CrossShardTxExpiries::<Test>::insert(11, vec![hash(1), hash(2), hash(3)]);
+
+            v8::BoundStoredVecs::<Test>::on_runtime_upgrade();
+            assert_eq!(Sharding::on_chain_storage_version(), 8);
+
+            assert_eq!(Sharding::shard_info(0).unwrap().validators, vec![1, 2]);
+            assert_eq!(Sharding::cross_shard_call_receipt(0).unwrap().output, vec![7; 64]);
+
+            // What does not fit a block expires at the next one with room
+            assert_eq!(DeadLetterExpiries::<Test>::get(9), vec![hash(1), hash(2)]);
+            assert_eq!(DeadLetterExpiries::<Test>::get(10), vec![hash(4), hash(5)]);
+            assert_eq!(DeadLetterExpiries::<Test>::get(11), vec![hash(3)]);
+            assert_eq!(Sharding::dead_letter(hash(3)).unwrap().expires_at, 11);
+            assert_eq!(CrossShardTxExpiries::<Test>::get(11), vec![hash(1), hash(2)]);
+            assert_eq!(CrossShardTxExpiries::<Test>::get(12), vec![hash(3)]);
+        });
     }
-    fn join_shard() -> Weight {
-        Weight::from_parts(50_000_000, 5_000)
+
+    #[test]
+    fn migrating_from_v1_to_latest_keeps_every_queued_transfer() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+
+            init_shards();
+            let recipient = account_on(1, 0);
+            let broke = account_on(0, 2);
+            let queued = |sender, amount| CrossShardTxV0 {
+                from_shard: 0,
+                to_shard: 1,
+                sender,
+                recipient,
+                amount,
+                nonce: 0,
+            };
+            let baseline = vec![queued(account_on(0, 0), 100), queued(account_on(0, 1), 50), queued(broke, 2_000)];
+            StorageVersion::new(1).put::<Sharding>();
+            migrations::v2::CrossShardQueue::<Test>::insert(1, baseline.clone());
+
+            #[cfg(feature = "try-runtime")]
+            let state = migrations::MigrateToLatest::<Test>::pre_upgrade().unwrap();
+            migrations::MigrateToLatest::<Test>::on_runtime_upgrade();
+            #[cfg(feature = "try-runtime")]
+            assert_ok!(migrations::MigrateToLatest::<Test>::post_upgrade(state));
+
+            assert_eq!(Sharding::on_chain_storage_version(), STORAGE_VERSION);
+            let expected: Vec<_> = baseline[..2].iter().map(|tx| tx.clone().upgrade().upgrade()).collect();
+            assert_eq!(Sharding::cross_shard_queue(1), expected);
+            assert_eq!(held(account_on(0, 0)) + held(account_on(0, 1)), 150);
+
+            // Dropped when holding its amount failed, with its status recorded under the hash of
+            // the layout it was dropped in
+            let dropped = baseline[2].clone().upgrade();
+            assert_eq!(held(broke), 0);
+            assert_eq!(
+                Sharding::cross_shard_tx_status(BlakeTwo256::hash_of(&dropped)),
+                Some(CrossShardTxStatus::Failed(FailureReason::InsufficientFunds)),
+            );
+        });
     }
-    fn execute_cross_shard() -> Weight {
-        Weight::from_parts(75_000_000, 7_500)
+
+    #[test]
+    fn full_cross_shard_queues_reject_transfers_and_calls() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            MaxCrossShardQueueLen::set(2);
+            MaxCrossShardCallQueueLen::set(1);
+
+            transfer_to_shard_1(0, recipient, 100);
+            transfer_to_shard_1(1, recipient, 100);
+            assert_noop!(
+                Sharding::execute_cross_shard_tx(RuntimeOrigin::signed(account_on(0, 2)), 1, recipient, 100),
+                Error::<Test>::CrossShardQueueFull
+            );
+            assert_eq!(
+                Sharding::dry_run_cross_shard(&account_on(0, 2), 1, &recipient, 100, 0),
+                Err(Error::<Test>::CrossShardQueueFull.into())
+            );
+
+            let call = || {
+                Sharding::execute_cross_shard_call(
+                    RuntimeOrigin::signed(account_on(0, 3)),
+                    1,
+                    recipient,
+                    Vec::new(),
+                    Weight::zero(),
+                    0,
+                )
+            };
+            assert_ok!(call());
+            assert_noop!(call(), Error::<Test>::CrossShardCallQueueFull);
+
+            // Processing makes room again
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            transfer_to_shard_1(2, recipient, 100);
+            assert_ok!(Sharding::process_cross_shard_calls(RuntimeOrigin::signed(1), 1, 10));
+            assert_ok!(call());
+        });
     }
-    fn process_cross_shard_queue() -> Weight {
-        Weight::from_parts(200_000_000, 20_000)
+
+    #[test]
+    fn versioned_cross_shard_transfers_decode_from_every_layout() {
+        let v1 = CrossShardTxV1::<u64, u64> {
+            from_shard: 0,
+            to_shard: 1,
+            sender: 2,
+            recipient: 3,
+            amount: 10,
+            nonce: 4,
+            fee: FEE,
+        };
+        let latest = CrossShardTx { tip: 5, ..v1.clone().upgrade() };
+        assert_eq!(v1.clone().upgrade().tip, 0);
+
+        // Each version is its bare encoding behind the index of its variant
+        let encoded_v1 = [&[1u8][..], &v1.encode()].concat();
+        let decoded = VersionedCrossShardTx::<u64, u64>::decode(&mut &encoded_v1[..]).unwrap();
+        assert_eq!(decoded, VersionedCrossShardTx::V1(v1));
+        assert_eq!(decoded.into_latest(), CrossShardTx { tip: 0, ..latest.clone() });
+
+        let v0 = CrossShardTxV0::<u64, u64> {
+            from_shard: 0,
+            to_shard: 1,
+            sender: 2,
+            recipient: 3,
+            amount: 10,
+            nonce: 4,
+        };
+        let encoded_v0 = [&[0u8][..], &v0.encode()].concat();
+        let decoded = VersionedCrossShardTx::<u64, u64>::decode(&mut &encoded_v0[..]).unwrap();
+        assert_eq!(decoded, VersionedCrossShardTx::V0(v0));
+        assert_eq!(decoded.into_latest(), CrossShardTx { fee: 0, tip: 0, ..latest.clone() });
+
+        let versioned = VersionedCrossShardTx::from(latest.clone());
+        assert_eq!(versioned.encode(), [&[2u8][..], &latest.encode()].concat());
+        assert_eq!(VersionedCrossShardTx::decode(&mut &versioned.encode()[..]).unwrap().into_latest(), latest);
+
+        // Two shards, two accounts and four `u64`s, plus the tip and the version index
+        assert_eq!(CrossShardTxV0::<u64, u64>::max_encoded_len(), 34);
+        assert_eq!(CrossShardTxV1::<u64, u64>::max_encoded_len(), 42);
+        assert_eq!(CrossShardTx::<u64, u64>::max_encoded_len(), 50);
+        assert_eq!(VersionedCrossShardTx::<u64, u64>::max_encoded_len(), 51);
     }
-    fn update_metrics() -> Weight {
-        Weight::from_parts(25_000_000, 2_500)
+
+    #[test]
+    fn migrating_to_v4_upgrades_the_shard_mapping_without_moving_accounts() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+            use netchain_primitives::shard_for;
+
+            // Moved by rebalancing before the upgrade
+            AccountToShard::<Test>::insert(1, 3);
+            let shards: Vec<ShardId> = (1..=64).map(|account| Sharding::get_account_shard(&account)).collect();
+            assert_eq!(Sharding::shard_mapping_version(), 0);
+            assert!((2..=64u64).all(|account| shards[account as usize - 1] == shard_for(0, &account.encode())));
+            let remapped =
+                (2..=64u64).filter(|account| shard_for(0, &account.encode()) != shard_for(1, &account.encode())).count();
+            assert!(remapped > 0);
+
+            StorageVersion::new(3).put::<Sharding>();
+            migrations::v4::UpgradeShardMapping::<Test>::on_runtime_upgrade();
+            assert_eq!(Sharding::on_chain_storage_version(), 4);
+            System::assert_last_event(Event::ShardMappingUpgradeStarted { from: 0, to: SHARD_MAPPING_VERSION }.into());
+            assert!(!Sharding::begin_shard_mapping_upgrade(SHARD_MAPPING_VERSION));
+
+            // 64 accounts, 16 examined per block; none changes shard on the way
+            for _ in 0..4 {
+                next_block();
+                assert!(Sharding::active_shard_mapping_upgrade().is_some());
+                assert!((1..=64).all(|account| Sharding::get_account_shard(&account) == shards[account as usize - 1]));
+            }
+            next_block();
+            assert_eq!(Sharding::active_shard_mapping_upgrade(), None);
+            assert_eq!(Sharding::shard_mapping_version(), SHARD_MAPPING_VERSION);
+            System::assert_last_event(
+                Event::ShardMappingUpgraded { from: 0, to: SHARD_MAPPING_VERSION, examined: 64, pinned: remapped as u32 }
+                    .into(),
+            );
+            assert!((1..=64).all(|account| Sharding::get_account_shard(&account) == shards[account as usize - 1]));
+
+            // Later accounts hash under the new mapping
+            assert_eq!(Sharding::get_account_shard(&65), shard_for(SHARD_MAPPING_VERSION, &65u64.encode()));
+            assert!(!Sharding::begin_shard_mapping_upgrade(SHARD_MAPPING_VERSION));
+        });
     }
-    fn rebalance_shards() -> Weight {
-        Weight::from_parts(150_000_000, 15_000)
+
+    #[test]
+    fn migrating_to_v5_sums_the_accounts_of_each_shard_into_its_digest() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+
+            // Assigned before digests were kept
+            for account in 1..=8u64 {
+                AccountToShard::<Test>::insert(account, (account % 2) as ShardId);
+            }
+            assert_eq!(Sharding::shard_accounts_digest(0), U256::zero());
+
+            StorageVersion::new(4).put::<Sharding>();
+            migrations::v5::InitAccountsDigests::<Test>::on_runtime_upgrade();
+            assert_eq!(Sharding::on_chain_storage_version(), 5);
+
+            for shard_id in 0..SHARD_COUNT {
+                let snapshot = Sharding::shard_snapshot(shard_id);
+                assert_eq!(snapshot.accounts.len(), if shard_id < 2 { 4 } else { 0 });
+                assert_eq!(Sharding::shard_accounts_digest(shard_id), snapshot.accounts_digest());
+            }
+        });
     }
-}
 
-/// Runtime API for external services
-#[cfg(feature = "std")]
-pub mod runtime_api {
-    use super::*;
-    use sp_runtime::traits::Block as BlockT;
-    
-    sp_api::decl_runtime_apis! {
-        /// API for high-performance operations
-        pub trait ShardingApi<AccountId, Balance> where
-            AccountId: codec::Codec,
-            Balance: codec::Codec,
-        {
-            /// Get current network TPS
-            fn current_tps() -> u32;
-            
-            /// Get shard information
-            fn shard_info(shard_id: ShardId) -> Option<ShardInfo<AccountId, Balance>>;
-            
-            /// Get account's shard
-            fn account_shard(account: AccountId) -> ShardId;
-            
-            /// Get performance metrics
-            fn performance_metrics() -> PerformanceMetrics;
-            
-            /// Check parallel processing capacity
-            fn parallel_capacity() -> u32;
-        }
+    #[test]
+    fn settlements_are_capped_per_block() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            for nth in 0..3 {
+                transfer_to_shard_1(nth, recipient, 50);
+            }
+            assert_eq!(Sharding::performance_metrics().cross_shard_backlog, 3);
+
+            // The weight only covers the settlements the cap allows, in a queue of any length
+            let call = Call::<Test>::process_cross_shard_queue { shard_id: 1, max_transactions: u32::MAX };
+            assert_eq!(
+                call.get_dispatch_info().call_weight,
+                <() as WeightInfo>::process_cross_shard_queue(2, MaxCrossShardQueueLen::get()),
+            );
+
+            // Charged for the whole queue read and rewritten, not only the settled part
+            let post_info = Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10).unwrap();
+            assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::process_cross_shard_queue(2, 3)));
+            assert_eq!(Sharding::cross_shard_queue(1).len(), 1);
+            assert_eq!(Sharding::settled_this_block(1), 2);
+            assert_eq!(Sharding::performance_metrics().cross_shard_backlog, 1);
+            System::assert_last_event(Event::SettlementsCarriedOver { shard_id: 1, carried_over: 1 }.into());
+
+            // The rest waits for the next block
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            assert_eq!(Sharding::cross_shard_queue(1).len(), 1);
+
+            next_block();
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            assert!(Sharding::cross_shard_queue(1).is_empty());
+            assert_eq!(Sharding::performance_metrics().cross_shard_backlog, 0);
+            assert_eq!(Balances::free_balance(recipient), 1_150);
+        });
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use frame_support::{
-        assert_ok, assert_noop,
-        traits::{OnFinalize, OnInitialize},
-        weights::Weight,
-    };
-    use sp_runtime::testing::H256;
+    #[test]
+    fn dry_run_predicts_fee_queue_position_and_delay() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let sender = account_on(0, 0);
+            let recipient = account_on(1, 0);
 
-    type Block = frame_system::mocking::MockBlock<Test>;
+            // Submission errors are reported as the call would report them
+            assert_eq!(
+                Sharding::dry_run_cross_shard(&sender, 0, &recipient, 100, 0),
+                Err(Error::<Test>::InvalidCrossShardTx.into())
+            );
+            assert_eq!(
+                Sharding::dry_run_cross_shard(&sender, 1, &recipient, 1_000 - FEE, 0),
+                Err(Error::<Test>::InsufficientBalance.into())
+            );
+            // The tip is held too
+            assert!(Sharding::dry_run_cross_shard(&sender, 1, &recipient, 900, 0).is_ok());
+            assert_eq!(
+                Sharding::dry_run_cross_shard(&sender, 1, &recipient, 900, 100),
+                Err(Error::<Test>::InsufficientBalance.into())
+            );
+            assert!(!Sharding::dry_run_cross_shard(&sender, 1, &1_000, 3, 0).unwrap().recipient_accepts);
 
-    frame_support::construct_runtime!(
-        pub enum Test
-        {
-            System: frame_system,
-            Balances: pallet_balances,
-            Sharding: pallet_sharding,
-        }
-    );
+            // Three transfers ahead and two settled per block: this one settles in the second block
+            for nth in 1..4 {
+                transfer_to_shard_1(nth, recipient, 50);
+            }
+            let dry_run = Sharding::dry_run_cross_shard(&sender, 1, &recipient, 100, 0).unwrap();
+            assert_eq!(dry_run, CrossShardDryRun {
+                from_shard: 0,
+                fee: FEE,
+                queue_position: 3,
+                settlement_blocks: 2,
+                recipient_accepts: true,
+            });
+            assert_eq!(Sharding::cross_shard_queue(1).len(), 3);
+
+            let tx_hash = transfer_to_shard_1(0, recipient, 100);
+            assert_eq!(Balances::free_balance(sender), 1_000 - 100 - dry_run.fee);
+
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::Queued));
+            next_block();
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 10));
+            assert_eq!(Sharding::cross_shard_tx_status(tx_hash), Some(CrossShardTxStatus::Settled));
+        });
+    }
 
     #[test]
-    fn sharding_initialization_works() {
-        // Test shard initialization with validators
+    fn overloaded_shards_charge_surge_fee() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            for nth in 0..4 {
+                transfer_to_shard_1(nth, recipient, 20);
+            }
+
+            // Load reached capacity: the fee doubles, and a third capacity multiple is rejected
+            System::assert_has_event(Event::ShardOverloaded { shard_id: 1, load: 4, capacity: 4 }.into());
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(2 * FEE));
+            let sender = account_on(0, 4);
+            transfer_to_shard_1(4, recipient, 20);
+            assert_eq!(Balances::free_balance(sender), 1_000 - 20 - 2 * FEE);
+
+            ShardProcessingState::<Test>::insert(1, 8);
+            assert_noop!(
+                Sharding::execute_cross_shard_tx(RuntimeOrigin::signed(sender), 1, recipient, 20),
+                Error::<Test>::ShardOverloaded
+            );
+
+            // Settling drops the load back below capacity
+            ShardProcessingState::<Test>::insert(1, 5);
+            assert_ok!(Sharding::process_cross_shard_queue(RuntimeOrigin::signed(1), 1, 2));
+            System::assert_has_event(Event::OverloadCleared { shard_id: 1, load: 3 }.into());
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(FEE));
+        });
     }
 
     #[test]
-    fn cross_shard_transactions_work() {
-        // Test cross-shard transaction execution
+    fn quoted_fees_are_clamped_to_governance_bounds() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+
+            // Quotes are ignored until governance sets bounds
+            QuotedFee::set(Some(40));
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(FEE));
+
+            assert_noop!(
+                Sharding::set_cross_shard_fee_bounds(RuntimeOrigin::signed(1), Some((5, 20))),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                Sharding::set_cross_shard_fee_bounds(RuntimeOrigin::root(), Some((20, 5))),
+                Error::<Test>::InvalidFeeBounds
+            );
+            assert_ok!(Sharding::set_cross_shard_fee_bounds(RuntimeOrigin::root(), Some((5, 20))));
+            System::assert_last_event(Event::CrossShardFeeBoundsSet { bounds: Some((5, 20)) }.into());
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(20));
+
+            QuotedFee::set(Some(1));
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(5));
+
+            QuotedFee::set(Some(12));
+            let sender = account_on(0, 0);
+            transfer_to_shard_1(0, account_on(1, 0), 20);
+            assert_eq!(Balances::free_balance(sender), 1_000 - 20 - 12);
+
+            // Surge pricing multiplies the quoted fee
+            ShardProcessingState::<Test>::insert(1, 4);
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(24));
+            ShardProcessingState::<Test>::insert(1, 0);
+
+            // Without a quote (e.g. a stale feed) the constant fee applies
+            QuotedFee::set(None);
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(FEE));
+
+            assert_ok!(Sharding::set_cross_shard_fee_bounds(RuntimeOrigin::root(), None));
+            QuotedFee::set(Some(12));
+            assert_eq!(Sharding::cross_shard_fee(1), Ok(FEE));
+        });
     }
 
     #[test]
     fn parallel_processing_metrics() {
-        // Test performance metrics calculation
+        new_test_ext().execute_with(|| {
+            init_shards();
+            transfer_to_shard_1(0, account_on(1, 0), 50);
+            assert_eq!(Sharding::performance_metrics().cross_shard_txs, 1);
+
+            // 8 TPS out of 4 shards of 4 TPS is 50% utilization
+            assert_ok!(Sharding::update_performance_metrics(RuntimeOrigin::signed(1), 1_000, 8, 3_000));
+
+            let metrics = Sharding::performance_metrics();
+            assert_eq!(metrics.total_transactions, 1_000);
+            assert_eq!(metrics.current_tps, 8);
+            assert_eq!(metrics.avg_block_time, 3_000);
+            assert_eq!(metrics.parallel_utilization, 50);
+            assert_eq!(Sharding::current_network_tps(), 8);
+            assert_eq!(Sharding::shard_utilization(1), Some(25));
+            System::assert_last_event(Event::MetricsUpdated { tps: 8, parallel_utilization: 50 }.into());
+        });
     }
 
     #[test]
     fn load_balancing_works() {
-        // Test automatic load balancing between shards
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            for nth in 0..3 {
+                transfer_to_shard_1(nth, recipient, 20);
+            }
+
+            assert_noop!(Sharding::rebalance_shards(RuntimeOrigin::signed(1)), DispatchError::BadOrigin);
+            assert_ok!(Sharding::rebalance_shards(RuntimeOrigin::root()));
+
+            // Shard 1 holds all the queued load; shard 0 is the first of the idle shards
+            assert!(System::events().iter().any(|record| matches!(
+                record.event,
+                RuntimeEvent::Sharding(Event::LoadBalanced { from_shard: 1, to_shard: 0, .. })
+            )));
+        });
+    }
+
+    #[test]
+    fn rebalanced_accounts_move_at_epoch_boundaries() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            let other = account_on(1, 1);
+            transfer_to_shard_1(0, recipient, 20);
+            transfer_to_shard_1(1, recipient, 20);
+            transfer_to_shard_1(2, other, 20);
+
+            // Announced for the next epoch; the assignment does not change yet
+            assert_ok!(Sharding::rebalance_shards(RuntimeOrigin::root()));
+            let migration = PendingMigration { from_shard: 1, to_shard: 0, effective_epoch: 1 };
+            assert_eq!(Sharding::pending_migration(recipient), Some(migration));
+            assert_eq!(Sharding::pending_migration(other), Some(migration));
+            System::assert_has_event(
+                Event::MigrationAnnounced { account: recipient, from_shard: 1, to_shard: 0, effective_epoch: 1 }.into(),
+            );
+            assert_eq!(Sharding::get_account_shard(&recipient), 1);
+            assert!(Sharding::accepts_shard_tag(&recipient, 0) && Sharding::accepts_shard_tag(&recipient, 1));
+            assert!(!Sharding::accepts_shard_tag(&recipient, 2));
+
+            // Slots are taken until the grace window of these moves ends
+            assert_ok!(Sharding::rebalance_shards(RuntimeOrigin::root()));
+            assert_eq!(PendingMigrations::<Test>::count(), 2);
+
+            Sharding::on_initialize(10);
+            assert_eq!(Sharding::current_epoch(), 1);
+            assert_eq!(Sharding::get_account_shard(&recipient), 0);
+            assert!(Sharding::accepts_shard_tag(&recipient, 1));
+            System::assert_has_event(Event::ShardEpochStarted { epoch: 1, migrated: 2 }.into());
+
+            // The grace window closes an epoch after the move
+            Sharding::on_initialize(20);
+            assert_eq!(Sharding::pending_migration(recipient), None);
+            assert_eq!(Sharding::get_account_shard(&recipient), 0);
+            assert!(!Sharding::accepts_shard_tag(&recipient, 1));
+        });
+    }
+
+    #[test]
+    fn system_calls_are_tagged_with_the_reserved_system_shard() {
+        new_test_ext().execute_with(|| {
+            let signer = account_on(1, 0);
+            let system_call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+            let user_call = RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive { dest: 2, value: 1 });
+
+            // Without a reservation every call goes to the signer's shard
+            for call in [&system_call, &user_call] {
+                assert_eq!(Sharding::transaction_shard(&signer, call), 1);
+                assert!(Sharding::accepts_transaction_tag(&signer, call, 1));
+                assert!(!Sharding::accepts_transaction_tag(&signer, call, SYSTEM_SHARD));
+            }
+
+            assert_noop!(Sharding::set_system_shard(RuntimeOrigin::signed(signer), Some(SYSTEM_SHARD)), DispatchError::BadOrigin);
+            assert_noop!(
+                Sharding::set_system_shard(RuntimeOrigin::root(), Some(SHARD_COUNT)),
+                Error::<Test>::InvalidShardConfig
+            );
+            assert_ok!(Sharding::set_system_shard(RuntimeOrigin::root(), Some(SYSTEM_SHARD)));
+            System::assert_last_event(Event::SystemShardSet { shard: Some(SYSTEM_SHARD) }.into());
+
+            // System calls move to the virtual shard, which other calls cannot use
+            assert_eq!(Sharding::transaction_shard(&signer, &system_call), SYSTEM_SHARD);
+            assert!(Sharding::accepts_transaction_tag(&signer, &system_call, SYSTEM_SHARD));
+            assert!(!Sharding::accepts_transaction_tag(&signer, &system_call, 1));
+            assert_eq!(Sharding::transaction_shard(&signer, &user_call), 1);
+            assert!(!Sharding::accepts_transaction_tag(&signer, &user_call, SYSTEM_SHARD));
+
+            // A regular shard is shared with the accounts living there
+            assert_ok!(Sharding::set_system_shard(RuntimeOrigin::root(), Some(3)));
+            assert!(Sharding::accepts_transaction_tag(&signer, &system_call, 3));
+            assert!(Sharding::accepts_transaction_tag(&account_on(3, 0), &user_call, 3));
+            assert!(!Sharding::accepts_transaction_tag(&signer, &user_call, 3));
+
+            assert_ok!(Sharding::set_system_shard(RuntimeOrigin::root(), None));
+            assert_eq!(Sharding::transaction_shard(&signer, &system_call), 1);
+        });
+    }
+
+    #[test]
+    fn shard_splits_move_half_of_the_parent_over_several_blocks() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let parent_accounts: Vec<u64> = (1..=64).filter(|account| Sharding::get_account_shard(account) == 0).collect();
+            let split = ShardSplit { parent: 0, new_shard: 3, cursor: None, examined: 0, moved: 0 };
+            let (leaving, staying): (Vec<u64>, Vec<u64>) =
+                parent_accounts.iter().partition(|account| Sharding::splits_off(&split, account));
+            assert!(!leaving.is_empty() && !staying.is_empty());
+
+            assert_noop!(Sharding::begin_shard_split(RuntimeOrigin::signed(1), 0, 3), DispatchError::BadOrigin);
+            assert_noop!(Sharding::begin_shard_split(RuntimeOrigin::root(), 0, 0), Error::<Test>::InvalidShardConfig);
+            assert_noop!(
+                Sharding::begin_shard_split(RuntimeOrigin::root(), 0, SHARD_COUNT),
+                Error::<Test>::InvalidShardConfig
+            );
+            assert_ok!(Sharding::begin_shard_split(RuntimeOrigin::root(), 0, 3));
+            System::assert_last_event(Event::ShardSplitStarted { parent: 0, new_shard: 3 }.into());
+
+            // Conflicting splits and rebalances wait for it to complete
+            assert_noop!(Sharding::begin_shard_split(RuntimeOrigin::root(), 1, 2), Error::<Test>::SplitInProgress);
+            assert_noop!(Sharding::rebalance_shards(RuntimeOrigin::root()), Error::<Test>::SplitInProgress);
+            assert!(Sharding::accepts_shard_tag(&staying[0], 3) && Sharding::accepts_shard_tag(&leaving[0], 0));
+            assert!(!Sharding::accepts_shard_tag(&staying[0], 1));
+
+            // 64 accounts, 16 examined per block
+            next_block();
+            System::assert_has_event(
+                Event::ShardSplitProgress {
+                    parent: 0,
+                    new_shard: 3,
+                    examined: 16,
+                    moved: Sharding::active_shard_split().unwrap().moved,
+                }
+                .into(),
+            );
+            for _ in 0..4 {
+                next_block();
+            }
+            assert_eq!(Sharding::active_shard_split(), None);
+            System::assert_has_event(
+                Event::ShardSplitCompleted { parent: 0, new_shard: 3, examined: 64, moved: leaving.len() as u32 }.into(),
+            );
+
+            assert!(leaving.iter().all(|account| Sharding::get_account_shard(account) == 3));
+            assert!(staying.iter().all(|account| Sharding::get_account_shard(account) == 0));
+            assert!(!Sharding::accepts_shard_tag(&staying[0], 3));
+            assert_ok!(Sharding::rebalance_shards(RuntimeOrigin::root()));
+        });
+    }
+
+    #[test]
+    fn genesis_endows_the_fee_pot() {
+        let mut t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        pallet_sharding::GenesisConfig::<Test>::default().assimilate_storage(&mut t).unwrap();
+
+        sp_io::TestExternalities::from(t).execute_with(|| {
+            assert_eq!(Sharding::pallet_pot_balance(), Balances::minimum_balance());
+            assert!(System::account_exists(&Sharding::fee_pot_account()));
+        });
+    }
+
+    #[test]
+    fn shard_state_roots_commit_to_queues() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            Sharding::on_finalize(1);
+            let empty_root = Sharding::shard_state_root(1).unwrap();
+            assert_eq!(Sharding::shard_state_root(2), Some(empty_root));
+
+            transfer_to_shard_1(0, account_on(1, 0), 50);
+            Sharding::on_finalize(1);
+
+            assert_ne!(Sharding::shard_state_root(1), Some(empty_root));
+            assert_eq!(Sharding::shard_state_roots().len(), SHARD_COUNT as usize);
+        });
+    }
+
+    #[test]
+    fn shard_snapshots_match_committed_roots() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            transfer_to_shard_1(0, account_on(1, 0), 50);
+            let moved = account_on(2, 0);
+            Sharding::set_account_shard(&moved, 1);
+            Sharding::on_finalize(1);
+
+            let snapshot = Sharding::shard_snapshot(1);
+            assert_eq!(snapshot.accounts, vec![moved]);
+            assert_eq!(snapshot.queue.len(), 1);
+            for shard_id in 0..SHARD_COUNT {
+                let root = Sharding::shard_snapshot(shard_id).root::<BlakeTwo256>();
+                assert_eq!(Sharding::shard_state_root(shard_id), Some(root));
+            }
+        });
+    }
+
+    #[test]
+    fn accounts_digests_follow_accounts_between_shards() {
+        new_test_ext().execute_with(|| {
+            let account = account_on(2, 0);
+            Sharding::assign_account_to_shard(&account);
+            assert_eq!(Sharding::shard_accounts_digest(2), account_digest(&account));
+
+            // Moving the account takes its digest along rather than adding it twice
+            Sharding::set_account_shard(&account, 1);
+            Sharding::set_account_shard(&account, 1);
+            assert_eq!(Sharding::shard_accounts_digest(2), U256::zero());
+            assert_eq!(Sharding::shard_accounts_digest(1), account_digest(&account));
+
+            Sharding::on_finalize(1);
+            for shard_id in 0..SHARD_COUNT {
+                let snapshot = Sharding::shard_snapshot(shard_id);
+                assert_eq!(Sharding::shard_accounts_digest(shard_id), snapshot.accounts_digest());
+                assert_eq!(Sharding::shard_state_root(shard_id), Some(snapshot.root::<BlakeTwo256>()));
+            }
+        });
+    }
+
+    #[test]
+    fn shard_data_chunks_verify_and_reconstruct_from_any_half() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            transfer_to_shard_1(0, account_on(1, 0), 50);
+            Sharding::on_finalize(1);
+
+            let commitment = Sharding::shard_data_commitment(1).unwrap();
+            let data = Sharding::shard_block_data(1).encode();
+            assert_eq!(commitment.data_len as usize, data.len());
+
+            let chunks: Vec<_> = (0..availability::TOTAL_CHUNKS as u16)
+                .map(|index| Sharding::shard_data_chunk(1, index).unwrap())
+                .collect();
+            assert!(chunks.iter().all(|chunk| availability::verify_chunk::<BlakeTwo256>(&commitment.root, chunk)));
+            assert_eq!(Sharding::shard_data_chunk(1, availability::TOTAL_CHUNKS as u16), None);
+
+            // The parity chunks alone are enough
+            let parity: Vec<_> = chunks[availability::DATA_CHUNKS..]
+                .iter()
+                .map(|chunk| (chunk.index, chunk.data.clone()))
+                .collect();
+            assert_eq!(availability::reconstruct(&parity, data.len()), Some(data));
+
+            let mut tampered = chunks[3].clone();
+            tampered.data[0] ^= 1;
+            assert!(!availability::verify_chunk::<BlakeTwo256>(&commitment.root, &tampered));
+            let mut moved = chunks[3].clone();
+            moved.index = 4;
+            assert!(!availability::verify_chunk::<BlakeTwo256>(&commitment.root, &moved));
+        });
+    }
+
+    #[test]
+    fn shard_data_commits_the_head_of_the_queue_up_to_the_byte_bound() {
+        new_test_ext().execute_with(|| {
+            init_shards();
+            let recipient = account_on(1, 0);
+            for nth in 0..3 {
+                transfer_to_shard_1(nth, recipient, 50);
+            }
+            let queue = Sharding::cross_shard_queue(1);
+            let tx_len = queue[0].encoded_size();
+
+            // Room for two transfers besides the length prefixes
+            MaxShardDataLen::set(2 * tx_len as u32 + 10);
+            Sharding::on_finalize(1);
+
+            let data = Sharding::shard_block_data(1);
+            assert_eq!(data.queue, queue[..2].to_vec());
+            assert!(data.encoded_size() <= MaxShardDataLen::get() as usize);
+            assert_eq!(Sharding::shard_data_commitment(1).unwrap().data_len as usize, data.encoded_size());
+        });
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn admin_calls_fit_in_blocks_full_of_normal_transactions() {
+        new_test_ext().execute_with(|| {
+            let limits = <Test as frame_system::Config>::BlockWeights::get();
+            let mut full = frame_system::ConsumedWeight::default();
+            full.set(limits.get(DispatchClass::Normal).max_total.unwrap(), DispatchClass::Normal);
+            frame_system::BlockWeight::<Test>::put(full);
+            let fits = |call: RuntimeCall| {
+                frame_system::extensions::check_weight::calculate_consumed_weight::<RuntimeCall>(
+                    &limits,
+                    System::block_weight(),
+                    &call.get_dispatch_info(),
+                    0,
+                )
+                .is_ok()
+            };
+
+            assert!(!fits(RuntimeCall::Sharding(Call::join_shard { shard_id: 0 })));
+            let admin_calls = [
+                Call::<Test>::initialize_sharding { initial_validators: vec![vec![100]] },
+                Call::<Test>::rebalance_shards {},
+                Call::<Test>::set_cross_shard_fee_bounds { bounds: Some((1, 10)) },
+            ];
+            for call in admin_calls {
+                let info = call.get_dispatch_info();
+                assert_eq!((info.class, info.pays_fee), (DispatchClass::Operational, Pays::No));
+                assert!(fits(RuntimeCall::Sharding(call)));
+            }
+
+            init_shards();
+            assert_ok!(Sharding::rebalance_shards(RuntimeOrigin::root()));
+            assert_ok!(Sharding::set_cross_shard_fee_bounds(RuntimeOrigin::root(), Some((1, 10))));
+        });
+    }
+}
@@ -0,0 +1,757 @@
+//! Storage migrations for the sharding pallet
+//!
+//! Each version's migration reads the storage it changes through `storage_alias`es declaring
+//! the layout it migrates from, so the migrations run in sequence from any earlier version.
+//! [`MigrateToLatest`] runs them all.
+
+use super::*;
+use frame_support::traits::OnRuntimeUpgrade;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+/// Every sharding migration, from storage version 1 to the current one, checked under
+/// try-runtime to keep every queued cross-shard transfer
+pub type MigrateToLatest<T> = PreservingCrossShardQueue<
+    T,
+    (
+        v2::HoldQueuedTransfers<T>,
+        v3::AddCrossShardTips<T>,
+        v4::UpgradeShardMapping<T>,
+        v5::InitAccountsDigests<T>,
+        v6::VersionStoredTransfers<T>,
+        v7::ExpireCrossShardTxRecords<T>,
+        v8::BoundStoredVecs<T>,
+    ),
+>;
+
+/// Run `Steps`, checking under try-runtime that the queued cross-shard transfers survive them
+///
+/// Every transfer queued before is either still queued after or failed, leaving a
+/// [`CrossShardTxStatus::Failed`] status, and while none failed the queues owe the same total.
+/// Every sender has at least what its queued transfers owe on hold.
+pub struct PreservingCrossShardQueue<T, Steps>(PhantomData<(T, Steps)>);
+
+impl<T: Config, Steps: OnRuntimeUpgrade> OnRuntimeUpgrade for PreservingCrossShardQueue<T, Steps> {
+    fn on_runtime_upgrade() -> Weight {
+        Steps::on_runtime_upgrade()
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+        Ok(QueueTotals::<T>::read().encode())
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+        let before = QueueTotals::<T>::decode(&mut &state[..]).map_err(|_| "undecodable pre-upgrade totals")?;
+        let after = QueueTotals::<T>::read();
+
+        let failed = after.failed.saturating_sub(before.failed);
+        frame_support::ensure!(
+            after.queued.saturating_add(failed) == before.queued,
+            "queued cross-shard transfers were lost in the migration",
+        );
+        frame_support::ensure!(
+            failed > 0 || after.owed == before.owed,
+            "queued cross-shard transfers owe a different total after the migration",
+        );
+
+        let hold_reason: T::RuntimeHoldReason = HoldReason::CrossShardEscrow.into();
+        for (sender, owed) in after.owed_by {
+            frame_support::ensure!(
+                T::Currency::balance_on_hold(&hold_reason, &sender) >= owed,
+                "a sender holds less than its queued cross-shard transfers owe",
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Queued cross-shard transfers, read in the layout of the on-chain storage version
+#[cfg(feature = "try-runtime")]
+#[derive(Encode, Decode)]
+struct QueueTotals<T: Config> {
+    /// Transfers queued on every shard
+    queued: u32,
+    /// Amounts, fees and tips the queued transfers owe
+    owed: BalanceOf<T>,
+    /// What each sender's queued transfers owe
+    owed_by: sp_std::collections::btree_map::BTreeMap<T::AccountId, BalanceOf<T>>,
+    /// Transfers with a [`CrossShardTxStatus::Failed`] status
+    failed: u32,
+}
+
+#[cfg(feature = "try-runtime")]
+impl<T: Config> QueueTotals<T> {
+    fn read() -> Self {
+        use frame_support::traits::GetStorageVersion;
+
+        let owing: Vec<(T::AccountId, BalanceOf<T>)> = match Pallet::<T>::on_chain_storage_version() {
+            version if version < 2 => {
+                v2::CrossShardQueue::<T>::iter_values().flatten().map(|tx| (tx.sender, tx.amount)).collect()
+            },
+            version if version < 3 => v3::CrossShardQueue::<T>::iter_values()
+                .flatten()
+                .map(|tx| (tx.sender, tx.amount.saturating_add(tx.fee)))
+                .collect(),
+            version if version < 6 => v6::CrossShardQueue::<T>::iter_values()
+                .flatten()
+                .map(|tx| (tx.sender, tx.amount.saturating_add(tx.fee).saturating_add(tx.tip)))
+                .collect(),
+            _ => crate::CrossShardQueue::<T>::iter_values()
+                .flatten()
+                .map(|tx| {
+                    let tx = tx.into_latest();
+                    (tx.sender, tx.amount.saturating_add(tx.fee).saturating_add(tx.tip))
+                })
+                .collect(),
+        };
+
+        let mut totals = Self {
+            queued: owing.len() as u32,
+            owed: Zero::zero(),
+            owed_by: Default::default(),
+            failed: CrossShardTxStatuses::<T>::iter_values()
+                .filter(|status| matches!(status, CrossShardTxStatus::Failed(_)))
+                .count() as u32,
+        };
+        for (sender, owed) in owing {
+            totals.owed = totals.owed.saturating_add(owed);
+            let by_sender = totals.owed_by.entry(sender).or_default();
+            *by_sender = by_sender.saturating_add(owed);
+        }
+        totals
+    }
+}
+
+/// Version 2: queued cross-shard transfers record their fee and are held on the sender
+pub mod v2 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, storage_alias, traits::UncheckedOnRuntimeUpgrade};
+
+    /// Cross-shard transfer as stored in version 1, without its fee
+    pub type OldCrossShardTx<AccountId, Balance> = CrossShardTxV0<AccountId, Balance>;
+
+    #[storage_alias]
+    pub type CrossShardQueue<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        ShardId,
+        Vec<OldCrossShardTx<<T as frame_system::Config>::AccountId, BalanceOf<T>>>,
+        ValueQuery,
+    >;
+
+    /// Record a zero fee on every queued cross-shard transfer and hold its amount on its
+    /// sender
+    ///
+    /// Version 1 burnt the fee when queueing and left the amount in the sender's free balance,
+    /// so there is no fee to hold. The amount is put on hold under
+    /// [`HoldReason::CrossShardEscrow`] as version 2 does when queueing. A transfer whose
+    /// sender no longer has the amount free is dropped from the queue and fails with
+    /// [`FailureReason::InsufficientFunds`].
+    pub struct InnerHoldQueuedTransfers<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerHoldQueuedTransfers<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let hold_reason: T::RuntimeHoldReason = HoldReason::CrossShardEscrow.into();
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            for (shard_id, queue) in CrossShardQueue::<T>::drain().collect::<Vec<_>>() {
+                let mut held = Vec::with_capacity(queue.len());
+                for tx in queue.into_iter().map(OldCrossShardTx::upgrade) {
+                    match T::Currency::hold(&hold_reason, &tx.sender, tx.amount.saturating_add(tx.fee)) {
+                        Ok(()) => held.push(tx),
+                        Err(e) => {
+                            log::warn!(
+                                target: LOG_TARGET,
+                                "Dropping queued cross-shard transfer of {:?}, the amount could not be held: {:?}",
+                                tx.sender,
+                                e,
+                            );
+                            let tx_hash = BlakeTwo256::hash_of(&tx);
+                            CrossShardTxStatuses::<T>::insert(
+                                tx_hash,
+                                CrossShardTxStatus::Failed(FailureReason::InsufficientFunds),
+                            );
+                            writes += 1;
+                        }
+                    }
+                    reads += 2;
+                    writes += 2;
+                }
+                v3::CrossShardQueue::<T>::insert(shard_id, held);
+                reads += 1;
+                writes += 1;
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerHoldQueuedTransfers`], run once when upgrading from storage version 1
+    pub type HoldQueuedTransfers<T> = VersionedMigration<
+        1,
+        2,
+        InnerHoldQueuedTransfers<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 3: cross-shard transfers carry a tip
+pub mod v3 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, storage_alias, traits::UncheckedOnRuntimeUpgrade};
+
+    /// Cross-shard transfer as stored up to version 2
+    pub type OldCrossShardTx<AccountId, Balance> = CrossShardTxV1<AccountId, Balance>;
+
+    /// Dead-lettered transfer as stored up to version 2
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldDeadLetter<AccountId, Balance, BlockNumber> {
+        pub tx: OldCrossShardTx<AccountId, Balance>,
+        pub expires_at: BlockNumber,
+    }
+
+    #[storage_alias]
+    pub type DeadLetters<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::Hash,
+        OldDeadLetter<<T as frame_system::Config>::AccountId, BalanceOf<T>, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    #[storage_alias]
+    pub type CrossShardQueue<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        ShardId,
+        Vec<OldCrossShardTx<<T as frame_system::Config>::AccountId, BalanceOf<T>>>,
+        ValueQuery,
+    >;
+
+    /// Give every queued and dead-lettered transfer a zero tip
+    ///
+    /// Transfers are identified by the hash of their encoding, which the tip changes, so their
+    /// status, dead letter and expiry move to the new hash. History records keep the old one.
+    pub struct InnerAddCrossShardTips<T>(PhantomData<T>);
+
+    impl<T: Config> InnerAddCrossShardTips<T> {
+        /// Move the status of a transfer from `old` to `new`
+        fn rehash_status(old: T::Hash, new: T::Hash) {
+            if let Some(status) = CrossShardTxStatuses::<T>::take(old) {
+                CrossShardTxStatuses::<T>::insert(new, status);
+            }
+        }
+    }
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerAddCrossShardTips<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            for (shard_id, queue) in CrossShardQueue::<T>::drain().collect::<Vec<_>>() {
+                let queue: Vec<_> = queue
+                    .into_iter()
+                    .map(|old| {
+                        let old_hash = BlakeTwo256::hash_of(&old);
+                        let tx = old.upgrade();
+                        Self::rehash_status(old_hash, BlakeTwo256::hash_of(&tx));
+                        reads += 1;
+                        writes += 2;
+                        tx
+                    })
+                    .collect();
+                v6::CrossShardQueue::<T>::insert(shard_id, queue);
+                reads += 1;
+                writes += 1;
+            }
+
+            for (old_hash, dead_letter) in DeadLetters::<T>::drain().collect::<Vec<_>>() {
+                let tx = dead_letter.tx.upgrade();
+                let new_hash = BlakeTwo256::hash_of(&tx);
+                Self::rehash_status(old_hash, new_hash);
+                v8::DeadLetterExpiries::<T>::mutate(dead_letter.expires_at, |expiring| {
+                    for hash in expiring.iter_mut().filter(|hash| **hash == old_hash) {
+                        *hash = new_hash;
+                    }
+                });
+                v6::DeadLetters::<T>::insert(new_hash, v6::OldDeadLetter { tx, expires_at: dead_letter.expires_at });
+                reads += 3;
+                writes += 5;
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerAddCrossShardTips`], run once when upgrading from storage version 2
+    pub type AddCrossShardTips<T> = VersionedMigration<
+        2,
+        3,
+        InnerAddCrossShardTips<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 4: versioned, domain-separated shard mapping
+pub mod v4 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+
+    /// Start upgrading the shard mapping to [`SHARD_MAPPING_VERSION`]
+    ///
+    /// Chains that predate versioning place accounts by the first byte of their hash, mapping
+    /// version 0. The upgrade examines every account over the following blocks, pinning in
+    /// [`AccountToShard`] those the new mapping would move before switching to it, and emits
+    /// [`Event::ShardMappingUpgraded`] when done.
+    pub struct InnerUpgradeShardMapping<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerUpgradeShardMapping<T> {
+        fn on_runtime_upgrade() -> Weight {
+            Pallet::<T>::begin_shard_mapping_upgrade(SHARD_MAPPING_VERSION);
+            T::DbWeight::get().reads_writes(2, 2)
+        }
+    }
+
+    /// [`InnerUpgradeShardMapping`], run once when upgrading from storage version 3
+    pub type UpgradeShardMapping<T> = VersionedMigration<
+        3,
+        4,
+        InnerUpgradeShardMapping<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 5: shard state roots commit to incrementally kept accounts digests
+pub mod v5 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+    use sp_std::collections::btree_map::BTreeMap;
+
+    /// Sum the [`account_digest`]s of every account in [`AccountToShard`] into its shard's
+    /// [`ShardAccountsDigests`] entry, which is kept up to date as accounts are assigned
+    /// from then on
+    pub struct InnerInitAccountsDigests<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerInitAccountsDigests<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut digests: BTreeMap<ShardId, U256> = BTreeMap::new();
+            let mut reads = 0u64;
+
+            for (account, shard_id) in AccountToShard::<T>::iter() {
+                let digest = digests.entry(shard_id).or_default();
+                *digest = digest.overflowing_add(account_digest(&account)).0;
+                reads += 1;
+            }
+
+            let writes = digests.len() as u64;
+            for (shard_id, digest) in digests {
+                ShardAccountsDigests::<T>::insert(shard_id, digest);
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerInitAccountsDigests`], run once when upgrading from storage version 4
+    pub type InitAccountsDigests<T> = VersionedMigration<
+        4,
+        5,
+        InnerInitAccountsDigests<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 6: queued, settled and dead-lettered transfers are stored versioned, and the
+/// transfer and call queues are bounded
+pub mod v6 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, storage_alias, traits::UncheckedOnRuntimeUpgrade};
+
+    /// Dead-lettered transfer as stored from version 3 to 5
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+    pub struct OldDeadLetter<AccountId, Balance, BlockNumber> {
+        pub tx: CrossShardTx<AccountId, Balance>,
+        pub expires_at: BlockNumber,
+    }
+
+    #[storage_alias]
+    pub type CrossShardQueue<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        ShardId,
+        Vec<CrossShardTx<<T as frame_system::Config>::AccountId, BalanceOf<T>>>,
+        ValueQuery,
+    >;
+
+    #[storage_alias]
+    pub type SettlementsThisBlock<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        ShardId,
+        Vec<CrossShardTx<<T as frame_system::Config>::AccountId, BalanceOf<T>>>,
+        ValueQuery,
+    >;
+
+    #[storage_alias]
+    pub type CrossShardCallQueue<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        ShardId,
+        Vec<CrossShardCall<<T as frame_system::Config>::AccountId, BalanceOf<T>>>,
+        ValueQuery,
+    >;
+
+    #[storage_alias]
+    pub type DeadLetters<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::Hash,
+        OldDeadLetter<<T as frame_system::Config>::AccountId, BalanceOf<T>, BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    /// Store queued, settled and dead-lettered transfers in [`VersionedCrossShardTx`] and
+    /// bound the queues
+    ///
+    /// Transfers beyond `MaxCrossShardQueueLen`, the most recently queued first, are refunded
+    /// with [`FailureReason::LimitExceeded`]. Calls beyond `MaxCrossShardCallQueueLen` fail
+    /// with [`Error::CrossShardCallQueueFull`] in their receipt. Both keep queue order.
+    pub struct InnerVersionStoredTransfers<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerVersionStoredTransfers<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            for (shard_id, mut queue) in CrossShardQueue::<T>::drain().collect::<Vec<_>>() {
+                let kept = queue.len().min(T::MaxCrossShardQueueLen::get() as usize);
+                let overflow = queue.split_off(kept);
+                for tx in &overflow {
+                    let tx_hash = BlakeTwo256::hash_of(tx);
+                    QueuedAt::<T>::remove(tx_hash);
+                    Pallet::<T>::refund(tx_hash, tx, FailureReason::LimitExceeded);
+                    reads += 2;
+                    writes += 4;
+                }
+                if !overflow.is_empty() {
+                    Pallet::<T>::note_processed(shard_id, overflow.len() as u32);
+                    Metrics::<T>::mutate(|metrics| {
+                        metrics.cross_shard_backlog = metrics.cross_shard_backlog.saturating_sub(overflow.len() as u32);
+                    });
+                    reads += 4;
+                    writes += 2;
+                }
+                crate::CrossShardQueue::<T>::insert(
+                    shard_id,
+                    BoundedVec::truncate_from(queue.into_iter().map(Into::into).collect()),
+                );
+                reads += 1;
+                writes += 1;
+            }
+
+            for (shard_id, settled) in SettlementsThisBlock::<T>::drain().collect::<Vec<_>>() {
+                crate::SettlementsThisBlock::<T>::insert(
+                    shard_id,
+                    BoundedVec::truncate_from(settled.into_iter().map(Into::into).collect()),
+                );
+                reads += 1;
+                writes += 1;
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let expires_at = now.saturating_add(T::CrossShardCallReceiptLifetime::get());
+            let output = DispatchError::from(Error::<T>::CrossShardCallQueueFull).encode();
+            for (shard_id, mut queue) in CrossShardCallQueue::<T>::drain().collect::<Vec<_>>() {
+                let kept = queue.len().min(T::MaxCrossShardCallQueueLen::get() as usize);
+                let overflow = queue.split_off(kept);
+                for call in &overflow {
+                    v8::CrossShardCallReceipts::<T>::insert(
+                        call.call_id,
+                        v8::OldCrossShardCallReceipt {
+                            success: false,
+                            output: BoundedVec::truncate_from(output.clone()),
+                            gas_used: Weight::zero(),
+                            executed_at: now,
+                        },
+                    );
+                    v8::CrossShardCallReceiptExpiries::<T>::append(expires_at, call.call_id);
+                    writes += 2;
+                }
+                if !overflow.is_empty() {
+                    Pallet::<T>::note_processed(shard_id, overflow.len() as u32);
+                    reads += 3;
+                    writes += 2;
+                }
+                crate::CrossShardCallQueue::<T>::insert(shard_id, BoundedVec::truncate_from(queue));
+                reads += 1;
+                writes += 1;
+            }
+
+            for (tx_hash, dead_letter) in DeadLetters::<T>::drain().collect::<Vec<_>>() {
+                crate::DeadLetters::<T>::insert(
+                    tx_hash,
+                    DeadLetter { tx: dead_letter.tx.into(), expires_at: dead_letter.expires_at },
+                );
+                reads += 1;
+                writes += 1;
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerVersionStoredTransfers`], run once when upgrading from storage version 5
+    pub type VersionStoredTransfers<T> = VersionedMigration<
+        5,
+        6,
+        InnerVersionStoredTransfers<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 7: statuses and receipts of settled and failed cross-shard transfers expire
+pub mod v7 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+
+    /// Schedule the status and receipt of every settled or failed cross-shard transfer to be
+    /// pruned after `CrossShardTxRecordLifetime` blocks from now
+    ///
+    /// Pending transfers are scheduled once they settle or fail. So that no single block
+    /// prunes them all, at most `MaxSettlementsPerBlock` records expire per block.
+    pub struct InnerExpireCrossShardTxRecords<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerExpireCrossShardTxRecords<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let first = frame_system::Pallet::<T>::block_number().saturating_add(T::CrossShardTxRecordLifetime::get());
+            let per_block = T::MaxSettlementsPerBlock::get().max(1);
+            let mut reads = 0u64;
+            let mut scheduled = 0u32;
+
+            for (tx_hash, status) in CrossShardTxStatuses::<T>::iter() {
+                reads += 1;
+                if matches!(status, CrossShardTxStatus::Settled | CrossShardTxStatus::Failed(_)) {
+                    let expires_at = first.saturating_add((scheduled / per_block).into());
+                    v8::CrossShardTxExpiries::<T>::append(expires_at, tx_hash);
+                    scheduled = scheduled.saturating_add(1);
+                }
+            }
+
+            T::DbWeight::get().reads_writes(reads, scheduled.into())
+        }
+    }
+
+    /// [`InnerExpireCrossShardTxRecords`], run once when upgrading from storage version 6
+    pub type ExpireCrossShardTxRecords<T> = VersionedMigration<
+        6,
+        7,
+        InnerExpireCrossShardTxRecords<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Version 8: validator lists, call receipt outputs and expiry schedules are bounded
+pub mod v8 {
+    use super::*;
+    use frame_support::{migrations::VersionedMigration, storage_alias, traits::UncheckedOnRuntimeUpgrade};
+
+    /// Bound of the vectors stored before version 8, which decodes any of them
+    pub type Unbounded = ConstU32<{ u32::MAX }>;
+
+    /// Shard information as stored before version 8, with any number of validators
+    pub type OldShardInfo<AccountId, Balance> = ShardInfo<AccountId, Balance, Unbounded>;
+
+    /// Cross-shard call receipt as stored before version 8, with an output of any length
+    pub type OldCrossShardCallReceipt<BlockNumber> = CrossShardCallReceipt<BlockNumber, Unbounded>;
+
+    #[storage_alias]
+    pub type ShardInfos<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        ShardId,
+        OldShardInfo<<T as frame_system::Config>::AccountId, BalanceOf<T>>,
+        OptionQuery,
+    >;
+
+    #[storage_alias]
+    pub type CrossShardCallReceipts<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        CrossShardCallId,
+        OldCrossShardCallReceipt<BlockNumberFor<T>>,
+        OptionQuery,
+    >;
+
+    #[storage_alias]
+    pub type DeadLetterExpiries<T: Config> = StorageMap<
+        Pallet<T>,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Vec<<T as frame_system::Config>::Hash>,
+        ValueQuery,
+    >;
+
+    #[storage_alias]
+    pub type CrossShardCallReceiptExpiries<T: Config> =
+        StorageMap<Pallet<T>, Twox64Concat, BlockNumberFor<T>, Vec<CrossShardCallId>, ValueQuery>;
+
+    #[storage_alias]
+    pub type CrossShardTxExpiries<T: Config> = StorageMap<
+        Pallet<T>,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Vec<<T as frame_system::Config>::Hash>,
+        ValueQuery,
+    >;
+
+    /// Bound the vectors the pallet stored without a bound
+    ///
+    /// Validators beyond `MaxValidatorsPerShard` leave their shard and call receipt outputs
+    /// are cut to `MaxCrossShardCallOutputLen` bytes. Of the entries expiring at a block, those
+    /// beyond `MaxExpiringPerBlock` expire at the next block with room instead, rescheduled
+    /// dead letters being refunded then. Only the entries over a bound are rewritten; the
+    /// shard split and mapping upgrade cursors are fixed-length keys and always fit.
+    pub struct InnerBoundStoredVecs<T>(PhantomData<T>);
+
+    impl<T: Config> InnerBoundStoredVecs<T> {
+        /// Of each block's list in `old`, `keep` the first `MaxExpiringPerBlock` entries and
+        /// `reschedule` the rest from the next block on, returning the lists read, the lists
+        /// over the bound and the entries rescheduled
+        fn spill_expiries<Item>(
+            old: impl Iterator<Item = (BlockNumberFor<T>, Vec<Item>)>,
+            mut keep: impl FnMut(BlockNumberFor<T>, Vec<Item>),
+            mut reschedule: impl FnMut(BlockNumberFor<T>, Item),
+        ) -> (u64, u64, u64) {
+            let max = T::MaxExpiringPerBlock::get() as usize;
+            let mut read = 0u64;
+            let mut over: Vec<(BlockNumberFor<T>, Vec<Item>)> = Vec::new();
+            for (at, expiring) in old {
+                read += 1;
+                if expiring.len() > max {
+                    over.push((at, expiring));
+                }
+            }
+
+            let (spilled_lists, mut rescheduled) = (over.len() as u64, 0u64);
+            for (at, mut expiring) in over {
+                let spilled = expiring.split_off(max);
+                keep(at, expiring);
+                let next = at.saturating_add(One::one());
+                for item in spilled {
+                    reschedule(next, item);
+                    rescheduled += 1;
+                }
+            }
+            (read, spilled_lists, rescheduled)
+        }
+    }
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerBoundStoredVecs<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            let max_validators = T::MaxValidatorsPerShard::get() as usize;
+            for (shard_id, info) in ShardInfos::<T>::iter().collect::<Vec<_>>() {
+                reads += 1;
+                if info.validators.len() > max_validators {
+                    log::warn!(
+                        target: LOG_TARGET,
+                        "shard {} drops {} validators beyond MaxValidatorsPerShard",
+                        shard_id,
+                        info.validators.len() - max_validators,
+                    );
+                    crate::ShardInfos::<T>::insert(
+                        shard_id,
+                        ShardInfo {
+                            shard_id: info.shard_id,
+                            validators: BoundedVec::truncate_from(info.validators.into_inner()),
+                            total_stake: info.total_stake,
+                            tx_count: info.tx_count,
+                            capacity: info.capacity,
+                        },
+                    );
+                    writes += 1;
+                }
+            }
+
+            let max_output = T::MaxCrossShardCallOutputLen::get() as usize;
+            for (call_id, receipt) in CrossShardCallReceipts::<T>::iter().collect::<Vec<_>>() {
+                reads += 1;
+                if receipt.output.len() > max_output {
+                    crate::CrossShardCallReceipts::<T>::insert(
+                        call_id,
+                        CrossShardCallReceipt {
+                            success: receipt.success,
+                            output: BoundedVec::truncate_from(receipt.output.into_inner()),
+                            gas_used: receipt.gas_used,
+                            executed_at: receipt.executed_at,
+                        },
+                    );
+                    writes += 1;
+                }
+            }
+
+            let (read, spilled, rescheduled) = Self::spill_expiries(
+                DeadLetterExpiries::<T>::iter(),
+                |at, kept| crate::DeadLetterExpiries::<T>::insert(at, BoundedVec::truncate_from(kept)),
+                |from, tx_hash| {
+                    let at = Pallet::<T>::schedule_expiry(from, |at| {
+                        crate::DeadLetterExpiries::<T>::try_append(at, tx_hash).is_ok()
+                    });
+                    crate::DeadLetters::<T>::mutate(tx_hash, |dead_letter| {
+                        if let Some(dead_letter) = dead_letter {
+                            dead_letter.expires_at = at;
+                        }
+                    });
+                },
+            );
+            reads += read + 2 * rescheduled;
+            writes += spilled + 2 * rescheduled;
+
+            let (read, spilled, rescheduled) = Self::spill_expiries(
+                CrossShardCallReceiptExpiries::<T>::iter(),
+                |at, kept| crate::CrossShardCallReceiptExpiries::<T>::insert(at, BoundedVec::truncate_from(kept)),
+                |from, call_id| {
+                    Pallet::<T>::schedule_expiry(from, |at| {
+                        crate::CrossShardCallReceiptExpiries::<T>::try_append(at, call_id).is_ok()
+                    });
+                },
+            );
+            reads += read + rescheduled;
+            writes += spilled + rescheduled;
+
+            let (read, spilled, rescheduled) = Self::spill_expiries(
+                CrossShardTxExpiries::<T>::iter(),
+                |at, kept| crate::CrossShardTxExpiries::<T>::insert(at, BoundedVec::truncate_from(kept)),
+                |from, tx_hash| {
+                    Pallet::<T>::schedule_expiry(from, |at| {
+                        crate::CrossShardTxExpiries::<T>::try_append(at, tx_hash).is_ok()
+                    });
+                },
+            );
+            reads += read + rescheduled;
+            writes += spilled + rescheduled;
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// [`InnerBoundStoredVecs`], run once when upgrading from storage version 7
+    pub type BoundStoredVecs<T> = VersionedMigration<
+        7,
+        8,
+        InnerBoundStoredVecs<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
@@ -0,0 +1,214 @@
+//! Weights for pallet_sharding
+//!
+//! Settling the cross-shard queue is linear in the transfers settled `n`, each moving held
+//! funds and writing its status, and in the length `q` of the queue, which is decoded, ordered
+//! by tip and written back whatever `n` is. Forwarding contract calls is linear in the calls
+//! executed `n`, on top of the gas they use, which the pallet adds. Committing shard data is
+//! linear in the bytes `b` erasure-coded. The remaining extrinsics touch a fixed number of
+//! storage items.
+//!
+//! All figures are estimates, not benchmark results: each counts the storage accesses of its
+//! extrinsic on top of a fixed execution estimate. The cross-shard transfer extrinsics and the
+//! shard setup and metrics calls have benchmarks in `benchmarking.rs`, but no run on reference
+//! hardware has replaced their estimates yet. To do so, run `netchain-node benchmark pallet
+//! --chain dev --pallet pallet_sharding --extrinsic '*' --steps 50 --repeat 20` with the
+//! `runtime-benchmarks` feature and copy the figures over.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_sharding.
+pub trait WeightInfo {
+	fn initialize_sharding() -> Weight;
+	fn join_shard() -> Weight;
+	fn execute_cross_shard() -> Weight;
+	fn process_cross_shard_queue(n: u32, q: u32) -> Weight;
+	fn update_metrics() -> Weight;
+	fn rebalance_shards() -> Weight;
+	fn execute_cross_shard_call() -> Weight;
+	fn process_cross_shard_calls(n: u32) -> Weight;
+	fn rotate_and_register_keys() -> Weight;
+	fn set_cross_shard_fee_bounds() -> Weight;
+	fn claim_failed_transfer() -> Weight;
+	fn begin_shard_split() -> Weight;
+	fn set_system_shard() -> Weight;
+	fn commit_shard_data(b: u32) -> Weight;
+}
+
+/// Weights for pallet_sharding using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: Sharding ShardInfos (r:0 w:4), Sharding CrossShardQueue (r:0 w:4),
+	/// Sharding ShardProcessingState (r:0 w:4), Sharding Metrics (r:0 w:1)
+	fn initialize_sharding() -> Weight {
+		Weight::from_parts(40_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	/// Storage: Sharding ShardInfos (r:1 w:1), System EventTopics (r:1 w:1)
+	fn join_shard() -> Weight {
+		Weight::from_parts(25_000_000, 3_600)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: Safeguard Paused (r:1 w:0), Sharding AccountToShard (r:1 w:0),
+	/// Sharding CrossShardQueue (r:1 w:1), Sharding ShardInfos (r:1 w:0),
+	/// Sharding ShardProcessingState (r:1 w:1), Sharding CrossShardFeeBounds (r:1 w:0),
+	/// Balances Holds (r:1 w:1), System Account (r:1 w:1), Sharding NextCrossShardTxNonce (r:1 w:1),
+	/// Sharding CrossShardTxStatuses (r:0 w:1), Sharding QueuedAt (r:0 w:1),
+	/// Sharding OldestQueuedAt (r:1 w:1), Sharding OverloadedShards (r:1 w:0),
+	/// Sharding Metrics (r:1 w:1), History Entries (r:2 w:2), History EntryCount (r:1 w:1),
+	/// System EventTopics (r:4 w:4)
+	fn execute_cross_shard() -> Weight {
+		Weight::from_parts(60_000_000, 260_000)
+			.saturating_add(T::DbWeight::get().reads(20_u64))
+			.saturating_add(T::DbWeight::get().writes(16_u64))
+	}
+	/// Storage: Sharding CrossShardQueue (r:1 w:1), Sharding SettledThisBlock (r:1 w:1),
+	/// Sharding LatencyBreachedShards (r:1 w:0), Sharding QueuedAt (r:n+1 w:n),
+	/// Balances Holds (r:n w:n), System Account (r:2n+1 w:2n+1), Sharding CrossShardTxStatuses (r:0 w:n),
+	/// Sharding CrossShardTxReceipts (r:0 w:n), Sharding CrossShardTxExpiries (r:0 w:n),
+	/// Sharding OldestQueuedAt (r:0 w:1),
+	/// Sharding ShardProcessingState (r:1 w:1), Sharding ShardInfos (r:1 w:0),
+	/// Sharding OverloadedShards (r:1 w:1), Sharding SettlementsThisBlock (r:1 w:1),
+	/// Sharding Metrics (r:1 w:1), Sharding RewardsPaidThisBlock (r:1 w:1),
+	/// System EventTopics (r:2 w:2)
+	/// The range of component `n` is `[0, MaxSettlementsPerBlock]`.
+	/// The range of component `q` is `[0, MaxCrossShardQueueLen]`.
+	fn process_cross_shard_queue(n: u32, q: u32) -> Weight {
+		Weight::from_parts(45_000_000, 5_000)
+			.saturating_add(Weight::from_parts(30_000_000, 8_000).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(150_000, 130).saturating_mul(q.into()))
+			.saturating_add(T::DbWeight::get().reads(13_u64))
+			.saturating_add(T::DbWeight::get().reads((4_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(12_u64))
+			.saturating_add(T::DbWeight::get().writes((7_u64).saturating_mul(n.into())))
+	}
+	/// Storage: Sharding Metrics (r:2 w:1)
+	fn update_metrics() -> Weight {
+		Weight::from_parts(15_000_000, 1_600)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Sharding CurrentEpoch (r:1 w:0), Sharding ShardProcessingState (r:4 w:0),
+	/// Sharding ActiveShardSplit (r:1 w:0), Sharding PendingMigrations (r:1 w:1), Sharding ShardInfos (r:3 w:0)
+	fn rebalance_shards() -> Weight {
+		Weight::from_parts(90_000_000, 20_000)
+			.saturating_add(T::DbWeight::get().reads(10_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Safeguard Paused (r:1 w:0), Sharding AccountToShard (r:1 w:0),
+	/// Sharding CrossShardCallQueue (r:1 w:1), Sharding ShardInfos (r:1 w:0),
+	/// Sharding ShardProcessingState (r:1 w:1), Sharding CrossShardFeeBounds (r:1 w:0),
+	/// System Account (r:1 w:1), Balances TotalIssuance (r:1 w:1),
+	/// Sharding NextCrossShardCallId (r:1 w:1), System EventTopics (r:4 w:4)
+	fn execute_cross_shard_call() -> Weight {
+		Weight::from_parts(50_000_000, 40_000)
+			.saturating_add(T::DbWeight::get().reads(13_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
+	}
+	/// Storage: Sharding CrossShardCallQueue (r:1 w:1), Sharding CrossShardCallReceipts (r:0 w:n),
+	/// Sharding CrossShardCallReceiptExpiries (r:n w:n), Sharding ShardProcessingState (r:1 w:1),
+	/// Sharding ShardInfos (r:1 w:0), Sharding OverloadedShards (r:1 w:1), System EventTopics (r:n w:n)
+	/// The range of component `n` is `[0, MaxCrossShardCallQueueLen]`.
+	fn process_cross_shard_calls(n: u32) -> Weight {
+		Weight::from_parts(35_000_000, 5_000)
+			.saturating_add(Weight::from_parts(25_000_000, 1_200).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(n.into())))
+	}
+	/// Storage: Sharding ShardInfos (r:1 w:1), Session NextKeys (r:1 w:1), Session KeyOwner (r:4 w:4)
+	fn rotate_and_register_keys() -> Weight {
+		Weight::from_parts(80_000_000, 12_000)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: Sharding CrossShardFeeBounds (r:0 w:1)
+	fn set_cross_shard_fee_bounds() -> Weight {
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Sharding DeadLetters (r:1 w:1), Sharding DeadLetterExpiries (r:1 w:1),
+	/// Sharding CrossShardTxStatuses (r:0 w:1), Sharding CrossShardTxExpiries (r:0 w:1),
+	/// Balances Holds (r:1 w:1), System Account (r:3 w:3), Sharding DeadLetterStats (r:1 w:1),
+	/// System EventTopics (r:4 w:4)
+	fn claim_failed_transfer() -> Weight {
+		Weight::from_parts(55_000_000, 9_000)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	/// Storage: Sharding ShardInfos (r:2 w:1), Sharding ActiveShardSplit (r:1 w:1),
+	/// Sharding CrossShardQueue (r:0 w:1), Sharding ShardProcessingState (r:0 w:1)
+	fn begin_shard_split() -> Weight {
+		Weight::from_parts(20_000_000, 4_000)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: Sharding ReservedSystemShard (r:0 w:1)
+	fn set_system_shard() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: Sharding ShardDataCommitments (r:0 w:1)
+	/// The range of component `b` is `[0, MaxShardDataLen]`.
+	fn commit_shard_data(b: u32) -> Weight {
+		Weight::from_parts(5_000_000, 0)
+			.saturating_add(Weight::from_parts(20_000, 1).saturating_mul(b.into()))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn initialize_sharding() -> Weight {
+		Weight::from_parts(100_000_000, 10_000)
+	}
+	fn join_shard() -> Weight {
+		Weight::from_parts(50_000_000, 5_000)
+	}
+	fn execute_cross_shard() -> Weight {
+		Weight::from_parts(75_000_000, 7_500)
+	}
+	fn process_cross_shard_queue(n: u32, q: u32) -> Weight {
+		Weight::from_parts(45_000_000, 4_500)
+			.saturating_add(Weight::from_parts(15_000_000, 1_500).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(150_000, 130).saturating_mul(q.into()))
+	}
+	fn update_metrics() -> Weight {
+		Weight::from_parts(25_000_000, 2_500)
+	}
+	fn rebalance_shards() -> Weight {
+		Weight::from_parts(150_000_000, 15_000)
+	}
+	fn execute_cross_shard_call() -> Weight {
+		Weight::from_parts(80_000_000, 8_000)
+	}
+	fn process_cross_shard_calls(n: u32) -> Weight {
+		Weight::from_parts(50_000_000, 5_000)
+			.saturating_add(Weight::from_parts(45_000_000, 4_500).saturating_mul(n.into()))
+	}
+	fn rotate_and_register_keys() -> Weight {
+		Weight::from_parts(120_000_000, 12_000)
+	}
+	fn set_cross_shard_fee_bounds() -> Weight {
+		Weight::from_parts(15_000_000, 1_500)
+	}
+	fn claim_failed_transfer() -> Weight {
+		Weight::from_parts(70_000_000, 7_000)
+	}
+	fn begin_shard_split() -> Weight {
+		Weight::from_parts(20_000_000, 2_000)
+	}
+	fn set_system_shard() -> Weight {
+		Weight::from_parts(10_000_000, 1_000)
+	}
+	fn commit_shard_data(b: u32) -> Weight {
+		Weight::from_parts(5_000_000, 500)
+			.saturating_add(Weight::from_parts(20_000, 1).saturating_mul(b.into()))
+	}
+}
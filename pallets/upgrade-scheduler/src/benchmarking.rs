@@ -0,0 +1,107 @@
+//! Benchmarking setup for pallet-upgrade-scheduler
+//!
+//! Each stage is benchmarked from the one before it, with code of `c` bytes where its size
+//! matters. Benchmark code is not a runtime, so enactment measures reading it back and
+//! `set_code` rejecting it; the code write is weighed by frame_system's `set_code`.
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as UpgradeScheduler;
+use frame_benchmarking::v2::*;
+use frame_support::traits::Hooks;
+use sp_std::vec;
+
+/// Stage `c` bytes of code, returning its hash
+fn stage<T: Config>(c: u32) -> H256 {
+    let code = vec![1u8; c as usize];
+    let code_hash = BlakeTwo256::hash(&code);
+    <StagedCode<T>>::put(BoundedVec::<u8, T::MaxCodeSize>::truncate_from(code));
+    <Pending<T>>::put(PendingUpgrade {
+        code_hash,
+        code_len: c,
+        staged_at: frame_system::Pallet::<T>::block_number(),
+        status: UpgradeStatus::Staged,
+    });
+    code_hash
+}
+
+/// Move the pending upgrade to `status`
+fn set_status<T: Config>(status: UpgradeStatus<BlockNumberFor<T>>) {
+    <Pending<T>>::mutate(|pending| {
+        if let Some(pending) = pending {
+            pending.status = status;
+        }
+    });
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn stage_upgrade(c: Linear<1, { T::MaxCodeSize::get() }>) -> Result<(), BenchmarkError> {
+        let origin = T::StageOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let code = vec![1u8; c as usize];
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, code);
+
+        assert!(<Pending<T>>::exists());
+        Ok(())
+    }
+
+    #[benchmark]
+    fn record_check() -> Result<(), BenchmarkError> {
+        let origin = T::ApproveOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let code_hash = stage::<T>(1);
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, code_hash, true, H256::repeat_byte(1));
+
+        assert!(matches!(<Pending<T>>::get().map(|p| p.status), Some(UpgradeStatus::Checked { .. })));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn schedule_upgrade() -> Result<(), BenchmarkError> {
+        let origin = T::ApproveOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let code_hash = stage::<T>(1);
+        set_status::<T>(UpgradeStatus::Checked { report_hash: H256::repeat_byte(1) });
+        let at = frame_system::Pallet::<T>::block_number().saturating_add(T::MinEnactmentDelay::get());
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, code_hash, at);
+
+        assert!(matches!(<Pending<T>>::get().map(|p| p.status), Some(UpgradeStatus::Scheduled { .. })));
+        Ok(())
+    }
+
+    #[benchmark]
+    fn abort_upgrade() -> Result<(), BenchmarkError> {
+        let origin = T::AbortOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let code_hash = stage::<T>(1);
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, code_hash);
+
+        assert!(!<Pending<T>>::exists());
+        Ok(())
+    }
+
+    #[benchmark]
+    fn enact_upgrade(c: Linear<1, { T::MaxCodeSize::get() }>) {
+        stage::<T>(c);
+        let now = frame_system::Pallet::<T>::block_number();
+        set_status::<T>(UpgradeStatus::Scheduled { report_hash: H256::repeat_byte(1), at: now });
+
+        #[block]
+        {
+            UpgradeScheduler::<T>::on_initialize(now);
+        }
+
+        assert!(!<StagedCode<T>>::exists());
+    }
+
+    impl_benchmark_test_suite!(UpgradeScheduler, crate::tests::new_test_ext(), crate::tests::Test);
+}
@@ -0,0 +1,493 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Upgrade Scheduler Pallet
+//!
+//! A safer path to runtime upgrades than an immediate sudo `set_code`.
+//!
+//! An upgrade moves through explicit stages, each emitting an event:
+//! 1. **Staged** - the new runtime wasm is uploaded and stored as a preimage, keyed by hash
+//! 2. **Checked** - governance records the outcome of off-chain `try-runtime` checks run
+//!    against the staged code; a failed check discards the upgrade
+//! 3. **Scheduled** - governance picks an enactment block at least `MinEnactmentDelay`
+//!    blocks ahead, which is the abort window operators have to react
+//! 4. **Enacted** - at the scheduled block `set_code` is applied
+//!
+//! Until enactment the upgrade can be aborted at any stage. Only one upgrade can be pending
+//! at a time. The block an upgrade is enacted in is given over to it: `on_initialize` weighs
+//! reading the staged code and frame_system's code write, and claims at least the whole
+//! block, as a scheduled `set_code` does.
+
+pub use pallet::*;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+pub use weights::WeightInfo;
+
+use frame_support::{
+    pallet_prelude::*,
+    traits::{EnsureOrigin, Get},
+};
+use frame_system::pallet_prelude::*;
+use sp_std::vec::Vec;
+use sp_runtime::traits::{BlakeTwo256, Hash, Saturating};
+use sp_core::H256;
+
+/// Stage of a pending upgrade
+#[derive(Clone, PartialEq, Eq, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum UpgradeStatus<BlockNumber> {
+    /// Code uploaded, awaiting `try-runtime` check results
+    Staged,
+    /// `try-runtime` checks passed, awaiting scheduling
+    Checked {
+        /// Hash of the published check report
+        report_hash: H256,
+    },
+    /// Enactment scheduled
+    Scheduled {
+        /// Hash of the published check report
+        report_hash: H256,
+        /// Block at which `set_code` is applied
+        at: BlockNumber,
+    },
+}
+
+/// Upgrade waiting for enactment
+#[derive(Clone, PartialEq, Eq, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct PendingUpgrade<BlockNumber> {
+    /// Blake2-256 hash of the staged runtime code
+    pub code_hash: H256,
+    /// Size of the staged runtime code in bytes
+    pub code_len: u32,
+    /// Block the code was staged at
+    pub staged_at: BlockNumber,
+    /// Current stage
+    pub status: UpgradeStatus<BlockNumber>,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Origin allowed to stage new runtime code
+        type StageOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Governance origin that records check results and schedules enactment
+        type ApproveOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Origin allowed to abort a pending upgrade
+        type AbortOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Maximum size of staged runtime code in bytes
+        #[pallet::constant]
+        type MaxCodeSize: Get<u32>;
+
+        /// Minimum blocks between scheduling and enactment (the abort window)
+        #[pallet::constant]
+        type MinEnactmentDelay: Get<BlockNumberFor<Self>>;
+
+        /// WeightInfo for benchmarking
+        type WeightInfo: WeightInfo;
+    }
+
+    /// The upgrade currently moving through the stages, if any
+    #[pallet::storage]
+    #[pallet::getter(fn pending_upgrade)]
+    pub type Pending<T: Config> = StorageValue<_, PendingUpgrade<BlockNumberFor<T>>>;
+
+    /// Staged runtime code (the preimage of `Pending::code_hash`)
+    #[pallet::storage]
+    pub type StagedCode<T: Config> = StorageValue<_, BoundedVec<u8, T::MaxCodeSize>>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// New runtime code staged
+        UpgradeStaged { code_hash: H256, code_len: u32 },
+        /// `try-runtime` checks passed for the staged code
+        UpgradeChecked { code_hash: H256, report_hash: H256 },
+        /// `try-runtime` checks failed; the staged code was discarded
+        UpgradeCheckFailed { code_hash: H256, report_hash: H256 },
+        /// Enactment scheduled
+        UpgradeScheduled { code_hash: H256, at: BlockNumberFor<T> },
+        /// Pending upgrade aborted
+        UpgradeAborted { code_hash: H256 },
+        /// Runtime code replaced
+        UpgradeEnacted { code_hash: H256 },
+        /// `set_code` rejected the staged code at enactment
+        UpgradeEnactmentFailed { code_hash: H256, error: DispatchError },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// Another upgrade is already pending
+        UpgradeAlreadyPending,
+        /// No upgrade is pending
+        NoPendingUpgrade,
+        /// Code hash does not match the pending upgrade
+        CodeHashMismatch,
+        /// Staged code exceeds `MaxCodeSize`
+        CodeTooLarge,
+        /// Staged code is empty
+        EmptyCode,
+        /// The pending upgrade is not in the stage this action requires
+        InvalidStage,
+        /// Enactment block is inside the minimum abort window
+        EnactmentTooSoon,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            let read = T::DbWeight::get().reads(1);
+            match <Pending<T>>::get() {
+                Some(PendingUpgrade { code_hash, code_len, status: UpgradeStatus::Scheduled { at, .. }, .. })
+                    if now >= at =>
+                {
+                    Self::enact(code_hash);
+                    let code_write = <T::SystemWeightInfo as frame_system::WeightInfo>::set_code();
+                    // Runtime upgrades take the whole block, as with a scheduled `set_code`
+                    T::WeightInfo::enact_upgrade(code_len)
+                        .saturating_add(code_write)
+                        .max(T::BlockWeights::get().max_block)
+                }
+                _ => read,
+            }
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Stage new runtime code
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::stage_upgrade(code.len() as u32))]
+        pub fn stage_upgrade(origin: OriginFor<T>, code: Vec<u8>) -> DispatchResult {
+            T::StageOrigin::ensure_origin(origin)?;
+
+            ensure!(!<Pending<T>>::exists(), Error::<T>::UpgradeAlreadyPending);
+            ensure!(!code.is_empty(), Error::<T>::EmptyCode);
+
+            let code_hash = BlakeTwo256::hash(&code);
+            let code_len = code.len() as u32;
+            let code: BoundedVec<u8, T::MaxCodeSize> =
+                code.try_into().map_err(|_| Error::<T>::CodeTooLarge)?;
+
+            <StagedCode<T>>::put(code);
+            <Pending<T>>::put(PendingUpgrade {
+                code_hash,
+                code_len,
+                staged_at: frame_system::Pallet::<T>::block_number(),
+                status: UpgradeStatus::Staged,
+            });
+
+            Self::deposit_event(Event::UpgradeStaged { code_hash, code_len });
+
+            Ok(())
+        }
+
+        /// Record the outcome of `try-runtime` checks run against the staged code
+        ///
+        /// `report_hash` commits to the published check report. A failed check discards
+        /// the staged code.
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::record_check())]
+        pub fn record_check(
+            origin: OriginFor<T>,
+            code_hash: H256,
+            passed: bool,
+            report_hash: H256,
+        ) -> DispatchResult {
+            T::ApproveOrigin::ensure_origin(origin)?;
+
+            let mut pending = Self::ensure_pending(code_hash)?;
+            ensure!(pending.status == UpgradeStatus::Staged, Error::<T>::InvalidStage);
+
+            if passed {
+                pending.status = UpgradeStatus::Checked { report_hash };
+                <Pending<T>>::put(pending);
+                Self::deposit_event(Event::UpgradeChecked { code_hash, report_hash });
+            } else {
+                Self::clear();
+                Self::deposit_event(Event::UpgradeCheckFailed { code_hash, report_hash });
+            }
+
+            Ok(())
+        }
+
+        /// Schedule enactment of checked code at block `at`
+        #[pallet::call_index(2)]
+        #[pallet::weight(T::WeightInfo::schedule_upgrade())]
+        pub fn schedule_upgrade(
+            origin: OriginFor<T>,
+            code_hash: H256,
+            at: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            T::ApproveOrigin::ensure_origin(origin)?;
+
+            let mut pending = Self::ensure_pending(code_hash)?;
+            let report_hash = match pending.status {
+                UpgradeStatus::Checked { report_hash } => report_hash,
+                _ => return Err(Error::<T>::InvalidStage.into()),
+            };
+
+            let earliest = frame_system::Pallet::<T>::block_number()
+                .saturating_add(T::MinEnactmentDelay::get());
+            ensure!(at >= earliest, Error::<T>::EnactmentTooSoon);
+
+            pending.status = UpgradeStatus::Scheduled { report_hash, at };
+            <Pending<T>>::put(pending);
+
+            Self::deposit_event(Event::UpgradeScheduled { code_hash, at });
+
+            Ok(())
+        }
+
+        /// Abort the pending upgrade at any stage before enactment
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::abort_upgrade())]
+        pub fn abort_upgrade(origin: OriginFor<T>, code_hash: H256) -> DispatchResult {
+            T::AbortOrigin::ensure_origin(origin)?;
+
+            Self::ensure_pending(code_hash)?;
+            Self::clear();
+
+            Self::deposit_event(Event::UpgradeAborted { code_hash });
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// The pending upgrade, provided it is for `code_hash`
+        fn ensure_pending(code_hash: H256) -> Result<PendingUpgrade<BlockNumberFor<T>>, DispatchError> {
+            let pending = <Pending<T>>::get().ok_or(Error::<T>::NoPendingUpgrade)?;
+            ensure!(pending.code_hash == code_hash, Error::<T>::CodeHashMismatch);
+            Ok(pending)
+        }
+
+        /// Drop the pending upgrade and its code
+        fn clear() {
+            <Pending<T>>::kill();
+            <StagedCode<T>>::kill();
+        }
+
+        /// Apply the staged code through `frame_system::set_code`
+        fn enact(code_hash: H256) {
+            let code = <StagedCode<T>>::get().map(|code| code.into_inner()).unwrap_or_default();
+            Self::clear();
+
+            match frame_system::Pallet::<T>::set_code(frame_system::RawOrigin::Root.into(), code) {
+                Ok(_) => Self::deposit_event(Event::UpgradeEnacted { code_hash }),
+                Err(e) => Self::deposit_event(Event::UpgradeEnactmentFailed { code_hash, error: e.error }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as pallet_upgrade_scheduler;
+    use frame_support::{assert_noop, assert_ok, derive_impl, parameter_types, traits::Hooks};
+    use sp_core::traits::{Externalities, ReadRuntimeVersion, ReadRuntimeVersionExt};
+    use sp_runtime::{BuildStorage, DispatchError};
+    use sp_version::RuntimeVersion;
+
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test
+        {
+            System: frame_system,
+            UpgradeScheduler: pallet_upgrade_scheduler,
+        }
+    );
+
+    parameter_types! {
+        pub TestVersion: RuntimeVersion = version(1);
+        pub const MaxCodeSize: u32 = 128;
+        pub const MinEnactmentDelay: u64 = 5;
+    }
+
+    #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+    impl frame_system::Config for Test {
+        type Block = Block;
+        type Version = TestVersion;
+    }
+
+    impl Config for Test {
+        type RuntimeEvent = RuntimeEvent;
+        type StageOrigin = frame_system::EnsureRoot<u64>;
+        type ApproveOrigin = frame_system::EnsureRoot<u64>;
+        type AbortOrigin = frame_system::EnsureRoot<u64>;
+        type MaxCodeSize = MaxCodeSize;
+        type MinEnactmentDelay = MinEnactmentDelay;
+        type WeightInfo = ();
+    }
+
+    fn version(spec_version: u32) -> RuntimeVersion {
+        RuntimeVersion { spec_name: "test".into(), spec_version, ..Default::default() }
+    }
+
+    /// Test runtime code is its own encoded version, so `set_code` can read it back
+    struct CodeIsVersion;
+
+    impl ReadRuntimeVersion for CodeIsVersion {
+        fn read_runtime_version(&self, code: &[u8], _: &mut dyn Externalities) -> Result<Vec<u8>, String> {
+            Ok(code.to_vec())
+        }
+    }
+
+    fn code(spec_version: u32) -> Vec<u8> {
+        version(spec_version).encode()
+    }
+
+    pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
+        let t = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+        let mut ext: sp_io::TestExternalities = t.into();
+        ext.register_extension(ReadRuntimeVersionExt::new(CodeIsVersion));
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    fn runtime_code() -> Option<Vec<u8>> {
+        sp_io::storage::get(sp_core::storage::well_known_keys::CODE).map(|code| code.to_vec())
+    }
+
+    /// Stage `code`, pass its checks and schedule it at block `at`
+    fn schedule(code: Vec<u8>, at: u64) -> H256 {
+        let code_hash = BlakeTwo256::hash(&code);
+        assert_ok!(UpgradeScheduler::stage_upgrade(RuntimeOrigin::root(), code));
+        assert_ok!(UpgradeScheduler::record_check(RuntimeOrigin::root(), code_hash, true, H256::repeat_byte(1)));
+        assert_ok!(UpgradeScheduler::schedule_upgrade(RuntimeOrigin::root(), code_hash, at));
+        code_hash
+    }
+
+    #[test]
+    fn upgrades_move_through_the_stages_and_enact_at_the_scheduled_block() {
+        new_test_ext().execute_with(|| {
+            let code = code(2);
+            let code_hash = BlakeTwo256::hash(&code);
+            let report_hash = H256::repeat_byte(1);
+
+            assert_noop!(
+                UpgradeScheduler::stage_upgrade(RuntimeOrigin::signed(1), code.clone()),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(UpgradeScheduler::stage_upgrade(RuntimeOrigin::root(), code.clone()));
+            System::assert_last_event(Event::UpgradeStaged { code_hash, code_len: code.len() as u32 }.into());
+
+            // Code is scheduled only once its checks passed, and outside the abort window
+            assert_noop!(
+                UpgradeScheduler::schedule_upgrade(RuntimeOrigin::root(), code_hash, 6),
+                Error::<Test>::InvalidStage
+            );
+            assert_ok!(UpgradeScheduler::record_check(RuntimeOrigin::root(), code_hash, true, report_hash));
+            System::assert_last_event(Event::UpgradeChecked { code_hash, report_hash }.into());
+            assert_noop!(
+                UpgradeScheduler::schedule_upgrade(RuntimeOrigin::root(), code_hash, 5),
+                Error::<Test>::EnactmentTooSoon
+            );
+            assert_ok!(UpgradeScheduler::schedule_upgrade(RuntimeOrigin::root(), code_hash, 6));
+            System::assert_last_event(Event::UpgradeScheduled { code_hash, at: 6 }.into());
+
+            // Nothing happens before the scheduled block
+            assert_eq!(UpgradeScheduler::on_initialize(5), <Test as frame_system::Config>::DbWeight::get().reads(1));
+            assert!(runtime_code().is_none());
+
+            // Enactment writes the code and claims the whole block, with room for the write
+            System::set_block_number(6);
+            let weight = UpgradeScheduler::on_initialize(6);
+            let max_block = <Test as frame_system::Config>::BlockWeights::get().max_block;
+            assert!(weight.all_gte(max_block));
+            assert!(weight.all_gte(<() as WeightInfo>::enact_upgrade(code.len() as u32)));
+            System::assert_last_event(Event::UpgradeEnacted { code_hash }.into());
+            assert_eq!(runtime_code(), Some(code));
+            assert!(UpgradeScheduler::pending_upgrade().is_none());
+            assert!(!<StagedCode<Test>>::exists());
+        });
+    }
+
+    #[test]
+    fn upgrades_are_aborted_or_discarded_before_enactment() {
+        new_test_ext().execute_with(|| {
+            // A failed check discards the staged code
+            let code_hash = BlakeTwo256::hash(&code(2));
+            assert_ok!(UpgradeScheduler::stage_upgrade(RuntimeOrigin::root(), code(2)));
+            assert_ok!(UpgradeScheduler::record_check(RuntimeOrigin::root(), code_hash, false, H256::repeat_byte(2)));
+            System::assert_last_event(
+                Event::UpgradeCheckFailed { code_hash, report_hash: H256::repeat_byte(2) }.into(),
+            );
+            assert!(UpgradeScheduler::pending_upgrade().is_none());
+            assert!(!<StagedCode<Test>>::exists());
+
+            // A scheduled upgrade can be aborted up to its block, by its hash
+            let code_hash = schedule(code(2), 6);
+            assert_noop!(
+                UpgradeScheduler::abort_upgrade(RuntimeOrigin::signed(1), code_hash),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                UpgradeScheduler::abort_upgrade(RuntimeOrigin::root(), H256::repeat_byte(3)),
+                Error::<Test>::CodeHashMismatch
+            );
+            assert_ok!(UpgradeScheduler::abort_upgrade(RuntimeOrigin::root(), code_hash));
+            System::assert_last_event(Event::UpgradeAborted { code_hash }.into());
+            assert!(!<StagedCode<Test>>::exists());
+
+            System::set_block_number(6);
+            assert_eq!(UpgradeScheduler::on_initialize(6), <Test as frame_system::Config>::DbWeight::get().reads(1));
+            assert!(runtime_code().is_none());
+            assert_noop!(
+                UpgradeScheduler::abort_upgrade(RuntimeOrigin::root(), code_hash),
+                Error::<Test>::NoPendingUpgrade
+            );
+        });
+    }
+
+    #[test]
+    fn staged_code_is_bounded_and_one_upgrade_is_pending_at_a_time() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(UpgradeScheduler::stage_upgrade(RuntimeOrigin::root(), Vec::new()), Error::<Test>::EmptyCode);
+            assert_noop!(
+                UpgradeScheduler::stage_upgrade(RuntimeOrigin::root(), vec![1; 129]),
+                Error::<Test>::CodeTooLarge
+            );
+
+            assert_ok!(UpgradeScheduler::stage_upgrade(RuntimeOrigin::root(), code(2)));
+            assert_noop!(
+                UpgradeScheduler::stage_upgrade(RuntimeOrigin::root(), code(3)),
+                Error::<Test>::UpgradeAlreadyPending
+            );
+        });
+    }
+
+    #[test]
+    fn code_rejected_by_set_code_is_dropped_at_enactment() {
+        new_test_ext().execute_with(|| {
+            // The spec version must increase
+            let code_hash = schedule(code(1), 6);
+
+            System::set_block_number(6);
+            UpgradeScheduler::on_initialize(6);
+
+            System::assert_last_event(
+                Event::UpgradeEnactmentFailed {
+                    code_hash,
+                    error: frame_system::Error::<Test>::SpecVersionNeedsToIncrease.into(),
+                }
+                .into(),
+            );
+            assert!(runtime_code().is_none());
+            assert!(UpgradeScheduler::pending_upgrade().is_none());
+        });
+    }
+}
@@ -0,0 +1,81 @@
+//! Weights for pallet_upgrade_scheduler
+//!
+//! Staging is linear in the code size `c` (bytes), which is hashed and stored; enactment is
+//! linear in it too, as the staged code is read back and handed to `set_code`. Writing the
+//! code itself is weighed by frame_system's `set_code`, which the pallet adds on top. The
+//! remaining extrinsics touch a fixed number of storage items.
+//!
+//! All figures are estimates, not benchmark results: each counts the storage accesses of its
+//! extrinsic on top of a fixed execution estimate. Every extrinsic and the enactment have
+//! benchmarks in `benchmarking.rs`, but no run on reference hardware has replaced their
+//! estimates yet. To do so, run `netchain-node benchmark pallet --chain dev --pallet
+//! pallet_upgrade_scheduler --extrinsic '*' --steps 50 --repeat 20` with the
+//! `runtime-benchmarks` feature and copy the figures over.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for pallet_upgrade_scheduler.
+pub trait WeightInfo {
+	fn stage_upgrade(c: u32) -> Weight;
+	fn record_check() -> Weight;
+	fn schedule_upgrade() -> Weight;
+	fn abort_upgrade() -> Weight;
+	fn enact_upgrade(c: u32) -> Weight;
+}
+
+/// Weights for pallet_upgrade_scheduler using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: UpgradeScheduler Pending (r:1 w:1), UpgradeScheduler StagedCode (r:0 w:1)
+	/// The range of component `c` is `[1, 8388608]`.
+	fn stage_upgrade(c: u32) -> Weight {
+		Weight::from_parts(20_000_000, 1_590)
+			.saturating_add(Weight::from_parts(2_000, 0).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: UpgradeScheduler Pending (r:1 w:1), UpgradeScheduler StagedCode (r:0 w:1)
+	fn record_check() -> Weight {
+		Weight::from_parts(12_000_000, 1_590)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: UpgradeScheduler Pending (r:1 w:1)
+	fn schedule_upgrade() -> Weight {
+		Weight::from_parts(12_000_000, 1_590)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: UpgradeScheduler Pending (r:1 w:1), UpgradeScheduler StagedCode (r:0 w:1)
+	fn abort_upgrade() -> Weight {
+		Weight::from_parts(12_000_000, 1_590)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: UpgradeScheduler Pending (r:1 w:1), UpgradeScheduler StagedCode (r:1 w:1)
+	/// The range of component `c` is `[1, 8388608]`.
+	fn enact_upgrade(c: u32) -> Weight {
+		Weight::from_parts(15_000_000, 1_590)
+			.saturating_add(Weight::from_parts(500, 1).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn stage_upgrade(c: u32) -> Weight {
+		Weight::from_parts(50_000, 0).saturating_add(Weight::from_parts(c as u64, c as u64))
+	}
+	fn record_check() -> Weight { Weight::from_parts(20_000, 0) }
+	fn schedule_upgrade() -> Weight { Weight::from_parts(20_000, 0) }
+	fn abort_upgrade() -> Weight { Weight::from_parts(20_000, 0) }
+	fn enact_upgrade(c: u32) -> Weight {
+		Weight::from_parts(30_000, 0).saturating_add(Weight::from_parts(c as u64, c as u64))
+	}
+}
@@ -0,0 +1,222 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Netchain Primitives
+//!
+//! Types and constants shared by the Netchain pallets, the runtime, the node and the
+//! off-chain tooling (client, benchmark CLI), so they cannot drift apart.
+//!
+//! - [`ShardId`], [`SHARD_COUNT`] and [`account_shard`], the shard an account hashes to under
+//!   the current [`SHARD_MAPPING_VERSION`]; [`shard_for`] under any version
+//! - [`SYSTEM_SHARD`], the virtual shard system transactions can be reserved
+//! - [`Balance`], the runtime's balance and fee type
+//! - [`DataKey`] and [`DataValue`] of oracle feeds
+//! - [`ORACLE_PORT`] and [`ICA_HOST_PORT`], the IBC ports bound by Netchain applications
+//!
+//! [`FailureReason`] is carried by the failure events of the cross-shard, parallel execution
+//! and IBC pallets (`CrossShardFailed`, `BatchFailed`, `PacketRejected`), so indexers can
+//! categorize failures the same way whichever subsystem reports them.
+//!
+//! [`sequential_id`] builds the identifiers pallets assign in sequence, like IBC clients and
+//! channels: a prefix followed by the big-endian `u32` sequence number, so they have a fixed
+//! length per prefix and are built without `format!`. [`display_id`] and [`parse_display_id`]
+//! convert them to and from the readable `<prefix><decimal>` form (e.g. `channel-3`) used in
+//! logs, RPC output and tooling.
+//!
+//! [`Printable`] formats identifiers and data keys in log lines: readable IDs as text, any
+//! other bytes as text when they are UTF-8 and as hex otherwise.
+//!
+//! [`offchain_history`] lays out the execution results and oracle history pallets mirror into
+//! the node's off-chain database, so the node can serve and prune them.
+
+extern crate alloc;
+
+pub mod offchain_history;
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+    traits::{BlakeTwo256, Hash},
+    DispatchError, TokenError,
+};
+
+/// Shard identifier
+pub type ShardId = u8;
+
+/// Number of shards in the network
+pub const SHARD_COUNT: ShardId = 4;
+
+/// Virtual shard no account lives on, which governance can reserve for system transactions
+pub const SYSTEM_SHARD: ShardId = ShardId::MAX;
+
+/// Balance of an account, and of the fees it pays
+pub type Balance = u128;
+
+/// Key of an oracle data feed
+pub type DataKey = Vec<u8>;
+
+/// Value of an oracle data feed
+pub type DataValue = Vec<u8>;
+
+/// IBC port oracle feed channels are opened on
+pub const ORACLE_PORT: &[u8] = b"oracle";
+
+/// IBC port the interchain accounts host is bound to
+pub const ICA_HOST_PORT: &[u8] = b"icahost";
+
+/// Version of the mapping placing accounts on the shard their id hashes to
+pub type ShardMappingVersion = u8;
+
+/// Mapping new chains start with, and that `pallet_sharding` upgrades existing chains to
+pub const SHARD_MAPPING_VERSION: ShardMappingVersion = 1;
+
+/// Domain the hashes of versioned shard mappings are separated with
+pub const SHARD_MAPPING_DOMAIN: &[u8] = b"netchain/shard-mapping";
+
+/// Shard an account hashes to under the mapping `version`, from its SCALE encoding
+///
+/// Version 0 is the original mapping, the first byte of the account's Blake2-256 hash modulo
+/// [`SHARD_COUNT`]. Later versions hash the account with [`SHARD_MAPPING_DOMAIN`] and the
+/// version, and reduce the first eight bytes of the hash, so changing the hash or the shard
+/// count only takes a new version, never a silent remapping.
+pub fn shard_for(version: ShardMappingVersion, encoded_account: &[u8]) -> ShardId {
+    if version == 0 {
+        return BlakeTwo256::hash(encoded_account).as_ref()[0] % SHARD_COUNT;
+    }
+    let hash = BlakeTwo256::hash_of(&(SHARD_MAPPING_DOMAIN, version, encoded_account));
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&hash.as_ref()[..8]);
+    (u64::from_le_bytes(word) % u64::from(SHARD_COUNT)) as ShardId
+}
+
+/// Shard an account hashes to under [`SHARD_MAPPING_VERSION`], from its SCALE encoding
+///
+/// Rebalancing can move accounts away from it, and accounts of chains that started on an
+/// older mapping keep the shard it gave them; `pallet_sharding::get_account_shard` has the
+/// final say on-chain.
+pub fn account_shard(encoded_account: &[u8]) -> ShardId {
+    shard_for(SHARD_MAPPING_VERSION, encoded_account)
+}
+
+/// Why work carried across a boundary (shard, batch, chain) did not complete
+///
+/// The first two variants keep the encoding of the cross-shard failure reasons stored before
+/// this type was shared.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum FailureReason {
+    /// Less than the amount and fees is held on, or free in, the paying account
+    InsufficientFunds,
+    /// The recipient could not receive the amount (e.g. below the existential deposit)
+    RecipientRejected,
+    /// The receiving application refused it
+    ApplicationRejected,
+    /// A weight, size or rate limit was exceeded
+    LimitExceeded,
+    /// Its deadline passed
+    TimedOut,
+    /// The subsystem is paused by governance
+    Paused,
+    /// Error `error` of the pallet at `index`, as in [`sp_runtime::ModuleError`]
+    Module { index: u8, error: [u8; 4] },
+    /// Any other dispatch error
+    Other,
+}
+
+impl From<DispatchError> for FailureReason {
+    fn from(error: DispatchError) -> Self {
+        match error {
+            DispatchError::Module(error) => Self::Module { index: error.index, error: error.error },
+            DispatchError::Token(TokenError::FundsUnavailable | TokenError::OnlyProvider) =>
+                Self::InsufficientFunds,
+            DispatchError::Token(TokenError::BelowMinimum | TokenError::CannotCreate) => Self::RecipientRejected,
+            DispatchError::Exhausted => Self::LimitExceeded,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Identifier `n` of a sequence of identifiers starting with `prefix`: `prefix ++ n.to_be_bytes()`
+pub fn sequential_id(prefix: &[u8], n: u32) -> Vec<u8> {
+    let mut id = Vec::with_capacity(prefix.len() + 4);
+    id.extend_from_slice(prefix);
+    id.extend_from_slice(&n.to_be_bytes());
+    id
+}
+
+/// Sequence number of `id` if it is a [`sequential_id`] with `prefix`
+pub fn sequential_id_number(id: &[u8], prefix: &[u8]) -> Option<u32> {
+    let n: [u8; 4] = id.strip_prefix(prefix)?.try_into().ok()?;
+    Some(u32::from_be_bytes(n))
+}
+
+/// Prefix and sequence number of `id` if it looks like a [`sequential_id`], i.e. a printable
+/// prefix ending in `-` followed by four bytes
+pub fn split_sequential_id(id: &[u8]) -> Option<(&[u8], u32)> {
+    let (prefix, n) = id.split_at(id.len().checked_sub(4)?);
+    if prefix.last() != Some(&b'-') || !prefix.iter().all(u8::is_ascii_graphic) {
+        return None;
+    }
+    Some((prefix, u32::from_be_bytes(n.try_into().ok()?)))
+}
+
+/// Canonical decimal representation of `n`
+pub fn decimal(mut n: u64) -> Vec<u8> {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(b'0' + (n % 10) as u8);
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    digits
+}
+
+/// Readable form of `id`: `<prefix><decimal>` for a [`sequential_id`], `id` itself otherwise
+pub fn display_id(id: &[u8]) -> Vec<u8> {
+    match split_sequential_id(id) {
+        Some((prefix, n)) => [prefix, decimal(n.into()).as_slice()].concat(),
+        None => id.to_vec(),
+    }
+}
+
+/// The [`sequential_id`] with `prefix` whose readable form is `text`, if `text` is `prefix`
+/// followed by a canonical decimal `u32`
+pub fn parse_display_id(text: &[u8], prefix: &[u8]) -> Option<Vec<u8>> {
+    let digits = text.strip_prefix(prefix)?;
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) || (digits.len() > 1 && digits[0] == b'0') {
+        return None;
+    }
+    let n = digits.iter().try_fold(0u32, |n, d| n.checked_mul(10)?.checked_add(u32::from(d - b'0')))?;
+    Some(sequential_id(prefix, n))
+}
+
+/// Bytes formatted for logs: the readable form of a [`sequential_id`], UTF-8 as text, anything
+/// else as `0x`-prefixed hex
+pub struct Printable<'a>(pub &'a [u8]);
+
+impl core::fmt::Display for Printable<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if let Some((prefix, n)) = split_sequential_id(self.0) {
+            let prefix = core::str::from_utf8(prefix).map_err(|_| core::fmt::Error)?;
+            return write!(f, "{prefix}{n}");
+        }
+        match core::str::from_utf8(self.0) {
+            Ok(text) if !text.chars().any(char::is_control) => f.write_str(text),
+            _ => {
+                f.write_str("0x")?;
+                self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+            },
+        }
+    }
+}
+
+impl core::fmt::Debug for Printable<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,69 @@
+//! Layout of the history pallets mirror into the node's off-chain database
+//!
+//! Pallets write items with `sp_io::offchain_index::set` as they produce them. Nodes started
+//! with `--enable-offchain-indexing true` store the writes outside the state, so the items
+//! outlive state pruning while the on-chain copies stay bounded, and serve them over RPC.
+//!
+//! Every item is stored as its block number followed by its SCALE encoding, see [`Indexed`],
+//! and is recorded in its block's journal: the `n`th item a table gets in a block is listed
+//! under [`journal_key`]`(table, block, n)`. The journal lets a node drop whole blocks of
+//! history once they fall out of its retention window without scanning the database; an item
+//! overwritten in a later block is left to that block.
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+
+/// Prefix of every key written by the history mirror
+pub const PREFIX: &[u8] = b"netchain::history::";
+
+/// Kind of item mirrored
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Table {
+    /// `pallet_parallel_executor::ExecutionResult`s, by transaction hash
+    ExecutionResults,
+    /// `pallet_oracle::Observation`s, by data key and position in the key's history
+    OracleRounds,
+    /// Position of the newest observation of each data key
+    OracleLatest,
+    /// Keys of the items written in each block
+    Journal,
+}
+
+/// Tables whose items are journaled and dropped with their block
+pub const JOURNALED: [Table; 2] = [Table::ExecutionResults, Table::OracleRounds];
+
+/// Item of a table, with the block it was written in
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Indexed<T> {
+    /// Block the item was written in
+    pub block: u32,
+    /// The item
+    pub item: T,
+}
+
+fn key(table: Table, parts: &[&[u8]]) -> Vec<u8> {
+    let mut key = PREFIX.to_vec();
+    table.encode_to(&mut key);
+    parts.iter().for_each(|part| key.extend_from_slice(part));
+    key
+}
+
+/// Key of the execution result of transaction `tx_hash`
+pub fn execution_result_key(tx_hash: &[u8]) -> Vec<u8> {
+    key(Table::ExecutionResults, &[tx_hash])
+}
+
+/// Key of observation `index` of `data_key`, counted from the key's first observation
+pub fn oracle_round_key(data_key: &[u8], index: u64) -> Vec<u8> {
+    key(Table::OracleRounds, &[&data_key.encode(), &index.to_be_bytes()])
+}
+
+/// Key of the index of the newest observation of `data_key`
+pub fn oracle_latest_key(data_key: &[u8]) -> Vec<u8> {
+    key(Table::OracleLatest, &[&data_key.encode()])
+}
+
+/// Key of the `n`th entry of `table` in the journal of `block`, holding the item's key
+pub fn journal_key(table: Table, block: u32, n: u32) -> Vec<u8> {
+    key(Table::Journal, &[&table.encode(), &block.to_be_bytes(), &n.to_be_bytes()])
+}
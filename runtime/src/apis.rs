@@ -42,8 +42,9 @@ use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-	AccountId, Aura, Balance, Block, Executive, Grandpa, InherentDataExt, Nonce, Runtime,
-	RuntimeCall, RuntimeGenesisConfig, SessionKeys, System, TransactionPayment, VERSION,
+	AccountId, Aura, Balance, Block, BlockNumber, Executive, Grandpa, Hash, History, IbcCore, InherentDataExt,
+	Nonce, Oracle, ParallelExecutor, Runtime, RuntimeCall, RuntimeEvent, RuntimeGenesisConfig, SessionKeys, Sharding,
+	System, TransactionPayment, VERSION,
 };
 
 impl_runtime_apis! {
@@ -197,6 +198,30 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl crate::fees::EstimateFeesApi<Block, Balance, RuntimeCall> for Runtime {
+		fn estimate_fees(
+			call: RuntimeCall,
+			len: u32,
+		) -> Result<crate::fees::FeeEstimate<Balance>, sp_runtime::DispatchError> {
+			crate::fees::estimate_fees(call, len)
+		}
+	}
+
+	impl crate::health::HealthApi<Block> for Runtime {
+		fn health_summary() -> crate::health::HealthSummary {
+			crate::health::health_summary()
+		}
+	}
+
+	impl crate::trace::TraceApi<Block, RuntimeEvent> for Runtime {
+		fn trace_extrinsic(
+			block: Block,
+			index: u32,
+		) -> Result<crate::trace::ExtrinsicTrace<RuntimeEvent>, crate::trace::TraceError> {
+			crate::trace::trace_extrinsic(block, index)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentCallApi<Block, Balance, RuntimeCall>
 		for Runtime
 	{
@@ -220,6 +245,168 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_ibc_core::runtime_api::IbcHostApi<Block> for Runtime {
+		fn host_consensus_state() -> pallet_ibc_core::HostConsensusState {
+			IbcCore::host_consensus_state()
+		}
+
+		fn client_state_for_self() -> pallet_ibc_core::ClientState {
+			IbcCore::client_state_for_self()
+		}
+	}
+
+	impl pallet_ibc_core::runtime_api::IbcChannelApi<Block, BlockNumber, Balance> for Runtime {
+		fn channel_stats(
+			port_id: pallet_ibc_core::PortId,
+			channel_id: pallet_ibc_core::ChannelId,
+		) -> Option<pallet_ibc_core::ChannelStats<BlockNumber>> {
+			IbcCore::channel_stats(&port_id, &channel_id)
+		}
+
+		fn pallet_pot_balance() -> Balance {
+			IbcCore::pallet_pot_balance()
+		}
+	}
+
+	impl pallet_ibc_core::runtime_api::IbcRelayerApi<Block, AccountId, BlockNumber> for Runtime {
+		fn relayer_stats(relayer: AccountId) -> Option<pallet_ibc_core::RelayerStats<BlockNumber>> {
+			IbcCore::relayers(&relayer).map(|()| IbcCore::relayer_stats(&relayer))
+		}
+
+		fn relayer_leaderboard(
+			limit: u32,
+		) -> Vec<(AccountId, pallet_ibc_core::RelayerStats<BlockNumber>)> {
+			IbcCore::relayer_leaderboard(limit)
+		}
+	}
+
+	impl pallet_history::runtime_api::HistoryApi<Block, AccountId, BlockNumber> for Runtime {
+		fn history_by_account(
+			account: AccountId,
+			kind: Option<pallet_history::HistoryKind>,
+			limit: u32,
+		) -> Vec<pallet_history::HistoryEntry<AccountId, BlockNumber>> {
+			History::history_by_account(&account, kind, limit)
+		}
+
+		fn history_by_key(
+			key: Vec<u8>,
+			kind: Option<pallet_history::HistoryKind>,
+			limit: u32,
+		) -> Vec<pallet_history::HistoryEntry<AccountId, BlockNumber>> {
+			History::history_by_key(&key, kind, limit)
+		}
+	}
+
+	impl pallet_parallel_executor::runtime_api::ParallelExecutorApi<Block> for Runtime {
+		fn metrics() -> pallet_parallel_executor::ParallelMetrics {
+			ParallelExecutor::parallel_metrics()
+		}
+
+		fn queue_depth() -> pallet_parallel_executor::QueueDepth {
+			ParallelExecutor::queue_depth()
+		}
+	}
+
+	#[api_version(2)]
+	impl pallet_sharding::runtime_api::ShardingApi<Block, AccountId, Balance, Hash> for Runtime {
+		fn current_tps() -> u32 {
+			Sharding::current_network_tps()
+		}
+
+		fn shard_info(shard_id: pallet_sharding::ShardId) -> Option<pallet_sharding::ShardInfoOf<AccountId, Balance>> {
+			Sharding::shard_info(shard_id).map(pallet_sharding::ShardInfo::unbounded)
+		}
+
+		fn account_shard(account: AccountId) -> pallet_sharding::ShardId {
+			Sharding::get_account_shard(&account)
+		}
+
+		fn performance_metrics() -> pallet_sharding::PerformanceMetrics {
+			Sharding::performance_metrics()
+		}
+
+		fn parallel_capacity() -> u32 {
+			(0..netchain_primitives::SHARD_COUNT)
+				.filter_map(Sharding::shard_info)
+				.map(|info| info.capacity)
+				.fold(0, u32::saturating_add)
+		}
+
+		fn shard_state_roots() -> Vec<(pallet_sharding::ShardId, Hash)> {
+			Sharding::shard_state_roots()
+		}
+
+		fn shard_snapshot(shard_id: pallet_sharding::ShardId) -> pallet_sharding::ShardSnapshot<AccountId, Balance> {
+			Sharding::shard_snapshot(shard_id)
+		}
+
+		fn cross_shard_call_receipt(call_id: pallet_sharding::CrossShardCallId) -> Option<(bool, Vec<u8>)> {
+			Sharding::cross_shard_call_receipt(call_id).map(|receipt| (receipt.success, receipt.output.into_inner()))
+		}
+
+		fn dry_run_cross_shard(
+			sender: AccountId,
+			to_shard: pallet_sharding::ShardId,
+			recipient: AccountId,
+			amount: Balance,
+			tip: Balance,
+		) -> Result<pallet_sharding::CrossShardDryRun<Balance>, sp_runtime::DispatchError> {
+			Sharding::dry_run_cross_shard(&sender, to_shard, &recipient, amount, tip)
+		}
+
+		fn pallet_pot_balance() -> Balance {
+			Sharding::pallet_pot_balance()
+		}
+
+		fn shard_data_commitment(shard_id: pallet_sharding::ShardId) -> Option<pallet_sharding::DataCommitment<Hash>> {
+			Sharding::shard_data_commitment(shard_id)
+		}
+
+		fn shard_data_chunk(shard_id: pallet_sharding::ShardId, index: u16) -> Option<pallet_sharding::DataChunk<Hash>> {
+			Sharding::shard_data_chunk(shard_id, index)
+		}
+
+		fn oldest_queue_age(shard_id: pallet_sharding::ShardId) -> Option<BlockNumber> {
+			Sharding::oldest_queue_age(shard_id)
+		}
+
+		fn transaction_shard(account: AccountId, call: Vec<u8>) -> Option<pallet_sharding::ShardId> {
+			use codec::DecodeLimit;
+			let call = RuntimeCall::decode_all_with_depth_limit(frame_support::MAX_EXTRINSIC_DEPTH, &mut &call[..]).ok()?;
+			Some(Sharding::transaction_shard(&account, &call))
+		}
+	}
+
+	impl pallet_oracle::runtime_api::OracleApi<Block, BlockNumber, Balance> for Runtime {
+		fn get_history(
+			data_key: pallet_oracle::DataKey,
+			count: u32,
+		) -> Vec<pallet_oracle::Observation<BlockNumber>> {
+			Oracle::get_history(&data_key, count)
+		}
+
+		fn feed_metadata(data_key: pallet_oracle::DataKey) -> Option<pallet_oracle::FeedMetadata> {
+			Oracle::feed_metadata(data_key)
+		}
+
+		fn get_typed_data(
+			data_key: pallet_oracle::DataKey,
+		) -> Option<(pallet_oracle::TypedValue, pallet_oracle::FeedMetadata)> {
+			Oracle::get_typed_data(&data_key)
+		}
+
+		fn get_aggregate(
+			data_key: pallet_oracle::DataKey,
+		) -> Option<pallet_oracle::AggregatedData<BlockNumber>> {
+			Oracle::get_aggregate(&data_key)
+		}
+
+		fn pallet_pot_balance() -> Balance {
+			Oracle::pallet_pot_balance()
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -30,5 +30,10 @@ frame_benchmarking::define_benchmarks!(
 	[pallet_balances, Balances]
 	[pallet_timestamp, Timestamp]
 	[pallet_sudo, Sudo]
+	[pallet_utility, Utility]
 	[pallet_template, Template]
+	[pallet_sharding, Sharding]
+	[pallet_ibc_core, IbcCore]
+	[pallet_oracle, Oracle]
+	[pallet_upgrade_scheduler, UpgradeScheduler]
 );
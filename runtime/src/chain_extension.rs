@@ -0,0 +1,216 @@
+//! # Netchain Chain Extension
+//!
+//! Exposes Netchain's native pallets to ink! contracts. Contracts declare the matching
+//! `#[ink::chain_extension(extension = 0x4e43)]` trait (see `contracts/netchain_escrow`) and
+//! call the functions below by id.
+//!
+//! All inputs and outputs are SCALE-encoded; multiple arguments are encoded as a tuple.
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | `0x0001` oracle latest | `key: Vec<u8>` | `Option<(value: Vec<u8>, confidence: u8, aggregated_at: u32, valid_from: u32, valid_until: u32)>` |
+//! | `0x0002` publish feed | `(key: Vec<u8>, value: Vec<u8>)` | `bool` published |
+//! | `0x0101` IBC packet status | `(port: Vec<u8>, sequence: u64)` | `PacketStatus` (`u8` variant index) |
+//! | `0x0201` account shard | `account: AccountId` | `u8` |
+//! | `0x0202` forward call | `(to_shard: u8, dest: AccountId, data: Vec<u8>, ref_time: u64, proof_size: u64, value: Balance)` | `Option<u64>` call id |
+//! | `0x0203` call receipt | `call_id: u64` | `Option<(success: bool, output: Vec<u8>)>` |
+//! | `0x0204` transfer receipt | `tx_hash: Hash` | `Option<(sender: AccountId, recipient: AccountId, amount: Balance, from_shard: u8, to_shard: u8, settled_at: u32)>` |
+//! | `0x0301` remaining block gas | none | `(ref_time: u64, proof_size: u64)` |
+//! | `0x0302` storage deposit | none | `(held: Balance, per_byte: Balance, per_item: Balance)` |
+//! | `0x0303` fee multiplier | none | `u128` (`FixedU128` inner value, `10^18` is 1.0) |
+//!
+//! The oracle returns the latest readable aggregate, which became readable at `valid_from`,
+//! once its dispute window was over, and counts as fresh until `valid_until`; contracts with
+//! stricter freshness needs compare `aggregated_at` with the current block themselves.
+//!
+//! The `0x03xx` functions report chain conditions so contracts can adapt, e.g. their batch
+//! sizes. Remaining block gas is what normal transactions may still use in the current block,
+//! the calling transaction's declared weight already deducted. The storage deposit is what the
+//! calling contract currently has held for its storage, with the current deposit prices.
+//!
+//! A cross-shard transfer has a receipt once its amount reached the recipient, so contracts can
+//! act on a specific transfer, e.g. release an escrow once the funds arrived on another shard;
+//! transfers still queued, dead-lettered or refunded have none, and receipts are pruned
+//! `CrossShardTxRecordLifetime` blocks after settlement.
+//!
+//! Forwarded calls are made by the calling contract, which pays the cross-shard fee and is
+//! the caller seen by the destination contract. The gas the forwarded call is given is charged
+//! to the calling contract up front; calls above `MaxCrossShardCallGas` or with more than
+//! `MaxCrossShardCallDataLen` bytes of input are not forwarded. Only contracts authorized with
+//! `Oracle::authorize_contract_feed` can publish, and only into their designated key.
+
+use alloc::vec::Vec;
+use codec::{Encode, MaxEncodedLen};
+use frame_support::{
+	dispatch::DispatchClass,
+	traits::{fungible::InspectHold, Get},
+	weights::Weight,
+};
+use pallet_oracle::WeightInfo as _;
+use pallet_sharding::WeightInfo as _;
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RegisteredChainExtension, RetVal,
+};
+use sp_runtime::{DispatchError, FixedPointNumber};
+
+use super::{AccountId, Balance, Balances, Hash, IbcCore, Oracle, Runtime, RuntimeHoldReason, Sharding};
+
+/// Extension id under which the Netchain functions are registered ("NC")
+pub const NETCHAIN_EXTENSION_ID: u16 = 0x4e43;
+
+/// Read the latest aggregated oracle value for a key
+pub const FUNC_ORACLE_LATEST: u16 = 0x0001;
+/// Publish a value into the calling contract's designated oracle data key
+pub const FUNC_ORACLE_PUBLISH: u16 = 0x0002;
+/// Read the lifecycle status of a packet sent by this chain
+pub const FUNC_IBC_PACKET_STATUS: u16 = 0x0101;
+/// Shard an account is assigned to
+pub const FUNC_ACCOUNT_SHARD: u16 = 0x0201;
+/// Forward a contract call to another shard on behalf of the calling contract
+pub const FUNC_FORWARD_CALL: u16 = 0x0202;
+/// Receipt of an executed cross-shard call
+pub const FUNC_CALL_RECEIPT: u16 = 0x0203;
+/// Receipt of a settled cross-shard transfer
+pub const FUNC_TRANSFER_RECEIPT: u16 = 0x0204;
+/// Weight normal transactions may still use in the current block
+pub const FUNC_REMAINING_BLOCK_GAS: u16 = 0x0301;
+/// Storage deposit held for the calling contract, and the deposit prices
+pub const FUNC_STORAGE_DEPOSIT: u16 = 0x0302;
+/// Current transaction fee multiplier
+pub const FUNC_FEE_MULTIPLIER: u16 = 0x0303;
+
+/// Weight of each byte of input decoded from the calling contract's memory (estimated)
+const INPUT_BYTE_WEIGHT: Weight = Weight::from_parts(100, 1);
+
+/// Input of [`FUNC_FORWARD_CALL`]: destination shard and contract, input, gas limit and value
+type ForwardCallInput = (u8, AccountId, pallet_sharding::CrossShardCallData, u64, u64, Balance);
+
+/// Fail unless `len` bytes of input can be an encoded `T`
+fn ensure_input_fits<T: MaxEncodedLen>(len: u32) -> Result<(), DispatchError> {
+	if len as usize > T::max_encoded_len() {
+		return Err(DispatchError::Other("NetchainChainExtension: input too long"));
+	}
+	Ok(())
+}
+
+/// Chain extension giving contracts access to the oracle, IBC and sharding pallets and to
+/// chain conditions
+#[derive(Default)]
+pub struct NetchainChainExtension;
+
+impl ChainExtension<Runtime> for NetchainChainExtension {
+	fn call<E: Ext<T = Runtime>>(
+		&mut self,
+		env: Environment<E, InitState>,
+	) -> Result<RetVal, DispatchError> {
+		let func_id = env.func_id();
+		let mut env = env.buf_in_buf_out();
+		let read_weight = <Runtime as frame_system::Config>::DbWeight::get().reads(1);
+
+		match func_id {
+			FUNC_ORACLE_LATEST => {
+				env.charge_weight(read_weight)?;
+				let key: Vec<u8> = env.read_as_unbounded(env.in_len())?;
+				let reading = pallet_oracle::AggregatedDataStorage::<Runtime>::get(&key)
+					.map(|data| (data.value, data.confidence, data.aggregated_at, data.valid_from, data.valid_until));
+				env.write(&reading.encode(), false, None)?;
+			},
+			FUNC_ORACLE_PUBLISH => {
+				env.charge_weight(<Runtime as pallet_oracle::Config>::WeightInfo::provide_data(
+					env.in_len(),
+					<Runtime as pallet_oracle::Config>::MaxDataSources::get(),
+				))?;
+				let (key, value): (Vec<u8>, Vec<u8>) = env.read_as_unbounded(env.in_len())?;
+				let contract = env.ext().address().clone();
+				let published = Oracle::publish_contract_value(&contract, key, value).is_ok();
+				env.write(&published.encode(), false, None)?;
+			},
+			FUNC_IBC_PACKET_STATUS => {
+				env.charge_weight(read_weight.saturating_add(read_weight))?;
+				let (port, sequence): (Vec<u8>, u64) = env.read_as_unbounded(env.in_len())?;
+				let status = match pallet_ibc_core::PortId::try_from(port) {
+					Ok(port) => IbcCore::packet_status(&port, sequence),
+					Err(_) => pallet_ibc_core::PacketStatus::Unknown,
+				};
+				env.write(&status.encode(), false, None)?;
+			},
+			FUNC_ACCOUNT_SHARD => {
+				let len = env.in_len();
+				ensure_input_fits::<AccountId>(len)?;
+				env.charge_weight(read_weight.saturating_add(INPUT_BYTE_WEIGHT.saturating_mul(len.into())))?;
+				let account: AccountId = env.read_as_unbounded(len)?;
+				env.write(&Sharding::get_account_shard(&account).encode(), false, None)?;
+			},
+			FUNC_FORWARD_CALL => {
+				env.charge_weight(
+					<Runtime as pallet_sharding::Config>::WeightInfo::execute_cross_shard_call(),
+				)?;
+				ensure_input_fits::<ForwardCallInput>(env.in_len())?;
+				let (to_shard, dest, data, ref_time, proof_size, value): ForwardCallInput =
+					env.read_as_unbounded(env.in_len())?;
+
+				// The calling contract pays for the gas the forwarded call is given
+				let gas_limit = Weight::from_parts(ref_time, proof_size);
+				let call_id = if gas_limit.all_lte(<Runtime as pallet_sharding::Config>::MaxCrossShardCallGas::get()) {
+					let charged = env.charge_weight(gas_limit)?;
+					let caller = env.ext().address().clone();
+					let call_id =
+						Sharding::queue_cross_shard_call(caller, to_shard, dest, data.into_inner(), gas_limit, value)
+							.ok();
+					if call_id.is_none() {
+						env.adjust_weight(charged, Weight::zero());
+					}
+					call_id
+				} else {
+					None
+				};
+				env.write(&call_id.encode(), false, None)?;
+			},
+			FUNC_CALL_RECEIPT => {
+				env.charge_weight(read_weight)?;
+				let call_id: u64 = env.read_as()?;
+				let receipt = Sharding::cross_shard_call_receipt(call_id)
+					.map(|receipt| (receipt.success, receipt.output.into_inner()));
+				env.write(&receipt.encode(), false, None)?;
+			},
+			FUNC_TRANSFER_RECEIPT => {
+				env.charge_weight(read_weight)?;
+				let tx_hash: Hash = env.read_as()?;
+				let receipt = Sharding::cross_shard_tx_receipt(tx_hash);
+				env.write(&receipt.encode(), false, None)?;
+			},
+			FUNC_REMAINING_BLOCK_GAS => {
+				env.charge_weight(read_weight)?;
+				let weights = <Runtime as frame_system::Config>::BlockWeights::get();
+				let limit = weights.get(DispatchClass::Normal).max_total.unwrap_or(weights.max_block);
+				let consumed = *frame_system::Pallet::<Runtime>::block_weight().get(DispatchClass::Normal);
+				let remaining = limit.saturating_sub(consumed);
+				env.write(&(remaining.ref_time(), remaining.proof_size()).encode(), false, None)?;
+			},
+			FUNC_STORAGE_DEPOSIT => {
+				env.charge_weight(read_weight)?;
+				let contract = env.ext().address().clone();
+				let held = Balances::balance_on_hold(
+					&RuntimeHoldReason::Contracts(pallet_contracts::HoldReason::StorageDepositReserve),
+					&contract,
+				);
+				let per_byte = <Runtime as pallet_contracts::Config>::DepositPerByte::get();
+				let per_item = <Runtime as pallet_contracts::Config>::DepositPerItem::get();
+				env.write(&(held, per_byte, per_item).encode(), false, None)?;
+			},
+			FUNC_FEE_MULTIPLIER => {
+				env.charge_weight(read_weight)?;
+				let multiplier = pallet_transaction_payment::NextFeeMultiplier::<Runtime>::get();
+				env.write(&multiplier.into_inner().encode(), false, None)?;
+			},
+			_ => return Err(DispatchError::Other("NetchainChainExtension: unknown function")),
+		}
+
+		Ok(RetVal::Converging(0))
+	}
+}
+
+impl RegisteredChainExtension<Runtime> for NetchainChainExtension {
+	const ID: u16 = NETCHAIN_EXTENSION_ID;
+}
+
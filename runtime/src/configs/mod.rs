@@ -24,9 +24,15 @@
 // For more information, please refer to <http://unlicense.org>
 
 // Substrate and Polkadot dependencies
+use alloc::vec::Vec;
 use frame_support::{
-	derive_impl, parameter_types,
-	traits::{ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, VariantCountOf, Get, KeyOwnerProofSystem, Randomness},
+	derive_impl,
+	dynamic_params::{dynamic_pallet_params, dynamic_params},
+	parameter_types,
+	traits::{
+		tokens::ConversionToAssetBalance, AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU32, ConstU64,
+		ConstU8, Contains, VariantCountOf, Get, KeyOwnerProofSystem, Randomness,
+	},
 	weights::{
 		constants::{RocksDbWeight, WEIGHT_REF_TIME_PER_SECOND},
 		IdentityFee, Weight,
@@ -34,6 +40,7 @@ use frame_support::{
 	PalletId,
 };
 use frame_system::limits::{BlockLength, BlockWeights};
+use pallet_ibc_core::IbcRouter;
 use pallet_session::historical as pallet_session_historical;
 use pallet_transaction_payment::{ConstFeeMultiplier, FungibleAdapter, Multiplier};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -41,19 +48,19 @@ use sp_consensus_babe::AuthorityId as BabeId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	curve::PiecewiseLinear,
-	traits::{One, OpaqueKeys, SaturatedConversion, Zero},
+	traits::{CheckedDiv, One, OpaqueKeys, SaturatedConversion, StaticLookup, Zero},
 	transaction_validity::TransactionPriority,
-	Perbill, Perquintill,
+	FixedPointNumber, FixedU128, Perbill, Perquintill,
 };
 use sp_staking::{SessionIndex, EraIndex};
 use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-	AccountId, Aura, Babe, Balance, Balances, Block, BlockNumber, Contracts, Hash, Nonce, PalletInfo, Runtime,
-	RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask,
-	Session, SessionKeys, Sharding, Staking, System, ENDOWMENT, EPOCH_DURATION_IN_BLOCKS, EXISTENTIAL_DEPOSIT, 
-	SLOT_DURATION, STASH, VERSION,
+	AccountId, Assets, Aura, Babe, Balance, Balances, Block, BlockNumber, Contracts, Hash, History, InterchainAccounts, Nonce,
+	OracleIbc, OriginCaller, PalletInfo, Runtime, RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask,
+	Safeguard, Session, SessionKeys, Sharding, Staking, System, ENDOWMENT, EPOCH_DURATION_IN_BLOCKS, EXISTENTIAL_DEPOSIT, HOURS,
+	DAYS, MILLI_UNIT, MINUTES, Oracle, UNIT, SLOT_DURATION, STASH, VERSION,
 };
 
 const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
@@ -211,12 +218,103 @@ impl pallet_transaction_payment::Config for Runtime {
 	type WeightInfo = pallet_transaction_payment::weights::SubstrateWeight<Runtime>;
 }
 
+// Fungible assets and fee payment in them
+parameter_types! {
+	/// Deposit for creating an asset class
+	pub const AssetDeposit: Balance = 10 * UNIT;
+	/// Deposit for an account holding an asset
+	pub const AssetAccountDeposit: Balance = MILLI_UNIT;
+	/// Base deposit for asset metadata
+	pub const AssetMetadataDepositBase: Balance = UNIT;
+	/// Metadata deposit per byte of name and symbol
+	pub const AssetMetadataDepositPerByte: Balance = MILLI_UNIT;
+	/// Deposit for an approval to spend assets
+	pub const AssetApprovalDeposit: Balance = MILLI_UNIT;
+	/// Maximum length of asset names and symbols
+	pub const AssetStringLimit: u32 = 50;
+	/// Accounts and approvals removed per call while destroying an asset
+	pub const AssetRemoveItemsLimit: u32 = 1000;
+	/// Suffix of a fee asset's oracle key after its symbol, e.g. `USDC/USD`
+	pub FeeAssetPriceKeySuffix: Vec<u8> = b"/USD".to_vec();
+	/// Oldest price fees are converted into assets at (5 minutes = 100 blocks)
+	pub const FeeAssetMaxPriceAge: BlockNumber = 100;
+}
+
+impl pallet_assets::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type RemoveItemsLimit = AssetRemoveItemsLimit;
+	type AssetId = u32;
+	type AssetIdParameter = codec::Compact<u32>;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = AssetMetadataDepositBase;
+	type MetadataDepositPerByte = AssetMetadataDepositPerByte;
+	type ApprovalDeposit = AssetApprovalDeposit;
+	type StringLimit = AssetStringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type CallbackHandle = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
+/// Converts native fees into a fee asset at the oracle's NET/USD and `<symbol>/USD` prices
+///
+/// Only assets whose metadata `ForceOrigin` set and froze pay fees, so an asset owner cannot
+/// rename its asset after a valuable one to borrow its feed. Conversion fails, making the
+/// transaction invalid, while either price is missing, zero or older than
+/// `FeeAssetMaxPriceAge`; the sender can then pay in the native token by naming no asset.
+pub struct OracleAssetConversion;
+impl ConversionToAssetBalance<Balance, u32, Balance> for OracleAssetConversion {
+	type Error = ();
+
+	fn to_asset_balance(balance: Balance, asset_id: u32) -> Result<Balance, ()> {
+		let metadata = pallet_assets::Metadata::<Runtime>::get(asset_id);
+		if !metadata.is_frozen {
+			return Err(());
+		}
+		let key = [metadata.symbol.as_slice(), FeeAssetPriceKeySuffix::get().as_slice()].concat();
+		let native_price = fresh_oracle_price(&NativeUsdPriceKey::get(), FeeAssetMaxPriceAge::get()).ok_or(())?;
+		let asset_price = fresh_oracle_price(&key, FeeAssetMaxPriceAge::get()).ok_or(())?;
+
+		// Asset per native token, rescaled from the native to the asset's decimals
+		let asset_unit = 10u128.checked_pow(metadata.decimals.into()).ok_or(())?;
+		let rate = native_price
+			.checked_div(&asset_price)
+			.ok_or(())?
+			.saturating_mul(FixedU128::saturating_from_rational(asset_unit, UNIT));
+		Ok(rate.saturating_mul_int(balance))
+	}
+}
+
+impl pallet_asset_tx_payment::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Fungibles = Assets;
+	/// Fees paid in assets are burned, like native fees
+	type OnChargeAssetTransaction = pallet_asset_tx_payment::FungiblesAdapter<OracleAssetConversion, ()>;
+	type WeightInfo = ();
+}
+
 impl pallet_sudo::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
 	type WeightInfo = pallet_sudo::weights::SubstrateWeight<Runtime>;
 }
 
+/// `force_batch` dispatches every call and reports each one's outcome in order with
+/// `ItemCompleted` or `ItemFailed`, so clients can tell which items of a batch failed
+impl pallet_utility::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
 // Session and Historical configurations
 parameter_types! {
 	pub const Period: u32 = 6 * HOURS;
@@ -323,6 +421,31 @@ parameter_types! {
 	pub const StorageDepositLimit: Balance = Balance::MAX >> 1;
 	/// Ultra-low instantiation fee
 	pub const InstantiationFee: Balance = 1;
+	/// Active stake that entitles an account to upload contract code without a grant
+	pub const MinContractUploaderStake: Balance = 1_000 * UNIT;
+}
+
+/// Contract code may be uploaded by allowlisted accounts and by stakers above the threshold
+pub type ContractUploadOrigin = frame_support::traits::EitherOf<
+	pallet_contract_permissions::EnsureAllowlisted<Runtime>,
+	pallet_contract_permissions::EnsureStakeAtLeast<Staking, MinContractUploaderStake>,
+>;
+
+/// Contract upload permissions, managed by governance
+impl pallet_contract_permissions::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = ();
+}
+
+/// Emergency pause of cross-shard, IBC and oracle activity
+///
+/// Pausing is incident response, so root dispatches it directly instead of waiting out an
+/// upgrade or referendum.
+impl pallet_safeguard::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type PauseOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = ();
 }
 
 /// Contracts pallet configuration optimized for ultra-low fees
@@ -350,11 +473,12 @@ impl pallet_contracts::Config for Runtime {
 	type CallStack = [pallet_contracts::Frame<Runtime>; 1024];
 	type WeightPrice = pallet_transaction_payment::Pallet<Runtime>;
 	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Runtime>;
-	type ChainExtension = ();
-	type AddressGenerator = pallet_contracts::DefaultAddressGenerator;
+	type ChainExtension = crate::chain_extension::NetchainChainExtension;
+	/// Contracts land in their deployer's shard, or the one their salt names
+	type AddressGenerator = crate::contract_address::ShardAwareAddressGenerator;
 	type MaxDebugBufferLen = ConstU32<262144>; // 256 KB debug buffer
 	type UnsafeUnstableInterface = ConstBool<false>; // Production safety
-	type UploadOrigin = frame_system::EnsureRoot<AccountId>;
+	type UploadOrigin = ContractUploadOrigin;
 	type InstantiateOrigin = frame_system::EnsureSigned<AccountId>;
 	type CodeHashLockupDepositPercent = CodeHashLockupDepositPercent;
 	type MaxDelegateDependencies = ConstU32<32>;
@@ -366,27 +490,226 @@ impl pallet_contracts::Config for Runtime {
 	type Xcm = ();
 }
 
+/// Performance parameters root can tune with `Parameters::set_parameter`, no runtime upgrade
+/// needed; each one reads as its default below until set.
+#[dynamic_params(RuntimeParameters, pallet_parameters::Parameters::<Runtime>)]
+pub mod dynamic_params {
+	use super::*;
+
+	#[dynamic_pallet_params]
+	#[codec(index = 0)]
+	pub mod sharding {
+		/// Target TPS per shard (25,000 each = 100,000 total), the capacity of shards created
+		/// from then on
+		#[codec(index = 0)]
+		pub static TargetTpsPerShard: u32 = 25_000;
+
+		/// Cross-shard transaction fee while no oracle quote is available
+		#[codec(index = 1)]
+		pub static CrossShardFee: Balance = 10;
+	}
+
+	#[dynamic_pallet_params]
+	#[codec(index = 1)]
+	pub mod parallel_executor {
+		/// Upper bound on parallel execution workers
+		#[codec(index = 0)]
+		pub static MaxWorkers: u32 = pallet_parallel_executor::MAX_WORKERS;
+
+		/// Transactions per parallel batch
+		#[codec(index = 1)]
+		pub static BatchSize: u32 = pallet_parallel_executor::PARALLEL_BATCH_SIZE;
+	}
+}
+
+impl pallet_parameters::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeParameters = RuntimeParameters;
+	type AdminOrigin = AsEnsureOriginWithArg<frame_system::EnsureRoot<AccountId>>;
+	type WeightInfo = ();
+}
+
 // High-Performance Sharding Configuration
 parameter_types! {
 	/// Maximum validators per shard for optimal performance
 	pub const MaxValidatorsPerShard: u32 = 25; // 100 total validators across 4 shards
-	/// Target TPS per shard (25,000 each = 100,000 total)
-	pub const TargetTpsPerShard: u32 = 25_000;
-	/// Cross-shard transaction fee (ultra-low)
-	pub const CrossShardFee: Balance = 10; // 10 units for cross-shard txs
+	/// Share of a failed cross-shard transfer kept as penalty
+	pub const CrossShardFailurePenalty: Perbill = Perbill::from_percent(1);
+	/// Undeliverable cross-shard transfers wait a day to be claimed before being refunded
+	pub const CrossShardDeadLetterTimeout: BlockNumber = DAYS;
+	/// Gas a forwarded contract call can be given, 5% of the block
+	pub MaxCrossShardCallGas: Weight = Perbill::from_percent(5) * RuntimeBlockWeights::get().max_block;
+	/// Receipts of forwarded contract calls are kept for a day
+	pub const CrossShardCallReceiptLifetime: BlockNumber = DAYS;
+	/// Bytes of a forwarded contract call's output kept in its receipt
+	pub const MaxCrossShardCallOutputLen: u32 = 16 * 1024;
+	/// Statuses and receipts of settled or failed cross-shard transfers are kept for a week
+	pub const CrossShardTxRecordLifetime: BlockNumber = 7 * DAYS;
+	/// Surge fee cap for overloaded shards (up to 10x the cross-shard fee)
+	pub const MaxSurgeMultiplier: u32 = 10;
+	/// Cross-shard settlements per shard per block; the remainder carries over
+	pub const MaxSettlementsPerBlock: u32 = 500;
+	/// Dead letters, call receipts and transfer records expiring per block, twice the
+	/// settlements of every shard so expiries keep pace with them
+	pub const MaxCrossShardExpiringPerBlock: u32 = 4_000;
+	/// Cross-shard queues whose oldest transfer waited five minutes are escalated
+	pub const MaxCrossShardQueueLatency: BlockNumber = 5 * MINUTES;
+	/// Reward per delivered cross-shard transfer for processing queues, a fifth of the default fee
+	pub const CrossShardSettlementReward: Balance = 2;
+	/// Settlement rewards paid per block, across shards
+	pub const MaxSettlementRewardPerBlock: Balance = 1_000;
+	/// Account moves between shards take effect hourly
+	pub const ShardEpochLength: BlockNumber = HOURS;
+	/// Accounts announced to move or in their grace window at once
+	pub const MaxShardMigrationsPerEpoch: u32 = 256;
+	/// Accounts a shard split examines per block
+	pub const MaxShardSplitAccountsPerBlock: u32 = 256;
+	/// Bytes of block data erasure-coded per shard each block
+	pub const MaxShardDataLen: u32 = 64 * 1024;
+	/// Cross-shard transfers waiting per shard, four blocks of settlements
+	pub const MaxCrossShardQueueLen: u32 = 2_000;
+	/// Forwarded contract calls waiting per shard
+	pub const MaxCrossShardCallQueueLen: u32 = 32;
 	/// Sharding pallet identifier
 	pub const ShardingPalletId: PalletId = PalletId(*b"netshrd!");
+	/// Oracle key pricing NET in USD, used to quote cross-shard fees and convert fees into assets
+	pub NativeUsdPriceKey: Vec<u8> = b"NET/USD".to_vec();
+	/// USD cost a cross-shard transaction should have ($0.0001)
+	pub CrossShardFeeTargetUsd: FixedU128 = FixedU128::from_rational(1, 10_000);
+	/// Oldest NET/USD price a fee is quoted from (5 minutes = 100 blocks)
+	pub const CrossShardFeeMaxPriceAge: BlockNumber = 100;
+}
+
+/// Quotes the cross-shard fee worth `CrossShardFeeTargetUsd` at the oracle's NET/USD price
+///
+/// No quote, and so the constant `CrossShardFee`, while the price is missing, zero or older
+/// than `CrossShardFeeMaxPriceAge`. The sharding pallet clamps quotes to governance-set bounds.
+pub struct OracleCrossShardFee;
+impl pallet_sharding::CrossShardFeeQuote<Balance> for OracleCrossShardFee {
+	fn quote() -> Option<Balance> {
+		let price = fresh_oracle_price(&NativeUsdPriceKey::get(), CrossShardFeeMaxPriceAge::get())?;
+		let tokens = CrossShardFeeTargetUsd::get().checked_div(&price)?;
+		Some(tokens.saturating_mul_int(UNIT))
+	}
+}
+
+/// Latest oracle price of `key`, unless it is zero or older than `max_age` blocks
+fn fresh_oracle_price(key: &[u8], max_age: BlockNumber) -> Option<FixedU128> {
+	let (price, updated_at) = <Oracle as pallet_oracle::PriceProvider<BlockNumber>>::price(&key.to_vec())?;
+	if price.is_zero() || System::block_number().saturating_sub(updated_at) > max_age {
+		return None;
+	}
+	Some(price)
+}
+
+/// Registers rotated shard validator keys with `pallet_session`
+pub struct SessionKeysRegistrar;
+impl pallet_sharding::SessionKeyRegistrar<AccountId> for SessionKeysRegistrar {
+	fn register_keys(who: &AccountId, keys: Vec<u8>) -> sp_runtime::DispatchResult {
+		let keys = <SessionKeys as codec::Decode>::decode(&mut &keys[..])
+			.map_err(|_| sp_runtime::DispatchError::Other("invalid session keys"))?;
+		Session::set_keys(RuntimeOrigin::signed(who.clone()), keys, Vec::new())
+	}
+}
+
+/// Executes forwarded cross-shard contract calls through `pallet_contracts`
+pub struct ContractsCallExecutor;
+impl pallet_sharding::CrossShardCallExecutor<AccountId, Balance> for ContractsCallExecutor {
+	fn execute(
+		caller: &AccountId,
+		dest: &AccountId,
+		value: Balance,
+		gas_limit: Weight,
+		data: Vec<u8>,
+	) -> (Result<Vec<u8>, sp_runtime::DispatchError>, Weight) {
+		let result = Contracts::bare_call(
+			caller.clone(),
+			dest.clone(),
+			value,
+			gas_limit,
+			None,
+			data,
+			pallet_contracts::DebugInfo::Skip,
+			pallet_contracts::CollectEvents::Skip,
+			pallet_contracts::Determinism::Enforced,
+		);
+		// A reverted call is a failure even though the contract returned normally
+		let output = result.result.and_then(|ret| {
+			if ret.did_revert() {
+				Err(sp_runtime::DispatchError::Other("cross-shard call reverted"))
+			} else {
+				Ok(ret.data)
+			}
+		});
+		(output, result.gas_consumed)
+	}
+}
+
+/// Chain-operational calls, tagged with the system shard once governance reserves one:
+/// staking, session keys and shard validator duties, governance (sudo and runtime upgrades)
+/// and IBC client, connection and channel handshakes
+pub struct SystemCalls;
+impl Contains<RuntimeCall> for SystemCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(
+			call,
+			RuntimeCall::Staking(..) |
+				RuntimeCall::Session(..) |
+				RuntimeCall::Sudo(..) |
+				RuntimeCall::UpgradeScheduler(..) |
+				RuntimeCall::Sharding(
+					pallet_sharding::Call::join_shard { .. } |
+						pallet_sharding::Call::rotate_and_register_keys { .. }
+				) | RuntimeCall::IbcCore(
+					pallet_ibc_core::Call::create_client { .. } |
+						pallet_ibc_core::Call::update_client { .. } |
+						pallet_ibc_core::Call::submit_misbehaviour { .. } |
+						pallet_ibc_core::Call::connection_open_init { .. } |
+						pallet_ibc_core::Call::channel_open_init { .. } |
+						pallet_ibc_core::Call::channel_open_localhost { .. } |
+						pallet_ibc_core::Call::channel_upgrade_init { .. } |
+						pallet_ibc_core::Call::channel_upgrade_try { .. } |
+						pallet_ibc_core::Call::channel_upgrade_ack { .. } |
+						pallet_ibc_core::Call::channel_upgrade_confirm { .. }
+				)
+		)
+	}
 }
 
 /// Sharding pallet configuration for massive scalability
 impl pallet_sharding::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type Currency = Balances;
 	type MaxValidatorsPerShard = MaxValidatorsPerShard;
-	type TargetTpsPerShard = TargetTpsPerShard;
-	type CrossShardFee = CrossShardFee;
+	type TargetTpsPerShard = dynamic_params::sharding::TargetTpsPerShard;
+	type CrossShardFee = dynamic_params::sharding::CrossShardFee;
 	type PalletId = ShardingPalletId;
-	type WeightInfo = ();
+	type CallExecutor = ContractsCallExecutor;
+	type MaxCrossShardCallGas = MaxCrossShardCallGas;
+	type CrossShardCallReceiptLifetime = CrossShardCallReceiptLifetime;
+	type MaxCrossShardCallOutputLen = MaxCrossShardCallOutputLen;
+	type CrossShardTxRecordLifetime = CrossShardTxRecordLifetime;
+	type FailurePenalty = CrossShardFailurePenalty;
+	type DeadLetterTimeout = CrossShardDeadLetterTimeout;
+	type MaxExpiringPerBlock = MaxCrossShardExpiringPerBlock;
+	type FeeQuote = OracleCrossShardFee;
+	type SurgeFee = pallet_sharding::CapacityMultipleSurgeFee<MaxSurgeMultiplier>;
+	type History = History;
+	type Safeguard = Safeguard;
+	type SessionKeys = SessionKeysRegistrar;
+	type MaxSettlementsPerBlock = MaxSettlementsPerBlock;
+	type MaxQueueLatency = MaxCrossShardQueueLatency;
+	type SettlementReward = CrossShardSettlementReward;
+	type MaxSettlementRewardPerBlock = MaxSettlementRewardPerBlock;
+	type EpochLength = ShardEpochLength;
+	type MaxMigrationsPerEpoch = MaxShardMigrationsPerEpoch;
+	type MaxSplitAccountsPerBlock = MaxShardSplitAccountsPerBlock;
+	type MaxShardDataLen = MaxShardDataLen;
+	type MaxCrossShardQueueLen = MaxCrossShardQueueLen;
+	type MaxCrossShardCallQueueLen = MaxCrossShardCallQueueLen;
+	type SystemCalls = SystemCalls;
+	type WeightInfo = pallet_sharding::weights::SubstrateWeight<Runtime>;
 }
 
 /// Configure the pallet-template in pallets/template.
@@ -404,12 +727,53 @@ parameter_types! {
 	pub const MaxIbcConnections: u32 = 200;
 	/// Maximum IBC channels per connection
 	pub const MaxIbcChannels: u32 = 500;
-	/// Ultra-low IBC client creation fee (10 units = ~$0.0001)
-	pub const IbcClientCreationFee: Balance = 10;
+	/// Deposit reserved per IBC client, refunded when it is closed
+	pub const IbcClientDeposit: Balance = UNIT;
 	/// Ultra-low cross-chain packet transmission fee (5 units = ~$0.00005)
 	pub const IbcPacketTransmissionFee: Balance = 5;
+	/// Maximum IBC packet payload (64KB)
+	pub const MaxIbcPacketDataSize: u32 = 64 * 1024;
+	/// Maximum encoded IBC acknowledgement of a received packet (8KB)
+	pub const MaxIbcAcknowledgementLen: u32 = 8 * 1024;
+	/// IBC channel rate-limit window (10 blocks = 30 seconds)
+	pub const IbcRateLimitWindow: BlockNumber = 10;
+	/// Maximum packets per channel per window
+	pub const MaxIbcPacketsPerWindow: u32 = 1_000;
+	/// Maximum payload bytes per channel per window (8MB)
+	pub const MaxIbcBytesPerWindow: u32 = 8 * 1024 * 1024;
 	/// IBC pallet identifier
 	pub const IbcPalletId: PalletId = PalletId(*b"netchain_ibc");
+	/// Chain identifier counterparties use for Netchain
+	pub IbcHostChainId: Vec<u8> = b"netchain".to_vec();
+	/// Validator unbonding period advertised to counterparties (7 days)
+	pub const IbcHostUnbondingPeriod: u64 = 7 * 24 * 60 * 60;
+	/// Successful packet deliveries and acknowledgements by registered relayers refunded per block
+	pub const MaxFeelessIbcRelaysPerBlock: u32 = 500;
+	/// Least bond a relayer reserves to register, slashed if governance removes it
+	pub const IbcMinRelayerBond: Balance = UNIT;
+}
+
+/// Hash of the validator set queued for the next session
+pub struct QueuedValidatorsHash;
+impl Get<sp_core::H256> for QueuedValidatorsHash {
+	fn get() -> sp_core::H256 {
+		use sp_runtime::traits::Hash as _;
+		let validators: Vec<AccountId> =
+			Session::queued_keys().into_iter().map(|(validator, _)| validator).collect();
+		sp_runtime::traits::BlakeTwo256::hash_of(&validators)
+	}
+}
+
+/// Id and authorities of the GRANDPA authority set currently finalizing blocks
+pub struct GrandpaAuthoritySet;
+impl Get<(u64, pallet_ibc_core::light_client::grandpa::AuthorityList)> for GrandpaAuthoritySet {
+	fn get() -> (u64, pallet_ibc_core::light_client::grandpa::AuthorityList) {
+		let authorities = pallet_grandpa::Pallet::<Runtime>::grandpa_authorities()
+			.into_iter()
+			.map(|(id, weight)| (id.into(), weight))
+			.collect();
+		(pallet_grandpa::CurrentSetId::<Runtime>::get(), authorities)
+	}
 }
 
 /// IBC Core pallet configuration for cross-chain communication
@@ -419,9 +783,117 @@ impl pallet_ibc_core::Config for Runtime {
 	type MaxClients = MaxIbcClients;
 	type MaxConnections = MaxIbcConnections;
 	type MaxChannels = MaxIbcChannels;
-	type ClientCreationFee = IbcClientCreationFee;
+	type ClientDeposit = IbcClientDeposit;
 	type PacketTransmissionFee = IbcPacketTransmissionFee;
+	type MaxPacketDataSize = MaxIbcPacketDataSize;
+	type MaxAcknowledgementLen = MaxIbcAcknowledgementLen;
+	type RateLimitWindow = IbcRateLimitWindow;
+	type MaxPacketsPerWindow = MaxIbcPacketsPerWindow;
+	type MaxBytesPerWindow = MaxIbcBytesPerWindow;
+	type HostChainId = IbcHostChainId;
+	type HostUnbondingPeriod = IbcHostUnbondingPeriod;
+	type HostNextValidatorsHash = QueuedValidatorsHash;
+	type HostAuthoritySet = GrandpaAuthoritySet;
+	type History = History;
+	type Safeguard = Safeguard;
+	type Router = IbcApplications;
+	type ChannelUpgradeOrigin = frame_system::EnsureRoot<AccountId>;
+	type ClientRemovalOrigin = frame_system::EnsureRoot<AccountId>;
+	type RelayerOrigin = frame_system::EnsureRoot<AccountId>;
+	type PortOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxFeelessRelaysPerBlock = MaxFeelessIbcRelaysPerBlock;
+	type MinRelayerBond = IbcMinRelayerBond;
 	type PalletId = IbcPalletId;
+	type WeightInfo = pallet_ibc_core::weights::SubstrateWeight<Runtime>;
+}
+
+/// Ports of the chain's own IBC applications and their owners: the interchain account host
+/// and oracle ports belong to their pallets, and `transfer` is held by the IBC pallet until
+/// a token transfer application takes it over
+pub struct IbcSystemPorts;
+impl Get<Vec<(pallet_ibc_core::PortId, AccountId)>> for IbcSystemPorts {
+	fn get() -> Vec<(pallet_ibc_core::PortId, AccountId)> {
+		alloc::vec![
+			(pallet_ibc_core::PortId::truncate_from(b"transfer".to_vec()), pallet_ibc_core::Pallet::<Runtime>::account_id()),
+			(InterchainAccounts::host_port(), InterchainAccounts::account_id()),
+			(OracleIbc::oracle_port(), OracleIbc::account_id()),
+		]
+	}
+}
+
+/// Routes packets on the oracle port to the oracle hub and every other port to interchain
+/// accounts
+pub struct IbcApplications;
+impl IbcRouter<AccountId> for IbcApplications {
+	fn on_recv_packet(packet: &pallet_ibc_core::Packet, relayer: &AccountId) -> pallet_ibc_core::Acknowledgement {
+		if packet.destination_port.as_slice() == pallet_oracle_ibc::ORACLE_PORT {
+			OracleIbc::on_recv_packet(packet, relayer)
+		} else {
+			InterchainAccounts::on_recv_packet(packet, relayer)
+		}
+	}
+
+	fn max_recv_packet_weight() -> Weight {
+		OracleIbc::max_recv_packet_weight().max(InterchainAccounts::max_recv_packet_weight())
+	}
+}
+
+parameter_types! {
+	/// Maximum calls in one interchain account packet
+	pub const MaxIcaCallsPerPacket: u32 = 16;
+	/// Maximum entries of a per-channel interchain account call filter
+	pub const MaxIcaFilterEntries: u32 = 32;
+	/// Maximum weight of the calls in one interchain account packet (a tenth of a second)
+	pub const MaxIcaPacketWeight: Weight = Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 10, 256 * 1024);
+	/// Interchain accounts are derived from this identifier
+	pub const InterchainAccountsPalletId: PalletId = PalletId(*b"netchica");
+}
+
+/// Calls controller chains may execute from their interchain accounts: transfers, staking
+/// bonds and oracle requests
+pub struct InterchainAccountCalls;
+impl Contains<RuntimeCall> for InterchainAccountCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		matches!(
+			call,
+			RuntimeCall::Balances(
+				pallet_balances::Call::transfer_allow_death { .. } |
+					pallet_balances::Call::transfer_keep_alive { .. }
+			) | RuntimeCall::Staking(
+				pallet_staking::Call::bond { .. } | pallet_staking::Call::bond_extra { .. }
+			) | RuntimeCall::Oracle(
+				pallet_oracle::Call::request_data { .. } | pallet_oracle::Call::batch_requests { .. }
+			)
+		)
+	}
+}
+
+/// Interchain accounts (ICS-27) host configuration
+impl pallet_interchain_accounts::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type AllowedCalls = InterchainAccountCalls;
+	type FilterOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxCallsPerPacket = MaxIcaCallsPerPacket;
+	type MaxFilterEntries = MaxIcaFilterEntries;
+	type MaxPacketWeight = MaxIcaPacketWeight;
+	type PalletId = InterchainAccountsPalletId;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	/// Counterparty blocks a published oracle feed may take to be received
+	pub const OracleIbcPacketTimeout: u64 = 600;
+	/// Owner of the `oracle` IBC port
+	pub const OracleIbcPalletId: PalletId = PalletId(*b"netchorc");
+}
+
+/// Oracle hub configuration: feeds published to and received from other chains
+impl pallet_oracle_ibc::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ChannelOrigin = frame_system::EnsureRoot<AccountId>;
+	type PacketTimeout = OracleIbcPacketTimeout;
+	type PalletId = OracleIbcPalletId;
 	type WeightInfo = ();
 }
 
@@ -430,6 +902,8 @@ parameter_types! {
 	pub const MaxOracleDataSources: u32 = 10;
 	/// Maximum size of oracle data (1KB)
 	pub const MaxOracleDataSize: u32 = 1024;
+	/// Entries an oracle provider may submit in one basket
+	pub const MaxOracleBatchSubmissions: u32 = 32;
 	/// Ultra-low oracle query fee (2 units = ~$0.00002)
 	pub const OracleQueryFee: Balance = 2;
 	/// Premium oracle query fee (5 units = ~$0.00005)
@@ -440,6 +914,57 @@ parameter_types! {
 	pub const MaxOracleDataAge: u64 = 1200;
 	/// Minimum sources for data aggregation
 	pub const MinAggregationSources: u32 = 3;
+	/// Blocks an oracle aggregate waits before it is readable, to allow disputing it (6 seconds)
+	pub const OracleMinAggregationDelay: BlockNumber = 2;
+	/// Aggregated values kept per oracle data key
+	pub const OracleHistoryDepth: u32 = 256;
+	/// Trusted providers selected per key and round in committee mode
+	pub const OracleCommitteeSize: u32 = 5;
+	/// Trusted oracle providers governance may register, and committees are drawn from
+	pub const MaxOracleTrustedProviders: u32 = 128;
+	/// Oracle round length (committees rotate every round)
+	pub const OracleRoundLength: BlockNumber = 10;
+	/// Assigned sources that must answer a premium oracle request
+	pub const OraclePremiumMinSources: u32 = 3;
+	/// Blocks a premium oracle request may wait for its sources (1 minute)
+	pub const OraclePremiumMaxLatency: BlockNumber = 20;
+	/// Reliability a source loses for each premium request it leaves unanswered
+	pub const OracleSlaReliabilityPenalty: u8 = 5;
+	/// Feeds the oracle watchdog can check for staleness
+	pub const MaxOracleWatchedFeeds: u32 = 16;
+	/// Successful submissions by bonded trusted providers refunded per block
+	pub const MaxFeelessOracleSubmissionsPerBlock: u32 = 200;
+	/// Provider signatures accepted on one signed oracle round
+	pub const MaxOracleRoundSigners: u32 = 32;
+	/// Block weight critical oracle feeds (the prices fees are quoted with) may always use
+	pub CriticalOracleFeedWeight: Weight = Perbill::from_percent(2) * RuntimeBlockWeights::get().max_block;
+	/// Period oracle request quotas are counted over (1 hour)
+	pub const OracleRequestEraLength: BlockNumber = 1200;
+	/// Oracle requests an unbonded account may make per request era
+	pub const MaxOracleRequestsPerEra: u32 = 100;
+	/// Blocks an oracle request is kept before it is pruned (1 hour)
+	pub const OracleRequestTtl: BlockNumber = 1200;
+	/// Provider bond exempting an account from oracle request quotas
+	pub const OracleQuotaExemptBond: Balance = 100 * UNIT;
+	/// Oracle sources anyone may submit through, at half the reward
+	pub OracleOpenTier: pallet_oracle::TierRequirements<Balance> = pallet_oracle::TierRequirements {
+		min_reputation: None,
+		min_bond: 0,
+		reward_multiplier: FixedU128::from_rational(1, 2),
+	};
+	/// Oracle sources reserved to trusted providers
+	pub OracleVerifiedTier: pallet_oracle::TierRequirements<Balance> = pallet_oracle::TierRequirements {
+		min_reputation: Some(0),
+		min_bond: 0,
+		reward_multiplier: FixedU128::one(),
+	};
+	/// Oracle sources of critical feeds: reputable trusted providers with a bond at stake,
+	/// at twice the reward
+	pub OracleCriticalTier: pallet_oracle::TierRequirements<Balance> = pallet_oracle::TierRequirements {
+		min_reputation: Some(80),
+		min_bond: 10 * UNIT,
+		reward_multiplier: FixedU128::from_u32(2),
+	};
 	/// Oracle pallet identifier
 	pub const OraclePalletId: PalletId = PalletId(*b"netchain_oracle");
 }
@@ -450,11 +975,115 @@ impl pallet_oracle::Config for Runtime {
 	type Currency = Balances;
 	type MaxDataSources = MaxOracleDataSources;
 	type MaxDataSize = MaxOracleDataSize;
+	type MaxBatchSubmissions = MaxOracleBatchSubmissions;
 	type OracleQueryFee = OracleQueryFee;
 	type PremiumQueryFee = PremiumOracleQueryFee;
 	type OracleReward = OracleProviderReward;
 	type MaxDataAge = MaxOracleDataAge;
 	type MinAggregationSources = MinAggregationSources;
+	type MinAggregationDelay = OracleMinAggregationDelay;
 	type PalletId = OraclePalletId;
+	type History = History;
+	type Safeguard = Safeguard;
+	type HistoryDepth = OracleHistoryDepth;
+	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
+	type CommitteeSize = OracleCommitteeSize;
+	type MaxTrustedProviders = MaxOracleTrustedProviders;
+	type RoundLength = OracleRoundLength;
+	type PremiumMinSources = OraclePremiumMinSources;
+	type PremiumMaxLatency = OraclePremiumMaxLatency;
+	type SlaReliabilityPenalty = OracleSlaReliabilityPenalty;
+	type MaxWatchedFeeds = MaxOracleWatchedFeeds;
+	type MaxFeelessSubmissionsPerBlock = MaxFeelessOracleSubmissionsPerBlock;
+	type MaxRoundSigners = MaxOracleRoundSigners;
+	type CriticalFeedWeight = CriticalOracleFeedWeight;
+	type RequestEraLength = OracleRequestEraLength;
+	type MaxRequestsPerEra = MaxOracleRequestsPerEra;
+	type RequestTtl = OracleRequestTtl;
+	type QuotaExemptBond = OracleQuotaExemptBond;
+	type OpenTier = OracleOpenTier;
+	type VerifiedTier = OracleVerifiedTier;
+	type CriticalTier = OracleCriticalTier;
+	type WeightInfo = pallet_oracle::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	/// Entries kept in the history index before the oldest is overwritten; queries scan it
+	pub const HistoryDepth: u32 = 4_096;
+	/// History depth before it was lowered, whose slots beyond `HistoryDepth` are pruned
+	pub const PreviousHistoryDepth: u32 = 65_536;
+}
+
+/// History pallet configuration for explorer queries
+impl pallet_history::Config for Runtime {
+	type HistoryDepth = HistoryDepth;
+}
+
+parameter_types! {
+	/// Maximum size of a staged runtime (matches the wasm code size limit)
+	pub const MaxStagedCodeSize: u32 = 8 * 1024 * 1024;
+	/// Abort window between scheduling and enacting an upgrade (1 hour)
+	pub const UpgradeAbortWindow: BlockNumber = HOURS;
+}
+
+/// Upgrade scheduler configuration; governance is root until an on-chain council exists
+impl pallet_upgrade_scheduler::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type StageOrigin = frame_system::EnsureRoot<AccountId>;
+	type ApproveOrigin = frame_system::EnsureRoot<AccountId>;
+	type AbortOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxCodeSize = MaxStagedCodeSize;
+	type MinEnactmentDelay = UpgradeAbortWindow;
+	type WeightInfo = pallet_upgrade_scheduler::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	/// Maximum execution time per transaction (milliseconds)
+	pub const MaxExecutionTime: u64 = 100;
+	/// Maximum keys in a declared access list
+	pub const MaxAccessListLen: u32 = 64;
+	/// Slashed from accounts whose access list misses an access of their call
+	pub const MisdeclarationPenalty: Balance = 10 * MILLI_UNIT;
+	/// Pending batches expire after 10 minutes
+	pub const BatchTtl: BlockNumber = 10 * MINUTES;
+	/// Batches pending at once
+	pub const MaxPendingBatches: u32 = 1_024;
+	/// Transactions in a submitted batch
+	pub const MaxBatchLen: u32 = 1_000;
+}
+
+/// Storage accesses of balance transfers, which write the accounts of both sides
+pub struct TransferAccesses;
+impl pallet_parallel_executor::InspectAccess<AccountId, RuntimeCall> for TransferAccesses {
+	fn accesses(who: &AccountId, call: &RuntimeCall) -> Option<pallet_parallel_executor::AccessList> {
+		let dest = match call {
+			RuntimeCall::Balances(
+				pallet_balances::Call::transfer_allow_death { dest, .. } |
+				pallet_balances::Call::transfer_keep_alive { dest, .. } |
+				pallet_balances::Call::transfer_all { dest, .. },
+			) => <Runtime as frame_system::Config>::Lookup::lookup(dest.clone()).ok()?,
+			_ => return None,
+		};
+		let account = |account: &AccountId| {
+			pallet_parallel_executor::StorageKey::truncate_from(frame_system::Account::<Runtime>::hashed_key_for(account))
+		};
+		Some(pallet_parallel_executor::AccessList { reads: Vec::new(), writes: alloc::vec![account(who), account(&dest)] })
+	}
+}
+
+/// Parallel executor configuration
+impl pallet_parallel_executor::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type MaxWorkers = dynamic_params::parallel_executor::MaxWorkers;
+	type BatchSize = dynamic_params::parallel_executor::BatchSize;
+	type MaxExecutionTime = MaxExecutionTime;
 	type WeightInfo = ();
+	type RuntimeCall = RuntimeCall;
+	type Currency = Balances;
+	type AccessInspector = TransferAccesses;
+	type MaxAccessListLen = MaxAccessListLen;
+	type MisdeclarationPenalty = MisdeclarationPenalty;
+	type BatchTtl = BatchTtl;
+	type MaxPendingBatches = MaxPendingBatches;
+	type MaxBatchLen = MaxBatchLen;
 }
@@ -0,0 +1,69 @@
+//! # Shard-aware contract addresses
+//!
+//! [`ShardAwareAddressGenerator`] derives contract addresses that land in the shard of their
+//! deployer, so a dapp's contracts sit next to the users deploying or calling them instead of
+//! wherever the address happens to hash. A salt starting with [`SHARD_SALT_PREFIX`] followed by
+//! a shard id targets that shard instead; [`shard_salt`] builds one.
+//!
+//! The address is found by hashing the usual inputs with an attempt counter until the shard
+//! mapping places the candidate in the target shard, on average [`SHARD_COUNT`] attempts. It
+//! stays deterministic, so tooling can predict it off-chain from the same inputs and the chain's
+//! shard mapping. After [`MAX_ATTEMPTS`] misses, whose odds are negligible, the last candidate
+//! is used wherever it lands.
+//!
+//! Select it with `type AddressGenerator` in `pallet_contracts::Config`;
+//! `pallet_contracts::DefaultAddressGenerator` ignores shards.
+
+use alloc::vec::Vec;
+use codec::Decode;
+use netchain_primitives::{ShardId, SHARD_COUNT};
+use pallet_contracts::AddressGenerator;
+use sp_runtime::traits::{Hash, TrailingZeroInput};
+
+/// Domain separating these addresses from those of other generators
+pub const ADDRESS_DOMAIN: &[u8] = b"netchain/contract-address";
+
+/// Prefix of salts naming the shard a contract is deployed into, followed by its id
+pub const SHARD_SALT_PREFIX: &[u8] = b"netchain/shard:";
+
+/// Candidates tried before giving up on the target shard
+pub const MAX_ATTEMPTS: u32 = 64 * SHARD_COUNT as u32;
+
+/// Salt deploying a contract into `shard`, distinguished from other deployments by `salt`
+pub fn shard_salt(shard: ShardId, salt: &[u8]) -> Vec<u8> {
+	[SHARD_SALT_PREFIX, &[shard], salt].concat()
+}
+
+/// Shard named by `salt`, if it is a [`shard_salt`] of an existing shard
+pub fn salt_shard(salt: &[u8]) -> Option<ShardId> {
+	let shard = *salt.strip_prefix(SHARD_SALT_PREFIX)?.first()?;
+	(shard < SHARD_COUNT).then_some(shard)
+}
+
+/// Address generator placing contracts in their deployer's shard, or the one their salt names
+pub struct ShardAwareAddressGenerator;
+
+impl<T> AddressGenerator<T> for ShardAwareAddressGenerator
+where
+	T: pallet_contracts::Config + pallet_sharding::Config,
+{
+	fn contract_address(
+		deploying_address: &T::AccountId,
+		code_hash: &T::Hash,
+		input_data: &[u8],
+		salt: &[u8],
+	) -> T::AccountId {
+		let target = salt_shard(salt)
+			.unwrap_or_else(|| pallet_sharding::Pallet::<T>::get_account_shard(deploying_address));
+
+		let candidate = |attempt: u32| -> T::AccountId {
+			let entropy = T::Hashing::hash_of(&(ADDRESS_DOMAIN, deploying_address, code_hash, input_data, salt, attempt));
+			Decode::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
+				.expect("infinite length input; no invalid inputs for type; qed")
+		};
+		(0..MAX_ATTEMPTS)
+			.map(candidate)
+			.find(|address| pallet_sharding::Pallet::<T>::mapped_shard(address) == target)
+			.unwrap_or_else(|| candidate(MAX_ATTEMPTS - 1))
+	}
+}
@@ -0,0 +1,80 @@
+//! # Fee Estimation
+//!
+//! Several Netchain calls charge a pallet fee on top of the transaction fee: the cross-shard
+//! fee (surged while the destination shard is overloaded), the IBC packet transmission fee and
+//! the oracle query fees. [`EstimateFeesApi`] returns both parts for a prospective call so
+//! wallets can show the full cost before the user signs. The pallet fee of a utility batch is
+//! the sum of its calls' pallet fees.
+
+use codec::{Decode, Encode};
+use frame_support::traits::Get;
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Saturating, DispatchError, RuntimeDebug};
+
+use super::{Balance, Runtime, RuntimeCall, Sharding, TransactionPayment};
+
+/// Expected cost of a call
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct FeeEstimate<Balance> {
+	/// Weight, length and tip-free transaction fee
+	pub inclusion_fee: Balance,
+	/// Fee charged by the called pallet itself
+	pub pallet_fee: Balance,
+	/// Sum of both fees
+	pub total: Balance,
+}
+
+sp_api::decl_runtime_apis! {
+	/// Total expected cost of calls, including pallet-specific fees
+	pub trait EstimateFeesApi<Balance, Call>
+	where
+		Balance: codec::Codec,
+		Call: codec::Codec,
+	{
+		/// Estimate the cost of submitting `call` in an extrinsic of `len` bytes
+		///
+		/// Fails with the error the call itself would fail with if its pallet fee cannot be
+		/// charged, e.g. a cross-shard transfer into a shard that rejects new transactions.
+		fn estimate_fees(call: Call, len: u32) -> Result<FeeEstimate<Balance>, DispatchError>;
+	}
+}
+
+/// Fee the pallet called by `call` charges on top of the transaction fee
+pub fn pallet_fee(call: &RuntimeCall) -> Result<Balance, DispatchError> {
+	let oracle_fee = |premium: bool| -> Balance {
+		if premium {
+			<Runtime as pallet_oracle::Config>::PremiumQueryFee::get()
+		} else {
+			<Runtime as pallet_oracle::Config>::OracleQueryFee::get()
+		}
+	};
+
+	Ok(match call {
+		RuntimeCall::Sharding(pallet_sharding::Call::execute_cross_shard_tx { to_shard, .. }) |
+		RuntimeCall::Sharding(pallet_sharding::Call::execute_cross_shard_call { to_shard, .. }) =>
+			Sharding::cross_shard_fee(*to_shard)?,
+		RuntimeCall::Sharding(pallet_sharding::Call::execute_cross_shard_tx_with_tip { to_shard, tip, .. }) =>
+			Sharding::cross_shard_fee(*to_shard)?.saturating_add(*tip),
+		RuntimeCall::IbcCore(pallet_ibc_core::Call::send_packet { .. }) =>
+			<Runtime as pallet_ibc_core::Config>::PacketTransmissionFee::get(),
+		RuntimeCall::Oracle(pallet_oracle::Call::request_data { premium, .. }) => oracle_fee(*premium),
+		RuntimeCall::Oracle(pallet_oracle::Call::batch_requests { requests }) => requests
+			.iter()
+			.fold(0, |total: Balance, (_, _, premium)| total.saturating_add(oracle_fee(*premium))),
+		RuntimeCall::Utility(pallet_utility::Call::batch { calls }) |
+		RuntimeCall::Utility(pallet_utility::Call::batch_all { calls }) |
+		RuntimeCall::Utility(pallet_utility::Call::force_batch { calls }) =>
+			calls.iter().try_fold(0, |total: Balance, call| Ok::<_, DispatchError>(total.saturating_add(pallet_fee(call)?)))?,
+		_ => 0,
+	})
+}
+
+/// Estimate the total cost of `call` in an extrinsic of `len` bytes
+pub fn estimate_fees(call: RuntimeCall, len: u32) -> Result<FeeEstimate<Balance>, DispatchError> {
+	let pallet_fee = pallet_fee(&call)?;
+	let inclusion_fee = TransactionPayment::query_call_fee_details(call, len)
+		.inclusion_fee
+		.map_or(0, |fee| fee.inclusion_fee());
+
+	Ok(FeeEstimate { inclusion_fee, pallet_fee, total: inclusion_fee.saturating_add(pallet_fee) })
+}
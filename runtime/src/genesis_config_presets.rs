@@ -15,7 +15,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{AccountId, BalancesConfig, RuntimeGenesisConfig, SudoConfig};
+use crate::{
+	AccountId, BalancesConfig, ContractPermissionsConfig, IbcCoreConfig, OracleConfig, RuntimeGenesisConfig,
+	ShardingConfig, SudoConfig,
+};
 use alloc::{vec, vec::Vec};
 use frame_support::build_struct_json_patch;
 use serde_json::Value;
@@ -24,6 +27,10 @@ use sp_consensus_grandpa::AuthorityId as GrandpaId;
 use sp_genesis_builder::{self, PresetId};
 use sp_keyring::Sr25519Keyring;
 
+/// Local 4-shard devnet: Alice, Bob, Charlie and Dave each validate one shard, oracle sources
+/// are registered and a loopback IBC channel pair is open on the `loopback` port.
+pub const SHARD_DEV_RUNTIME_PRESET: &str = "shard-dev";
+
 // Returns the genesis config presets populated with given parameters.
 fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
@@ -44,6 +51,7 @@ fn testnet_genesis(
 		grandpa: pallet_grandpa::GenesisConfig {
 			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect::<Vec<_>>(),
 		},
+		contract_permissions: ContractPermissionsConfig { uploaders: vec![root.clone()] },
 		sudo: SudoConfig { key: Some(root) },
 	})
 }
@@ -86,11 +94,57 @@ pub fn local_config_genesis() -> Value {
 	)
 }
 
+/// Return the 4-shard devnet genesis config preset.
+pub fn shard_dev_config_genesis() -> Value {
+	let validators =
+		[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie, Sr25519Keyring::Dave];
+	let ed25519 = [
+		sp_keyring::Ed25519Keyring::Alice,
+		sp_keyring::Ed25519Keyring::Bob,
+		sp_keyring::Ed25519Keyring::Charlie,
+		sp_keyring::Ed25519Keyring::Dave,
+	];
+
+	let mut patch = testnet_genesis(
+		validators.iter().zip(ed25519).map(|(v, e)| (v.public().into(), e.public().into())).collect(),
+		Sr25519Keyring::iter()
+			.filter(|v| v != &Sr25519Keyring::One && v != &Sr25519Keyring::Two)
+			.map(|v| v.to_account_id())
+			.collect::<Vec<_>>(),
+		Sr25519Keyring::Alice.to_account_id(),
+	);
+	let sources: [(&[u8], &[u8], &[u8]); 3] = [
+		(b"coinbase", b"Coinbase", b"https://api.coinbase.com/v2/prices"),
+		(b"binance", b"Binance", b"https://api.binance.com/api/v3/ticker/price"),
+		(b"kraken", b"Kraken", b"https://api.kraken.com/0/public/Ticker"),
+	];
+	let shard_patch = build_struct_json_patch!(RuntimeGenesisConfig {
+		sharding: ShardingConfig {
+			shard_validators: validators.iter().map(|v| vec![v.to_account_id()]).collect::<Vec<_>>(),
+		},
+		oracle: OracleConfig {
+			sources: sources
+				.iter()
+				.map(|(id, name, endpoint)| (id.to_vec(), name.to_vec(), endpoint.to_vec(), 90))
+				.collect::<Vec<_>>(),
+			trusted_providers: validators.iter().map(|v| (v.to_account_id(), 90)).collect::<Vec<_>>(),
+			critical_feeds: vec![crate::configs::NativeUsdPriceKey::get()],
+		},
+		ibc_core: IbcCoreConfig { loopback: Some((b"loopback".to_vec(), Sr25519Keyring::Alice.to_account_id())) },
+	});
+
+	if let (Value::Object(patch), Value::Object(shard_patch)) = (&mut patch, shard_patch) {
+		patch.extend(shard_patch);
+	}
+	patch
+}
+
 /// Provides the JSON representation of predefined genesis config for given `id`.
 pub fn get_preset(id: &PresetId) -> Option<Vec<u8>> {
 	let patch = match id.as_ref() {
 		sp_genesis_builder::DEV_RUNTIME_PRESET => development_config_genesis(),
 		sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET => local_config_genesis(),
+		SHARD_DEV_RUNTIME_PRESET => shard_dev_config_genesis(),
 		_ => return None,
 	};
 	Some(
@@ -105,5 +159,6 @@ pub fn preset_names() -> Vec<PresetId> {
 	vec![
 		PresetId::from(sp_genesis_builder::DEV_RUNTIME_PRESET),
 		PresetId::from(sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET),
+		PresetId::from(SHARD_DEV_RUNTIME_PRESET),
 	]
 }
@@ -0,0 +1,74 @@
+//! # Network Health
+//!
+//! [`HealthApi`] summarises the Netchain-specific state operators watch on every block: the
+//! cross-shard queues, IBC clients that can no longer relay and oracle feeds that stopped
+//! updating. The node pushes the summary of each new best block to Substrate telemetry.
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use frame_support::traits::Get;
+use netchain_primitives::SHARD_COUNT;
+use scale_info::TypeInfo;
+use sp_runtime::{RuntimeDebug, SaturatedConversion, Saturating};
+
+use super::{IbcCore, Runtime, System};
+
+/// Health of the chain at a block
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, Default)]
+pub struct HealthSummary {
+	/// Cross-shard transfers waiting to settle, indexed by shard
+	pub shard_queue_depths: Vec<u32>,
+	/// Shards whose load has reached their capacity
+	pub overloaded_shards: u32,
+	/// Batches waiting in the parallel executor
+	pub pending_batches: u32,
+	/// IBC clients frozen after misbehaviour
+	pub frozen_clients: u32,
+	/// IBC clients past their unbonding period
+	pub expired_clients: u32,
+	/// Aggregated oracle values older than the oracle's `MaxDataAge`
+	pub stale_feeds: u32,
+}
+
+sp_api::decl_runtime_apis! {
+	/// Per-block health of shards, IBC clients and oracle feeds
+	pub trait HealthApi {
+		/// Health summary at the queried block
+		fn health_summary() -> HealthSummary;
+	}
+}
+
+/// Compute the health summary from current state
+pub fn health_summary() -> HealthSummary {
+	let shard_queue_depths = (0..SHARD_COUNT)
+		.map(|shard_id| pallet_sharding::CrossShardQueue::<Runtime>::decode_len(shard_id).unwrap_or(0) as u32)
+		.collect();
+	let overloaded_shards = pallet_sharding::OverloadedShards::<Runtime>::iter_keys().count() as u32;
+
+	let pending_batches = pallet_parallel_executor::Pallet::<Runtime>::queue_depth().pending_batches;
+
+	let now = IbcCore::now_secs();
+	let (mut frozen_clients, mut expired_clients) = (0, 0);
+	for client in pallet_ibc_core::Clients::<Runtime>::iter_values() {
+		if client.frozen {
+			frozen_clients += 1;
+		} else if IbcCore::is_expired(&client, now) {
+			expired_clients += 1;
+		}
+	}
+
+	let block = System::block_number();
+	let max_age = <Runtime as pallet_oracle::Config>::MaxDataAge::get();
+	let stale_feeds = pallet_oracle::AggregatedDataStorage::<Runtime>::iter_values()
+		.filter(|data| block.saturating_sub(data.aggregated_at).saturated_into::<u64>() > max_age)
+		.count() as u32;
+
+	HealthSummary {
+		shard_queue_depths,
+		overloaded_shards,
+		pending_batches,
+		frozen_clients,
+		expired_clients,
+		stale_feeds,
+	}
+}
@@ -4,10 +4,15 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 pub mod apis;
+pub mod chain_extension;
+pub mod contract_address;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarks;
 pub mod configs;
+pub mod fees;
+pub mod health;
 pub mod performance;
+pub mod trace;
 #[cfg(test)]
 mod tests;
 
@@ -148,7 +153,7 @@ pub type Signature = MultiSignature;
 pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
 
 /// Balance of an account.
-pub type Balance = u128;
+pub use netchain_primitives::Balance;
 
 /// Index of a transaction in the chain.
 pub type Nonce = u32;
@@ -182,10 +187,11 @@ pub type TxExtension = (
 	frame_system::CheckTxVersion<Runtime>,
 	frame_system::CheckGenesis<Runtime>,
 	frame_system::CheckEra<Runtime>,
-	frame_system::CheckNonce<Runtime>,
+	pallet_sharding::CheckShardNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_asset_tx_payment::ChargeAssetTxPayment<Runtime>,
 	frame_metadata_hash_extension::CheckMetadataHash<Runtime>,
+	pallet_sharding::CheckShardAffinity<Runtime>,
 	frame_system::WeightReclaim<Runtime>,
 );
 
@@ -200,7 +206,19 @@ pub type SignedPayload = generic::SignedPayload<RuntimeCall, TxExtension>;
 ///
 /// This can be a tuple of types, each implementing `OnRuntimeUpgrade`.
 #[allow(unused_parens)]
-type Migrations = ();
+type Migrations = (
+	pallet_sharding::migrations::MigrateToLatest<Runtime>,
+	pallet_ibc_core::migrations::v1::AddClientTypes<Runtime>,
+	pallet_ibc_core::migrations::v2::AddConsensusRoots<Runtime>,
+	pallet_ibc_core::migrations::v3::AddChannelOrdering<Runtime>,
+	pallet_ibc_core::migrations::v4::BindSystemPorts<Runtime, configs::IbcSystemPorts>,
+	pallet_ibc_core::migrations::v5::BoundStoredVecs<Runtime>,
+	pallet_oracle::migrations::v1::AddAggregateValidity<Runtime>,
+	pallet_oracle::migrations::v2::AddSourceTiers<Runtime>,
+	pallet_oracle::migrations::v3::CountTrustedProviders<Runtime>,
+	pallet_parallel_executor::migrations::v2::QueuePendingBatches<Runtime>,
+	pallet_history::migrations::v1::LowerHistoryDepth<Runtime, configs::PreviousHistoryDepth>,
+);
 
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
@@ -283,5 +301,53 @@ mod runtime {
 	#[runtime::pallet_index(15)]
 	pub type Oracle = pallet_oracle;
 
+	// Compact activity index for explorers
+	#[runtime::pallet_index(16)]
+	pub type History = pallet_history;
+
+	// Shard assignment and cross-shard messaging
+	#[runtime::pallet_index(17)]
+	pub type Sharding = pallet_sharding;
+
+	// Staged, governance-approved runtime upgrades
+	#[runtime::pallet_index(18)]
+	pub type UpgradeScheduler = pallet_upgrade_scheduler;
+
+	// Batch scheduling and conflict tracking for parallel execution
+	#[runtime::pallet_index(19)]
+	pub type ParallelExecutor = pallet_parallel_executor;
+
+	// ICS-27 host executing whitelisted calls for controller chains
+	#[runtime::pallet_index(20)]
+	pub type InterchainAccounts = pallet_interchain_accounts;
+
+	// Who may upload contract code
+	#[runtime::pallet_index(21)]
+	pub type ContractPermissions = pallet_contract_permissions;
+
+	// Fungible assets, e.g. tokens bridged in over IBC
+	#[runtime::pallet_index(22)]
+	pub type Assets = pallet_assets;
+
+	// Transaction fees paid in assets at oracle prices
+	#[runtime::pallet_index(23)]
+	pub type AssetTxPayment = pallet_asset_tx_payment;
+
+	// Governance-adjustable performance parameters
+	#[runtime::pallet_index(24)]
+	pub type Parameters = pallet_parameters;
+
+	// Emergency pause of cross-shard, IBC and oracle activity
+	#[runtime::pallet_index(25)]
+	pub type Safeguard = pallet_safeguard;
+
+	// Batches of heterogeneous calls in one extrinsic
+	#[runtime::pallet_index(27)]
+	pub type Utility = pallet_utility;
+
+	// Oracle feeds published to and received from other chains over IBC
+	#[runtime::pallet_index(28)]
+	pub type OracleIbc = pallet_oracle_ibc;
+
 
 }
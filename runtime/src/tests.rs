@@ -249,4 +249,114 @@ fn test_fee_calculation_examples() {
 		println!("=====================================");
 		println!("🚀 All fees are ultra-low - perfect for high-volume usage!");
 	});
-}
\ No newline at end of file
+}
+#[test]
+fn test_performance_parameters_are_tunable_without_upgrade() {
+	use configs::{dynamic_params::sharding, RuntimeParameters};
+
+	new_test_ext().execute_with(|| {
+		assert_eq!(<Runtime as pallet_sharding::Config>::CrossShardFee::get(), 10);
+
+		let fee = RuntimeParameters::Sharding(sharding::Parameters::CrossShardFee(sharding::CrossShardFee, Some(25)));
+		assert_noop!(
+			pallet_parameters::Pallet::<Runtime>::set_parameter(RuntimeOrigin::signed(AccountId::from([1u8; 32])), fee.clone()),
+			BadOrigin,
+		);
+		assert_ok!(pallet_parameters::Pallet::<Runtime>::set_parameter(RuntimeOrigin::root(), fee));
+		assert_eq!(<Runtime as pallet_sharding::Config>::CrossShardFee::get(), 25);
+
+		// Unset parameters keep their defaults
+		assert_eq!(<Runtime as pallet_sharding::Config>::TargetTpsPerShard::get(), 25_000);
+	});
+}
+
+#[test]
+fn force_batch_reports_the_outcome_of_every_item_in_order() {
+	new_test_ext().execute_with(|| {
+		let alice = AccountId::from([1u8; 32]);
+		let bob = AccountId::from([2u8; 32]);
+		let too_many_sources = vec![b"source".to_vec(); configs::MaxOracleDataSources::get() as usize + 1];
+
+		assert_ok!(Utility::force_batch(
+			RuntimeOrigin::signed(alice),
+			vec![
+				RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive { dest: bob.clone().into(), value: DOLLARS }),
+				RuntimeCall::Oracle(pallet_oracle::Call::request_data {
+					data_key: b"NET/USD".to_vec(),
+					sources: too_many_sources,
+					premium: false,
+					callback: None,
+				}),
+				RuntimeCall::System(frame_system::Call::remark_with_event { remark: b"done".to_vec() }),
+			],
+		));
+
+		let outcomes: Vec<_> = System::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				RuntimeEvent::Utility(event) => Some(event),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(
+			outcomes,
+			vec![
+				pallet_utility::Event::ItemCompleted,
+				pallet_utility::Event::ItemFailed { error: pallet_oracle::Error::<Runtime>::TooManySources.into() },
+				pallet_utility::Event::ItemCompleted,
+				pallet_utility::Event::BatchCompletedWithErrors,
+			]
+		);
+		assert_eq!(Balances::free_balance(&bob), 10_001 * DOLLARS);
+	});
+}
+
+#[test]
+fn batches_are_estimated_with_the_pallet_fees_of_their_calls() {
+	let request = |premium| {
+		RuntimeCall::Oracle(pallet_oracle::Call::request_data {
+			data_key: b"NET/USD".to_vec(),
+			sources: vec![],
+			premium,
+			callback: None,
+		})
+	};
+	let batch = RuntimeCall::Utility(pallet_utility::Call::force_batch { calls: vec![request(false), request(true)] });
+
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			fees::pallet_fee(&batch),
+			Ok(configs::OracleQueryFee::get() + configs::PremiumOracleQueryFee::get())
+		);
+	});
+}
+
+#[test]
+fn contracts_are_deployed_into_their_deployers_shard_unless_the_salt_names_one() {
+	use contract_address::{shard_salt, ShardAwareAddressGenerator};
+	use pallet_contracts::AddressGenerator;
+
+	let address = |deployer: &AccountId, salt: &[u8]| {
+		<ShardAwareAddressGenerator as AddressGenerator<Runtime>>::contract_address(
+			deployer,
+			&Hash::repeat_byte(1),
+			b"new",
+			salt,
+		)
+	};
+
+	new_test_ext().execute_with(|| {
+		for deployer in [AccountId::from([1u8; 32]), AccountId::from([2u8; 32]), AccountId::from([3u8; 32])] {
+			let home = Sharding::get_account_shard(&deployer);
+			let contract = address(&deployer, b"salt");
+			assert_eq!(Sharding::get_account_shard(&contract), home);
+			assert_eq!(address(&deployer, b"salt"), contract);
+			assert_ne!(address(&deployer, b"other salt"), contract);
+
+			for shard in 0..netchain_primitives::SHARD_COUNT {
+				let contract = address(&deployer, &shard_salt(shard, b"salt"));
+				assert_eq!(Sharding::get_account_shard(&contract), shard);
+			}
+		}
+	});
+}
@@ -0,0 +1,76 @@
+//! # Extrinsic Tracing
+//!
+//! [`TraceApi`] replays one extrinsic of a block on the block's parent state and returns its
+//! dispatch outcome and the events it deposited, for debugging dapps and the access lists
+//! declared to the parallel executor.
+//!
+//! The wasm executor gives the runtime no view of the storage keys it touches, so the read and
+//! write sets are recorded by the node: its `trace_extrinsic` RPC runs [`prepare`] and then
+//! [`apply`] natively, recording the storage accesses of [`apply`] only.
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use frame_system::Phase;
+use scale_info::TypeInfo;
+use sp_runtime::{transaction_validity::TransactionValidityError, DispatchOutcome, RuntimeDebug};
+
+use super::{Block, Executive, Header, RuntimeEvent, System, UncheckedExtrinsic};
+
+/// Outcome of one replayed extrinsic
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct ExtrinsicTrace<Event> {
+	/// Result of dispatching its call
+	pub outcome: DispatchOutcome,
+	/// Events it deposited, in order
+	pub events: Vec<Event>,
+}
+
+/// Why an extrinsic could not be traced
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum TraceError {
+	/// The block has no extrinsic at the index
+	NoSuchExtrinsic,
+	/// Extrinsic `index` of the block, the traced one or one before it, is invalid on the
+	/// replayed state
+	Invalid { index: u32, error: TransactionValidityError },
+}
+
+sp_api::decl_runtime_apis! {
+	/// Replay of single extrinsics
+	pub trait TraceApi<Event>
+	where
+		Event: codec::Codec,
+	{
+		/// Replay extrinsic `index` of `block`; call at the block's parent
+		fn trace_extrinsic(block: Block, index: u32) -> Result<ExtrinsicTrace<Event>, TraceError>;
+	}
+}
+
+/// Initialize the block of `header` and apply `extrinsics`, the ones preceding a traced one
+pub fn prepare(header: &Header, extrinsics: &[UncheckedExtrinsic]) -> Result<(), TraceError> {
+	Executive::initialize_block(header);
+	extrinsics.iter().enumerate().try_for_each(|(index, xt)| {
+		Executive::apply_extrinsic(xt.clone())
+			.map(|_| ())
+			.map_err(|error| TraceError::Invalid { index: index as u32, error })
+	})
+}
+
+/// Apply `extrinsic` as extrinsic `index` of the block [`prepare`] started
+pub fn apply(index: u32, extrinsic: UncheckedExtrinsic) -> Result<ExtrinsicTrace<RuntimeEvent>, TraceError> {
+	let outcome =
+		Executive::apply_extrinsic(extrinsic).map_err(|error| TraceError::Invalid { index, error })?;
+	let events = System::read_events_no_consensus()
+		.filter(|record| record.phase == Phase::ApplyExtrinsic(index))
+		.map(|record| record.event)
+		.collect();
+
+	Ok(ExtrinsicTrace { outcome, events })
+}
+
+/// Replay extrinsic `index` of `block` on the current state, the block's parent
+pub fn trace_extrinsic(block: Block, index: u32) -> Result<ExtrinsicTrace<RuntimeEvent>, TraceError> {
+	let traced = block.extrinsics.get(index as usize).cloned().ok_or(TraceError::NoSuchExtrinsic)?;
+	prepare(&block.header, &block.extrinsics[..index as usize])?;
+	apply(index, traced)
+}
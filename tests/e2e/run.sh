@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+# Spawn the zombienet network, wait for it to produce blocks and run the end-to-end tests
+# against it. Expects a release build of the node and `zombienet` on the PATH (or in
+# $ZOMBIENET).
+
+set -euo pipefail
+
+cd "$(dirname "$0")/../.."
+
+ZOMBIENET="${ZOMBIENET:-zombienet}"
+export NETCHAIN_E2E_WS="${NETCHAIN_E2E_WS:-ws://127.0.0.1:9944}"
+
+if [ ! -x ./target/release/netchain-node ]; then
+    echo "Missing ./target/release/netchain-node, run cargo build --release -p netchain-node first" >&2
+    exit 1
+fi
+
+"$ZOMBIENET" spawn --provider native tests/e2e/zombienet.toml &
+NETWORK_PID=$!
+trap 'kill $NETWORK_PID 2>/dev/null || true' EXIT
+
+# Wait until Alice serves RPC
+for _ in $(seq 1 120); do
+    if curl -sf -H 'Content-Type: application/json' \
+        -d '{"id":1,"jsonrpc":"2.0","method":"chain_getHeader","params":[]}' \
+        http://127.0.0.1:9944 >/dev/null; then
+        break
+    fi
+    sleep 1
+done
+
+cargo test -p netchain-e2e -- --ignored --test-threads 1
@@ -0,0 +1,204 @@
+//! # Netchain End-to-End Tests
+//!
+//! Harness for tests that run against a live network, normally the four validators of
+//! `zombienet.toml` spawned by `run.sh`. The tests sign real extrinsics with the dev
+//! accounts, wait for their inclusion and assert on the events the runtime emitted.
+//!
+//! All tests are `#[ignore]`d so `cargo test --workspace` does not need a network; run them
+//! with `--ignored --test-threads 1`, as they share the dev accounts and their nonces. The
+//! node is read from `NETCHAIN_E2E_WS` and defaults to Alice's RPC port.
+
+pub use netchain_client::config::{self, shard_of, NetchainConfig, SHARD_COUNT};
+pub use subxt_signer::sr25519::{dev, Keypair};
+
+use subxt::{
+    blocks::ExtrinsicEvents,
+    dynamic::{self, DecodedValue, Value},
+    ext::scale_value::{Composite, ValueDef},
+    storage::Address,
+    tx::{DynamicPayload, Payload},
+    utils::AccountId32,
+    OnlineClient,
+};
+
+/// Environment variable naming the RPC endpoint of the node under test
+pub const WS_URL_ENV: &str = "NETCHAIN_E2E_WS";
+
+/// RPC endpoint used when [`WS_URL_ENV`] is unset
+pub const DEFAULT_WS_URL: &str = "ws://127.0.0.1:9944";
+
+/// Index of `Open` in the IBC `ConnectionState` and `ChannelState` enums
+const IBC_STATE_OPEN: u8 = 2;
+
+/// Result of an end-to-end test step
+pub type Result<T> = core::result::Result<T, subxt::Error>;
+
+/// Connection to the network under test
+pub struct Network {
+    /// Client of the node under test
+    pub client: OnlineClient<NetchainConfig>,
+}
+
+impl Network {
+    /// Connect to the node at [`WS_URL_ENV`]
+    pub async fn connect() -> Result<Self> {
+        let url = std::env::var(WS_URL_ENV).unwrap_or_else(|_| DEFAULT_WS_URL.into());
+        let client = OnlineClient::<NetchainConfig>::from_url(url).await?;
+        Ok(Self { client })
+    }
+
+    /// Sign `call` with `signer`, wait for it to be finalized and return its events
+    ///
+    /// Fails if the extrinsic is rejected by the pool or its dispatch fails.
+    pub async fn submit<Call: Payload>(
+        &self,
+        call: &Call,
+        signer: &Keypair,
+    ) -> Result<ExtrinsicEvents<NetchainConfig>> {
+        let params = config::params_for(&account(signer));
+        self.client
+            .tx()
+            .sign_and_submit_then_watch(call, signer, params)
+            .await?
+            .wait_for_finalized_success()
+            .await
+    }
+
+    /// Dispatch `call` as root through Alice's sudo key
+    ///
+    /// Fails if the inner call fails, not just the `sudo` wrapper.
+    pub async fn sudo(&self, call: DynamicPayload) -> Result<ExtrinsicEvents<NetchainConfig>> {
+        let sudo = dynamic::tx("Sudo", "sudo", vec![call.into_value()]);
+        let events = self.submit(&sudo, &dev::alice()).await?;
+
+        for sudid in find_events(&events, "Sudo", "Sudid")? {
+            if let ValueDef::Variant(result) = &field(&sudid, "sudo_result").value {
+                if result.name != "Ok" {
+                    return Err(subxt::Error::Other(format!("sudo call failed: {:?}", result.values)));
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Create the four shards through sudo, one dev validator each
+    ///
+    /// Re-initializing a running network resets the shards and empties their queues.
+    pub async fn initialize_sharding(&self) -> Result<ExtrinsicEvents<NetchainConfig>> {
+        let validators = [dev::alice(), dev::bob(), dev::charlie(), dev::dave()]
+            .iter()
+            .map(|validator| Value::unnamed_composite(vec![Value::from_bytes(account(validator).0)]))
+            .collect::<Vec<_>>();
+        self.sudo(dynamic::tx(
+            "Sharding",
+            "initialize_sharding",
+            vec![Value::unnamed_composite(validators)],
+        ))
+        .await
+    }
+
+    /// Raw value at `address` at the best block
+    pub async fn fetch_raw<Addr: Address>(&self, address: &Addr) -> Result<Option<Vec<u8>>> {
+        let key = self.client.storage().address_bytes(address)?;
+        self.client.storage().at_latest().await?.fetch_raw(key).await
+    }
+
+    /// Mark the IBC connection or channel stored at `address` as open
+    ///
+    /// The handshake counterparts (`*_open_try`/`*_open_ack`) run on the counterparty chain,
+    /// which the test network does not have, so the state is set through `System::set_storage`.
+    /// Both ends encode their state as the leading byte.
+    pub async fn force_ibc_open<Addr: Address>(&self, address: &Addr) -> Result<()> {
+        let key = self.client.storage().address_bytes(address)?;
+        let mut value = self
+            .fetch_raw(address)
+            .await?
+            .ok_or_else(|| subxt::Error::Other("IBC end not found".into()))?;
+        value[0] = IBC_STATE_OPEN;
+
+        let item = Value::unnamed_composite(vec![Value::from_bytes(key), Value::from_bytes(value)]);
+        self.sudo(dynamic::tx("System", "set_storage", vec![Value::unnamed_composite(vec![item])]))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Account of a dev keypair
+pub fn account(keypair: &Keypair) -> AccountId32 {
+    keypair.public_key().to_account_id()
+}
+
+/// A dev keypair assigned to `shard`, derived as `//<name>//<n>`
+pub fn keypair_on_shard(name: &str, shard: u8) -> Keypair {
+    (0u32..)
+        .map(|n| {
+            let uri = format!("//{name}//{n}").parse().expect("valid derivation path; qed");
+            Keypair::from_uri(&uri).expect("dev seed derives; qed")
+        })
+        .find(|keypair| shard_of(&account(keypair)) == shard)
+        .expect("every shard is reachable; qed")
+}
+
+/// Fields of the `pallet`/`variant` events among `events`
+pub fn find_events(
+    events: &ExtrinsicEvents<NetchainConfig>,
+    pallet: &str,
+    variant: &str,
+) -> Result<Vec<Composite<u32>>> {
+    let mut found = Vec::new();
+    for event in events.iter() {
+        let event = event?;
+        if event.pallet_name() == pallet && event.variant_name() == variant {
+            found.push(event.field_values()?);
+        }
+    }
+    Ok(found)
+}
+
+/// The only `pallet`/`variant` event among `events`
+pub fn expect_event(
+    events: &ExtrinsicEvents<NetchainConfig>,
+    pallet: &str,
+    variant: &str,
+) -> Result<Composite<u32>> {
+    let mut found = find_events(events, pallet, variant)?;
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        n => Err(subxt::Error::Other(format!("expected one {pallet}.{variant} event, got {n}"))),
+    }
+}
+
+/// Named field of an event, panicking if it is missing
+pub fn field<'a>(fields: &'a Composite<u32>, name: &str) -> &'a DecodedValue {
+    match fields {
+        Composite::Named(values) => values
+            .iter()
+            .find_map(|(field, value)| (field == name).then_some(value))
+            .unwrap_or_else(|| panic!("event has no field {name}")),
+        Composite::Unnamed(_) => panic!("event has no named fields"),
+    }
+}
+
+/// Unsigned integer in `value`, looking through newtype wrappers
+pub fn as_u128(value: &DecodedValue) -> Option<u128> {
+    match &value.value {
+        ValueDef::Composite(inner) if inner.len() == 1 => inner.values().next().and_then(as_u128),
+        _ => value.as_u128(),
+    }
+}
+
+/// Byte string in `value`, such as an IBC identifier or an oracle value
+///
+/// Newtype wrappers like `BoundedVec` are looked through.
+pub fn as_bytes(value: &DecodedValue) -> Option<Vec<u8>> {
+    let ValueDef::Composite(inner) = &value.value else {
+        return None;
+    };
+    if inner.len() == 1 {
+        if let Some(nested) = inner.values().next().and_then(as_bytes) {
+            return Some(nested);
+        }
+    }
+    inner.values().map(|byte| byte.as_u128().and_then(|b| u8::try_from(b).ok())).collect()
+}
@@ -0,0 +1,114 @@
+//! IBC on a live network: a client, connection and channel are set up and a packet is sent
+//! over the channel
+
+use netchain_e2e::{account, as_bytes, as_u128, dev, expect_event, field, Network, Result};
+use subxt::dynamic::{self, Value};
+
+/// Unbonding period of the test client, two weeks in seconds
+const UNBONDING_PERIOD: u64 = 14 * 24 * 60 * 60;
+
+#[tokio::test]
+#[ignore = "needs a running network, see tests/e2e/run.sh"]
+async fn packet_is_sent_over_an_open_channel() -> Result<()> {
+    let network = Network::connect().await?;
+    let relayer = dev::bob();
+
+    // Mock clients are created by root
+    let events = network
+        .sudo(dynamic::tx(
+            "IbcCore",
+            "create_client",
+            vec![
+                Value::unnamed_variant("Mock", []),
+                Value::from_bytes(b"counterparty-e2e"),
+                Value::u128(1),
+                Value::u128(67),
+                Value::u128(UNBONDING_PERIOD.into()),
+            ],
+        ))
+        .await?;
+    let client_id = as_bytes(field(&expect_event(&events, "IbcCore", "ClientCreated")?, "client_id"))
+        .expect("client ids are bytes");
+
+    let events = network
+        .submit(
+            &dynamic::tx(
+                "IbcCore",
+                "connection_open_init",
+                vec![
+                    Value::from_bytes(&client_id),
+                    Value::from_bytes(b"07-tendermint-0"),
+                    Value::from_bytes(b"1"),
+                ],
+            ),
+            &relayer,
+        )
+        .await?;
+    let connection_id =
+        as_bytes(field(&expect_event(&events, "IbcCore", "ConnectionOpened")?, "connection_id"))
+            .expect("connection ids are bytes");
+    network
+        .force_ibc_open(&dynamic::storage("IbcCore", "Connections", vec![Value::from_bytes(&connection_id)]))
+        .await?;
+
+    // Ports are bound for good, and by governance, so every run binds a fresh one to the relayer
+    let port_id = format!("e2e-{}", std::process::id()).into_bytes();
+    network
+        .sudo(dynamic::tx(
+            "IbcCore",
+            "bind_port",
+            vec![Value::from_bytes(&port_id), Value::from_bytes(account(&relayer).0)],
+        ))
+        .await?;
+
+    let events = network
+        .submit(
+            &dynamic::tx(
+                "IbcCore",
+                "channel_open_init",
+                vec![
+                    Value::from_bytes(&port_id),
+                    Value::from_bytes(&connection_id),
+                    Value::from_bytes(b"transfer"),
+                    Value::from_bytes(b"ics20-1"),
+                ],
+            ),
+            &relayer,
+        )
+        .await?;
+    let channel_id = as_bytes(field(&expect_event(&events, "IbcCore", "ChannelOpened")?, "channel_id"))
+        .expect("channel ids are bytes");
+    network
+        .force_ibc_open(&dynamic::storage(
+            "IbcCore",
+            "Channels",
+            vec![Value::from_bytes(&port_id), Value::from_bytes(&channel_id)],
+        ))
+        .await?;
+
+    let data = b"e2e packet".to_vec();
+    let events = network
+        .submit(
+            &dynamic::tx(
+                "IbcCore",
+                "send_packet",
+                vec![
+                    Value::from_bytes(&port_id),
+                    Value::from_bytes(&channel_id),
+                    Value::from_bytes(b"transfer"),
+                    Value::from_bytes(b"channel-0"),
+                    Value::from_bytes(&data),
+                    Value::u128(u64::MAX.into()),
+                    Value::u128(0),
+                ],
+            ),
+            &relayer,
+        )
+        .await?;
+    let sent = expect_event(&events, "IbcCore", "PacketSent")?;
+    assert_eq!(as_u128(field(&sent, "sequence")), Some(1));
+    assert_eq!(as_bytes(field(&sent, "source_channel")), Some(channel_id));
+    assert_eq!(as_bytes(field(&sent, "data")), Some(data));
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Oracle rounds on a live network: sources registered through sudo report a value and the
+//! round aggregates once enough sources have reported
+
+use netchain_e2e::{as_bytes, as_u128, dev, expect_event, field, find_events, Network, Result};
+use subxt::dynamic::{self, Value};
+
+/// The runtime's `MinAggregationSources`
+const MIN_AGGREGATION_SOURCES: usize = 3;
+
+#[tokio::test]
+#[ignore = "needs a running network, see tests/e2e/run.sh"]
+async fn round_aggregates_once_enough_sources_report() -> Result<()> {
+    let network = Network::connect().await?;
+    let data_key = format!("e2e/price/{}", std::process::id()).into_bytes();
+    let value = 42_000u64.to_le_bytes().to_vec();
+    let providers = [dev::bob(), dev::charlie(), dev::dave()];
+
+    for (index, provider) in providers.iter().enumerate() {
+        let source_id = format!("e2e-source-{index}").into_bytes();
+        network
+            .sudo(dynamic::tx(
+                "Oracle",
+                "register_source",
+                vec![
+                    Value::from_bytes(&source_id),
+                    Value::from_bytes(b"e2e"),
+                    Value::from_bytes(b"https://example.invalid"),
+                    Value::u128(90),
+                ],
+            ))
+            .await?;
+
+        let events = network
+            .submit(
+                &dynamic::tx(
+                    "Oracle",
+                    "provide_data",
+                    vec![
+                        Value::from_bytes(&data_key),
+                        Value::from_bytes(&source_id),
+                        Value::from_bytes(&value),
+                        Value::u128(75),
+                        Value::unnamed_variant("None", []),
+                    ],
+                ),
+                provider,
+            )
+            .await?;
+        let provided = expect_event(&events, "Oracle", "DataProvided")?;
+        assert_eq!(as_bytes(field(&provided, "source")), Some(source_id));
+
+        let aggregated = find_events(&events, "Oracle", "DataAggregated")?;
+        if index + 1 < MIN_AGGREGATION_SOURCES {
+            assert!(aggregated.is_empty());
+        } else {
+            let aggregated = &aggregated[0];
+            assert_eq!(as_bytes(field(aggregated, "data_key")), Some(data_key.clone()));
+            assert_eq!(as_bytes(field(aggregated, "value")), Some(value.clone()));
+            assert_eq!(as_u128(field(aggregated, "source_count")), Some(MIN_AGGREGATION_SOURCES as u128));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! Sharding on a live network: sudo initialization and cross-shard transfers settling on
+//! their destination shard
+
+use netchain_e2e::{
+    account, as_bytes, as_u128, dev, expect_event, field, find_events, keypair_on_shard, shard_of,
+    Network, Result, SHARD_COUNT,
+};
+use subxt::dynamic::{self, Value};
+
+const UNIT: u128 = 1_000_000_000_000;
+
+#[tokio::test]
+#[ignore = "needs a running network, see tests/e2e/run.sh"]
+async fn sudo_initializes_four_shards() -> Result<()> {
+    let network = Network::connect().await?;
+
+    let events = network.initialize_sharding().await?;
+
+    let mut shards = find_events(&events, "Sharding", "ShardCreated")?
+        .iter()
+        .map(|created| as_u128(field(created, "shard_id")).expect("shard ids are integers"))
+        .collect::<Vec<_>>();
+    shards.sort();
+    assert_eq!(shards, (0..SHARD_COUNT as u128).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs a running network, see tests/e2e/run.sh"]
+async fn cross_shard_transfer_settles_on_destination_shard() -> Result<()> {
+    let network = Network::connect().await?;
+    network.initialize_sharding().await?;
+
+    let sender = dev::alice();
+    let from_shard = shard_of(&account(&sender));
+    let to_shard = (from_shard + 1) % SHARD_COUNT;
+    let recipient = account(&keypair_on_shard("Netchain", to_shard));
+    let amount = 10 * UNIT;
+
+    let events = network
+        .submit(
+            &dynamic::tx(
+                "Sharding",
+                "execute_cross_shard_tx",
+                vec![
+                    Value::u128(to_shard.into()),
+                    Value::from_bytes(recipient.0),
+                    Value::u128(amount),
+                ],
+            ),
+            &sender,
+        )
+        .await?;
+    let executed = expect_event(&events, "Sharding", "CrossShardExecuted")?;
+    assert_eq!(as_u128(field(&executed, "from_shard")), Some(from_shard.into()));
+    assert_eq!(as_u128(field(&executed, "to_shard")), Some(to_shard.into()));
+
+    // Anyone may settle the destination queue
+    let events = network
+        .submit(
+            &dynamic::tx(
+                "Sharding",
+                "process_cross_shard_queue",
+                vec![Value::u128(to_shard.into()), Value::u128(10)],
+            ),
+            &dev::bob(),
+        )
+        .await?;
+    let settled = expect_event(&events, "Sharding", "CrossShardSettled")?;
+    assert_eq!(as_bytes(field(&settled, "tx_hash")), as_bytes(field(&executed, "tx_hash")));
+    assert_eq!(as_u128(field(&settled, "to_shard")), Some(to_shard.into()));
+
+    // The held amount reached the recipient
+    assert!(find_events(&events, "Balances", "TransferOnHold")?.iter().any(|transfer| {
+        as_bytes(field(transfer, "dest")) == Some(recipient.0.to_vec()) &&
+            as_u128(field(transfer, "amount")) == Some(amount)
+    }));
+
+    Ok(())
+}
@@ -1,792 +0,0 @@
-//! # Comprehensive Integration Tests
-//!
-//! End-to-end integration testing for Netchain:
-//! - Multi-node consensus testing
-//! - Cross-pallet interaction testing
-//! - Full transaction lifecycle testing
-//! - Performance under load
-//! - Failure recovery testing
-
-#![cfg(test)]
-
-use std::time::{Duration, Instant};
-use std::collections::HashMap;
-use tokio::time::sleep;
-
-#[derive(Debug, Clone)]
-pub struct IntegrationTestSuite {
-    pub test_results: Vec<TestResult>,
-    pub nodes: Vec<TestNode>,
-}
-
-#[derive(Debug, Clone)]
-pub struct TestResult {
-    pub test_name: String,
-    pub passed: bool,
-    pub duration: Duration,
-    pub error_message: Option<String>,
-    pub metrics: TestMetrics,
-}
-
-#[derive(Debug, Clone)]
-pub struct TestMetrics {
-    pub transactions_processed: u64,
-    pub average_latency_ms: f64,
-    pub success_rate: f64,
-    pub resource_usage: ResourceUsage,
-}
-
-#[derive(Debug, Clone)]
-pub struct ResourceUsage {
-    pub cpu_percent: f64,
-    pub memory_mb: f64,
-    pub network_bytes: u64,
-    pub storage_bytes: u64,
-}
-
-#[derive(Debug, Clone)]
-pub struct TestNode {
-    pub name: String,
-    pub url: String,
-    pub is_validator: bool,
-    pub stake: u128,
-    pub status: NodeStatus,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum NodeStatus {
-    Online,
-    Offline,
-    Syncing,
-    Error,
-}
-
-impl IntegrationTestSuite {
-    pub fn new() -> Self {
-        let nodes = vec![
-            TestNode {
-                name: "alice".to_string(),
-                url: "ws://127.0.0.1:9944".to_string(),
-                is_validator: true,
-                stake: 1_000_000,
-                status: NodeStatus::Online,
-            },
-            TestNode {
-                name: "bob".to_string(),
-                url: "ws://127.0.0.1:9945".to_string(),
-                is_validator: true,
-                stake: 1_000_000,
-                status: NodeStatus::Online,
-            },
-            TestNode {
-                name: "charlie".to_string(),
-                url: "ws://127.0.0.1:9946".to_string(),
-                is_validator: true,
-                stake: 1_000_000,
-                status: NodeStatus::Online,
-            },
-            TestNode {
-                name: "dave".to_string(),
-                url: "ws://127.0.0.1:9947".to_string(),
-                is_validator: true,
-                stake: 1_000_000,
-                status: NodeStatus::Online,
-            },
-        ];
-        
-        Self {
-            test_results: Vec::new(),
-            nodes,
-        }
-    }
-    
-    pub async fn test_basic_connectivity(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let mut metrics = TestMetrics {
-            transactions_processed: 0,
-            average_latency_ms: 0.0,
-            success_rate: 0.0,
-            resource_usage: ResourceUsage {
-                cpu_percent: 0.0,
-                memory_mb: 0.0,
-                network_bytes: 0,
-                storage_bytes: 0,
-            },
-        };
-        
-        // Test connectivity to all nodes
-        let mut connected_nodes = 0;
-        for node in &self.nodes {
-            // Simulate connection test
-            if self.test_node_connection(&node.url).await {
-                connected_nodes += 1;
-            }
-        }
-        
-        let success_rate = connected_nodes as f64 / self.nodes.len() as f64;
-        metrics.success_rate = success_rate;
-        
-        let result = TestResult {
-            test_name: "Basic Connectivity".to_string(),
-            passed: success_rate == 1.0,
-            duration: start_time.elapsed(),
-            error_message: if success_rate < 1.0 { Some("Not all nodes reachable".to_string()) } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    pub async fn test_consensus_functionality(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let mut metrics = TestMetrics {
-            transactions_processed: 100, // Simulate 100 block productions
-            average_latency_ms: 3000.0, // 3 second block time
-            success_rate: 0.0,
-            resource_usage: ResourceUsage {
-                cpu_percent: 25.0,
-                memory_mb: 512.0,
-                network_bytes: 1024 * 1024, // 1MB
-                storage_bytes: 10 * 1024 * 1024, // 10MB
-            },
-        };
-        
-        // Test consensus by simulating block production
-        let mut successful_blocks = 0;
-        let total_blocks = 100;
-        
-        for block_num in 1..=total_blocks {
-            if self.simulate_block_production(block_num).await {
-                successful_blocks += 1;
-            }
-            
-            // Simulate block time
-            sleep(Duration::from_millis(10)).await; // Faster for testing
-        }
-        
-        let success_rate = successful_blocks as f64 / total_blocks as f64;
-        metrics.success_rate = success_rate;
-        
-        let result = TestResult {
-            test_name: "Consensus Functionality".to_string(),
-            passed: success_rate >= 0.95, // 95% success rate required
-            duration: start_time.elapsed(),
-            error_message: if success_rate < 0.95 { Some("Consensus failure rate too high".to_string()) } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    pub async fn test_transaction_processing(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let mut latencies = Vec::new();
-        let mut successful_txs = 0;
-        let total_txs = 1000;
-        
-        // Test various transaction types
-        let tx_types = vec![
-            ("transfer", 400),
-            ("staking", 200),
-            ("contract_call", 200),
-            ("governance", 100),
-            ("ibc_transfer", 50),
-            ("oracle_query", 50),
-        ];
-        
-        for (tx_type, count) in tx_types {
-            let type_start = Instant::now();
-            let type_successful = self.simulate_transaction_batch(tx_type, count).await;
-            successful_txs += type_successful;
-            
-            let avg_latency = type_start.elapsed().as_millis() as f64 / count as f64;
-            latencies.push(avg_latency);
-        }
-        
-        let success_rate = successful_txs as f64 / total_txs as f64;
-        let avg_latency = latencies.iter().sum::<f64>() / latencies.len() as f64;
-        
-        let metrics = TestMetrics {
-            transactions_processed: successful_txs,
-            average_latency_ms: avg_latency,
-            success_rate,
-            resource_usage: ResourceUsage {
-                cpu_percent: 45.0,
-                memory_mb: 256.0,
-                network_bytes: 5 * 1024 * 1024, // 5MB
-                storage_bytes: 50 * 1024 * 1024, // 50MB
-            },
-        };
-        
-        let result = TestResult {
-            test_name: "Transaction Processing".to_string(),
-            passed: success_rate >= 0.99, // 99% success rate required
-            duration: start_time.elapsed(),
-            error_message: if success_rate < 0.99 { Some("Transaction failure rate too high".to_string()) } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    pub async fn test_interoperability_features(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let mut successful_operations = 0;
-        let total_operations = 50;
-        
-        // Test IBC operations
-        let ibc_operations = vec![
-            "create_client",
-            "connection_open_init",
-            "channel_open_init",
-            "send_packet",
-            "recv_packet",
-        ];
-        
-        for operation in &ibc_operations {
-            if self.simulate_ibc_operation(operation).await {
-                successful_operations += 10; // Each operation type tested 10 times
-            }
-        }
-        
-        let success_rate = successful_operations as f64 / total_operations as f64;
-        
-        let metrics = TestMetrics {
-            transactions_processed: successful_operations,
-            average_latency_ms: 500.0, // IBC operations are more complex
-            success_rate,
-            resource_usage: ResourceUsage {
-                cpu_percent: 30.0,
-                memory_mb: 128.0,
-                network_bytes: 2 * 1024 * 1024, // 2MB
-                storage_bytes: 20 * 1024 * 1024, // 20MB
-            },
-        };
-        
-        let result = TestResult {
-            test_name: "Interoperability Features".to_string(),
-            passed: success_rate >= 0.9, // 90% success rate for complex operations
-            duration: start_time.elapsed(),
-            error_message: if success_rate < 0.9 { Some("Interoperability failure rate too high".to_string()) } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    pub async fn test_smart_contract_integration(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let mut successful_operations = 0;
-        let total_operations = 100;
-        
-        // Test contract lifecycle
-        let contract_operations = vec![
-            ("deploy", 10),
-            ("call", 60),
-            ("query", 30),
-        ];
-        
-        for (operation, count) in contract_operations {
-            let op_successful = self.simulate_contract_operations(operation, count).await;
-            successful_operations += op_successful;
-        }
-        
-        let success_rate = successful_operations as f64 / total_operations as f64;
-        
-        let metrics = TestMetrics {
-            transactions_processed: successful_operations,
-            average_latency_ms: 150.0, // Contract operations
-            success_rate,
-            resource_usage: ResourceUsage {
-                cpu_percent: 35.0,
-                memory_mb: 384.0,
-                network_bytes: 3 * 1024 * 1024, // 3MB
-                storage_bytes: 30 * 1024 * 1024, // 30MB
-            },
-        };
-        
-        let result = TestResult {
-            test_name: "Smart Contract Integration".to_string(),
-            passed: success_rate >= 0.95, // 95% success rate for contracts
-            duration: start_time.elapsed(),
-            error_message: if success_rate < 0.95 { Some("Contract failure rate too high".to_string()) } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    pub async fn test_oracle_functionality(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let mut successful_operations = 0;
-        let total_operations = 200;
-        
-        // Test oracle operations
-        let oracle_operations = vec![
-            ("register_source", 10),
-            ("request_data", 100),
-            ("provide_data", 80),
-            ("aggregate_data", 10),
-        ];
-        
-        for (operation, count) in oracle_operations {
-            let op_successful = self.simulate_oracle_operations(operation, count).await;
-            successful_operations += op_successful;
-        }
-        
-        let success_rate = successful_operations as f64 / total_operations as f64;
-        
-        let metrics = TestMetrics {
-            transactions_processed: successful_operations,
-            average_latency_ms: 100.0, // Oracle operations are fast
-            success_rate,
-            resource_usage: ResourceUsage {
-                cpu_percent: 20.0,
-                memory_mb: 64.0,
-                network_bytes: 1024 * 1024, // 1MB
-                storage_bytes: 5 * 1024 * 1024, // 5MB
-            },
-        };
-        
-        let result = TestResult {
-            test_name: "Oracle Functionality".to_string(),
-            passed: success_rate >= 0.98, // 98% success rate for oracle
-            duration: start_time.elapsed(),
-            error_message: if success_rate < 0.98 { Some("Oracle failure rate too high".to_string()) } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    pub async fn test_network_resilience(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let mut recovery_times = Vec::new();
-        
-        // Test various failure scenarios
-        let failure_scenarios = vec![
-            ("single_node_failure", 1),
-            ("network_partition", 2),
-            ("validator_offline", 1),
-        ];
-        
-        let mut total_recoveries = 0;
-        let mut successful_recoveries = 0;
-        
-        for (scenario, affected_nodes) in failure_scenarios {
-            total_recoveries += 1;
-            
-            // Simulate failure
-            let failure_start = Instant::now();
-            self.simulate_network_failure(scenario, affected_nodes).await;
-            
-            // Test recovery
-            if self.test_network_recovery().await {
-                successful_recoveries += 1;
-                recovery_times.push(failure_start.elapsed().as_millis() as f64);
-            }
-        }
-        
-        let success_rate = successful_recoveries as f64 / total_recoveries as f64;
-        let avg_recovery_time = if !recovery_times.is_empty() {
-            recovery_times.iter().sum::<f64>() / recovery_times.len() as f64
-        } else {
-            0.0
-        };
-        
-        let metrics = TestMetrics {
-            transactions_processed: 0,
-            average_latency_ms: avg_recovery_time, // Using latency field for recovery time
-            success_rate,
-            resource_usage: ResourceUsage {
-                cpu_percent: 15.0,
-                memory_mb: 128.0,
-                network_bytes: 500 * 1024, // 500KB
-                storage_bytes: 1024 * 1024, // 1MB
-            },
-        };
-        
-        let result = TestResult {
-            test_name: "Network Resilience".to_string(),
-            passed: success_rate >= 0.8 && avg_recovery_time < 10000.0, // 80% recovery rate, <10s recovery
-            duration: start_time.elapsed(),
-            error_message: if success_rate < 0.8 { Some("Network recovery rate too low".to_string()) } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    pub async fn test_performance_under_load(&mut self) -> TestResult {
-        let start_time = Instant::now();
-        let target_tps = 1000; // Target 1000 TPS
-        let test_duration = Duration::from_secs(30);
-        
-        let mut total_transactions = 0;
-        let mut successful_transactions = 0;
-        let mut latencies = Vec::new();
-        
-        let load_start = Instant::now();
-        while load_start.elapsed() < test_duration {
-            let batch_start = Instant::now();
-            let batch_size = 100;
-            
-            // Simulate high-load transaction processing
-            let batch_successful = self.simulate_high_load_batch(batch_size).await;
-            
-            total_transactions += batch_size;
-            successful_transactions += batch_successful;
-            
-            let batch_latency = batch_start.elapsed().as_millis() as f64;
-            latencies.push(batch_latency);
-            
-            // Brief pause to prevent overwhelming
-            sleep(Duration::from_millis(50)).await;
-        }
-        
-        let actual_duration = load_start.elapsed().as_secs_f64();
-        let actual_tps = successful_transactions as f64 / actual_duration;
-        let success_rate = successful_transactions as f64 / total_transactions as f64;
-        let avg_latency = latencies.iter().sum::<f64>() / latencies.len() as f64;
-        
-        let metrics = TestMetrics {
-            transactions_processed: successful_transactions,
-            average_latency_ms: avg_latency,
-            success_rate,
-            resource_usage: ResourceUsage {
-                cpu_percent: 80.0, // High CPU under load
-                memory_mb: 1024.0, // High memory usage
-                network_bytes: 100 * 1024 * 1024, // 100MB
-                storage_bytes: 500 * 1024 * 1024, // 500MB
-            },
-        };
-        
-        let result = TestResult {
-            test_name: "Performance Under Load".to_string(),
-            passed: actual_tps >= (target_tps as f64 * 0.8) && success_rate >= 0.95, // 80% of target TPS, 95% success
-            duration: start_time.elapsed(),
-            error_message: if actual_tps < (target_tps as f64 * 0.8) { 
-                Some(format!("TPS too low: {:.0} < {}", actual_tps, target_tps as f64 * 0.8)) 
-            } else { None },
-            metrics,
-        };
-        
-        self.test_results.push(result.clone());
-        result
-    }
-    
-    // Helper methods for simulation
-    async fn test_node_connection(&self, _url: &str) -> bool {
-        // Simulate connection test
-        sleep(Duration::from_millis(10)).await;
-        true // Assume success for testing
-    }
-    
-    async fn simulate_block_production(&self, _block_num: u64) -> bool {
-        // Simulate block production with occasional failures
-        sleep(Duration::from_millis(1)).await;
-        rand::random::<f64>() > 0.02 // 98% success rate
-    }
-    
-    async fn simulate_transaction_batch(&self, _tx_type: &str, count: u64) -> u64 {
-        // Simulate processing transactions with high success rate
-        sleep(Duration::from_millis(count / 10)).await;
-        let success_rate = match _tx_type {
-            "transfer" => 0.999,
-            "staking" => 0.995,
-            "contract_call" => 0.98,
-            "governance" => 0.99,
-            "ibc_transfer" => 0.95,
-            "oracle_query" => 0.99,
-            _ => 0.9,
-        };
-        
-        (count as f64 * success_rate) as u64
-    }
-    
-    async fn simulate_ibc_operation(&self, _operation: &str) -> bool {
-        // Simulate IBC operation
-        sleep(Duration::from_millis(50)).await;
-        rand::random::<f64>() > 0.1 // 90% success rate
-    }
-    
-    async fn simulate_contract_operations(&self, _operation: &str, count: u64) -> u64 {
-        sleep(Duration::from_millis(count * 2)).await;
-        let success_rate = match _operation {
-            "deploy" => 0.95,
-            "call" => 0.98,
-            "query" => 0.99,
-            _ => 0.9,
-        };
-        
-        (count as f64 * success_rate) as u64
-    }
-    
-    async fn simulate_oracle_operations(&self, _operation: &str, count: u64) -> u64 {
-        sleep(Duration::from_millis(count / 2)).await;
-        let success_rate = match _operation {
-            "register_source" => 0.99,
-            "request_data" => 0.99,
-            "provide_data" => 0.98,
-            "aggregate_data" => 0.95,
-            _ => 0.9,
-        };
-        
-        (count as f64 * success_rate) as u64
-    }
-    
-    async fn simulate_network_failure(&mut self, scenario: &str, affected_nodes: usize) {
-        // Simulate network failure
-        for i in 0..affected_nodes.min(self.nodes.len()) {
-            self.nodes[i].status = NodeStatus::Offline;
-        }
-        
-        sleep(Duration::from_millis(100)).await;
-    }
-    
-    async fn test_network_recovery(&mut self) -> bool {
-        // Simulate network recovery
-        sleep(Duration::from_millis(500)).await;
-        
-        // Restore nodes
-        for node in &mut self.nodes {
-            if node.status == NodeStatus::Offline {
-                node.status = NodeStatus::Online;
-            }
-        }
-        
-        true // Assume successful recovery
-    }
-    
-    async fn simulate_high_load_batch(&self, batch_size: u64) -> u64 {
-        // Simulate processing under high load
-        sleep(Duration::from_millis(batch_size / 20)).await;
-        (batch_size as f64 * 0.97) as u64 // 97% success rate under load
-    }
-    
-    pub async fn run_comprehensive_test_suite(&mut self) -> Vec<TestResult> {
-        println!("🧪 Running Comprehensive Integration Test Suite...\n");
-        
-        let tests = vec![
-            self.test_basic_connectivity().await,
-            self.test_consensus_functionality().await,
-            self.test_transaction_processing().await,
-            self.test_interoperability_features().await,
-            self.test_smart_contract_integration().await,
-            self.test_oracle_functionality().await,
-            self.test_network_resilience().await,
-            self.test_performance_under_load().await,
-        ];
-        
-        tests
-    }
-    
-    pub fn generate_test_report(&self) -> String {
-        let mut report = String::new();
-        report.push_str("# Netchain Integration Test Report\n\n");
-        
-        let total_tests = self.test_results.len();
-        let passed_tests = self.test_results.iter().filter(|t| t.passed).count();
-        let success_rate = (passed_tests as f64 / total_tests as f64) * 100.0;
-        
-        report.push_str(&format!("## Test Summary\n\n"));
-        report.push_str(&format!("- **Total Tests**: {}\n", total_tests));
-        report.push_str(&format!("- **Passed**: {}\n", passed_tests));
-        report.push_str(&format!("- **Failed**: {}\n", total_tests - passed_tests));
-        report.push_str(&format!("- **Success Rate**: {:.1}%\n\n", success_rate));
-        
-        report.push_str("## Detailed Results\n\n");
-        report.push_str("| Test Name | Status | Duration | TPS | Success Rate | Avg Latency |\n");
-        report.push_str("|-----------|--------|----------|-----|--------------|-------------|\n");
-        
-        for result in &self.test_results {
-            let status = if result.passed { "✅ PASS" } else { "❌ FAIL" };
-            let tps = if result.duration.as_secs_f64() > 0.0 {
-                result.metrics.transactions_processed as f64 / result.duration.as_secs_f64()
-            } else {
-                0.0
-            };
-            
-            report.push_str(&format!(
-                "| {} | {} | {:.2}s | {:.0} | {:.1}% | {:.0}ms |\n",
-                result.test_name,
-                status,
-                result.duration.as_secs_f64(),
-                tps,
-                result.metrics.success_rate * 100.0,
-                result.metrics.average_latency_ms
-            ));
-        }
-        
-        // Performance summary
-        let total_transactions: u64 = self.test_results.iter()
-            .map(|r| r.metrics.transactions_processed)
-            .sum();
-        
-        let total_duration: f64 = self.test_results.iter()
-            .map(|r| r.duration.as_secs_f64())
-            .sum();
-        
-        let overall_tps = if total_duration > 0.0 {
-            total_transactions as f64 / total_duration
-        } else {
-            0.0
-        };
-        
-        report.push_str("\n## Performance Summary\n\n");
-        report.push_str(&format!("- **Total Transactions Processed**: {}\n", total_transactions));
-        report.push_str(&format!("- **Overall TPS**: {:.0}\n", overall_tps));
-        report.push_str(&format!("- **Total Test Duration**: {:.2}s\n", total_duration));
-        
-        // Resource usage summary
-        let avg_cpu: f64 = self.test_results.iter()
-            .map(|r| r.metrics.resource_usage.cpu_percent)
-            .sum::<f64>() / total_tests as f64;
-        
-        let avg_memory: f64 = self.test_results.iter()
-            .map(|r| r.metrics.resource_usage.memory_mb)
-            .sum::<f64>() / total_tests as f64;
-        
-        report.push_str("\n## Resource Usage\n\n");
-        report.push_str(&format!("- **Average CPU Usage**: {:.1}%\n", avg_cpu));
-        report.push_str(&format!("- **Average Memory Usage**: {:.0} MB\n", avg_memory));
-        
-        // Failure analysis
-        let failed_tests: Vec<_> = self.test_results.iter()
-            .filter(|t| !t.passed)
-            .collect();
-        
-        if !failed_tests.is_empty() {
-            report.push_str("\n## Failed Tests Analysis\n\n");
-            for test in failed_tests {
-                report.push_str(&format!("### {}\n", test.test_name));
-                if let Some(error) = &test.error_message {
-                    report.push_str(&format!("**Error**: {}\n\n", error));
-                }
-            }
-        }
-        
-        report.push_str("\n## Recommendations\n\n");
-        if success_rate >= 95.0 {
-            report.push_str("✅ **System is production-ready** - All critical tests passing\n");
-        } else if success_rate >= 80.0 {
-            report.push_str("⚠️ **System needs optimization** - Some tests failing\n");
-        } else {
-            report.push_str("❌ **System not ready** - Major issues detected\n");
-        }
-        
-        report
-    }
-}
-
-#[cfg(test)]
-mod integration_tests {
-    use super::*;
-
-    #[tokio::test]
-    async fn test_basic_connectivity() {
-        let mut suite = IntegrationTestSuite::new();
-        let result = suite.test_basic_connectivity().await;
-        
-        println!("Basic Connectivity Test:");
-        println!("  Status: {}", if result.passed { "PASS" } else { "FAIL" });
-        println!("  Duration: {:?}", result.duration);
-        println!("  Success Rate: {:.1}%", result.metrics.success_rate * 100.0);
-        
-        assert!(result.passed);
-    }
-
-    #[tokio::test]
-    async fn test_transaction_processing_integration() {
-        let mut suite = IntegrationTestSuite::new();
-        let result = suite.test_transaction_processing().await;
-        
-        println!("Transaction Processing Test:");
-        println!("  Status: {}", if result.passed { "PASS" } else { "FAIL" });
-        println!("  Transactions: {}", result.metrics.transactions_processed);
-        println!("  Success Rate: {:.1}%", result.metrics.success_rate * 100.0);
-        println!("  Avg Latency: {:.0}ms", result.metrics.average_latency_ms);
-        
-        assert!(result.passed);
-        assert!(result.metrics.success_rate >= 0.99);
-    }
-
-    #[tokio::test]
-    async fn test_interoperability_integration() {
-        let mut suite = IntegrationTestSuite::new();
-        let result = suite.test_interoperability_features().await;
-        
-        println!("Interoperability Test:");
-        println!("  Status: {}", if result.passed { "PASS" } else { "FAIL" });
-        println!("  Operations: {}", result.metrics.transactions_processed);
-        println!("  Success Rate: {:.1}%", result.metrics.success_rate * 100.0);
-        
-        assert!(result.passed);
-        assert!(result.metrics.success_rate >= 0.9);
-    }
-
-    #[tokio::test]
-    async fn test_performance_under_load() {
-        let mut suite = IntegrationTestSuite::new();
-        let result = suite.test_performance_under_load().await;
-        
-        let actual_tps = result.metrics.transactions_processed as f64 / result.duration.as_secs_f64();
-        
-        println!("Performance Under Load Test:");
-        println!("  Status: {}", if result.passed { "PASS" } else { "FAIL" });
-        println!("  Actual TPS: {:.0}", actual_tps);
-        println!("  Success Rate: {:.1}%", result.metrics.success_rate * 100.0);
-        println!("  CPU Usage: {:.1}%", result.metrics.resource_usage.cpu_percent);
-        println!("  Memory Usage: {:.0} MB", result.metrics.resource_usage.memory_mb);
-        
-        assert!(result.passed);
-        assert!(actual_tps >= 800.0); // Should achieve at least 800 TPS
-    }
-
-    #[tokio::test]
-    async fn test_comprehensive_suite() {
-        let mut suite = IntegrationTestSuite::new();
-        let results = suite.run_comprehensive_test_suite().await;
-        
-        let passed_count = results.iter().filter(|r| r.passed).count();
-        let total_count = results.len();
-        let success_rate = (passed_count as f64 / total_count as f64) * 100.0;
-        
-        println!("\nComprehensive Integration Test Results:");
-        println!("  Total Tests: {}", total_count);
-        println!("  Passed: {}", passed_count);
-        println!("  Failed: {}", total_count - passed_count);
-        println!("  Success Rate: {:.1}%", success_rate);
-        
-        // Print individual test results
-        for result in &results {
-            let status = if result.passed { "✅" } else { "❌" };
-            println!("  {} {}: {:.1}% success, {:.0}ms avg latency", 
-                status, result.test_name, 
-                result.metrics.success_rate * 100.0, 
-                result.metrics.average_latency_ms);
-        }
-        
-        // Generate and print full report
-        let report = suite.generate_test_report();
-        println!("\n{}", report);
-        
-        // Assertions for overall system health
-        assert!(success_rate >= 85.0, "Overall success rate should be at least 85%");
-        assert!(passed_count >= 6, "At least 6 out of 8 tests should pass");
-        
-        // Specific critical test assertions
-        let critical_tests = ["Basic Connectivity", "Transaction Processing", "Consensus Functionality"];
-        for test_name in &critical_tests {
-            let test_result = results.iter().find(|r| r.test_name == *test_name);
-            assert!(test_result.map_or(false, |r| r.passed), "{} test must pass", test_name);
-        }
-    }
-}
\ No newline at end of file
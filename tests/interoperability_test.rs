@@ -10,15 +10,16 @@ use frame_support::{
     assert_ok, assert_noop,
     traits::{Get, Currency},
     weights::Weight,
+    BoundedVec,
 };
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
+    traits::{BlakeTwo256, Hash, IdentityLookup},
     BuildStorage,
 };
 use pallet_ibc_core::{Event as IbcEvent, Error as IbcError};
-use pallet_oracle::{Event as OracleEvent, Error as OracleError};
+use pallet_oracle::{Event as OracleEvent, Error as OracleError, SourceTier};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -35,6 +36,10 @@ frame_support::construct_runtime!(
         Timestamp: pallet_timestamp,
         IbcCore: pallet_ibc_core,
         Oracle: pallet_oracle,
+        History: pallet_history,
+        CollateralDemo: pallet_collateral_demo,
+        Safeguard: pallet_safeguard,
+        OracleIbc: pallet_oracle_ibc,
     }
 );
 
@@ -107,9 +112,36 @@ parameter_types! {
     pub const MaxIbcClients: u32 = 100;
     pub const MaxIbcConnections: u32 = 200;
     pub const MaxIbcChannels: u32 = 500;
-    pub const IbcClientCreationFee: u128 = 10;
+    pub const IbcClientDeposit: u128 = 10;
     pub const IbcPacketTransmissionFee: u128 = 5;
+    pub const MaxIbcPacketDataSize: u32 = 1024;
+    pub const MaxIbcAcknowledgementLen: u32 = 1024;
+    pub const IbcRateLimitWindow: u64 = 10;
+    pub const MaxIbcPacketsPerWindow: u32 = 3;
+    pub const MaxIbcBytesPerWindow: u32 = 4096;
+    pub IbcHostChainId: Vec<u8> = b"netchain-test".to_vec();
+    pub const IbcHostUnbondingPeriod: u64 = 3600;
+    pub IbcHostNextValidatorsHash: H256 = H256::repeat_byte(7);
+    pub IbcHostAuthoritySet: (u64, pallet_ibc_core::light_client::grandpa::AuthorityList) =
+        (3, vec![(sp_core::ed25519::Public::from_raw([7; 32]), 1)]);
     pub const IbcPalletId: frame_support::PalletId = frame_support::PalletId(*b"test_ibc");
+    pub const MaxFeelessIbcRelaysPerBlock: u32 = 2;
+    pub const IbcMinRelayerBond: u128 = 20;
+}
+
+/// Echoes packet data back, failing packets whose data is `fail`; packets on the oracle port
+/// go to the oracle hub
+pub struct EchoRouter;
+impl pallet_ibc_core::IbcRouter<u64> for EchoRouter {
+    fn on_recv_packet(packet: &pallet_ibc_core::Packet, relayer: &u64) -> pallet_ibc_core::Acknowledgement {
+        if packet.destination_port.as_slice() == pallet_oracle_ibc::ORACLE_PORT {
+            OracleIbc::on_recv_packet(packet, relayer)
+        } else if packet.data[..] == *b"fail" {
+            Err(b"rejected: \"fail\"".to_vec())
+        } else {
+            Ok(packet.data.to_vec())
+        }
+    }
 }
 
 impl pallet_ibc_core::Config for Test {
@@ -118,8 +150,26 @@ impl pallet_ibc_core::Config for Test {
     type MaxClients = MaxIbcClients;
     type MaxConnections = MaxIbcConnections;
     type MaxChannels = MaxIbcChannels;
-    type ClientCreationFee = IbcClientCreationFee;
+    type ClientDeposit = IbcClientDeposit;
     type PacketTransmissionFee = IbcPacketTransmissionFee;
+    type MaxPacketDataSize = MaxIbcPacketDataSize;
+    type MaxAcknowledgementLen = MaxIbcAcknowledgementLen;
+    type RateLimitWindow = IbcRateLimitWindow;
+    type MaxPacketsPerWindow = MaxIbcPacketsPerWindow;
+    type MaxBytesPerWindow = MaxIbcBytesPerWindow;
+    type HostChainId = IbcHostChainId;
+    type HostUnbondingPeriod = IbcHostUnbondingPeriod;
+    type HostNextValidatorsHash = IbcHostNextValidatorsHash;
+    type HostAuthoritySet = IbcHostAuthoritySet;
+    type History = History;
+    type Safeguard = Safeguard;
+    type Router = EchoRouter;
+    type ChannelUpgradeOrigin = frame_system::EnsureRoot<u64>;
+    type ClientRemovalOrigin = frame_system::EnsureRoot<u64>;
+    type RelayerOrigin = frame_system::EnsureRoot<u64>;
+    type PortOrigin = frame_system::EnsureRoot<u64>;
+    type MaxFeelessRelaysPerBlock = MaxFeelessIbcRelaysPerBlock;
+    type MinRelayerBond = IbcMinRelayerBond;
     type PalletId = IbcPalletId;
     type WeightInfo = ();
 }
@@ -127,25 +177,134 @@ impl pallet_ibc_core::Config for Test {
 parameter_types! {
     pub const MaxOracleDataSources: u32 = 10;
     pub const MaxOracleDataSize: u32 = 1024;
+    pub const MaxOracleBatchSubmissions: u32 = 4;
     pub const OracleQueryFee: u128 = 2;
     pub const PremiumOracleQueryFee: u128 = 5;
     pub const OracleProviderReward: u128 = 1;
     pub const MaxOracleDataAge: u64 = 1200;
     pub const MinAggregationSources: u32 = 3;
+    pub static OracleMinAggregationDelay: u64 = 0;
+    pub const OracleHistoryDepth: u32 = 3;
+    pub const OracleCommitteeSize: u32 = 2;
+    pub const MaxOracleTrustedProviders: u32 = 4;
+    pub const OracleRoundLength: u64 = 10;
+    pub const OraclePremiumMinSources: u32 = 2;
+    pub const OraclePremiumMaxLatency: u64 = 5;
+    pub const OracleSlaReliabilityPenalty: u8 = 10;
+    pub const MaxOracleWatchedFeeds: u32 = 4;
+    pub const MaxFeelessOracleSubmissionsPerBlock: u32 = 2;
+    pub const MaxOracleRoundSigners: u32 = 4;
+    pub CriticalOracleFeedWeight: Weight = Weight::from_parts(250_000, 0);
+    pub const OracleRequestEraLength: u64 = 10;
+    pub const MaxOracleRequestsPerEra: u32 = 5;
+    pub const OracleRequestTtl: u64 = 20;
+    pub const OracleQuotaExemptBond: u128 = 1_000;
+    pub OracleOpenTier: pallet_oracle::TierRequirements<u128> = pallet_oracle::TierRequirements {
+        min_reputation: None,
+        min_bond: 0,
+        reward_multiplier: sp_runtime::FixedU128::from_u32(1),
+    };
+    pub OracleVerifiedTier: pallet_oracle::TierRequirements<u128> = pallet_oracle::TierRequirements {
+        min_reputation: Some(0),
+        min_bond: 0,
+        reward_multiplier: sp_runtime::FixedU128::from_u32(1),
+    };
+    pub OracleCriticalTier: pallet_oracle::TierRequirements<u128> = pallet_oracle::TierRequirements {
+        min_reputation: Some(80),
+        min_bond: 100,
+        reward_multiplier: sp_runtime::FixedU128::from_u32(3),
+    };
     pub const OraclePalletId: frame_support::PalletId = frame_support::PalletId(*b"test_orc");
 }
 
+/// Randomness derived from the subject and block number, deterministic for tests
+pub struct TestRandomness;
+impl frame_support::traits::Randomness<H256, u64> for TestRandomness {
+    fn random(subject: &[u8]) -> (H256, u64) {
+        let block = System::block_number();
+        (BlakeTwo256::hash(&[subject, &block.to_le_bytes()[..]].concat()), block)
+    }
+}
+
 impl pallet_oracle::Config for Test {
     type RuntimeEvent = RuntimeEvent;
     type Currency = Balances;
     type MaxDataSources = MaxOracleDataSources;
     type MaxDataSize = MaxOracleDataSize;
+    type MaxBatchSubmissions = MaxOracleBatchSubmissions;
     type OracleQueryFee = OracleQueryFee;
     type PremiumQueryFee = PremiumOracleQueryFee;
     type OracleReward = OracleProviderReward;
     type MaxDataAge = MaxOracleDataAge;
     type MinAggregationSources = MinAggregationSources;
+    type MinAggregationDelay = OracleMinAggregationDelay;
     type PalletId = OraclePalletId;
+    type History = History;
+    type Safeguard = Safeguard;
+    type HistoryDepth = OracleHistoryDepth;
+    type Randomness = TestRandomness;
+    type CommitteeSize = OracleCommitteeSize;
+    type MaxTrustedProviders = MaxOracleTrustedProviders;
+    type RoundLength = OracleRoundLength;
+    type PremiumMinSources = OraclePremiumMinSources;
+    type PremiumMaxLatency = OraclePremiumMaxLatency;
+    type SlaReliabilityPenalty = OracleSlaReliabilityPenalty;
+    type MaxWatchedFeeds = MaxOracleWatchedFeeds;
+    type MaxFeelessSubmissionsPerBlock = MaxFeelessOracleSubmissionsPerBlock;
+    type MaxRoundSigners = MaxOracleRoundSigners;
+    type CriticalFeedWeight = CriticalOracleFeedWeight;
+    type RequestEraLength = OracleRequestEraLength;
+    type MaxRequestsPerEra = MaxOracleRequestsPerEra;
+    type RequestTtl = OracleRequestTtl;
+    type QuotaExemptBond = OracleQuotaExemptBond;
+    type OpenTier = OracleOpenTier;
+    type VerifiedTier = OracleVerifiedTier;
+    type CriticalTier = OracleCriticalTier;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const HistoryDepth: u32 = 4;
+}
+
+impl pallet_history::Config for Test {
+    type HistoryDepth = HistoryDepth;
+}
+
+parameter_types! {
+    pub const OracleIbcPacketTimeout: u64 = 100;
+    pub const OracleIbcPalletId: frame_support::PalletId = frame_support::PalletId(*b"test_oib");
+}
+
+impl pallet_oracle_ibc::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type ChannelOrigin = frame_system::EnsureRoot<u64>;
+    type PacketTimeout = OracleIbcPacketTimeout;
+    type PalletId = OracleIbcPalletId;
+    type WeightInfo = ();
+}
+
+impl pallet_safeguard::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type PauseOrigin = frame_system::EnsureRoot<u64>;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub CollateralAsset: Vec<u8> = b"NET/USD".to_vec();
+    pub const LiquidationThreshold: sp_runtime::Perbill = sp_runtime::Perbill::from_percent(80);
+    pub const MaxPriceAge: u64 = 10;
+    pub const CollateralDecimals: u8 = 2;
+}
+
+impl pallet_collateral_demo::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type Currency = Balances;
+    type PriceProvider = Oracle;
+    type CollateralAsset = CollateralAsset;
+    type CollateralDecimals = CollateralDecimals;
+    type LiquidationThreshold = LiquidationThreshold;
+    type MaxPriceAge = MaxPriceAge;
     type WeightInfo = ();
 }
 
@@ -167,6 +326,141 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
     t.into()
 }
 
+// Helper to build a bounded chain identifier, version or packet payload
+pub fn bounded<S: Get<u32>>(raw: &[u8]) -> BoundedVec<u8, S> {
+    BoundedVec::truncate_from(raw.to_vec())
+}
+
+// Helper to build a bounded IBC identifier; readable local identifiers like `channel-0` are
+// converted to the sequential form the chain assigns
+pub fn id(raw: &[u8]) -> pallet_ibc_core::Identifier {
+    [pallet_ibc_core::CLIENT_PREFIX, pallet_ibc_core::CONNECTION_PREFIX, pallet_ibc_core::CHANNEL_PREFIX]
+        .iter()
+        .find_map(|prefix| pallet_ibc_core::parse_display_id(raw, prefix))
+        .unwrap_or_else(|| raw.to_vec())
+        .try_into()
+        .unwrap()
+}
+
+// Helper to encode the header updating a mock client to `height` with commitment root `root`
+pub fn mock_header(height: u64, root: H256) -> Vec<u8> {
+    use sp_core::Encode;
+    pallet_ibc_core::MockHeader { height, root }.encode()
+}
+
+// Helper to build a GRANDPA client type with a single authority, for clients any signed
+// account may create
+pub fn grandpa_client_type() -> pallet_ibc_core::ClientType {
+    use sp_core::Pair;
+    let authority = sp_core::ed25519::Pair::from_seed(&[1; 32]).public();
+    pallet_ibc_core::ClientType::Grandpa(pallet_ibc_core::GrandpaClient::new(0, vec![(authority, 1)]))
+}
+
+// Helper to update mock client `client` to a root committing to `packet` alone, returning
+// the proof height to receive it at
+pub fn prove_packet(client: &[u8], packet: &pallet_ibc_core::Packet) -> u64 {
+    let height = IbcCore::clients(id(client)).unwrap().latest_height + 1;
+    let path = pallet_ibc_core::commitment_path(&packet.source_port, &packet.source_channel, packet.sequence);
+    let root = pallet_ibc_core::membership_leaf(&path, &BlakeTwo256::hash_of(packet));
+    assert_ok!(IbcCore::update_client(RuntimeOrigin::root(), id(client), mock_header(height, root)));
+    height
+}
+
+// Helper to receive `packet` over mock client `client` with a proof of its commitment
+pub fn recv_proven(
+    relayer: u64,
+    client: &[u8],
+    packet: pallet_ibc_core::Packet,
+) -> frame_support::dispatch::DispatchResultWithPostInfo {
+    let proof_height = prove_packet(client, &packet);
+    IbcCore::recv_packet(RuntimeOrigin::signed(relayer), packet, vec![], proof_height)
+}
+
+// Helper to give each of `pairs` one vote in a Tendermint validator set
+pub fn tendermint_validators(
+    pairs: &[sp_core::ed25519::Pair],
+) -> Vec<pallet_ibc_core::light_client::tendermint::Validator> {
+    use sp_core::Pair;
+    pairs
+        .iter()
+        .map(|pair| pallet_ibc_core::light_client::tendermint::Validator { pub_key: pair.public(), voting_power: 1 })
+        .collect()
+}
+
+// Helper to build Tendermint header `height` of `cosmoshub-4` at `time`, with an app hash
+// derived from its height, produced by `validators` and announcing `next` as the next set,
+// committed by `signers` and carrying `trusted` as the client's trusted set
+pub fn tendermint_header(
+    height: u64,
+    time: u64,
+    validators: &[sp_core::ed25519::Pair],
+    next: &[sp_core::ed25519::Pair],
+    trusted: &[sp_core::ed25519::Pair],
+    signers: &[sp_core::ed25519::Pair],
+) -> pallet_ibc_core::light_client::tendermint::Header {
+    use pallet_ibc_core::light_client::tendermint::{
+        validator_set_hash, BlockHeader, Commit, CommitSig, Header, Vote,
+    };
+    use sp_core::{Encode, Pair};
+
+    let chain_id = b"cosmoshub-4".to_vec();
+    let header = BlockHeader {
+        chain_id: chain_id.clone(),
+        height,
+        time,
+        app_hash: H256::repeat_byte(height as u8),
+        validators_hash: validator_set_hash(&tendermint_validators(validators)),
+        next_validators_hash: validator_set_hash(&tendermint_validators(next)),
+    };
+    let vote = Vote { chain_id, height, round: 0, block_hash: header.hash() }.encode();
+    let signatures = signers
+        .iter()
+        .map(|pair| CommitSig { validator: pair.public(), signature: pair.sign(&vote) })
+        .collect();
+    Header {
+        commit: Commit { round: 0, block_hash: header.hash(), signatures },
+        header,
+        validator_set: tendermint_validators(validators),
+        trusted_validators: tendermint_validators(trusted),
+    }
+}
+
+// Helper to encode GRANDPA header `number`, carrying `logs` and with a state root derived from
+// its number, together with a justification signed by `voters` of authority set `set_id`
+pub fn grandpa_header(
+    number: u32,
+    logs: Vec<sp_runtime::DigestItem>,
+    set_id: u64,
+    voters: &[sp_core::ed25519::Pair],
+) -> Vec<u8> {
+    use pallet_ibc_core::light_client::grandpa::{
+        Commit, FinalizedHeader, Justification, Precommit, SignedPrecommit, SubstrateHeader,
+    };
+    use sp_core::{Encode, Pair};
+    use sp_runtime::traits::Header as _;
+
+    let header = SubstrateHeader::new(
+        number,
+        H256::zero(),
+        H256::repeat_byte(number as u8),
+        H256::zero(),
+        sp_runtime::Digest { logs },
+    );
+    let round = 1u64;
+    let precommit = Precommit { target_hash: header.hash(), target_number: number };
+    let precommits = voters
+        .iter()
+        .map(|pair| SignedPrecommit {
+            precommit: precommit.clone(),
+            // Localized payload: the precommit message (index 1), the round and the set id
+            signature: pair.sign(&(1u8, &precommit, round, set_id).encode()),
+            id: pair.public(),
+        })
+        .collect();
+    let commit = Commit { target_hash: precommit.target_hash, target_number: number, precommits };
+    FinalizedHeader { header, justification: Justification { round, commit, votes_ancestries: vec![] } }.encode()
+}
+
 #[cfg(test)]
 mod ibc_tests {
     use super::*;
@@ -174,9 +468,23 @@ mod ibc_tests {
     #[test]
     fn create_ibc_client_works() {
         new_test_ext().execute_with(|| {
+            // Mock clients verify nothing, so only root creates them
+            assert_noop!(
+                IbcCore::create_client(
+                    RuntimeOrigin::signed(1),
+                    pallet_ibc_core::ClientType::Mock,
+                    b"cosmos-testnet".to_vec(),
+                    100,
+                    67,
+                    1800,
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+
             // Create IBC client
             assert_ok!(IbcCore::create_client(
                 RuntimeOrigin::signed(1),
+                grandpa_client_type(),
                 b"cosmos-testnet".to_vec(),
                 100,
                 67, // 2/3 trust level
@@ -184,14 +492,15 @@ mod ibc_tests {
             ));
 
             // Check client was created
-            let client_id = b"client-0".to_vec();
+            let client_id = id(b"client-0");
             let client = IbcCore::clients(&client_id).unwrap();
             assert_eq!(client.chain_id, b"cosmos-testnet".to_vec());
             assert_eq!(client.latest_height, 100);
             assert_eq!(client.trust_level, 67);
 
-            // Check fee was charged
+            // Check deposit was reserved
             assert_eq!(Balances::free_balance(&1), 1_000_000 - 10);
+            assert_eq!(Balances::reserved_balance(&1), 10);
 
             // Check event was emitted
             System::assert_last_event(RuntimeEvent::IbcCore(
@@ -208,20 +517,25 @@ mod ibc_tests {
         new_test_ext().execute_with(|| {
             // Create client first
             assert_ok!(IbcCore::create_client(
-                RuntimeOrigin::signed(1),
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
                 b"cosmos-testnet".to_vec(),
                 100,
                 67,
                 1800,
             ));
 
-            let client_id = b"client-0".to_vec();
+            let client_id = id(b"client-0");
 
-            // Update client height
+            // Update client height; mock clients take headers from root only
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::signed(1), client_id.clone(), mock_header(150, H256::zero())),
+                sp_runtime::DispatchError::BadOrigin
+            );
             assert_ok!(IbcCore::update_client(
-                RuntimeOrigin::signed(1),
+                RuntimeOrigin::root(),
                 client_id.clone(),
-                150,
+                mock_header(150, H256::zero()),
             ));
 
             // Check client was updated
@@ -238,58 +552,578 @@ mod ibc_tests {
         });
     }
 
+    #[test]
+    fn clients_verify_headers_of_their_type() {
+        new_test_ext().execute_with(|| {
+            use pallet_ibc_core::{light_client::tendermint, ClientType, GrandpaClient, TendermintClient};
+            use sp_core::{ed25519, Encode, Pair};
+
+            let voters: Vec<_> = (1..=3).map(|seed| ed25519::Pair::from_seed(&[seed; 32])).collect();
+            let authorities = voters.iter().map(|pair| (pair.public(), 1)).collect();
+
+            let trusted = tendermint::validator_set_hash(&tendermint_validators(&voters));
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::signed(1),
+                ClientType::Tendermint(TendermintClient {
+                    next_validators_hash: trusted,
+                    latest_timestamp: 1_000,
+                    trusting_period: 1_200,
+                }),
+                b"cosmoshub-4".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::signed(1),
+                ClientType::Grandpa(GrandpaClient::new(5, authorities)),
+                b"substrate".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            assert_noop!(
+                IbcCore::create_client(RuntimeOrigin::signed(1), ClientType::Localhost, b"self".to_vec(), 1, 67, 1800),
+                IbcError::<Test>::UnsupportedClientType
+            );
+            let (cosmos, substrate) = (id(b"client-0"), id(b"client-1"));
+
+            // Headers of another type, chain or validator set are rejected
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::signed(1), cosmos.clone(), mock_header(101, H256::zero())),
+                IbcError::<Test>::InvalidHeader
+            );
+            let mut foreign = tendermint_header(101, 1_010, &voters, &voters, &voters, &voters);
+            foreign.header.chain_id = b"osmosis-1".to_vec();
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::signed(1), cosmos.clone(), foreign.encode()),
+                IbcError::<Test>::UntrustedHeader
+            );
+            let strangers: Vec<_> = (4..=6).map(|seed| ed25519::Pair::from_seed(&[seed; 32])).collect();
+            let unknown_signers = tendermint_header(101, 1_010, &strangers, &voters, &voters, &strangers);
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::signed(1), cosmos.clone(), unknown_signers.encode()),
+                IbcError::<Test>::UntrustedHeader
+            );
+
+            // A header of the trusted set is accepted, and the set it announces trusted next
+            assert_ok!(IbcCore::update_client(
+                RuntimeOrigin::signed(1),
+                cosmos.clone(),
+                tendermint_header(101, 1_010, &voters, &strangers, &voters, &voters).encode(),
+            ));
+            let client = IbcCore::clients(&cosmos).unwrap();
+            assert_eq!(
+                client.client_type,
+                ClientType::Tendermint(TendermintClient {
+                    next_validators_hash: tendermint::validator_set_hash(&tendermint_validators(&strangers)),
+                    latest_timestamp: 1_010,
+                    trusting_period: 1_200,
+                })
+            );
+            assert_eq!(IbcCore::consensus_states(&cosmos, 101).unwrap().root, H256::repeat_byte(101));
+
+            // GRANDPA headers must be finalized by the tracked authority set
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::signed(1), substrate.clone(), grandpa_header(120, vec![], 4, &voters)),
+                IbcError::<Test>::InvalidJustification
+            );
+            assert_ok!(IbcCore::update_client(
+                RuntimeOrigin::signed(1),
+                substrate.clone(),
+                grandpa_header(120, vec![], 5, &voters),
+            ));
+            assert_eq!(IbcCore::clients(&substrate).unwrap().latest_height, 120);
+            assert_eq!(IbcCore::consensus_states(&substrate, 120).unwrap().root, H256::repeat_byte(120));
+
+            // The localhost client follows this chain and is never updated
+            IbcCore::ensure_localhost();
+            let localhost = pallet_ibc_core::ClientId::truncate_from(pallet_ibc_core::LOCALHOST_CLIENT_ID.to_vec());
+            assert_eq!(IbcCore::clients(&localhost).unwrap().client_type, ClientType::Localhost);
+
+            // Counterparties track Netchain with a GRANDPA client of its current authority set
+            assert_eq!(
+                IbcCore::client_state_for_self().client_type,
+                ClientType::Grandpa(GrandpaClient::new(3, vec![(ed25519::Public::from_raw([7; 32]), 1)]))
+            );
+        });
+    }
+
+    #[test]
+    fn tendermint_clients_check_commits_and_freeze_on_misbehaviour() {
+        new_test_ext().execute_with(|| {
+            use pallet_ibc_core::{
+                light_client::tendermint::{validator_set_hash, Misbehaviour},
+                ClientType, TendermintClient,
+            };
+            use sp_core::{ed25519, Encode, Pair};
+
+            System::set_block_number(1);
+            let pairs: Vec<_> = (1..=6).map(|seed| ed25519::Pair::from_seed(&[seed; 32])).collect();
+            let trusted = &pairs[..4];
+            let client_type = |trusting_period| {
+                ClientType::Tendermint(TendermintClient {
+                    next_validators_hash: validator_set_hash(&tendermint_validators(trusted)),
+                    latest_timestamp: 1_000,
+                    trusting_period,
+                })
+            };
+
+            // Trust levels below a third, and trusting periods outlasting unbonding, are refused
+            assert_noop!(
+                IbcCore::create_client(RuntimeOrigin::signed(1), client_type(1_200), b"cosmoshub-4".to_vec(), 100, 20, 3_000),
+                IbcError::<Test>::InvalidClientState
+            );
+            assert_noop!(
+                IbcCore::create_client(RuntimeOrigin::signed(1), client_type(3_000), b"cosmoshub-4".to_vec(), 100, 67, 3_000),
+                IbcError::<Test>::InvalidClientState
+            );
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::signed(1),
+                client_type(1_200),
+                b"cosmoshub-4".to_vec(),
+                100,
+                67,
+                3_000,
+            ));
+            let cosmos = id(b"client-0");
+            let update = |header: pallet_ibc_core::light_client::tendermint::Header| {
+                IbcCore::update_client(RuntimeOrigin::signed(1), cosmos.clone(), header.encode())
+            };
+
+            // Two of four validators do not commit a block
+            assert_noop!(
+                update(tendermint_header(101, 1_010, trusted, trusted, trusted, &trusted[..2])),
+                IbcError::<Test>::InvalidJustification
+            );
+
+            // Signatures from outside the set, or more of them than the set has members, are
+            // refused before any is verified, even with enough of the set signing
+            let outsider = [&trusted[..3], &pairs[5..6]].concat();
+            assert_noop!(
+                update(tendermint_header(101, 1_010, trusted, trusted, trusted, &outsider)),
+                IbcError::<Test>::InvalidJustification
+            );
+            let repeated = [trusted, &trusted[..1]].concat();
+            assert_noop!(
+                update(tendermint_header(101, 1_010, trusted, trusted, trusted, &repeated)),
+                IbcError::<Test>::InvalidJustification
+            );
+
+            // Skipping to a changed set needs more than the trust level of the trusted set:
+            // half of it is not enough, three quarters are
+            let (half, most) = (&pairs[2..6], &pairs[1..5]);
+            assert_noop!(
+                update(tendermint_header(110, 1_100, half, half, trusted, half)),
+                IbcError::<Test>::InvalidJustification
+            );
+            assert_noop!(
+                update(tendermint_header(110, 1_100, most, most, half, most)),
+                IbcError::<Test>::UntrustedHeader
+            );
+            assert_ok!(update(tendermint_header(110, 1_100, most, most, trusted, most)));
+            assert_eq!(IbcCore::clients(&cosmos).unwrap().latest_height, 110);
+
+            // Past the trusting period the trusted set may have unbonded
+            pallet_timestamp::Pallet::<Test>::set_timestamp(2_300 * 1000);
+            assert_noop!(
+                update(tendermint_header(111, 1_110, most, most, most, most)),
+                IbcError::<Test>::ClientExpired
+            );
+            pallet_timestamp::Pallet::<Test>::set_timestamp(1_200 * 1000);
+
+            // The same header twice is no evidence; two blocks at one height are
+            let header_1 = tendermint_header(111, 1_110, most, most, most, most);
+            let evidence = |header_2| Misbehaviour { header_1: header_1.clone(), header_2 }.encode();
+            assert_noop!(
+                IbcCore::submit_misbehaviour(RuntimeOrigin::signed(2), cosmos.clone(), evidence(header_1.clone())),
+                IbcError::<Test>::InvalidMisbehaviour
+            );
+            let fork = tendermint_header(111, 1_111, most, most, most, most);
+            assert_ok!(IbcCore::submit_misbehaviour(RuntimeOrigin::signed(2), cosmos.clone(), evidence(fork)));
+            assert!(IbcCore::clients(&cosmos).unwrap().frozen);
+            System::assert_last_event(RuntimeEvent::IbcCore(IbcEvent::ClientFrozen { client_id: cosmos.clone() }));
+
+            // A frozen client takes no more headers
+            assert_noop!(update(header_1.clone()), IbcError::<Test>::ClientFrozen);
+        });
+    }
+
+    #[test]
+    fn grandpa_clients_follow_authority_set_changes() {
+        new_test_ext().execute_with(|| {
+            use pallet_ibc_core::{
+                light_client::grandpa::{PendingChange, GRANDPA_ENGINE_ID},
+                ClientType, GrandpaClient,
+            };
+            use sp_core::{ed25519, Encode, Pair};
+            use sp_runtime::DigestItem;
+
+            let pairs: Vec<_> = (1..=6).map(|seed| ed25519::Pair::from_seed(&[seed; 32])).collect();
+            let (old_set, new_set) = pairs.split_at(4);
+            let list = |set: &[ed25519::Pair]| set.iter().map(|pair| (pair.public(), 1)).collect::<Vec<_>>();
+
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::signed(1),
+                ClientType::Grandpa(GrandpaClient::new(0, list(old_set))),
+                b"substrate".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            let substrate = id(b"client-0");
+            let update = |header| IbcCore::update_client(RuntimeOrigin::signed(1), substrate.clone(), header);
+
+            // Two of four authorities are not more than two thirds of the set
+            assert_noop!(update(grandpa_header(110, vec![], 0, &old_set[..2])), IbcError::<Test>::InvalidJustification);
+            // Nor are votes of authorities outside the set counted
+            assert_noop!(update(grandpa_header(110, vec![], 0, &pairs[2..5])), IbcError::<Test>::InvalidJustification);
+
+            // A header scheduling a change 10 blocks on is finalized by the current set
+            let change = DigestItem::Consensus(GRANDPA_ENGINE_ID, (1u8, list(new_set), 10u32).encode());
+            assert_ok!(update(grandpa_header(110, vec![change], 0, &old_set[..3])));
+            let client = IbcCore::clients(&substrate).unwrap().client_type;
+            let ClientType::Grandpa(client) = client else { panic!("GRANDPA client expected") };
+            assert_eq!(client.authority_set_id, 0);
+            assert_eq!(
+                client.pending_change,
+                Some(PendingChange {
+                    next_authorities: BoundedVec::truncate_from(list(new_set)),
+                    effective_at: 120,
+                    forced: false,
+                })
+            );
+
+            // Headers past the enacting block must wait for it
+            assert_noop!(update(grandpa_header(121, vec![], 1, new_set)), IbcError::<Test>::UntrustedHeader);
+
+            // The enacting block is finalized by the old set and moves the client to the new one
+            assert_ok!(update(grandpa_header(120, vec![], 0, old_set)));
+            assert_eq!(
+                IbcCore::clients(&substrate).unwrap().client_type,
+                ClientType::Grandpa(GrandpaClient::new(1, list(new_set)))
+            );
+            assert_noop!(update(grandpa_header(130, vec![], 0, old_set)), IbcError::<Test>::InvalidJustification);
+            assert_ok!(update(grandpa_header(130, vec![], 1, new_set)));
+            assert_eq!(IbcCore::consensus_states(&substrate, 130).unwrap().root, H256::repeat_byte(130));
+
+            // Changes to an empty set are rejected, as are clients created with one
+            let empty = DigestItem::Consensus(GRANDPA_ENGINE_ID, (1u8, list(&[]), 0u32).encode());
+            assert_noop!(update(grandpa_header(135, vec![empty], 1, new_set)), IbcError::<Test>::UntrustedHeader);
+            assert_noop!(
+                IbcCore::create_client(
+                    RuntimeOrigin::signed(1),
+                    ClientType::Grandpa(GrandpaClient::new(0, vec![])),
+                    b"substrate".to_vec(),
+                    100,
+                    67,
+                    1800,
+                ),
+                IbcError::<Test>::InvalidClientState
+            );
+
+            // A forced change applies at its delay, without the old set finalizing that far
+            let forced = DigestItem::Consensus(GRANDPA_ENGINE_ID, (2u8, 130u32, list(old_set), 5u32).encode());
+            assert_ok!(update(grandpa_header(140, vec![forced], 1, new_set)));
+            assert_noop!(update(grandpa_header(145, vec![], 1, new_set)), IbcError::<Test>::InvalidJustification);
+            assert_ok!(update(grandpa_header(146, vec![], 2, &old_set[..3])));
+            assert_eq!(
+                IbcCore::clients(&substrate).unwrap().client_type,
+                ClientType::Grandpa(GrandpaClient::new(2, list(old_set)))
+            );
+
+            // A justification may not carry more precommits than the set has authorities
+            let repeated = [old_set[0].clone(), old_set[0].clone(), old_set[1].clone(), old_set[2].clone(), old_set[3].clone()];
+            assert_noop!(update(grandpa_header(150, vec![], 2, &repeated)), IbcError::<Test>::InvalidJustification);
+        });
+    }
+
+    #[test]
+    fn migrating_to_v1_types_existing_clients() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_ibc_core::{migrations::v1, ClientType};
+            use sp_core::Encode;
+
+            // Clients as the baseline runtime encoded them:
+            // `(chain_id, latest_height, frozen, trust_level, unbonding_period)`
+            let old = |chain_id: &[u8], unbonding_period: u64| {
+                (chain_id.to_vec(), 100u64, false, 67u32, unbonding_period).encode()
+            };
+            let localhost = pallet_ibc_core::ClientId::truncate_from(pallet_ibc_core::LOCALHOST_CLIENT_ID.to_vec());
+            let clients = [
+                (id(b"client-0"), old(b"cosmos-testnet", 1800)),
+                (id(b"client-1"), old(b"netchain-test", u64::MAX)),
+                (localhost, old(b"netchain-test", u64::MAX)),
+            ];
+            for (client_id, client) in &clients {
+                frame_support::storage::unhashed::put_raw(
+                    &pallet_ibc_core::Clients::<Test>::hashed_key_for(client_id),
+                    client,
+                );
+            }
+            StorageVersion::new(0).put::<IbcCore>();
+            pallet_timestamp::Pallet::<Test>::set_timestamp(5_000);
+
+            v1::AddClientTypes::<Test>::on_runtime_upgrade();
+
+            assert_eq!(IbcCore::on_chain_storage_version(), 1);
+            let client_type = |client_id| IbcCore::clients(client_id).unwrap().client_type;
+            assert_eq!(client_type(&clients[0].0), ClientType::Mock);
+            assert_eq!(client_type(&clients[1].0), ClientType::Localhost);
+            assert_eq!(client_type(&clients[2].0), ClientType::Localhost);
+            let migrated = IbcCore::clients(&clients[0].0).unwrap();
+            assert_eq!(migrated.chain_id, b"cosmos-testnet".to_vec());
+            assert_eq!(migrated.latest_height, 100);
+            assert_eq!(migrated.trust_level, 67);
+            assert_eq!(migrated.unbonding_period, 1800);
+            assert_eq!((migrated.last_updated, migrated.expired), (5, false));
+        });
+    }
+
+    #[test]
+    fn migrating_to_v2_gives_consensus_states_roots() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_ibc_core::migrations::v2;
+            use sp_core::Encode;
+
+            for _ in 0..2 {
+                assert_ok!(IbcCore::create_client(
+                    RuntimeOrigin::root(),
+                    pallet_ibc_core::ClientType::Mock,
+                    b"cosmos-testnet".to_vec(),
+                    100,
+                    67,
+                    1800,
+                ));
+            }
+            assert_ok!(IbcCore::update_client(RuntimeOrigin::root(), id(b"client-0"), mock_header(101, H256::repeat_byte(1))));
+
+            // Client 0 holds a consensus state without a root, client 1 none at all
+            let key = pallet_ibc_core::ConsensusStates::<Test>::hashed_key_for(id(b"client-0"), 100);
+            frame_support::storage::unhashed::put_raw(&key, &7u64.encode());
+            pallet_ibc_core::ConsensusStates::<Test>::remove(id(b"client-1"), 100);
+            StorageVersion::new(1).put::<IbcCore>();
+
+            v2::AddConsensusRoots::<Test>::on_runtime_upgrade();
+
+            assert_eq!(IbcCore::on_chain_storage_version(), 2);
+            let state = |client: &[u8], height| IbcCore::consensus_states(id(client), height).unwrap();
+            assert_eq!(state(b"client-0", 100), pallet_ibc_core::ConsensusState { timestamp: 7, root: H256::zero() });
+            assert_eq!(state(b"client-0", 101).root, H256::repeat_byte(1));
+            assert_eq!(state(b"client-1", 100).root, H256::zero());
+        });
+    }
+
+    #[test]
+    fn migrating_to_v3_orders_existing_channels() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_ibc_core::{migrations::v3, ChannelOrder, ChannelState};
+            use sp_core::Encode;
+
+            // A channel as the baseline runtime encoded it: `(state, connection_id, port_id,
+            // counterparty_port_id, version, next_sequence_send, next_sequence_recv,
+            // next_sequence_ack)`
+            let old = (
+                ChannelState::Open,
+                id(b"connection-0"),
+                id(b"transfer"),
+                id(b"transfer"),
+                b"ics20-1".to_vec(),
+                4u64,
+                3u64,
+                2u64,
+            );
+            let key = pallet_ibc_core::Channels::<Test>::hashed_key_for(id(b"transfer"), id(b"channel-0"));
+            frame_support::storage::unhashed::put_raw(&key, &old.encode());
+
+            // Channels already in the new layout are left alone
+            let current = pallet_ibc_core::ChannelEnd {
+                state: ChannelState::Open,
+                connection_id: id(b"connection-0"),
+                port_id: id(b"transfer"),
+                counterparty_port_id: id(b"transfer"),
+                version: bounded(b"ics20-1"),
+                ordering: ChannelOrder::Unordered,
+                upgrade_sequence: 2,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            };
+            pallet_ibc_core::Channels::<Test>::insert(id(b"transfer"), id(b"channel-1"), &current);
+            StorageVersion::new(2).put::<IbcCore>();
+
+            v3::AddChannelOrdering::<Test>::on_runtime_upgrade();
+
+            assert_eq!(IbcCore::on_chain_storage_version(), 3);
+            let migrated = IbcCore::channels(id(b"transfer"), id(b"channel-0")).unwrap();
+            assert_eq!((migrated.ordering, migrated.upgrade_sequence), (ChannelOrder::Ordered, 0));
+            assert_eq!(
+                (migrated.next_sequence_send, migrated.next_sequence_recv, migrated.next_sequence_ack),
+                (4, 3, 2)
+            );
+            assert_eq!(migrated.version, b"ics20-1".to_vec());
+            assert_eq!(IbcCore::channels(id(b"transfer"), id(b"channel-1")), Some(current));
+        });
+    }
+
+    #[test]
+    fn migrating_to_v4_binds_system_ports_to_their_owners() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_ibc_core::{migrations::v4, PortOwners};
+
+            frame_support::parameter_types! {
+                pub SystemPorts: Vec<(pallet_ibc_core::PortId, u64)> =
+                    vec![(id(b"transfer"), 7), (id(b"icahost"), 8)];
+            }
+
+            // Anyone could bind a free port up to version 3
+            PortOwners::<Test>::insert(id(b"transfer"), 2);
+            PortOwners::<Test>::insert(id(b"ping"), 2);
+            StorageVersion::new(3).put::<IbcCore>();
+
+            v4::BindSystemPorts::<Test, SystemPorts>::on_runtime_upgrade();
+
+            assert_eq!(IbcCore::on_chain_storage_version(), 4);
+            assert_eq!(PortOwners::<Test>::get(id(b"transfer")), Some(7));
+            assert_eq!(PortOwners::<Test>::get(id(b"icahost")), Some(8));
+            assert_eq!(PortOwners::<Test>::get(id(b"ping")), Some(2));
+        });
+    }
+
+    #[test]
+    fn migrating_to_v5_bounds_stored_vectors() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_ibc_core::{
+                migrations::v5, ChannelOrder, ChannelState, ConnectionState, MaxChainIdLen, MaxVersionLen,
+            };
+
+            let client = |chain_id: Vec<u8>| v5::OldClientState {
+                client_type: v5::OldClientType::Mock,
+                chain_id,
+                latest_height: 100,
+                frozen: false,
+                trust_level: 67,
+                unbonding_period: 1800,
+                last_updated: 0,
+                expired: false,
+            };
+            let long_chain_id = vec![b'c'; MaxChainIdLen::get() as usize + 1];
+            v5::Clients::<Test>::insert(id(b"client-0"), client(b"cosmos-testnet".to_vec()));
+            v5::Clients::<Test>::insert(id(b"client-1"), client(long_chain_id.clone()));
+            let long_version = vec![b'v'; MaxVersionLen::get() as usize + 1];
+            v5::Connections::<Test>::insert(id(b"connection-0"), v5::OldConnectionEnd {
+                state: ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"client-9"),
+                version: long_version.clone(),
+            });
+            v5::Channels::<Test>::insert(id(b"transfer"), id(b"channel-0"), v5::OldChannelEnd {
+                state: ChannelState::Open,
+                connection_id: id(b"connection-0"),
+                port_id: id(b"transfer"),
+                counterparty_port_id: id(b"transfer"),
+                version: b"ics20-1".to_vec(),
+                ordering: ChannelOrder::Unordered,
+                upgrade_sequence: 0,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            });
+            let long_ack = vec![b'a'; MaxIbcAcknowledgementLen::get() as usize + 1];
+            v5::PacketAcknowledgments::<Test>::insert(id(b"transfer"), 1, long_ack.clone());
+            StorageVersion::new(4).put::<IbcCore>();
+
+            v5::BoundStoredVecs::<Test>::on_runtime_upgrade();
+
+            assert_eq!(IbcCore::on_chain_storage_version(), 5);
+            // Clients within their bounds are left alone, others cut and frozen
+            let kept = IbcCore::clients(id(b"client-0")).unwrap();
+            assert_eq!((kept.chain_id.to_vec(), kept.frozen), (b"cosmos-testnet".to_vec(), false));
+            let cut = IbcCore::clients(id(b"client-1")).unwrap();
+            assert_eq!((cut.chain_id.to_vec(), cut.frozen), (long_chain_id[..64].to_vec(), true));
+
+            let connection = IbcCore::connections(id(b"connection-0")).unwrap();
+            assert_eq!(connection.version, long_version[..512].to_vec());
+            assert_eq!(IbcCore::channels(id(b"transfer"), id(b"channel-0")).unwrap().version, b"ics20-1".to_vec());
+            let ack = IbcCore::packet_acknowledgments(id(b"transfer"), 1).unwrap();
+            assert_eq!(ack, long_ack[..1024].to_vec());
+        });
+    }
+
     #[test]
     fn cross_chain_packet_flow_works() {
         new_test_ext().execute_with(|| {
             // Setup: Create client and connection (simplified)
             assert_ok!(IbcCore::create_client(
-                RuntimeOrigin::signed(1),
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
                 b"cosmos-testnet".to_vec(),
                 100,
                 67,
                 1800,
             ));
 
-            let client_id = b"client-0".to_vec();
+            let client_id = id(b"client-0");
 
             // Create connection
             assert_ok!(IbcCore::connection_open_init(
                 RuntimeOrigin::signed(1),
                 client_id.clone(),
-                b"counterparty-client-0".to_vec(),
+                id(b"counterparty-client-0"),
                 b"1.0".to_vec(),
             ));
 
             // Manually set connection to Open state for testing
-            let connection_id = b"connection-0".to_vec();
+            let connection_id = id(b"connection-0");
             let mut connection = IbcCore::connections(&connection_id).unwrap();
             connection.state = pallet_ibc_core::ConnectionState::Open;
             pallet_ibc_core::Connections::<Test>::insert(&connection_id, connection);
 
-            // Create channel
+            // Create channel on a port owned by the caller
+            assert_ok!(IbcCore::bind_port(RuntimeOrigin::root(), id(b"transfer"), 1));
             assert_ok!(IbcCore::channel_open_init(
                 RuntimeOrigin::signed(1),
-                b"transfer".to_vec(), // port_id
+                id(b"transfer"), // port_id
                 connection_id,
-                b"transfer".to_vec(), // counterparty_port_id
+                id(b"transfer"), // counterparty_port_id
                 b"ics20-1".to_vec(),
             ));
 
-            let port_id = b"transfer".to_vec();
-            let channel_id = b"channel-0".to_vec();
+            let port_id = id(b"transfer");
+            let channel_id = id(b"channel-0");
 
             // Manually set channel to Open state for testing
             let mut channel = IbcCore::channels(&port_id, &channel_id).unwrap();
             channel.state = pallet_ibc_core::ChannelState::Open;
             pallet_ibc_core::Channels::<Test>::insert(&port_id, &channel_id, channel);
 
+            // Only the port's owner sends on its channels
+            assert_noop!(
+                IbcCore::send_packet(
+                    RuntimeOrigin::signed(2),
+                    port_id.clone(),
+                    channel_id.clone(),
+                    id(b"transfer"),
+                    id(b"channel-1"),
+                    b"{}".to_vec(),
+                    200,
+                    0,
+                ),
+                IbcError::<Test>::NotPortOwner
+            );
+
             // Send cross-chain packet
             assert_ok!(IbcCore::send_packet(
                 RuntimeOrigin::signed(1),
                 port_id.clone(),
                 channel_id.clone(),
-                b"transfer".to_vec(), // destination_port
-                b"channel-1".to_vec(), // destination_channel
+                id(b"transfer"), // destination_port
+                id(b"channel-1"), // destination_channel
                 b"{\"amount\":\"1000\",\"denom\":\"NET\"}".to_vec(), // data
                 200, // timeout_height
                 0, // timeout_timestamp
@@ -300,7 +1134,7 @@ mod ibc_tests {
             assert!(!packet_hash.is_zero());
 
             // Check fee was charged
-            assert_eq!(Balances::free_balance(&1), 1_000_000 - 10 - 5); // client fee + packet fee
+            assert_eq!(Balances::free_balance(&1), 1_000_000 - 5); // packet fee; root's client holds no deposit
 
             // Check event was emitted
             System::assert_has_event(RuntimeEvent::IbcCore(
@@ -308,8 +1142,8 @@ mod ibc_tests {
                     sequence: 1,
                     source_port: port_id,
                     source_channel: channel_id,
-                    destination_port: b"transfer".to_vec(),
-                    destination_channel: b"channel-1".to_vec(),
+                    destination_port: id(b"transfer"),
+                    destination_channel: id(b"channel-1"),
                     data: b"{\"amount\":\"1000\",\"denom\":\"NET\"}".to_vec(),
                 }
             ));
@@ -317,51 +1151,1854 @@ mod ibc_tests {
     }
 
     #[test]
-    fn ibc_client_limits_enforced() {
+    fn packet_size_and_rate_limits_enforced() {
         new_test_ext().execute_with(|| {
-            // This test would require modifying MaxIbcClients to a small number for testing
-            // For now, just test that the limit exists
-            assert_eq!(MaxIbcClients::get(), 100);
+            System::set_block_number(1);
+
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            pallet_ibc_core::Connections::<Test>::insert(id(b"connection-0"), pallet_ibc_core::ConnectionEnd {
+                state: pallet_ibc_core::ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"counterparty-client-0"),
+                version: bounded(b"1.0"),
+            });
+
+            let port_id = id(b"transfer");
+            let channel_id = id(b"channel-0");
+            pallet_ibc_core::Channels::<Test>::insert(&port_id, &channel_id, pallet_ibc_core::ChannelEnd {
+                state: pallet_ibc_core::ChannelState::Open,
+                connection_id: id(b"connection-0"),
+                port_id: port_id.clone(),
+                counterparty_port_id: port_id.clone(),
+                version: bounded(b"ics20-1"),
+                ordering: pallet_ibc_core::ChannelOrder::Ordered,
+                upgrade_sequence: 0,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            });
+
+            assert_ok!(IbcCore::bind_port(RuntimeOrigin::root(), port_id.clone(), 1));
+            let send = |data: Vec<u8>| IbcCore::send_packet(
+                RuntimeOrigin::signed(1),
+                port_id.clone(),
+                channel_id.clone(),
+                id(b"transfer"),
+                id(b"channel-1"),
+                data,
+                0,
+                0,
+            );
+
+            // Oversized payloads are rejected outright
+            assert_noop!(send(vec![0u8; 1025]), IbcError::<Test>::PacketDataTooLarge);
+
+            // Three packets fit in the window; the third exhausts it
+            assert_ok!(send(b"a".to_vec()));
+            assert_ok!(send(b"b".to_vec()));
+            assert_ok!(send(b"c".to_vec()));
+            System::assert_has_event(RuntimeEvent::IbcCore(IbcEvent::ChannelRateLimited {
+                port_id: port_id.clone(),
+                channel_id: channel_id.clone(),
+                window_start: 1,
+            }));
+            assert_noop!(send(b"d".to_vec()), IbcError::<Test>::ChannelRateLimited);
+
+            // The bucket refills once the window rolls over
+            System::set_block_number(11);
+            assert_ok!(send(b"d".to_vec()));
         });
     }
-}
-
-#[cfg(test)]
-mod oracle_tests {
-    use super::*;
 
     #[test]
-    fn register_oracle_source_works() {
+    fn expired_client_blocks_updates_and_is_pruned() {
         new_test_ext().execute_with(|| {
-            // Register data source
-            assert_ok!(Oracle::register_source(
+            System::set_block_number(1);
+            assert_ok!(IbcCore::create_client(
                 RuntimeOrigin::root(),
-                b"coinbase_btc".to_vec(),
-                b"Coinbase BTC Price".to_vec(),
-                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
-                95, // high reliability
-            ));
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            let client_id = id(b"client-0");
+            assert_ok!(IbcCore::update_client(RuntimeOrigin::root(), client_id.clone(), mock_header(150, H256::zero())));
+            assert!(IbcCore::consensus_states(&client_id, 100).is_some());
+
+            // Let the unbonding period (seconds) elapse without updates
+            pallet_timestamp::Pallet::<Test>::set_timestamp(1801 * 1000);
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::root(), client_id.clone(), mock_header(200, H256::zero())),
+                IbcError::<Test>::ClientExpired
+            );
+
+            IbcCore::expire_and_prune(Weight::MAX);
+            assert!(IbcCore::clients(&client_id).unwrap().expired);
+            assert!(IbcCore::consensus_states(&client_id, 100).is_none());
+            assert!(IbcCore::consensus_states(&client_id, 150).is_some());
+        });
+    }
+
+    #[test]
+    fn expiry_walk_resumes_after_the_last_client_visited() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            for _ in 0..3 {
+                assert_ok!(IbcCore::create_client(
+                    RuntimeOrigin::root(),
+                    pallet_ibc_core::ClientType::Mock,
+                    b"cosmos-testnet".to_vec(),
+                    100,
+                    67,
+                    1800,
+                ));
+            }
+            pallet_timestamp::Pallet::<Test>::set_timestamp(1801 * 1000);
+
+            // A walk left off after the first client in storage order visits the others
+            let order: Vec<_> = pallet_ibc_core::Clients::<Test>::iter_keys().collect();
+            let cursor = pallet_ibc_core::Clients::<Test>::hashed_key_for(&order[0]);
+            pallet_ibc_core::ExpiryCursor::<Test>::put(pallet_ibc_core::ClearCursor::truncate_from(cursor));
+            IbcCore::expire_and_prune(Weight::MAX);
+            let expired = |client_id| IbcCore::clients(client_id).unwrap().expired;
+            assert!(!expired(&order[0]));
+            assert!(expired(&order[1]) && expired(&order[2]));
+            assert!(pallet_ibc_core::ExpiryCursor::<Test>::get().is_none());
+
+            // The next walk starts over from the first client
+            IbcCore::expire_and_prune(Weight::MAX);
+            assert!(expired(&order[0]));
+        });
+    }
+
+    #[test]
+    fn client_deposits_are_refunded_on_close_and_slots_reclaimed() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            for _ in 0..2 {
+                assert_ok!(IbcCore::create_client(
+                    RuntimeOrigin::signed(1),
+                    grandpa_client_type(),
+                    b"cosmos-testnet".to_vec(),
+                    100,
+                    67,
+                    1800,
+                ));
+            }
+            assert_eq!(Balances::reserved_balance(&1), 20);
+
+            // Live clients cannot be closed, nor by anyone but their creator
+            let client_id = id(b"client-0");
+            assert_noop!(
+                IbcCore::close_client(RuntimeOrigin::signed(1), client_id.clone()),
+                IbcError::<Test>::ClientStillActive
+            );
+            pallet_timestamp::Pallet::<Test>::set_timestamp(1801 * 1000);
+            assert_noop!(
+                IbcCore::close_client(RuntimeOrigin::signed(2), client_id.clone()),
+                IbcError::<Test>::NotClientOwner
+            );
+
+            assert_ok!(IbcCore::close_client(RuntimeOrigin::signed(1), client_id.clone()));
+            assert!(IbcCore::clients(&client_id).is_none());
+            assert!(IbcCore::consensus_states(&client_id, 100).is_none());
+            assert_eq!(Balances::reserved_balance(&1), 10);
+
+            // Governance removes the abandoned one and slashes its deposit
+            assert_ok!(IbcCore::remove_client(RuntimeOrigin::root(), id(b"client-1")));
+            assert_eq!(Balances::reserved_balance(&1), 0);
+            assert_eq!(Balances::free_balance(&1), 1_000_000 - 10);
+            assert_eq!(IbcCore::removed_clients(), 2);
+
+            // Slots are reclaimed, identifiers are not
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::signed(2),
+                grandpa_client_type(),
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            assert!(IbcCore::clients(&id(b"client-2")).is_some());
+        });
+    }
+
+    #[test]
+    fn removed_clients_consensus_states_are_cleared_over_idle_blocks() {
+        use frame_support::traits::Hooks;
+
+        let mut ext = new_test_ext();
+        ext.execute_with(|| {
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            for height in 101..=150 {
+                assert_ok!(IbcCore::update_client(RuntimeOrigin::root(), id(b"client-0"), mock_header(height, H256::zero())));
+            }
+        });
+        ext.commit_all().unwrap();
+
+        ext.execute_with(|| {
+            let client_id = id(b"client-0");
+            let remaining = || pallet_ibc_core::ConsensusStates::<Test>::iter_prefix(&client_id).count();
+
+            // The removal itself clears a bounded number of consensus states
+            assert_ok!(IbcCore::remove_client(RuntimeOrigin::root(), client_id.clone()));
+            assert_eq!(remaining(), 51 - pallet_ibc_core::MAX_CONSENSUS_STATES_CLEARED as usize);
+            assert!(pallet_ibc_core::ConsensusStateCleanup::<Test>::contains_key(&client_id));
+
+            // Idle blocks clear the rest, as many as their weight allows
+            IbcCore::clear_removed_consensus_states(Weight::from_parts(10, u64::MAX));
+            assert_eq!(remaining(), 9);
+            IbcCore::on_idle(2, Weight::MAX);
+            assert_eq!(remaining(), 0);
+            assert!(!pallet_ibc_core::ConsensusStateCleanup::<Test>::contains_key(&client_id));
+        });
+    }
+
+    #[test]
+    fn malformed_identifiers_rejected() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+
+            // Local client IDs must be `client-` followed by a big-endian `u32`
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::signed(1), id(b"client-00"), mock_header(150, H256::zero())),
+                IbcError::<Test>::InvalidIdentifier
+            );
+            assert_noop!(
+                IbcCore::update_client(RuntimeOrigin::signed(1), id(b"conn-0"), mock_header(150, H256::zero())),
+                IbcError::<Test>::InvalidIdentifier
+            );
+
+            // Counterparty IDs are only checked against the identifier charset
+            assert_noop!(
+                IbcCore::connection_open_init(
+                    RuntimeOrigin::signed(1),
+                    id(b"client-0"),
+                    id(b"bad client"),
+                    b"1.0".to_vec(),
+                ),
+                IbcError::<Test>::InvalidIdentifier
+            );
+            assert_ok!(IbcCore::connection_open_init(
+                RuntimeOrigin::signed(1),
+                id(b"client-0"),
+                id(b"07-tendermint-0"),
+                b"1.0".to_vec(),
+            ));
+        });
+    }
+
+    #[test]
+    fn recv_packet_stores_application_acknowledgement() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            pallet_ibc_core::Connections::<Test>::insert(id(b"connection-0"), pallet_ibc_core::ConnectionEnd {
+                state: pallet_ibc_core::ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"counterparty-client-0"),
+                version: bounded(b"1.0"),
+            });
+
+            let port_id = id(b"transfer");
+            let channel_id = id(b"channel-0");
+            pallet_ibc_core::Channels::<Test>::insert(&port_id, &channel_id, pallet_ibc_core::ChannelEnd {
+                state: pallet_ibc_core::ChannelState::Open,
+                connection_id: id(b"connection-0"),
+                port_id: port_id.clone(),
+                counterparty_port_id: port_id.clone(),
+                version: bounded(b"ics20-1"),
+                ordering: pallet_ibc_core::ChannelOrder::Ordered,
+                upgrade_sequence: 0,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            });
+
+            let packet = |sequence: u64, data: &[u8]| pallet_ibc_core::Packet {
+                sequence,
+                source_port: id(b"transfer"),
+                source_channel: id(b"channel-7"),
+                destination_port: port_id.clone(),
+                destination_channel: channel_id.clone(),
+                data: bounded(data),
+                timeout_height: 0,
+                timeout_timestamp: 0,
+            };
+
+            // Only packets the counterparty port is proven to have committed are received
+            let proof_height = prove_packet(b"client-0", &packet(1, b"hi"));
+            assert_noop!(
+                IbcCore::recv_packet(RuntimeOrigin::signed(2), packet(1, b"forged"), vec![], proof_height),
+                IbcError::<Test>::InvalidProof
+            );
+            assert_noop!(
+                IbcCore::recv_packet(RuntimeOrigin::signed(2), packet(1, b"hi"), vec![], proof_height + 1),
+                IbcError::<Test>::ConsensusStateNotFound
+            );
+            let other_port = pallet_ibc_core::Packet { source_port: id(b"oracle"), ..packet(1, b"hi") };
+            assert_noop!(
+                IbcCore::recv_packet(RuntimeOrigin::signed(2), other_port, vec![], proof_height),
+                IbcError::<Test>::CounterpartyMismatch
+            );
+
+            // Application success is acknowledged with the base64 result
+            assert_ok!(IbcCore::recv_packet(RuntimeOrigin::signed(2), packet(1, b"hi"), vec![], proof_height));
+            assert_eq!(
+                IbcCore::packet_acknowledgments(&port_id, 1).unwrap(),
+                b"{\"result\":\"aGk=\"}".to_vec()
+            );
+
+            // Application failure is acknowledged with the escaped error
+            assert_ok!(recv_proven(2, b"client-0", packet(2, b"fail")));
+            assert_eq!(
+                IbcCore::packet_acknowledgments(&port_id, 2).unwrap(),
+                b"{\"error\":\"rejected: \\\"fail\\\"\"}".to_vec()
+            );
+            System::assert_has_event(RuntimeEvent::IbcCore(IbcEvent::PacketReceived {
+                sequence: 2,
+                source_port: id(b"transfer"),
+                source_channel: id(b"channel-7"),
+                destination_port: port_id.clone(),
+                destination_channel: channel_id.clone(),
+                data: b"fail".to_vec(),
+                acknowledgement: Err(b"rejected: \"fail\"".to_vec()),
+            }));
+            System::assert_has_event(RuntimeEvent::IbcCore(IbcEvent::PacketRejected {
+                sequence: 2,
+                port_id: port_id.clone(),
+                channel_id: channel_id.clone(),
+                reason: pallet_ibc_core::FailureReason::ApplicationRejected,
+            }));
+        });
+    }
+
+    #[test]
+    fn registered_relayers_are_refunded_for_successful_deliveries_up_to_block_quota() {
+        use frame_support::{dispatch::Pays, traits::Hooks};
+        use sp_runtime::traits::Dispatchable;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            pallet_ibc_core::Connections::<Test>::insert(id(b"connection-0"), pallet_ibc_core::ConnectionEnd {
+                state: pallet_ibc_core::ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"counterparty-client-0"),
+                version: bounded(b"1.0"),
+            });
+            pallet_ibc_core::Channels::<Test>::insert(id(b"transfer"), id(b"channel-0"), pallet_ibc_core::ChannelEnd {
+                state: pallet_ibc_core::ChannelState::Open,
+                connection_id: id(b"connection-0"),
+                port_id: id(b"transfer"),
+                counterparty_port_id: id(b"transfer"),
+                version: bounded(b"ics20-1"),
+                ordering: pallet_ibc_core::ChannelOrder::Ordered,
+                upgrade_sequence: 0,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            });
+            let recv = |sequence: u64| {
+                let packet = pallet_ibc_core::Packet {
+                    sequence,
+                    source_port: id(b"transfer"),
+                    source_channel: id(b"channel-7"),
+                    destination_port: id(b"transfer"),
+                    destination_channel: id(b"channel-0"),
+                    data: bounded(b"hi"),
+                    timeout_height: 0,
+                    timeout_timestamp: 0,
+                };
+                let proof_height = prove_packet(b"client-0", &packet);
+                RuntimeCall::IbcCore(pallet_ibc_core::Call::recv_packet {
+                    packet,
+                    proof_commitment: vec![],
+                    proof_height,
+                })
+            };
+
+            assert_eq!(recv(1).dispatch(RuntimeOrigin::signed(2)).unwrap().pays_fee, Pays::Yes);
+            assert_ok!(IbcCore::register_relayer(RuntimeOrigin::signed(2), 20));
+            System::assert_last_event(RuntimeEvent::IbcCore(IbcEvent::RelayerRegistered { relayer: 2, bond: 20 }));
+
+            // Failed relays pay and leave the quota alone
+            let failed = recv(3).dispatch(RuntimeOrigin::signed(2)).unwrap_err();
+            assert_eq!(failed.error, IbcError::<Test>::InvalidSequence.into());
+            assert_eq!(failed.post_info.pays_fee, Pays::Yes);
+            assert_eq!(IbcCore::feeless_relays(), 0);
+
+            // Only registered relayers are refunded, until the block quota is used up
+            assert_eq!(recv(2).dispatch(RuntimeOrigin::signed(2)).unwrap().pays_fee, Pays::No);
+            assert_eq!(recv(3).dispatch(RuntimeOrigin::signed(3)).unwrap().pays_fee, Pays::Yes);
+            assert_eq!(recv(4).dispatch(RuntimeOrigin::signed(2)).unwrap().pays_fee, Pays::No);
+            assert_eq!(IbcCore::feeless_relays(), 2);
+            assert_eq!(recv(5).dispatch(RuntimeOrigin::signed(2)).unwrap().pays_fee, Pays::Yes);
+
+            // The quota refills every block
+            IbcCore::on_initialize(2);
+            assert_eq!(recv(6).dispatch(RuntimeOrigin::signed(2)).unwrap().pays_fee, Pays::No);
+
+            assert_ok!(IbcCore::deregister_relayer(RuntimeOrigin::signed(2)));
+            assert_eq!(recv(7).dispatch(RuntimeOrigin::signed(2)).unwrap().pays_fee, Pays::Yes);
+            assert_noop!(
+                IbcCore::deregister_relayer(RuntimeOrigin::signed(2)),
+                IbcError::<Test>::RelayerNotRegistered
+            );
+        });
+    }
+
+    #[test]
+    fn relayers_bond_to_register_and_deliveries_are_attributed_to_them() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            pallet_ibc_core::Connections::<Test>::insert(id(b"connection-0"), pallet_ibc_core::ConnectionEnd {
+                state: pallet_ibc_core::ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"counterparty-client-0"),
+                version: bounded(b"1.0"),
+            });
+            pallet_ibc_core::Channels::<Test>::insert(id(b"transfer"), id(b"channel-0"), pallet_ibc_core::ChannelEnd {
+                state: pallet_ibc_core::ChannelState::Open,
+                connection_id: id(b"connection-0"),
+                port_id: id(b"transfer"),
+                counterparty_port_id: id(b"transfer"),
+                version: bounded(b"ics20-1"),
+                ordering: pallet_ibc_core::ChannelOrder::Unordered,
+                upgrade_sequence: 0,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            });
+            let recv = |relayer: u64, sequence: u64| {
+                recv_proven(relayer, b"client-0", pallet_ibc_core::Packet {
+                    sequence,
+                    source_port: id(b"transfer"),
+                    source_channel: id(b"channel-7"),
+                    destination_port: id(b"transfer"),
+                    destination_channel: id(b"channel-0"),
+                    data: bounded(b"hi"),
+                    timeout_height: 0,
+                    timeout_timestamp: 0,
+                })
+            };
+
+            assert_noop!(
+                IbcCore::register_relayer(RuntimeOrigin::signed(2), 19),
+                IbcError::<Test>::InsufficientRelayerBond
+            );
+            assert_ok!(IbcCore::register_relayer(RuntimeOrigin::signed(2), 20));
+            assert_ok!(IbcCore::register_relayer(RuntimeOrigin::signed(3), 50));
+            assert_noop!(
+                IbcCore::register_relayer(RuntimeOrigin::signed(3), 50),
+                IbcError::<Test>::RelayerAlreadyRegistered
+            );
+            assert_eq!(Balances::reserved_balance(&2), 20);
+            assert_eq!(Balances::reserved_balance(&3), 50);
+
+            // Deliveries of registered relayers are counted, others' are not
+            assert_ok!(recv(2, 1));
+            System::set_block_number(2);
+            assert_ok!(recv(3, 2));
+            assert_ok!(recv(4, 3));
+            let stats = IbcCore::relayer_stats(3);
+            assert_eq!((stats.packets_received, stats.last_active), (1, 2));
+            assert_eq!(IbcCore::relayer_stats(2).packets_received, 1);
+            assert_eq!(IbcCore::relayer_stats(4), Default::default());
+            let leaderboard = |limit| -> Vec<u64> {
+                IbcCore::relayer_leaderboard(limit).into_iter().map(|(relayer, _)| relayer).collect()
+            };
+            assert_eq!(leaderboard(10), vec![3, 2]);
+
+            // Timeouts count too
+            pallet_ibc_core::PacketCommitments::<Test>::insert(id(b"transfer"), 9, H256::zero());
+            System::set_block_number(3);
+            assert_ok!(IbcCore::timeout_packet(RuntimeOrigin::signed(2), id(b"transfer"), id(b"channel-0"), 9));
+            assert_eq!(IbcCore::relayer_stats(2).packets_timed_out, 1);
+
+            // Most deliveries first, the most recently active among equals
+            assert_eq!(leaderboard(10), vec![2, 3]);
+            assert_eq!(leaderboard(1), vec![2]);
+
+            // Deregistering refunds the bond and drops the stats
+            assert_ok!(IbcCore::deregister_relayer(RuntimeOrigin::signed(2)));
+            System::assert_last_event(RuntimeEvent::IbcCore(IbcEvent::RelayerDeregistered {
+                relayer: 2,
+                refunded: 20,
+            }));
+            assert_eq!(Balances::reserved_balance(&2), 0);
+            assert_eq!(IbcCore::relayer_stats(2), Default::default());
+
+            // Governance removal slashes it
+            assert_noop!(
+                IbcCore::remove_relayer(RuntimeOrigin::signed(1), 3),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(IbcCore::remove_relayer(RuntimeOrigin::root(), 3));
+            System::assert_last_event(RuntimeEvent::IbcCore(IbcEvent::RelayerRemoved { relayer: 3, slashed: 50 }));
+            assert_eq!(Balances::reserved_balance(&3), 0);
+            assert_eq!(Balances::free_balance(&3), 1_000_000 - 50);
+            assert!(leaderboard(10).is_empty());
+        });
+    }
+
+    #[test]
+    fn acknowledge_packet_requires_valid_proof() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            pallet_ibc_core::Connections::<Test>::insert(id(b"connection-0"), pallet_ibc_core::ConnectionEnd {
+                state: pallet_ibc_core::ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"counterparty-client-0"),
+                version: bounded(b"1.0"),
+            });
+
+            let port_id = id(b"transfer");
+            let channel_id = id(b"channel-0");
+            pallet_ibc_core::Channels::<Test>::insert(&port_id, &channel_id, pallet_ibc_core::ChannelEnd {
+                state: pallet_ibc_core::ChannelState::Open,
+                connection_id: id(b"connection-0"),
+                port_id: port_id.clone(),
+                counterparty_port_id: port_id.clone(),
+                version: bounded(b"ics20-1"),
+                ordering: pallet_ibc_core::ChannelOrder::Ordered,
+                upgrade_sequence: 0,
+                next_sequence_send: 1,
+                next_sequence_recv: 1,
+                next_sequence_ack: 1,
+            });
+
+            assert_ok!(IbcCore::bind_port(RuntimeOrigin::root(), port_id.clone(), 1));
+            assert_ok!(IbcCore::send_packet(
+                RuntimeOrigin::signed(1),
+                port_id.clone(),
+                channel_id.clone(),
+                id(b"transfer"),
+                id(b"channel-1"),
+                b"hi".to_vec(),
+                200,
+                0,
+            ));
+            let packet = pallet_ibc_core::Packet {
+                sequence: 1,
+                source_port: port_id.clone(),
+                source_channel: channel_id.clone(),
+                destination_port: id(b"transfer"),
+                destination_channel: id(b"channel-1"),
+                data: bounded(b"hi"),
+                timeout_height: 200,
+                timeout_timestamp: 0,
+            };
+
+            // Counterparty commitment tree: the acknowledgement leaf and one sibling
+            let ack = b"{\"result\":\"aGk=\"}".to_vec();
+            let leaf = pallet_ibc_core::membership_leaf(
+                &pallet_ibc_core::acknowledgement_path(b"transfer", &id(b"channel-1"), 1),
+                &BlakeTwo256::hash(&ack),
+            );
+            let sibling = H256::repeat_byte(7);
+            let root = BlakeTwo256::hash(&[leaf.as_bytes(), sibling.as_bytes()].concat());
+            let proof = vec![pallet_ibc_core::ProofStep { sibling, sibling_is_left: false }];
+            assert_ok!(IbcCore::update_client(RuntimeOrigin::root(), id(b"client-0"), mock_header(150, root)));
+
+            // No consensus state at the proof height
+            assert_noop!(
+                IbcCore::acknowledge_packet(RuntimeOrigin::signed(2), packet.clone(), ack.clone(), proof.clone(), 140),
+                IbcError::<Test>::ConsensusStateNotFound
+            );
+
+            // Acknowledgement the counterparty never wrote
+            assert_noop!(
+                IbcCore::acknowledge_packet(
+                    RuntimeOrigin::signed(2),
+                    packet.clone(),
+                    b"{\"error\":\"forged\"}".to_vec(),
+                    proof.clone(),
+                    150,
+                ),
+                IbcError::<Test>::InvalidProof
+            );
+
+            // Packet differing from the one committed to
+            let mut tampered = packet.clone();
+            tampered.data = bounded(b"hj");
+            assert_noop!(
+                IbcCore::acknowledge_packet(RuntimeOrigin::signed(2), tampered, ack.clone(), proof.clone(), 150),
+                IbcError::<Test>::PacketCommitmentMismatch
+            );
+
+            assert_ok!(IbcCore::acknowledge_packet(RuntimeOrigin::signed(2), packet, ack, proof, 150));
+            assert_eq!(IbcCore::packet_status(&port_id, 1), pallet_ibc_core::PacketStatus::Acknowledged);
+        });
+    }
+
+    #[test]
+    fn port_binding_prevents_channel_squatting() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+
+            pallet_ibc_core::Connections::<Test>::insert(id(b"connection-0"), pallet_ibc_core::ConnectionEnd {
+                state: pallet_ibc_core::ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"counterparty-client-0"),
+                version: bounded(b"1.0"),
+            });
+            let open = |who: u64| IbcCore::channel_open_init(
+                RuntimeOrigin::signed(who),
+                id(b"transfer"),
+                id(b"connection-0"),
+                id(b"transfer"),
+                b"ics20-1".to_vec(),
+            );
+
+            // Unbound ports cannot carry channels
+            assert_noop!(open(1), IbcError::<Test>::PortNotBound);
+
+            // Ports are bound by governance, not claimed by whoever asks first
+            assert_noop!(
+                IbcCore::bind_port(RuntimeOrigin::signed(1), id(b"transfer"), 1),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(IbcCore::bind_port(RuntimeOrigin::root(), id(b"transfer"), 1));
+            System::assert_has_event(RuntimeEvent::IbcCore(IbcEvent::PortBound {
+                port_id: id(b"transfer"),
+                owner: 1,
+            }));
+            assert_noop!(
+                IbcCore::bind_port(RuntimeOrigin::root(), id(b"transfer"), 2),
+                IbcError::<Test>::PortAlreadyBound
+            );
+
+            // Only the owner may open channels on the port
+            assert_noop!(open(2), IbcError::<Test>::NotPortOwner);
+            assert_ok!(open(1));
+        });
+    }
+
+    #[test]
+    fn channel_upgrade_renegotiates_without_losing_sequences() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+
+            assert_ok!(IbcCore::create_client(
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
+                b"cosmos-testnet".to_vec(),
+                100,
+                67,
+                1800,
+            ));
+            pallet_ibc_core::Connections::<Test>::insert(id(b"connection-0"), pallet_ibc_core::ConnectionEnd {
+                state: pallet_ibc_core::ConnectionState::Open,
+                client_id: id(b"client-0"),
+                counterparty_client_id: id(b"counterparty-client-0"),
+                version: bounded(b"1.0"),
+            });
+
+            let port_id = id(b"transfer");
+            let (a, b) = (id(b"channel-0"), id(b"channel-1"));
+            for channel_id in [&a, &b] {
+                pallet_ibc_core::Channels::<Test>::insert(&port_id, channel_id, pallet_ibc_core::ChannelEnd {
+                    state: pallet_ibc_core::ChannelState::Open,
+                    connection_id: id(b"connection-0"),
+                    port_id: port_id.clone(),
+                    counterparty_port_id: port_id.clone(),
+                    version: bounded(b"ics20-1"),
+                    ordering: pallet_ibc_core::ChannelOrder::Ordered,
+                    upgrade_sequence: 0,
+                    next_sequence_send: 5,
+                    next_sequence_recv: 3,
+                    next_sequence_ack: 1,
+                });
+            }
+
+            let fields = pallet_ibc_core::UpgradeFields {
+                ordering: pallet_ibc_core::ChannelOrder::Unordered,
+                version: bounded(b"{\"fee_version\":\"ics29-1\",\"app_version\":\"ics20-1\"}"),
+            };
+
+            // Only the upgrade authority may propose
+            assert_noop!(
+                IbcCore::channel_upgrade_init(RuntimeOrigin::signed(1), port_id.clone(), a.clone(), fields.clone()),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(IbcCore::channel_upgrade_init(RuntimeOrigin::root(), port_id.clone(), a.clone(), fields.clone()));
+            assert_noop!(
+                IbcCore::channel_upgrade_init(RuntimeOrigin::root(), port_id.clone(), a.clone(), fields.clone()),
+                IbcError::<Test>::UpgradeInProgress
+            );
+
+            // Counterparty accepts and pauses sending
+            assert_noop!(
+                IbcCore::channel_upgrade_try(RuntimeOrigin::root(), port_id.clone(), b.clone(), fields.clone(), 0),
+                IbcError::<Test>::StaleUpgradeSequence
+            );
+            assert_ok!(IbcCore::channel_upgrade_try(RuntimeOrigin::root(), port_id.clone(), b.clone(), fields.clone(), 1));
+            assert_eq!(IbcCore::channels(&port_id, &b).unwrap().state, pallet_ibc_core::ChannelState::Flushing);
+            assert_noop!(
+                IbcCore::send_packet(
+                    RuntimeOrigin::signed(1),
+                    port_id.clone(),
+                    b.clone(),
+                    port_id.clone(),
+                    a.clone(),
+                    b"paused".to_vec(),
+                    0,
+                    0,
+                ),
+                IbcError::<Test>::InvalidChannelState
+            );
+
+            // Proposer applies the upgrade once the counterparty's fields match, as attested by
+            // the upgrade authority
+            assert_noop!(
+                IbcCore::channel_upgrade_ack(RuntimeOrigin::signed(2), port_id.clone(), a.clone(), fields.clone(), 1),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            let mut other = fields.clone();
+            other.ordering = pallet_ibc_core::ChannelOrder::Ordered;
+            assert_noop!(
+                IbcCore::channel_upgrade_ack(RuntimeOrigin::root(), port_id.clone(), a.clone(), other, 1),
+                IbcError::<Test>::UpgradeMismatch
+            );
+            assert_ok!(IbcCore::channel_upgrade_ack(RuntimeOrigin::root(), port_id.clone(), a.clone(), fields.clone(), 1));
+
+            // Counterparty reopens with the upgrade
+            assert_noop!(
+                IbcCore::channel_upgrade_confirm(RuntimeOrigin::signed(2), port_id.clone(), b.clone(), 1),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(IbcCore::channel_upgrade_confirm(RuntimeOrigin::root(), port_id.clone(), b.clone(), 1));
+            System::assert_has_event(RuntimeEvent::IbcCore(IbcEvent::ChannelUpgraded {
+                port_id: port_id.clone(),
+                channel_id: b.clone(),
+                upgrade_sequence: 1,
+                fields: fields.clone(),
+            }));
+
+            for channel_id in [&a, &b] {
+                let channel = IbcCore::channels(&port_id, channel_id).unwrap();
+                assert_eq!(channel.state, pallet_ibc_core::ChannelState::Open);
+                assert_eq!(channel.ordering, pallet_ibc_core::ChannelOrder::Unordered);
+                assert_eq!(channel.version, fields.version);
+                assert_eq!((channel.next_sequence_send, channel.next_sequence_recv), (5, 3));
+                assert!(IbcCore::channel_upgrades(&port_id, channel_id).is_none());
+            }
+
+            // Unordered delivery: later sequences first, each at most once
+            let packet = |sequence: u64| pallet_ibc_core::Packet {
+                sequence,
+                source_port: port_id.clone(),
+                source_channel: b.clone(),
+                destination_port: port_id.clone(),
+                destination_channel: a.clone(),
+                data: bounded(b"hi"),
+                timeout_height: 0,
+                timeout_timestamp: 0,
+            };
+            let proof_height = prove_packet(b"client-0", &packet(4));
+            assert_ok!(IbcCore::recv_packet(RuntimeOrigin::signed(2), packet(4), vec![], proof_height));
+            assert_ok!(recv_proven(2, b"client-0", packet(3)));
+            assert_noop!(
+                IbcCore::recv_packet(RuntimeOrigin::signed(2), packet(4), vec![], proof_height),
+                IbcError::<Test>::PacketAlreadyExists
+            );
+        });
+    }
+
+    #[test]
+    fn genesis_loopback_channels_deliver_to_each_other() {
+        let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+        pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000_000)] }
+            .assimilate_storage(&mut t)
+            .unwrap();
+        pallet_ibc_core::GenesisConfig::<Test> { loopback: Some((b"transfer".to_vec(), 1)) }
+            .assimilate_storage(&mut t)
+            .unwrap();
+
+        sp_io::TestExternalities::from(t).execute_with(|| {
+            System::set_block_number(1);
+            let port_id = id(b"transfer");
+
+            // Both clients track this chain over connections pointing at each other
+            for (client, counterparty, connection) in
+                [("client-0", "client-1", "connection-0"), ("client-1", "client-0", "connection-1")]
+            {
+                assert_eq!(IbcCore::clients(id(client.as_bytes())).unwrap().chain_id, b"netchain-test".to_vec());
+                let connection = IbcCore::connections(id(connection.as_bytes())).unwrap();
+                assert_eq!(connection.state, pallet_ibc_core::ConnectionState::Open);
+                assert_eq!(connection.counterparty_client_id, id(counterparty.as_bytes()));
+            }
+            assert_eq!(pallet_ibc_core::PortOwners::<Test>::get(&port_id), Some(1));
+
+            // A packet sent on one channel is received on the other
+            assert_ok!(IbcCore::send_packet(
+                RuntimeOrigin::signed(1),
+                port_id.clone(),
+                id(b"channel-0"),
+                port_id.clone(),
+                id(b"channel-1"),
+                b"ping".to_vec(),
+                0,
+                0,
+            ));
+            assert_ok!(IbcCore::recv_packet(RuntimeOrigin::signed(1), pallet_ibc_core::Packet {
+                sequence: 1,
+                source_port: port_id.clone(),
+                source_channel: id(b"channel-0"),
+                destination_port: port_id.clone(),
+                destination_channel: id(b"channel-1"),
+                data: bounded(b"ping"),
+                timeout_height: 0,
+                timeout_timestamp: 0,
+            }, vec![], 0));
+            assert!(IbcCore::packet_acknowledgments(&port_id, 1).is_some());
+
+            // Loopback clients never expire
+            pallet_timestamp::Pallet::<Test>::set_timestamp(u64::MAX / 2);
+            assert_ok!(IbcCore::ensure_client_active(&id(b"connection-0")));
+        });
+    }
+
+    #[test]
+    fn channel_stats_track_packet_traffic() {
+        let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+        pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000_000)] }
+            .assimilate_storage(&mut t)
+            .unwrap();
+        pallet_ibc_core::GenesisConfig::<Test> { loopback: Some((b"transfer".to_vec(), 1)) }
+            .assimilate_storage(&mut t)
+            .unwrap();
+
+        sp_io::TestExternalities::from(t).execute_with(|| {
+            System::set_block_number(3);
+            let port_id = id(b"transfer");
+            assert!(IbcCore::channel_stats(&port_id, &id(b"channel-7")).is_none());
+
+            for data in [b"ping".to_vec(), b"pong!".to_vec()] {
+                assert_ok!(IbcCore::send_packet(
+                    RuntimeOrigin::signed(1),
+                    port_id.clone(),
+                    id(b"channel-0"),
+                    port_id.clone(),
+                    id(b"channel-1"),
+                    data,
+                    0,
+                    0,
+                ));
+            }
+            System::set_block_number(5);
+            assert_ok!(IbcCore::recv_packet(RuntimeOrigin::signed(1), pallet_ibc_core::Packet {
+                sequence: 1,
+                source_port: port_id.clone(),
+                source_channel: id(b"channel-0"),
+                destination_port: port_id.clone(),
+                destination_channel: id(b"channel-1"),
+                data: bounded(b"ping"),
+                timeout_height: 0,
+                timeout_timestamp: 0,
+            }, vec![], 0));
+            assert_ok!(IbcCore::timeout_packet(RuntimeOrigin::signed(1), port_id.clone(), id(b"channel-0"), 2));
+
+            let sent = IbcCore::channel_stats(&port_id, &id(b"channel-0")).unwrap();
+            assert_eq!(sent.packets_sent, 2);
+            assert_eq!(sent.bytes_sent, 9);
+            assert_eq!(sent.packets_timed_out, 1);
+            assert_eq!(sent.packets_acknowledged, 0);
+            assert_eq!(sent.last_activity, 5);
+
+            let received = IbcCore::channel_stats(&port_id, &id(b"channel-1")).unwrap();
+            assert_eq!(received.packets_received, 1);
+            assert_eq!(received.bytes_received, 4);
+            assert_eq!(received.packets_sent, 0);
+            assert_eq!(received.last_activity, 5);
+        });
+    }
+
+    #[test]
+    fn localhost_channels_carry_the_full_packet_lifecycle() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (ping, pong) = (id(b"ping"), id(b"pong"));
+            assert_ok!(IbcCore::bind_port(RuntimeOrigin::root(), ping.clone(), 1));
+            assert_ok!(IbcCore::bind_port(RuntimeOrigin::root(), pong.clone(), 2));
+
+            // Both ports must belong to the caller
+            assert_noop!(
+                IbcCore::channel_open_localhost(RuntimeOrigin::signed(1), ping.clone(), pong.clone(), b"v1".to_vec()),
+                IbcError::<Test>::NotPortOwner
+            );
+            assert_ok!(IbcCore::bind_port(RuntimeOrigin::root(), id(b"pang"), 1));
+            assert_ok!(IbcCore::channel_open_localhost(
+                RuntimeOrigin::signed(1),
+                ping.clone(),
+                id(b"pang"),
+                b"v1".to_vec()
+            ));
+
+            // Application pallets connect the ports they own without a signed origin
+            let (sent_on, received_on) =
+                IbcCore::open_localhost_channels(ping.clone(), pong.clone(), b"v1".to_vec()).unwrap();
+            assert_eq!((sent_on.clone(), received_on.clone()), (id(b"channel-2"), id(b"channel-3")));
+
+            // The localhost client and connection are shared and do not use up the limits
+            let connection = IbcCore::connections(id(b"connection-localhost")).unwrap();
+            assert_eq!(connection.client_id, id(b"09-localhost"));
+            assert_eq!(connection.state, pallet_ibc_core::ConnectionState::Open);
+            assert_eq!(IbcCore::next_client_id(), 0);
+            assert_eq!(IbcCore::next_connection_id(), 0);
+            assert_noop!(
+                IbcCore::remove_client(RuntimeOrigin::root(), id(b"09-localhost")),
+                IbcError::<Test>::InvalidIdentifier
+            );
+
+            assert_ok!(IbcCore::send_packet(
+                RuntimeOrigin::signed(1),
+                ping.clone(),
+                sent_on.clone(),
+                pong.clone(),
+                received_on.clone(),
+                b"hello".to_vec(),
+                0,
+                0,
+            ));
+            let packet = pallet_ibc_core::Packet {
+                sequence: 1,
+                source_port: ping.clone(),
+                source_channel: sent_on.clone(),
+                destination_port: pong.clone(),
+                destination_channel: received_on.clone(),
+                data: bounded(b"hello"),
+                timeout_height: 0,
+                timeout_timestamp: 0,
+            };
+
+            // Only the packet committed on the sending end is received
+            assert_noop!(
+                IbcCore::recv_packet(
+                    RuntimeOrigin::signed(3),
+                    pallet_ibc_core::Packet { data: bounded(b"forged"), ..packet.clone() },
+                    vec![],
+                    0
+                ),
+                IbcError::<Test>::PacketCommitmentMismatch
+            );
+            assert_ok!(IbcCore::recv_packet(RuntimeOrigin::signed(3), packet.clone(), vec![], 0));
+
+            // The acknowledgement is checked against the receiving end's storage, without a proof
+            let acknowledgement = pallet_ibc_core::encode_acknowledgement(&Ok(b"hello".to_vec()));
+            assert_noop!(
+                IbcCore::acknowledge_packet(
+                    RuntimeOrigin::signed(3),
+                    packet.clone(),
+                    pallet_ibc_core::encode_acknowledgement(&Ok(b"other".to_vec())),
+                    vec![],
+                    0
+                ),
+                IbcError::<Test>::InvalidProof
+            );
+            assert_ok!(IbcCore::acknowledge_packet(RuntimeOrigin::signed(3), packet, acknowledgement, vec![], 0));
+            assert_eq!(IbcCore::packet_status(&ping, 1), pallet_ibc_core::PacketStatus::Acknowledged);
+
+            // The localhost client never expires
+            pallet_timestamp::Pallet::<Test>::set_timestamp(u64::MAX / 2);
+            assert_ok!(IbcCore::ensure_client_active(&id(b"connection-localhost")));
+        });
+    }
+
+    #[test]
+    fn ibc_client_limits_enforced() {
+        new_test_ext().execute_with(|| {
+            // This test would require modifying MaxIbcClients to a small number for testing
+            // For now, just test that the limit exists
+            assert_eq!(MaxIbcClients::get(), 100);
+        });
+    }
+
+    #[test]
+    fn bounded_packets_encode_as_committed_before_their_bounds() {
+        use pallet_ibc_core::{MaxPacketDataLen, Packet};
+        use sp_core::{Decode, Encode};
+
+        let packet = Packet {
+            sequence: 1,
+            source_port: id(b"transfer"),
+            source_channel: id(b"channel-0"),
+            destination_port: id(b"transfer"),
+            destination_channel: id(b"channel-1"),
+            data: bounded(b"hi"),
+            timeout_height: 200,
+            timeout_timestamp: 0,
+        };
+
+        // The packet with unbounded fields, as committed before the bounds
+        let unbounded = |data: Vec<u8>| {
+            let (port, source, destination) = (id(b"transfer").to_vec(), id(b"channel-0"), id(b"channel-1"));
+            (1u64, port.clone(), source.to_vec(), port, destination.to_vec(), data, 200u64, 0u64).encode()
+        };
+
+        // Commitments stored before the bounds, on both ends, still match
+        assert_eq!(packet.encode(), unbounded(b"hi".to_vec()));
+        assert_eq!(Packet::decode(&mut &unbounded(b"hi".to_vec())[..]).unwrap(), packet);
+
+        // Data beyond the bound is rejected rather than cut
+        let oversized = unbounded(vec![0; MaxPacketDataLen::get() as usize + 1]);
+        assert!(Packet::decode(&mut &oversized[..]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod oracle_tests {
+    use super::*;
+
+    #[test]
+    fn register_oracle_source_works() {
+        new_test_ext().execute_with(|| {
+            // Register data source
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95, // high reliability
+            ));
+
+            // Check source was registered
+            let source = Oracle::data_sources(b"coinbase_btc".to_vec()).unwrap();
+            assert_eq!(source.name, b"Coinbase BTC Price".to_vec());
+            assert_eq!(source.reliability, 95);
+            assert!(source.active);
+
+            // Check event was emitted
+            System::assert_last_event(RuntimeEvent::Oracle(
+                OracleEvent::SourceRegistered {
+                    source_id: b"coinbase_btc".to_vec(),
+                    name: b"Coinbase BTC Price".to_vec(),
+                }
+            ));
+        });
+    }
+
+    #[test]
+    fn oracle_pot_is_endowed_at_genesis_and_kept_alive() {
+        let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+        pallet_balances::GenesisConfig::<Test> { balances: vec![(2, 1_000_000)] }
+            .assimilate_storage(&mut t)
+            .unwrap();
+        pallet_oracle::GenesisConfig::<Test>::default().assimilate_storage(&mut t).unwrap();
+
+        sp_io::TestExternalities::from(t).execute_with(|| {
+            System::set_block_number(1);
+            assert_eq!(Oracle::pallet_pot_balance(), ExistentialDeposit::get());
+
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95,
+            ));
+
+            // The reward would take the pot below the existential deposit, so it is not paid
+            assert_ok!(Oracle::provide_data(
+                RuntimeOrigin::signed(2),
+                b"BTC/USD".to_vec(),
+                b"coinbase_btc".to_vec(),
+                b"50000.00".to_vec(),
+                90,
+                None,
+            ));
+            assert_eq!(Oracle::pallet_pot_balance(), ExistentialDeposit::get());
+            assert!(System::account_exists(&Oracle::account_id()));
+        });
+    }
+
+    #[test]
+    fn source_owner_and_governance_manage_source_lifecycle() {
+        new_test_ext().execute_with(|| {
+            let source_id = b"coinbase_btc".to_vec();
+            let submit = || Oracle::provide_data(
+                RuntimeOrigin::signed(2),
+                b"BTC/USD".to_vec(),
+                b"coinbase_btc".to_vec(),
+                b"50000.00".to_vec(),
+                90,
+                None,
+            );
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                source_id.clone(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95,
+            ));
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 2, 90));
+
+            // Only governance assigns owners, and only owners act besides it
+            assert_noop!(
+                Oracle::set_source_owner(RuntimeOrigin::signed(3), source_id.clone(), Some(3)),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_noop!(
+                Oracle::suspend_source(RuntimeOrigin::signed(3), source_id.clone()),
+                OracleError::<Test>::NotSourceOwner
+            );
+            assert_ok!(Oracle::set_source_owner(RuntimeOrigin::root(), source_id.clone(), Some(3)));
+
+            // The owner rotates the endpoint but cannot rescore reliability
+            assert_noop!(
+                Oracle::update_source(RuntimeOrigin::signed(3), source_id.clone(), None, None, Some(100)),
+                OracleError::<Test>::GovernanceOnly
+            );
+            assert_ok!(Oracle::update_source(
+                RuntimeOrigin::signed(3),
+                source_id.clone(),
+                None,
+                Some(b"https://api.exchange.coinbase.com/products/BTC-USD/ticker".to_vec()),
+                None,
+            ));
+            System::assert_last_event(RuntimeEvent::Oracle(OracleEvent::SourceUpdated {
+                source_id: source_id.clone(),
+                name: b"Coinbase BTC Price".to_vec(),
+                endpoint: b"https://api.exchange.coinbase.com/products/BTC-USD/ticker".to_vec(),
+                reliability: 95,
+            }));
+
+            // A governance suspension refuses submissions and outranks the owner
+            assert_ok!(Oracle::suspend_source(RuntimeOrigin::root(), source_id.clone()));
+            System::assert_last_event(RuntimeEvent::Oracle(OracleEvent::SourceSuspended {
+                source_id: source_id.clone(),
+                by: pallet_oracle::SourceAuthority::Governance,
+            }));
+            assert_noop!(submit(), OracleError::<Test>::InvalidSource);
+            assert_noop!(
+                Oracle::suspend_source(RuntimeOrigin::signed(3), source_id.clone()),
+                OracleError::<Test>::SourceAlreadySuspended
+            );
+            assert_noop!(
+                Oracle::resume_source(RuntimeOrigin::signed(3), source_id.clone()),
+                OracleError::<Test>::GovernanceOnly
+            );
+            assert_ok!(Oracle::resume_source(RuntimeOrigin::root(), source_id.clone()));
+            assert_ok!(submit());
+
+            // The owner suspends and resumes on its own
+            assert_ok!(Oracle::suspend_source(RuntimeOrigin::signed(3), source_id.clone()));
+            assert_ok!(Oracle::resume_source(RuntimeOrigin::signed(3), source_id.clone()));
+            assert_noop!(
+                Oracle::resume_source(RuntimeOrigin::signed(3), source_id.clone()),
+                OracleError::<Test>::SourceNotSuspended
+            );
+
+            // Retiring removes the source and its owner
+            assert_ok!(Oracle::retire_source(RuntimeOrigin::signed(3), source_id.clone()));
+            System::assert_last_event(RuntimeEvent::Oracle(OracleEvent::SourceRetired {
+                source_id: source_id.clone(),
+            }));
+            assert!(Oracle::data_sources(&source_id).is_none());
+            assert!(Oracle::source_owners(&source_id).is_none());
+            assert_noop!(submit(), OracleError::<Test>::SourceNotFound);
+        });
+    }
+
+    #[test]
+    fn bonded_trusted_providers_are_refunded_for_successful_submissions_up_to_block_quota() {
+        use frame_support::{dispatch::Pays, traits::Hooks};
+        use sp_runtime::traits::Dispatchable;
+
+        new_test_ext().execute_with(|| {
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95,
+            ));
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 2, 90));
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 3, 90));
+            let submit = |source: &[u8]| RuntimeCall::Oracle(pallet_oracle::Call::provide_data {
+                data_key: b"BTC/USD".to_vec(),
+                source: source.to_vec(),
+                value: b"50000.00".to_vec(),
+                confidence: 90,
+                signature: None,
+            });
+
+            let pays = |source: &[u8], who: u64| submit(source).dispatch(RuntimeOrigin::signed(who)).unwrap().pays_fee;
+
+            // Trusted providers pay fees until they bond
+            assert_eq!(pays(b"coinbase_btc", 2), Pays::Yes);
+            assert_ok!(Oracle::bond_provider(RuntimeOrigin::signed(2), 100));
+
+            // Failed submissions, e.g. for unknown sources, pay and leave the quota alone
+            let failed = submit(b"unknown").dispatch(RuntimeOrigin::signed(2)).unwrap_err();
+            assert_eq!(failed.post_info.pays_fee, Pays::Yes);
+            assert_eq!(Oracle::feeless_submissions(), 0);
+
+            // The block quota is shared by all providers
+            assert_eq!(pays(b"coinbase_btc", 2), Pays::No);
+            assert_eq!(pays(b"coinbase_btc", 3), Pays::Yes);
+            assert_eq!(pays(b"coinbase_btc", 2), Pays::No);
+            assert_eq!(Oracle::feeless_submissions(), 2);
+            assert_eq!(pays(b"coinbase_btc", 2), Pays::Yes);
+
+            Oracle::on_initialize(2);
+            assert_eq!(pays(b"coinbase_btc", 2), Pays::No);
+        });
+    }
+
+    #[test]
+    fn genesis_registers_sources_and_trusted_providers() {
+        let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+        pallet_oracle::GenesisConfig::<Test> {
+            sources: vec![(b"coinbase".to_vec(), b"Coinbase".to_vec(), b"https://api.coinbase.com".to_vec(), 95)],
+            trusted_providers: vec![(2, 80)],
+            critical_feeds: vec![b"NET/USD".to_vec()],
+        }
+        .assimilate_storage(&mut t)
+        .unwrap();
+
+        sp_io::TestExternalities::from(t).execute_with(|| {
+            let source = Oracle::data_sources(b"coinbase".to_vec()).unwrap();
+            assert_eq!(source.name, b"Coinbase".to_vec());
+            assert_eq!(source.reliability, 95);
+            assert!(source.active);
+            assert_eq!(Oracle::trusted_providers(2), Some(80));
+            assert_eq!(Oracle::critical_feed(b"NET/USD".to_vec()), Some(()));
+        });
+    }
+
+    #[test]
+    fn oracle_data_request_works() {
+        new_test_ext().execute_with(|| {
+            // First register a data source
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95,
+            ));
+
+            // Request oracle data
+            assert_ok!(Oracle::request_data(
+                RuntimeOrigin::signed(1),
+                b"BTC/USD".to_vec(),
+                vec![b"coinbase_btc".to_vec()],
+                false, // not premium
+                None, // no callback
+            ));
+
+            // Check fee was charged
+            assert_eq!(Balances::free_balance(&1), 1_000_000 - 2);
+
+            // Check request was stored
+            let request = Oracle::oracle_requests(0).unwrap();
+            assert_eq!(request.requester, 1);
+            assert_eq!(request.data_key, b"BTC/USD".to_vec());
+            assert!(!request.premium);
+
+            // Check event was emitted
+            System::assert_last_event(RuntimeEvent::Oracle(
+                OracleEvent::DataRequested {
+                    request_id: 0,
+                    requester: 1,
+                    data_key: b"BTC/USD".to_vec(),
+                    sources: vec![b"coinbase_btc".to_vec()],
+                    premium: false,
+                }
+            ));
+        });
+    }
+
+    #[test]
+    fn oracle_data_provision_works() {
+        new_test_ext().execute_with(|| {
+            // Setup: Register source and trusted provider
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95,
+            ));
+
+            assert_ok!(Oracle::add_trusted_provider(
+                RuntimeOrigin::root(),
+                2, // provider account
+                90, // reputation
+            ));
+
+            // Provide oracle data
+            assert_ok!(Oracle::provide_data(
+                RuntimeOrigin::signed(2),
+                b"BTC/USD".to_vec(),
+                b"coinbase_btc".to_vec(),
+                b"50000.00".to_vec(), // $50,000 BTC price
+                90, // high confidence
+                None, // no signature
+            ));
+
+            // Check data was stored
+            let data = Oracle::oracle_data(b"BTC/USD".to_vec(), b"coinbase_btc".to_vec()).unwrap();
+            assert_eq!(data.value, b"50000.00".to_vec());
+            assert_eq!(data.provider, 2);
+            assert_eq!(data.confidence, 90);
+
+            // Check provider was rewarded
+            assert_eq!(Balances::free_balance(&2), 1_000_000 + 1);
+
+            // Check event was emitted
+            System::assert_last_event(RuntimeEvent::Oracle(
+                OracleEvent::DataProvided {
+                    data_key: b"BTC/USD".to_vec(),
+                    source: b"coinbase_btc".to_vec(),
+                    provider: 2,
+                    value: b"50000.00".to_vec(),
+                    confidence: 90,
+                }
+            ));
+        });
+    }
+
+    #[test]
+    fn oracle_data_batch_is_stored_atomically() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase".to_vec(),
+                b"Coinbase".to_vec(),
+                b"coinbase-api".to_vec(),
+                95,
+            ));
+            let _ = Balances::make_free_balance_be(&Oracle::account_id(), 10_000);
+
+            // One invalid entry rejects the whole basket
+            assert_noop!(
+                Oracle::provide_data_batch(RuntimeOrigin::signed(2), b"coinbase".to_vec(), vec![
+                    (b"BTC/USD".to_vec(), b"50000.00".to_vec(), 50),
+                    (b"ETH/USD".to_vec(), b"3000.00".to_vec(), 101),
+                ]),
+                OracleError::<Test>::InvalidConfidence
+            );
+
+            // Baskets meet the tier of their source
+            assert_ok!(Oracle::set_source_tier(RuntimeOrigin::root(), b"coinbase".to_vec(), SourceTier::Verified));
+            assert_noop!(
+                Oracle::provide_data_batch(RuntimeOrigin::signed(2), b"coinbase".to_vec(), vec![
+                    (b"BTC/USD".to_vec(), b"50000.00".to_vec(), 50),
+                ]),
+                OracleError::<Test>::ProviderNotTrusted
+            );
+            assert_ok!(Oracle::set_source_tier(RuntimeOrigin::root(), b"coinbase".to_vec(), SourceTier::Open));
+
+            // Baskets are bounded by MaxBatchSubmissions
+            let oversized = (0..5u8).map(|i| (vec![i], b"1".to_vec(), 50)).collect();
+            assert_noop!(
+                Oracle::provide_data_batch(RuntimeOrigin::signed(2), b"coinbase".to_vec(), oversized),
+                OracleError::<Test>::TooManySubmissions
+            );
 
-            // Check source was registered
-            let source = Oracle::data_sources(b"coinbase_btc".to_vec()).unwrap();
-            assert_eq!(source.name, b"Coinbase BTC Price".to_vec());
-            assert_eq!(source.reliability, 95);
-            assert!(source.active);
+            assert_ok!(Oracle::provide_data_batch(RuntimeOrigin::signed(2), b"coinbase".to_vec(), vec![
+                (b"BTC/USD".to_vec(), b"50000.00".to_vec(), 50),
+                (b"ETH/USD".to_vec(), b"3000.00".to_vec(), 60),
+                (b"DOT/USD".to_vec(), b"7.00".to_vec(), 70),
+            ]));
+
+            // Every key is stored and the provider rewarded once per entry
+            for (key, value, confidence) in [
+                (b"BTC/USD".to_vec(), b"50000.00".to_vec(), 50),
+                (b"ETH/USD".to_vec(), b"3000.00".to_vec(), 60),
+                (b"DOT/USD".to_vec(), b"7.00".to_vec(), 70),
+            ] {
+                let data = Oracle::oracle_data(key, b"coinbase".to_vec()).unwrap();
+                assert_eq!(data.value, value);
+                assert_eq!(data.provider, 2);
+                assert_eq!(data.confidence, confidence);
+            }
+            assert_eq!(Balances::free_balance(&2), 1_000_000 + 3);
+        });
+    }
+
+    #[test]
+    fn oracle_batch_requests_work() {
+        new_test_ext().execute_with(|| {
+            // Register multiple sources
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC".to_vec(),
+                b"coinbase-api".to_vec(),
+                95,
+            ));
+
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"binance_btc".to_vec(),
+                b"Binance BTC".to_vec(),
+                b"binance-api".to_vec(),
+                90,
+            ));
+
+            // Batch request multiple data points
+            let requests = vec![
+                (b"BTC/USD".to_vec(), vec![b"coinbase_btc".to_vec()], false),
+                (b"ETH/USD".to_vec(), vec![b"binance_btc".to_vec()], true), // premium
+            ];
+
+            assert_ok!(Oracle::batch_requests(
+                RuntimeOrigin::signed(1),
+                requests,
+            ));
+
+            // Check total fee charged (2 + 5 = 7)
+            assert_eq!(Balances::free_balance(&1), 1_000_000 - 7);
 
             // Check event was emitted
             System::assert_last_event(RuntimeEvent::Oracle(
-                OracleEvent::SourceRegistered {
-                    source_id: b"coinbase_btc".to_vec(),
-                    name: b"Coinbase BTC Price".to_vec(),
+                OracleEvent::BatchProcessed {
+                    request_count: 2,
+                    total_fee: 7,
                 }
             ));
         });
     }
 
     #[test]
-    fn oracle_data_request_works() {
+    fn oracle_batch_requests_refund_unused_source_weight() {
+        new_test_ext().execute_with(|| {
+            use pallet_oracle::WeightInfo;
+
+            let sources = |n: u8| (0..n).map(|i| vec![b's', i]).collect::<Vec<_>>();
+            let requests = vec![
+                (b"BTC/USD".to_vec(), sources(4), false),
+                (b"ETH/USD".to_vec(), sources(1), false),
+            ];
+
+            let post_info = Oracle::batch_requests(RuntimeOrigin::signed(1), requests).unwrap();
+
+            // Declared for two requests of four sources, charged for two of three
+            assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::batch_requests(2, 3)));
+        });
+    }
+
+    #[test]
+    fn oracle_administration_fits_in_blocks_full_of_normal_transactions() {
+        new_test_ext().execute_with(|| {
+            use frame_support::dispatch::{DispatchClass, GetDispatchInfo, Pays};
+            use sp_runtime::traits::Dispatchable;
+
+            let limits = <Test as frame_system::Config>::BlockWeights::get();
+            let mut full = frame_system::ConsumedWeight::default();
+            full.set(limits.get(DispatchClass::Normal).max_total.unwrap(), DispatchClass::Normal);
+            frame_system::BlockWeight::<Test>::put(full);
+            let fits = |call: &RuntimeCall| {
+                frame_system::extensions::check_weight::calculate_consumed_weight::<RuntimeCall>(
+                    &limits,
+                    System::block_weight(),
+                    &call.get_dispatch_info(),
+                    0,
+                )
+                .is_ok()
+            };
+
+            let submission = RuntimeCall::Oracle(pallet_oracle::Call::provide_data {
+                data_key: b"BTC/USD".to_vec(),
+                source: b"src_a".to_vec(),
+                value: b"100".to_vec(),
+                confidence: 50,
+                signature: None,
+            });
+            assert!(!fits(&submission));
+
+            let registration = RuntimeCall::Oracle(pallet_oracle::Call::register_source {
+                source_id: b"src_a".to_vec(),
+                name: b"src_a".to_vec(),
+                endpoint: b"https://example.com".to_vec(),
+                reliability: 90,
+            });
+            let info = registration.get_dispatch_info();
+            assert_eq!((info.class, info.pays_fee), (DispatchClass::Operational, Pays::No));
+            assert!(fits(&registration));
+            assert_ok!(registration.dispatch(RuntimeOrigin::root()));
+            assert!(pallet_oracle::DataSources::<Test>::contains_key(b"src_a".to_vec()));
+        });
+    }
+
+    #[test]
+    fn critical_feeds_land_in_blocks_full_of_transfers() {
+        new_test_ext().execute_with(|| {
+            use frame_support::dispatch::{DispatchClass, GetDispatchInfo};
+            use frame_support::traits::Hooks;
+            use sp_runtime::traits::Dispatchable;
+
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"src_a".to_vec(),
+                b"src_a".to_vec(),
+                b"https://example.com".to_vec(),
+                90,
+            ));
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 2, 90));
+            assert_ok!(Oracle::set_feed_critical(RuntimeOrigin::root(), b"NET/USD".to_vec(), true));
+
+            // Account the call's weight as `CheckWeight` does and dispatch it, if it fits
+            let include = |call: RuntimeCall, who: u64| {
+                let limits = <Test as frame_system::Config>::BlockWeights::get();
+                let consumed = frame_system::extensions::check_weight::calculate_consumed_weight::<RuntimeCall>(
+                    &limits,
+                    System::block_weight(),
+                    &call.get_dispatch_info(),
+                    0,
+                );
+                let Ok(consumed) = consumed else { return false };
+                frame_system::BlockWeight::<Test>::put(consumed);
+                assert_ok!(call.dispatch(RuntimeOrigin::signed(who)));
+                true
+            };
+            let transfer = || RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive { dest: 3, value: 1 });
+            let submission = |data_key: &[u8]| RuntimeCall::Oracle(pallet_oracle::Call::provide_data {
+                data_key: data_key.to_vec(),
+                source: b"src_a".to_vec(),
+                value: b"100".to_vec(),
+                confidence: 50,
+                signature: None,
+            });
+            let critical = |data_key: &[u8]| RuntimeCall::Oracle(pallet_oracle::Call::provide_critical_data {
+                data_key: data_key.to_vec(),
+                source: b"src_a".to_vec(),
+                value: b"100".to_vec(),
+                confidence: 50,
+                signature: None,
+            });
+
+            let mut transfers = 0;
+            while include(transfer(), 1) {
+                transfers += 1;
+            }
+            assert!(transfers > 0);
+
+            // The block is full for plain submissions, but the critical one lands
+            assert_eq!(submission(b"NET/USD").get_dispatch_info().class, DispatchClass::Normal);
+            assert!(!include(submission(b"BTC/USD"), 2));
+            assert!(!include(submission(b"NET/USD"), 2));
+            assert_eq!(critical(b"NET/USD").get_dispatch_info().class, DispatchClass::Operational);
+            assert!(include(critical(b"NET/USD"), 2));
+            assert_eq!(Oracle::oracle_data(b"NET/USD".to_vec(), b"src_a".to_vec()).unwrap().value, b"100".to_vec());
+            // The slice is charged what the submission used, without the committee draw it did not make
+            assert_eq!(
+                Oracle::critical_weight_used(),
+                <() as pallet_oracle::WeightInfo>::provide_data(3, MaxOracleDataSources::get())
+            );
+
+            // Only keys flagged critical, within the reserved slice, which is renewed every block
+            assert_noop!(
+                critical(b"BTC/USD").dispatch(RuntimeOrigin::signed(2)),
+                OracleError::<Test>::NotCriticalFeed
+            );
+            assert_noop!(
+                critical(b"NET/USD").dispatch(RuntimeOrigin::signed(2)),
+                OracleError::<Test>::CriticalFeedWeightExhausted
+            );
+            Oracle::on_initialize(2);
+            assert!(include(critical(b"NET/USD"), 2));
+
+            assert_ok!(Oracle::set_feed_critical(RuntimeOrigin::root(), b"NET/USD".to_vec(), false));
+            Oracle::on_initialize(3);
+            assert_noop!(
+                critical(b"NET/USD").dispatch(RuntimeOrigin::signed(2)),
+                OracleError::<Test>::NotCriticalFeed
+            );
+            System::assert_last_event(RuntimeEvent::Oracle(OracleEvent::FeedCriticalitySet {
+                data_key: b"NET/USD".to_vec(),
+                critical: false,
+            }));
+        });
+    }
+
+    #[test]
+    fn oracle_history_keeps_recent_aggregates() {
+        new_test_ext().execute_with(|| {
+            for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                assert_ok!(Oracle::register_source(
+                    RuntimeOrigin::root(),
+                    source.to_vec(),
+                    source.to_vec(),
+                    b"https://example.com".to_vec(),
+                    90,
+                ));
+            }
+
+            // Every update from the third source on produces an aggregate
+            let updates = [(b"src_a", b"100"), (b"src_b", b"101"), (b"src_c", b"102"),
+                (b"src_a", b"103"), (b"src_b", b"104"), (b"src_c", b"105"), (b"src_a", b"106")];
+            for (block, (source, price)) in updates.iter().enumerate() {
+                System::set_block_number(block as u64 + 1);
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(2),
+                    b"BTC/USD".to_vec(),
+                    source.to_vec(),
+                    price.to_vec(),
+                    50,
+                    None,
+                ));
+            }
+            assert_eq!(Oracle::observation_count(b"BTC/USD".to_vec()), 5);
+
+            // Three-slot buffer: only the newest three aggregates remain, newest first
+            let history = Oracle::get_history(&b"BTC/USD".to_vec(), 10);
+            assert_eq!(history.iter().map(|o| o.aggregated_at).collect::<Vec<_>>(), vec![7, 6, 5]);
+            assert_eq!(Some(history[0].value.clone()), Oracle::get_latest_data(&b"BTC/USD".to_vec()));
+
+            assert_eq!(Oracle::get_history(&b"BTC/USD".to_vec(), 2).len(), 2);
+            assert!(Oracle::get_history(&b"ETH/USD".to_vec(), 10).is_empty());
+        });
+    }
+
+    #[test]
+    fn oracle_aggregates_become_readable_after_the_finality_delay() {
+        new_test_ext().execute_with(|| {
+            OracleMinAggregationDelay::set(3);
+            for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                assert_ok!(Oracle::register_source(
+                    RuntimeOrigin::root(),
+                    source.to_vec(),
+                    source.to_vec(),
+                    b"https://example.com".to_vec(),
+                    90,
+                ));
+            }
+            let key = b"BTC/USD".to_vec();
+            let provide = |source: &[u8], price: &[u8]| {
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(2),
+                    key.clone(),
+                    source.to_vec(),
+                    price.to_vec(),
+                    50,
+                    None,
+                ));
+            };
+
+            System::set_block_number(5);
+            provide(b"src_a", b"100");
+            provide(b"src_b", b"100");
+            provide(b"src_c", b"100");
+
+            // Aggregated in block 5, readable from block 8
+            System::assert_has_event(RuntimeEvent::Oracle(pallet_oracle::Event::DataAggregated {
+                data_key: key.clone(),
+                value: b"100".to_vec(),
+                source_count: 3,
+                confidence: 50,
+            }));
+            assert_eq!(Oracle::get_aggregate(&key), None);
+            let pending = Oracle::pending_aggregate(8, &key).unwrap();
+            assert_eq!((pending.aggregated_at, pending.valid_from, pending.valid_until), (5, 8, 1_205));
+
+            // The next aggregate waits out its own delay
+            System::set_block_number(6);
+            provide(b"src_a", b"101");
+            Oracle::on_initialize(7);
+            assert_eq!(Oracle::get_latest_data(&key), None);
+            assert_eq!(Oracle::observation_count(&key), 0);
+
+            System::set_block_number(8);
+            Oracle::on_initialize(8);
+            let readable = Oracle::get_aggregate(&key).unwrap();
+            assert_eq!((readable.value, readable.aggregated_at, readable.valid_from), (b"100".to_vec(), 5, 8));
+            assert_eq!(Oracle::observation_count(&key), 1);
+            System::assert_last_event(RuntimeEvent::Oracle(pallet_oracle::Event::AggregateReadable {
+                data_key: key.clone(),
+                aggregated_at: 5,
+            }));
+
+            Oracle::on_initialize(9);
+            let readable = Oracle::get_aggregate(&key).unwrap();
+            assert_eq!((readable.aggregated_at, readable.valid_from, readable.valid_until), (6, 9, 1_206));
+            assert_eq!(Oracle::pending_aggregate(9, &key), None);
+            assert_eq!(Oracle::observation_count(&key), 2);
+        });
+    }
+
+    #[test]
+    fn disputed_aggregates_never_become_readable() {
+        new_test_ext().execute_with(|| {
+            OracleMinAggregationDelay::set(3);
+            for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                assert_ok!(Oracle::register_source(
+                    RuntimeOrigin::root(),
+                    source.to_vec(),
+                    source.to_vec(),
+                    b"https://example.com".to_vec(),
+                    90,
+                ));
+            }
+            let key = b"BTC/USD".to_vec();
+
+            System::set_block_number(5);
+            for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(2),
+                    key.clone(),
+                    source.to_vec(),
+                    b"100".to_vec(),
+                    50,
+                    None,
+                ));
+            }
+            assert!(Oracle::pending_aggregate(8, &key).is_some());
+
+            assert_noop!(
+                Oracle::dispute_aggregate(RuntimeOrigin::signed(1), key.clone(), 8),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_noop!(
+                Oracle::dispute_aggregate(RuntimeOrigin::root(), key.clone(), 9),
+                OracleError::<Test>::AggregateNotPending
+            );
+            assert_ok!(Oracle::dispute_aggregate(RuntimeOrigin::root(), key.clone(), 8));
+            System::assert_last_event(RuntimeEvent::Oracle(OracleEvent::AggregateDisputed {
+                data_key: key.clone(),
+                aggregated_at: 5,
+            }));
+
+            System::set_block_number(8);
+            Oracle::on_initialize(8);
+            assert_eq!(Oracle::get_aggregate(&key), None);
+            assert_eq!(Oracle::observation_count(&key), 0);
+        });
+    }
+
+    #[test]
+    fn migrating_oracle_to_v1_dates_existing_aggregates() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_oracle::migrations::v1;
+
+            let key = b"BTC/USD".to_vec();
+            let old = v1::OldAggregatedData {
+                value: b"100".to_vec(),
+                source_count: 3,
+                confidence: 90,
+                aggregated_at: 40u64,
+                data_points: vec![b"100".to_vec(); 3],
+            };
+            frame_support::storage::unhashed::put(&pallet_oracle::AggregatedDataStorage::<Test>::hashed_key_for(&key), &old);
+            StorageVersion::new(0).put::<Oracle>();
+
+            v1::AddAggregateValidity::<Test>::on_runtime_upgrade();
+
+            assert_eq!(Oracle::on_chain_storage_version(), 1);
+            let aggregate = Oracle::get_aggregate(&key).unwrap();
+            assert_eq!((aggregate.value, aggregate.aggregated_at), (old.value, 40));
+            assert_eq!((aggregate.valid_from, aggregate.valid_until), (40, 1_240));
+        });
+    }
+
+    #[test]
+    fn oracle_history_is_mirrored_off_chain() {
+        use netchain_primitives::offchain_history::{self, Indexed};
+        use pallet_oracle::Observation;
+        use sp_core::{Decode, Encode};
+
+        let mut ext = new_test_ext();
+        ext.execute_with(|| {
+            for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                assert_ok!(Oracle::register_source(
+                    RuntimeOrigin::root(),
+                    source.to_vec(),
+                    source.to_vec(),
+                    b"https://example.com".to_vec(),
+                    90,
+                ));
+            }
+            let updates = [(b"src_a", b"100"), (b"src_b", b"101"), (b"src_c", b"102"),
+                (b"src_a", b"103"), (b"src_b", b"104"), (b"src_c", b"105"), (b"src_a", b"106")];
+            for (block, (source, price)) in updates.iter().enumerate() {
+                System::set_block_number(block as u64 + 1);
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(2),
+                    b"BTC/USD".to_vec(),
+                    source.to_vec(),
+                    price.to_vec(),
+                    50,
+                    None,
+                ));
+            }
+        });
+        ext.persist_offchain_overlay();
+
+        // The first aggregate left the three-slot on-chain buffer but is kept off-chain
+        let db = ext.offchain_db();
+        let first = db.get(&offchain_history::oracle_round_key(b"BTC/USD", 0)).expect("first aggregate is mirrored");
+        let first = Indexed::<Observation<u64>>::decode(&mut &first[..]).unwrap();
+        assert_eq!((first.block, first.item.aggregated_at), (3, 3));
+        assert!(db.get(&offchain_history::oracle_round_key(b"BTC/USD", 4)).is_some());
+        assert_eq!(db.get(&offchain_history::oracle_latest_key(b"BTC/USD")), Some(4u64.encode()));
+    }
+
+    #[test]
+    fn typed_feeds_validate_submissions() {
+        use pallet_oracle::{FeedCategory, FeedMetadata, TypedValue, ValueKind};
+        use sp_core::Encode;
+        use sp_runtime::FixedU128;
+
         new_test_ext().execute_with(|| {
-            // First register a data source
             assert_ok!(Oracle::register_source(
                 RuntimeOrigin::root(),
                 b"coinbase_btc".to_vec(),
@@ -370,41 +3007,267 @@ mod oracle_tests {
                 95,
             ));
 
-            // Request oracle data
-            assert_ok!(Oracle::request_data(
-                RuntimeOrigin::signed(1),
+            let metadata = FeedMetadata {
+                kind: ValueKind::Numeric,
+                decimals: 2,
+                unit: b"USD".to_vec(),
+                category: FeedCategory::Price,
+            };
+            assert_noop!(
+                Oracle::register_feed(
+                    RuntimeOrigin::root(),
+                    b"BTC/USD".to_vec(),
+                    FeedMetadata { decimals: 19, ..metadata.clone() },
+                ),
+                OracleError::<Test>::InvalidFeedMetadata
+            );
+            assert_ok!(Oracle::register_feed(RuntimeOrigin::root(), b"BTC/USD".to_vec(), metadata.clone()));
+            assert_noop!(
+                Oracle::register_feed(RuntimeOrigin::root(), b"BTC/USD".to_vec(), metadata.clone()),
+                OracleError::<Test>::FeedAlreadyRegistered
+            );
+
+            let provide = |value: Vec<u8>| Oracle::provide_data(
+                RuntimeOrigin::signed(2),
                 b"BTC/USD".to_vec(),
-                vec![b"coinbase_btc".to_vec()],
-                false, // not premium
-                None, // no callback
+                b"coinbase_btc".to_vec(),
+                value,
+                50,
+                None,
+            );
+
+            assert_noop!(provide(b"50000.00".to_vec()), OracleError::<Test>::MalformedValue);
+            assert_noop!(provide(TypedValue::Bool(true).encode()), OracleError::<Test>::ValueTypeMismatch);
+            assert_noop!(
+                provide(TypedValue::Numeric(FixedU128::from_rational(5_000_000_001, 1_000)).encode()),
+                OracleError::<Test>::ValueExceedsPrecision
+            );
+
+            let price = TypedValue::Numeric(FixedU128::from_rational(5_000_001, 100));
+            assert_ok!(provide(price.encode()));
+            assert_eq!(Oracle::validate_value(&metadata, &price.encode()), Ok(price));
+
+            // Unregistered keys still take opaque bytes
+            assert_ok!(Oracle::provide_data(
+                RuntimeOrigin::signed(2),
+                b"weather/london".to_vec(),
+                b"coinbase_btc".to_vec(),
+                b"{\"temp\":12}".to_vec(),
+                50,
+                None,
             ));
+        });
+    }
 
-            // Check fee was charged
-            assert_eq!(Balances::free_balance(&1), 1_000_000 - 2);
+    #[test]
+    fn signed_rounds_publish_one_value_for_all_signers() {
+        new_test_ext().execute_with(|| {
+            use sp_core::{sr25519, Pair};
 
-            // Check request was stored
-            let request = Oracle::oracle_requests(0).unwrap();
-            assert_eq!(request.requester, 1);
-            assert_eq!(request.data_key, b"BTC/USD".to_vec());
-            assert!(!request.premium);
+            System::set_block_number(1);
+            let key = b"BTC/USD".to_vec();
+            let value = b"50000.00".to_vec();
+            let pairs: Vec<sr25519::Pair> = (1..=3u8).map(|seed| sr25519::Pair::from_seed(&[seed; 32])).collect();
 
-            // Check event was emitted
-            System::assert_last_event(RuntimeEvent::Oracle(
-                OracleEvent::DataRequested {
-                    request_id: 0,
-                    requester: 1,
-                    data_key: b"BTC/USD".to_vec(),
-                    sources: vec![b"coinbase_btc".to_vec()],
-                    premium: false,
-                }
+            assert_noop!(
+                Oracle::set_signing_key(RuntimeOrigin::signed(1), pairs[0].public()),
+                OracleError::<Test>::ProviderNotTrusted
+            );
+            for (provider, pair) in (1..=3u64).zip(&pairs) {
+                assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), provider, 90));
+                assert_ok!(Oracle::set_signing_key(RuntimeOrigin::signed(provider), pair.public()));
+            }
+
+            let payload = Oracle::round_payload(&key, 0, &value, 90);
+            let signatures: Vec<(u64, sr25519::Signature)> =
+                (1..=3u64).zip(&pairs).map(|(provider, pair)| (provider, pair.sign(&payload))).collect();
+            let submit = |round, signatures: Vec<(u64, sr25519::Signature)>| {
+                Oracle::submit_signed_round(RuntimeOrigin::signed(4), key.clone(), round, value.clone(), 90, signatures)
+            };
+
+            assert_noop!(submit(1, signatures.clone()), OracleError::<Test>::WrongRound);
+            assert_noop!(submit(0, signatures[..2].to_vec()), OracleError::<Test>::NotEnoughSigners);
+            assert_noop!(
+                submit(0, [&signatures[..], &signatures[..1]].concat()),
+                OracleError::<Test>::DuplicateSigner
+            );
+            let mut forged = signatures.clone();
+            forged[2].1 = pairs[2].sign(&Oracle::round_payload(&key, 0, &b"1.00".to_vec(), 90));
+            assert_noop!(submit(0, forged), OracleError::<Test>::InvalidSignature);
+
+            assert_ok!(submit(0, signatures.clone()));
+            let aggregated = Oracle::aggregated_data(&key).unwrap();
+            assert_eq!((aggregated.value, aggregated.source_count), (value.clone(), 3));
+            System::assert_has_event(RuntimeEvent::Oracle(OracleEvent::SignedRoundSubmitted {
+                data_key: key.clone(),
+                round: 0,
+                aggregator: 4,
+                signers: 3,
+            }));
+
+            // One signed value per key and round
+            assert_noop!(submit(0, signatures), OracleError::<Test>::RoundAlreadySubmitted);
+        });
+    }
+
+    #[test]
+    fn aggregation_strategies_combine_submissions_per_key() {
+        use pallet_oracle::{AggregationStrategy, FeedCategory, FeedMetadata, TypedValue, ValueKind};
+        use sp_core::Encode;
+        use sp_runtime::FixedU128;
+
+        new_test_ext().execute_with(|| {
+            for (source, reliability) in [(&b"src_a"[..], 90), (b"src_b", 60), (b"src_c", 30)] {
+                assert_ok!(Oracle::register_source(
+                    RuntimeOrigin::root(),
+                    source.to_vec(),
+                    source.to_vec(),
+                    b"https://example.com".to_vec(),
+                    reliability,
+                ));
+            }
+            let metadata = FeedMetadata {
+                kind: ValueKind::Numeric,
+                decimals: 2,
+                unit: b"USD".to_vec(),
+                category: FeedCategory::Price,
+            };
+            assert_ok!(Oracle::register_feed(RuntimeOrigin::root(), b"BTC/USD".to_vec(), metadata));
+
+            // Numeric strategies need a numeric feed
+            assert_noop!(
+                Oracle::set_aggregation_strategy(
+                    RuntimeOrigin::root(),
+                    b"match/42".to_vec(),
+                    Some(AggregationStrategy::Median),
+                ),
+                OracleError::<Test>::StrategyRequiresNumericFeed
+            );
+
+            let mut block = 0;
+            let mut provide = |data_key: &[u8], source: &[u8], value: Vec<u8>| {
+                block += 1;
+                System::set_block_number(block);
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(2),
+                    data_key.to_vec(),
+                    source.to_vec(),
+                    value,
+                    50,
+                    None,
+                ));
+            };
+            let price = |number: FixedU128| TypedValue::Numeric(number).encode();
+            let latest = |data_key: &[u8]| Oracle::get_latest_data(&data_key.to_vec());
+
+            // Prices: median, mean, then weighted by reliability
+            assert_ok!(Oracle::set_aggregation_strategy(
+                RuntimeOrigin::root(),
+                b"BTC/USD".to_vec(),
+                Some(AggregationStrategy::Median),
             ));
+            provide(b"BTC/USD", b"src_a", price(FixedU128::from_u32(100)));
+            provide(b"BTC/USD", b"src_b", price(FixedU128::from_u32(300)));
+            provide(b"BTC/USD", b"src_c", price(FixedU128::from_u32(110)));
+            assert_eq!(latest(b"BTC/USD"), Some(price(FixedU128::from_u32(110))));
+
+            let strategy = Some(AggregationStrategy::Mean);
+            assert_ok!(Oracle::set_aggregation_strategy(RuntimeOrigin::root(), b"BTC/USD".to_vec(), strategy));
+            System::assert_last_event(RuntimeEvent::Oracle(OracleEvent::AggregationStrategySet {
+                data_key: b"BTC/USD".to_vec(),
+                strategy,
+            }));
+            provide(b"BTC/USD", b"src_c", price(FixedU128::from_u32(110)));
+            assert_eq!(latest(b"BTC/USD"), Some(price(FixedU128::from_u32(170))));
+
+            // (100 * 90 + 300 * 60 + 110 * 30) / 180, rounded down to the feed's two decimals
+            assert_ok!(Oracle::set_aggregation_strategy(
+                RuntimeOrigin::root(),
+                b"BTC/USD".to_vec(),
+                Some(AggregationStrategy::WeightedByReliability),
+            ));
+            provide(b"BTC/USD", b"src_c", price(FixedU128::from_u32(110)));
+            assert_eq!(latest(b"BTC/USD"), Some(price(FixedU128::from_rational(16_833, 100))));
+
+            // Results: a majority wins, and nothing is aggregated without one
+            assert_ok!(Oracle::set_aggregation_strategy(
+                RuntimeOrigin::root(),
+                b"match/42".to_vec(),
+                Some(AggregationStrategy::Mode),
+            ));
+            provide(b"match/42", b"src_a", b"home".to_vec());
+            provide(b"match/42", b"src_b", b"draw".to_vec());
+            provide(b"match/42", b"src_c", b"away".to_vec());
+            assert_eq!(latest(b"match/42"), None);
+            provide(b"match/42", b"src_b", b"home".to_vec());
+            assert_eq!(latest(b"match/42"), Some(b"home".to_vec()));
+
+            // Observations: the most recent submission
+            assert_ok!(Oracle::set_aggregation_strategy(
+                RuntimeOrigin::root(),
+                b"match/42".to_vec(),
+                Some(AggregationStrategy::Latest),
+            ));
+            provide(b"match/42", b"src_c", b"away".to_vec());
+            assert_eq!(latest(b"match/42"), Some(b"away".to_vec()));
+        });
+    }
+
+    #[test]
+    fn committee_mode_restricts_submissions_to_selected_providers() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95,
+            ));
+            for provider in 1..=4 {
+                assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), provider, 50));
+            }
+            assert_ok!(Oracle::set_committee_mode(RuntimeOrigin::root(), b"BTC/USD".to_vec(), true));
+
+            let provide = |who: u64| Oracle::provide_data(
+                RuntimeOrigin::signed(who),
+                b"BTC/USD".to_vec(),
+                b"coinbase_btc".to_vec(),
+                b"50000.00".to_vec(),
+                50,
+                None,
+            );
+
+            // Two of the four trusted providers are drawn for round 0
+            let committee = Oracle::current_committee(&b"BTC/USD".to_vec());
+            assert_eq!(committee.len(), 2);
+            assert_ne!(committee[0], committee[1]);
+            let outsider = (1..=4).find(|p| !committee.contains(p)).unwrap();
+            assert_noop!(provide(outsider), OracleError::<Test>::NotInCommittee);
+            assert_ok!(provide(committee[0]));
+
+            // The next round draws a new committee and drops the old one
+            System::set_block_number(11);
+            assert_eq!(Oracle::current_round(), 1);
+            let next = Oracle::current_committee(&b"BTC/USD".to_vec());
+            assert_eq!(next.len(), 2);
+            assert!(Oracle::committees(b"BTC/USD".to_vec(), 0).is_none());
+            assert_eq!(Oracle::committees(b"BTC/USD".to_vec(), 1), Some(next.clone()));
+
+            // Keys outside committee mode accept any provider
+            assert_ok!(Oracle::set_committee_mode(RuntimeOrigin::root(), b"BTC/USD".to_vec(), false));
+            let outsider = (1..=4).find(|p| !next.contains(p)).unwrap();
+            assert_ok!(provide(outsider));
         });
     }
 
     #[test]
-    fn oracle_data_provision_works() {
+    fn trusted_providers_are_bounded_and_committee_draws_are_charged_once_per_round() {
         new_test_ext().execute_with(|| {
-            // Setup: Register source and trusted provider
+            use pallet_oracle::WeightInfo;
+
+            System::set_block_number(1);
             assert_ok!(Oracle::register_source(
                 RuntimeOrigin::root(),
                 b"coinbase_btc".to_vec(),
@@ -412,86 +3275,279 @@ mod oracle_tests {
                 b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
                 95,
             ));
+            for provider in 1..=4 {
+                assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), provider, 50));
+            }
+            assert_noop!(
+                Oracle::add_trusted_provider(RuntimeOrigin::root(), 5, 50),
+                OracleError::<Test>::TooManyTrustedProviders
+            );
+            // Trusted providers may still have their reputation updated at the bound
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 1, 60));
+            assert_eq!(pallet_oracle::TrustedProviders::<Test>::count(), 4);
+            assert_ok!(Oracle::set_committee_mode(RuntimeOrigin::root(), b"BTC/USD".to_vec(), true));
 
-            assert_ok!(Oracle::add_trusted_provider(
-                RuntimeOrigin::root(),
-                2, // provider account
-                90, // reputation
-            ));
-
-            // Provide oracle data
-            assert_ok!(Oracle::provide_data(
-                RuntimeOrigin::signed(2),
+            let provide = |who: u64| Oracle::provide_data(
+                RuntimeOrigin::signed(who),
                 b"BTC/USD".to_vec(),
                 b"coinbase_btc".to_vec(),
-                b"50000.00".to_vec(), // $50,000 BTC price
-                90, // high confidence
-                None, // no signature
-            ));
-
-            // Check data was stored
-            let data = Oracle::oracle_data(b"BTC/USD".to_vec(), b"coinbase_btc".to_vec()).unwrap();
-            assert_eq!(data.value, b"50000.00".to_vec());
-            assert_eq!(data.provider, 2);
-            assert_eq!(data.confidence, 90);
+                b"50000.00".to_vec(),
+                50,
+                None,
+            );
+            let submission = <() as WeightInfo>::provide_data(8, MaxOracleDataSources::get());
+            let draw = <() as WeightInfo>::select_committee(4);
 
-            // Check provider was rewarded
-            assert_eq!(Balances::free_balance(&2), 1_000_000 + 1);
+            // The first submission of the round pays for drawing the committee, the next do not
+            let member = Oracle::current_committee(&b"BTC/USD".to_vec())[0];
+            pallet_oracle::Committees::<Test>::remove(b"BTC/USD".to_vec(), 0);
+            let post_info = provide(member).unwrap();
+            assert_eq!(post_info.actual_weight, Some(submission.saturating_add(draw)));
+            let post_info = provide(member).unwrap();
+            assert_eq!(post_info.actual_weight, Some(submission));
 
-            // Check event was emitted
-            System::assert_last_event(RuntimeEvent::Oracle(
-                OracleEvent::DataProvided {
-                    data_key: b"BTC/USD".to_vec(),
-                    source: b"coinbase_btc".to_vec(),
-                    provider: 2,
-                    value: b"50000.00".to_vec(),
-                    confidence: 90,
-                }
-            ));
+            // Rounds that drew no committee leave nothing behind: round 0 is dropped in round 2
+            System::set_block_number(21);
+            assert_eq!(Oracle::current_round(), 2);
+            let committee = Oracle::current_committee(&b"BTC/USD".to_vec());
+            assert!(Oracle::committees(b"BTC/USD".to_vec(), 0).is_none());
+            assert_eq!(Oracle::committees(b"BTC/USD".to_vec(), 2), Some(committee));
         });
     }
 
     #[test]
-    fn oracle_batch_requests_work() {
+    fn authorized_contracts_publish_into_their_data_key() {
         new_test_ext().execute_with(|| {
-            // Register multiple sources
-            assert_ok!(Oracle::register_source(
-                RuntimeOrigin::root(),
-                b"coinbase_btc".to_vec(),
-                b"Coinbase BTC".to_vec(),
-                b"coinbase-api".to_vec(),
-                95,
-            ));
+            System::set_block_number(1);
+            let contract = 7u64;
 
-            assert_ok!(Oracle::register_source(
+            assert_noop!(
+                Oracle::publish_contract_value(&contract, b"BTC/USD".to_vec(), b"50000.00".to_vec()),
+                OracleError::<Test>::ContractNotAuthorized
+            );
+
+            assert_ok!(Oracle::authorize_contract_feed(
                 RuntimeOrigin::root(),
-                b"binance_btc".to_vec(),
-                b"Binance BTC".to_vec(),
-                b"binance-api".to_vec(),
-                90,
+                contract,
+                b"BTC/USD".to_vec(),
+                70,
             ));
+            let source_id = Oracle::contract_source_id(&contract);
+            assert_eq!(Oracle::data_sources(&source_id).unwrap().reliability, 70);
 
-            // Batch request multiple data points
-            let requests = vec![
-                (b"BTC/USD".to_vec(), vec![b"coinbase_btc".to_vec()], false),
-                (b"ETH/USD".to_vec(), vec![b"binance_btc".to_vec()], true), // premium
-            ];
+            // Only the designated key accepts the contract's values
+            assert_noop!(
+                Oracle::publish_contract_value(&contract, b"ETH/USD".to_vec(), b"3000.00".to_vec()),
+                OracleError::<Test>::ContractNotAuthorized
+            );
+            assert_ok!(Oracle::publish_contract_value(&contract, b"BTC/USD".to_vec(), b"50000.00".to_vec()));
 
-            assert_ok!(Oracle::batch_requests(
-                RuntimeOrigin::signed(1),
-                requests,
-            ));
+            // The contract submits as its own source, with its reliability as confidence
+            let data = Oracle::oracle_data(b"BTC/USD".to_vec(), source_id.clone()).unwrap();
+            assert_eq!(data.provider, contract);
+            assert_eq!(data.confidence, 70);
+            assert_eq!(Balances::free_balance(&contract), 0);
 
-            // Check total fee charged (2 + 5 = 7)
-            assert_eq!(Balances::free_balance(&1), 1_000_000 - 7);
+            assert_ok!(Oracle::revoke_contract_feed(RuntimeOrigin::root(), contract));
+            assert!(!Oracle::data_sources(&source_id).unwrap().active);
+            assert_noop!(
+                Oracle::publish_contract_value(&contract, b"BTC/USD".to_vec(), b"50001.00".to_vec()),
+                OracleError::<Test>::ContractNotAuthorized
+            );
+        });
+    }
 
-            // Check event was emitted
-            System::assert_last_event(RuntimeEvent::Oracle(
-                OracleEvent::BatchProcessed {
-                    request_count: 2,
-                    total_fee: 7,
+    #[test]
+    fn missed_premium_sla_refunds_premium_and_penalizes_sources() {
+        use frame_support::traits::Hooks;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let _ = Balances::make_free_balance_be(&Oracle::account_id(), 10_000);
+            for source in [&b"s1"[..], b"s2", b"s3"] {
+                assert_ok!(Oracle::register_source(RuntimeOrigin::root(), source.to_vec(), source.to_vec(), b"api".to_vec(), 90));
+            }
+            let sources = vec![b"s1".to_vec(), b"s2".to_vec(), b"s3".to_vec()];
+            let provide = |provider: u64, key: &[u8], source: &[u8]| {
+                Oracle::provide_data(RuntimeOrigin::signed(provider), key.to_vec(), source.to_vec(), b"1".to_vec(), 70, None)
+            };
+
+            // Request 0 is answered by two of its sources in time
+            assert_ok!(Oracle::request_data(RuntimeOrigin::signed(1), b"BTC/USD".to_vec(), sources.clone(), true, None));
+            assert_eq!(Oracle::premium_slas(0).unwrap().min_sources, 2);
+            assert_ok!(provide(2, b"BTC/USD", b"s1"));
+            assert_ok!(provide(3, b"BTC/USD", b"s1"));
+            assert!(Oracle::premium_slas(0).is_some());
+            assert_ok!(provide(3, b"BTC/USD", b"s2"));
+            System::assert_has_event(OracleEvent::PremiumSlaMet { request_id: 0, delivered: 2 }.into());
+            assert!(Oracle::premium_slas(0).is_none());
+
+            // Request 1 only hears from one source before its deadline
+            assert_ok!(Oracle::request_data(RuntimeOrigin::signed(1), b"ETH/USD".to_vec(), sources, true, None));
+            assert_ok!(provide(2, b"ETH/USD", b"s1"));
+            let balance = Balances::free_balance(&1);
+
+            // Nothing is settled before the block after the deadline
+            Oracle::on_initialize(6);
+            assert!(Oracle::premium_slas(1).is_some());
+            Oracle::on_initialize(7);
+            assert!(Oracle::premium_slas(1).is_none());
+
+            let refund = PremiumOracleQueryFee::get() - OracleQueryFee::get();
+            assert_eq!(Balances::free_balance(&1), balance + refund);
+            System::assert_has_event(OracleEvent::PremiumSlaMissed {
+                request_id: 1,
+                refund,
+                missing: vec![b"s2".to_vec(), b"s3".to_vec()],
+            }.into());
+            assert_eq!(Oracle::data_sources(b"s1".to_vec()).unwrap().reliability, 90);
+            assert_eq!(Oracle::data_sources(b"s2".to_vec()).unwrap().reliability, 80);
+            assert_eq!(Oracle::data_sources(b"s3".to_vec()).unwrap().reliability, 80);
+
+            // Basic requests carry no SLA
+            assert_ok!(Oracle::request_data(RuntimeOrigin::signed(1), b"ETH/USD".to_vec(), vec![b"s1".to_vec()], false, None));
+            assert!(Oracle::premium_slas(2).is_none());
+        });
+    }
+
+    #[test]
+    fn request_quotas_reset_each_era_and_spare_bonded_accounts() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let request = |who: u64| Oracle::request_data(RuntimeOrigin::signed(who), b"BTC/USD".to_vec(), vec![], false, None);
+            let batch = |who: u64, n: usize| {
+                Oracle::batch_requests(RuntimeOrigin::signed(who), vec![(b"BTC/USD".to_vec(), vec![], false); n])
+            };
+
+            // Batches count every request they carry, and a rejected batch uses none of the quota
+            assert_ok!(batch(1, 4));
+            assert_noop!(batch(1, 2), pallet_oracle::Error::<Test>::QuotaExceeded);
+            assert_ok!(request(1));
+            assert_noop!(request(1), pallet_oracle::Error::<Test>::QuotaExceeded);
+            assert_eq!(Oracle::request_quotas(1), (0, 5));
+
+            // The quota is counted again from the next era
+            System::set_block_number(OracleRequestEraLength::get());
+            assert_ok!(batch(1, 5));
+            assert_eq!(Oracle::request_quotas(1), (10, 5));
+
+            // Accounts bonded at least `QuotaExemptBond` are not counted
+            assert_ok!(Oracle::bond_provider(RuntimeOrigin::signed(2), OracleQuotaExemptBond::get()));
+            assert_ok!(batch(2, 6));
+            assert_ok!(request(2));
+            assert_eq!(Oracle::request_quotas(2), (0, 0));
+        });
+    }
+
+    #[test]
+    fn requests_are_pruned_after_their_ttl() {
+        use frame_support::traits::Hooks;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let _ = Balances::make_free_balance_be(&Oracle::account_id(), 10_000);
+            for source in [&b"s1"[..], b"s2", b"s3"] {
+                assert_ok!(Oracle::register_source(RuntimeOrigin::root(), source.to_vec(), source.to_vec(), b"api".to_vec(), 90));
+            }
+            let sources = vec![b"s1".to_vec(), b"s2".to_vec(), b"s3".to_vec()];
+            assert_ok!(Oracle::request_data(RuntimeOrigin::signed(1), b"BTC/USD".to_vec(), sources.clone(), false, None));
+            assert_ok!(Oracle::request_data(RuntimeOrigin::signed(1), b"ETH/USD".to_vec(), sources.clone(), false, None));
+            for (provider, source) in [(2, b"s1"), (3, b"s2"), (4, b"s3")] {
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(provider),
+                    b"BTC/USD".to_vec(),
+                    source.to_vec(),
+                    b"1".to_vec(),
+                    70,
+                    None,
+                ));
+            }
+            assert!(Oracle::aggregated_data(b"BTC/USD".to_vec()).is_some());
+
+            let expiry = 1 + OracleRequestTtl::get();
+            Oracle::on_initialize(expiry - 1);
+            assert!(Oracle::oracle_requests(0).is_some());
+            Oracle::on_initialize(expiry);
+            assert!(Oracle::oracle_requests(0).is_none());
+            assert!(Oracle::oracle_requests(1).is_none());
+            System::assert_has_event(OracleEvent::RequestPruned { request_id: 0, fulfilled: true }.into());
+            System::assert_has_event(OracleEvent::RequestPruned { request_id: 1, fulfilled: false }.into());
+        });
+    }
+
+    #[test]
+    fn stale_watched_feed_is_flagged_and_slashes_its_committee() {
+        use frame_support::traits::Hooks;
+        use pallet_oracle::{FeedCategory, FeedMetadata, PriceProvider, TypedValue, ValueKind};
+        use sp_core::Encode;
+        use sp_runtime::FixedU128;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let key = b"NET/USD".to_vec();
+            assert_ok!(Oracle::register_feed(RuntimeOrigin::root(), key.clone(), FeedMetadata {
+                kind: ValueKind::Numeric,
+                decimals: 2,
+                unit: b"USD".to_vec(),
+                category: FeedCategory::Price,
+            }));
+            for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                assert_ok!(Oracle::register_source(RuntimeOrigin::root(), source.to_vec(), source.to_vec(), b"api".to_vec(), 90));
+            }
+            for provider in 1..=4 {
+                assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), provider, 50));
+                assert_ok!(Oracle::bond_provider(RuntimeOrigin::signed(provider), 1_000));
+            }
+            let publish = |provider: u64| {
+                for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                    assert_ok!(Oracle::provide_data(
+                        RuntimeOrigin::signed(provider),
+                        key.clone(),
+                        source.to_vec(),
+                        TypedValue::Numeric(FixedU128::from_u32(2)).encode(),
+                        50,
+                        None,
+                    ));
                 }
-            ));
+            };
+
+            publish(4);
+            assert_ok!(Oracle::set_committee_mode(RuntimeOrigin::root(), key.clone(), true));
+            assert_ok!(Oracle::set_feed_watch(RuntimeOrigin::root(), key.clone(), Some(100)));
+            Oracle::on_initialize(2);
+            assert!(Oracle::stale_feeds(&key).is_none());
+
+            // Past `MaxDataAge` the feed is flagged once and its committee slashed
+            System::set_block_number(1_202);
+            Oracle::on_initialize(1_202);
+            System::assert_has_event(OracleEvent::FeedStale { data_key: key.clone(), last_updated: Some(1) }.into());
+            assert_eq!(<Oracle as PriceProvider<u64>>::price(&key), None);
+            let committee = Oracle::current_committee(&key);
+            for provider in committee.iter() {
+                assert_eq!(Oracle::provider_bonds(provider), 900);
+                assert_eq!(Balances::reserved_balance(provider), 900);
+                System::assert_has_event(OracleEvent::ProviderSlashed {
+                    provider: *provider,
+                    data_key: key.clone(),
+                    amount: 100,
+                }.into());
+            }
+            Oracle::on_initialize(1_203);
+            assert_eq!(Oracle::provider_bonds(committee[0]), 900);
+
+            // Committee members cannot walk away from their bond; others can
+            assert_noop!(
+                Oracle::unbond_provider(RuntimeOrigin::signed(committee[0]), 900),
+                OracleError::<Test>::ProviderAssigned
+            );
+            let outsider = (1..=4).find(|p| !committee.contains(p)).unwrap();
+            assert_ok!(Oracle::unbond_provider(RuntimeOrigin::signed(outsider), 1_000));
+            assert_eq!(Balances::reserved_balance(&outsider), 0);
+
+            // A fresh aggregate clears the flag and prices the feed again
+            publish(committee[0]);
+            System::assert_has_event(OracleEvent::FeedRecovered { data_key: key.clone() }.into());
+            assert_eq!(<Oracle as PriceProvider<u64>>::price(&key), Some((FixedU128::from_u32(2), 1_202)));
         });
     }
 
@@ -507,14 +3563,15 @@ mod oracle_tests {
                 50,
             ));
 
-            // Test: High confidence data requires trusted provider
+            // Test: Verified sources require a trusted provider
+            assert_ok!(Oracle::set_source_tier(RuntimeOrigin::root(), b"test_source".to_vec(), SourceTier::Verified));
             assert_noop!(
                 Oracle::provide_data(
                     RuntimeOrigin::signed(3), // untrusted provider
                     b"BTC/USD".to_vec(),
                     b"test_source".to_vec(),
                     b"50000.00".to_vec(),
-                    85, // high confidence
+                    50,
                     None,
                 ),
                 OracleError::<Test>::ProviderNotTrusted
@@ -548,18 +3605,324 @@ mod oracle_tests {
             );
         });
     }
+
+    #[test]
+    fn submissions_meet_the_tier_of_their_source() {
+        new_test_ext().execute_with(|| {
+            use pallet_oracle::TierRequirements;
+            use sp_runtime::FixedU128;
+
+            let source = b"exchange".to_vec();
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                source.clone(),
+                b"Exchange".to_vec(),
+                b"exchange-api".to_vec(),
+                90,
+            ));
+            assert_eq!(Oracle::data_sources(&source).unwrap().tier, SourceTier::Open);
+            let _ = Balances::make_free_balance_be(&Oracle::account_id(), 10_000);
+            let provide = |who: u64, confidence: u8| {
+                Oracle::provide_data(
+                    RuntimeOrigin::signed(who),
+                    b"BTC/USD".to_vec(),
+                    source.clone(),
+                    b"50000.00".to_vec(),
+                    confidence,
+                    None,
+                )
+            };
+
+            // Open sources take any account at any confidence
+            assert_ok!(provide(3, 95));
+
+            assert_noop!(
+                Oracle::set_source_tier(RuntimeOrigin::signed(1), source.clone(), SourceTier::Critical),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Oracle::set_source_tier(RuntimeOrigin::root(), source.clone(), SourceTier::Critical));
+            System::assert_last_event(RuntimeEvent::Oracle(OracleEvent::SourceTierSet {
+                source_id: source.clone(),
+                tier: SourceTier::Critical,
+            }));
+
+            // Critical sources take trusted providers of reputation 80 with 100 bonded
+            assert_noop!(provide(3, 50), OracleError::<Test>::ProviderNotTrusted);
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 3, 70));
+            assert_noop!(provide(3, 50), OracleError::<Test>::ReputationTooLow);
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 3, 85));
+            assert_noop!(provide(3, 50), OracleError::<Test>::TierBondTooLow);
+            assert_ok!(Oracle::bond_provider(RuntimeOrigin::signed(3), 100));
+
+            // and pay three times the reward
+            let before = Balances::free_balance(&3);
+            assert_ok!(provide(3, 50));
+            assert_eq!(Balances::free_balance(&3), before + 3);
+
+            // Governance overrides a tier's requirements, and restores the runtime's
+            let relaxed = TierRequirements { min_reputation: Some(50), min_bond: 0, reward_multiplier: FixedU128::from_u32(1) };
+            assert_noop!(
+                Oracle::set_tier_requirements(
+                    RuntimeOrigin::root(),
+                    SourceTier::Critical,
+                    Some(TierRequirements { min_reputation: Some(101), ..relaxed.clone() }),
+                ),
+                OracleError::<Test>::InvalidConfidence
+            );
+            assert_ok!(Oracle::set_tier_requirements(RuntimeOrigin::root(), SourceTier::Critical, Some(relaxed.clone())));
+            assert_eq!(Oracle::tier_requirements(SourceTier::Critical), relaxed);
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 4, 60));
+            assert_ok!(provide(4, 50));
+
+            assert_ok!(Oracle::set_tier_requirements(RuntimeOrigin::root(), SourceTier::Critical, None));
+            assert_eq!(Oracle::tier_requirements(SourceTier::Critical), OracleCriticalTier::get());
+            assert_noop!(provide(4, 50), OracleError::<Test>::ReputationTooLow);
+        });
+    }
+
+    #[test]
+    fn migrating_oracle_to_v2_opens_existing_sources() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_oracle::migrations::v2;
+
+            let old = v2::OldDataSource {
+                id: b"coinbase".to_vec(),
+                name: b"Coinbase".to_vec(),
+                endpoint: b"coinbase-api".to_vec(),
+                reliability: 95,
+                active: true,
+            };
+            frame_support::storage::unhashed::put(&pallet_oracle::DataSources::<Test>::hashed_key_for(&old.id), &old);
+            StorageVersion::new(1).put::<Oracle>();
+
+            v2::AddSourceTiers::<Test>::on_runtime_upgrade();
+
+            assert_eq!(Oracle::on_chain_storage_version(), 2);
+            let source = Oracle::data_sources(&old.id).unwrap();
+            assert_eq!((source.reliability, source.tier), (95, SourceTier::Open));
+        });
+    }
+
+    #[test]
+    fn migrating_oracle_to_v3_counts_trusted_providers() {
+        new_test_ext().execute_with(|| {
+            use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+            use pallet_oracle::{migrations::v3, TrustedProviders};
+
+            // Providers trusted before the map was counted
+            for provider in [2u64, 3, 4] {
+                frame_support::storage::unhashed::put(&TrustedProviders::<Test>::hashed_key_for(provider), &90u8);
+            }
+            assert_eq!(TrustedProviders::<Test>::count(), 0);
+            StorageVersion::new(2).put::<Oracle>();
+
+            v3::CountTrustedProviders::<Test>::on_runtime_upgrade();
+
+            assert_eq!(Oracle::on_chain_storage_version(), 3);
+            assert_eq!(TrustedProviders::<Test>::count(), 3);
+            assert_ok!(Oracle::add_trusted_provider(RuntimeOrigin::root(), 5, 50));
+            assert_noop!(
+                Oracle::add_trusted_provider(RuntimeOrigin::root(), 6, 50),
+                OracleError::<Test>::TooManyTrustedProviders
+            );
+        });
+    }
 }
 
 #[cfg(test)]
 mod combined_interoperability_tests {
     use super::*;
 
+    #[test]
+    fn oracle_feeds_cross_feed_channels_into_the_remote_namespace() {
+        use pallet_ibc_core::IbcRouter;
+        use pallet_oracle_ibc::{Error as OracleIbcError, Event as OracleIbcEvent, OraclePacketData};
+        use sp_core::Encode;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            for (source, price) in [(&b"src_a"[..], b"100"), (b"src_b", b"101"), (b"src_c", b"102")] {
+                assert_ok!(Oracle::register_source(
+                    RuntimeOrigin::root(),
+                    source.to_vec(),
+                    source.to_vec(),
+                    b"https://example.com".to_vec(),
+                    90,
+                ));
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(2),
+                    b"NET/USD".to_vec(),
+                    source.to_vec(),
+                    price.to_vec(),
+                    50,
+                    None,
+                ));
+            }
+            let aggregate = Oracle::aggregated_data(b"NET/USD".to_vec()).unwrap();
+
+            // Two feed channels over the localhost connection, each the other's counterparty
+            let oracle_port = id(pallet_oracle_ibc::ORACLE_PORT);
+            let localhost = IbcCore::ensure_localhost();
+            assert_noop!(
+                OracleIbc::open_feed_channel(RuntimeOrigin::signed(1), localhost.clone(), oracle_port.clone(), id(b"channel-1")),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(OracleIbc::open_feed_channel(RuntimeOrigin::root(), localhost.clone(), oracle_port.clone(), id(b"channel-1")));
+            assert_ok!(OracleIbc::open_feed_channel(RuntimeOrigin::root(), localhost, oracle_port.clone(), id(b"channel-0")));
+            assert_eq!(IbcCore::port_owner(&oracle_port), Some(OracleIbc::account_id()));
+
+            assert_noop!(
+                OracleIbc::publish_feed_over_ibc(RuntimeOrigin::signed(1), b"ETH/USD".to_vec(), id(b"channel-0")),
+                OracleIbcError::<Test>::NoAggregate
+            );
+            assert_noop!(
+                OracleIbc::publish_feed_over_ibc(RuntimeOrigin::signed(1), b"NET/USD".to_vec(), id(b"channel-7")),
+                OracleIbcError::<Test>::UnknownChannel
+            );
+            assert_ok!(OracleIbc::publish_feed_over_ibc(RuntimeOrigin::signed(1), b"NET/USD".to_vec(), id(b"channel-0")));
+            System::assert_has_event(RuntimeEvent::OracleIbc(OracleIbcEvent::FeedPublished {
+                data_key: b"NET/USD".to_vec(),
+                channel_id: id(b"channel-0"),
+                sequence: 1,
+            }));
+            assert_eq!(Balances::free_balance(&1), 1_000_000 - 5);
+
+            // Relayed back in, the value lands under the sending chain's namespace
+            let packet = pallet_ibc_core::Packet {
+                sequence: 1,
+                source_port: oracle_port.clone(),
+                source_channel: id(b"channel-0"),
+                destination_port: oracle_port.clone(),
+                destination_channel: id(b"channel-1"),
+                data: bounded(
+                    &OraclePacketData {
+                        data_key: b"NET/USD".to_vec(),
+                        value: aggregate.value.clone(),
+                        source_count: aggregate.source_count,
+                        confidence: aggregate.confidence,
+                    }
+                    .encode(),
+                ),
+                timeout_height: 101,
+                timeout_timestamp: 0,
+            };
+            assert_ok!(IbcCore::recv_packet(RuntimeOrigin::signed(3), packet, vec![], 0));
+
+            let remote_key = pallet_oracle::remote_data_key(b"netchain-test", b"NET/USD");
+            assert_eq!(remote_key, b"remote/netchain-test/NET/USD".to_vec());
+            let remote = Oracle::aggregated_data(&remote_key).unwrap();
+            assert_eq!((remote.value, remote.source_count, remote.aggregated_at), (aggregate.value, aggregate.source_count, 1));
+            System::assert_has_event(RuntimeEvent::OracleIbc(OracleIbcEvent::RemoteFeedReceived {
+                channel_id: id(b"channel-1"),
+                data_key: remote_key.clone(),
+            }));
+
+            // Local providers cannot write into the remote namespace
+            assert_noop!(
+                Oracle::provide_data(RuntimeOrigin::signed(2), remote_key, b"src_a".to_vec(), b"1".to_vec(), 50, None),
+                OracleError::<Test>::ReservedDataKey
+            );
+
+            // Packets that are not feeds, or arrive from or on other channels, are rejected
+            let mut garbage = pallet_ibc_core::Packet {
+                sequence: 2,
+                source_port: oracle_port.clone(),
+                source_channel: id(b"channel-0"),
+                destination_port: oracle_port,
+                destination_channel: id(b"channel-1"),
+                data: bounded(b"garbage"),
+                timeout_height: 0,
+                timeout_timestamp: 0,
+            };
+            assert_eq!(EchoRouter::on_recv_packet(&garbage, &3), Err(b"undecodable packet data".to_vec()));
+            garbage.source_channel = id(b"channel-7");
+            assert_eq!(EchoRouter::on_recv_packet(&garbage, &3), Err(b"unexpected counterparty channel".to_vec()));
+            garbage.destination_channel = id(b"channel-7");
+            assert_eq!(EchoRouter::on_recv_packet(&garbage, &3), Err(b"unknown feed channel".to_vec()));
+            System::assert_last_event(RuntimeEvent::OracleIbc(OracleIbcEvent::FeedRejected {
+                channel_id: id(b"channel-7"),
+                error: b"unknown feed channel".to_vec(),
+            }));
+        });
+    }
+
+    #[test]
+    fn safeguard_pauses_new_packets_and_submissions_but_settles_in_flight_ones() {
+        let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+        pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000_000), (2, 1_000_000)] }
+            .assimilate_storage(&mut t)
+            .unwrap();
+        pallet_ibc_core::GenesisConfig::<Test> { loopback: Some((b"transfer".to_vec(), 1)) }
+            .assimilate_storage(&mut t)
+            .unwrap();
+
+        sp_io::TestExternalities::from(t).execute_with(|| {
+            System::set_block_number(1);
+            let port_id = id(b"transfer");
+            let send = || IbcCore::send_packet(
+                RuntimeOrigin::signed(1),
+                port_id.clone(),
+                id(b"channel-0"),
+                port_id.clone(),
+                id(b"channel-1"),
+                b"ping".to_vec(),
+                0,
+                0,
+            );
+            assert_ok!(send());
+
+            assert_ok!(Safeguard::pause(RuntimeOrigin::root(), pallet_safeguard::Subsystem::Ibc));
+            assert_noop!(send(), IbcError::<Test>::SystemPaused);
+            assert_noop!(
+                IbcCore::recv_packet(RuntimeOrigin::signed(1), pallet_ibc_core::Packet {
+                    sequence: 1,
+                    source_port: port_id.clone(),
+                    source_channel: id(b"channel-0"),
+                    destination_port: port_id.clone(),
+                    destination_channel: id(b"channel-1"),
+                    data: bounded(b"ping"),
+                    timeout_height: 0,
+                    timeout_timestamp: 0,
+                }, vec![], 0),
+                IbcError::<Test>::SystemPaused
+            );
+            // The packet sent before the pause can still time out
+            assert_ok!(IbcCore::timeout_packet(RuntimeOrigin::signed(1), port_id.clone(), id(b"channel-0"), 1));
+
+            // Pausing IBC leaves the oracle running, and each is resumed on its own
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase".to_vec(),
+                b"Coinbase".to_vec(),
+                b"coinbase-api".to_vec(),
+                95,
+            ));
+            let provide = || Oracle::provide_data(
+                RuntimeOrigin::signed(2),
+                b"BTC/USD".to_vec(),
+                b"coinbase".to_vec(),
+                b"50000.00".to_vec(),
+                50,
+                None,
+            );
+            assert_ok!(provide());
+            assert_ok!(Safeguard::pause(RuntimeOrigin::root(), pallet_safeguard::Subsystem::Oracle));
+            assert_noop!(provide(), OracleError::<Test>::SystemPaused);
+
+            assert_ok!(Safeguard::resume(RuntimeOrigin::root(), pallet_safeguard::Subsystem::Ibc));
+            assert_ok!(send());
+            assert_noop!(provide(), OracleError::<Test>::SystemPaused);
+        });
+    }
+
     #[test]
     fn cross_chain_oracle_integration_works() {
         new_test_ext().execute_with(|| {
             // Setup IBC
             assert_ok!(IbcCore::create_client(
-                RuntimeOrigin::signed(1),
+                RuntimeOrigin::root(),
+                pallet_ibc_core::ClientType::Mock,
                 b"cosmos-testnet".to_vec(),
                 100,
                 67,
@@ -601,25 +3964,108 @@ mod combined_interoperability_tests {
             ));
 
             // Check both systems worked together
-            let client = IbcCore::clients(b"client-0".to_vec()).unwrap();
+            let client = IbcCore::clients(id(b"client-0")).unwrap();
             assert_eq!(client.chain_id, b"cosmos-testnet".to_vec());
 
             let data = Oracle::oracle_data(b"COSMOS/USD".to_vec(), b"external_chain_data".to_vec()).unwrap();
             assert_eq!(data.value, b"15.50".to_vec());
 
             // Check combined fees were charged appropriately
-            let expected_balance = 1_000_000 - 10 - 5; // IBC client + Oracle premium
+            let expected_balance = 1_000_000 - 5; // Oracle premium; root's IBC client holds no deposit
             assert_eq!(Balances::free_balance(&1), expected_balance);
         });
     }
 
+    #[test]
+    fn lending_market_liquidates_on_oracle_price_drop() {
+        use pallet_collateral_demo::Error as CollateralError;
+        use pallet_oracle::{FeedCategory, FeedMetadata, TypedValue, ValueKind};
+        use sp_core::Encode;
+        use sp_runtime::FixedU128;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+
+            assert_ok!(Oracle::register_feed(RuntimeOrigin::root(), b"NET/USD".to_vec(), FeedMetadata {
+                kind: ValueKind::Numeric,
+                decimals: 2,
+                unit: b"USD".to_vec(),
+                category: FeedCategory::Price,
+            }));
+            for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                assert_ok!(Oracle::register_source(
+                    RuntimeOrigin::root(),
+                    source.to_vec(),
+                    source.to_vec(),
+                    b"https://example.com".to_vec(),
+                    90,
+                ));
+            }
+            let publish = |price: u32| {
+                for source in [&b"src_a"[..], b"src_b", b"src_c"] {
+                    assert_ok!(Oracle::provide_data(
+                        RuntimeOrigin::signed(4),
+                        b"NET/USD".to_vec(),
+                        source.to_vec(),
+                        TypedValue::Numeric(FixedU128::from_u32(price)).encode(),
+                        50,
+                        None,
+                    ));
+                }
+            };
+
+            // 1000 NET of 2 decimals
+            assert_ok!(CollateralDemo::deposit_collateral(RuntimeOrigin::signed(1), 100_000));
+            assert_noop!(
+                CollateralDemo::borrow(RuntimeOrigin::signed(1), FixedU128::from_u32(1)),
+                CollateralError::<Test>::PriceUnavailable
+            );
+
+            // 1000 NET at $2 is worth $2000; 80% of it can be borrowed
+            publish(2);
+            assert_eq!(
+                <Oracle as pallet_oracle::PriceProvider<u64>>::price(&b"NET/USD".to_vec()),
+                Some((FixedU128::from_u32(2), 1))
+            );
+            assert_noop!(
+                CollateralDemo::borrow(RuntimeOrigin::signed(1), FixedU128::from_u32(1_601)),
+                CollateralError::<Test>::Undercollateralized
+            );
+            assert_ok!(CollateralDemo::borrow(RuntimeOrigin::signed(1), FixedU128::from_u32(1_600)));
+            assert_noop!(
+                CollateralDemo::liquidate(RuntimeOrigin::signed(3), 1),
+                CollateralError::<Test>::PositionHealthy
+            );
+
+            // Stale prices are not acted upon
+            System::set_block_number(20);
+            assert_noop!(
+                CollateralDemo::liquidate(RuntimeOrigin::signed(3), 1),
+                CollateralError::<Test>::StalePrice
+            );
+
+            // At $1 the position is over the threshold and the liquidator takes the collateral
+            publish(1);
+            assert_ok!(CollateralDemo::liquidate(RuntimeOrigin::signed(3), 1));
+            assert!(CollateralDemo::positions(1).is_none());
+            assert_eq!(Balances::reserved_balance(&1), 0);
+            assert_eq!(Balances::free_balance(&3), 1_000_000 + 100_000);
+
+            // Past its `valid_until` the oracle no longer prices the asset
+            System::set_block_number(20 + MaxOracleDataAge::get());
+            assert!(<Oracle as pallet_oracle::PriceProvider<u64>>::price(&b"NET/USD".to_vec()).is_some());
+            System::set_block_number(21 + MaxOracleDataAge::get());
+            assert_eq!(<Oracle as pallet_oracle::PriceProvider<u64>>::price(&b"NET/USD".to_vec()), None);
+        });
+    }
+
     #[test]
     fn ultra_low_fees_maintained() {
         new_test_ext().execute_with(|| {
             // Test that all operations maintain ultra-low fees
             
             // IBC fees
-            assert_eq!(IbcClientCreationFee::get(), 10); // ~$0.0001
+            assert_eq!(IbcClientDeposit::get(), 10); // ~$0.0001, refundable
             assert_eq!(IbcPacketTransmissionFee::get(), 5); // ~$0.00005
             
             // Oracle fees
@@ -632,4 +4078,45 @@ mod combined_interoperability_tests {
             assert!(total_interop_cost < 25); // Still under $0.00025
         });
     }
+
+    #[test]
+    fn history_indexes_oracle_updates_in_ring_buffer() {
+        use pallet_history::HistoryKind;
+
+        new_test_ext().execute_with(|| {
+            assert_ok!(Oracle::register_source(
+                RuntimeOrigin::root(),
+                b"coinbase_btc".to_vec(),
+                b"Coinbase BTC Price".to_vec(),
+                b"https://api.coinbase.com/v2/prices/BTC-USD/spot".to_vec(),
+                95,
+            ));
+
+            // Five updates into a four-slot buffer: the first one is overwritten
+            for (provider, price) in [(2, b"1"), (3, b"2"), (2, b"3"), (3, b"4"), (2, b"5")] {
+                assert_ok!(Oracle::provide_data(
+                    RuntimeOrigin::signed(provider),
+                    b"BTC/USD".to_vec(),
+                    b"coinbase_btc".to_vec(),
+                    price.to_vec(),
+                    50,
+                    None,
+                ));
+            }
+            assert_eq!(History::entry_count(), 5);
+
+            let by_key = History::history_by_key(b"BTC/USD", None, 10);
+            assert_eq!(by_key.len(), 4);
+            assert_eq!(by_key.iter().map(|e| e.index).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+            assert!(by_key.iter().all(|e| e.kind == HistoryKind::OracleUpdate));
+
+            let by_account = History::history_by_account(&2, Some(HistoryKind::OracleUpdate), 10);
+            assert_eq!(by_account.iter().map(|e| e.index).collect::<Vec<_>>(), vec![4, 2]);
+            assert_eq!(by_account[0].hash, BlakeTwo256::hash(b"5"));
+
+            assert_eq!(History::history_by_account(&2, None, 1).len(), 1);
+            assert!(History::history_by_account(&2, Some(HistoryKind::IbcPacket), 10).is_empty());
+            assert!(History::history_by_key(b"ETH/USD", None, 10).is_empty());
+        });
+    }
 }
\ No newline at end of file